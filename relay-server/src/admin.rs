@@ -0,0 +1,268 @@
+//! Operator admin socket for community relays
+//!
+//! Enabled with `--admin-port` and `--admin-token-file`, and bound to
+//! localhost only. Localhost is shared with every local user and with
+//! browsers on the host, so each request must also carry the token from the
+//! token file. Each request is one JSON object per line, answered with one
+//! JSON object per line:
+//!
+//! ```text
+//! {"token":"...","command":"list_peers"}
+//! {"token":"...","command":"prune_peers","inactive_days":90,"purge_posts":false}
+//! {"token":"...","command":"set_rate_limit","max_requests":30,"window_secs":60}
+//! ```
+//!
+//! The connection is closed after the first line that isn't a request with
+//! the right token, so other protocols (e.g. an HTTP request from a web
+//! page) get no further than their first line.
+//!
+//! Requests are handed to the main event loop, which owns the board service
+//! and the board sync rate limiter.
+
+use crate::board_service::BoardService;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::path::Path;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, oneshot};
+use tracing::{debug, info, warn};
+
+/// Maximum admin requests waiting for the event loop
+const ADMIN_QUEUE_SIZE: usize = 16;
+
+/// Admin request (one JSON line)
+#[derive(Debug, Clone, serde::Deserialize)]
+#[serde(tag = "command", rename_all = "snake_case")]
+pub enum AdminRequest {
+    ListPeers,
+    PrunePeers {
+        inactive_days: u32,
+        /// Also delete the pruned peers' board and wall posts
+        #[serde(default)]
+        purge_posts: bool,
+    },
+    /// Change the board sync rate limit; omitted values are kept
+    SetRateLimit {
+        max_requests: Option<u64>,
+        window_secs: Option<u64>,
+    },
+}
+
+/// A request line: the admin token and the request itself
+#[derive(Debug, serde::Deserialize)]
+struct AdminEnvelope {
+    token: String,
+    #[serde(flatten)]
+    request: AdminRequest,
+}
+
+/// Registered peer in admin responses
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct RegisteredPeerInfo {
+    pub peer_id: String,
+    pub display_name: String,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+    pub board_post_count: i64,
+    pub wall_post_count: i64,
+}
+
+/// Admin response (one JSON line)
+#[derive(Debug, Clone, serde::Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum AdminResponse {
+    Peers { peers: Vec<RegisteredPeerInfo> },
+    PeersPruned { peer_ids: Vec<String> },
+    RateLimitUpdated { max_requests: u64, window_secs: u64 },
+    Error { error: String },
+}
+
+/// A request on its way to the event loop, with the channel for its answer
+pub type AdminCommand = (AdminRequest, oneshot::Sender<AdminResponse>);
+
+/// Read the admin token from its file, refusing an empty one
+pub fn load_admin_token(path: &Path) -> std::io::Result<String> {
+    let token = std::fs::read_to_string(path)?.trim().to_string();
+    if token.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Admin token file {} is empty", path.display()),
+        ));
+    }
+    Ok(token)
+}
+
+/// Bind the admin socket on localhost and accept connections in the
+/// background. Requests arrive on the returned receiver.
+pub async fn start_admin_listener(
+    port: u16,
+    token: String,
+) -> std::io::Result<mpsc::Receiver<AdminCommand>> {
+    let listener = TcpListener::bind((Ipv4Addr::LOCALHOST, port)).await?;
+    info!("Admin socket listening on {}", listener.local_addr()?);
+    let token: Arc<str> = token.into();
+
+    let (command_tx, command_rx) = mpsc::channel(ADMIN_QUEUE_SIZE);
+    tokio::spawn(async move {
+        loop {
+            match listener.accept().await {
+                Ok((stream, addr)) => {
+                    tokio::spawn(handle_connection(
+                        stream,
+                        addr,
+                        token.clone(),
+                        command_tx.clone(),
+                    ));
+                }
+                Err(accept_error) => warn!(error = %accept_error, "Admin socket accept failed"),
+            }
+        }
+    });
+    Ok(command_rx)
+}
+
+/// Answer requests on one admin connection until it closes
+async fn handle_connection(
+    stream: TcpStream,
+    addr: SocketAddr,
+    token: Arc<str>,
+    command_tx: mpsc::Sender<AdminCommand>,
+) {
+    debug!(%addr, "Admin connection opened");
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+
+    loop {
+        let line = match lines.next_line().await {
+            Ok(Some(line)) => line,
+            Ok(None) => break,
+            Err(read_error) => {
+                warn!(%addr, error = %read_error, "Admin connection read failed");
+                break;
+            }
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let (response, keep_open) = match parse_request(&line, &token) {
+            Ok(request) => (dispatch(&command_tx, request).await, true),
+            Err(error) => {
+                warn!(%addr, error = %error, "Rejected admin request, closing connection");
+                (AdminResponse::Error { error }, false)
+            }
+        };
+        let mut encoded = match serde_json::to_string(&response) {
+            Ok(encoded) => encoded,
+            Err(encode_error) => {
+                warn!(error = %encode_error, "Failed to encode admin response");
+                break;
+            }
+        };
+        encoded.push('\n');
+        if writer.write_all(encoded.as_bytes()).await.is_err() || !keep_open {
+            break;
+        }
+    }
+    debug!(%addr, "Admin connection closed");
+}
+
+/// Parse a request line and check its token
+fn parse_request(line: &str, token: &str) -> Result<AdminRequest, String> {
+    let envelope = serde_json::from_str::<AdminEnvelope>(line)
+        .map_err(|parse_error| format!("Invalid request: {}", parse_error))?;
+    if !tokens_match(envelope.token.as_bytes(), token.as_bytes()) {
+        return Err("Invalid admin token".to_string());
+    }
+    Ok(envelope.request)
+}
+
+/// Compare tokens without stopping at the first differing byte
+fn tokens_match(given: &[u8], expected: &[u8]) -> bool {
+    given.len() == expected.len()
+        && given
+            .iter()
+            .zip(expected)
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+/// Pass a request to the event loop and wait for its answer
+async fn dispatch(command_tx: &mpsc::Sender<AdminCommand>, request: AdminRequest) -> AdminResponse {
+    let (reply_tx, reply_rx) = oneshot::channel();
+    if command_tx.send((request, reply_tx)).await.is_err() {
+        return AdminResponse::Error {
+            error: "Relay is shutting down".to_string(),
+        };
+    }
+    reply_rx.await.unwrap_or_else(|_| AdminResponse::Error {
+        error: "Relay is shutting down".to_string(),
+    })
+}
+
+/// Handle an admin request against the board service (event loop side).
+/// `SetRateLimit` is applied by the event loop itself, which owns the limiter.
+pub fn handle_admin_request(service: &BoardService, request: AdminRequest) -> AdminResponse {
+    match request {
+        AdminRequest::ListPeers => match service.list_registered_peers() {
+            Ok(rows) => AdminResponse::Peers {
+                peers: rows
+                    .into_iter()
+                    .map(|row| RegisteredPeerInfo {
+                        peer_id: row.peer_id,
+                        display_name: row.display_name,
+                        first_seen_at: row.first_seen_at,
+                        last_seen_at: row.last_seen_at,
+                        board_post_count: row.board_post_count,
+                        wall_post_count: row.wall_post_count,
+                    })
+                    .collect(),
+            },
+            Err(error) => AdminResponse::Error { error },
+        },
+        AdminRequest::PrunePeers {
+            inactive_days,
+            purge_posts,
+        } => match service.prune_inactive_peers(inactive_days, purge_posts) {
+            Ok(peer_ids) => AdminResponse::PeersPruned { peer_ids },
+            Err(error) => AdminResponse::Error { error },
+        },
+        AdminRequest::SetRateLimit { .. } => AdminResponse::Error {
+            error: "Rate limiter is not running".to_string(),
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TOKEN: &str = "s3cret";
+
+    #[test]
+    fn test_requests_need_the_token() {
+        let request = parse_request(r#"{"token":"s3cret","command":"list_peers"}"#, TOKEN);
+        assert!(matches!(request, Ok(AdminRequest::ListPeers)));
+
+        let request = parse_request(
+            r#"{"token":"s3cret","command":"set_rate_limit","window_secs":30}"#,
+            TOKEN,
+        );
+        assert!(matches!(
+            request,
+            Ok(AdminRequest::SetRateLimit {
+                max_requests: None,
+                window_secs: Some(30)
+            })
+        ));
+
+        assert!(parse_request(r#"{"token":"wrong!","command":"list_peers"}"#, TOKEN).is_err());
+        assert!(parse_request(r#"{"command":"list_peers"}"#, TOKEN).is_err());
+    }
+
+    #[test]
+    fn test_http_request_line_is_rejected() {
+        assert!(parse_request("POST / HTTP/1.1", TOKEN).is_err());
+    }
+}
@@ -1,7 +1,9 @@
 //! Server-side board logic for the relay server
 
-use crate::db::RelayDatabase;
+use crate::db::{RegisteredPeerRow, RelayDatabase};
 use ed25519_dalek::{Signature, Verifier, VerifyingKey};
+use libp2p::identity::Keypair;
+use libp2p::PeerId;
 use serde::Serialize;
 use tracing::{info, warn};
 
@@ -46,6 +48,19 @@ struct SignableBoardPostDelete {
 
 impl Signable for SignableBoardPostDelete {}
 
+/// Signable version of a board post reaction (excludes signature field).
+/// Must match `SignableBoardPostReaction` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignableBoardPostReaction {
+    pub post_id: String,
+    pub reactor_peer_id: String,
+    pub reaction_type: String,
+    pub remove: bool,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableBoardPostReaction {}
+
 /// Signable version of a peer registration (excludes signature field).
 /// Must match `SignablePeerRegistration` on the client side.
 #[derive(Debug, Clone, Serialize)]
@@ -57,6 +72,16 @@ struct SignablePeerRegistration {
 
 impl Signable for SignablePeerRegistration {}
 
+/// Signable version of a peer deregistration (excludes signature field).
+/// Must match `SignablePeerDeregistration` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignablePeerDeregistration {
+    pub peer_id: String,
+    pub timestamp: i64,
+}
+
+impl Signable for SignablePeerDeregistration {}
+
 /// Signable version of a board list request (excludes signature field).
 /// Must match `SignableBoardListRequest` on the client side.
 #[derive(Debug, Clone, Serialize)]
@@ -119,6 +144,45 @@ struct SignableWallPostDelete {
 
 impl Signable for SignableWallPostDelete {}
 
+/// Signable version of a board creation request (excludes signature).
+/// Must match `SignableBoardCreate` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignableBoardCreate {
+    pub requester_peer_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableBoardCreate {}
+
+/// Signable version of a member's request for a community invite (excludes
+/// signature). Must match `SignableInviteRequest` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignableInviteRequest {
+    pub requester_peer_id: String,
+    pub expires_at: Option<i64>,
+    pub max_uses: Option<u32>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableInviteRequest {}
+
+/// The relay's attestation of when it received a board or wall post, signed
+/// with the relay's identity key. Binding the author's signature ties the
+/// timestamp to the exact post content.
+/// Must match `SignableRelayTimestamp` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignableRelayTimestamp {
+    pub relay_peer_id: String,
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub author_signature: Vec<u8>,
+    pub relay_observed_at: i64,
+}
+
+impl Signable for SignableRelayTimestamp {}
+
 // ============================================================
 // Signature verification helpers
 // ============================================================
@@ -149,7 +213,29 @@ fn verify_signature(
         .map_err(|_| "Signature verification failed".to_string())
 }
 
+/// Check that a peer ID is derived from the given Ed25519 public key.
+///
+/// A valid signature only proves possession of *some* key; binding the key
+/// to the peer ID keeps a peer from registering its own key under another
+/// peer's ID and then signing as them.
+fn verify_peer_key_binding(peer_id: &str, public_key_bytes: &[u8]) -> Result<(), String> {
+    let ed25519_key = libp2p::identity::ed25519::PublicKey::try_from_bytes(public_key_bytes)
+        .map_err(|key_error| format!("Invalid Ed25519 public key: {}", key_error))?;
+    let derived_peer_id = PeerId::from_public_key(&ed25519_key.into());
+
+    if derived_peer_id.to_string() != peer_id {
+        return Err(format!(
+            "Public key does not belong to peer {} (derives {})",
+            peer_id, derived_peer_id
+        ));
+    }
+    Ok(())
+}
+
 /// Look up a registered peer's public key from the database and verify the signature.
+///
+/// The stored key is checked against the peer ID as well, so keys registered
+/// before registration enforced the binding can't sign for the peer.
 fn verify_registered_peer_signature(
     database: &RelayDatabase,
     peer_id: &str,
@@ -161,9 +247,59 @@ fn verify_registered_peer_signature(
         .map_err(|db_error| format!("Database error looking up peer key: {}", db_error))?
         .ok_or_else(|| format!("No public key found for peer: {}", peer_id))?;
 
+    verify_peer_key_binding(peer_id, &stored_public_key)?;
     verify_signature(&stored_public_key, signable, signature_bytes)
 }
 
+/// Maximum reaction type length in characters (e.g. "like" or an emoji)
+const MAX_REACTION_TYPE_CHARS: usize = 32;
+
+// ============================================================
+// Board creation policy
+// ============================================================
+
+/// Maximum board name length in characters
+const MAX_BOARD_NAME_CHARS: usize = 64;
+
+/// Maximum board description length in characters
+const MAX_BOARD_DESCRIPTION_CHARS: usize = 500;
+
+/// Default maximum size of a board or wall post's text (`--max-post-bytes`)
+pub const DEFAULT_MAX_POST_BYTES: usize = 64 * 1024;
+
+/// Longest a member-requested invite stays valid; longer or open-ended
+/// requests are cut to this
+const MAX_MEMBER_INVITE_LIFETIME_SECS: i64 = 30 * 24 * 60 * 60;
+
+/// Operator policy for boards created by peers (`--allow-peer-boards`)
+#[derive(Debug, Clone)]
+pub struct PeerBoardPolicy {
+    /// Whether peers may create boards at all
+    pub enabled: bool,
+    /// Maximum boards a single peer may create within `window_secs`
+    pub max_boards_per_window: u32,
+    /// Length of the per-peer creation window in seconds
+    pub window_secs: i64,
+}
+
+/// Trim and validate a board name, returning the cleaned name.
+fn validate_board_name(name: &str) -> Result<String, String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Board name cannot be empty".to_string());
+    }
+    if name.chars().count() > MAX_BOARD_NAME_CHARS {
+        return Err(format!(
+            "Board name too long: maximum {} characters",
+            MAX_BOARD_NAME_CHARS
+        ));
+    }
+    if name.chars().any(char::is_control) {
+        return Err("Board name cannot contain control characters".to_string());
+    }
+    Ok(name.to_string())
+}
+
 // ============================================================
 // Board service
 // ============================================================
@@ -172,22 +308,153 @@ fn verify_registered_peer_signature(
 pub struct BoardService {
     db: RelayDatabase,
     community_name: String,
+    peer_board_policy: PeerBoardPolicy,
+    /// Whether a leaving peer's board and wall posts are deleted with them
+    purge_posts_on_leave: bool,
+    /// Relay identity, used to sign the receive time of stored posts
+    relay_keypair: Keypair,
+    /// Public address put in invites members request (`--allow-member-invites`);
+    /// `None` refuses member invites
+    member_invite_address: Option<String>,
+    /// Largest post text accepted, in bytes (`--max-post-bytes`)
+    max_post_bytes: usize,
 }
 
 impl BoardService {
-    pub fn new(db: RelayDatabase, community_name: String) -> Self {
-        Self { db, community_name }
+    pub fn new(
+        db: RelayDatabase,
+        community_name: String,
+        peer_board_policy: PeerBoardPolicy,
+        purge_posts_on_leave: bool,
+        relay_keypair: Keypair,
+    ) -> Self {
+        Self {
+            db,
+            community_name,
+            peer_board_policy,
+            purge_posts_on_leave,
+            relay_keypair,
+            member_invite_address: None,
+            max_post_bytes: DEFAULT_MAX_POST_BYTES,
+        }
+    }
+
+    /// Reject board and wall posts whose text is longer than `max_post_bytes`
+    pub fn set_max_post_bytes(&mut self, max_post_bytes: usize) {
+        self.max_post_bytes = max_post_bytes;
+    }
+
+    /// Check a post's text against `max_post_bytes`, before anything is stored
+    fn check_post_size(&self, content_text: Option<&str>) -> Result<(), String> {
+        let size = content_text.map_or(0, str::len);
+        if size > self.max_post_bytes {
+            return Err(format!(
+                "Post too large: {} bytes, maximum {} bytes",
+                size, self.max_post_bytes
+            ));
+        }
+        Ok(())
+    }
+
+    /// Let registered members request invites leading to `relay_address`
+    pub fn allow_member_invites(&mut self, relay_address: String) {
+        self.member_invite_address = Some(relay_address);
+    }
+
+    /// Sign an invite to this community for a registered member.
+    ///
+    /// The expiry is capped at `MAX_MEMBER_INVITE_LIFETIME_SECS` from now, so
+    /// a member can't hand out an invite that outlives their membership by long.
+    pub fn process_create_invite(
+        &self,
+        requester_peer_id: &str,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<String, String> {
+        let Some(ref relay_address) = self.member_invite_address else {
+            return Err("Invites by members are disabled on this relay".to_string());
+        };
+        if !self.db.is_peer_known(requester_peer_id).unwrap_or(false) {
+            return Err("Peer not registered. Call RegisterPeer first.".to_string());
+        }
+        if self.db.is_peer_banned(requester_peer_id).unwrap_or(false) {
+            return Err("Peer is banned".to_string());
+        }
+
+        let signable_request = SignableInviteRequest {
+            requester_peer_id: requester_peer_id.to_string(),
+            expires_at,
+            max_uses,
+            timestamp,
+        };
+        verify_registered_peer_signature(&self.db, requester_peer_id, &signable_request, signature)
+            .map_err(|verification_error| {
+                warn!(
+                    request_type = "create_invite",
+                    peer_id = requester_peer_id,
+                    error = %verification_error,
+                    "Signature verification failed"
+                );
+                format!("Signature verification failed: {}", verification_error)
+            })?;
+
+        let latest_expiry = chrono::Utc::now().timestamp() + MAX_MEMBER_INVITE_LIFETIME_SECS;
+        let expires_at = expires_at.map_or(latest_expiry, |at| at.min(latest_expiry));
+        let token = crate::invite::create_invite(
+            &self.relay_keypair,
+            relay_address,
+            &self.community_name,
+            Some(expires_at),
+            max_uses,
+        )?;
+        info!(
+            peer_id = requester_peer_id,
+            expires_at, "Invite created for member"
+        );
+        Ok(token)
+    }
+
+    /// Record the current time as the relay-observed time of a post and sign
+    /// it, so clients can compare it with the author's claimed `created_at`.
+    fn anchor_timestamp(
+        &self,
+        post_id: &str,
+        author_peer_id: &str,
+        author_signature: &[u8],
+    ) -> Result<(i64, Vec<u8>), String> {
+        let relay_observed_at = chrono::Utc::now().timestamp();
+        let signable = SignableRelayTimestamp {
+            relay_peer_id: PeerId::from(self.relay_keypair.public()).to_string(),
+            post_id: post_id.to_string(),
+            author_peer_id: author_peer_id.to_string(),
+            author_signature: author_signature.to_vec(),
+            relay_observed_at,
+        };
+        let relay_signature = self
+            .relay_keypair
+            .sign(&signable.signable_bytes()?)
+            .map_err(|sign_error| format!("Failed to sign post timestamp: {}", sign_error))?;
+        Ok((relay_observed_at, relay_signature))
     }
 
     pub fn community_name(&self) -> &str {
         &self.community_name
     }
 
+    /// Flush the database before the relay exits
+    pub fn flush(&self) -> Result<(), String> {
+        self.db
+            .flush()
+            .map_err(|db_error| format!("Failed to flush database: {}", db_error))
+    }
+
     /// Register a peer so they can post.
     ///
     /// For registration, the public key is provided in the request itself
-    /// (this is the first time we see this peer), so we verify the signature
-    /// against the supplied public key before storing it.
+    /// (this is the first time we see this peer), so we check that the peer ID
+    /// derives from it and verify the signature against it before storing it.
     pub fn process_register_peer(
         &self,
         peer_id: &str,
@@ -201,31 +468,113 @@ impl BoardService {
         }
 
         // Verify the signature using the public key provided in the request.
-        // This proves the registrant actually holds the corresponding private key.
+        // This proves the registrant actually holds the corresponding private key,
+        // and the binding check proves that key is the one behind the peer ID.
         let signable_registration = SignablePeerRegistration {
             peer_id: peer_id.to_string(),
             display_name: display_name.to_string(),
             timestamp,
         };
 
-        verify_signature(public_key, &signable_registration, signature).map_err(
-            |verification_error| {
+        verify_peer_key_binding(peer_id, public_key)
+            .and_then(|()| verify_signature(public_key, &signable_registration, signature))
+            .map_err(|verification_error| {
                 warn!(
-                    "RegisterPeer signature verification failed for {}: {}",
-                    peer_id, verification_error
+                    request_type = "register_peer",
+                    peer_id,
+                    error = %verification_error,
+                    "Signature verification failed"
                 );
                 format!("Signature verification failed: {}", verification_error)
-            },
-        )?;
+            })?;
 
         self.db
             .register_peer(peer_id, public_key, display_name)
             .map_err(|db_error| format!("Failed to register peer: {}", db_error))?;
 
-        info!("Registered peer: {} ({})", display_name, peer_id);
+        info!(peer_id, display_name, "Registered peer");
+        Ok(())
+    }
+
+    /// Remove a peer's registration when they leave the community.
+    ///
+    /// The signature is checked against the peer's stored public key, so
+    /// only the key holder can deregister themselves. Their posts are also
+    /// removed when the relay runs with `--purge-posts-on-leave`.
+    pub fn process_deregister_peer(
+        &self,
+        peer_id: &str,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<(), String> {
+        let signable_deregistration = SignablePeerDeregistration {
+            peer_id: peer_id.to_string(),
+            timestamp,
+        };
+
+        verify_registered_peer_signature(&self.db, peer_id, &signable_deregistration, signature)
+            .map_err(|verification_error| {
+                warn!(
+                    request_type = "deregister_peer",
+                    peer_id,
+                    error = %verification_error,
+                    "Signature verification failed"
+                );
+                format!("Signature verification failed: {}", verification_error)
+            })?;
+
+        let removed = self
+            .db
+            .deregister_peer(peer_id, self.purge_posts_on_leave)
+            .map_err(|db_error| format!("Failed to deregister peer: {}", db_error))?;
+
+        if !removed {
+            return Err("Peer not registered".to_string());
+        }
+
+        info!(
+            peer_id,
+            purged_posts = self.purge_posts_on_leave,
+            "Deregistered peer"
+        );
         Ok(())
     }
 
+    /// List every registered peer for the operator (admin socket only)
+    pub fn list_registered_peers(&self) -> Result<Vec<RegisteredPeerRow>, String> {
+        self.db
+            .list_registered_peers()
+            .map_err(|db_error| format!("Failed to list peers: {}", db_error))
+    }
+
+    /// Remove registrations not renewed within `days` (admin socket only).
+    ///
+    /// This is housekeeping, not a ban: a pruned peer can register again.
+    /// Their posts are deleted too when `purge_posts` is set. Returns the
+    /// IDs of the removed peers.
+    pub fn prune_inactive_peers(
+        &self,
+        days: u32,
+        purge_posts: bool,
+    ) -> Result<Vec<String>, String> {
+        if days == 0 {
+            return Err("Inactivity period must be at least one day".to_string());
+        }
+        let cutoff = chrono::Utc::now().timestamp() - i64::from(days) * 24 * 60 * 60;
+        let pruned = self
+            .db
+            .prune_inactive_peers(cutoff, purge_posts)
+            .map_err(|db_error| format!("Failed to prune peers: {}", db_error))?;
+
+        info!(
+            pruned = pruned.len(),
+            inactive_days = days,
+            purged_posts = purge_posts,
+            "Pruned inactive peers"
+        );
+        Ok(pruned)
+    }
+
     /// Submit a post to a board.
     ///
     /// Verifies the signature against the author's stored public key
@@ -256,6 +605,8 @@ impl BoardService {
             return Err(format!("Board {} does not exist", board_id));
         }
 
+        self.check_post_size(content_text)?;
+
         // Verify signature against the author's stored public key.
         // This must happen before the database transaction so that we never
         // write a post whose signature is invalid.
@@ -272,12 +623,18 @@ impl BoardService {
         verify_registered_peer_signature(&self.db, author_peer_id, &signable_post, signature)
             .map_err(|verification_error| {
                 warn!(
-                    "SubmitPost signature verification failed for post {} by {}: {}",
-                    post_id, author_peer_id, verification_error
+                    request_type = "submit_post",
+                    peer_id = author_peer_id,
+                    post_id,
+                    error = %verification_error,
+                    "Signature verification failed"
                 );
                 format!("Signature verification failed: {}", verification_error)
             })?;
 
+        let (relay_observed_at, relay_signature) =
+            self.anchor_timestamp(post_id, author_peer_id, signature)?;
+
         // Atomically validate the lamport clock, insert the post, and advance
         // the clock high-water mark inside a single database transaction.
         // This eliminates TOCTOU races where two concurrent submissions from
@@ -292,18 +649,23 @@ impl BoardService {
                 lamport_clock,
                 created_at,
                 signature,
+                relay_observed_at,
+                &relay_signature,
             )
             .map_err(|validation_or_db_error| {
                 warn!(
-                    "Rejected post {} from {}: {}",
-                    post_id, author_peer_id, validation_or_db_error
+                    peer_id = author_peer_id,
+                    post_id,
+                    board_id,
+                    error = %validation_or_db_error,
+                    "Rejected post"
                 );
                 validation_or_db_error
             })?;
 
         info!(
-            "Post {} accepted from {} on board {} (lamport_clock={})",
-            post_id, author_peer_id, board_id, lamport_clock
+            peer_id = author_peer_id,
+            post_id, board_id, lamport_clock, "Post accepted"
         );
         Ok(())
     }
@@ -332,8 +694,10 @@ impl BoardService {
         )
         .map_err(|verification_error| {
             warn!(
-                "ListBoards signature verification failed for {}: {}",
-                requester_peer_id, verification_error
+                request_type = "list_boards",
+                peer_id = requester_peer_id,
+                error = %verification_error,
+                "Signature verification failed"
             );
             format!("Signature verification failed: {}", verification_error)
         })?;
@@ -343,6 +707,100 @@ impl BoardService {
             .map_err(|db_error| format!("Failed to list boards: {}", db_error))
     }
 
+    /// Create a new board on behalf of a peer.
+    ///
+    /// Only allowed when the operator started the relay with
+    /// `--allow-peer-boards`. The requester must be registered and not
+    /// banned; the name is validated, duplicate names are rejected, and each
+    /// peer may only create a limited number of boards per window.
+    pub fn process_create_board(
+        &self,
+        requester_peer_id: &str,
+        name: &str,
+        description: Option<&str>,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<crate::db::BoardRow, String> {
+        if !self.peer_board_policy.enabled {
+            return Err("Board creation by peers is disabled on this relay".to_string());
+        }
+
+        // Check peer is known
+        if !self.db.is_peer_known(requester_peer_id).unwrap_or(false) {
+            return Err("Peer not registered. Call RegisterPeer first.".to_string());
+        }
+
+        // Check not banned
+        if self.db.is_peer_banned(requester_peer_id).unwrap_or(false) {
+            return Err("Peer is banned".to_string());
+        }
+
+        // Verify the signature over the request exactly as sent
+        let signable_create = SignableBoardCreate {
+            requester_peer_id: requester_peer_id.to_string(),
+            name: name.to_string(),
+            description: description.map(|d| d.to_string()),
+            timestamp,
+        };
+
+        verify_registered_peer_signature(
+            &self.db,
+            requester_peer_id,
+            &signable_create,
+            signature,
+        )
+        .map_err(|verification_error| {
+            warn!(
+                request_type = "create_board",
+                peer_id = requester_peer_id,
+                error = %verification_error,
+                "Signature verification failed"
+            );
+            format!("Signature verification failed: {}", verification_error)
+        })?;
+
+        let name = validate_board_name(name)?;
+        let description = description.map(str::trim).filter(|d| !d.is_empty());
+        if let Some(description) = description {
+            if description.chars().count() > MAX_BOARD_DESCRIPTION_CHARS {
+                return Err(format!(
+                    "Board description too long: maximum {} characters",
+                    MAX_BOARD_DESCRIPTION_CHARS
+                ));
+            }
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        let board = self
+            .db
+            .insert_peer_board(
+                &uuid::Uuid::new_v4().to_string(),
+                &name,
+                description,
+                requester_peer_id,
+                now,
+                self.peer_board_policy.max_boards_per_window,
+                now - self.peer_board_policy.window_secs,
+            )
+            .map_err(|validation_or_db_error| {
+                warn!(
+                    peer_id = requester_peer_id,
+                    board_name = name,
+                    error = %validation_or_db_error,
+                    "Rejected board"
+                );
+                validation_or_db_error
+            })?;
+
+        info!(
+            peer_id = requester_peer_id,
+            board_id = %board.board_id,
+            board_name = %board.name,
+            "Board created"
+        );
+        Ok(board)
+    }
+
     /// Get paginated posts for a board.
     ///
     /// Verifies the requester's signature before returning data.
@@ -370,8 +828,10 @@ impl BoardService {
         )
         .map_err(|verification_error| {
             warn!(
-                "GetBoardPosts signature verification failed for {}: {}",
-                requester_peer_id, verification_error
+                request_type = "get_board_posts",
+                peer_id = requester_peer_id,
+                error = %verification_error,
+                "Signature verification failed"
             );
             format!("Signature verification failed: {}", verification_error)
         })?;
@@ -379,7 +839,12 @@ impl BoardService {
         let clamped_limit = limit.min(100);
         let posts = self
             .db
-            .get_board_posts(board_id, after_timestamp, clamped_limit + 1)
+            .get_board_posts(
+                board_id,
+                requester_peer_id,
+                after_timestamp,
+                clamped_limit + 1,
+            )
             .map_err(|db_error| format!("Failed to get board posts: {}", db_error))?;
 
         let has_more = posts.len() > clamped_limit as usize;
@@ -413,8 +878,11 @@ impl BoardService {
         verify_registered_peer_signature(&self.db, author_peer_id, &signable_delete, signature)
             .map_err(|verification_error| {
                 warn!(
-                    "DeletePost signature verification failed for post {} by {}: {}",
-                    post_id, author_peer_id, verification_error
+                    request_type = "delete_post",
+                    peer_id = author_peer_id,
+                    post_id,
+                    error = %verification_error,
+                    "Signature verification failed"
                 );
                 format!("Signature verification failed: {}", verification_error)
             })?;
@@ -426,16 +894,78 @@ impl BoardService {
 
         if !deleted {
             warn!(
-                "Post {} not found or not owned by {}",
-                post_id, author_peer_id
+                peer_id = author_peer_id,
+                post_id,
+                "Post not found or not owned by peer"
             );
             return Err("Post not found or not owned by you".to_string());
         }
 
-        info!("Post {} deleted by {}", post_id, author_peer_id);
+        info!(peer_id = author_peer_id, post_id, "Post deleted");
         Ok(())
     }
 
+    /// Add or remove a peer's reaction to a board post.
+    ///
+    /// Each peer holds at most one reaction per post, so reacting again
+    /// replaces the earlier reaction and removing one that isn't there is a
+    /// no-op. Returns the post's reaction count afterwards.
+    pub fn process_react_to_post(
+        &self,
+        post_id: &str,
+        reactor_peer_id: &str,
+        reaction_type: &str,
+        remove: bool,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<u64, String> {
+        let signable_reaction = SignableBoardPostReaction {
+            post_id: post_id.to_string(),
+            reactor_peer_id: reactor_peer_id.to_string(),
+            reaction_type: reaction_type.to_string(),
+            remove,
+            timestamp,
+        };
+
+        verify_registered_peer_signature(&self.db, reactor_peer_id, &signable_reaction, signature)
+            .map_err(|verification_error| {
+                warn!(
+                    request_type = "react_to_post",
+                    peer_id = reactor_peer_id,
+                    post_id,
+                    error = %verification_error,
+                    "Signature verification failed"
+                );
+                format!("Signature verification failed: {}", verification_error)
+            })?;
+
+        let reaction_count = if remove {
+            self.db.remove_post_reaction(post_id, reactor_peer_id)
+        } else {
+            if reaction_type.is_empty() || reaction_type.chars().count() > MAX_REACTION_TYPE_CHARS {
+                return Err(format!(
+                    "Reaction type must be 1-{} characters",
+                    MAX_REACTION_TYPE_CHARS
+                ));
+            }
+            self.db.set_post_reaction(
+                post_id,
+                reactor_peer_id,
+                reaction_type,
+                timestamp,
+                signature,
+            )
+        }
+        .map_err(|db_error| format!("Failed to record reaction: {}", db_error))?
+        .ok_or_else(|| "Post not found".to_string())?;
+
+        info!(
+            peer_id = reactor_peer_id,
+            post_id, remove, reaction_count, "Post reaction recorded"
+        );
+        Ok(reaction_count)
+    }
+
     // ============================================================
     // Wall post operations
     // ============================================================
@@ -477,6 +1007,8 @@ impl BoardService {
             ));
         }
 
+        self.check_post_size(content_text)?;
+
         // Verify request_signature against the author's stored public key.
         let signable_submit = SignableWallPostSubmit {
             author_peer_id: author_peer_id.to_string(),
@@ -498,13 +1030,18 @@ impl BoardService {
         )
         .map_err(|verification_error| {
             warn!(
-                "SubmitWallPost signature verification failed for post {} by {}: {}",
-                post_id, author_peer_id, verification_error
+                request_type = "submit_wall_post",
+                peer_id = author_peer_id,
+                post_id,
+                error = %verification_error,
+                "Signature verification failed"
             );
             format!("Signature verification failed: {}", verification_error)
         })?;
 
         // Store the wall post
+        let (stored_at, relay_signature) =
+            self.anchor_timestamp(post_id, author_peer_id, signature)?;
         self.db
             .insert_wall_post(
                 post_id,
@@ -515,6 +1052,8 @@ impl BoardService {
                 lamport_clock,
                 created_at,
                 signature,
+                stored_at,
+                &relay_signature,
             )
             .map_err(|db_error| format!("Failed to store wall post: {}", db_error))?;
 
@@ -531,16 +1070,17 @@ impl BoardService {
                 item.height,
                 item.sort_order,
             ) {
-                warn!(
-                    "Failed to store media metadata for post {}: {}",
-                    post_id, e
-                );
+                warn!(post_id, error = %e, "Failed to store media metadata");
             }
         }
 
         info!(
-            "Wall post {} stored for {} (visibility={}, lamport_clock={}, media={})",
-            post_id, author_peer_id, visibility, lamport_clock, media_items.len()
+            peer_id = author_peer_id,
+            post_id,
+            visibility,
+            lamport_clock,
+            media_count = media_items.len(),
+            "Wall post stored"
         );
         Ok(())
     }
@@ -575,8 +1115,10 @@ impl BoardService {
         )
         .map_err(|verification_error| {
             warn!(
-                "GetWallPosts signature verification failed for {}: {}",
-                requester_peer_id, verification_error
+                request_type = "get_wall_posts",
+                peer_id = requester_peer_id,
+                error = %verification_error,
+                "Signature verification failed"
             );
             format!("Signature verification failed: {}", verification_error)
         })?;
@@ -629,8 +1171,11 @@ impl BoardService {
         verify_registered_peer_signature(&self.db, author_peer_id, &signable_delete, signature)
             .map_err(|verification_error| {
                 warn!(
-                    "DeleteWallPost signature verification failed for post {} by {}: {}",
-                    post_id, author_peer_id, verification_error
+                    request_type = "delete_wall_post",
+                    peer_id = author_peer_id,
+                    post_id,
+                    error = %verification_error,
+                    "Signature verification failed"
                 );
                 format!("Signature verification failed: {}", verification_error)
             })?;
@@ -642,13 +1187,342 @@ impl BoardService {
 
         if !deleted {
             warn!(
-                "Wall post {} not found or not owned by {}",
-                post_id, author_peer_id
+                peer_id = author_peer_id,
+                post_id,
+                "Wall post not found or not owned by peer"
             );
             return Err("Wall post not found or not owned by you".to_string());
         }
 
-        info!("Wall post {} deleted by {}", post_id, author_peer_id);
+        info!(peer_id = author_peer_id, post_id, "Wall post deleted");
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_service() -> BoardService {
+        BoardService::new(
+            RelayDatabase::open(":memory:").unwrap(),
+            "Test Community".to_string(),
+            PeerBoardPolicy {
+                enabled: false,
+                max_boards_per_window: 0,
+                window_secs: 0,
+            },
+            false,
+            Keypair::generate_ed25519(),
+        )
+    }
+
+    fn peer_id_of(keypair: &Keypair) -> String {
+        PeerId::from(keypair.public()).to_string()
+    }
+
+    fn public_key_of(keypair: &Keypair) -> Vec<u8> {
+        keypair
+            .public()
+            .try_into_ed25519()
+            .unwrap()
+            .to_bytes()
+            .to_vec()
+    }
+
+    fn sign(keypair: &Keypair, signable: &impl Signable) -> Vec<u8> {
+        keypair.sign(&signable.signable_bytes().unwrap()).unwrap()
+    }
+
+    /// Register `keypair` under `peer_id`, signing with `keypair`
+    fn register_as(service: &BoardService, peer_id: &str, keypair: &Keypair) -> Result<(), String> {
+        let timestamp = chrono::Utc::now().timestamp();
+        let registration = SignablePeerRegistration {
+            peer_id: peer_id.to_string(),
+            display_name: "Peer".to_string(),
+            timestamp,
+        };
+        service.process_register_peer(
+            peer_id,
+            &public_key_of(keypair),
+            "Peer",
+            timestamp,
+            &sign(keypair, &registration),
+        )
+    }
+
+    fn post(service: &BoardService, post_id: &str, author_peer_id: &str) -> SignableBoardPost {
+        SignableBoardPost {
+            post_id: post_id.to_string(),
+            board_id: service.db.list_boards().unwrap()[0].board_id.clone(),
+            author_peer_id: author_peer_id.to_string(),
+            content_type: "text".to_string(),
+            content_text: Some("hello board".to_string()),
+            lamport_clock: 1,
+            created_at: chrono::Utc::now().timestamp(),
+        }
+    }
+
+    fn submit(
+        service: &BoardService,
+        post: &SignableBoardPost,
+        signature: &[u8],
+    ) -> Result<(), String> {
+        service.process_submit_post(
+            &post.post_id,
+            &post.board_id,
+            &post.author_peer_id,
+            &post.content_type,
+            post.content_text.as_deref(),
+            post.lamport_clock,
+            post.created_at,
+            signature,
+        )
+    }
+
+    #[test]
+    fn test_submit_post_rejects_forged_signatures() {
+        let service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let mallory = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+        register_as(&service, &alice_id, &alice).unwrap();
+        register_as(&service, &peer_id_of(&mallory), &mallory).unwrap();
+
+        let original = post(&service, "post-1", &alice_id);
+
+        // Signed by another registered peer
+        assert!(submit(&service, &original, &sign(&mallory, &original)).is_err());
+
+        // Alice's signature over different content
+        let mut tampered = original.clone();
+        tampered.content_text = Some("edited by someone else".to_string());
+        assert!(submit(&service, &tampered, &sign(&alice, &original)).is_err());
+
+        // Unsigned or garbage signatures
+        assert!(submit(&service, &original, &[]).is_err());
+        assert!(submit(&service, &original, &[0u8; 64]).is_err());
+
+        // None of the rejected submissions were stored
+        submit(&service, &original, &sign(&alice, &original)).unwrap();
+    }
+
+    #[test]
+    fn test_oversized_posts_are_rejected_without_storing() {
+        let mut service = create_service();
+        service.set_max_post_bytes(16);
+        let alice = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+        register_as(&service, &alice_id, &alice).unwrap();
+
+        let mut oversized = post(&service, "post-1", &alice_id);
+        oversized.content_text = Some("x".repeat(17));
+        let error = submit(&service, &oversized, &sign(&alice, &oversized)).unwrap_err();
+        assert!(error.contains("Post too large"), "{}", error);
+        let board_id = oversized.board_id.clone();
+        assert!(service
+            .db
+            .get_board_posts(&board_id, &alice_id, None, 10)
+            .unwrap()
+            .is_empty());
+
+        // The limit is inclusive
+        let mut at_limit = post(&service, "post-2", &alice_id);
+        at_limit.content_text = Some("x".repeat(16));
+        submit(&service, &at_limit, &sign(&alice, &at_limit)).unwrap();
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let wall_post = SignableWallPostSubmit {
+            author_peer_id: alice_id.clone(),
+            post_id: "wall-1".to_string(),
+            content_type: "text".to_string(),
+            content_text: Some("x".repeat(17)),
+            visibility: "public".to_string(),
+            lamport_clock: 1,
+            created_at: timestamp,
+            signature: vec![0u8; 64],
+            timestamp,
+        };
+        let error = service
+            .process_submit_wall_post(
+                &alice_id,
+                &wall_post.post_id,
+                &wall_post.content_type,
+                wall_post.content_text.as_deref(),
+                &wall_post.visibility,
+                wall_post.lamport_clock,
+                wall_post.created_at,
+                &wall_post.signature,
+                wall_post.timestamp,
+                &sign(&alice, &wall_post),
+                &[],
+            )
+            .unwrap_err();
+        assert!(error.contains("Post too large"), "{}", error);
+        assert!(service
+            .db
+            .get_wall_posts(&alice_id, 0, 10)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_member_invites_need_operator_opt_in_and_a_valid_signature() {
+        let mut service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+        register_as(&service, &alice_id, &alice).unwrap();
+
+        let request = SignableInviteRequest {
+            requester_peer_id: alice_id.clone(),
+            expires_at: None,
+            max_uses: Some(5),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        let create = |service: &BoardService, signature: &[u8]| {
+            service.process_create_invite(
+                &request.requester_peer_id,
+                request.expires_at,
+                request.max_uses,
+                request.timestamp,
+                signature,
+            )
+        };
+
+        assert!(create(&service, &sign(&alice, &request)).is_err());
+
+        service.allow_member_invites("/ip4/203.0.113.7/tcp/4001".to_string());
+        assert!(create(&service, &[0u8; 64]).is_err());
+        let token = create(&service, &sign(&alice, &request)).unwrap();
+        assert!(token.starts_with(crate::invite::INVITE_PREFIX));
+    }
+
+    #[test]
+    fn test_register_peer_requires_key_behind_peer_id() {
+        let service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let mallory = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+
+        // Mallory signs correctly with her own key, but it isn't Alice's
+        assert!(register_as(&service, &alice_id, &mallory).is_err());
+        assert!(!service.db.is_peer_known(&alice_id).unwrap());
+
+        register_as(&service, &alice_id, &alice).unwrap();
+        assert!(service.db.is_peer_known(&alice_id).unwrap());
+    }
+
+    #[test]
+    fn test_unbound_stored_key_cannot_sign_posts() {
+        let service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let mallory = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+
+        // A key stored before registration checked the binding
+        service
+            .db
+            .register_peer(&alice_id, &public_key_of(&mallory), "Alice")
+            .unwrap();
+        let forged = post(&service, "post-1", &alice_id);
+        assert!(submit(&service, &forged, &sign(&mallory, &forged)).is_err());
+
+        // Alice registering again replaces the stale key
+        register_as(&service, &alice_id, &alice).unwrap();
+        submit(&service, &forged, &sign(&alice, &forged)).unwrap();
+    }
+
+    fn react(
+        service: &BoardService,
+        keypair: &Keypair,
+        post_id: &str,
+        reaction_type: &str,
+        remove: bool,
+        timestamp: i64,
+    ) -> Result<u64, String> {
+        let reaction = SignableBoardPostReaction {
+            post_id: post_id.to_string(),
+            reactor_peer_id: peer_id_of(keypair),
+            reaction_type: reaction_type.to_string(),
+            remove,
+            timestamp,
+        };
+        service.process_react_to_post(
+            &reaction.post_id,
+            &reaction.reactor_peer_id,
+            &reaction.reaction_type,
+            remove,
+            timestamp,
+            &sign(keypair, &reaction),
+        )
+    }
+
+    #[test]
+    fn test_reactions_count_each_peer_once() {
+        let service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let bob = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+        register_as(&service, &alice_id, &alice).unwrap();
+        register_as(&service, &peer_id_of(&bob), &bob).unwrap();
+        let original = post(&service, "post-1", &alice_id);
+        submit(&service, &original, &sign(&alice, &original)).unwrap();
+
+        assert_eq!(react(&service, &alice, "post-1", "like", false, 1), Ok(1));
+        assert_eq!(react(&service, &bob, "post-1", "like", false, 1), Ok(2));
+        // Reacting again, even differently, replaces the earlier reaction
+        assert_eq!(react(&service, &bob, "post-1", "like", false, 2), Ok(2));
+        assert_eq!(react(&service, &bob, "post-1", "heart", false, 3), Ok(2));
+
+        let board_id = original.board_id.clone();
+        let posts = service
+            .db
+            .get_board_posts(&board_id, &peer_id_of(&bob), None, 10)
+            .unwrap();
+        assert_eq!(posts[0].reaction_count, 2);
+        assert_eq!(posts[0].requester_reaction.as_deref(), Some("heart"));
+
+        // Unregistered, forged and unknown-post reactions are refused
+        let mallory = Keypair::generate_ed25519();
+        assert!(react(&service, &mallory, "post-1", "like", false, 1).is_err());
+        // Alice removing Bob's reaction
+        let forged = SignableBoardPostReaction {
+            post_id: "post-1".to_string(),
+            reactor_peer_id: peer_id_of(&bob),
+            reaction_type: "heart".to_string(),
+            remove: true,
+            timestamp: 4,
+        };
+        assert!(service
+            .process_react_to_post(
+                "post-1",
+                &forged.reactor_peer_id,
+                "heart",
+                true,
+                4,
+                &sign(&alice, &forged)
+            )
+            .is_err());
+        assert!(react(&service, &alice, "missing", "like", false, 1).is_err());
+    }
+
+    #[test]
+    fn test_removing_a_reaction_is_idempotent() {
+        let service = create_service();
+        let alice = Keypair::generate_ed25519();
+        let bob = Keypair::generate_ed25519();
+        let alice_id = peer_id_of(&alice);
+        register_as(&service, &alice_id, &alice).unwrap();
+        register_as(&service, &peer_id_of(&bob), &bob).unwrap();
+        let original = post(&service, "post-1", &alice_id);
+        submit(&service, &original, &sign(&alice, &original)).unwrap();
+
+        react(&service, &alice, "post-1", "like", false, 1).unwrap();
+        react(&service, &bob, "post-1", "like", false, 1).unwrap();
+
+        assert_eq!(react(&service, &bob, "post-1", "like", true, 2), Ok(1));
+        assert_eq!(react(&service, &bob, "post-1", "like", true, 3), Ok(1));
+        assert_eq!(react(&service, &alice, "post-1", "like", true, 2), Ok(0));
+        assert_eq!(react(&service, &alice, "post-1", "like", true, 3), Ok(0));
+    }
+}
@@ -1,6 +1,6 @@
 //! Relay server SQLite database for community board data
 
-use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
+use rusqlite::{params, Connection, OpenFlags, OptionalExtension, Result as SqliteResult};
 use std::sync::{Arc, Mutex};
 use tracing::info;
 
@@ -24,12 +24,24 @@ CREATE TABLE IF NOT EXISTS board_posts (
     created_at INTEGER NOT NULL,
     deleted_at INTEGER,
     signature BLOB NOT NULL,
+    relay_observed_at INTEGER,
+    relay_signature BLOB,
     FOREIGN KEY (board_id) REFERENCES boards(board_id) ON DELETE CASCADE
 );
 
 CREATE INDEX IF NOT EXISTS idx_board_posts_board_time
     ON board_posts(board_id, created_at DESC);
 
+CREATE TABLE IF NOT EXISTS board_post_reactions (
+    post_id TEXT NOT NULL,
+    reactor_peer_id TEXT NOT NULL,
+    reaction_type TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    signature BLOB NOT NULL,
+    PRIMARY KEY (post_id, reactor_peer_id),
+    FOREIGN KEY (post_id) REFERENCES board_posts(post_id) ON DELETE CASCADE
+);
+
 CREATE TABLE IF NOT EXISTS known_peers (
     peer_id TEXT PRIMARY KEY,
     public_key BLOB NOT NULL,
@@ -62,7 +74,8 @@ CREATE TABLE IF NOT EXISTS wall_posts (
     lamport_clock INTEGER NOT NULL,
     created_at INTEGER NOT NULL,
     signature BLOB NOT NULL,
-    stored_at INTEGER NOT NULL
+    stored_at INTEGER NOT NULL,
+    relay_signature BLOB
 );
 
 CREATE INDEX IF NOT EXISTS idx_wall_posts_author
@@ -86,6 +99,18 @@ CREATE INDEX IF NOT EXISTS idx_wall_post_media_post
     ON wall_post_media(post_id);
 "#;
 
+/// Schema version stamped into `PRAGMA user_version`. Bump it whenever the
+/// schema changes, so snapshots from newer relays are refused on import.
+pub const SCHEMA_VERSION: u32 = 2;
+
+/// Columns added after the first release, as (table, column, type). Databases
+/// created before a column existed get it added on open.
+const ADDED_COLUMNS: &[(&str, &str, &str)] = &[
+    ("board_posts", "relay_observed_at", "INTEGER"),
+    ("board_posts", "relay_signature", "BLOB"),
+    ("wall_posts", "relay_signature", "BLOB"),
+];
+
 /// Relay server database
 #[derive(Clone)]
 pub struct RelayDatabase {
@@ -98,6 +123,8 @@ impl RelayDatabase {
         let conn = Connection::open(path)?;
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
         conn.execute_batch(SCHEMA)?;
+        Self::add_missing_columns(&conn)?;
+        conn.pragma_update(None, "user_version", SCHEMA_VERSION)?;
 
         let db = Self {
             conn: Arc::new(Mutex::new(conn)),
@@ -110,6 +137,41 @@ impl RelayDatabase {
         Ok(db)
     }
 
+    fn add_missing_columns(conn: &Connection) -> SqliteResult<()> {
+        for (table, column, column_type) in ADDED_COLUMNS {
+            let exists: bool = conn.query_row(
+                "SELECT COUNT(*) > 0 FROM pragma_table_info(?) WHERE name = ?",
+                params![table, column],
+                |row| row.get(0),
+            )?;
+            if !exists {
+                conn.execute_batch(&format!(
+                    "ALTER TABLE {} ADD COLUMN {} {}",
+                    table, column, column_type
+                ))?;
+                info!("Added column {}.{}", table, column);
+            }
+        }
+        Ok(())
+    }
+
+    /// Schema version and `PRAGMA quick_check` result ("ok" when intact) of
+    /// the database file at `path`. Opens the file read-only.
+    pub fn inspect_file(path: &str) -> SqliteResult<(u32, String)> {
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let version: u32 = conn.query_row("PRAGMA user_version", [], |row| row.get(0))?;
+        let integrity: String = conn.query_row("PRAGMA quick_check", [], |row| row.get(0))?;
+        Ok((version, integrity))
+    }
+
+    /// Write a consistent copy of the database to `path` while it stays in use.
+    /// Fails if `path` already exists.
+    pub fn backup_to(&self, path: &str) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.execute("VACUUM INTO ?", [path])?;
+        Ok(())
+    }
+
     fn ensure_default_board(&self) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
         let count: i64 = conn.query_row(
@@ -183,9 +245,12 @@ impl RelayDatabase {
         Ok(())
     }
 
+    /// Posts on a board, newest first, with their reaction counts and
+    /// `requester_peer_id`'s own reaction
     pub fn get_board_posts(
         &self,
         board_id: &str,
+        requester_peer_id: &str,
         after_timestamp: Option<i64>,
         limit: u32,
     ) -> SqliteResult<Vec<PostRow>> {
@@ -196,14 +261,17 @@ impl RelayDatabase {
             let mut stmt = conn.prepare(
                 "SELECT bp.post_id, bp.board_id, bp.author_peer_id, bp.content_type, bp.content_text,
                         bp.lamport_clock, bp.created_at, bp.deleted_at, bp.signature,
-                        kp.display_name
+                        kp.display_name, bp.relay_observed_at, bp.relay_signature,
+                        (SELECT COUNT(*) FROM board_post_reactions r WHERE r.post_id = bp.post_id),
+                        (SELECT r.reaction_type FROM board_post_reactions r
+                         WHERE r.post_id = bp.post_id AND r.reactor_peer_id = ?)
                  FROM board_posts bp
                  LEFT JOIN known_peers kp ON bp.author_peer_id = kp.peer_id
                  WHERE bp.board_id = ? AND bp.created_at > ?
                  ORDER BY bp.created_at DESC
                  LIMIT ?",
             )?;
-            let mut rows = stmt.query(params![board_id, after, limit])?;
+            let mut rows = stmt.query(params![requester_peer_id, board_id, after, limit])?;
             while let Some(row) = rows.next()? {
                 posts.push(Self::row_to_post(row)?);
             }
@@ -211,14 +279,17 @@ impl RelayDatabase {
             let mut stmt = conn.prepare(
                 "SELECT bp.post_id, bp.board_id, bp.author_peer_id, bp.content_type, bp.content_text,
                         bp.lamport_clock, bp.created_at, bp.deleted_at, bp.signature,
-                        kp.display_name
+                        kp.display_name, bp.relay_observed_at, bp.relay_signature,
+                        (SELECT COUNT(*) FROM board_post_reactions r WHERE r.post_id = bp.post_id),
+                        (SELECT r.reaction_type FROM board_post_reactions r
+                         WHERE r.post_id = bp.post_id AND r.reactor_peer_id = ?)
                  FROM board_posts bp
                  LEFT JOIN known_peers kp ON bp.author_peer_id = kp.peer_id
                  WHERE bp.board_id = ?
                  ORDER BY bp.created_at DESC
                  LIMIT ?",
             )?;
-            let mut rows = stmt.query(params![board_id, limit])?;
+            let mut rows = stmt.query(params![requester_peer_id, board_id, limit])?;
             while let Some(row) = rows.next()? {
                 posts.push(Self::row_to_post(row)?);
             }
@@ -238,6 +309,10 @@ impl RelayDatabase {
             deleted_at: row.get(7)?,
             signature: row.get(8)?,
             author_display_name: row.get(9)?,
+            relay_observed_at: row.get(10)?,
+            relay_signature: row.get(11)?,
+            reaction_count: row.get::<_, i64>(12)? as u64,
+            requester_reaction: row.get(13)?,
         })
     }
 
@@ -251,8 +326,84 @@ impl RelayDatabase {
         Ok(rows > 0)
     }
 
+    // ========== Reaction Operations ==========
+
+    /// Record a peer's reaction to a post, replacing any earlier reaction of
+    /// theirs so each peer counts once. A reaction signed before the stored
+    /// one is ignored. Returns the post's reaction count, or `None` if the
+    /// post doesn't exist or was deleted.
+    pub fn set_post_reaction(
+        &self,
+        post_id: &str,
+        reactor_peer_id: &str,
+        reaction_type: &str,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> SqliteResult<Option<u64>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        if !Self::is_post_live(&tx, post_id)? {
+            return Ok(None);
+        }
+        tx.execute(
+            "INSERT INTO board_post_reactions (post_id, reactor_peer_id, reaction_type, timestamp, signature)
+             VALUES (?, ?, ?, ?, ?)
+             ON CONFLICT(post_id, reactor_peer_id) DO UPDATE SET
+                 reaction_type = excluded.reaction_type,
+                 timestamp = excluded.timestamp,
+                 signature = excluded.signature
+             WHERE excluded.timestamp >= board_post_reactions.timestamp",
+            params![post_id, reactor_peer_id, reaction_type, timestamp, signature],
+        )?;
+        let count = Self::count_post_reactions(&tx, post_id)?;
+        tx.commit()?;
+        Ok(Some(count))
+    }
+
+    /// Remove a peer's reaction to a post. Removing a reaction that isn't
+    /// there changes nothing. Returns the post's reaction count, or `None`
+    /// if the post doesn't exist or was deleted.
+    pub fn remove_post_reaction(
+        &self,
+        post_id: &str,
+        reactor_peer_id: &str,
+    ) -> SqliteResult<Option<u64>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        if !Self::is_post_live(&tx, post_id)? {
+            return Ok(None);
+        }
+        tx.execute(
+            "DELETE FROM board_post_reactions WHERE post_id = ? AND reactor_peer_id = ?",
+            params![post_id, reactor_peer_id],
+        )?;
+        let count = Self::count_post_reactions(&tx, post_id)?;
+        tx.commit()?;
+        Ok(Some(count))
+    }
+
+    fn is_post_live(conn: &Connection, post_id: &str) -> SqliteResult<bool> {
+        conn.query_row(
+            "SELECT COUNT(*) > 0 FROM board_posts WHERE post_id = ? AND deleted_at IS NULL",
+            [post_id],
+            |row| row.get(0),
+        )
+    }
+
+    fn count_post_reactions(conn: &Connection, post_id: &str) -> SqliteResult<u64> {
+        let count: i64 = conn.query_row(
+            "SELECT COUNT(*) FROM board_post_reactions WHERE post_id = ?",
+            [post_id],
+            |row| row.get(0),
+        )?;
+        Ok(count as u64)
+    }
+
     // ========== Peer Operations ==========
 
+    /// Register a peer or refresh its details. Callers check that the key
+    /// derives the peer ID, so replacing the stored key only ever repairs a
+    /// key stored before that check existed.
     pub fn register_peer(
         &self,
         peer_id: &str,
@@ -265,6 +416,7 @@ impl RelayDatabase {
             "INSERT INTO known_peers (peer_id, public_key, display_name, first_seen_at, last_seen_at)
              VALUES (?, ?, ?, ?, ?)
              ON CONFLICT(peer_id) DO UPDATE SET
+                 public_key = excluded.public_key,
                  display_name = excluded.display_name,
                  last_seen_at = excluded.last_seen_at",
             params![peer_id, public_key, display_name, now, now],
@@ -272,6 +424,95 @@ impl RelayDatabase {
         Ok(())
     }
 
+    /// Remove a peer's registration, and their board posts, reactions and
+    /// wall posts when `purge_posts` is set. Returns true if the peer was
+    /// registered.
+    pub fn deregister_peer(&self, peer_id: &str, purge_posts: bool) -> SqliteResult<bool> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        if purge_posts {
+            // Reactions to the posts go with them via ON DELETE CASCADE
+            tx.execute(
+                "DELETE FROM board_posts WHERE author_peer_id = ?",
+                [peer_id],
+            )?;
+            tx.execute(
+                "DELETE FROM board_post_reactions WHERE reactor_peer_id = ?",
+                [peer_id],
+            )?;
+            // Media metadata goes with the posts via ON DELETE CASCADE
+            tx.execute(
+                "DELETE FROM wall_posts WHERE author_peer_id = ?",
+                [peer_id],
+            )?;
+        }
+        // The author's lamport clock high-water mark cascades with this row
+        let rows = tx.execute("DELETE FROM known_peers WHERE peer_id = ?", [peer_id])?;
+        tx.commit()?;
+        Ok(rows > 0)
+    }
+
+    /// List registered peers with the number of board and wall posts each
+    /// has stored, most recently seen first
+    pub fn list_registered_peers(&self) -> SqliteResult<Vec<RegisteredPeerRow>> {
+        let conn = self.conn.lock().unwrap();
+        let mut stmt = conn.prepare(
+            "SELECT k.peer_id, k.display_name, k.first_seen_at, k.last_seen_at,
+                    (SELECT COUNT(*) FROM board_posts b
+                     WHERE b.author_peer_id = k.peer_id AND b.deleted_at IS NULL),
+                    (SELECT COUNT(*) FROM wall_posts w WHERE w.author_peer_id = k.peer_id)
+             FROM known_peers k
+             ORDER BY k.last_seen_at DESC",
+        )?;
+        let rows = stmt.query_map([], |row| {
+            Ok(RegisteredPeerRow {
+                peer_id: row.get(0)?,
+                display_name: row.get(1)?,
+                first_seen_at: row.get(2)?,
+                last_seen_at: row.get(3)?,
+                board_post_count: row.get(4)?,
+                wall_post_count: row.get(5)?,
+            })
+        })?;
+        rows.collect()
+    }
+
+    /// Remove every registration last seen before `cutoff`, and their board
+    /// posts, reactions and wall posts when `purge_posts` is set. Returns the
+    /// removed peer IDs.
+    pub fn prune_inactive_peers(
+        &self,
+        cutoff: i64,
+        purge_posts: bool,
+    ) -> SqliteResult<Vec<String>> {
+        let mut conn = self.conn.lock().unwrap();
+        let tx = conn.transaction()?;
+        let peer_ids = {
+            let mut stmt = tx.prepare("SELECT peer_id FROM known_peers WHERE last_seen_at < ?")?;
+            let rows = stmt.query_map([cutoff], |row| row.get::<_, String>(0))?;
+            rows.collect::<SqliteResult<Vec<String>>>()?
+        };
+        for peer_id in &peer_ids {
+            if purge_posts {
+                tx.execute(
+                    "DELETE FROM board_posts WHERE author_peer_id = ?",
+                    [peer_id],
+                )?;
+                tx.execute(
+                    "DELETE FROM board_post_reactions WHERE reactor_peer_id = ?",
+                    [peer_id],
+                )?;
+                tx.execute(
+                    "DELETE FROM wall_posts WHERE author_peer_id = ?",
+                    [peer_id],
+                )?;
+            }
+            tx.execute("DELETE FROM known_peers WHERE peer_id = ?", [peer_id])?;
+        }
+        tx.commit()?;
+        Ok(peer_ids)
+    }
+
     /// Retrieve the stored public key for a registered peer
     pub fn get_peer_public_key(&self, peer_id: &str) -> SqliteResult<Option<Vec<u8>>> {
         let conn = self.conn.lock().unwrap();
@@ -378,6 +619,8 @@ impl RelayDatabase {
         lamport_clock: u64,
         created_at: i64,
         signature: &[u8],
+        relay_observed_at: i64,
+        relay_signature: &[u8],
     ) -> Result<(), String> {
         let conn = self.conn.lock().unwrap();
 
@@ -409,9 +652,9 @@ impl RelayDatabase {
 
         // Step 3: Insert the post.
         conn.execute(
-            "INSERT INTO board_posts (post_id, board_id, author_peer_id, content_type, content_text, lamport_clock, created_at, signature)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
-            params![post_id, board_id, author_peer_id, content_type, content_text, lamport_clock as i64, created_at, signature],
+            "INSERT INTO board_posts (post_id, board_id, author_peer_id, content_type, content_text, lamport_clock, created_at, signature, relay_observed_at, relay_signature)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+            params![post_id, board_id, author_peer_id, content_type, content_text, lamport_clock as i64, created_at, signature, relay_observed_at, relay_signature],
         )
         .map_err(|e| {
             let _ = conn.execute_batch("ROLLBACK");
@@ -449,6 +692,91 @@ impl RelayDatabase {
         Ok(count > 0)
     }
 
+    /// Create a board on behalf of a peer.
+    ///
+    /// Runs inside a single `BEGIN IMMEDIATE` transaction so that concurrent
+    /// requests cannot slip past the checks:
+    /// 1. Reject if the peer already created `max_boards` boards since
+    ///    `window_start`.
+    /// 2. Reject if a board with the same name (case-insensitive) exists.
+    /// 3. Insert the board row.
+    ///
+    /// Returns the new board on success, or an error string on validation
+    /// failure / database error.
+    pub fn insert_peer_board(
+        &self,
+        board_id: &str,
+        name: &str,
+        description: Option<&str>,
+        created_by_peer_id: &str,
+        created_at: i64,
+        max_boards: u32,
+        window_start: i64,
+    ) -> Result<BoardRow, String> {
+        let conn = self.conn.lock().unwrap();
+
+        conn.execute_batch("BEGIN IMMEDIATE")
+            .map_err(|e| format!("Failed to begin transaction: {}", e))?;
+
+        // Step 1: Per-peer creation limit.
+        let created_recently: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM boards WHERE created_by_peer_id = ? AND created_at >= ?",
+                params![created_by_peer_id, window_start],
+                |row| row.get(0),
+            )
+            .map_err(|e| {
+                let _ = conn.execute_batch("ROLLBACK");
+                format!("Failed to count boards: {}", e)
+            })?;
+
+        if created_recently >= max_boards as i64 {
+            let _ = conn.execute_batch("ROLLBACK");
+            return Err(format!(
+                "Board creation limit reached: {} boards per window",
+                max_boards
+            ));
+        }
+
+        // Step 2: Reject duplicate names.
+        let duplicates: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM boards WHERE name = ? COLLATE NOCASE",
+                [name],
+                |row| row.get(0),
+            )
+            .map_err(|e| {
+                let _ = conn.execute_batch("ROLLBACK");
+                format!("Failed to check board name: {}", e)
+            })?;
+
+        if duplicates > 0 {
+            let _ = conn.execute_batch("ROLLBACK");
+            return Err(format!("A board named '{}' already exists", name));
+        }
+
+        // Step 3: Insert the board.
+        conn.execute(
+            "INSERT INTO boards (board_id, name, description, created_by_peer_id, created_at, is_default)
+             VALUES (?, ?, ?, ?, ?, 0)",
+            params![board_id, name, description, created_by_peer_id, created_at],
+        )
+        .map_err(|e| {
+            let _ = conn.execute_batch("ROLLBACK");
+            format!("Failed to insert board: {}", e)
+        })?;
+
+        conn.execute_batch("COMMIT")
+            .map_err(|e| format!("Failed to commit transaction: {}", e))?;
+
+        Ok(BoardRow {
+            board_id: board_id.to_string(),
+            name: name.to_string(),
+            description: description.map(|d| d.to_string()),
+            is_default: false,
+        })
+    }
+
     // ========== Wall Post Operations ==========
 
     /// Insert a wall post into relay storage.
@@ -465,14 +793,15 @@ impl RelayDatabase {
         lamport_clock: i64,
         created_at: i64,
         signature: &[u8],
+        stored_at: i64,
+        relay_signature: &[u8],
     ) -> SqliteResult<()> {
         let conn = self.conn.lock().unwrap();
-        let now = chrono::Utc::now().timestamp();
         conn.execute(
             "INSERT OR REPLACE INTO wall_posts
                 (post_id, author_peer_id, content_type, content_text, visibility,
-                 lamport_clock, created_at, signature, stored_at)
-             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                 lamport_clock, created_at, signature, stored_at, relay_signature)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
             params![
                 post_id,
                 author_peer_id,
@@ -482,7 +811,8 @@ impl RelayDatabase {
                 lamport_clock,
                 created_at,
                 signature,
-                now,
+                stored_at,
+                relay_signature,
             ],
         )?;
         Ok(())
@@ -500,7 +830,7 @@ impl RelayDatabase {
         let conn = self.conn.lock().unwrap();
         let mut stmt = conn.prepare(
             "SELECT post_id, author_peer_id, content_type, content_text, visibility,
-                    lamport_clock, created_at, signature, stored_at
+                    lamport_clock, created_at, signature, stored_at, relay_signature
              FROM wall_posts
              WHERE author_peer_id = ? AND lamport_clock > ?
              ORDER BY lamport_clock DESC
@@ -520,6 +850,7 @@ impl RelayDatabase {
                 created_at: row.get(6)?,
                 signature: row.get(7)?,
                 stored_at: row.get(8)?,
+                relay_signature: row.get(9)?,
             });
         }
         Ok(posts)
@@ -585,6 +916,14 @@ impl RelayDatabase {
         )?;
         Ok(rows > 0)
     }
+
+    /// Write any dirty pages still held in the connection's cache to disk.
+    /// Called on shutdown, once no more requests will be handled.
+    pub fn flush(&self) -> SqliteResult<()> {
+        let conn = self.conn.lock().unwrap();
+        conn.cache_flush()?;
+        conn.execute_batch("PRAGMA optimize;")
+    }
 }
 
 /// A board row from the database
@@ -596,6 +935,17 @@ pub struct BoardRow {
     pub is_default: bool,
 }
 
+/// A registered peer and how much they have stored on the relay
+#[derive(Debug, Clone)]
+pub struct RegisteredPeerRow {
+    pub peer_id: String,
+    pub display_name: String,
+    pub first_seen_at: i64,
+    pub last_seen_at: i64,
+    pub board_post_count: i64,
+    pub wall_post_count: i64,
+}
+
 /// A post row from the database
 #[derive(Debug, Clone)]
 pub struct PostRow {
@@ -609,6 +959,14 @@ pub struct PostRow {
     pub deleted_at: Option<i64>,
     pub signature: Vec<u8>,
     pub author_display_name: Option<String>,
+    /// When the relay received the post (absent for posts stored before anchoring)
+    pub relay_observed_at: Option<i64>,
+    /// Relay signature over the post's `SignableRelayTimestamp`
+    pub relay_signature: Option<Vec<u8>>,
+    /// Number of peers who reacted to the post
+    pub reaction_count: u64,
+    /// The requesting peer's reaction to the post, if any
+    pub requester_reaction: Option<String>,
 }
 
 /// A wall post row from the database
@@ -623,6 +981,8 @@ pub struct WallPostRow {
     pub created_at: i64,
     pub signature: Vec<u8>,
     pub stored_at: i64,
+    /// Relay signature over the post's `SignableRelayTimestamp`, anchoring `stored_at`
+    pub relay_signature: Option<Vec<u8>>,
 }
 
 /// A wall post media metadata row from the database
@@ -0,0 +1,154 @@
+//! Signed community invitations
+//!
+//! An invite carries the relay's address, community name and peer ID with an
+//! optional expiry and max-uses hint, signed with the relay's identity key.
+//! It is CBOR-encoded and base64url'd behind `harbor://invite/`, so joining a
+//! community is a single paste. Clients verify the signature against the key
+//! embedded in the relay's peer ID, which proves the invite leads to the
+//! relay that issued it. Uses aren't counted: `max_uses` is a hint for the
+//! people passing the invite around.
+
+use base64::Engine;
+use libp2p::identity::Keypair;
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use serde::Serialize;
+
+/// Prefix of the text form of an invite
+pub const INVITE_PREFIX: &str = "harbor://invite/";
+
+/// The signed part of an invite.
+/// Must match `SignableCommunityInvite` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct SignableCommunityInvite {
+    pub relay_address: String,
+    pub community_name: String,
+    pub relay_peer_id: String,
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,
+    pub max_uses: Option<u32>,
+}
+
+/// An invite as encoded in the token.
+/// Must match `CommunityInviteToken` on the client side.
+#[derive(Debug, Clone, Serialize)]
+struct CommunityInviteToken {
+    pub invite: SignableCommunityInvite,
+    pub signature: Vec<u8>,
+}
+
+/// Sign an invite to this relay's community and encode it as a token.
+///
+/// `relay_address` must be dialable from outside; `/p2p/<peer id>` is
+/// appended when missing and must name this relay when present.
+pub fn create_invite(
+    keypair: &Keypair,
+    relay_address: &str,
+    community_name: &str,
+    expires_at: Option<i64>,
+    max_uses: Option<u32>,
+) -> Result<String, String> {
+    let relay_peer_id = PeerId::from(keypair.public());
+    let mut address: Multiaddr = relay_address
+        .parse()
+        .map_err(|parse_error| format!("Invalid relay address: {}", parse_error))?;
+    match address.iter().find_map(|protocol| match protocol {
+        Protocol::P2p(peer_id) => Some(peer_id),
+        _ => None,
+    }) {
+        Some(peer_id) if peer_id != relay_peer_id => {
+            return Err(format!(
+                "Relay address names peer {} but this relay is {}",
+                peer_id, relay_peer_id
+            ));
+        }
+        Some(_) => {}
+        None => address.push(Protocol::P2p(relay_peer_id)),
+    }
+
+    let invite = SignableCommunityInvite {
+        relay_address: address.to_string(),
+        community_name: community_name.to_string(),
+        relay_peer_id: relay_peer_id.to_string(),
+        issued_at: chrono::Utc::now().timestamp(),
+        expires_at,
+        max_uses,
+    };
+    let mut signable_bytes = Vec::new();
+    ciborium::into_writer(&invite, &mut signable_bytes)
+        .map_err(|encode_error| format!("CBOR encoding failed: {}", encode_error))?;
+    let signature = keypair
+        .sign(&signable_bytes)
+        .map_err(|sign_error| format!("Failed to sign invite: {}", sign_error))?;
+
+    let mut token_bytes = Vec::new();
+    ciborium::into_writer(
+        &CommunityInviteToken { invite, signature },
+        &mut token_bytes,
+    )
+    .map_err(|encode_error| format!("CBOR encoding failed: {}", encode_error))?;
+    Ok(format!(
+        "{}{}",
+        INVITE_PREFIX,
+        base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(token_bytes)
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Decode a token far enough to check what was signed
+    fn decode(token: &str) -> (ciborium::Value, Vec<u8>) {
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(token.strip_prefix(INVITE_PREFIX).unwrap())
+            .unwrap();
+        let value: ciborium::Value = ciborium::from_reader(bytes.as_slice()).unwrap();
+        let fields = value.into_map().unwrap();
+        let invite = fields[0].1.clone();
+        let signature = fields[1].1.clone().into_bytes().unwrap();
+        (invite, signature)
+    }
+
+    #[test]
+    fn test_invite_is_signed_by_relay() {
+        let keypair = Keypair::generate_ed25519();
+        let relay_peer_id = PeerId::from(keypair.public());
+
+        let token = create_invite(
+            &keypair,
+            "/ip4/203.0.113.7/tcp/4001",
+            "Harbor Community",
+            Some(1_900_000_000),
+            Some(10),
+        )
+        .unwrap();
+
+        let (invite, signature) = decode(&token);
+        let mut signed = Vec::new();
+        ciborium::into_writer(&invite, &mut signed).unwrap();
+        assert!(keypair.public().verify(&signed, &signature));
+
+        let address = invite.into_map().unwrap()[0].1.clone().into_text().unwrap();
+        assert_eq!(
+            address,
+            format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", relay_peer_id)
+        );
+    }
+
+    #[test]
+    fn test_invite_rejects_another_relays_address() {
+        let keypair = Keypair::generate_ed25519();
+        let other = PeerId::random();
+
+        let result = create_invite(
+            &keypair,
+            &format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", other),
+            "Harbor Community",
+            None,
+            None,
+        );
+        assert!(result.is_err());
+        assert!(create_invite(&keypair, "not an address", "Harbor Community", None, None).is_err());
+    }
+}
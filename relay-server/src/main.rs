@@ -3,26 +3,33 @@
 //! A libp2p relay server that enables NAT traversal for Harbor chat app users.
 //! Run with `--community` to enable community boards with SQLite storage.
 
+mod admin;
 mod board_service;
 mod db;
+mod invite;
+mod peer_store;
+mod snapshot;
 
-use board_service::BoardService;
-use clap::Parser;
+use admin::{AdminCommand, AdminRequest, AdminResponse};
+use board_service::{BoardService, PeerBoardPolicy, DEFAULT_MAX_POST_BYTES};
+use clap::{Parser, ValueEnum};
 use db::RelayDatabase;
+use peer_store::PeerStore;
 use futures::StreamExt;
 use libp2p::{
     identify, noise, ping, relay,
     request_response::{self, ProtocolSupport},
-    swarm::{behaviour::toggle::Toggle, NetworkBehaviour, SwarmEvent},
+    swarm::{behaviour::toggle::Toggle, dial_opts::DialOpts, NetworkBehaviour, SwarmEvent},
     tcp, yamux, Multiaddr, PeerId, StreamProtocol, SwarmBuilder,
     identity::Keypair,
 };
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::fs;
 use std::net::Ipv4Addr;
 use std::path::PathBuf;
 use std::time::{Duration, Instant};
-use tracing::{info, warn};
+use tokio::sync::mpsc;
+use tracing::{debug, info, warn};
 use tracing_subscriber::EnvFilter;
 
 /// Board sync protocol version
@@ -34,9 +41,33 @@ const DEFAULT_RATE_LIMIT_MAX_REQUESTS: u64 = 60;
 /// Default rate limit window duration in seconds
 const DEFAULT_RATE_LIMIT_WINDOW_SECS: u64 = 60;
 
+/// Longest rate limit window accepted, from the command line or the admin socket
+const MAX_RATE_LIMIT_WINDOW_SECS: u64 = 24 * 60 * 60;
+
+/// Default maximum boards a single peer may create per window (with --allow-peer-boards)
+const DEFAULT_PEER_BOARD_LIMIT: u32 = 3;
+
+/// Default per-peer board creation window in seconds (one day)
+const DEFAULT_PEER_BOARD_WINDOW_SECS: i64 = 24 * 60 * 60;
+
 /// How often to purge stale entries from the rate limiter (in seconds)
 const RATE_LIMITER_CLEANUP_INTERVAL_SECS: u64 = 300;
 
+/// Default time to keep relaying existing circuits after a shutdown signal
+const DEFAULT_SHUTDOWN_GRACE_SECS: u64 = 10;
+
+/// How long to wait for connections to close once the drain is over
+const CONNECTION_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// How often to write the peer store to disk (in seconds)
+const PEER_STORE_SAVE_INTERVAL_SECS: u64 = 300;
+
+/// Default age in days after which a stored peer is dropped on load
+const DEFAULT_PEER_STORE_MAX_AGE_DAYS: u64 = 7;
+
+/// Most stored peers dialed on startup
+const WARM_START_DIAL_LIMIT: usize = 16;
+
 /// Per-peer rate limiter for board sync requests.
 ///
 /// Tracks the number of requests each peer has made within a sliding window.
@@ -81,8 +112,10 @@ impl PeerRateLimiter {
         // Check if the peer has exceeded the limit
         if *request_count >= self.max_requests {
             warn!(
-                "Rate limit exceeded for peer {}: {} requests in {}s window",
-                peer_id, request_count, self.window_duration.as_secs()
+                peer_id = %peer_id,
+                request_count = *request_count,
+                window_secs = self.window_duration.as_secs(),
+                "Rate limit exceeded"
             );
             return Err("Rate limit exceeded. Try again later.".to_string());
         }
@@ -91,6 +124,34 @@ impl PeerRateLimiter {
         Ok(())
     }
 
+    /// Change the limit and window while running, returning the previous pair.
+    ///
+    /// Tracked peers keep their count and window start: a longer window
+    /// extends their current one and a shorter window ends it sooner, but no
+    /// peer is reset early or blocked by requests outside its window.
+    fn reconfigure(
+        &mut self,
+        max_requests: u64,
+        window_duration: Duration,
+    ) -> Result<(u64, Duration), String> {
+        if max_requests == 0 {
+            return Err("max_requests must be at least 1".to_string());
+        }
+        if window_duration.is_zero() {
+            return Err("window_secs must be at least 1".to_string());
+        }
+        if window_duration > Duration::from_secs(MAX_RATE_LIMIT_WINDOW_SECS) {
+            return Err(format!(
+                "window_secs must be at most {}",
+                MAX_RATE_LIMIT_WINDOW_SECS
+            ));
+        }
+        let previous = (self.max_requests, self.window_duration);
+        self.max_requests = max_requests;
+        self.window_duration = window_duration;
+        Ok(previous)
+    }
+
     /// Remove entries for peers whose windows have long since expired.
     ///
     /// This prevents unbounded memory growth from peers that connect once
@@ -98,7 +159,7 @@ impl PeerRateLimiter {
     /// more than `2 * window_duration` ago.
     fn cleanup_stale_entries(&mut self) {
         let now = Instant::now();
-        let stale_threshold = self.window_duration * 2;
+        let stale_threshold = self.window_duration.saturating_mul(2);
         let initial_count = self.peers.len();
 
         self.peers
@@ -109,9 +170,9 @@ impl PeerRateLimiter {
         let removed_count = initial_count - self.peers.len();
         if removed_count > 0 {
             info!(
-                "Rate limiter cleanup: removed {} stale entries, {} remaining",
-                removed_count,
-                self.peers.len()
+                removed = removed_count,
+                remaining = self.peers.len(),
+                "Rate limiter cleanup"
             );
         }
     }
@@ -157,6 +218,14 @@ pub enum BoardSyncRequest {
         timestamp: i64,
         signature: Vec<u8>,
     },
+    ReactToPost {
+        post_id: String,
+        reactor_peer_id: String,
+        reaction_type: String,
+        remove: bool,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
     SubmitWallPost {
         author_peer_id: String,
         post_id: String,
@@ -185,6 +254,25 @@ pub enum BoardSyncRequest {
         timestamp: i64,
         signature: Vec<u8>,
     },
+    CreateBoard {
+        requester_peer_id: String,
+        name: String,
+        description: Option<String>,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
+    DeregisterPeer {
+        peer_id: String,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
+    CreateInvite {
+        requester_peer_id: String,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
 }
 
 /// Board info in responses
@@ -209,6 +297,18 @@ pub struct BoardPostInfoProto {
     pub created_at: i64,
     pub deleted_at: Option<i64>,
     pub signature: Vec<u8>,
+    /// When the relay received the post
+    #[serde(default)]
+    pub relay_observed_at: Option<i64>,
+    /// Relay signature anchoring `relay_observed_at` to this post
+    #[serde(default)]
+    pub relay_signature: Option<Vec<u8>>,
+    /// Number of peers who reacted to the post
+    #[serde(default)]
+    pub reaction_count: u64,
+    /// The requesting peer's own reaction to the post
+    #[serde(default)]
+    pub requester_reaction: Option<String>,
 }
 
 /// Media metadata attached to a wall post
@@ -238,6 +338,9 @@ pub struct WallPostData {
     pub stored_at: i64,
     #[serde(default)]
     pub media_items: Vec<WallPostMediaItemProto>,
+    /// Relay signature anchoring `stored_at` to this post
+    #[serde(default)]
+    pub relay_signature: Option<Vec<u8>>,
 }
 
 /// Board sync response (wire protocol)
@@ -255,6 +358,7 @@ pub enum BoardSyncResponse {
     },
     PostAccepted { post_id: String },
     PeerRegistered { peer_id: String },
+    PeerDeregistered { peer_id: String },
     PostDeleted { post_id: String },
     WallPosts {
         posts: Vec<WallPostData>,
@@ -262,7 +366,45 @@ pub enum BoardSyncResponse {
     },
     WallPostStored { post_id: String },
     WallPostDeleted { post_id: String },
+    BoardCreated { board: BoardInfoProto },
     Error { error: String },
+    ReactionRecorded {
+        post_id: String,
+        reaction_count: u64,
+        requester_reaction: Option<String>,
+    },
+    InviteCreated {
+        token: String,
+    },
+}
+
+impl BoardSyncRequest {
+    /// Wire name of the request, used as a structured log field
+    fn request_type(&self) -> &'static str {
+        match self {
+            BoardSyncRequest::ListBoards { .. } => "list_boards",
+            BoardSyncRequest::GetBoardPosts { .. } => "get_board_posts",
+            BoardSyncRequest::SubmitPost { .. } => "submit_post",
+            BoardSyncRequest::DeletePost { .. } => "delete_post",
+            BoardSyncRequest::ReactToPost { .. } => "react_to_post",
+            BoardSyncRequest::RegisterPeer { .. } => "register_peer",
+            BoardSyncRequest::SubmitWallPost { .. } => "submit_wall_post",
+            BoardSyncRequest::GetWallPosts { .. } => "get_wall_posts",
+            BoardSyncRequest::DeleteWallPost { .. } => "delete_wall_post",
+            BoardSyncRequest::CreateBoard { .. } => "create_board",
+            BoardSyncRequest::DeregisterPeer { .. } => "deregister_peer",
+            BoardSyncRequest::CreateInvite { .. } => "create_invite",
+        }
+    }
+}
+
+/// Log output format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, ValueEnum)]
+enum LogFormat {
+    /// Human-readable lines
+    Text,
+    /// One JSON object per line, for log aggregation
+    Json,
 }
 
 /// Harbor Relay Server - Enables NAT traversal and optionally hosts community boards
@@ -310,8 +452,155 @@ struct Args {
     rate_limit_max_requests: u64,
 
     /// Rate limit window duration in seconds (only used with --community)
-    #[arg(long, default_value_t = DEFAULT_RATE_LIMIT_WINDOW_SECS)]
+    #[arg(
+        long,
+        default_value_t = DEFAULT_RATE_LIMIT_WINDOW_SECS,
+        value_parser = clap::value_parser!(u64).range(1..=MAX_RATE_LIMIT_WINDOW_SECS)
+    )]
     rate_limit_window_secs: u64,
+
+    /// Allow registered peers to create new boards (only used with --community)
+    #[arg(long, default_value_t = false)]
+    allow_peer_boards: bool,
+
+    /// Maximum boards a single peer may create per window (only used with --allow-peer-boards)
+    #[arg(long, default_value_t = DEFAULT_PEER_BOARD_LIMIT)]
+    peer_board_limit: u32,
+
+    /// Peer board creation window in seconds (only used with --allow-peer-boards)
+    #[arg(long, default_value_t = DEFAULT_PEER_BOARD_WINDOW_SECS)]
+    peer_board_window_secs: i64,
+
+    /// Maximum size in bytes of a board or wall post's text; larger posts are rejected (only used with --community)
+    #[arg(long, default_value_t = DEFAULT_MAX_POST_BYTES)]
+    max_post_bytes: usize,
+
+    /// Delete a peer's board and wall posts when they leave the community (only used with --community)
+    #[arg(long, default_value_t = false)]
+    purge_posts_on_leave: bool,
+
+    /// Localhost port for the operator admin socket: list and prune registered peers, adjust the rate limit (only used with --community)
+    #[arg(long, requires = "admin_token_file")]
+    admin_port: Option<u16>,
+
+    /// File holding the token every admin socket request must carry (required with --admin-port)
+    #[arg(long)]
+    admin_token_file: Option<PathBuf>,
+
+    /// Log output format
+    #[arg(long, value_enum, default_value_t = LogFormat::Text)]
+    log_format: LogFormat,
+
+    /// Seconds to keep relaying existing circuits after SIGTERM/SIGINT before exiting
+    #[arg(long, default_value_t = DEFAULT_SHUTDOWN_GRACE_SECS)]
+    shutdown_grace_secs: u64,
+
+    /// Write the database, identity key and a manifest to this directory, then exit
+    #[arg(long, value_name = "DIR", conflicts_with = "import_snapshot")]
+    export_snapshot: Option<String>,
+
+    /// Restore the database and identity key from a snapshot directory, then exit
+    #[arg(long, value_name = "DIR")]
+    import_snapshot: Option<String>,
+
+    /// Remember peer addresses in this file and dial recent peers on startup
+    #[arg(long, value_name = "PATH")]
+    peer_store_path: Option<String>,
+
+    /// Days after which a peer not seen again is dropped from the peer store
+    #[arg(long, default_value_t = DEFAULT_PEER_STORE_MAX_AGE_DAYS)]
+    peer_store_max_age_days: u64,
+
+    /// Print a signed invite to this community, then exit (requires --community)
+    #[arg(long, default_value_t = false)]
+    create_invite: bool,
+
+    /// Public address put in invites, e.g. /dns4/relay.example.com/tcp/4001 (defaults to --announce-ip and --port)
+    #[arg(long, value_name = "MULTIADDR")]
+    invite_address: Option<String>,
+
+    /// Hours until an invite printed by --create-invite expires (never by default)
+    #[arg(long)]
+    invite_expires_hours: Option<u64>,
+
+    /// Number of uses to suggest in an invite printed by --create-invite (a hint, not enforced)
+    #[arg(long)]
+    invite_max_uses: Option<u32>,
+
+    /// Let registered peers request signed invites to share (only used with --community)
+    #[arg(long, default_value_t = false)]
+    allow_member_invites: bool,
+}
+
+impl Args {
+    /// Address invites lead to: `--invite-address`, else the announced TCP address
+    fn invite_address(&self) -> Option<String> {
+        self.invite_address.clone().or_else(|| {
+            self.announce_ip
+                .map(|announce_ip| format!("/ip4/{}/tcp/{}", announce_ip, self.port))
+        })
+    }
+}
+
+/// Reservations and circuits currently held on this relay, so a shutdown can
+/// report what it cuts off and finish early once every circuit has closed
+#[derive(Default)]
+struct RelayActivity {
+    reservations: HashSet<PeerId>,
+    circuits: usize,
+}
+
+impl RelayActivity {
+    fn record(&mut self, event: &relay::Event) {
+        match event {
+            relay::Event::ReservationReqAccepted { src_peer_id, .. } => {
+                self.reservations.insert(*src_peer_id);
+            }
+            relay::Event::ReservationTimedOut { src_peer_id } => {
+                self.reservations.remove(src_peer_id);
+            }
+            relay::Event::CircuitReqAccepted { .. } => self.circuits += 1,
+            relay::Event::CircuitClosed { .. } => {
+                self.circuits = self.circuits.saturating_sub(1);
+            }
+            _ => {}
+        }
+    }
+
+    /// A reservation ends silently with the peer's last connection
+    fn peer_disconnected(&mut self, peer_id: &PeerId) {
+        self.reservations.remove(peer_id);
+    }
+}
+
+/// Resolve on SIGINT (Ctrl-C) or, on Unix, SIGTERM
+async fn shutdown_signal() {
+    let ctrl_c = async {
+        if let Err(signal_error) = tokio::signal::ctrl_c().await {
+            warn!(error = %signal_error, "Failed to listen for Ctrl-C");
+            std::future::pending::<()>().await;
+        }
+    };
+
+    #[cfg(unix)]
+    let terminate = async {
+        match tokio::signal::unix::signal(tokio::signal::unix::SignalKind::terminate()) {
+            Ok(mut sigterm) => {
+                sigterm.recv().await;
+            }
+            Err(signal_error) => {
+                warn!(error = %signal_error, "Failed to listen for SIGTERM");
+                std::future::pending::<()>().await;
+            }
+        }
+    };
+    #[cfg(not(unix))]
+    let terminate = std::future::pending::<()>();
+
+    tokio::select! {
+        _ = ctrl_c => {}
+        _ = terminate => {}
+    }
 }
 
 /// Combined behaviour for the relay server
@@ -350,16 +639,48 @@ fn load_or_generate_identity(path: &str) -> Result<Keypair, Box<dyn std::error::
     Ok(key)
 }
 
+/// Path of the community database, in `--data-dir` or the default config directory
+fn database_path(data_dir: Option<&str>) -> std::io::Result<String> {
+    if let Some(data_dir) = data_dir {
+        fs::create_dir_all(data_dir)?;
+        Ok(format!("{}/relay.db", data_dir))
+    } else {
+        let default_dir = dirs::home_dir()
+            .unwrap_or_else(|| PathBuf::from("."))
+            .join(".config/harbor-relay");
+        fs::create_dir_all(&default_dir)?;
+        Ok(default_dir.join("relay.db").display().to_string())
+    }
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    let args = Args::parse();
+
     // Initialize logging
-    tracing_subscriber::fmt()
-        .with_env_filter(
-            EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info")),
-        )
-        .init();
+    let env_filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    match args.log_format {
+        LogFormat::Text => tracing_subscriber::fmt().with_env_filter(env_filter).init(),
+        LogFormat::Json => tracing_subscriber::fmt()
+            .json()
+            .with_env_filter(env_filter)
+            .with_target(true)
+            .with_file(true)
+            .with_line_number(true)
+            .init(),
+    }
 
-    let args = Args::parse();
+    // Snapshot modes move a relay between hosts and exit without serving
+    if let Some(ref dir) = args.export_snapshot {
+        let db_path = database_path(args.data_dir.as_deref())?;
+        snapshot::export_snapshot(dir, &db_path, &args.identity_key_path, &args.community_name)?;
+        return Ok(());
+    }
+    if let Some(ref dir) = args.import_snapshot {
+        let db_path = database_path(args.data_dir.as_deref())?;
+        snapshot::import_snapshot(dir, &db_path, &args.identity_key_path, &args.community_name)?;
+        return Ok(());
+    }
 
     // Warn if community-only options are used without --community
     if !args.community {
@@ -369,12 +690,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         if args.community_name != "Harbor Community" {
             warn!("--community-name has no effect without --community");
         }
+        if args.allow_peer_boards {
+            warn!("--allow-peer-boards has no effect without --community");
+        }
+        if args.purge_posts_on_leave {
+            warn!("--purge-posts-on-leave has no effect without --community");
+        }
+        if args.max_post_bytes != DEFAULT_MAX_POST_BYTES {
+            warn!("--max-post-bytes has no effect without --community");
+        }
+        if args.admin_port.is_some() {
+            warn!("--admin-port has no effect without --community");
+        }
+        if args.allow_member_invites {
+            warn!("--allow-member-invites has no effect without --community");
+        }
     }
 
     info!("Starting Harbor Relay Server...");
     if args.community {
         info!("Mode: Community (boards + relay)");
-        info!("Community: {}", args.community_name);
+        info!(community_name = %args.community_name, "Community");
     } else {
         info!("Mode: Relay only (NAT traversal pass-through)");
     }
@@ -385,27 +721,84 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let keypair = load_or_generate_identity(&args.identity_key_path)?;
     info!("Using identity key at {}", args.identity_key_path);
 
+    if args.create_invite {
+        if !args.community {
+            return Err("--create-invite requires --community".into());
+        }
+        let address = args
+            .invite_address()
+            .ok_or("--create-invite needs --invite-address or --announce-ip")?;
+        let expires_at = args
+            .invite_expires_hours
+            .map(|hours| chrono::Utc::now().timestamp() + hours as i64 * 60 * 60);
+        let token = invite::create_invite(
+            &keypair,
+            &address,
+            &args.community_name,
+            expires_at,
+            args.invite_max_uses,
+        )?;
+        println!("{}", token);
+        return Ok(());
+    }
+
     // Initialize database and board service only in community mode
     let board_service: Option<BoardService> = if args.community {
-        let db_path = if let Some(ref data_dir) = args.data_dir {
-            fs::create_dir_all(data_dir)?;
-            format!("{}/relay.db", data_dir)
-        } else {
-            let default_dir = dirs::home_dir()
-                .unwrap_or_else(|| PathBuf::from("."))
-                .join(".config/harbor-relay");
-            fs::create_dir_all(&default_dir)?;
-            default_dir.join("relay.db").display().to_string()
-        };
+        let db_path = database_path(args.data_dir.as_deref())?;
 
         let relay_db = RelayDatabase::open(&db_path)?;
-        let service = BoardService::new(relay_db, args.community_name.clone());
+        let peer_board_policy = PeerBoardPolicy {
+            enabled: args.allow_peer_boards,
+            max_boards_per_window: args.peer_board_limit,
+            window_secs: args.peer_board_window_secs,
+        };
+        if peer_board_policy.enabled {
+            info!(
+                "Peer board creation enabled: {} boards per peer per {}s",
+                args.peer_board_limit, args.peer_board_window_secs
+            );
+        }
+        if args.purge_posts_on_leave {
+            info!("Posts of peers leaving the community will be deleted");
+        }
+        let mut service = BoardService::new(
+            relay_db,
+            args.community_name.clone(),
+            peer_board_policy,
+            args.purge_posts_on_leave,
+            keypair.clone(),
+        );
+        service.set_max_post_bytes(args.max_post_bytes);
+        if args.allow_member_invites {
+            match args.invite_address() {
+                Some(address) => {
+                    info!(invite_address = %address, "Members may request invites");
+                    service.allow_member_invites(address);
+                }
+                None => warn!(
+                    "--allow-member-invites needs --invite-address or --announce-ip; member invites stay disabled"
+                ),
+            }
+        }
         info!("Database initialized at {}", db_path);
         Some(service)
     } else {
         None
     };
 
+    // Operator admin socket (community mode only)
+    let mut admin_requests: Option<mpsc::Receiver<AdminCommand>> = match args.admin_port {
+        Some(port) if args.community => {
+            let token_file = args
+                .admin_token_file
+                .as_deref()
+                .expect("clap requires --admin-token-file with --admin-port");
+            let token = admin::load_admin_token(token_file)?;
+            Some(admin::start_admin_listener(port, token).await?)
+        }
+        _ => None,
+    };
+
     // Initialize rate limiter for board sync requests (community mode only)
     let mut rate_limiter: Option<PeerRateLimiter> = if args.community {
         let limiter = PeerRateLimiter::new(
@@ -493,8 +886,11 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let listen_addr_tcp: Multiaddr = format!("/ip4/0.0.0.0/tcp/{}", args.port).parse()?;
     let listen_addr_quic: Multiaddr = format!("/ip4/0.0.0.0/udp/{}/quic-v1", args.port).parse()?;
 
-    swarm.listen_on(listen_addr_tcp.clone())?;
-    swarm.listen_on(listen_addr_quic.clone())?;
+    // Kept so a shutdown can stop accepting new connections
+    let mut listeners = vec![
+        swarm.listen_on(listen_addr_tcp.clone())?,
+        swarm.listen_on(listen_addr_quic.clone())?,
+    ];
 
     info!("Listening on TCP: {}", listen_addr_tcp);
     info!("Listening on QUIC: {}", listen_addr_quic);
@@ -527,6 +923,27 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         info!("========================================");
     }
 
+    // Preload the peers we knew before the restart and reconnect to the most recent ones
+    let mut peer_store = args.peer_store_path.as_ref().map(|path| {
+        let max_age_secs = (args.peer_store_max_age_days * 24 * 60 * 60) as i64;
+        PeerStore::load(path, max_age_secs).unwrap_or_else(|load_error| {
+            warn!(error = %load_error, "Failed to load peer store, starting empty");
+            PeerStore::default()
+        })
+    });
+    if let Some(ref store) = peer_store {
+        for (peer_id, addresses) in store.warm_start_peers(WARM_START_DIAL_LIMIT) {
+            if let Err(dial_error) =
+                swarm.dial(DialOpts::peer_id(peer_id).addresses(addresses).build())
+            {
+                debug!(peer_id = %peer_id, error = %dial_error, "Failed to dial stored peer");
+            }
+        }
+    }
+    let mut peer_store_interval =
+        tokio::time::interval(Duration::from_secs(PEER_STORE_SAVE_INTERVAL_SECS));
+    peer_store_interval.tick().await;
+
     // Periodic cleanup timer for the rate limiter
     let mut cleanup_interval = tokio::time::interval(Duration::from_secs(
         RATE_LIMITER_CLEANUP_INTERVAL_SECS,
@@ -535,27 +952,92 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     // run cleanup at startup.
     cleanup_interval.tick().await;
 
+    let mut activity = RelayActivity::default();
+    let shutdown = shutdown_signal();
+    tokio::pin!(shutdown);
+    // Set once a shutdown signal arrives; existing circuits are relayed until then
+    let mut drain_deadline: Option<tokio::time::Instant> = None;
+
     // Run the event loop
     loop {
         tokio::select! {
+            _ = &mut shutdown => {
+                if drain_deadline.is_some() {
+                    warn!("Second shutdown signal, not waiting for circuits to drain");
+                    break;
+                }
+                info!(
+                    reservations = activity.reservations.len(),
+                    circuits = activity.circuits,
+                    grace_secs = args.shutdown_grace_secs,
+                    "Shutdown requested, no longer accepting connections"
+                );
+                // Without listeners no new peer can connect, let alone reserve
+                for listener in listeners.drain(..) {
+                    swarm.remove_listener(listener);
+                }
+                if activity.circuits == 0 {
+                    break;
+                }
+                drain_deadline = Some(
+                    tokio::time::Instant::now() + Duration::from_secs(args.shutdown_grace_secs),
+                );
+                shutdown.set(shutdown_signal());
+            }
+            _ = tokio::time::sleep_until(drain_deadline.unwrap_or_else(tokio::time::Instant::now)),
+                if drain_deadline.is_some() => {
+                info!(circuits = activity.circuits, "Shutdown grace period over");
+                break;
+            }
             _ = cleanup_interval.tick() => {
                 if let Some(ref mut limiter) = rate_limiter {
                     limiter.cleanup_stale_entries();
                 }
             }
+            _ = peer_store_interval.tick(), if peer_store.is_some() => {
+                save_peer_store(&mut peer_store, args.peer_store_path.as_deref());
+            }
+            Some((request, reply)) = next_admin_request(&mut admin_requests) => {
+                info!(?request, "Admin request");
+                if let (
+                    AdminRequest::SetRateLimit { max_requests, window_secs },
+                    Some(limiter),
+                ) = (&request, rate_limiter.as_mut())
+                {
+                    let _ = reply.send(reconfigure_rate_limit(limiter, *max_requests, *window_secs));
+                } else if let Some(ref service) = board_service {
+                    let _ = reply.send(admin::handle_admin_request(service, request));
+                }
+            }
             event = swarm.select_next_some() => match event {
                 SwarmEvent::NewListenAddr { address, .. } => {
                     info!("Listening on: {}/p2p/{}", address, local_peer_id);
                 }
                 SwarmEvent::Behaviour(RelayServerBehaviourEvent::Relay(event)) => {
                     info!("Relay event: {:?}", event);
+                    activity.record(&event);
+                    if drain_deadline.is_some() {
+                        // A peer already connected can still reserve while we drain.
+                        // It would be cut off shortly anyway, so let it find another relay now.
+                        if let relay::Event::ReservationReqAccepted { src_peer_id, renewed: false, .. } = event {
+                            info!(peer_id = %src_peer_id, "Dropping new reservation during shutdown");
+                            let _ = swarm.disconnect_peer_id(src_peer_id);
+                        }
+                        if activity.circuits == 0 {
+                            info!("All circuits drained");
+                            break;
+                        }
+                    }
                 }
                 SwarmEvent::Behaviour(RelayServerBehaviourEvent::Identify(identify::Event::Received {
                     peer_id,
                     info,
                     ..
                 })) => {
-                    info!("Identified peer {}: {}", peer_id, info.agent_version);
+                    info!(peer_id = %peer_id, agent_version = %info.agent_version, "Identified peer");
+                    if let Some(ref mut store) = peer_store {
+                        store.record(peer_id, &info.listen_addrs);
+                    }
                 }
                 SwarmEvent::Behaviour(RelayServerBehaviourEvent::BoardSync(
                     request_response::Event::Message { peer, message, .. },
@@ -564,13 +1046,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                         request, channel, ..
                     } => {
                         if let Some(ref service) = board_service {
+                            debug!(peer_id = %peer, request_type = request.request_type(), "Board sync request");
                             // Check per-peer rate limit before processing the request
                             let response = if let Some(ref mut limiter) = rate_limiter {
                                 match limiter.check_rate_limit(&peer) {
                                     Ok(()) => handle_board_request(service, &local_peer_id, &peer, request),
-                                    Err(rate_limit_error) => BoardSyncResponse::Error {
-                                        error: rate_limit_error,
-                                    },
+                                    Err(rate_limit_error) => {
+                                        warn!(
+                                            peer_id = %peer,
+                                            request_type = request.request_type(),
+                                            "Board sync request rejected by rate limiter"
+                                        );
+                                        BoardSyncResponse::Error {
+                                            error: rate_limit_error,
+                                        }
+                                    }
                                 }
                             } else {
                                 handle_board_request(service, &local_peer_id, &peer, request)
@@ -583,7 +1073,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                                 .expect("board_sync enabled in community mode")
                                 .send_response(channel, response)
                             {
-                                warn!("Failed to send board sync response: {:?}", send_error);
+                                warn!(peer_id = %peer, error = ?send_error, "Failed to send board sync response");
                             }
                         }
                     }
@@ -592,15 +1082,97 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     }
                 },
                 SwarmEvent::ConnectionEstablished { peer_id, connection_id, endpoint, .. } => {
-                    info!("Connection established with: {} via {:?} ({:?})", peer_id, connection_id, endpoint);
+                    info!(peer_id = %peer_id, ?connection_id, ?endpoint, "Connection established");
                 }
-                SwarmEvent::ConnectionClosed { peer_id, connection_id, cause, endpoint, .. } => {
-                    info!("Connection closed with: {} via {:?} ({:?}), cause: {:?}", peer_id, connection_id, endpoint, cause);
+                SwarmEvent::ConnectionClosed { peer_id, connection_id, cause, endpoint, num_established, .. } => {
+                    info!(peer_id = %peer_id, ?connection_id, ?endpoint, ?cause, "Connection closed");
+                    if num_established == 0 {
+                        activity.peer_disconnected(&peer_id);
+                    }
                 }
                 _ => {}
             }
         }
     }
+
+    info!(
+        reservations = activity.reservations.len(),
+        circuits = activity.circuits,
+        connections = swarm.network_info().num_peers(),
+        "Closing remaining connections"
+    );
+    let peers: Vec<PeerId> = swarm.connected_peers().copied().collect();
+    for peer_id in peers {
+        let _ = swarm.disconnect_peer_id(peer_id);
+    }
+    // Keep polling so the connections actually close instead of being dropped
+    let close_timeout = tokio::time::sleep(CONNECTION_CLOSE_TIMEOUT);
+    tokio::pin!(close_timeout);
+    while swarm.network_info().num_peers() > 0 {
+        tokio::select! {
+            _ = &mut close_timeout => break,
+            _ = swarm.select_next_some() => {}
+        }
+    }
+
+    if let Some(ref service) = board_service {
+        match service.flush() {
+            Ok(()) => info!("Database flushed"),
+            Err(flush_error) => warn!(error = %flush_error, "Database flush failed"),
+        }
+    }
+    save_peer_store(&mut peer_store, args.peer_store_path.as_deref());
+
+    info!("Relay server stopped");
+    Ok(())
+}
+
+/// Write the peer store, if enabled; a failed save is retried at the next interval
+fn save_peer_store(peer_store: &mut Option<PeerStore>, path: Option<&str>) {
+    if let (Some(store), Some(path)) = (peer_store.as_mut(), path) {
+        if let Err(save_error) = store.save(path) {
+            warn!(error = %save_error, "Failed to save peer store");
+        }
+    }
+}
+
+/// Apply an admin rate limit change, keeping whichever values were omitted
+fn reconfigure_rate_limit(
+    limiter: &mut PeerRateLimiter,
+    max_requests: Option<u64>,
+    window_secs: Option<u64>,
+) -> AdminResponse {
+    let max_requests = max_requests.unwrap_or(limiter.max_requests);
+    let window_duration = window_secs.map_or(limiter.window_duration, Duration::from_secs);
+    match limiter.reconfigure(max_requests, window_duration) {
+        Ok((old_max_requests, old_window)) => {
+            info!(
+                old_max_requests,
+                old_window_secs = old_window.as_secs(),
+                new_max_requests = max_requests,
+                new_window_secs = window_duration.as_secs(),
+                "Rate limiter reconfigured"
+            );
+            AdminResponse::RateLimitUpdated {
+                max_requests,
+                window_secs: window_duration.as_secs(),
+            }
+        }
+        Err(error) => {
+            warn!(error = %error, "Rejected rate limiter change");
+            AdminResponse::Error { error }
+        }
+    }
+}
+
+/// Wait for the next admin request, or forever when the admin socket is off
+async fn next_admin_request(
+    admin_requests: &mut Option<mpsc::Receiver<AdminCommand>>,
+) -> Option<AdminCommand> {
+    match admin_requests {
+        Some(receiver) => receiver.recv().await,
+        None => std::future::pending().await,
+    }
 }
 
 fn handle_board_request(
@@ -627,13 +1199,28 @@ fn handle_board_request(
                 Err(e) => BoardSyncResponse::Error { error: e },
             }
         }
+        BoardSyncRequest::DeregisterPeer {
+            peer_id,
+            timestamp,
+            signature,
+        } => {
+            if peer_id != peer.to_string() {
+                return BoardSyncResponse::Error {
+                    error: "peer_id mismatch".to_string(),
+                };
+            }
+            match service.process_deregister_peer(&peer_id, timestamp, &signature) {
+                Ok(()) => BoardSyncResponse::PeerDeregistered { peer_id },
+                Err(e) => BoardSyncResponse::Error { error: e },
+            }
+        }
         BoardSyncRequest::ListBoards {
             requester_peer_id,
             timestamp,
             signature,
         } => match service.process_list_boards(&requester_peer_id, timestamp, &signature) {
             Ok(boards) => {
-                info!("Serving board list for community: {}", service.community_name());
+                info!(peer_id = %peer, community_name = service.community_name(), "Serving board list");
                 BoardSyncResponse::BoardList {
                     boards: boards
                         .into_iter()
@@ -672,6 +1259,10 @@ fn handle_board_request(
                         created_at: p.created_at,
                         deleted_at: p.deleted_at,
                         signature: p.signature,
+                        relay_observed_at: p.relay_observed_at,
+                        relay_signature: p.relay_signature,
+                        reaction_count: p.reaction_count,
+                        requester_reaction: p.requester_reaction,
                     })
                     .collect(),
                 has_more,
@@ -723,6 +1314,35 @@ fn handle_board_request(
                 Err(e) => BoardSyncResponse::Error { error: e },
             }
         }
+        BoardSyncRequest::ReactToPost {
+            post_id,
+            reactor_peer_id,
+            reaction_type,
+            remove,
+            timestamp,
+            signature,
+        } => {
+            if reactor_peer_id != peer.to_string() {
+                return BoardSyncResponse::Error {
+                    error: "reactor_peer_id mismatch".to_string(),
+                };
+            }
+            match service.process_react_to_post(
+                &post_id,
+                &reactor_peer_id,
+                &reaction_type,
+                remove,
+                timestamp,
+                &signature,
+            ) {
+                Ok(reaction_count) => BoardSyncResponse::ReactionRecorded {
+                    post_id,
+                    reaction_count,
+                    requester_reaction: (!remove).then_some(reaction_type),
+                },
+                Err(e) => BoardSyncResponse::Error { error: e },
+            }
+        }
         BoardSyncRequest::SubmitWallPost {
             author_peer_id,
             post_id,
@@ -816,6 +1436,7 @@ fn handle_board_request(
                                     signature: p.signature,
                                     stored_at: p.stored_at,
                                     media_items,
+                                    relay_signature: p.relay_signature,
                                 }
                             })
                             .collect(),
@@ -841,5 +1462,113 @@ fn handle_board_request(
                 Err(e) => BoardSyncResponse::Error { error: e },
             }
         }
+        BoardSyncRequest::CreateBoard {
+            requester_peer_id,
+            name,
+            description,
+            timestamp,
+            signature,
+        } => {
+            if requester_peer_id != peer.to_string() {
+                return BoardSyncResponse::Error {
+                    error: "requester_peer_id mismatch".to_string(),
+                };
+            }
+            match service.process_create_board(
+                &requester_peer_id,
+                &name,
+                description.as_deref(),
+                timestamp,
+                &signature,
+            ) {
+                Ok(board) => BoardSyncResponse::BoardCreated {
+                    board: BoardInfoProto {
+                        board_id: board.board_id,
+                        name: board.name,
+                        description: board.description,
+                        is_default: board.is_default,
+                    },
+                },
+                Err(e) => BoardSyncResponse::Error { error: e },
+            }
+        }
+        BoardSyncRequest::CreateInvite {
+            requester_peer_id,
+            expires_at,
+            max_uses,
+            timestamp,
+            signature,
+        } => {
+            if requester_peer_id != peer.to_string() {
+                return BoardSyncResponse::Error {
+                    error: "requester_peer_id mismatch".to_string(),
+                };
+            }
+            match service.process_create_invite(
+                &requester_peer_id,
+                expires_at,
+                max_uses,
+                timestamp,
+                &signature,
+            ) {
+                Ok(token) => BoardSyncResponse::InviteCreated { token },
+                Err(e) => BoardSyncResponse::Error { error: e },
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_rate_limiter_reconfigure_keeps_tracked_peers() {
+        let peer = PeerId::random();
+        let mut limiter = PeerRateLimiter::new(3, Duration::from_secs(60));
+        for _ in 0..3 {
+            limiter.check_rate_limit(&peer).unwrap();
+        }
+        assert!(limiter.check_rate_limit(&peer).is_err());
+
+        // Loosening the limit lets the peer continue from its current count
+        let previous = limiter.reconfigure(5, Duration::from_secs(600)).unwrap();
+        assert_eq!(previous, (3, Duration::from_secs(60)));
+        limiter.check_rate_limit(&peer).unwrap();
+        limiter.check_rate_limit(&peer).unwrap();
+        assert!(limiter.check_rate_limit(&peer).is_err());
+
+        // Tightening applies to requests already counted in the window
+        limiter.reconfigure(1, Duration::from_secs(600)).unwrap();
+        assert!(limiter.check_rate_limit(&peer).is_err());
+        assert!(limiter.check_rate_limit(&PeerId::random()).is_ok());
+
+        assert!(limiter.reconfigure(0, Duration::from_secs(60)).is_err());
+        assert!(limiter.reconfigure(5, Duration::ZERO).is_err());
+        assert!(limiter
+            .reconfigure(5, Duration::from_secs(MAX_RATE_LIMIT_WINDOW_SECS + 1))
+            .is_err());
+        assert_eq!(limiter.max_requests, 1);
+        assert_eq!(limiter.window_duration, Duration::from_secs(600));
+
+        // A huge window from the admin socket is refused rather than
+        // overflowing the next cleanup
+        assert!(matches!(
+            reconfigure_rate_limit(&mut limiter, None, Some(u64::MAX)),
+            AdminResponse::Error { .. }
+        ));
+        limiter.cleanup_stale_entries();
+    }
+
+    #[test]
+    fn test_shorter_window_ends_current_window_sooner() {
+        let peer = PeerId::random();
+        let mut limiter = PeerRateLimiter::new(1, Duration::from_secs(3600));
+        limiter.check_rate_limit(&peer).unwrap();
+        assert!(limiter.check_rate_limit(&peer).is_err());
+
+        limiter.reconfigure(1, Duration::from_millis(10)).unwrap();
+        std::thread::sleep(Duration::from_millis(20));
+        assert!(limiter.check_rate_limit(&peer).is_ok());
     }
 }
@@ -0,0 +1,151 @@
+//! Disk-backed peer addresses for a warm start
+//!
+//! With `--peer-store-path <file>` the relay remembers the listen addresses
+//! peers report over identify. The file is rewritten periodically and on
+//! shutdown. On startup entries not seen within `--peer-store-max-age-days`
+//! are dropped and the most recently seen peers are dialed, so a restarted
+//! relay reconnects to its regulars instead of waiting for them to return.
+
+use libp2p::multiaddr::Protocol;
+use libp2p::{Multiaddr, PeerId};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use tracing::info;
+
+/// Peer store layout version
+const PEER_STORE_FORMAT_VERSION: u32 = 1;
+
+/// Most addresses kept for a single peer
+const MAX_ADDRESSES_PER_PEER: usize = 8;
+
+/// Most peers written to disk; the least recently seen are dropped first
+const MAX_STORED_PEERS: usize = 1024;
+
+type PeerStoreResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+struct StoredPeer {
+    peer_id: PeerId,
+    addresses: Vec<Multiaddr>,
+    last_seen: i64,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+struct PeerStoreFile {
+    format_version: u32,
+    peers: Vec<StoredPeer>,
+}
+
+/// Known peer addresses, keyed by peer
+#[derive(Debug, Default)]
+pub struct PeerStore {
+    peers: HashMap<PeerId, StoredPeer>,
+    /// Set when something changed since the last save
+    dirty: bool,
+}
+
+impl PeerStore {
+    /// Load the store at `path`, dropping peers not seen for `max_age_secs`.
+    /// A missing file is an empty store.
+    pub fn load(path: &str, max_age_secs: i64) -> PeerStoreResult<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let file: PeerStoreFile = serde_json::from_slice(&fs::read(path)?)?;
+        if file.format_version != PEER_STORE_FORMAT_VERSION {
+            return Err(format!(
+                "Unsupported peer store format {} (expected {})",
+                file.format_version, PEER_STORE_FORMAT_VERSION
+            )
+            .into());
+        }
+
+        let cutoff = chrono::Utc::now().timestamp() - max_age_secs;
+        let total = file.peers.len();
+        let peers: HashMap<PeerId, StoredPeer> = file
+            .peers
+            .into_iter()
+            .filter(|peer| peer.last_seen >= cutoff && !peer.addresses.is_empty())
+            .map(|peer| (peer.peer_id, peer))
+            .collect();
+        info!(
+            peers = peers.len(),
+            expired = total - peers.len(),
+            "Loaded peer store from {}",
+            path
+        );
+        Ok(Self {
+            dirty: peers.len() != total,
+            peers,
+        })
+    }
+
+    /// Remember the dialable addresses a peer reported
+    pub fn record(&mut self, peer_id: PeerId, addresses: &[Multiaddr]) {
+        let mut addresses: Vec<Multiaddr> = addresses
+            .iter()
+            .filter(|address| is_dialable(address))
+            .cloned()
+            .collect();
+        if addresses.is_empty() {
+            return;
+        }
+        addresses.truncate(MAX_ADDRESSES_PER_PEER);
+        self.peers.insert(
+            peer_id,
+            StoredPeer {
+                peer_id,
+                addresses,
+                last_seen: chrono::Utc::now().timestamp(),
+            },
+        );
+        self.dirty = true;
+    }
+
+    /// The `limit` most recently seen peers and their addresses
+    pub fn warm_start_peers(&self, limit: usize) -> Vec<(PeerId, Vec<Multiaddr>)> {
+        let mut peers: Vec<&StoredPeer> = self.peers.values().collect();
+        peers.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        peers
+            .into_iter()
+            .take(limit)
+            .map(|peer| (peer.peer_id, peer.addresses.clone()))
+            .collect()
+    }
+
+    /// Write the store to `path` if anything changed since the last save
+    pub fn save(&mut self, path: &str) -> PeerStoreResult<()> {
+        if !self.dirty {
+            return Ok(());
+        }
+        let mut peers: Vec<StoredPeer> = self.peers.values().cloned().collect();
+        peers.sort_by(|a, b| b.last_seen.cmp(&a.last_seen));
+        peers.truncate(MAX_STORED_PEERS);
+        let file = PeerStoreFile {
+            format_version: PEER_STORE_FORMAT_VERSION,
+            peers,
+        };
+
+        // Write beside the store and rename so a crash never leaves half a file
+        let path = Path::new(path);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = path.with_extension("saving");
+        fs::write(&partial, serde_json::to_vec_pretty(&file)?)?;
+        fs::rename(&partial, path)?;
+        self.dirty = false;
+        Ok(())
+    }
+}
+
+/// Addresses worth dialing from elsewhere: not relayed, loopback or unspecified
+fn is_dialable(address: &Multiaddr) -> bool {
+    address.iter().all(|protocol| match protocol {
+        Protocol::P2pCircuit => false,
+        Protocol::Ip4(ip) => !ip.is_loopback() && !ip.is_unspecified(),
+        Protocol::Ip6(ip) => !ip.is_loopback() && !ip.is_unspecified(),
+        _ => true,
+    })
+}
@@ -0,0 +1,182 @@
+//! Snapshots for moving a community relay to new hardware
+//!
+//! `--export-snapshot <dir>` writes everything a relay needs to come back up
+//! elsewhere as the same peer:
+//!
+//! ```text
+//! <dir>/relay.db       online backup of the community database
+//! <dir>/id.key         identity key, so the peer ID and saved addresses survive
+//! <dir>/manifest.json  community name, relay version, schema version, peer ID
+//! ```
+//!
+//! `--import-snapshot <dir>` checks the manifest against the key and database
+//! and restores both to the paths the relay would use with the same flags.
+
+use crate::db::{RelayDatabase, SCHEMA_VERSION};
+use libp2p::identity::Keypair;
+use std::fs;
+use std::path::Path;
+use tracing::{info, warn};
+
+/// Snapshot layout version written to the manifest
+const SNAPSHOT_FORMAT_VERSION: u32 = 1;
+
+const MANIFEST_FILE: &str = "manifest.json";
+const DATABASE_FILE: &str = "relay.db";
+const IDENTITY_FILE: &str = "id.key";
+
+type SnapshotResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// Describes a snapshot and is checked against its contents on import
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SnapshotManifest {
+    pub format_version: u32,
+    pub community_name: String,
+    /// Relay server version that wrote the snapshot
+    pub relay_version: String,
+    pub schema_version: u32,
+    pub peer_id: String,
+    pub created_at: i64,
+}
+
+/// Write a snapshot of the relay's database and identity key into `dir`
+pub fn export_snapshot(
+    dir: &str,
+    db_path: &str,
+    identity_key_path: &str,
+    community_name: &str,
+) -> SnapshotResult<SnapshotManifest> {
+    let dir = Path::new(dir);
+    if !Path::new(identity_key_path).exists() {
+        return Err(format!("No identity key at {}", identity_key_path).into());
+    }
+    if !Path::new(db_path).exists() {
+        return Err(format!("No relay database at {}", db_path).into());
+    }
+    if dir.join(MANIFEST_FILE).exists() || dir.join(DATABASE_FILE).exists() {
+        return Err(format!("{} already contains a snapshot", dir.display()).into());
+    }
+    fs::create_dir_all(dir)?;
+
+    let key_bytes = fs::read(identity_key_path)?;
+    let peer_id = Keypair::from_protobuf_encoding(&key_bytes)?
+        .public()
+        .to_peer_id();
+
+    // The backup is consistent even if a running relay writes meanwhile
+    let snapshot_db = dir.join(DATABASE_FILE);
+    RelayDatabase::open(db_path)?.backup_to(&snapshot_db.display().to_string())?;
+    fs::write(dir.join(IDENTITY_FILE), &key_bytes)?;
+
+    let manifest = SnapshotManifest {
+        format_version: SNAPSHOT_FORMAT_VERSION,
+        community_name: community_name.to_string(),
+        relay_version: env!("CARGO_PKG_VERSION").to_string(),
+        schema_version: SCHEMA_VERSION,
+        peer_id: peer_id.to_string(),
+        created_at: chrono::Utc::now().timestamp(),
+    };
+    fs::write(
+        dir.join(MANIFEST_FILE),
+        serde_json::to_vec_pretty(&manifest)?,
+    )?;
+
+    info!(
+        peer_id = %manifest.peer_id,
+        "Exported relay snapshot to {}",
+        dir.display()
+    );
+    Ok(manifest)
+}
+
+/// Restore the database and identity key from the snapshot in `dir`.
+/// Refuses to replace an existing database or identity key.
+pub fn import_snapshot(
+    dir: &str,
+    db_path: &str,
+    identity_key_path: &str,
+    community_name: &str,
+) -> SnapshotResult<SnapshotManifest> {
+    let dir = Path::new(dir);
+    let manifest: SnapshotManifest = serde_json::from_slice(&fs::read(dir.join(MANIFEST_FILE))?)?;
+    if manifest.format_version != SNAPSHOT_FORMAT_VERSION {
+        return Err(format!(
+            "Unsupported snapshot format {} (expected {})",
+            manifest.format_version, SNAPSHOT_FORMAT_VERSION
+        )
+        .into());
+    }
+
+    // The key must be the identity the manifest names
+    let snapshot_key = dir.join(IDENTITY_FILE);
+    let key_bytes = fs::read(&snapshot_key)?;
+    let peer_id = Keypair::from_protobuf_encoding(&key_bytes)?
+        .public()
+        .to_peer_id();
+    if peer_id.to_string() != manifest.peer_id {
+        return Err(format!(
+            "Snapshot identity key is for {} but the manifest names {}",
+            peer_id, manifest.peer_id
+        )
+        .into());
+    }
+
+    let snapshot_db = dir.join(DATABASE_FILE);
+    let (schema_version, integrity) =
+        RelayDatabase::inspect_file(&snapshot_db.display().to_string())?;
+    if integrity != "ok" {
+        return Err(format!("Snapshot database is damaged: {}", integrity).into());
+    }
+    if schema_version != manifest.schema_version {
+        return Err(format!(
+            "Snapshot database has schema version {} but the manifest says {}",
+            schema_version, manifest.schema_version
+        )
+        .into());
+    }
+    if schema_version > SCHEMA_VERSION {
+        return Err(format!(
+            "Snapshot schema version {} is newer than this relay supports ({}); upgrade the relay first",
+            schema_version, SCHEMA_VERSION
+        )
+        .into());
+    }
+
+    for existing in [db_path, identity_key_path] {
+        if Path::new(existing).exists() {
+            return Err(format!(
+                "{} already exists; move it aside before importing",
+                existing
+            )
+            .into());
+        }
+    }
+
+    // Copy next to the destination first so a failed copy leaves nothing behind
+    for (from, to) in [
+        (snapshot_db.as_path(), Path::new(db_path)),
+        (snapshot_key.as_path(), Path::new(identity_key_path)),
+    ] {
+        if let Some(parent) = to.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let partial = to.with_extension("importing");
+        fs::copy(from, &partial)?;
+        fs::rename(&partial, to)?;
+    }
+
+    if manifest.community_name != community_name {
+        warn!(
+            "Snapshot is for community {:?}; start the relay with --community-name {:?} to keep it",
+            manifest.community_name, manifest.community_name
+        );
+    }
+    info!(
+        peer_id = %manifest.peer_id,
+        schema_version,
+        relay_version = %manifest.relay_version,
+        "Imported relay snapshot from {}",
+        dir.display()
+    );
+    Ok(manifest)
+}
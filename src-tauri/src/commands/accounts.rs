@@ -64,6 +64,7 @@ pub async fn update_account_metadata(
     display_name: Option<String>,
     bio: Option<Option<String>>,
     avatar_hash: Option<Option<String>>,
+    accent_color: Option<Option<String>>,
 ) -> Result<AccountInfo, AppError> {
-    accounts_service.update_account(&account_id, display_name, bio, avatar_hash)
+    accounts_service.update_account(&account_id, display_name, bio, avatar_hash, accent_color)
 }
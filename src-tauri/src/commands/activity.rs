@@ -0,0 +1,37 @@
+//! Tauri commands for the activity feed (likes, reactions, comments and views)
+
+use crate::db::repositories::{ActivityItem, ActivityRepository};
+use crate::db::Database;
+use crate::error::{AppError, Result};
+use std::sync::Arc;
+use tauri::State;
+
+/// Get activity on our content, newest first. Pass the `created_at` of the
+/// oldest entry already shown as `before` to page further back.
+#[tauri::command]
+pub async fn get_activity(
+    db: State<'_, Arc<Database>>,
+    limit: Option<i64>,
+    before: Option<i64>,
+) -> Result<Vec<ActivityItem>> {
+    ActivityRepository::get_activity(&db, limit.unwrap_or(50), before)
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+}
+
+/// Mark activity entries as read, or all of them when `ids` is omitted.
+/// Returns how many entries were newly marked.
+#[tauri::command]
+pub async fn mark_activity_read(
+    db: State<'_, Arc<Database>>,
+    ids: Option<Vec<i64>>,
+) -> Result<usize> {
+    let now = chrono::Utc::now().timestamp();
+    ActivityRepository::mark_read(&db, ids.as_deref(), now)
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+}
+
+/// Get the number of unread activity entries
+#[tauri::command]
+pub async fn get_unread_activity_count(db: State<'_, Arc<Database>>) -> Result<i64> {
+    ActivityRepository::get_unread_count(&db).map_err(|e| AppError::DatabaseString(e.to_string()))
+}
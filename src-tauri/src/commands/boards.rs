@@ -6,7 +6,7 @@ use tauri::State;
 
 use crate::commands::NetworkState;
 use crate::error::AppError;
-use crate::services::BoardService;
+use crate::services::{BoardService, CommunityInviteToken};
 
 /// Community info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -28,6 +28,7 @@ pub struct BoardInfoFe {
     pub name: String,
     pub description: Option<String>,
     pub is_default: bool,
+    pub is_subscribed: bool,
 }
 
 /// Board post info for the frontend
@@ -43,6 +44,24 @@ pub struct BoardPostInfoFe {
     pub content_text: Option<String>,
     pub lamport_clock: i64,
     pub created_at: i64,
+    /// When the relay received the post, if it signed a timestamp
+    pub relay_observed_at: Option<i64>,
+    pub reaction_count: i64,
+    /// Our own reaction to the post
+    pub my_reaction: Option<String>,
+}
+
+/// A verified community invite for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CommunityInviteInfo {
+    pub relay_peer_id: String,
+    pub relay_address: String,
+    pub community_name: String,
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,
+    /// Suggested number of uses; relays don't count them
+    pub max_uses: Option<u32>,
 }
 
 /// Get all joined communities
@@ -94,13 +113,98 @@ pub async fn join_community(
     handle.join_community(relay_peer_id, relay_address).await
 }
 
-/// Leave a community
+/// Join a community from a signed invite (`harbor://invite/...`).
+///
+/// The invite must be signed by the relay it points at and not have expired.
+#[tauri::command]
+pub async fn join_community_from_invite(
+    network_state: State<'_, NetworkState>,
+    board_service: State<'_, Arc<BoardService>>,
+    token: String,
+) -> Result<CommunityInviteInfo, AppError> {
+    let handle = network_state.get_handle().await?;
+
+    let invite_token = CommunityInviteToken::decode(&token)?;
+    let invite = invite_token.verify(chrono::Utc::now().timestamp())?;
+
+    let addr: libp2p::Multiaddr = invite
+        .relay_address
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid address: {}", e)))?;
+    let relay_peer_id: libp2p::PeerId = invite
+        .relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+
+    // Store the signed community name now; the relay's own name replaces it on sync
+    board_service.join_community(
+        &invite.relay_peer_id,
+        &invite.relay_address,
+        Some(&invite.community_name),
+    )?;
+
+    handle.dial(relay_peer_id, vec![addr]).await.ok();
+    handle
+        .join_community(relay_peer_id, invite.relay_address.clone())
+        .await?;
+
+    Ok(CommunityInviteInfo {
+        relay_peer_id: invite.relay_peer_id.clone(),
+        relay_address: invite.relay_address.clone(),
+        community_name: invite.community_name.clone(),
+        issued_at: invite.issued_at,
+        expires_at: invite.expires_at,
+        max_uses: invite.max_uses,
+    })
+}
+
+/// Ask a community relay for an invite to share.
+///
+/// Only relays started with `--allow-member-invites` issue these; the
+/// invite arrives as a `community_invite_created` or `board_sync_error` event.
+#[tauri::command]
+pub async fn create_community_invite(
+    network_state: State<'_, NetworkState>,
+    relay_peer_id: String,
+    expires_in_secs: Option<i64>,
+    max_uses: Option<u32>,
+) -> Result<(), AppError> {
+    let handle = network_state.get_handle().await?;
+
+    let peer_id: libp2p::PeerId = relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+    if expires_in_secs.is_some_and(|secs| secs <= 0) {
+        return Err(AppError::Validation(
+            "Invite expiry must be in the future".to_string(),
+        ));
+    }
+    let expires_at = expires_in_secs.map(|secs| chrono::Utc::now().timestamp() + secs);
+
+    handle
+        .create_community_invite(peer_id, expires_at, max_uses)
+        .await
+}
+
+/// Leave a community.
+///
+/// With the network running this also asks the relay to drop our
+/// registration; otherwise the community is only removed locally.
 #[tauri::command]
 pub async fn leave_community(
+    network_state: State<'_, NetworkState>,
     board_service: State<'_, Arc<BoardService>>,
     relay_peer_id: String,
 ) -> Result<(), AppError> {
-    board_service.leave_community(&relay_peer_id)
+    let Ok(handle) = network_state.get_handle().await else {
+        return board_service.leave_community(&relay_peer_id);
+    };
+
+    let peer_id: libp2p::PeerId = relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+
+    handle.leave_community(peer_id).await
 }
 
 /// Get boards for a community (from local cache)
@@ -110,14 +214,21 @@ pub async fn get_boards(
     relay_peer_id: String,
 ) -> Result<Vec<BoardInfoFe>, AppError> {
     let boards = board_service.get_boards(&relay_peer_id)?;
+    let subscriptions = board_service.get_subscriptions()?;
     Ok(boards
         .into_iter()
-        .map(|b| BoardInfoFe {
-            board_id: b.board_id,
-            relay_peer_id: b.relay_peer_id,
-            name: b.name,
-            description: b.description,
-            is_default: b.is_default,
+        .map(|b| {
+            let is_subscribed = subscriptions
+                .iter()
+                .any(|s| s.relay_peer_id == b.relay_peer_id && s.board_id == b.board_id);
+            BoardInfoFe {
+                board_id: b.board_id,
+                relay_peer_id: b.relay_peer_id,
+                name: b.name,
+                description: b.description,
+                is_default: b.is_default,
+                is_subscribed,
+            }
         })
         .collect())
 }
@@ -146,6 +257,9 @@ pub async fn get_board_posts(
             content_text: p.content_text,
             lamport_clock: p.lamport_clock,
             created_at: p.created_at,
+            relay_observed_at: p.relay_observed_at,
+            reaction_count: p.reaction_count,
+            my_reaction: p.my_reaction,
         })
         .collect())
 }
@@ -169,6 +283,26 @@ pub async fn submit_board_post(
         .await
 }
 
+/// Ask a relay to create a new board.
+///
+/// Only relays started with `--allow-peer-boards` accept this; the result
+/// arrives as a `board_created` or `board_sync_error` event.
+#[tauri::command]
+pub async fn create_board(
+    network_state: State<'_, NetworkState>,
+    relay_peer_id: String,
+    name: String,
+    description: Option<String>,
+) -> Result<(), AppError> {
+    let handle = network_state.get_handle().await?;
+
+    let peer_id: libp2p::PeerId = relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+
+    handle.create_board(peer_id, name, description).await
+}
+
 /// Delete a board post on a relay
 #[tauri::command]
 pub async fn delete_board_post(
@@ -185,6 +319,34 @@ pub async fn delete_board_post(
     handle.delete_board_post(peer_id, post_id).await
 }
 
+/// React to a board post on a relay, or withdraw our reaction.
+///
+/// The reaction type defaults to "like". The relay's updated count arrives
+/// as a `board_post_reactions_updated` event.
+#[tauri::command]
+pub async fn react_to_board_post(
+    network_state: State<'_, NetworkState>,
+    relay_peer_id: String,
+    post_id: String,
+    reaction_type: Option<String>,
+    remove: bool,
+) -> Result<(), AppError> {
+    let handle = network_state.get_handle().await?;
+
+    let peer_id: libp2p::PeerId = relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+
+    handle
+        .react_to_board_post(
+            peer_id,
+            post_id,
+            reaction_type.unwrap_or_else(|| "like".to_string()),
+            remove,
+        )
+        .await
+}
+
 /// Sync a board (fetch latest posts from relay)
 #[tauri::command]
 pub async fn sync_board(
@@ -201,3 +363,23 @@ pub async fn sync_board(
     // Use list_boards as a simple way to trigger sync — actually use get_board_posts
     handle.get_board_posts(peer_id, board_id, None, 50).await
 }
+
+/// Subscribe to a board to be notified of new posts
+#[tauri::command]
+pub async fn subscribe_board(
+    board_service: State<'_, Arc<BoardService>>,
+    relay_peer_id: String,
+    board_id: String,
+) -> Result<(), AppError> {
+    board_service.subscribe_board(&relay_peer_id, &board_id)
+}
+
+/// Unsubscribe from a board
+#[tauri::command]
+pub async fn unsubscribe_board(
+    board_service: State<'_, Arc<BoardService>>,
+    relay_peer_id: String,
+    board_id: String,
+) -> Result<(), AppError> {
+    board_service.unsubscribe_board(&relay_peer_id, &board_id)
+}
@@ -1,10 +1,14 @@
 //! Tauri commands for voice calling
 
+use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 use tauri::State;
 
+use crate::commands::network::NetworkState;
 use crate::error::AppError;
+use crate::p2p::protocols::call_data::CallDataFrame;
 use crate::services::calling_service::IncomingIceParams;
 use crate::services::CallingService;
 
@@ -141,6 +145,34 @@ pub async fn hangup_call(
     })
 }
 
+/// Send text or a small file over a connected call's data channel
+#[tauri::command]
+pub async fn send_call_data(
+    calling_service: State<'_, Arc<CallingService>>,
+    network: State<'_, NetworkState>,
+    call_id: String,
+    data: Vec<u8>,
+) -> Result<(), AppError> {
+    let outgoing = calling_service.create_call_data(&call_id, &data)?;
+
+    let peer_id = PeerId::from_str(&outgoing.recipient_peer_id)
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
+
+    let handle = network.get_handle().await?;
+    handle
+        .send_call_data(
+            peer_id,
+            CallDataFrame {
+                call_id: outgoing.call_id,
+                sender_peer_id: outgoing.sender_peer_id,
+                payload: outgoing.payload,
+                timestamp: outgoing.timestamp,
+                signature: outgoing.signature,
+            },
+        )
+        .await
+}
+
 /// Process an incoming offer (validate it)
 #[tauri::command]
 pub async fn process_offer(
@@ -2,14 +2,19 @@
 
 use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
 use std::str::FromStr;
 use std::sync::Arc;
 use tauri::State;
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::commands::network::NetworkState;
+use crate::db::{Capability, ContactGroup, ContactNameChange};
 use crate::error::AppError;
-use crate::services::ContactsService;
+use crate::services::{
+    ContactSuggestion, ContactsService, MediaStorageService, PermissionsService, SharedContact,
+    StaleReason,
+};
 
 /// Contact info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +31,21 @@ pub struct ContactInfo {
     pub added_at: i64,
 }
 
+/// A contact suggested for cleanup, with why it is considered stale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StaleContactInfo {
+    pub peer_id: String,
+    pub display_name: String,
+    pub avatar_hash: Option<String>,
+    pub is_blocked: bool,
+    pub last_seen_at: Option<i64>,
+    pub last_message_at: Option<i64>,
+    pub last_post_at: Option<i64>,
+    pub added_at: i64,
+    pub reasons: Vec<StaleReason>,
+}
+
 /// Get all contacts
 #[tauri::command]
 pub async fn get_contacts(
@@ -101,14 +121,16 @@ pub async fn add_contact(
     avatar_hash: Option<String>,
     bio: Option<String>,
 ) -> Result<i64, AppError> {
-    contacts_service.add_contact(
-        &peer_id,
-        &public_key,
-        &x25519_public,
-        &display_name,
-        avatar_hash.as_deref(),
-        bio.as_deref(),
-    )
+    contacts_service
+        .add_contact(
+            &peer_id,
+            &public_key,
+            &x25519_public,
+            &display_name,
+            avatar_hash.as_deref(),
+            bio.as_deref(),
+        )
+        .map(|upsert| upsert.contact_id)
 }
 
 /// Block a contact
@@ -138,6 +160,29 @@ pub async fn remove_contact(
     contacts_service.remove_contact(&peer_id)
 }
 
+/// Forget a contact: delete them and everything stored about them, then any
+/// media only they were using. Returns the number of rows deleted.
+#[tauri::command]
+pub async fn forget_contact(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    media_service: State<'_, Arc<MediaStorageService>>,
+    peer_id: String,
+) -> Result<usize, AppError> {
+    let forgotten = contacts_service.forget_contact(&peer_id)?;
+    for hash in &forgotten.media_hashes {
+        if let Err(e) = media_service.delete_media_if_orphaned(hash) {
+            warn!("Failed to delete media {} of forgotten peer: {}", hash, e);
+        }
+    }
+    info!(
+        "Forgot peer {} ({} rows, {} media files)",
+        peer_id,
+        forgotten.rows_deleted,
+        forgotten.media_hashes.len()
+    );
+    Ok(forgotten.rows_deleted)
+}
+
 /// Check if a peer is a contact
 #[tauri::command]
 pub async fn is_contact(
@@ -156,6 +201,109 @@ pub async fn is_contact_blocked(
     contacts_service.is_blocked(&peer_id)
 }
 
+/// List contacts with no activity in the last `inactive_days` days.
+///
+/// Read-only; nothing is removed.
+#[tauri::command]
+pub async fn get_stale_contacts(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    inactive_days: u32,
+) -> Result<Vec<StaleContactInfo>, AppError> {
+    let stale = contacts_service.get_stale_contacts(inactive_days)?;
+    Ok(stale
+        .into_iter()
+        .map(|s| StaleContactInfo {
+            peer_id: s.contact.peer_id,
+            display_name: s.contact.display_name,
+            avatar_hash: s.contact.avatar_hash,
+            is_blocked: s.contact.is_blocked,
+            last_seen_at: s.contact.last_seen_at,
+            last_message_at: s.last_message_at,
+            last_post_at: s.last_post_at,
+            added_at: s.contact.added_at,
+            reasons: s.reasons,
+        })
+        .collect())
+}
+
+/// Get every display name observed for a peer, oldest first
+#[tauri::command]
+pub async fn get_contact_name_history(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    peer_id: String,
+) -> Result<Vec<ContactNameChange>, AppError> {
+    contacts_service.get_name_history(&peer_id)
+}
+
+/// Create a contact group
+#[tauri::command]
+pub async fn create_contact_group(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    name: String,
+) -> Result<ContactGroup, AppError> {
+    contacts_service.create_group(&name)
+}
+
+/// Delete a contact group. Its members stay in the contact list.
+#[tauri::command]
+pub async fn delete_contact_group(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    group_id: String,
+) -> Result<bool, AppError> {
+    contacts_service.delete_group(&group_id)
+}
+
+/// Get all contact groups
+#[tauri::command]
+pub async fn get_contact_groups(
+    contacts_service: State<'_, Arc<ContactsService>>,
+) -> Result<Vec<ContactGroup>, AppError> {
+    contacts_service.get_groups()
+}
+
+/// Add a contact to a group
+#[tauri::command]
+pub async fn add_contact_to_group(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    group_id: String,
+    peer_id: String,
+) -> Result<bool, AppError> {
+    contacts_service.add_contact_to_group(&group_id, &peer_id)
+}
+
+/// Remove a contact from a group
+#[tauri::command]
+pub async fn remove_contact_from_group(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    group_id: String,
+    peer_id: String,
+) -> Result<bool, AppError> {
+    contacts_service.remove_contact_from_group(&group_id, &peer_id)
+}
+
+/// Get the contacts in a group
+#[tauri::command]
+pub async fn get_contact_group_members(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    group_id: String,
+) -> Result<Vec<ContactInfo>, AppError> {
+    let contacts = contacts_service.get_group_members(&group_id)?;
+    Ok(contacts
+        .into_iter()
+        .map(|c| ContactInfo {
+            id: c.id,
+            peer_id: c.peer_id,
+            display_name: c.display_name,
+            avatar_hash: c.avatar_hash,
+            bio: c.bio,
+            is_blocked: c.is_blocked,
+            trust_level: c.trust_level,
+            last_seen_at: c.last_seen_at,
+            added_at: c.added_at,
+        })
+        .collect())
+}
+
 /// Request identity exchange with a peer (adds them as a contact)
 #[tauri::command]
 pub async fn request_peer_identity(
@@ -171,3 +319,65 @@ pub async fn request_peer_identity(
     info!("Requested identity from peer {}", peer_id);
     Ok(())
 }
+
+/// Suggest peers known to our connected contacts but not to us.
+///
+/// Only contacts that granted us `ShareContacts` are asked, and each decides
+/// whether to answer. Suggestions are never added as contacts.
+#[tauri::command]
+pub async fn get_contact_suggestions(
+    network: State<'_, NetworkState>,
+    contacts_service: State<'_, Arc<ContactsService>>,
+    permissions_service: State<'_, Arc<PermissionsService>>,
+) -> Result<Vec<ContactSuggestion>, AppError> {
+    let handle = network.get_handle().await?;
+    let connected: HashSet<String> = handle
+        .get_connected_peers()
+        .await?
+        .into_iter()
+        .map(|p| p.peer_id)
+        .collect();
+
+    let mut shared_lists = Vec::new();
+    for contact in contacts_service.get_active_contacts()? {
+        if !connected.contains(&contact.peer_id)
+            || !permissions_service
+                .we_have_capability(&contact.peer_id, Capability::ShareContacts)?
+        {
+            continue;
+        }
+        let Ok(libp2p_peer_id) = PeerId::from_str(&contact.peer_id) else {
+            continue;
+        };
+
+        match handle.fetch_shared_contacts(libp2p_peer_id).await {
+            Ok(shared) => shared_lists.push((
+                SharedContact {
+                    peer_id: contact.peer_id,
+                    display_name: contact.display_name,
+                },
+                shared,
+            )),
+            Err(e) => warn!("No contact list from {}: {}", contact.peer_id, e),
+        }
+    }
+
+    contacts_service.suggest_contacts(shared_lists)
+}
+
+/// Whether contacts granted `ShareContacts` may fetch our contact list
+#[tauri::command]
+pub async fn get_share_contact_list(
+    contacts_service: State<'_, Arc<ContactsService>>,
+) -> Result<bool, AppError> {
+    contacts_service.get_share_contact_list()
+}
+
+/// Turn sharing our contact list on or off
+#[tauri::command]
+pub async fn set_share_contact_list(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    contacts_service.set_share_contact_list(enabled)
+}
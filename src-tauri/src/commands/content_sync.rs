@@ -1,13 +1,30 @@
 //! Tauri commands for content synchronization
 
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
-use tauri::State;
+use tauri::{AppHandle, Emitter, State};
 
 use super::NetworkState;
+use crate::db::repositories::settings_repo::SETTING_MIN_SIGNATURE_VERSION;
+use crate::db::repositories::SettingsRepository;
+use crate::db::{Database, PostDelivery, PostViewSummary, RelayCommunity};
 use crate::error::AppError;
-use crate::services::ContentSyncService;
+use crate::p2p::NetworkHandle;
+use crate::services::{
+    BoardService, ContactsService, ContentSyncService, ManifestDetailLevel, PostViewSettings,
+    SignaturePolicy, CURRENT_SIG_VERSION, SIG_VERSION_CBOR,
+};
+
+/// Most manifest rounds a resync runs against one peer; whatever is left is
+/// picked up by the regular sync
+const MAX_RESYNC_ROUNDS: usize = 20;
+
+/// Manifest page size used while resyncing
+const RESYNC_MANIFEST_LIMIT: u32 = 200;
+
+/// Board posts requested per board while resyncing
+const RESYNC_BOARD_POST_LIMIT: u32 = 50;
 
 /// Content sync status for frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -19,12 +36,57 @@ pub struct SyncStatus {
     pub status: String,
 }
 
+/// Which part of a resync a progress event belongs to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ResyncStage {
+    Contacts,
+    Communities,
+    Done,
+}
+
+/// Progress of `resync_all`, emitted as `harbor:resync` after each contact
+/// or community is handled and once more when it finishes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncProgress {
+    pub stage: ResyncStage,
+    /// Contacts or communities handled so far in this stage
+    pub completed: usize,
+    pub total: usize,
+    /// Contact or relay just handled
+    pub peer_id: Option<String>,
+    /// Posts fetched from the contact just handled
+    pub posts_fetched: usize,
+    pub error: Option<String>,
+}
+
+/// What `resync_all` did
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ResyncSummary {
+    /// Inbound content sync cursor entries forgotten
+    pub cursors_cleared: usize,
+    /// Board sync cursors forgotten
+    pub board_cursors_cleared: usize,
+    /// Online contacts synced without errors
+    pub peers_synced: usize,
+    pub posts_fetched: usize,
+    pub communities_rejoined: usize,
+    /// One entry per contact or community that failed
+    pub errors: Vec<String>,
+}
+
 /// Request content manifest from a connected peer
+///
+/// `detail_level` defaults to full post summaries; pass `headers_only` to
+/// receive just post IDs and lamport clocks.
 #[tauri::command]
 pub async fn request_content_manifest(
     network_state: State<'_, NetworkState>,
     peer_id: String,
     limit: Option<u32>,
+    detail_level: Option<ManifestDetailLevel>,
 ) -> Result<(), AppError> {
     let handle = network_state.get_handle().await?;
     let peer_id = peer_id
@@ -35,7 +97,7 @@ pub async fn request_content_manifest(
     let limit = limit.unwrap_or(50);
 
     handle
-        .request_content_manifest(peer_id, cursor, limit)
+        .request_content_manifest(peer_id, cursor, limit, detail_level.unwrap_or_default())
         .await
 }
 
@@ -46,6 +108,7 @@ pub async fn request_content_manifest_with_cursor(
     peer_id: String,
     cursor: HashMap<String, u64>,
     limit: Option<u32>,
+    detail_level: Option<ManifestDetailLevel>,
 ) -> Result<(), AppError> {
     let handle = network_state.get_handle().await?;
     let peer_id = peer_id
@@ -55,7 +118,7 @@ pub async fn request_content_manifest_with_cursor(
     let limit = limit.unwrap_or(50);
 
     handle
-        .request_content_manifest(peer_id, cursor, limit)
+        .request_content_manifest(peer_id, cursor, limit, detail_level.unwrap_or_default())
         .await
 }
 
@@ -88,13 +151,260 @@ pub async fn get_sync_cursor(
     content_sync_service.get_sync_cursor(&peer_id)
 }
 
+/// Get the view count and viewers of one of our posts
+#[tauri::command]
+pub async fn get_post_views(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    post_id: String,
+) -> Result<PostViewSummary, AppError> {
+    content_sync_service.get_post_views(&post_id)
+}
+
+/// Get which online contacts one of our posts was pushed to, and whether
+/// each push arrived
+#[tauri::command]
+pub async fn get_post_delivery_status(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    post_id: String,
+) -> Result<Vec<PostDelivery>, AppError> {
+    content_sync_service.get_post_delivery_status(&post_id)
+}
+
+/// Get the post view privacy settings
+#[tauri::command]
+pub async fn get_post_view_settings(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+) -> Result<PostViewSettings, AppError> {
+    content_sync_service.get_post_view_settings()
+}
+
+/// Update the post view privacy settings
+#[tauri::command]
+pub async fn set_post_view_settings(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    settings: PostViewSettings,
+) -> Result<(), AppError> {
+    content_sync_service.set_post_view_settings(settings)
+}
+
+/// Get the oldest signature scheme version accepted on incoming posts and messages
+#[tauri::command]
+pub async fn get_min_signature_version(db: State<'_, Arc<Database>>) -> Result<u32, AppError> {
+    Ok(SignaturePolicy::from_settings(&db)?.min_version)
+}
+
+/// Refuse posts and messages signed with a scheme older than `version`.
+/// Items already stored are kept either way.
+#[tauri::command]
+pub async fn set_min_signature_version(
+    db: State<'_, Arc<Database>>,
+    version: u32,
+) -> Result<(), AppError> {
+    if !(SIG_VERSION_CBOR..=CURRENT_SIG_VERSION).contains(&version) {
+        return Err(AppError::Validation(format!(
+            "Signature version must be between {} and {}",
+            SIG_VERSION_CBOR, CURRENT_SIG_VERSION
+        )));
+    }
+    SettingsRepository::set(&db, SETTING_MIN_SIGNATURE_VERSION, &version.to_string())?;
+    Ok(())
+}
+
 /// Sync with all connected peers
 #[tauri::command]
 pub async fn sync_with_all_peers(
     network_state: State<'_, NetworkState>,
 ) -> Result<Vec<String>, AppError> {
     let handle = network_state.get_handle().await?;
+    sync_connected_peers(&handle).await
+}
+
+/// Get the background sync interval in seconds (0 = off)
+#[tauri::command]
+pub async fn get_auto_sync_interval(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+) -> Result<u64, AppError> {
+    content_sync_service.get_auto_sync_interval()
+}
+
+/// Set the background sync interval in seconds; 0 turns background sync off
+#[tauri::command]
+pub async fn set_auto_sync_interval(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    secs: u64,
+) -> Result<(), AppError> {
+    content_sync_service.set_auto_sync_interval(secs)
+}
+
+/// Report whether the current connection is metered (pauses background sync)
+#[tauri::command]
+pub async fn set_metered_connection(
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    metered: bool,
+) -> Result<(), AppError> {
+    content_sync_service.set_metered_connection(metered);
+    Ok(())
+}
+
+/// Catch up after restoring an old backup: forget the inbound sync cursors,
+/// sync every online contact from the start, then re-join each community and
+/// re-fetch its boards.
+///
+/// Only sync cursors are reset. Local posts are left alone, and a stored post
+/// is only replaced by a copy with a higher lamport clock, so newer local-only
+/// posts survive. Contacts that are offline catch up through the regular sync,
+/// which starts from the cleared cursors as well.
+#[tauri::command]
+pub async fn resync_all(
+    app: AppHandle,
+    network_state: State<'_, NetworkState>,
+    content_sync_service: State<'_, Arc<ContentSyncService>>,
+    contacts_service: State<'_, Arc<ContactsService>>,
+    board_service: State<'_, Arc<BoardService>>,
+) -> Result<ResyncSummary, AppError> {
+    let handle = network_state.get_handle().await?;
+
+    let mut summary = ResyncSummary {
+        cursors_cleared: content_sync_service.reset_sync_cursors()?,
+        board_cursors_cleared: board_service.reset_sync_cursors()?,
+        ..Default::default()
+    };
+
+    let connected: HashSet<String> = handle
+        .get_connected_peers()
+        .await?
+        .into_iter()
+        .map(|peer| peer.peer_id)
+        .collect();
+    let contacts: Vec<String> = contacts_service
+        .get_active_contacts()?
+        .into_iter()
+        .map(|contact| contact.peer_id)
+        .filter(|peer_id| connected.contains(peer_id))
+        .collect();
+
+    for (index, peer_id) in contacts.iter().enumerate() {
+        let (posts_fetched, error) = resync_peer(&handle, peer_id).await;
+        summary.posts_fetched += posts_fetched;
+        match &error {
+            None => summary.peers_synced += 1,
+            Some(e) => summary.errors.push(format!("{}: {}", peer_id, e)),
+        }
+        emit_resync_progress(
+            &app,
+            ResyncProgress {
+                stage: ResyncStage::Contacts,
+                completed: index + 1,
+                total: contacts.len(),
+                peer_id: Some(peer_id.clone()),
+                posts_fetched,
+                error,
+            },
+        );
+    }
+
+    let communities = board_service.get_communities()?;
+    for (index, community) in communities.iter().enumerate() {
+        let error = match rejoin_community(&handle, &board_service, community).await {
+            Ok(()) => {
+                summary.communities_rejoined += 1;
+                None
+            }
+            Err(e) => {
+                summary
+                    .errors
+                    .push(format!("{}: {}", community.relay_peer_id, e));
+                Some(e.to_string())
+            }
+        };
+        emit_resync_progress(
+            &app,
+            ResyncProgress {
+                stage: ResyncStage::Communities,
+                completed: index + 1,
+                total: communities.len(),
+                peer_id: Some(community.relay_peer_id.clone()),
+                posts_fetched: 0,
+                error,
+            },
+        );
+    }
+
+    emit_resync_progress(
+        &app,
+        ResyncProgress {
+            stage: ResyncStage::Done,
+            completed: summary.peers_synced + summary.communities_rejoined,
+            total: contacts.len() + communities.len(),
+            peer_id: None,
+            posts_fetched: summary.posts_fetched,
+            error: None,
+        },
+    );
+    Ok(summary)
+}
+
+/// Sync one contact until it has nothing more to send, returning the posts
+/// fetched and the first error
+async fn resync_peer(handle: &NetworkHandle, peer_id: &str) -> (usize, Option<String>) {
+    let peer: libp2p::PeerId = match peer_id.parse() {
+        Ok(peer) => peer,
+        Err(e) => return (0, Some(format!("Invalid peer ID: {}", e))),
+    };
+
+    let mut posts_fetched = 0;
+    for _ in 0..MAX_RESYNC_ROUNDS {
+        match handle.sync_with_peer(peer, RESYNC_MANIFEST_LIMIT).await {
+            Ok(round) => {
+                posts_fetched += round.posts_fetched;
+                if let Some(error) = round.errors.into_iter().next() {
+                    return (posts_fetched, Some(error));
+                }
+                if !round.has_more {
+                    break;
+                }
+            }
+            Err(e) => return (posts_fetched, Some(e.to_string())),
+        }
+    }
+    (posts_fetched, None)
+}
+
+/// Re-register with a community relay and request the posts of every board
+/// we know on it. Posts arrive as `board_posts_received` network events.
+async fn rejoin_community(
+    handle: &NetworkHandle,
+    board_service: &BoardService,
+    community: &RelayCommunity,
+) -> Result<(), AppError> {
+    let relay_peer_id: libp2p::PeerId = community
+        .relay_peer_id
+        .parse()
+        .map_err(|e| AppError::Network(format!("Invalid peer ID: {}", e)))?;
+    if let Ok(address) = community.relay_address.parse::<libp2p::Multiaddr>() {
+        handle.dial(relay_peer_id, vec![address]).await.ok();
+    }
+
+    handle
+        .join_community(relay_peer_id, community.relay_address.clone())
+        .await?;
+    for board in board_service.get_boards(&community.relay_peer_id)? {
+        handle
+            .get_board_posts(relay_peer_id, board.board_id, None, RESYNC_BOARD_POST_LIMIT)
+            .await?;
+    }
+    Ok(())
+}
+
+fn emit_resync_progress(app: &AppHandle, progress: ResyncProgress) {
+    if let Err(e) = app.emit("harbor:resync", &progress) {
+        tracing::warn!("Failed to emit resync progress: {}", e);
+    }
+}
 
+/// Request a content manifest from every connected peer, returning the
+/// peers asked. Shared by `sync_with_all_peers` and the background sync.
+pub async fn sync_connected_peers(handle: &NetworkHandle) -> Result<Vec<String>, AppError> {
     // Get connected peers
     let peers = handle.get_connected_peers().await?;
     let mut synced_peers = Vec::new();
@@ -108,7 +418,10 @@ pub async fn sync_with_all_peers(
         let cursor: HashMap<String, u64> = HashMap::new();
 
         // Request manifest from each peer (async, don't wait for response)
-        match handle.request_content_manifest(peer_id, cursor, 50).await {
+        match handle
+            .request_content_manifest(peer_id, cursor, 50, ManifestDetailLevel::Full)
+            .await
+        {
             Ok(_) => synced_peers.push(peer.peer_id),
             Err(e) => {
                 tracing::warn!("Failed to request manifest from {}: {}", peer.peer_id, e);
@@ -0,0 +1,59 @@
+//! Tauri commands for database startup status and recovery
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::db::{Database, DatabaseStartupStatus};
+use crate::error::AppError;
+
+/// How the database opened at startup, for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DatabaseStatusInfo {
+    pub path: String,
+    /// Set when the database couldn't be opened; only recovery is possible
+    pub error: Option<String>,
+    pub rebuilt: bool,
+    pub corrupt_copy: Option<String>,
+    /// When the last known-good backup was taken, if there is one
+    pub backup_taken_at: Option<i64>,
+}
+
+/// Get how opening the database went at startup
+#[tauri::command]
+pub async fn get_database_status(
+    status: State<'_, DatabaseStartupStatus>,
+) -> Result<DatabaseStatusInfo, AppError> {
+    let backup_taken_at = std::fs::metadata(Database::backup_path(&status.path))
+        .and_then(|meta| meta.modified())
+        .ok()
+        .and_then(|modified| modified.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|since_epoch| since_epoch.as_secs() as i64);
+
+    Ok(DatabaseStatusInfo {
+        path: status.path.to_string_lossy().to_string(),
+        error: status.error.clone(),
+        rebuilt: status.rebuilt,
+        corrupt_copy: status
+            .corrupt_copy
+            .as_ref()
+            .map(|p| p.to_string_lossy().to_string()),
+        backup_taken_at,
+    })
+}
+
+/// Replace a database that failed to open with its last backup. The app
+/// must be relaunched afterwards to open it.
+#[tauri::command]
+pub async fn restore_database_backup(
+    status: State<'_, DatabaseStartupStatus>,
+) -> Result<(), AppError> {
+    if status.error.is_none() {
+        return Err(AppError::Validation(
+            "The database is open; a backup can only be restored when it failed to open"
+                .to_string(),
+        ));
+    }
+    Database::restore_backup(&status.path)?;
+    Ok(())
+}
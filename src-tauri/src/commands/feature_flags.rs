@@ -0,0 +1,54 @@
+//! Tauri commands for per-identity feature flags
+
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tauri::State;
+
+use crate::error::AppError;
+use crate::services::FeatureFlagsService;
+
+/// A feature flag for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeatureFlagInfo {
+    pub name: String,
+    pub description: String,
+    pub enabled: bool,
+}
+
+/// Whether a feature flag is on for the current identity
+#[tauri::command]
+pub async fn get_feature_flag(
+    feature_flags_service: State<'_, Arc<FeatureFlagsService>>,
+    name: String,
+) -> Result<bool, AppError> {
+    let flag = FeatureFlagsService::parse(&name)?;
+    feature_flags_service.is_enabled(flag)
+}
+
+/// Turn a feature flag on or off for the current identity
+#[tauri::command]
+pub async fn set_feature_flag(
+    feature_flags_service: State<'_, Arc<FeatureFlagsService>>,
+    name: String,
+    enabled: bool,
+) -> Result<(), AppError> {
+    let flag = FeatureFlagsService::parse(&name)?;
+    feature_flags_service.set_enabled(flag, enabled)
+}
+
+/// List every known feature flag with its description and current state
+#[tauri::command]
+pub async fn list_feature_flags(
+    feature_flags_service: State<'_, Arc<FeatureFlagsService>>,
+) -> Result<Vec<FeatureFlagInfo>, AppError> {
+    Ok(feature_flags_service
+        .list()?
+        .into_iter()
+        .map(|state| FeatureFlagInfo {
+            name: state.flag.name().to_string(),
+            description: state.flag.description().to_string(),
+            enabled: state.enabled,
+        })
+        .collect())
+}
@@ -23,6 +23,9 @@ pub struct FeedItemInfo {
     pub created_at: i64,
     pub updated_at: i64,
     pub is_local: bool,
+    pub pinned: bool,
+    /// When a community relay received the post (signed by the relay)
+    pub relay_observed_at: Option<i64>,
 }
 
 impl From<FeedItem> for FeedItemInfo {
@@ -38,6 +41,8 @@ impl From<FeedItem> for FeedItemInfo {
             created_at: item.post.created_at,
             updated_at: item.post.updated_at,
             is_local: item.post.is_local,
+            pinned: item.post.pinned,
+            relay_observed_at: item.post.relay_observed_at,
         }
     }
 }
@@ -48,9 +53,10 @@ pub async fn get_feed(
     feed_service: State<'_, Arc<FeedService>>,
     limit: Option<i64>,
     before_timestamp: Option<i64>,
+    group_id: Option<String>,
 ) -> Result<Vec<FeedItemInfo>, AppError> {
     let limit = limit.unwrap_or(50);
-    let items = feed_service.get_feed(limit, before_timestamp)?;
+    let items = feed_service.get_feed(limit, before_timestamp, group_id.as_deref())?;
     Ok(items.into_iter().map(FeedItemInfo::from).collect())
 }
 
@@ -67,6 +73,19 @@ pub async fn get_wall(
     Ok(items.into_iter().map(FeedItemInfo::from).collect())
 }
 
+/// Get a single contact's posts in timeline order
+#[tauri::command]
+pub async fn get_contact_feed(
+    feed_service: State<'_, Arc<FeedService>>,
+    peer_id: String,
+    limit: Option<i64>,
+    before_timestamp: Option<i64>,
+) -> Result<Vec<FeedItemInfo>, AppError> {
+    let limit = limit.unwrap_or(50);
+    let items = feed_service.get_contact_feed(&peer_id, limit, before_timestamp)?;
+    Ok(items.into_iter().map(FeedItemInfo::from).collect())
+}
+
 /// View perspective for wall preview
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "lowercase")]
@@ -123,6 +142,8 @@ pub async fn get_wall_preview(
             created_at: post.created_at,
             updated_at: post.updated_at,
             is_local: post.is_local,
+            pinned: post.pinned,
+            relay_observed_at: post.relay_observed_at,
         })
         .collect();
 
@@ -147,8 +168,9 @@ pub async fn get_wall_visibility_stats(
         total_posts: counts.total_posts,
         public_posts: counts.public_posts,
         contacts_only_posts: counts.contacts_only_posts,
+        private_posts: counts.private_posts,
         guest_visible: counts.public_posts,
-        contact_visible: counts.total_posts,
+        contact_visible: counts.public_posts + counts.contacts_only_posts,
     })
 }
 
@@ -162,6 +184,8 @@ pub struct WallVisibilityStats {
     pub public_posts: usize,
     /// Number of contacts-only posts
     pub contacts_only_posts: usize,
+    /// Number of private (self-only) posts
+    pub private_posts: usize,
     /// Number of posts visible to guests
     pub guest_visible: usize,
     /// Number of posts visible to contacts
@@ -1,7 +1,43 @@
-use std::path::PathBuf;
-use tauri::Manager;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tauri::{Manager, State};
+
+use crate::db::repositories::settings_repo::SETTING_DOWNLOAD_DIR;
+use crate::db::repositories::SettingsRepository;
+use crate::db::Database;
+
+/// Name used when nothing usable is left of the requested filename
+const FALLBACK_FILENAME: &str = "download";
+
+/// Longest filename we write, in bytes. Most filesystems allow 255.
+const MAX_FILENAME_BYTES: usize = 200;
+
+/// Give up on finding a free name after this many numbered variants
+const MAX_COLLISION_SUFFIX: u32 = 1000;
+
+/// Device names Windows reserves regardless of extension
+const WINDOWS_RESERVED_NAMES: &[&str] = &[
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn resolve_download_dir(app: &tauri::AppHandle, db: &Database) -> Result<PathBuf, String> {
+    // A directory chosen in settings takes precedence
+    if let Some(configured) = SettingsRepository::get(db, SETTING_DOWNLOAD_DIR)
+        .map_err(|error| format!("Failed to read download directory setting: {}", error))?
+    {
+        let directory = PathBuf::from(&configured);
+        if directory.is_dir() || std::fs::create_dir_all(&directory).is_ok() {
+            return Ok(directory);
+        }
+        tracing::warn!(
+            "Configured download directory {} is unusable, falling back to the default",
+            configured
+        );
+    }
 
-fn resolve_download_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     // Try Tauri's download_dir first
     if let Ok(directory) = app.path().download_dir() {
         if directory.exists() || std::fs::create_dir_all(&directory).is_ok() {
@@ -27,17 +63,225 @@ fn resolve_download_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
     Err("Could not find a writable directory".to_string())
 }
 
+/// Turn a (possibly peer-supplied) filename into a single safe path component.
+///
+/// Only the last path segment is kept, characters that are invalid on any
+/// platform are replaced, and Windows device names are prefixed so the result
+/// can never point outside the download directory or at a device.
+fn sanitize_filename(filename: &str) -> String {
+    let last_segment = filename.rsplit(['/', '\\']).next().unwrap_or_default();
+
+    let cleaned: String = last_segment
+        .chars()
+        .map(|c| match c {
+            '<' | '>' | ':' | '"' | '|' | '?' | '*' => '_',
+            c if c.is_control() => '_',
+            c => c,
+        })
+        .collect();
+
+    // Leading dots would hide the file or form "..", trailing dots and spaces
+    // are silently dropped by Windows
+    let mut name = cleaned
+        .trim_start_matches(['.', ' '])
+        .trim_end_matches(['.', ' '])
+        .to_string();
+    if name.is_empty() {
+        return FALLBACK_FILENAME.to_string();
+    }
+
+    let stem = name.split('.').next().unwrap_or_default();
+    if WINDOWS_RESERVED_NAMES
+        .iter()
+        .any(|reserved| stem.eq_ignore_ascii_case(reserved))
+    {
+        name.insert(0, '_');
+    }
+
+    truncate_filename(&name, MAX_FILENAME_BYTES)
+}
+
+/// Split a filename into stem and extension (including the dot)
+fn split_extension(filename: &str) -> (&str, &str) {
+    match filename.rfind('.') {
+        Some(index) if index > 0 => filename.split_at(index),
+        _ => (filename, ""),
+    }
+}
+
+/// Shorten a filename to `max_bytes`, keeping the extension where possible
+fn truncate_filename(filename: &str, max_bytes: usize) -> String {
+    if filename.len() <= max_bytes {
+        return filename.to_string();
+    }
+    let (stem, extension) = split_extension(filename);
+    let extension = if extension.len() < max_bytes / 2 {
+        extension
+    } else {
+        ""
+    };
+    let mut end = max_bytes - extension.len();
+    while !stem.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}{}", &stem[..end], extension)
+}
+
+/// Write `content` to `directory/filename`, appending " (1)", " (2)", ... to
+/// the stem instead of overwriting an existing file. Returns the final path.
+fn write_unique(directory: &Path, filename: &str, content: &[u8]) -> std::io::Result<PathBuf> {
+    let (stem, extension) = split_extension(filename);
+    for counter in 0..=MAX_COLLISION_SUFFIX {
+        let candidate = if counter == 0 {
+            filename.to_string()
+        } else {
+            format!("{} ({}){}", stem, counter, extension)
+        };
+        let path = directory.join(candidate);
+        // create_new fails instead of clobbering a file that appeared meanwhile
+        match OpenOptions::new().write(true).create_new(true).open(&path) {
+            Ok(mut file) => {
+                file.write_all(content)?;
+                return Ok(path);
+            }
+            Err(error) if error.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(error) => return Err(error),
+        }
+    }
+    Err(std::io::Error::new(
+        std::io::ErrorKind::AlreadyExists,
+        format!("Too many files named {}", filename),
+    ))
+}
+
+/// Save text to the download directory under a sanitized, non-colliding
+/// name and return the path it was written to
 #[tauri::command]
 pub fn save_to_downloads(
     app: tauri::AppHandle,
+    db: State<'_, Arc<Database>>,
     filename: String,
     content: String,
 ) -> Result<String, String> {
-    let download_dir = resolve_download_dir(&app)?;
-    let file_path = download_dir.join(&filename);
+    let download_dir = resolve_download_dir(&app, &db)?;
+    let safe_name = sanitize_filename(&filename);
 
-    std::fs::write(&file_path, &content)
-        .map_err(|error| format!("Failed to write file to {}: {}", file_path.display(), error))?;
+    let file_path =
+        write_unique(&download_dir, &safe_name, content.as_bytes()).map_err(|error| {
+            format!(
+                "Failed to write {} to {}: {}",
+                safe_name,
+                download_dir.display(),
+                error
+            )
+        })?;
 
     Ok(file_path.to_string_lossy().to_string())
 }
+
+/// Get the directory downloads are currently saved to
+#[tauri::command]
+pub fn get_download_dir(
+    app: tauri::AppHandle,
+    db: State<'_, Arc<Database>>,
+) -> Result<String, String> {
+    let directory = resolve_download_dir(&app, &db)?;
+    Ok(directory.to_string_lossy().to_string())
+}
+
+/// Choose the directory downloads are saved to, or `None` for the system default
+#[tauri::command]
+pub fn set_download_dir(db: State<'_, Arc<Database>>, path: Option<String>) -> Result<(), String> {
+    let Some(path) = path else {
+        SettingsRepository::delete(&db, SETTING_DOWNLOAD_DIR)
+            .map_err(|error| format!("Failed to reset download directory: {}", error))?;
+        return Ok(());
+    };
+
+    let directory = PathBuf::from(&path);
+    if !directory.is_absolute() {
+        return Err("Download directory must be an absolute path".to_string());
+    }
+    if !directory.is_dir() {
+        return Err(format!("{} is not a directory", directory.display()));
+    }
+
+    SettingsRepository::set(&db, SETTING_DOWNLOAD_DIR, &path)
+        .map_err(|error| format!("Failed to save download directory: {}", error))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sanitize_filename_strips_traversal() {
+        assert_eq!(sanitize_filename("../../etc/passwd"), "passwd");
+        assert_eq!(sanitize_filename("..\\..\\Windows\\win.ini"), "win.ini");
+        assert_eq!(sanitize_filename("/absolute/path.txt"), "path.txt");
+        assert_eq!(sanitize_filename(".."), FALLBACK_FILENAME);
+        assert_eq!(sanitize_filename("dir/"), FALLBACK_FILENAME);
+        assert_eq!(sanitize_filename(""), FALLBACK_FILENAME);
+        assert_eq!(sanitize_filename(".bashrc"), "bashrc");
+    }
+
+    #[test]
+    fn test_sanitize_filename_replaces_invalid_characters() {
+        assert_eq!(sanitize_filename("evil\0name.txt"), "evil_name.txt");
+        assert_eq!(
+            sanitize_filename("a<b>c:d\"e|f?g*.yaml"),
+            "a_b_c_d_e_f_g_.yaml"
+        );
+        assert_eq!(sanitize_filename("line\nbreak.txt"), "line_break.txt");
+        assert_eq!(sanitize_filename("trailing. . "), "trailing");
+    }
+
+    #[test]
+    fn test_sanitize_filename_reserved_windows_names() {
+        assert_eq!(sanitize_filename("CON"), "_CON");
+        assert_eq!(sanitize_filename("con.txt"), "_con.txt");
+        assert_eq!(sanitize_filename("lpt9.tar.gz"), "_lpt9.tar.gz");
+        // Only exact device names are reserved
+        assert_eq!(sanitize_filename("CONFIG.yaml"), "CONFIG.yaml");
+        assert_eq!(sanitize_filename("COM10"), "COM10");
+    }
+
+    #[test]
+    fn test_sanitize_filename_limits_length() {
+        let long = format!("{}.yaml", "a".repeat(500));
+        let sanitized = sanitize_filename(&long);
+        assert_eq!(sanitized.len(), MAX_FILENAME_BYTES);
+        assert!(sanitized.ends_with(".yaml"));
+
+        // Multi-byte characters are never cut in half
+        let sanitized = sanitize_filename(&"é".repeat(300));
+        assert!(sanitized.len() <= MAX_FILENAME_BYTES);
+    }
+
+    #[test]
+    fn test_write_unique_appends_counter_instead_of_overwriting() {
+        let tmp = tempfile::tempdir().unwrap();
+
+        let first = write_unique(tmp.path(), "relay.yaml", b"first").unwrap();
+        let second = write_unique(tmp.path(), "relay.yaml", b"second").unwrap();
+        let third = write_unique(tmp.path(), "relay.yaml", b"third").unwrap();
+
+        assert_eq!(first, tmp.path().join("relay.yaml"));
+        assert_eq!(second, tmp.path().join("relay (1).yaml"));
+        assert_eq!(third, tmp.path().join("relay (2).yaml"));
+        assert_eq!(std::fs::read(&first).unwrap(), b"first");
+        assert_eq!(std::fs::read(&third).unwrap(), b"third");
+    }
+
+    #[test]
+    fn test_write_unique_stays_in_directory() {
+        let tmp = tempfile::tempdir().unwrap();
+        let downloads = tmp.path().join("downloads");
+        std::fs::create_dir(&downloads).unwrap();
+
+        let path = write_unique(&downloads, &sanitize_filename("../escape.txt"), b"x").unwrap();
+
+        assert_eq!(path, downloads.join("escape.txt"));
+        assert!(!tmp.path().join("escape.txt").exists());
+    }
+}
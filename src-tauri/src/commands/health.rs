@@ -0,0 +1,321 @@
+//! One-call backend health report for the Troubleshoot screen
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+use crate::commands::network::NetworkState;
+use crate::db::connection::LATEST_SCHEMA_VERSION;
+use crate::db::Database;
+use crate::error::AppError;
+use crate::p2p::{NatStatus, NetworkStats, RelayHealth};
+use crate::services::IdentityService;
+
+/// How long the report waits for the network task before calling it unresponsive
+const NETWORK_STATS_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Outcome of a single health check
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HealthStatus {
+    Ok,
+    Warn,
+    Fail,
+}
+
+/// One subsystem's health
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthCheck {
+    /// Stable identifier, e.g. `database` or `relays`
+    pub name: String,
+    pub status: HealthStatus,
+    pub message: String,
+}
+
+impl HealthCheck {
+    fn new(name: &str, status: HealthStatus, message: impl Into<String>) -> Self {
+        Self {
+            name: name.to_string(),
+            status,
+            message: message.into(),
+        }
+    }
+}
+
+/// Every check, plus the worst status among them
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HealthReport {
+    pub status: HealthStatus,
+    pub checks: Vec<HealthCheck>,
+    pub generated_at: i64,
+}
+
+impl HealthReport {
+    fn from_checks(checks: Vec<HealthCheck>) -> Self {
+        Self {
+            status: checks
+                .iter()
+                .map(|check| check.status)
+                .max()
+                .unwrap_or(HealthStatus::Ok),
+            checks,
+            generated_at: chrono::Utc::now().timestamp(),
+        }
+    }
+}
+
+/// Check the database answers and is on the current schema
+fn check_database(db: &Database) -> HealthCheck {
+    match db.schema_version() {
+        Ok(version) if version == LATEST_SCHEMA_VERSION => HealthCheck::new(
+            "database",
+            HealthStatus::Ok,
+            format!("Schema version {}", version),
+        ),
+        Ok(version) => HealthCheck::new(
+            "database",
+            HealthStatus::Fail,
+            format!(
+                "Schema version {} but this build expects {}",
+                version, LATEST_SCHEMA_VERSION
+            ),
+        ),
+        Err(e) => HealthCheck::new(
+            "database",
+            HealthStatus::Fail,
+            format!("Database unreachable: {}", e),
+        ),
+    }
+}
+
+/// Check an identity exists and is unlocked
+fn check_identity(identity_service: &IdentityService) -> HealthCheck {
+    match identity_service.has_identity() {
+        Ok(false) => HealthCheck::new("identity", HealthStatus::Fail, "No identity created"),
+        Ok(true) if identity_service.is_unlocked() => {
+            HealthCheck::new("identity", HealthStatus::Ok, "Identity unlocked")
+        }
+        Ok(true) => HealthCheck::new("identity", HealthStatus::Warn, "Identity is locked"),
+        Err(e) => HealthCheck::new(
+            "identity",
+            HealthStatus::Fail,
+            format!("Could not read identity: {}", e),
+        ),
+    }
+}
+
+/// Checks derived from a running network's stats
+fn network_checks(stats: &NetworkStats) -> Vec<HealthCheck> {
+    let mut checks = vec![HealthCheck::new(
+        "network",
+        HealthStatus::Ok,
+        format!("Running for {}s", stats.uptime_seconds),
+    )];
+
+    checks.push(match stats.nat_status {
+        NatStatus::Public | NatStatus::Private => HealthCheck::new(
+            "nat",
+            HealthStatus::Ok,
+            format!("NAT status {}", stats.nat_status.as_str()),
+        ),
+        NatStatus::Unknown => {
+            HealthCheck::new("nat", HealthStatus::Warn, "NAT status not determined yet")
+        }
+        NatStatus::BehindNat => HealthCheck::new(
+            "nat",
+            HealthStatus::Warn,
+            "Behind a strict NAT; peers may only reach us through a relay",
+        ),
+    });
+
+    // Without a public address, a relay reservation is how peers reach us
+    checks.push(if !stats.relay_addresses.is_empty() {
+        HealthCheck::new(
+            "relays",
+            HealthStatus::Ok,
+            format!("{} relay reservation(s) held", stats.relay_addresses.len()),
+        )
+    } else if stats.nat_status == NatStatus::Public {
+        HealthCheck::new(
+            "relays",
+            HealthStatus::Ok,
+            "No relay reservation needed with a public address",
+        )
+    } else {
+        HealthCheck::new(
+            "relays",
+            HealthStatus::Warn,
+            "No relay reservation held; peers behind NAT may not reach us",
+        )
+    });
+
+    let degraded: Vec<&str> = stats
+        .relay_liveness
+        .iter()
+        .filter(|relay| relay.health == RelayHealth::Degraded)
+        .map(|relay| relay.relay_peer_id.as_str())
+        .collect();
+    if !degraded.is_empty() {
+        checks.push(HealthCheck::new(
+            "relay_liveness",
+            HealthStatus::Warn,
+            format!("Relays not answering pings: {}", degraded.join(", ")),
+        ));
+    }
+
+    checks.push(if stats.connected_peers > 0 {
+        HealthCheck::new(
+            "peers",
+            HealthStatus::Ok,
+            format!("{} peer(s) connected", stats.connected_peers),
+        )
+    } else {
+        HealthCheck::new("peers", HealthStatus::Warn, "No peers connected")
+    });
+
+    if stats.dropped_events > 0 {
+        checks.push(HealthCheck::new(
+            "events",
+            HealthStatus::Warn,
+            format!(
+                "{} network events dropped because the app fell behind",
+                stats.dropped_events
+            ),
+        ));
+    }
+
+    checks
+}
+
+/// Check the network, asking the running service for its stats
+async fn check_network(network: &NetworkState) -> Vec<HealthCheck> {
+    let handle = match network.get_handle().await {
+        Ok(handle) => handle,
+        Err(_) => {
+            return vec![HealthCheck::new(
+                "network",
+                HealthStatus::Fail,
+                "Network is not running",
+            )]
+        }
+    };
+    match tokio::time::timeout(NETWORK_STATS_TIMEOUT, handle.get_stats()).await {
+        Ok(Ok(stats)) => network_checks(&stats),
+        Ok(Err(e)) => vec![HealthCheck::new(
+            "network",
+            HealthStatus::Fail,
+            format!("Network service not responding: {}", e),
+        )],
+        Err(_) => vec![HealthCheck::new(
+            "network",
+            HealthStatus::Fail,
+            format!(
+                "Network service did not answer within {}s",
+                NETWORK_STATS_TIMEOUT.as_secs()
+            ),
+        )],
+    }
+}
+
+/// Check every backend subsystem. Never fails: a failing subsystem is
+/// reported as a `fail` entry in the report.
+#[tauri::command]
+pub async fn run_health_check(
+    db: State<'_, Arc<Database>>,
+    identity_service: State<'_, Arc<IdentityService>>,
+    network: State<'_, NetworkState>,
+) -> Result<HealthReport, AppError> {
+    let mut checks = vec![check_database(&db), check_identity(&identity_service)];
+    checks.extend(check_network(&network).await);
+    Ok(HealthReport::from_checks(checks))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::p2p::RelayLiveness;
+
+    fn status_of(checks: &[HealthCheck], name: &str) -> Option<HealthStatus> {
+        checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| check.status)
+    }
+
+    #[test]
+    fn test_database_and_identity_checks() {
+        let db = Arc::new(Database::in_memory().unwrap());
+        assert_eq!(check_database(&db).status, HealthStatus::Ok);
+
+        let identity_service = IdentityService::new(db);
+        assert_eq!(check_identity(&identity_service).status, HealthStatus::Fail);
+    }
+
+    #[test]
+    fn test_healthy_network() {
+        let stats = NetworkStats {
+            connected_peers: 3,
+            nat_status: NatStatus::Private,
+            relay_addresses: vec!["/ip4/1.2.3.4/tcp/4001/p2p/relay/p2p-circuit".to_string()],
+            ..Default::default()
+        };
+        let checks = network_checks(&stats);
+        assert!(checks.iter().all(|check| check.status == HealthStatus::Ok));
+        assert_eq!(status_of(&checks, "relay_liveness"), None);
+    }
+
+    #[test]
+    fn test_degraded_network_warns() {
+        let stats = NetworkStats {
+            nat_status: NatStatus::BehindNat,
+            relay_liveness: vec![RelayLiveness {
+                relay_peer_id: "relay-1".to_string(),
+                health: RelayHealth::Degraded,
+                rtt_ms: None,
+                last_pong_at: None,
+                consecutive_failures: 4,
+            }],
+            dropped_events: 12,
+            ..Default::default()
+        };
+        let checks = network_checks(&stats);
+        for name in ["nat", "relays", "relay_liveness", "peers", "events"] {
+            assert_eq!(
+                status_of(&checks, name),
+                Some(HealthStatus::Warn),
+                "{}",
+                name
+            );
+        }
+
+        let report = HealthReport::from_checks(checks);
+        assert_eq!(report.status, HealthStatus::Warn);
+    }
+
+    #[test]
+    fn test_public_address_needs_no_relay() {
+        let stats = NetworkStats {
+            connected_peers: 1,
+            nat_status: NatStatus::Public,
+            ..Default::default()
+        };
+        assert_eq!(
+            status_of(&network_checks(&stats), "relays"),
+            Some(HealthStatus::Ok)
+        );
+    }
+
+    #[test]
+    fn test_report_takes_worst_status() {
+        let report = HealthReport::from_checks(vec![
+            HealthCheck::new("a", HealthStatus::Ok, ""),
+            HealthCheck::new("b", HealthStatus::Fail, ""),
+            HealthCheck::new("c", HealthStatus::Warn, ""),
+        ]);
+        assert_eq!(report.status, HealthStatus::Fail);
+    }
+}
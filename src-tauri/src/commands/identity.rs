@@ -1,5 +1,5 @@
 use crate::error::AppError;
-use crate::models::{CreateIdentityRequest, IdentityInfo};
+use crate::models::{CreateIdentityRequest, IdentityBackup, IdentityInfo, KdfProfile};
 use crate::services::{AccountsService, IdentityService};
 use std::sync::Arc;
 use tauri::State;
@@ -60,6 +60,33 @@ pub async fn create_identity(
     Ok(identity)
 }
 
+/// Create a throwaway guest identity (requires the app to run in ephemeral mode).
+///
+/// Unlike `create_identity`, this is not added to the accounts registry.
+#[tauri::command]
+pub async fn create_ephemeral_identity(
+    identity_service: State<'_, Arc<IdentityService>>,
+    display_name: String,
+) -> Result<IdentityInfo, AppError> {
+    identity_service.create_ephemeral_identity(&display_name)
+}
+
+/// Check if the identity is an ephemeral guest identity
+#[tauri::command]
+pub async fn is_identity_ephemeral(
+    identity_service: State<'_, Arc<IdentityService>>,
+) -> Result<bool, AppError> {
+    Ok(identity_service.is_ephemeral())
+}
+
+/// Export an identity backup (blocked for ephemeral identities)
+#[tauri::command]
+pub async fn export_identity_backup(
+    identity_service: State<'_, Arc<IdentityService>>,
+) -> Result<IdentityBackup, AppError> {
+    identity_service.export_backup()
+}
+
 /// Unlock the identity with passphrase
 #[tauri::command]
 pub async fn unlock_identity(
@@ -69,6 +96,17 @@ pub async fn unlock_identity(
     identity_service.unlock(&passphrase)
 }
 
+/// Change the identity passphrase, optionally with a new key derivation profile
+#[tauri::command]
+pub async fn change_passphrase(
+    identity_service: State<'_, Arc<IdentityService>>,
+    current_passphrase: String,
+    new_passphrase: String,
+    kdf_profile: Option<KdfProfile>,
+) -> Result<(), AppError> {
+    identity_service.change_passphrase(&current_passphrase, &new_passphrase, kdf_profile)
+}
+
 /// Lock the identity
 #[tauri::command]
 pub async fn lock_identity(
@@ -105,6 +143,23 @@ pub async fn update_passphrase_hint(
     identity_service.update_passphrase_hint(hint.as_deref())
 }
 
+/// Update the accent color, keeping the accounts registry in step
+#[tauri::command]
+pub async fn update_accent_color(
+    identity_service: State<'_, Arc<IdentityService>>,
+    accounts_service: State<'_, Arc<AccountsService>>,
+    accent_color: Option<String>,
+) -> Result<(), AppError> {
+    identity_service.update_accent_color(accent_color.as_deref())?;
+
+    // Ephemeral identities aren't registered, so there may be no account
+    let peer_id = identity_service.get_peer_id()?;
+    if accounts_service.get_account(&peer_id)?.is_some() {
+        accounts_service.update_account(&peer_id, None, None, None, Some(accent_color))?;
+    }
+    Ok(())
+}
+
 /// Get the local peer ID
 #[tauri::command]
 pub async fn get_peer_id(
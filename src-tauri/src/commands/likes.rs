@@ -1,5 +1,6 @@
 //! Tauri commands for post likes/reactions
 
+use crate::commands::NetworkState;
 use crate::db::repositories::{LikeData, LikeSummary, LikesRepository};
 use crate::db::Database;
 use crate::error::{AppError, Result};
@@ -13,6 +14,7 @@ use tauri::State;
 pub async fn like_post(
     db: State<'_, Arc<Database>>,
     identity_service: State<'_, Arc<IdentityService>>,
+    network_state: State<'_, NetworkState>,
     post_id: String,
 ) -> Result<LikeSummary> {
     // Get current identity
@@ -41,6 +43,7 @@ pub async fn like_post(
     };
 
     LikesRepository::add_like(&db, &data).map_err(|e| AppError::DatabaseString(e.to_string()))?;
+    notify_likes_changed(&network_state, &post_id).await;
 
     // Return updated summary
     LikesRepository::get_like_summary(&db, &post_id, &identity.peer_id)
@@ -52,6 +55,7 @@ pub async fn like_post(
 pub async fn unlike_post(
     db: State<'_, Arc<Database>>,
     identity_service: State<'_, Arc<IdentityService>>,
+    network_state: State<'_, NetworkState>,
     post_id: String,
 ) -> Result<LikeSummary> {
     // Get current identity
@@ -59,8 +63,11 @@ pub async fn unlike_post(
         .get_identity()?
         .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
 
-    LikesRepository::remove_like(&db, &post_id, &identity.peer_id)
+    let removed = LikesRepository::remove_like(&db, &post_id, &identity.peer_id)
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+    if removed {
+        notify_likes_changed(&network_state, &post_id).await;
+    }
 
     // Return updated summary
     LikesRepository::get_like_summary(&db, &post_id, &identity.peer_id)
@@ -115,3 +122,12 @@ pub async fn get_my_liked_posts(
     LikesRepository::get_liked_posts(&db, &identity.peer_id)
         .map_err(|e| AppError::DatabaseString(e.to_string()))
 }
+
+/// Let other views know a post's likes changed (no-op before the network starts)
+async fn notify_likes_changed(network_state: &NetworkState, post_id: &str) {
+    if let Ok(handle) = network_state.get_handle().await {
+        if let Err(e) = handle.notify_likes_changed(vec![post_id.to_string()]).await {
+            tracing::warn!("Failed to report like change for {}: {}", post_id, e);
+        }
+    }
+}
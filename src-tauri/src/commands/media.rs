@@ -57,18 +57,19 @@ pub async fn get_media_url(
     hash: String,
     media_service: State<'_, Arc<MediaStorageService>>,
 ) -> Result<String, String> {
-    let path = media_service
-        .get_media_path(&hash)
+    let data = media_service
+        .get_media(&hash)
         .map_err(|e| format!("Media not found: {}", e))?;
 
-    let data = std::fs::read(&path)
-        .map_err(|e| format!("Failed to read media file: {}", e))?;
-
-    // Determine MIME type from file extension
-    let mime = path
-        .extension()
-        .and_then(|ext| ext.to_str())
-        .map(extension_to_mime)
+    // Determine MIME type from file extension (when stored on disk)
+    let mime = media_service
+        .get_media_path(&hash)
+        .ok()
+        .and_then(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .map(extension_to_mime)
+        })
         .unwrap_or("application/octet-stream");
 
     let encoded = base64::Engine::encode(&base64::engine::general_purpose::STANDARD, &data);
@@ -91,6 +92,11 @@ fn extension_to_mime(ext: &str) -> &'static str {
         "mov" => "video/quicktime",
         "avi" => "video/x-msvideo",
         "mkv" => "video/x-matroska",
+        "ogg" => "audio/ogg",
+        "weba" => "audio/webm",
+        "m4a" => "audio/mp4",
+        "mp3" => "audio/mpeg",
+        "wav" => "audio/wav",
         _ => "application/octet-stream",
     }
 }
@@ -106,8 +112,9 @@ pub async fn has_media(
 
 /// Preload missing media from connected peers.
 ///
-/// Scans post_media for image entries where the file is missing locally,
-/// groups them by author peer ID, and either:
+/// Scans post_media for image entries and message_media for voice notes
+/// where the file is missing locally, groups them by author peer ID, and
+/// either:
 /// - Sends P2P fetch requests if the author is already connected
 /// - Dials the author through the relay circuit to establish a connection
 ///   (media will be fetched on the next preloader invocation once connected)
@@ -127,14 +134,19 @@ pub async fn preload_missing_media(
         .flatten()
         .map(|id| id.peer_id);
 
-    // Query all image-type media entries with their author (excluding own posts)
+    // Query all image-type media entries and voice notes with their author
+    // (excluding own posts)
     let all_media = db
         .with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT pm.media_hash, pm.media_type, p.author_peer_id
                  FROM post_media pm
                  JOIN posts p ON pm.post_id = p.post_id
-                 WHERE pm.media_type = 'image'",
+                 WHERE pm.media_type = 'image'
+                 UNION ALL
+                 SELECT mm.media_hash, 'voice', m.sender_peer_id
+                 FROM message_media mm
+                 JOIN messages m ON mm.message_id = m.message_id",
             )?;
 
             let mut results = Vec::new();
@@ -147,7 +159,7 @@ pub async fn preload_missing_media(
             }
             Ok(results)
         })
-        .map_err(|e| format!("Failed to query post_media: {}", e))?;
+        .map_err(|e| format!("Failed to query media: {}", e))?;
 
     // Filter out own posts — our media files should already exist locally
     let all_media: Vec<(String, String)> = if let Some(ref local_id) = local_peer_id {
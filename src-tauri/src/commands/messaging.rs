@@ -4,14 +4,21 @@ use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
 use std::str::FromStr;
 use std::sync::Arc;
-use tauri::State;
-use tracing::info;
+use tauri::{AppHandle, Emitter, State};
+use tracing::{info, warn};
 
 use crate::commands::network::NetworkState;
-use crate::db::repositories::Conversation;
+use crate::db::repositories::{Conversation, MessageCursor};
 use crate::error::AppError;
-use crate::p2p::protocols::messaging::{DirectMessage, MessagingCodec, MessagingMessage};
-use crate::services::{DecryptedMessage, MessagingService, OutgoingMessage};
+use crate::p2p::protocols::messaging::{
+    DirectMessage, MessageDelete, MessageEdit, MessagingCodec, MessagingMessage, ReadReceipt,
+    MAX_READ_RECEIPT_MESSAGES,
+};
+use crate::services::{
+    ContactsService, DecryptedMessage, MediaStorageService, MessageCipher, MessagePage,
+    MessagingService, NonceStrategy, OutgoingMessage, ReplyPreview, RetentionSweepSummary,
+    VoiceNote, CURRENT_SIG_VERSION,
+};
 
 /// Message info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +37,52 @@ pub struct MessageInfo {
     pub status: String,
     pub is_outgoing: bool,
     pub edited_at: Option<i64>,
+    /// When the sender deleted the message; `content` is then empty
+    pub deleted_at: Option<i64>,
+    pub reply_preview: Option<ReplyPreviewInfo>,
+    pub voice: Option<VoiceNoteInfo>,
+}
+
+/// Preview of a quoted message for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReplyPreviewInfo {
+    pub message_id: String,
+    pub sender_peer_id: Option<String>,
+    pub snippet: String,
+    pub is_deleted: bool,
+}
+
+impl From<ReplyPreview> for ReplyPreviewInfo {
+    fn from(preview: ReplyPreview) -> Self {
+        Self {
+            message_id: preview.message_id,
+            sender_peer_id: preview.sender_peer_id,
+            snippet: preview.snippet,
+            is_deleted: preview.is_deleted,
+        }
+    }
+}
+
+/// Voice note audio for the frontend, loaded with `get_media_url`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VoiceNoteInfo {
+    pub media_hash: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+impl From<VoiceNote> for VoiceNoteInfo {
+    fn from(voice: VoiceNote) -> Self {
+        Self {
+            media_hash: voice.media_hash,
+            mime_type: voice.mime_type,
+            size_bytes: voice.size_bytes,
+            duration_ms: voice.duration_ms,
+        }
+    }
 }
 
 impl From<DecryptedMessage> for MessageInfo {
@@ -48,6 +101,39 @@ impl From<DecryptedMessage> for MessageInfo {
             status: msg.status,
             is_outgoing: msg.is_outgoing,
             edited_at: msg.edited_at,
+            deleted_at: msg.deleted_at,
+            reply_preview: msg.reply_preview.map(ReplyPreviewInfo::from),
+            voice: msg.voice.map(VoiceNoteInfo::from),
+        }
+    }
+}
+
+/// A page of messages for the frontend, oldest first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessagePageInfo {
+    pub messages: Vec<MessageInfo>,
+    /// Pass back as `before_timestamp`/`before_message_id` for the older
+    /// page; `None` when this page reaches the start of the conversation
+    pub next_cursor: Option<MessageCursorInfo>,
+}
+
+/// Where the next older page of messages starts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageCursorInfo {
+    pub before_timestamp: i64,
+    pub before_message_id: Option<String>,
+}
+
+impl From<MessagePage> for MessagePageInfo {
+    fn from(page: MessagePage) -> Self {
+        Self {
+            messages: page.messages.into_iter().map(MessageInfo::from).collect(),
+            next_cursor: page.next_cursor.map(|cursor| MessageCursorInfo {
+                before_timestamp: cursor.sent_at,
+                before_message_id: cursor.message_id,
+            }),
         }
     }
 }
@@ -59,16 +145,29 @@ pub struct ConversationInfo {
     pub conversation_id: String,
     pub peer_id: String,
     pub last_message_at: i64,
+    /// Decrypted, truncated text of the last message, or `[encrypted]`
+    pub last_message_preview: Option<String>,
+    pub last_message_is_outgoing: bool,
+    pub last_message_status: String,
     pub unread_count: i64,
+    pub pinned: bool,
+    /// Listed as "Notes to self": messages we sent to our own peer ID
+    pub is_note_to_self: bool,
 }
 
 impl From<Conversation> for ConversationInfo {
     fn from(conv: Conversation) -> Self {
         Self {
+            last_message_is_outgoing: conv.is_note_to_self
+                || conv.last_message.sender_peer_id != conv.peer_id,
+            last_message_status: conv.last_message.status,
             conversation_id: conv.conversation_id,
             peer_id: conv.peer_id,
             last_message_at: conv.last_message_at,
+            last_message_preview: conv.last_message_preview,
             unread_count: conv.unread_count,
+            pinned: conv.pinned,
+            is_note_to_self: conv.is_note_to_self,
         }
     }
 }
@@ -80,6 +179,20 @@ pub struct SendMessageResult {
     pub message_id: String,
     pub conversation_id: String,
     pub sent_at: i64,
+    /// Matches the `message_send_result` events fired as each delivery
+    /// attempt completes. Empty for notes to self, which are never sent.
+    pub correlation_id: String,
+}
+
+/// Result of a one-off message sent by address
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DirectSendResult {
+    pub message_id: String,
+    pub peer_id: String,
+    /// Display name the peer announced during the identity exchange
+    pub display_name: String,
+    pub sent_at: i64,
 }
 
 /// Convert OutgoingMessage to DirectMessage for network transmission
@@ -96,10 +209,22 @@ fn outgoing_to_direct_message(outgoing: &OutgoingMessage) -> DirectMessage {
         lamport_clock: outgoing.lamport_clock,
         timestamp: outgoing.timestamp,
         signature: outgoing.signature.clone(),
+        nonce_salt: outgoing.nonce_salt.clone(),
+        sig_version: CURRENT_SIG_VERSION,
+        protocol_version: outgoing.protocol_version,
+        ratchet: outgoing.ratchet.clone(),
+        cipher: outgoing.cipher.as_str().to_string(),
+        voice: outgoing.voice.clone(),
     }
 }
 
-/// Send a message to a peer
+/// Send a message to a peer.
+///
+/// Returns as soon as the message is queued. The message stays `pending`
+/// until the peer responds, then becomes `sent` or `failed`, and a
+/// `message_send_result` event carrying the returned correlation ID is emitted.
+/// A message to our own peer ID is a note to self: it is stored sealed to
+/// our own key and nothing goes over the network.
 #[tauri::command]
 pub async fn send_message(
     messaging_service: State<'_, Arc<MessagingService>>,
@@ -115,43 +240,220 @@ pub async fn send_message(
     let outgoing =
         messaging_service.send_message(&peer_id, &content, &content_type, reply_to.as_deref())?;
 
+    queue_outgoing_message(messaging_service.inner(), &network, &peer_id, outgoing).await
+}
+
+/// Send a voice note to a peer.
+///
+/// The recorded audio is checked against the voice note limits, stored in
+/// media storage and announced in a `voice` message with an optional
+/// caption. The peer fetches the audio from us over media sync. Delivery is
+/// reported the same way as for `send_message`.
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn send_voice_note(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    media_service: State<'_, Arc<MediaStorageService>>,
+    network: State<'_, NetworkState>,
+    peer_id: String,
+    data: Vec<u8>,
+    mime_type: String,
+    duration_ms: u64,
+    caption: Option<String>,
+    reply_to: Option<String>,
+) -> Result<SendMessageResult, AppError> {
+    let voice = VoiceNote::for_audio(&data, &mime_type, duration_ms)?;
+    media_service.store_media(&data, &voice.mime_type)?;
+
+    let outgoing = messaging_service.send_voice_note(
+        &peer_id,
+        &voice,
+        caption.as_deref().unwrap_or_default(),
+        reply_to.as_deref(),
+    )?;
+
+    queue_outgoing_message(messaging_service.inner(), &network, &peer_id, outgoing).await
+}
+
+/// Queue a sealed message until the peer confirms it and ask the network to
+/// send it. Undelivered messages are retried with backoff, and whenever the
+/// peer reconnects, until the attempts run out.
+async fn queue_outgoing_message(
+    messaging_service: &Arc<MessagingService>,
+    network: &NetworkState,
+    peer_id: &str,
+    outgoing: OutgoingMessage,
+) -> Result<SendMessageResult, AppError> {
+    // Notes to self are already stored; there is no one to deliver them to
+    if outgoing.recipient_peer_id == outgoing.sender_peer_id {
+        return Ok(SendMessageResult {
+            message_id: outgoing.message_id,
+            conversation_id: outgoing.conversation_id,
+            sent_at: outgoing.timestamp,
+            correlation_id: String::new(),
+        });
+    }
+
     // Convert to DirectMessage and encode for network transmission
-    let direct_msg = outgoing_to_direct_message(&outgoing);
-    let msg_wrapper = MessagingMessage::Message(direct_msg);
-    let payload = MessagingCodec::encode(&msg_wrapper)
-        .map_err(|e| AppError::Internal(format!("Failed to encode message: {}", e)))?;
+    let payload = encode_direct_message(&outgoing)?;
 
     // Parse the peer ID
-    let libp2p_peer_id = PeerId::from_str(&peer_id)
+    let libp2p_peer_id = PeerId::from_str(peer_id)
         .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
 
-    // Send over the network
+    messaging_service.queue_outbound(&outgoing.message_id, peer_id, &payload)?;
+    info!(
+        "Message {} queued for peer {}",
+        outgoing.message_id, peer_id
+    );
+    flush_queue(network, libp2p_peer_id, &outgoing.message_id).await;
+
+    Ok(SendMessageResult {
+        correlation_id: outgoing.message_id.clone(),
+        message_id: outgoing.message_id,
+        conversation_id: outgoing.conversation_id,
+        sent_at: outgoing.timestamp,
+    })
+}
+
+/// Send a one-off message to a peer by address without adding them as a contact.
+///
+/// Dials the peer, verifies its identity in memory to learn its X25519 key,
+/// then sends the encrypted message and waits for the peer to accept it.
+/// Nothing about the peer is stored and no chat permission is granted.
+/// Blocked peers are refused. The recipient still decides whether to accept
+/// messages from senders that aren't in its own contacts.
+#[tauri::command]
+pub async fn send_direct_message(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    contacts_service: State<'_, Arc<ContactsService>>,
+    network: State<'_, NetworkState>,
+    multiaddr: String,
+    content: String,
+    content_type: Option<String>,
+    disconnect_after: Option<bool>,
+) -> Result<DirectSendResult, AppError> {
+    let content_type = content_type.unwrap_or_else(|| "text".to_string());
+    let addr: libp2p::Multiaddr = multiaddr
+        .parse()
+        .map_err(|e| AppError::Validation(format!("Invalid multiaddress: {}", e)))?;
+    // For relayed addresses the last /p2p component is the target, not the relay
+    let peer_id = addr
+        .iter()
+        .filter_map(|proto| match proto {
+            libp2p::multiaddr::Protocol::P2p(peer_id) => Some(peer_id),
+            _ => None,
+        })
+        .last()
+        .ok_or_else(|| AppError::Validation("Multiaddress has no /p2p peer ID".to_string()))?;
+
+    // Refuse before dialing so a blocked peer never sees a connection
+    if contacts_service.is_blocked(&peer_id.to_string())? {
+        return Err(AppError::PermissionDenied(
+            "Cannot message a blocked peer".to_string(),
+        ));
+    }
+
     let handle = network.get_handle().await?;
-    handle
-        .send_message(libp2p_peer_id, "message".to_string(), payload)
+    handle.dial(peer_id, vec![addr]).await?;
+    let identity = handle.fetch_identity(peer_id).await?;
+
+    let outgoing = messaging_service.send_transient_message(
+        &identity.peer_id,
+        &identity.x25519_public,
+        &content,
+        &content_type,
+    )?;
+    let msg_wrapper = MessagingMessage::Message(outgoing_to_direct_message(&outgoing));
+    let payload = MessagingCodec::encode(&msg_wrapper)
+        .map_err(|e| AppError::Internal(format!("Failed to encode message: {}", e)))?;
+
+    let send = handle
+        .send_message(peer_id, "message".to_string(), payload)
         .await?;
+    let delivered = send.confirmed().await;
+
+    if disconnect_after.unwrap_or(false) {
+        if let Err(e) = handle.disconnect(peer_id).await {
+            warn!(
+                "Failed to disconnect from {} after direct send: {}",
+                peer_id, e
+            );
+        }
+    }
+    delivered?;
 
-    info!("Message {} sent to peer {}", outgoing.message_id, peer_id);
+    info!(
+        "One-off message {} delivered to {}",
+        outgoing.message_id, peer_id
+    );
 
-    Ok(SendMessageResult {
+    Ok(DirectSendResult {
         message_id: outgoing.message_id,
-        conversation_id: outgoing.conversation_id,
+        peer_id: identity.peer_id,
+        display_name: identity.display_name,
         sent_at: outgoing.timestamp,
     })
 }
 
-/// Get messages for a conversation
+/// Get a page of messages for a conversation, newest page first.
+///
+/// `limit` defaults to 50 and is clamped to 200. Pass the returned cursor
+/// back as `before_timestamp`/`before_message_id` to load older messages.
 #[tauri::command]
 pub async fn get_messages(
     messaging_service: State<'_, Arc<MessagingService>>,
     peer_id: String,
     limit: Option<i64>,
     before_timestamp: Option<i64>,
-) -> Result<Vec<MessageInfo>, AppError> {
-    let limit = limit.unwrap_or(50);
+    before_message_id: Option<String>,
+) -> Result<MessagePageInfo, AppError> {
+    let before = before_timestamp.map(|sent_at| MessageCursor {
+        sent_at,
+        message_id: before_message_id,
+    });
+
+    let page = messaging_service.get_message_page(&peer_id, limit, before.as_ref())?;
+    Ok(MessagePageInfo::from(page))
+}
 
-    let messages =
-        messaging_service.get_conversation_messages(&peer_id, limit, before_timestamp)?;
+/// Matches found so far by a running `search_messages`, emitted as
+/// `harbor:message-search`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MessageSearchProgress {
+    pub query: String,
+    pub messages: Vec<MessageInfo>,
+}
+
+/// Search message content, newest first, across all conversations or just
+/// `conversation_id`. Matching is a case-insensitive substring; `limit`
+/// defaults to 50 and is clamped to 200.
+///
+/// Every message has to be decrypted to be searched, so this runs on a
+/// blocking thread and emits each batch of matches as it is found.
+#[tauri::command]
+pub async fn search_messages(
+    app: AppHandle,
+    messaging_service: State<'_, Arc<MessagingService>>,
+    query: String,
+    conversation_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<MessageInfo>, AppError> {
+    let messaging_service = messaging_service.inner().clone();
+    let messages = tauri::async_runtime::spawn_blocking(move || {
+        messaging_service.search_messages(&query, conversation_id.as_deref(), limit, |batch| {
+            let progress = MessageSearchProgress {
+                query: query.clone(),
+                messages: batch.iter().cloned().map(MessageInfo::from).collect(),
+            };
+            if let Err(e) = app.emit("harbor:message-search", &progress) {
+                warn!("Failed to emit message search progress: {}", e);
+            }
+        })
+    })
+    .await
+    .map_err(|e| AppError::Internal(format!("Message search failed: {}", e)))??;
 
     Ok(messages.into_iter().map(MessageInfo::from).collect())
 }
@@ -168,13 +470,35 @@ pub async fn get_conversations(
         .collect())
 }
 
-/// Mark a conversation as read
+/// Mark a conversation as read and tell the peer which messages we've seen,
+/// in as few read receipts as possible
 #[tauri::command]
 pub async fn mark_conversation_read(
     messaging_service: State<'_, Arc<MessagingService>>,
+    network: State<'_, NetworkState>,
     peer_id: String,
 ) -> Result<i64, AppError> {
-    messaging_service.mark_conversation_read(&peer_id)
+    let read = messaging_service.mark_conversation_read(&peer_id)?;
+    if read.is_empty() || messaging_service.is_note_to_self(&peer_id)? {
+        return Ok(read.len() as i64);
+    }
+
+    let libp2p_peer_id = PeerId::from_str(&peer_id)
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
+    for batch in read.chunks(MAX_READ_RECEIPT_MESSAGES) {
+        let (receipt, signature) = messaging_service.create_read_receipt(&peer_id, batch)?;
+        let receipt_msg = MessagingMessage::ReadReceipt(ReadReceipt {
+            conversation_id: receipt.conversation_id,
+            reader_peer_id: receipt.reader_peer_id,
+            message_ids: receipt.message_ids,
+            read_at: receipt.read_at,
+            signature,
+        });
+        messaging_service.queue_outbound_read_receipt(&peer_id, &encode(&receipt_msg)?)?;
+    }
+    flush_queue(&network, libp2p_peer_id, "Read receipts").await;
+
+    Ok(read.len() as i64)
 }
 
 /// Get unread count for a conversation
@@ -228,29 +552,235 @@ pub async fn edit_message(
     info!("Editing message {}", message_id);
 
     // Update locally
-    messaging_service.edit_message(&message_id, &new_content)?;
+    let edit = messaging_service.edit_message(&message_id, &new_content)?;
+    if messaging_service.is_note_to_self(&peer_id)? {
+        return Ok(());
+    }
+    let libp2p_peer_id = PeerId::from_str(&peer_id)
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
 
-    // Best-effort sync to peer: send an EditMessage over the network
-    let edit_msg = MessagingMessage::EditMessage {
-        message_id: message_id.clone(),
-        new_content: new_content.clone(),
-        edited_at: chrono::Utc::now().timestamp(),
-    };
+    // A message that hasn't gone out yet goes with its new content. The edit
+    // follows regardless, in case the original arrived but its confirmation
+    // didn't.
+    if let Some(resealed) = messaging_service.reseal_queued_message(&message_id)? {
+        messaging_service
+            .replace_queued_payload(&message_id, &encode_direct_message(&resealed)?)?;
+    }
 
-    if let Ok(payload) = MessagingCodec::encode(&edit_msg) {
-        let libp2p_peer_id = PeerId::from_str(&peer_id)
-            .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
-
-        if let Ok(handle) = network.get_handle().await {
-            let _ = handle
-                .send_message(libp2p_peer_id, "message".to_string(), payload)
-                .await;
-            info!(
-                "Edit for message {} sent to peer {} (best effort)",
-                message_id, peer_id
-            );
-        }
+    let edit_msg = MessagingMessage::Edit(MessageEdit {
+        message_id: edit.message_id,
+        conversation_id: edit.conversation_id,
+        sender_peer_id: edit.sender_peer_id,
+        content_encrypted: edit.content_encrypted,
+        nonce_salt: edit.nonce_salt,
+        lamport_clock: edit.lamport_clock,
+        edited_at: edit.edited_at,
+        ratchet: edit.ratchet,
+        signature: edit.signature,
+    });
+    let key = messaging_service.queue_outbound_edit(&message_id, &peer_id, &encode(&edit_msg)?)?;
+    info!(
+        "Edit for message {} queued for peer {}",
+        message_id, peer_id
+    );
+    flush_queue(&network, libp2p_peer_id, &key).await;
+
+    Ok(())
+}
+
+/// Delete a sent message, for us and for the peer
+#[tauri::command]
+pub async fn delete_message(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    network: State<'_, NetworkState>,
+    message_id: String,
+    peer_id: String,
+) -> Result<(), AppError> {
+    info!("Deleting message {}", message_id);
+
+    let delete = messaging_service.delete_message(&message_id)?;
+    if messaging_service.is_note_to_self(&peer_id)? {
+        return Ok(());
     }
+    let libp2p_peer_id = PeerId::from_str(&peer_id)
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
+
+    let delete_msg = MessagingMessage::Delete(MessageDelete {
+        message_id: delete.message_id,
+        conversation_id: delete.conversation_id,
+        sender_peer_id: delete.sender_peer_id,
+        lamport_clock: delete.lamport_clock,
+        deleted_at: delete.deleted_at,
+        signature: delete.signature,
+    });
+    let key =
+        messaging_service.queue_outbound_delete(&message_id, &peer_id, &encode(&delete_msg)?)?;
+    info!(
+        "Delete for message {} queued for peer {}",
+        message_id, peer_id
+    );
+    flush_queue(&network, libp2p_peer_id, &key).await;
 
     Ok(())
 }
+
+/// Encode a messaging frame for the outbound queue
+fn encode(message: &MessagingMessage) -> Result<Vec<u8>, AppError> {
+    MessagingCodec::encode(message)
+        .map_err(|e| AppError::Internal(format!("Failed to encode message: {}", e)))
+}
+
+/// Encode a sealed message as a `Message` frame
+fn encode_direct_message(outgoing: &OutgoingMessage) -> Result<Vec<u8>, AppError> {
+    encode(&MessagingMessage::Message(outgoing_to_direct_message(
+        outgoing,
+    )))
+}
+
+/// Ask the network to send everything queued for `peer`. What's safely
+/// queued but can't go out now is sent once the peer connects.
+async fn flush_queue(network: &NetworkState, peer: PeerId, what: &str) {
+    let flushed = match network.get_handle().await {
+        Ok(handle) => handle.flush_pending_messages(peer).await,
+        Err(e) => Err(e),
+    };
+    if let Err(e) = flushed {
+        warn!("{} left queued for {}: {}", what, peer, e);
+    }
+}
+
+/// Get how outgoing message nonces are derived
+#[tauri::command]
+pub async fn get_message_nonce_strategy(
+    messaging_service: State<'_, Arc<MessagingService>>,
+) -> Result<NonceStrategy, AppError> {
+    messaging_service.get_nonce_strategy()
+}
+
+/// Set how outgoing message nonces are derived. `counter` is only needed for
+/// peers running versions that do not understand salted nonces.
+#[tauri::command]
+pub async fn set_message_nonce_strategy(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    strategy: NonceStrategy,
+) -> Result<(), AppError> {
+    messaging_service.set_nonce_strategy(strategy)
+}
+
+/// Get the cipher preferred for outgoing messages
+#[tauri::command]
+pub async fn get_message_cipher(
+    messaging_service: State<'_, Arc<MessagingService>>,
+) -> Result<MessageCipher, AppError> {
+    messaging_service.get_message_cipher()
+}
+
+/// Set the cipher preferred for outgoing messages. Contacts that haven't
+/// announced support for it keep getting AES-256-GCM.
+#[tauri::command]
+pub async fn set_message_cipher(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    cipher: MessageCipher,
+) -> Result<(), AppError> {
+    messaging_service.set_message_cipher(cipher)
+}
+
+/// Retention override for a conversation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConversationRetentionInfo {
+    pub retention_days: Option<u32>,
+    pub never_delete: bool,
+}
+
+/// Get the global message retention window in days (null keeps messages forever)
+#[tauri::command]
+pub async fn get_message_retention(
+    messaging_service: State<'_, Arc<MessagingService>>,
+) -> Result<Option<u32>, AppError> {
+    messaging_service.get_message_retention()
+}
+
+/// Set the global message retention window in days (null disables pruning)
+#[tauri::command]
+pub async fn set_message_retention(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    days: Option<u32>,
+) -> Result<(), AppError> {
+    messaging_service.set_message_retention(days)
+}
+
+/// Count messages still waiting for delivery, to one peer or (without a
+/// peer) to anyone
+#[tauri::command]
+pub async fn get_pending_message_count(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    peer_id: Option<String>,
+) -> Result<i64, AppError> {
+    messaging_service.get_pending_message_count(peer_id.as_deref())
+}
+
+/// Get how many times an undelivered message is sent before giving up
+#[tauri::command]
+pub async fn get_max_send_attempts(
+    messaging_service: State<'_, Arc<MessagingService>>,
+) -> Result<u32, AppError> {
+    messaging_service.get_max_send_attempts()
+}
+
+/// Set how many times an undelivered message is sent before giving up
+#[tauri::command]
+pub async fn set_max_send_attempts(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    attempts: u32,
+) -> Result<(), AppError> {
+    messaging_service.set_max_send_attempts(attempts)
+}
+
+/// Get the retention override for a conversation, if any
+#[tauri::command]
+pub async fn get_conversation_retention(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    peer_id: String,
+) -> Result<Option<ConversationRetentionInfo>, AppError> {
+    Ok(messaging_service
+        .get_conversation_retention(&peer_id)?
+        .map(|r| ConversationRetentionInfo {
+            retention_days: r.retention_days,
+            never_delete: r.never_delete,
+        }))
+}
+
+/// Override retention for a conversation. With neither a window nor
+/// `never_delete`, the global window applies again.
+#[tauri::command]
+pub async fn set_conversation_retention(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    peer_id: String,
+    retention_days: Option<u32>,
+    never_delete: bool,
+) -> Result<(), AppError> {
+    messaging_service.set_conversation_retention(&peer_id, retention_days, never_delete)
+}
+
+/// Pin a conversation to the top of the list, or unpin it
+#[tauri::command]
+pub async fn set_conversation_pinned(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    peer_id: String,
+    pinned: bool,
+) -> Result<(), AppError> {
+    messaging_service.set_conversation_pinned(&peer_id, pinned)
+}
+
+/// Prune expired messages now instead of waiting for the background sweep
+#[tauri::command]
+pub async fn run_message_retention_sweep(
+    messaging_service: State<'_, Arc<MessagingService>>,
+    media_service: State<'_, Arc<MediaStorageService>>,
+) -> Result<RetentionSweepSummary, AppError> {
+    let summary =
+        messaging_service.sweep_expired_messages(messaging_service.clock().timestamp())?;
+    media_service.prune_message_media()?;
+    Ok(summary)
+}
@@ -1,12 +1,16 @@
 pub mod accounts;
+pub mod activity;
 pub mod boards;
 pub mod bootstrap;
 pub mod calling;
 pub mod comments;
 pub mod contacts;
 pub mod content_sync;
+pub mod database;
+pub mod feature_flags;
 pub mod feed;
 pub mod files;
+pub mod health;
 pub mod identity;
 pub mod likes;
 pub mod link_preview;
@@ -20,14 +24,18 @@ pub mod rss;
 pub mod wall_sync;
 
 pub use accounts::*;
+pub use activity::*;
 pub use boards::*;
 pub use bootstrap::*;
 pub use calling::*;
 pub use comments::*;
 pub use contacts::*;
 pub use content_sync::*;
+pub use database::*;
+pub use feature_flags::*;
 pub use feed::*;
 pub use files::*;
+pub use health::*;
 pub use identity::*;
 pub use likes::*;
 pub use link_preview::*;
@@ -1,26 +1,85 @@
+use crate::db::repositories::connection_failures_repo::MAX_CONNECTION_FAILURES;
+use crate::db::repositories::settings_repo::{
+    SETTING_ADDRESS_FILTER, SETTING_AUTONAT_ENABLED, SETTING_AUTO_REQUEST_IDENTITY,
+    SETTING_HEARTBEAT_ENABLED, SETTING_HEARTBEAT_INTERVAL, SETTING_NAT_OVERRIDE,
+    SETTING_PORT_FALLBACK, SETTING_QUIC_PORT, SETTING_RECONCILE_INTERVAL, SETTING_SYNC_STRATEGY,
+    SETTING_TCP_PORT,
+};
+use crate::db::repositories::{
+    BootstrapNodesRepo, ConnectionFailure, ConnectionFailureData, ConnectionFailuresRepository,
+    SettingsRepository,
+};
+use crate::db::Database;
 use crate::error::AppError;
-use crate::p2p::{NetworkConfig, NetworkHandle, NetworkService, NetworkStats, PeerInfo};
+use crate::p2p::{
+    AddressFilter, BoundPorts, DhtRoutingTable, NatStatus, NetworkConfig, NetworkEvent,
+    NetworkHandle, NetworkService, NetworkStats, PeerInfo, PeerSyncSummary, RelayCircuitLimits,
+    RelayLiveness, SyncStrategy,
+};
 use crate::services::{
-    BoardService, ContactsService, ContentSyncService, IdentityService, MediaStorageService,
-    MessagingService, PermissionsService, PostsService,
+    BoardService, CallingService, ContactsService, ContentSyncService, IdentityQrPayload,
+    IdentityService, MediaStorageService, MessagingService, PermissionsService, PostsService,
 };
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tauri::{AppHandle, Emitter, State};
-use tokio::sync::RwLock;
-use tracing::info;
+use tokio::sync::{Mutex, RwLock};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
 
 /// Wrapper for NetworkHandle to make it Tauri state compatible
+/// How long app exit waits for the network task to wind down
+pub const NETWORK_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
 pub struct NetworkState {
     pub handle: RwLock<Option<NetworkHandle>>,
+    /// Network service and event forwarding tasks, awaited on shutdown
+    tasks: Mutex<Vec<JoinHandle<()>>>,
 }
 
 impl NetworkState {
     pub fn new() -> Self {
         Self {
             handle: RwLock::new(None),
+            tasks: Mutex::new(Vec::new()),
         }
     }
 
+    async fn set_tasks(&self, tasks: Vec<JoinHandle<()>>) {
+        *self.tasks.lock().await = tasks;
+    }
+
+    /// Stop the network and wait for its tasks to finish so in-flight writes
+    /// and queued events aren't cut off. Returns false if `timeout` ran out.
+    pub async fn shutdown(&self, timeout: Duration) -> bool {
+        let maybe_handle = self.handle.write().await.take();
+        let tasks = std::mem::take(&mut *self.tasks.lock().await);
+        if maybe_handle.is_none() && tasks.is_empty() {
+            return true;
+        }
+
+        let started = Instant::now();
+        if let Some(handle) = maybe_handle {
+            // The service may already have exited; its tasks still get awaited
+            if let Err(e) = handle.shutdown().await {
+                warn!("Failed to send network shutdown command: {}", e);
+            }
+        }
+
+        let finished = tokio::time::timeout(timeout, futures::future::join_all(tasks))
+            .await
+            .is_ok();
+        if finished {
+            info!("Network shut down in {:?}", started.elapsed());
+        } else {
+            warn!(
+                "Network shutdown timed out after {:?}, abandoning its tasks",
+                started.elapsed()
+            );
+        }
+        finished
+    }
+
     pub async fn set_handle(&self, handle: NetworkHandle) {
         let mut guard = self.handle.write().await;
         *guard = Some(handle);
@@ -74,6 +133,7 @@ pub async fn bootstrap_network(network: State<'_, NetworkState>) -> Result<(), A
 
 /// Services needed to start the P2P network
 pub struct StartNetworkServices {
+    pub db: Arc<Database>,
     pub identity_service: Arc<IdentityService>,
     pub messaging_service: Arc<MessagingService>,
     pub contacts_service: Arc<ContactsService>,
@@ -82,6 +142,7 @@ pub struct StartNetworkServices {
     pub content_sync_service: Arc<ContentSyncService>,
     pub board_service: Arc<BoardService>,
     pub media_service: Arc<MediaStorageService>,
+    pub calling_service: Arc<CallingService>,
 }
 
 /// Start the P2P network (called after identity is unlocked)
@@ -94,6 +155,7 @@ pub struct StartNetworkServices {
 pub async fn start_network(
     app: AppHandle,
     network: State<'_, NetworkState>,
+    db: State<'_, Arc<Database>>,
     identity_service: State<'_, Arc<IdentityService>>,
     messaging_service: State<'_, Arc<MessagingService>>,
     contacts_service: State<'_, Arc<ContactsService>>,
@@ -102,8 +164,10 @@ pub async fn start_network(
     content_sync_service: State<'_, Arc<ContentSyncService>>,
     board_service: State<'_, Arc<BoardService>>,
     media_service: State<'_, Arc<MediaStorageService>>,
+    calling_service: State<'_, Arc<CallingService>>,
 ) -> Result<(), AppError> {
     let services = StartNetworkServices {
+        db: (*db).clone(),
         identity_service: (*identity_service).clone(),
         messaging_service: (*messaging_service).clone(),
         contacts_service: (*contacts_service).clone(),
@@ -112,6 +176,7 @@ pub async fn start_network(
         content_sync_service: (*content_sync_service).clone(),
         board_service: (*board_service).clone(),
         media_service: (*media_service).clone(),
+        calling_service: (*calling_service).clone(),
     };
     start_network_with_services(app, network, services).await
 }
@@ -163,8 +228,7 @@ async fn start_network_with_services(
         }
     }
 
-    // Create network config
-    let config = NetworkConfig::default();
+    let config = load_network_config(&services.db)?;
 
     // Create network service - clone the Arc to pass to the service
     let identity_arc: Arc<IdentityService> = services.identity_service.clone();
@@ -178,28 +242,53 @@ async fn start_network_with_services(
     service.set_content_sync_service(services.content_sync_service.clone());
     service.set_board_service(services.board_service.clone());
     service.set_media_service(services.media_service.clone());
+    service.set_calling_service(services.calling_service.clone());
+
+    // Bind before reporting success so a taken port fails this command
+    service.start_listening()?;
 
     // Store the handle
     network.set_handle(handle).await;
 
     // Spawn the network service in a background task
-    tokio::spawn(async move {
+    let service_task = tokio::spawn(async move {
         info!("Network service starting in background task");
         service.run().await;
         info!("Network service stopped");
     });
 
-    // Spawn a task to process network events and forward to frontend
+    // Spawn a task to process network events and forward to frontend. It
+    // ends once the service drops its sender, after the last event is emitted.
     let app_clone = app.clone();
-    tokio::spawn(async move {
+    let db = services.db.clone();
+    let forward_task = tokio::spawn(async move {
         while let Some(event) = event_rx.recv().await {
             info!("Network event: {:?}", event);
+            if let NetworkEvent::ConnectionFailed {
+                peer_id,
+                address,
+                category,
+                detail,
+            } = &event
+            {
+                let failure = ConnectionFailureData {
+                    peer_id: peer_id.clone(),
+                    address: address.clone(),
+                    category: category.as_str().to_string(),
+                    detail: detail.clone(),
+                    occurred_at: chrono::Utc::now().timestamp(),
+                };
+                if let Err(e) = ConnectionFailuresRepository::record(&db, &failure) {
+                    warn!("Failed to record connection failure: {}", e);
+                }
+            }
             // Emit event to frontend
             if let Err(e) = app_clone.emit("harbor:network", &event) {
                 tracing::warn!("Failed to emit network event: {}", e);
             }
         }
     });
+    network.set_tasks(vec![service_task, forward_task]).await;
 
     info!("Network started successfully");
     Ok(())
@@ -208,16 +297,7 @@ async fn start_network_with_services(
 /// Stop the P2P network
 #[tauri::command]
 pub async fn stop_network(network: State<'_, NetworkState>) -> Result<(), AppError> {
-    let maybe_handle: Option<NetworkHandle> = {
-        let mut guard = network.handle.write().await;
-        guard.take()
-    };
-
-    if let Some(handle) = maybe_handle {
-        handle.shutdown().await?;
-        info!("Network stopped");
-    }
-
+    network.shutdown(NETWORK_SHUTDOWN_TIMEOUT).await;
     Ok(())
 }
 
@@ -264,7 +344,8 @@ pub async fn add_bootstrap_node(
 }
 
 /// Get shareable addresses for remote peers to connect to us
-/// Returns external addresses discovered via AutoNAT or relay addresses if behind NAT
+/// Returns external addresses discovered via AutoNAT or relay addresses if behind NAT,
+/// limited to what the address filter allows
 #[tauri::command]
 pub async fn get_shareable_addresses(
     network: State<'_, NetworkState>,
@@ -283,14 +364,12 @@ pub async fn get_shareable_addresses(
     let mut addresses = Vec::new();
 
     // First, prefer external addresses (direct connectivity)
-    for addr in &stats.external_addresses {
-        if !addr.contains("127.0.0.1") && !addr.contains("::1") {
-            // Ensure address includes peer ID
-            if addr.contains("/p2p/") {
-                addresses.push(addr.clone());
-            } else {
-                addresses.push(format!("{}/p2p/{}", addr, peer_id));
-            }
+    for addr in shareable_external_addresses(&stats) {
+        // Ensure address includes peer ID
+        if addr.contains("/p2p/") {
+            addresses.push(addr.clone());
+        } else {
+            addresses.push(format!("{}/p2p/{}", addr, peer_id));
         }
     }
 
@@ -304,6 +383,15 @@ pub async fn get_shareable_addresses(
     Ok(addresses)
 }
 
+/// External addresses the address filter allows us to hand out
+fn shareable_external_addresses(stats: &NetworkStats) -> impl Iterator<Item = &String> {
+    stats.external_addresses.iter().filter(|addr| {
+        addr.parse::<libp2p::Multiaddr>()
+            .map(|parsed| stats.address_filter.allows(&parsed))
+            .unwrap_or(false)
+    })
+}
+
 /// Add a custom relay server address
 #[tauri::command]
 pub async fn add_relay_server(
@@ -319,6 +407,14 @@ pub async fn add_relay_server(
     handle.add_relay_server(addr).await
 }
 
+/// Re-dial dropped relays and bootstrap nodes now instead of waiting for
+/// the next reconnection pass, e.g. when the OS reports it's back online
+#[tauri::command]
+pub async fn reconcile_connections(network: State<'_, NetworkState>) -> Result<(), AppError> {
+    let handle: NetworkHandle = network.get_handle().await?;
+    handle.reconcile_connections().await
+}
+
 /// Connect to public relay servers for NAT traversal
 #[tauri::command]
 pub async fn connect_to_public_relays(network: State<'_, NetworkState>) -> Result<(), AppError> {
@@ -336,6 +432,431 @@ pub async fn get_nat_status(
     Ok(stats.nat_status)
 }
 
+/// Where the reported NAT status comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NatStatusSource {
+    /// Set manually by the user via `set_nat_override`
+    Manual,
+    /// Detected by AutoNAT probes and relay reservations
+    Detected,
+}
+
+/// Network diagnostics for troubleshooting connectivity
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct NetworkDiagnostics {
+    pub network_running: bool,
+    /// Effective NAT status used for connection decisions
+    pub nat_status: NatStatus,
+    /// Whether `nat_status` is a manual override or detected
+    pub nat_status_source: NatStatusSource,
+    /// What AutoNAT/relay detection reports, regardless of any override
+    pub detected_nat_status: NatStatus,
+    pub nat_override: Option<NatStatus>,
+    pub autonat_enabled: bool,
+    pub address_filter: AddressFilter,
+    pub connected_peers: usize,
+    pub relay_addresses: Vec<String>,
+    /// Circuit limits advertised by each relay in use
+    pub relay_limits: Vec<RelayCircuitLimits>,
+    /// Ping liveness of each relay we hold a reservation on
+    pub relay_liveness: Vec<RelayLiveness>,
+    pub external_addresses: Vec<String>,
+    pub uptime_seconds: u64,
+}
+
+/// A recorded connection failure, for diagnosing transport problems
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ConnectionFailureInfo {
+    pub peer_id: Option<String>,
+    pub address: Option<String>,
+    /// A `ConnectionFailureCategory`, e.g. `handshake` or `timeout`
+    pub category: String,
+    pub detail: String,
+    pub occurred_at: i64,
+}
+
+impl From<ConnectionFailure> for ConnectionFailureInfo {
+    fn from(failure: ConnectionFailure) -> Self {
+        Self {
+            peer_id: failure.peer_id,
+            address: failure.address,
+            category: failure.category,
+            detail: failure.detail,
+            occurred_at: failure.occurred_at,
+        }
+    }
+}
+
+/// Load the persisted address filter, falling back to the default for
+/// missing or unrecognized values
+fn load_address_filter(db: &Database) -> Result<AddressFilter, AppError> {
+    Ok(SettingsRepository::get(db, SETTING_ADDRESS_FILTER)?
+        .and_then(|value| value.parse::<AddressFilter>().ok())
+        .unwrap_or_default())
+}
+
+/// Load the persisted sync strategy, falling back to the default for missing,
+/// unparseable or invalid values
+fn load_sync_strategy(db: &Database) -> Result<SyncStrategy, AppError> {
+    Ok(SettingsRepository::get(db, SETTING_SYNC_STRATEGY)?
+        .and_then(|value| serde_json::from_str::<SyncStrategy>(&value).ok())
+        .filter(|strategy| strategy.validate().is_ok())
+        .unwrap_or_default())
+}
+
+/// Load the persisted NAT override, ignoring unrecognized values
+/// Enabled bootstrap nodes, skipping any whose address no longer parses
+fn load_bootstrap_nodes(db: &Database) -> Result<Vec<libp2p::Multiaddr>, AppError> {
+    Ok(BootstrapNodesRepo::get_all(db, true)?
+        .into_iter()
+        .filter_map(|node| node.address.parse().ok())
+        .collect())
+}
+
+/// Network config with the persisted NAT, port and other network settings applied
+fn load_network_config(db: &Database) -> Result<NetworkConfig, AppError> {
+    let ports = load_listen_ports(db)?;
+    Ok(NetworkConfig {
+        tcp_port: ports.tcp_port,
+        quic_port: ports.quic_port,
+        port_fallback: ports.port_fallback,
+        enable_autonat: SettingsRepository::get_bool(db, SETTING_AUTONAT_ENABLED, true)?,
+        nat_override: load_nat_override(db)?,
+        address_filter: load_address_filter(db)?,
+        auto_request_identity: SettingsRepository::get_bool(
+            db,
+            SETTING_AUTO_REQUEST_IDENTITY,
+            false,
+        )?,
+        enable_heartbeat: SettingsRepository::get_bool(db, SETTING_HEARTBEAT_ENABLED, false)?,
+        heartbeat_interval: load_heartbeat_interval(db)?,
+        sync_strategy: load_sync_strategy(db)?,
+        bootstrap_nodes: load_bootstrap_nodes(db)?,
+        reconcile_interval: load_reconcile_interval(db)?,
+        ..NetworkConfig::default()
+    })
+}
+
+fn load_heartbeat_interval(db: &Database) -> Result<Duration, AppError> {
+    Ok(SettingsRepository::get(db, SETTING_HEARTBEAT_INTERVAL)?
+        .and_then(|value| value.parse::<u64>().ok())
+        .filter(|secs| *secs > 0)
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| NetworkConfig::default().heartbeat_interval))
+}
+
+fn load_reconcile_interval(db: &Database) -> Result<Duration, AppError> {
+    Ok(SettingsRepository::get(db, SETTING_RECONCILE_INTERVAL)?
+        .and_then(|value| value.parse::<u64>().ok())
+        .map(Duration::from_secs)
+        .unwrap_or_else(|| NetworkConfig::default().reconcile_interval))
+}
+
+fn load_nat_override(db: &Database) -> Result<Option<NatStatus>, AppError> {
+    Ok(SettingsRepository::get(db, SETTING_NAT_OVERRIDE)?
+        .and_then(|value| value.parse::<NatStatus>().ok()))
+}
+
+/// Get network diagnostics, including whether the NAT status is a manual override.
+/// Works while the network is stopped, reporting the persisted settings.
+#[tauri::command]
+pub async fn get_network_diagnostics(
+    network: State<'_, NetworkState>,
+    db: State<'_, Arc<Database>>,
+) -> Result<NetworkDiagnostics, AppError> {
+    let stats = match network.get_handle().await {
+        Ok(handle) => Some(handle.get_stats().await?),
+        Err(_) => None,
+    };
+
+    let diagnostics = match stats {
+        Some(stats) => NetworkDiagnostics {
+            network_running: true,
+            nat_status: stats.nat_status,
+            nat_status_source: if stats.nat_override.is_some() {
+                NatStatusSource::Manual
+            } else {
+                NatStatusSource::Detected
+            },
+            detected_nat_status: stats.detected_nat_status,
+            nat_override: stats.nat_override,
+            autonat_enabled: stats.autonat_enabled,
+            address_filter: stats.address_filter,
+            connected_peers: stats.connected_peers,
+            relay_addresses: stats.relay_addresses,
+            relay_limits: stats.relay_limits,
+            relay_liveness: stats.relay_liveness,
+            external_addresses: stats.external_addresses,
+            uptime_seconds: stats.uptime_seconds,
+        },
+        None => {
+            let nat_override = load_nat_override(&db)?;
+            NetworkDiagnostics {
+                network_running: false,
+                nat_status: nat_override.unwrap_or_default(),
+                nat_status_source: if nat_override.is_some() {
+                    NatStatusSource::Manual
+                } else {
+                    NatStatusSource::Detected
+                },
+                detected_nat_status: NatStatus::Unknown,
+                nat_override,
+                autonat_enabled: SettingsRepository::get_bool(&db, SETTING_AUTONAT_ENABLED, true)?,
+                address_filter: load_address_filter(&db)?,
+                connected_peers: 0,
+                relay_addresses: Vec::new(),
+                relay_limits: Vec::new(),
+                relay_liveness: Vec::new(),
+                external_addresses: Vec::new(),
+                uptime_seconds: 0,
+            }
+        }
+    };
+
+    Ok(diagnostics)
+}
+
+/// Manually set the NAT status, taking precedence over AutoNAT results.
+/// Pass `None` to clear the override. The setting is persisted and applied
+/// immediately if the network is running.
+#[tauri::command]
+pub async fn set_nat_override(
+    network: State<'_, NetworkState>,
+    db: State<'_, Arc<Database>>,
+    status: Option<NatStatus>,
+) -> Result<(), AppError> {
+    match status {
+        Some(status) => SettingsRepository::set(&db, SETTING_NAT_OVERRIDE, status.as_str())?,
+        None => {
+            SettingsRepository::delete(&db, SETTING_NAT_OVERRIDE)?;
+        }
+    }
+
+    if let Ok(handle) = network.get_handle().await {
+        handle.set_nat_override(status).await?;
+    }
+    Ok(())
+}
+
+/// Enable or disable AutoNAT probing. Takes effect the next time the network starts.
+#[tauri::command]
+pub async fn set_autonat_enabled(
+    db: State<'_, Arc<Database>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_AUTONAT_ENABLED, &enabled.to_string())?;
+    Ok(())
+}
+
+/// Configured listen ports, as persisted in settings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ListenPortSettings {
+    /// TCP port to listen on (0 = OS-assigned)
+    pub tcp_port: u16,
+    /// QUIC port to listen on (0 = OS-assigned)
+    pub quic_port: u16,
+    /// Listen on a random port instead of failing when a fixed port is taken
+    pub port_fallback: bool,
+}
+
+/// Load the persisted listen ports, treating missing or invalid values as 0
+fn load_listen_ports(db: &Database) -> Result<ListenPortSettings, AppError> {
+    let load_port = |key| -> Result<u16, AppError> {
+        Ok(SettingsRepository::get(db, key)?
+            .and_then(|value| value.parse::<u16>().ok())
+            .unwrap_or(0))
+    };
+    Ok(ListenPortSettings {
+        tcp_port: load_port(SETTING_TCP_PORT)?,
+        quic_port: load_port(SETTING_QUIC_PORT)?,
+        port_fallback: SettingsRepository::get_bool(db, SETTING_PORT_FALLBACK, false)?,
+    })
+}
+
+/// Get the configured listen ports
+#[tauri::command]
+pub async fn get_listen_ports(
+    db: State<'_, Arc<Database>>,
+) -> Result<ListenPortSettings, AppError> {
+    load_listen_ports(&db)
+}
+
+/// Set the ports to listen on. Takes effect the next time the network starts.
+#[tauri::command]
+pub async fn set_listen_ports(
+    db: State<'_, Arc<Database>>,
+    settings: ListenPortSettings,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_TCP_PORT, &settings.tcp_port.to_string())?;
+    SettingsRepository::set(&db, SETTING_QUIC_PORT, &settings.quic_port.to_string())?;
+    SettingsRepository::set(
+        &db,
+        SETTING_PORT_FALLBACK,
+        &settings.port_fallback.to_string(),
+    )?;
+    Ok(())
+}
+
+/// Get the peers in the client's Kademlia routing table, grouped by k-bucket.
+/// Peer addresses are only included in debug builds, since they reveal
+/// where other users can be reached.
+#[tauri::command]
+pub async fn get_dht_routing_table(
+    network: State<'_, NetworkState>,
+) -> Result<DhtRoutingTable, AppError> {
+    let handle: NetworkHandle = network.get_handle().await?;
+    handle.get_dht_routing_table(cfg!(debug_assertions)).await
+}
+
+/// Get the ports the running network actually bound to, which differ from
+/// the configured ones for port 0 or after a random port fallback
+#[tauri::command]
+pub async fn get_bound_ports(network: State<'_, NetworkState>) -> Result<BoundPorts, AppError> {
+    let handle: NetworkHandle = network.get_handle().await?;
+    handle.get_bound_ports().await
+}
+
+/// Get recent connection failures, newest first, optionally only for one peer.
+/// Kept across restarts so support can see what failed and why.
+#[tauri::command]
+pub async fn get_connection_failures(
+    db: State<'_, Arc<Database>>,
+    peer_id: Option<String>,
+    limit: Option<i64>,
+) -> Result<Vec<ConnectionFailureInfo>, AppError> {
+    let limit = limit.unwrap_or(100).clamp(1, MAX_CONNECTION_FAILURES);
+    Ok(
+        ConnectionFailuresRepository::get_recent(&db, peer_id.as_deref(), limit)?
+            .into_iter()
+            .map(ConnectionFailureInfo::from)
+            .collect(),
+    )
+}
+
+/// Delete the recorded connection failures. Returns how many were deleted.
+#[tauri::command]
+pub async fn clear_connection_failures(db: State<'_, Arc<Database>>) -> Result<usize, AppError> {
+    Ok(ConnectionFailuresRepository::clear(&db)?)
+}
+
+/// Whether newly connected non-contacts are automatically asked for their identity
+#[tauri::command]
+pub async fn get_auto_request_identity(db: State<'_, Arc<Database>>) -> Result<bool, AppError> {
+    Ok(SettingsRepository::get_bool(
+        &db,
+        SETTING_AUTO_REQUEST_IDENTITY,
+        false,
+    )?)
+}
+
+/// Enable or disable automatic identity requests to newly connected
+/// non-contacts. Answers are reported as nearby peers and never added as
+/// contacts. Takes effect the next time the network starts.
+#[tauri::command]
+pub async fn set_auto_request_identity(
+    db: State<'_, Arc<Database>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_AUTO_REQUEST_IDENTITY, &enabled.to_string())?;
+    Ok(())
+}
+
+/// Set which of our addresses are announced to remote peers. Takes effect the
+/// next time the network starts.
+#[tauri::command]
+pub async fn set_address_filter(
+    db: State<'_, Arc<Database>>,
+    filter: AddressFilter,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_ADDRESS_FILTER, filter.as_str())?;
+    Ok(())
+}
+
+/// Whether signed presence heartbeats are exchanged with connected contacts
+#[tauri::command]
+pub async fn get_heartbeat_enabled(db: State<'_, Arc<Database>>) -> Result<bool, AppError> {
+    Ok(SettingsRepository::get_bool(
+        &db,
+        SETTING_HEARTBEAT_ENABLED,
+        false,
+    )?)
+}
+
+/// Enable or disable presence heartbeats to connected contacts. Takes effect
+/// the next time the network starts.
+#[tauri::command]
+pub async fn set_heartbeat_enabled(
+    db: State<'_, Arc<Database>>,
+    enabled: bool,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_HEARTBEAT_ENABLED, &enabled.to_string())?;
+    Ok(())
+}
+
+/// Get the seconds between presence heartbeats
+#[tauri::command]
+pub async fn get_heartbeat_interval(db: State<'_, Arc<Database>>) -> Result<u64, AppError> {
+    Ok(load_heartbeat_interval(&db)?.as_secs())
+}
+
+/// Set the seconds between presence heartbeats. Takes effect the next time
+/// the network starts.
+#[tauri::command]
+pub async fn set_heartbeat_interval(
+    db: State<'_, Arc<Database>>,
+    secs: u64,
+) -> Result<(), AppError> {
+    if secs == 0 {
+        return Err(AppError::Validation(
+            "Heartbeat interval must be at least one second".to_string(),
+        ));
+    }
+    SettingsRepository::set(&db, SETTING_HEARTBEAT_INTERVAL, &secs.to_string())?;
+    Ok(())
+}
+
+/// Get the seconds between reconnection passes over relays and bootstrap
+/// nodes (0 = off)
+#[tauri::command]
+pub async fn get_reconcile_interval(db: State<'_, Arc<Database>>) -> Result<u64, AppError> {
+    Ok(load_reconcile_interval(&db)?.as_secs())
+}
+
+/// Set the seconds between reconnection passes over relays and bootstrap
+/// nodes (0 = off). Takes effect the next time the network starts.
+#[tauri::command]
+pub async fn set_reconcile_interval(
+    db: State<'_, Arc<Database>>,
+    secs: u64,
+) -> Result<(), AppError> {
+    SettingsRepository::set(&db, SETTING_RECONCILE_INTERVAL, &secs.to_string())?;
+    Ok(())
+}
+
+/// Get how feed sync picks which connected peers to ask
+#[tauri::command]
+pub async fn get_sync_strategy(db: State<'_, Arc<Database>>) -> Result<SyncStrategy, AppError> {
+    load_sync_strategy(&db)
+}
+
+/// Set how feed sync picks which connected peers to ask. Takes effect the
+/// next time the network starts.
+#[tauri::command]
+pub async fn set_sync_strategy(
+    db: State<'_, Arc<Database>>,
+    strategy: SyncStrategy,
+) -> Result<(), AppError> {
+    strategy.validate()?;
+    let value =
+        serde_json::to_string(&strategy).map_err(|e| AppError::Serialization(e.to_string()))?;
+    SettingsRepository::set(&db, SETTING_SYNC_STRATEGY, &value)?;
+    Ok(())
+}
+
 /// Trigger feed sync from connected peers
 #[tauri::command]
 pub async fn sync_feed(
@@ -346,6 +867,20 @@ pub async fn sync_feed(
     handle.sync_feed(limit.unwrap_or(50)).await
 }
 
+/// Sync content from a single peer and wait for the result
+#[tauri::command]
+pub async fn sync_with_peer(
+    network: State<'_, NetworkState>,
+    peer_id: String,
+    limit: Option<u32>,
+) -> Result<PeerSyncSummary, AppError> {
+    let peer_id: libp2p::PeerId = peer_id
+        .parse()
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
+    let handle: NetworkHandle = network.get_handle().await?;
+    handle.sync_with_peer(peer_id, limit.unwrap_or(50)).await
+}
+
 /// Contact bundle for sharing - contains everything needed to add someone as a contact
 #[derive(serde::Serialize, serde::Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -389,9 +924,8 @@ pub async fn get_shareable_contact_string(
     let multiaddr = if !stats.relay_addresses.is_empty() {
         // Prefer relay addresses as they work through NAT
         stats.relay_addresses[0].clone()
-    } else if !stats.external_addresses.is_empty() {
+    } else if let Some(addr) = shareable_external_addresses(&stats).next() {
         // Use external address if available
-        let addr = &stats.external_addresses[0];
         if addr.contains("/p2p/") {
             addr.clone()
         } else {
@@ -479,9 +1013,9 @@ pub async fn add_contact_from_string(
         bundle.bio.as_deref(),
     )?;
 
-    // Grant them permissions (WallRead and Chat by default)
+    // Grant them permissions (WallRead plus the contact defaults)
     let _ = permissions_service.create_permission_grant(&peer_id, Capability::WallRead, None);
-    let _ = permissions_service.create_permission_grant(&peer_id, Capability::Chat, None);
+    let _ = permissions_service.grant_contact_defaults(&peer_id);
 
     // Connect to them
     let handle: NetworkHandle = network.get_handle().await?;
@@ -500,3 +1034,66 @@ pub async fn add_contact_from_string(
 
     Ok(peer_id)
 }
+
+/// Get our identity as a compact, version-tagged binary payload for rendering as a QR code.
+/// Unlike the contact string this carries no address; the peer is found via relay/DHT.
+#[tauri::command]
+pub async fn get_identity_qr_payload(
+    identity_service: State<'_, Arc<IdentityService>>,
+) -> Result<Vec<u8>, AppError> {
+    identity_service.get_identity_qr_payload()
+}
+
+/// Add a contact from a scanned identity QR payload
+#[tauri::command]
+pub async fn add_contact_from_qr_payload(
+    contacts_service: State<'_, Arc<ContactsService>>,
+    permissions_service: State<'_, Arc<PermissionsService>>,
+    payload: Vec<u8>,
+) -> Result<String, AppError> {
+    use crate::db::Capability;
+
+    let payload = IdentityQrPayload::decode(&payload)?;
+    contacts_service.add_contact_from_qr(&payload)?;
+
+    // Grant them permissions (WallRead plus the contact defaults)
+    let _ =
+        permissions_service.create_permission_grant(&payload.peer_id, Capability::WallRead, None);
+    let _ = permissions_service.grant_contact_defaults(&payload.peer_id);
+
+    info!(
+        "Added contact {} ({}) from QR payload",
+        payload.display_name, payload.peer_id
+    );
+
+    Ok(payload.peer_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_heartbeat_settings_reach_network_config() {
+        let db = Database::in_memory().unwrap();
+        let config = load_network_config(&db).unwrap();
+        assert!(!config.enable_heartbeat);
+        assert_eq!(
+            config.heartbeat_interval,
+            NetworkConfig::default().heartbeat_interval
+        );
+
+        SettingsRepository::set(&db, SETTING_HEARTBEAT_ENABLED, "true").unwrap();
+        SettingsRepository::set(&db, SETTING_HEARTBEAT_INTERVAL, "15").unwrap();
+        let config = load_network_config(&db).unwrap();
+        assert!(config.enable_heartbeat);
+        assert_eq!(config.heartbeat_interval, Duration::from_secs(15));
+
+        // A zero interval is unusable for the timer, so the default is kept
+        SettingsRepository::set(&db, SETTING_HEARTBEAT_INTERVAL, "0").unwrap();
+        assert_eq!(
+            load_network_config(&db).unwrap().heartbeat_interval,
+            NetworkConfig::default().heartbeat_interval
+        );
+    }
+}
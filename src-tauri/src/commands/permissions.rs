@@ -1,12 +1,15 @@
 //! Tauri commands for permission management
 
+use libp2p::PeerId;
 use serde::{Deserialize, Serialize};
+use std::str::FromStr;
 use std::sync::Arc;
 use tauri::State;
 
+use crate::commands::network::NetworkState;
 use crate::db::Capability;
 use crate::error::AppError;
-use crate::services::PermissionsService;
+use crate::services::{ContactsService, PermissionsService};
 
 /// Permission info for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,6 +23,25 @@ pub struct PermissionInfo {
     pub is_valid: bool,
 }
 
+/// A grant or revocation between us and a peer, for the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionHistoryEntry {
+    pub event_id: String,
+    /// "grant" or "revoke"
+    pub event_type: String,
+    pub grant_id: String,
+    pub capability: String,
+    pub issuer_peer_id: String,
+    pub subject_peer_id: String,
+    /// "granted" if we issued it to the peer, "received" if they issued it to us
+    pub direction: String,
+    pub timestamp: i64,
+    pub expires_at: Option<i64>,
+    /// Hex-encoded issuer signature over the event's payload
+    pub signature: String,
+}
+
 /// Permission grant result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct GrantResult {
@@ -35,9 +57,22 @@ fn capability_from_str(s: &str) -> Result<Capability, AppError> {
         .ok_or_else(|| AppError::Validation(format!("Invalid capability: {}", s)))
 }
 
+/// Send new grants to the subject right away if they're connected. If not,
+/// or if this is lost, they are resent when the subject next connects.
+async fn deliver_grants(network: &NetworkState, subject_peer_id: &str) {
+    let (Ok(handle), Ok(peer_id)) = (
+        network.get_handle().await,
+        PeerId::from_str(subject_peer_id),
+    ) else {
+        return;
+    };
+    let _ = handle.reconcile_permissions(peer_id).await;
+}
+
 /// Grant a permission to another peer
 #[tauri::command]
 pub async fn grant_permission(
+    network: State<'_, NetworkState>,
     permissions_service: State<'_, Arc<PermissionsService>>,
     subject_peer_id: String,
     capability: String,
@@ -46,6 +81,7 @@ pub async fn grant_permission(
     let cap = capability_from_str(&capability)?;
     let grant =
         permissions_service.create_permission_grant(&subject_peer_id, cap, expires_in_seconds)?;
+    deliver_grants(&network, &subject_peer_id).await;
 
     Ok(GrantResult {
         grant_id: grant.grant_id,
@@ -56,6 +92,36 @@ pub async fn grant_permission(
     })
 }
 
+/// Grant a permission to every member of a contact group
+#[tauri::command]
+pub async fn grant_group_permission(
+    network: State<'_, NetworkState>,
+    contacts_service: State<'_, Arc<ContactsService>>,
+    permissions_service: State<'_, Arc<PermissionsService>>,
+    group_id: String,
+    capability: String,
+    expires_in_seconds: Option<i64>,
+) -> Result<Vec<GrantResult>, AppError> {
+    let cap = capability_from_str(&capability)?;
+    let mut results = Vec::new();
+
+    for peer_id in contacts_service.get_group_member_ids(&group_id)? {
+        let grant =
+            permissions_service.create_permission_grant(&peer_id, cap, expires_in_seconds)?;
+        deliver_grants(&network, &peer_id).await;
+
+        results.push(GrantResult {
+            grant_id: grant.grant_id,
+            capability: grant.capability,
+            subject_peer_id: grant.subject_peer_id,
+            issued_at: grant.issued_at,
+            expires_at: grant.expires_at,
+        });
+    }
+
+    Ok(results)
+}
+
 /// Revoke a permission
 #[tauri::command]
 pub async fn revoke_permission(
@@ -134,6 +200,30 @@ pub async fn get_received_permissions(
         .collect())
 }
 
+/// Get every grant and revocation between us and a peer, oldest first
+#[tauri::command]
+pub async fn get_permission_history(
+    permissions_service: State<'_, Arc<PermissionsService>>,
+    peer_id: String,
+) -> Result<Vec<PermissionHistoryEntry>, AppError> {
+    Ok(permissions_service
+        .get_permission_history(&peer_id)?
+        .into_iter()
+        .map(|event| PermissionHistoryEntry {
+            event_id: event.event_id,
+            event_type: event.event_type,
+            grant_id: event.grant_id,
+            capability: event.capability,
+            issuer_peer_id: event.issuer_peer_id,
+            subject_peer_id: event.subject_peer_id,
+            direction: event.direction.as_str().to_string(),
+            timestamp: event.timestamp,
+            expires_at: event.expires_at,
+            signature: hex::encode(event.signature),
+        })
+        .collect())
+}
+
 /// Get all peers we can chat with
 #[tauri::command]
 pub async fn get_chat_peers(
@@ -142,15 +232,17 @@ pub async fn get_chat_peers(
     permissions_service.get_chat_peers()
 }
 
-/// Grant all standard permissions to a peer (chat, wall_read, call)
+/// Grant every capability to a peer (chat, wall_read, call, board_post, file_transfer,
+/// share_contacts)
 #[tauri::command]
 pub async fn grant_all_permissions(
+    network: State<'_, NetworkState>,
     permissions_service: State<'_, Arc<PermissionsService>>,
     subject_peer_id: String,
 ) -> Result<Vec<GrantResult>, AppError> {
     let mut results = Vec::new();
 
-    for cap in [Capability::Chat, Capability::WallRead, Capability::Call] {
+    for cap in Capability::ALL {
         let grant = permissions_service.create_permission_grant(&subject_peer_id, cap, None)?;
 
         results.push(GrantResult {
@@ -161,6 +253,47 @@ pub async fn grant_all_permissions(
             expires_at: grant.expires_at,
         });
     }
+    deliver_grants(&network, &subject_peer_id).await;
 
     Ok(results)
 }
+
+/// Get the capabilities granted to new contacts
+#[tauri::command]
+pub async fn get_default_grant_policy(
+    permissions_service: State<'_, Arc<PermissionsService>>,
+) -> Result<Vec<String>, AppError> {
+    Ok(permissions_service
+        .get_default_grant_policy()?
+        .into_iter()
+        .map(|cap| cap.as_str().to_string())
+        .collect())
+}
+
+/// Set the capabilities granted to new contacts. An empty list grants nothing.
+#[tauri::command]
+pub async fn set_default_grant_policy(
+    permissions_service: State<'_, Arc<PermissionsService>>,
+    capabilities: Vec<String>,
+) -> Result<(), AppError> {
+    let capabilities = capabilities
+        .iter()
+        .map(|s| capability_from_str(s))
+        .collect::<Result<Vec<_>, _>>()?;
+    permissions_service.set_default_grant_policy(&capabilities)
+}
+
+/// Resend any grants a connected peer hasn't acknowledged yet
+#[tauri::command]
+pub async fn reconcile_permissions(
+    network: State<'_, NetworkState>,
+    peer_id: String,
+) -> Result<(), AppError> {
+    let peer_id = PeerId::from_str(&peer_id)
+        .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?;
+    network
+        .get_handle()
+        .await?
+        .reconcile_permissions(peer_id)
+        .await
+}
@@ -4,7 +4,7 @@ use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use tauri::State;
 
-use crate::db::repositories::{Post, PostMedia, PostVisibility};
+use crate::db::repositories::{Post, PostEvent, PostMedia, PostVisibility};
 use crate::error::AppError;
 use crate::services::posts_service::AddMediaParams;
 use crate::services::PostsService;
@@ -23,6 +23,9 @@ pub struct PostInfo {
     pub updated_at: i64,
     pub deleted_at: Option<i64>,
     pub is_local: bool,
+    pub pinned: bool,
+    /// When a community relay received the post (signed by the relay)
+    pub relay_observed_at: Option<i64>,
 }
 
 impl From<Post> for PostInfo {
@@ -38,6 +41,8 @@ impl From<Post> for PostInfo {
             updated_at: post.updated_at,
             deleted_at: post.deleted_at,
             is_local: post.is_local,
+            pinned: post.pinned,
+            relay_observed_at: post.relay_observed_at,
         }
     }
 }
@@ -77,6 +82,39 @@ impl From<PostMedia> for PostMediaInfo {
     }
 }
 
+/// Recorded post event for the frontend (sync diagnostics)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostEventInfo {
+    pub event_id: String,
+    /// created, updated, deleted or received
+    pub event_type: String,
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub lamport_clock: i64,
+    pub timestamp: i64,
+    /// Hex of the signed CBOR payload
+    pub payload_hex: Option<String>,
+    pub signature_hex: String,
+    pub received_at: i64,
+}
+
+impl From<PostEvent> for PostEventInfo {
+    fn from(event: PostEvent) -> Self {
+        Self {
+            event_id: event.event_id,
+            event_type: event.event_type,
+            post_id: event.post_id,
+            author_peer_id: event.author_peer_id,
+            lamport_clock: event.lamport_clock,
+            timestamp: event.timestamp,
+            payload_hex: event.payload_cbor.map(hex::encode),
+            signature_hex: hex::encode(event.signature),
+            received_at: event.received_at,
+        }
+    }
+}
+
 /// Create post result for the frontend
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -86,6 +124,10 @@ pub struct CreatePostResult {
 }
 
 /// Create a new post
+///
+/// Unless `push_to_contacts` is false, a shareable post is also pushed to
+/// contacts with wall access who are online right now. Track the push with
+/// `get_post_delivery_status`.
 #[tauri::command]
 pub async fn create_post(
     posts_service: State<'_, Arc<PostsService>>,
@@ -93,9 +135,11 @@ pub async fn create_post(
     content_type: String,
     content_text: Option<String>,
     visibility: Option<String>,
+    push_to_contacts: Option<bool>,
 ) -> Result<CreatePostResult, AppError> {
     let vis = match visibility.as_deref() {
         Some("public") => PostVisibility::Public,
+        Some("private") => PostVisibility::Private,
         _ => PostVisibility::Contacts, // Default to contacts-only
     };
 
@@ -104,40 +148,58 @@ pub async fn create_post(
     // Auto-sync: submit the new post to the relay in the background.
     // We don't fail the command if relay submission fails -- the user can
     // always manually sync later via sync_wall_to_relay.
-    if let Ok(handle) = network_state.get_handle().await {
-        if let Ok(stats) = handle.get_stats().await {
-            if let Ok(relay_peer_id) =
-                crate::commands::wall_sync::find_relay_peer_id(&stats.relay_addresses)
-            {
+    // Private posts never leave this device.
+    if vis.is_shareable() {
+        if let Ok(handle) = network_state.get_handle().await {
+            // Online contacts get the post right away; the rest pull it later
+            if push_to_contacts.unwrap_or(true) {
+                let push_handle = handle.clone();
                 let post_id = outgoing.post_id.clone();
-                let ct = outgoing.content_type.clone();
-                let ct_text = outgoing.content_text.clone();
-                let vis_str = outgoing.visibility.clone();
-                let lc = outgoing.lamport_clock as i64;
-                let ca = outgoing.created_at;
-                let sig = outgoing.signature.clone();
-                // Fire and forget -- don't block post creation on relay submission
-                // Media is added separately via add_post_media, so pass empty vec here.
-                // The full wall sync (sync_wall_to_relay) will include media metadata.
                 tokio::spawn(async move {
-                    if let Err(e) = handle
-                        .submit_wall_post_to_relay(
-                            relay_peer_id,
-                            post_id.clone(),
-                            ct,
-                            ct_text,
-                            vis_str,
-                            lc,
-                            ca,
-                            sig,
-                            Vec::new(),
-                        )
-                        .await
-                    {
-                        tracing::warn!("Failed to auto-sync wall post {} to relay: {}", post_id, e);
+                    if let Err(e) = push_handle.announce_post(post_id.clone()).await {
+                        tracing::warn!("Failed to push post {} to contacts: {}", post_id, e);
                     }
                 });
             }
+
+            if let Ok(stats) = handle.get_stats().await {
+                if let Ok(relay_peer_id) =
+                    crate::commands::wall_sync::find_relay_peer_id(&stats.relay_addresses)
+                {
+                    let post_id = outgoing.post_id.clone();
+                    let ct = outgoing.content_type.clone();
+                    let ct_text = outgoing.content_text.clone();
+                    let vis_str = outgoing.visibility.clone();
+                    let lc = outgoing.lamport_clock as i64;
+                    let ca = outgoing.created_at;
+                    let sig = outgoing.signature.clone();
+                    // Fire and forget -- don't block post creation on relay submission
+                    // Media is added separately via add_post_media, so pass empty vec here.
+                    // The full wall sync (sync_wall_to_relay) will include media metadata.
+                    tokio::spawn(async move {
+                        if let Err(e) = handle
+                            .submit_wall_post_to_relay(
+                                relay_peer_id,
+                                post_id.clone(),
+                                ct,
+                                ct_text,
+                                vis_str,
+                                lc,
+                                ca,
+                                sig,
+                                Vec::new(),
+                            )
+                            .await
+                        {
+                            tracing::warn!(
+                                "Failed to auto-sync wall post {} to relay: {}",
+                                post_id,
+                                e
+                            );
+                        }
+                    });
+                }
+            }
         }
     }
 
@@ -189,6 +251,26 @@ pub async fn delete_post(
     Ok(())
 }
 
+/// Pin one of our posts to the top of our wall (replaces any previous pin)
+#[tauri::command]
+pub async fn pin_post(
+    posts_service: State<'_, Arc<PostsService>>,
+    post_id: String,
+) -> Result<(), AppError> {
+    posts_service.pin_post(&post_id)?;
+    Ok(())
+}
+
+/// Unpin our pinned post
+#[tauri::command]
+pub async fn unpin_post(
+    posts_service: State<'_, Arc<PostsService>>,
+    post_id: String,
+) -> Result<(), AppError> {
+    posts_service.unpin_post(&post_id)?;
+    Ok(())
+}
+
 /// Get a single post by ID
 #[tauri::command]
 pub async fn get_post(
@@ -199,6 +281,18 @@ pub async fn get_post(
     Ok(post.map(PostInfo::from))
 }
 
+/// Get the recorded event history of a post, oldest lamport clock first.
+///
+/// Read-only; for working out why two devices show different versions.
+#[tauri::command]
+pub async fn get_post_events(
+    posts_service: State<'_, Arc<PostsService>>,
+    post_id: String,
+) -> Result<Vec<PostEventInfo>, AppError> {
+    let events = posts_service.get_post_events(&post_id)?;
+    Ok(events.into_iter().map(PostEventInfo::from).collect())
+}
+
 /// Get the local user's posts (their wall)
 #[tauri::command]
 pub async fn get_my_posts(
@@ -269,3 +363,20 @@ pub async fn get_post_media(
     let media = posts_service.get_post_media(&post_id)?;
     Ok(media.into_iter().map(PostMediaInfo::from).collect())
 }
+
+/// Get the most media attachments a post may carry
+#[tauri::command]
+pub async fn get_max_post_attachments(
+    posts_service: State<'_, Arc<PostsService>>,
+) -> Result<usize, AppError> {
+    posts_service.get_max_post_attachments()
+}
+
+/// Set the most media attachments a post may carry, ours or a peer's
+#[tauri::command]
+pub async fn set_max_post_attachments(
+    posts_service: State<'_, Arc<PostsService>>,
+    max: usize,
+) -> Result<(), AppError> {
+    posts_service.set_max_post_attachments(max)
+}
@@ -1,12 +1,22 @@
-//! RSS feed generation commands for Wall posts
+//! RSS feed generation commands for Wall posts, and importing posts from
+//! RSS/Atom feeds
 
-use crate::db::repositories::{PostVisibility, PostsRepository};
-use crate::db::Database;
+use crate::db::repositories::{Post, PostVisibility, PostsRepository};
+use crate::db::{Capability, Database};
 use crate::error::{AppError, Result};
-use crate::services::IdentityService;
+use crate::services::feed_import::{self, FeedEnclosure};
+use crate::services::posts_service::AddMediaParams;
+use crate::services::{IdentityService, MediaStorageService, PermissionsService, PostsService};
+use std::path::PathBuf;
 use std::sync::Arc;
 use tauri::State;
 
+/// Largest feed document that is imported
+const MAX_FEED_BYTES: usize = 10 * 1024 * 1024;
+
+/// Largest enclosure that is imported as post media
+const MAX_ENCLOSURE_BYTES: usize = 50 * 1024 * 1024;
+
 /// RSS feed configuration
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RssFeedConfig {
@@ -31,6 +41,33 @@ impl Default for RssFeedConfig {
     }
 }
 
+/// Which posts a generated feed may carry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RssFeedKind {
+    /// Public posts only; safe to publish at a shareable URL
+    #[default]
+    Public,
+    /// Public and contacts-only posts, for a reader with wall_read access
+    Contacts,
+}
+
+impl RssFeedKind {
+    /// Whether a post with `visibility` belongs in this feed. Private posts
+    /// never do.
+    pub fn includes(&self, visibility: PostVisibility) -> bool {
+        match self {
+            RssFeedKind::Public => visibility == PostVisibility::Public,
+            RssFeedKind::Contacts => {
+                matches!(
+                    visibility,
+                    PostVisibility::Public | PostVisibility::Contacts
+                )
+            }
+        }
+    }
+}
+
 /// RSS feed item
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct RssItem {
@@ -41,69 +78,97 @@ pub struct RssItem {
     pub guid: String,
 }
 
-/// Generate RSS 2.0 XML for the current user's public wall posts
+/// Generate RSS 2.0 XML for the current user's wall posts.
+///
+/// The default public feed carries only public posts. A `contacts` feed also
+/// carries contacts-only posts and is generated for one reader,
+/// `reader_peer_id`, who must hold our wall_read grant.
 #[tauri::command]
 pub async fn generate_rss_feed(
     db: State<'_, Arc<Database>>,
     identity_service: State<'_, Arc<IdentityService>>,
+    permissions_service: State<'_, Arc<PermissionsService>>,
     config: Option<RssFeedConfig>,
+    kind: Option<RssFeedKind>,
+    reader_peer_id: Option<String>,
 ) -> Result<String> {
     let identity = identity_service
         .get_identity()?
         .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
 
+    let kind = kind.unwrap_or_default();
+    if kind == RssFeedKind::Contacts {
+        let reader = reader_peer_id.ok_or_else(|| {
+            AppError::Validation("A contacts feed needs the reader's peer ID".to_string())
+        })?;
+        if !permissions_service.peer_has_capability(&reader, Capability::WallRead)? {
+            return Err(AppError::PermissionDenied(
+                "Reader has no wall_read permission".to_string(),
+            ));
+        }
+    }
+
     let config = config.unwrap_or_else(|| RssFeedConfig {
         base_url: format!("harbor://peer/{}", identity.peer_id),
         title: format!("{}'s Wall", identity.display_name),
         ..Default::default()
     });
 
-    // Get public posts
-    let posts =
-        PostsRepository::get_by_author(&db, &identity.peer_id, config.max_items as i64, None)
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
-
-    // Filter to only public posts
-    let public_posts: Vec<_> = posts
-        .into_iter()
-        .filter(|p| p.visibility == PostVisibility::Public)
-        .collect();
-
-    // Generate RSS XML
-    let rss_xml = generate_rss_xml(&config, &public_posts, &identity.peer_id);
-
-    Ok(rss_xml)
+    let posts = feed_posts(&db, &identity.peer_id, kind, config.max_items)?;
+    Ok(generate_rss_xml(&config, &posts, &identity.peer_id))
 }
 
-/// Generate RSS feed for a specific peer's public posts (for viewing others' feeds)
+/// Generate an RSS feed of a peer's posts we hold (for viewing others' feeds).
+///
+/// The default public feed carries only their public posts; a `contacts`
+/// feed also carries their contacts-only posts and needs their wall_read
+/// grant to us.
 #[tauri::command]
 pub async fn get_peer_rss_feed(
     db: State<'_, Arc<Database>>,
+    permissions_service: State<'_, Arc<PermissionsService>>,
     peer_id: String,
     max_items: Option<usize>,
+    kind: Option<RssFeedKind>,
 ) -> Result<String> {
     let max_items = max_items.unwrap_or(50);
+    let kind = kind.unwrap_or_default();
+    if kind == RssFeedKind::Contacts
+        && !permissions_service.we_have_capability(&peer_id, Capability::WallRead)?
+    {
+        return Err(AppError::PermissionDenied(
+            "No wall_read permission from this peer".to_string(),
+        ));
+    }
 
-    // Get public posts from this peer
-    let posts = PostsRepository::get_by_author(&db, &peer_id, max_items as i64, None)
-        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
-
-    // Filter to only public posts
-    let public_posts: Vec<_> = posts
-        .into_iter()
-        .filter(|p| p.visibility == PostVisibility::Public)
-        .collect();
+    let posts = feed_posts(&db, &peer_id, kind, max_items)?;
 
     let config = RssFeedConfig {
         base_url: format!("harbor://peer/{}", peer_id),
         title: format!("Harbor Wall - {}", &peer_id[..12.min(peer_id.len())]),
-        description: "Public posts from a Harbor user".to_string(),
+        description: match kind {
+            RssFeedKind::Public => "Public posts from a Harbor user".to_string(),
+            RssFeedKind::Contacts => "Posts shared with contacts of a Harbor user".to_string(),
+        },
         max_items,
     };
 
-    let rss_xml = generate_rss_xml(&config, &public_posts, &peer_id);
+    Ok(generate_rss_xml(&config, &posts, &peer_id))
+}
 
-    Ok(rss_xml)
+/// The newest posts by `author_peer_id` that belong in a feed of `kind`
+fn feed_posts(
+    db: &Database,
+    author_peer_id: &str,
+    kind: RssFeedKind,
+    max_items: usize,
+) -> Result<Vec<Post>> {
+    let posts = PostsRepository::get_by_author(db, author_peer_id, max_items as i64, None)
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+    Ok(posts
+        .into_iter()
+        .filter(|post| kind.includes(post.visibility))
+        .collect())
 }
 
 /// Get RSS feed URL for sharing
@@ -117,12 +182,250 @@ pub async fn get_rss_feed_url(identity_service: State<'_, Arc<IdentityService>>)
     Ok(format!("harbor://feed/{}", identity.peer_id))
 }
 
+/// Result of importing posts from a feed
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FeedImportSummary {
+    pub imported: usize,
+    /// Entries already imported by an earlier run
+    pub duplicates: usize,
+    /// Malformed entries that were left out
+    pub skipped: usize,
+    /// Enclosures that couldn't be fetched or aren't images or videos
+    pub media_failed: usize,
+    pub post_ids: Vec<String>,
+}
+
+/// Where a feed is read from. Relative enclosure URLs resolve against it.
+enum FeedSource {
+    Url(reqwest::Url),
+    File(PathBuf),
+}
+
+impl FeedSource {
+    fn parse(url_or_file: &str) -> Self {
+        match reqwest::Url::parse(url_or_file) {
+            Ok(url) if matches!(url.scheme(), "http" | "https") => FeedSource::Url(url),
+            Ok(url) if url.scheme() == "file" => match url.to_file_path() {
+                Ok(path) => FeedSource::File(path),
+                Err(()) => FeedSource::File(PathBuf::from(url_or_file)),
+            },
+            _ => FeedSource::File(PathBuf::from(url_or_file)),
+        }
+    }
+
+    async fn read_feed(&self, client: &reqwest::Client) -> Result<String> {
+        let bytes = match self {
+            FeedSource::Url(url) => download(client, url.clone(), MAX_FEED_BYTES).await?.0,
+            FeedSource::File(path) => std::fs::read(path)?,
+        };
+        if bytes.len() > MAX_FEED_BYTES {
+            return Err(AppError::Validation(
+                "Feed is too large to import".to_string(),
+            ));
+        }
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Fetch an enclosure, returning its bytes and MIME type
+    async fn fetch_enclosure(
+        &self,
+        client: &reqwest::Client,
+        enclosure: &FeedEnclosure,
+    ) -> Result<(Vec<u8>, String)> {
+        let (data, served_type) = match self {
+            FeedSource::Url(base) => {
+                let url = base
+                    .join(&enclosure.url)
+                    .map_err(|e| AppError::Validation(format!("Invalid enclosure URL: {}", e)))?;
+                download(client, url, MAX_ENCLOSURE_BYTES).await?
+            }
+            FeedSource::File(feed_path) => match reqwest::Url::parse(&enclosure.url) {
+                Ok(url) if matches!(url.scheme(), "http" | "https") => {
+                    download(client, url, MAX_ENCLOSURE_BYTES).await?
+                }
+                _ => {
+                    let path = feed_path
+                        .parent()
+                        .unwrap_or(feed_path.as_path())
+                        .join(enclosure.url.trim_start_matches("file://"));
+                    if std::fs::metadata(&path)?.len() > MAX_ENCLOSURE_BYTES as u64 {
+                        return Err(AppError::Validation("Enclosure is too large".to_string()));
+                    }
+                    (std::fs::read(&path)?, None)
+                }
+            },
+        };
+
+        let mime_type = enclosure
+            .mime_type
+            .clone()
+            .or(served_type)
+            .or_else(|| mime_type_for_path(&enclosure.url).map(String::from))
+            .ok_or_else(|| AppError::Validation("Unknown enclosure type".to_string()))?;
+        Ok((data, mime_type))
+    }
+}
+
+/// Download up to `limit` bytes, returning them with the served content type
+async fn download(
+    client: &reqwest::Client,
+    url: reqwest::Url,
+    limit: usize,
+) -> Result<(Vec<u8>, Option<String>)> {
+    let mut response = client
+        .get(url)
+        .send()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to fetch {}", e)))?;
+    if !response.status().is_success() {
+        return Err(AppError::Network(format!(
+            "HTTP error: {}",
+            response.status()
+        )));
+    }
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .map(|v| v.split(';').next().unwrap_or(v).trim().to_string());
+
+    let mut data = Vec::new();
+    while let Some(chunk) = response
+        .chunk()
+        .await
+        .map_err(|e| AppError::Network(format!("Failed to read response: {}", e)))?
+    {
+        if data.len() + chunk.len() > limit {
+            return Err(AppError::Validation(format!(
+                "Response is larger than {} bytes",
+                limit
+            )));
+        }
+        data.extend_from_slice(&chunk);
+    }
+    Ok((data, content_type))
+}
+
+/// Guess a media MIME type from a file name or URL
+fn mime_type_for_path(path: &str) -> Option<&'static str> {
+    let path = path.split(['?', '#']).next().unwrap_or(path);
+    let extension = path.rsplit('.').next()?.to_ascii_lowercase();
+    Some(match extension.as_str() {
+        "jpg" | "jpeg" => "image/jpeg",
+        "png" => "image/png",
+        "gif" => "image/gif",
+        "webp" => "image/webp",
+        "mp4" => "video/mp4",
+        "webm" => "video/webm",
+        "mov" => "video/quicktime",
+        _ => return None,
+    })
+}
+
+/// Import posts from an RSS or Atom feed, given its URL or a file path.
+///
+/// Each entry becomes a post signed with our identity and dated with the
+/// entry's publication time; image and video enclosures become its media.
+/// Entries imported before (matched by guid or link) are not imported again,
+/// and malformed entries are skipped and counted instead of failing the
+/// import. Imported posts are not pushed to contacts; they sync like older
+/// posts.
+#[tauri::command]
+pub async fn import_posts_from_feed(
+    posts_service: State<'_, Arc<PostsService>>,
+    media_service: State<'_, Arc<MediaStorageService>>,
+    url_or_file: String,
+    visibility: Option<String>,
+) -> Result<FeedImportSummary> {
+    let visibility = match visibility.as_deref() {
+        Some("public") => PostVisibility::Public,
+        Some("private") => PostVisibility::Private,
+        _ => PostVisibility::Contacts, // Default to contacts-only
+    };
+
+    let client = reqwest::Client::builder()
+        .timeout(std::time::Duration::from_secs(30))
+        .user_agent("Mozilla/5.0 (compatible; HarborBot/1.0)")
+        .redirect(reqwest::redirect::Policy::limited(5))
+        .build()
+        .map_err(|e| AppError::Network(format!("Failed to create HTTP client: {}", e)))?;
+
+    let source = FeedSource::parse(&url_or_file);
+    let feed = feed_import::parse_feed(&source.read_feed(&client).await?)?;
+
+    let mut summary = FeedImportSummary {
+        skipped: feed.skipped,
+        ..Default::default()
+    };
+
+    // Oldest first, so lamport clocks follow publication order
+    let mut entries = feed.entries;
+    entries.sort_by_key(|entry| entry.published_at.unwrap_or(i64::MAX));
+
+    for entry in &entries {
+        let Some(post) = posts_service.import_feed_entry(entry, visibility)? else {
+            summary.duplicates += 1;
+            continue;
+        };
+
+        let mut sort_order = 0;
+        for enclosure in &entry.enclosures {
+            let fetched = source.fetch_enclosure(&client, enclosure).await;
+            let media_type = match &fetched {
+                Ok((_, mime)) if mime.starts_with("image/") => "image",
+                Ok((_, mime)) if mime.starts_with("video/") => "video",
+                Ok(_) => {
+                    summary.media_failed += 1;
+                    continue;
+                }
+                Err(e) => {
+                    tracing::warn!("Failed to import enclosure {}: {}", enclosure.url, e);
+                    summary.media_failed += 1;
+                    continue;
+                }
+            };
+            let (data, mime_type) = fetched?;
+
+            let media_hash = media_service.store_media(&data, &mime_type)?;
+            let file_name = enclosure
+                .url
+                .split(['?', '#'])
+                .next()
+                .and_then(|path| path.rsplit('/').next())
+                .filter(|name| !name.is_empty())
+                .unwrap_or("enclosure");
+            posts_service.add_media_to_post(&AddMediaParams {
+                post_id: &post.post_id,
+                media_hash: &media_hash,
+                media_type,
+                mime_type: &mime_type,
+                file_name,
+                file_size: data.len() as i64,
+                width: None,
+                height: None,
+                duration_seconds: None,
+                sort_order,
+            })?;
+            sort_order += 1;
+        }
+
+        summary.imported += 1;
+        summary.post_ids.push(post.post_id);
+    }
+
+    tracing::info!(
+        imported = summary.imported,
+        duplicates = summary.duplicates,
+        skipped = summary.skipped,
+        "Imported posts from feed {}",
+        url_or_file
+    );
+    Ok(summary)
+}
+
 /// Generate RSS 2.0 XML from posts
-fn generate_rss_xml(
-    config: &RssFeedConfig,
-    posts: &[crate::db::repositories::Post],
-    peer_id: &str,
-) -> String {
+fn generate_rss_xml(config: &RssFeedConfig, posts: &[Post], peer_id: &str) -> String {
     let now = chrono::Utc::now()
         .format("%a, %d %b %Y %H:%M:%S GMT")
         .to_string();
@@ -208,6 +511,7 @@ fn xml_escape(s: &str) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::db::repositories::PostData;
 
     #[test]
     fn test_xml_escape() {
@@ -216,6 +520,106 @@ mod tests {
         assert_eq!(xml_escape("\"quotes\""), "&quot;quotes&quot;");
     }
 
+    #[test]
+    fn test_feed_source_and_mime_type() {
+        assert!(matches!(
+            FeedSource::parse("https://blog.example.com/feed.xml"),
+            FeedSource::Url(_)
+        ));
+        assert!(matches!(
+            FeedSource::parse("/home/me/export.xml"),
+            FeedSource::File(path) if path == PathBuf::from("/home/me/export.xml")
+        ));
+
+        assert_eq!(
+            mime_type_for_path("https://x.example/a/Photo.JPG?w=800"),
+            Some("image/jpeg")
+        );
+        assert_eq!(mime_type_for_path("clip.webm"), Some("video/webm"));
+        assert_eq!(mime_type_for_path("episode.mp3"), None);
+    }
+
+    #[test]
+    fn test_generated_feed_imports_back() {
+        let config = RssFeedConfig {
+            base_url: "harbor://peer/test123".to_string(),
+            title: "Test Feed".to_string(),
+            description: "A test feed".to_string(),
+            max_items: 10,
+        };
+        let post = Post {
+            id: 1,
+            post_id: "post-1".to_string(),
+            author_peer_id: "test123".to_string(),
+            content_type: "text".to_string(),
+            content_text: Some("First line\nTom & Jerry <3".to_string()),
+            visibility: PostVisibility::Public,
+            lamport_clock: 1,
+            created_at: 1672653600,
+            updated_at: 1672653600,
+            deleted_at: None,
+            signature: Vec::new(),
+            is_local: true,
+            pinned: false,
+            relay_observed_at: None,
+            sig_version: 1,
+        };
+
+        let xml = generate_rss_xml(&config, &[post], "test123");
+        let feed = feed_import::parse_feed(&xml).unwrap();
+        assert_eq!(feed.skipped, 0);
+        assert_eq!(feed.entries[0].key, "harbor:post:test123:post-1");
+        assert_eq!(feed.entries[0].published_at, Some(1672653600));
+        assert_eq!(feed.entries[0].post_text(), "First line\nTom & Jerry <3");
+    }
+
+    #[test]
+    fn test_public_feed_never_carries_contacts_posts() {
+        let db = Database::in_memory().unwrap();
+        for (post_id, visibility, text) in [
+            ("post-public", PostVisibility::Public, "Hello world"),
+            (
+                "post-contacts",
+                PostVisibility::Contacts,
+                "Friends only secret",
+            ),
+            ("post-private", PostVisibility::Private, "Diary entry"),
+        ] {
+            PostsRepository::insert_post(
+                &db,
+                &PostData {
+                    post_id: post_id.to_string(),
+                    author_peer_id: "test123".to_string(),
+                    content_type: "text".to_string(),
+                    content_text: Some(text.to_string()),
+                    visibility,
+                    lamport_clock: 1,
+                    created_at: 1672653600,
+                    signature: Vec::new(),
+                    sig_version: 1,
+                },
+            )
+            .unwrap();
+        }
+        let config = RssFeedConfig {
+            base_url: "harbor://peer/test123".to_string(),
+            ..Default::default()
+        };
+
+        let public = feed_posts(&db, "test123", RssFeedKind::Public, 50).unwrap();
+        let xml = generate_rss_xml(&config, &public, "test123");
+        assert!(xml.contains("Hello world"));
+        assert!(!xml.contains("Friends only secret"));
+        assert!(!xml.contains("post-contacts"));
+        assert!(!xml.contains("Diary entry"));
+
+        let contacts = feed_posts(&db, "test123", RssFeedKind::Contacts, 50).unwrap();
+        let xml = generate_rss_xml(&config, &contacts, "test123");
+        assert!(xml.contains("Hello world"));
+        assert!(xml.contains("Friends only secret"));
+        assert!(!xml.contains("Diary entry"));
+    }
+
     #[test]
     fn test_rss_feed_generation() {
         let config = RssFeedConfig {
@@ -27,7 +27,7 @@ pub async fn sync_wall_to_relay(
     let mut submitted = 0u32;
 
     for post in posts {
-        if post.deleted_at.is_some() {
+        if post.deleted_at.is_some() || !post.visibility.is_shareable() {
             continue;
         }
 
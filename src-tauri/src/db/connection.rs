@@ -1,7 +1,8 @@
-use rusqlite::{Connection, Result as SqliteResult};
-use std::path::PathBuf;
+use rusqlite::{Connection, OpenFlags, OptionalExtension, Result as SqliteResult};
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, MutexGuard};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 const MIGRATION_001: &str = include_str!("migrations/001_initial.sql");
 const MIGRATION_002: &str = include_str!("migrations/002_schema_fixes.sql");
@@ -14,6 +15,50 @@ const MIGRATION_008: &str = include_str!("migrations/008_boards.sql");
 const MIGRATION_009: &str = include_str!("migrations/009_comments.sql");
 const MIGRATION_010: &str = include_str!("migrations/010_message_edit.sql");
 const MIGRATION_011: &str = include_str!("migrations/011_posts_lamport_index.sql");
+const MIGRATION_012: &str = include_str!("migrations/012_board_subscriptions.sql");
+const MIGRATION_013: &str = include_str!("migrations/013_message_nonce_salt.sql");
+const MIGRATION_014: &str = include_str!("migrations/014_post_pins.sql");
+const MIGRATION_015: &str = include_str!("migrations/015_message_retention.sql");
+const MIGRATION_016: &str = include_str!("migrations/016_contact_groups.sql");
+const MIGRATION_017: &str = include_str!("migrations/017_post_views.sql");
+const MIGRATION_018: &str = include_str!("migrations/018_post_deliveries.sql");
+const MIGRATION_019: &str = include_str!("migrations/019_contact_name_history.sql");
+const MIGRATION_020: &str = include_str!("migrations/020_default_contact_grants.sql");
+const MIGRATION_021: &str = include_str!("migrations/021_received_message_clocks.sql");
+const MIGRATION_022: &str = include_str!("migrations/022_permission_acks.sql");
+const MIGRATION_023: &str = include_str!("migrations/023_relay_timestamps.sql");
+const MIGRATION_024: &str = include_str!("migrations/024_activity_feed.sql");
+const MIGRATION_025: &str = include_str!("migrations/025_signature_versions.sql");
+const MIGRATION_026: &str = include_str!("migrations/026_conversation_ratchets.sql");
+const MIGRATION_027: &str = include_str!("migrations/027_conversation_pins.sql");
+const MIGRATION_028: &str = include_str!("migrations/028_imported_feed_entries.sql");
+const MIGRATION_029: &str = include_str!("migrations/029_message_ciphers.sql");
+const MIGRATION_030: &str = include_str!("migrations/030_message_media.sql");
+const MIGRATION_031: &str = include_str!("migrations/031_post_fetch_intents.sql");
+const MIGRATION_032: &str = include_str!("migrations/032_identity_accent_color.sql");
+const MIGRATION_033: &str = include_str!("migrations/033_board_post_reactions.sql");
+const MIGRATION_034: &str = include_str!("migrations/034_feature_flags.sql");
+const MIGRATION_035: &str = include_str!("migrations/035_connection_failures.sql");
+const MIGRATION_036: &str = include_str!("migrations/036_message_deletes.sql");
+const MIGRATION_037: &str = include_str!("migrations/037_pending_outbound.sql");
+const MIGRATION_038: &str = include_str!("migrations/038_held_message_changes.sql");
+const MIGRATION_039: &str = include_str!("migrations/039_outbound_changes.sql");
+
+/// Schema version the migrations above bring a database to
+pub const LATEST_SCHEMA_VERSION: i32 = 39;
+
+/// How opening the on-disk database went at startup, kept for the UI
+#[derive(Debug, Clone, Default)]
+pub struct DatabaseStartupStatus {
+    pub path: PathBuf,
+    /// Why the database couldn't be opened. When set the app is running on a
+    /// throwaway in-memory database and should only offer recovery.
+    pub error: Option<String>,
+    /// Whether a corrupted database was rebuilt from what could still be read
+    pub rebuilt: bool,
+    /// Copy of the corrupted file, kept aside before anything else was tried
+    pub corrupt_copy: Option<PathBuf>,
+}
 
 /// Database wrapper for SQLite connection management
 pub struct Database {
@@ -32,6 +77,9 @@ impl Database {
 
         let conn = Connection::open(&path)?;
 
+        // Refuse a damaged file before migrations write to it
+        check_integrity(&conn)?;
+
         // Enable foreign keys
         conn.execute_batch("PRAGMA foreign_keys = ON;")?;
 
@@ -47,6 +95,126 @@ impl Database {
         Ok(db)
     }
 
+    /// Open the database at `path`, rebuilding it if it's corrupted.
+    ///
+    /// A corrupted file is copied aside first, then everything SQLite can
+    /// still read is vacuumed into a fresh file that replaces it. If that
+    /// fails too the original is left as it was and the error is returned in
+    /// the status, so the UI can offer to restore the last backup instead.
+    pub fn open_or_recover(
+        path: PathBuf,
+    ) -> Result<(Self, DatabaseStartupStatus), DatabaseStartupStatus> {
+        let mut status = DatabaseStartupStatus {
+            path: path.clone(),
+            ..Default::default()
+        };
+
+        let err = match Self::new(path.clone()) {
+            Ok(db) => return Ok((db, status)),
+            Err(e) => e,
+        };
+        if !is_corruption(&err) {
+            error!("Failed to open database at {:?}: {}", path, err);
+            status.error = Some(err.to_string());
+            return Err(status);
+        }
+
+        warn!("Database at {:?} is corrupted ({}), rebuilding", path, err);
+        let corrupt_copy = sibling_path(
+            &path,
+            &format!("corrupt-{}", chrono::Utc::now().timestamp()),
+        );
+        let copied = std::fs::copy(&path, &corrupt_copy).and_then(|_| {
+            // The journals hold part of the data, so they go with the copy
+            for (journal, copy) in journal_paths(&path)
+                .iter()
+                .zip(journal_paths(&corrupt_copy))
+            {
+                if journal.exists() {
+                    std::fs::copy(journal, copy)?;
+                }
+            }
+            Ok(())
+        });
+        if let Err(e) = copied {
+            // Without a copy, rebuilding would risk the only remaining data
+            error!("Could not copy corrupted database aside: {}", e);
+            status.error = Some(err.to_string());
+            return Err(status);
+        }
+        status.corrupt_copy = Some(corrupt_copy);
+
+        match rebuild(&path).and_then(|()| Self::new(path.clone())) {
+            Ok(db) => {
+                info!("Rebuilt corrupted database at {:?}", path);
+                status.rebuilt = true;
+                Ok((db, status))
+            }
+            Err(e) => {
+                error!("Could not rebuild corrupted database: {}", e);
+                status.error = Some(err.to_string());
+                Err(status)
+            }
+        }
+    }
+
+    /// Where the last known-good copy of the database at `path` is kept
+    pub fn backup_path(path: &Path) -> PathBuf {
+        sibling_path(path, "bak")
+    }
+
+    /// Snapshot the database to its backup path. The snapshot is written
+    /// beside the backup and renamed so a crash never leaves half a file.
+    pub fn write_backup(&self) -> SqliteResult<PathBuf> {
+        let backup = Self::backup_path(&self.path);
+        let partial = sibling_path(&backup, "saving");
+        let _ = std::fs::remove_file(&partial);
+
+        self.with_connection(|conn| {
+            conn.execute("VACUUM INTO ?", [partial.to_string_lossy()])?;
+            Ok(())
+        })?;
+        std::fs::rename(&partial, &backup)
+            .map_err(|_| rusqlite::Error::InvalidPath(backup.clone()))?;
+        Ok(backup)
+    }
+
+    /// Replace the database at `path` with its last backup, after checking
+    /// the backup is intact. The file being replaced is moved aside rather
+    /// than deleted. Nothing may have the database open while this runs.
+    pub fn restore_backup(path: &Path) -> SqliteResult<()> {
+        let backup = Self::backup_path(path);
+        if !backup.exists() {
+            return Err(rusqlite::Error::InvalidPath(backup));
+        }
+        let conn = Connection::open_with_flags(&backup, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        check_integrity(&conn)?;
+        drop(conn);
+
+        if path.exists() {
+            let replaced = sibling_path(
+                path,
+                &format!("replaced-{}", chrono::Utc::now().timestamp()),
+            );
+            std::fs::rename(path, &replaced)
+                .map_err(|_| rusqlite::Error::InvalidPath(path.to_path_buf()))?;
+            // Leftover journals belong to the file that was moved aside.
+            // Left in place, SQLite would replay them into the backup.
+            for (journal, moved) in journal_paths(path).iter().zip(journal_paths(&replaced)) {
+                if journal.exists() {
+                    std::fs::rename(journal, moved)
+                        .map_err(|_| rusqlite::Error::InvalidPath(journal.clone()))?;
+                }
+            }
+        }
+        remove_journals(path)?;
+        std::fs::copy(&backup, path)
+            .map_err(|_| rusqlite::Error::InvalidPath(path.to_path_buf()))?;
+
+        info!("Restored database at {:?} from {:?}", path, backup);
+        Ok(())
+    }
+
     /// Create an in-memory database (for testing)
     pub fn in_memory() -> SqliteResult<Self> {
         let conn = Connection::open_in_memory()?;
@@ -169,9 +337,188 @@ impl Database {
             info!("Migration 011 complete");
         }
 
+        if version < 12 {
+            info!("Running migration 012...");
+            conn.execute_batch(MIGRATION_012)?;
+            info!("Migration 012 complete");
+        }
+
+        if version < 13 {
+            info!("Running migration 013...");
+            conn.execute_batch(MIGRATION_013)?;
+            info!("Migration 013 complete");
+        }
+
+        if version < 14 {
+            info!("Running migration 014...");
+            conn.execute_batch(MIGRATION_014)?;
+            info!("Migration 014 complete");
+        }
+
+        if version < 15 {
+            info!("Running migration 015...");
+            conn.execute_batch(MIGRATION_015)?;
+            info!("Migration 015 complete");
+        }
+
+        if version < 16 {
+            info!("Running migration 016...");
+            conn.execute_batch(MIGRATION_016)?;
+            info!("Migration 016 complete");
+        }
+
+        if version < 17 {
+            info!("Running migration 017...");
+            conn.execute_batch(MIGRATION_017)?;
+            info!("Migration 017 complete");
+        }
+
+        if version < 18 {
+            info!("Running migration 018...");
+            conn.execute_batch(MIGRATION_018)?;
+            info!("Migration 018 complete");
+        }
+
+        if version < 19 {
+            info!("Running migration 019...");
+            conn.execute_batch(MIGRATION_019)?;
+            info!("Migration 019 complete");
+        }
+
+        if version < 20 {
+            info!("Running migration 020...");
+            conn.execute_batch(MIGRATION_020)?;
+            info!("Migration 020 complete");
+        }
+
+        if version < 21 {
+            info!("Running migration 021...");
+            conn.execute_batch(MIGRATION_021)?;
+            info!("Migration 021 complete");
+        }
+
+        if version < 22 {
+            info!("Running migration 022...");
+            conn.execute_batch(MIGRATION_022)?;
+            info!("Migration 022 complete");
+        }
+
+        if version < 23 {
+            info!("Running migration 023...");
+            conn.execute_batch(MIGRATION_023)?;
+            info!("Migration 023 complete");
+        }
+
+        if version < 24 {
+            info!("Running migration 024...");
+            conn.execute_batch(MIGRATION_024)?;
+            info!("Migration 024 complete");
+        }
+
+        if version < 25 {
+            info!("Running migration 025...");
+            conn.execute_batch(MIGRATION_025)?;
+            info!("Migration 025 complete");
+        }
+
+        if version < 26 {
+            info!("Running migration 026...");
+            conn.execute_batch(MIGRATION_026)?;
+            info!("Migration 026 complete");
+        }
+
+        if version < 27 {
+            info!("Running migration 027...");
+            conn.execute_batch(MIGRATION_027)?;
+            info!("Migration 027 complete");
+        }
+
+        if version < 28 {
+            info!("Running migration 028...");
+            conn.execute_batch(MIGRATION_028)?;
+            info!("Migration 028 complete");
+        }
+
+        if version < 29 {
+            info!("Running migration 029...");
+            conn.execute_batch(MIGRATION_029)?;
+            info!("Migration 029 complete");
+        }
+
+        if version < 30 {
+            info!("Running migration 030...");
+            conn.execute_batch(MIGRATION_030)?;
+            info!("Migration 030 complete");
+        }
+
+        if version < 31 {
+            info!("Running migration 031...");
+            conn.execute_batch(MIGRATION_031)?;
+            info!("Migration 031 complete");
+        }
+
+        if version < 32 {
+            info!("Running migration 032...");
+            conn.execute_batch(MIGRATION_032)?;
+            info!("Migration 032 complete");
+        }
+
+        if version < 33 {
+            info!("Running migration 033...");
+            conn.execute_batch(MIGRATION_033)?;
+            info!("Migration 033 complete");
+        }
+
+        if version < 34 {
+            info!("Running migration 034...");
+            conn.execute_batch(MIGRATION_034)?;
+            info!("Migration 034 complete");
+        }
+
+        if version < 35 {
+            info!("Running migration 035...");
+            conn.execute_batch(MIGRATION_035)?;
+            info!("Migration 035 complete");
+        }
+
+        if version < 36 {
+            info!("Running migration 036...");
+            conn.execute_batch(MIGRATION_036)?;
+            info!("Migration 036 complete");
+        }
+
+        if version < 37 {
+            info!("Running migration 037...");
+            conn.execute_batch(MIGRATION_037)?;
+            info!("Migration 037 complete");
+        }
+
+        if version < 38 {
+            info!("Running migration 038...");
+            conn.execute_batch(MIGRATION_038)?;
+            info!("Migration 038 complete");
+        }
+
+        if version < 39 {
+            info!("Running migration 039...");
+            conn.execute_batch(MIGRATION_039)?;
+            info!("Migration 039 complete");
+        }
+
         Ok(())
     }
 
+    /// Schema version recorded in the database
+    pub fn schema_version(&self) -> SqliteResult<i32> {
+        self.with_connection(|conn| {
+            conn.query_row(
+                "SELECT version FROM schema_version WHERE id = 1",
+                [],
+                |row| row.get(0),
+            )
+        })
+    }
+
     /// Execute a function with the database connection
     pub fn with_connection<F, T>(&self, f: F) -> SqliteResult<T>
     where
@@ -195,6 +542,11 @@ impl Database {
         &self.path
     }
 
+    /// Whether this database lives only in memory and vanishes on close
+    pub fn is_in_memory(&self) -> bool {
+        self.path.as_os_str() == ":memory:"
+    }
+
     /// Get the next lamport clock value for the given author and increment it
     pub fn next_lamport_clock(&self, author_peer_id: &str) -> SqliteResult<i64> {
         self.with_connection_mut(|conn| {
@@ -319,6 +671,65 @@ impl Database {
         })
     }
 
+    /// Record the lamport clock of a received message for its sender and
+    /// conversation. Returns the id of the message that already used this
+    /// clock, or `None` if the clock is new.
+    pub fn check_and_record_message_clock(
+        &self,
+        conversation_id: &str,
+        sender_peer_id: &str,
+        lamport_clock: u64,
+        message_id: &str,
+    ) -> SqliteResult<Option<String>> {
+        self.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+
+            let existing: Option<String> = tx
+                .query_row(
+                    "SELECT message_id FROM received_message_clocks
+                     WHERE conversation_id = ? AND sender_peer_id = ? AND lamport_clock = ?",
+                    rusqlite::params![conversation_id, sender_peer_id, lamport_clock as i64],
+                    |row| row.get(0),
+                )
+                .optional()?;
+
+            if existing.is_none() {
+                tx.execute(
+                    "INSERT INTO received_message_clocks
+                     (conversation_id, sender_peer_id, lamport_clock, message_id, received_at)
+                     VALUES (?, ?, ?, ?, ?)",
+                    rusqlite::params![
+                        conversation_id,
+                        sender_peer_id,
+                        lamport_clock as i64,
+                        message_id,
+                        chrono::Utc::now().timestamp()
+                    ],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(existing)
+        })
+    }
+
+    /// Get the highest lamport clock received from a sender in a conversation
+    pub fn get_highest_received_message_clock(
+        &self,
+        conversation_id: &str,
+        sender_peer_id: &str,
+    ) -> SqliteResult<Option<u64>> {
+        self.with_connection(|conn| {
+            let highest: Option<i64> = conn.query_row(
+                "SELECT MAX(lamport_clock) FROM received_message_clocks
+                 WHERE conversation_id = ? AND sender_peer_id = ?",
+                rusqlite::params![conversation_id, sender_peer_id],
+                |row| row.get(0),
+            )?;
+            Ok(highest.map(|clock| clock as u64))
+        })
+    }
+
     // ============================================================
     // Sync Cursor Functions (lamport-based)
     // ============================================================
@@ -414,6 +825,12 @@ impl Database {
         })
     }
 
+    /// Forget every inbound sync cursor so the next manifests from each peer
+    /// report everything again. Returns how many cursor entries were removed.
+    pub fn clear_sync_cursors(&self) -> SqliteResult<usize> {
+        self.with_connection(|conn| conn.execute("DELETE FROM sync_cursors", []))
+    }
+
     /// Get last sync time for a peer
     pub fn get_last_sync_time(
         &self,
@@ -432,6 +849,78 @@ impl Database {
     }
 }
 
+/// Fail with `SQLITE_CORRUPT` unless SQLite's integrity check passes
+fn check_integrity(conn: &Connection) -> SqliteResult<()> {
+    let result: String = conn.query_row("PRAGMA integrity_check(1)", [], |row| row.get(0))?;
+    if result == "ok" {
+        return Ok(());
+    }
+    Err(rusqlite::Error::SqliteFailure(
+        rusqlite::ffi::Error::new(rusqlite::ffi::SQLITE_CORRUPT),
+        Some(result),
+    ))
+}
+
+/// Whether an error means the file is damaged or isn't a database at all
+fn is_corruption(err: &rusqlite::Error) -> bool {
+    matches!(
+        err,
+        rusqlite::Error::SqliteFailure(e, _)
+            if matches!(
+                e.code,
+                rusqlite::ErrorCode::DatabaseCorrupt | rusqlite::ErrorCode::NotADatabase
+            )
+    )
+}
+
+/// Copy what can still be read from the database at `path` into a fresh
+/// file and swap it in
+fn rebuild(path: &Path) -> SqliteResult<()> {
+    let rebuilt = sibling_path(path, "rebuilt");
+    let _ = std::fs::remove_file(&rebuilt);
+
+    let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_WRITE)?;
+    let vacuumed = conn.execute("VACUUM INTO ?", [rebuilt.to_string_lossy()]);
+    drop(conn);
+    if let Err(e) = vacuumed {
+        let _ = std::fs::remove_file(&rebuilt);
+        return Err(e);
+    }
+
+    // The old journals were copied aside with the corrupt file and must not
+    // be replayed into the rebuilt one
+    remove_journals(path)?;
+    std::fs::rename(&rebuilt, path).map_err(|_| rusqlite::Error::InvalidPath(path.to_path_buf()))
+}
+
+/// `path` with `.suffix` appended to its file name
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+/// SQLite's rollback journal and WAL files for the database at `path`.
+/// They're named with a dash rather than a dot, unlike `sibling_path`.
+fn journal_paths(path: &Path) -> [PathBuf; 3] {
+    ["-journal", "-wal", "-shm"]
+        .map(|suffix| PathBuf::from(format!("{}{}", path.display(), suffix)))
+}
+
+/// Delete any journal files left beside the database at `path`
+fn remove_journals(path: &Path) -> SqliteResult<()> {
+    for journal in journal_paths(path) {
+        match std::fs::remove_file(&journal) {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+                return Err(rusqlite::Error::InvalidPath(journal));
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
 impl Clone for Database {
     fn clone(&self) -> Self {
         Self {
@@ -462,6 +951,64 @@ mod tests {
         .unwrap();
     }
 
+    #[test]
+    fn test_corrupted_database_is_reported_and_restored_from_backup() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("harbor.db");
+
+        let db = Database::new(path.clone()).unwrap();
+        db.next_lamport_clock("12D3KooWAuthor").unwrap();
+        db.write_backup().unwrap();
+        drop(db);
+
+        // Not a database any more, and nothing to salvage
+        std::fs::write(&path, b"this is not a sqlite file, not even a little bit").unwrap();
+        let status = match Database::open_or_recover(path.clone()) {
+            Ok(_) => panic!("corrupted database should not open"),
+            Err(status) => status,
+        };
+        assert!(status.error.is_some());
+        assert!(!status.rebuilt);
+        assert!(status.corrupt_copy.as_ref().is_some_and(|p| p.exists()));
+
+        Database::restore_backup(&path).unwrap();
+        let (db, status) = Database::open_or_recover(path).unwrap();
+        assert!(status.error.is_none());
+        assert_eq!(db.get_lamport_clock("12D3KooWAuthor").unwrap(), 1);
+    }
+
+    #[test]
+    fn test_restore_moves_stale_journals_aside() {
+        let tmp = tempfile::tempdir().unwrap();
+        let path = tmp.path().join("harbor.db");
+
+        let db = Database::new(path.clone()).unwrap();
+        db.next_lamport_clock("12D3KooWAuthor").unwrap();
+        db.write_backup().unwrap();
+        db.next_lamport_clock("12D3KooWAuthor").unwrap();
+        drop(db);
+
+        let [journal, wal, _] = journal_paths(&path);
+        std::fs::write(&journal, b"stale rollback journal").unwrap();
+        std::fs::write(&wal, b"stale write-ahead log").unwrap();
+
+        Database::restore_backup(&path).unwrap();
+        assert!(!journal.exists());
+        assert!(!wal.exists());
+        let moved_aside = std::fs::read_dir(tmp.path())
+            .unwrap()
+            .filter_map(|entry| entry.ok())
+            .any(|entry| {
+                let name = entry.file_name().to_string_lossy().into_owned();
+                name.starts_with("harbor.db.replaced-") && name.ends_with("-journal")
+            });
+        assert!(moved_aside, "journal should be kept with the replaced file");
+
+        let (db, status) = Database::open_or_recover(path).unwrap();
+        assert!(status.error.is_none());
+        assert_eq!(db.get_lamport_clock("12D3KooWAuthor").unwrap(), 1);
+    }
+
     #[test]
     fn test_lamport_clock_per_author() {
         let db = Database::in_memory().unwrap();
@@ -531,6 +1078,43 @@ mod tests {
             .unwrap());
     }
 
+    #[test]
+    fn test_message_clock_tracking() {
+        let db = Database::in_memory().unwrap();
+        let conv_id = "conversation123";
+        let sender = "12D3KooWSender";
+
+        assert_eq!(
+            db.get_highest_received_message_clock(conv_id, sender)
+                .unwrap(),
+            None
+        );
+
+        // Clocks may arrive in any order
+        assert_eq!(
+            db.check_and_record_message_clock(conv_id, sender, 5, "msg-5")
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            db.check_and_record_message_clock(conv_id, sender, 2, "msg-2")
+                .unwrap(),
+            None
+        );
+        assert_eq!(
+            db.get_highest_received_message_clock(conv_id, sender)
+                .unwrap(),
+            Some(5)
+        );
+
+        // A seen clock reports the message that used it
+        assert_eq!(
+            db.check_and_record_message_clock(conv_id, sender, 2, "msg-other")
+                .unwrap(),
+            Some("msg-2".to_string())
+        );
+    }
+
     #[test]
     fn test_sync_cursor_empty() {
         let db = Database::in_memory().unwrap();
@@ -624,4 +1208,71 @@ mod tests {
         assert_eq!(cursor.get("12D3KooWAuthor2"), Some(&20));
         assert_eq!(cursor.get("12D3KooWAuthor3"), Some(&30));
     }
+
+    #[test]
+    fn test_clear_sync_cursors() {
+        let db = Database::in_memory().unwrap();
+        db.update_sync_cursor("12D3KooWPeer1", "posts", "12D3KooWAuthor1", 10)
+            .unwrap();
+        db.update_sync_cursor("12D3KooWPeer2", "permissions", "12D3KooWAuthor1", 5)
+            .unwrap();
+
+        assert_eq!(db.clear_sync_cursors().unwrap(), 2);
+        assert!(db
+            .get_sync_cursor("12D3KooWPeer1", "posts")
+            .unwrap()
+            .is_empty());
+        assert!(db
+            .get_sync_cursor("12D3KooWPeer2", "permissions")
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            db.get_last_sync_time("12D3KooWPeer1", "posts").unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_migration_020_grants_contact_defaults() {
+        let db = Database::in_memory().unwrap();
+
+        let grants = db
+            .with_connection(|conn| {
+                conn.execute_batch(
+                    "INSERT INTO local_identity (id, peer_id, public_key, x25519_public,
+                         private_key_encrypted, display_name, created_at, updated_at)
+                     VALUES (1, 'me', X'00', X'00', X'00', 'Me', 0, 0);
+                     INSERT INTO contacts (peer_id, public_key, x25519_public, display_name,
+                         is_blocked, added_at, updated_at)
+                     VALUES ('alice', X'00', X'00', 'Alice', 0, 0, 0),
+                            ('bob', X'00', X'00', 'Bob', 0, 0, 0),
+                            ('mallory', X'00', X'00', 'Mallory', 1, 0, 0);
+                     -- Bob's chat grant was revoked by the user
+                     INSERT INTO permissions_current (grant_id, issuer_peer_id, subject_peer_id,
+                         capability, issued_at, revoked_at, payload_cbor, signature)
+                     VALUES ('g1', 'me', 'bob', 'chat', 0, 10, X'', X'');",
+                )?;
+                conn.execute_batch(MIGRATION_020)?;
+                // Running it again must not duplicate anything
+                conn.execute_batch(MIGRATION_020)?;
+
+                let mut stmt = conn.prepare(
+                    "SELECT subject_peer_id, capability FROM permissions_current
+                     WHERE issuer_peer_id = 'me' AND revoked_at IS NULL
+                     ORDER BY subject_peer_id, capability",
+                )?;
+                let rows = stmt.query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?;
+                rows.collect::<SqliteResult<Vec<(String, String)>>>()
+            })
+            .unwrap();
+
+        assert_eq!(
+            grants,
+            vec![
+                ("alice".to_string(), "chat".to_string()),
+                ("alice".to_string(), "file_transfer".to_string()),
+                ("bob".to_string(), "file_transfer".to_string()),
+            ]
+        );
+    }
 }
@@ -0,0 +1,16 @@
+-- Migration 012: Board subscriptions
+-- Boards the user wants to be notified about. Subscribed boards are synced
+-- periodically in the background while connected to their relay.
+
+CREATE TABLE IF NOT EXISTS board_subscriptions (
+    relay_peer_id TEXT NOT NULL,
+    board_id TEXT NOT NULL,
+    subscribed_at INTEGER NOT NULL,
+    -- created_at of the newest post already reported as new
+    last_seen_post_at INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (relay_peer_id, board_id),
+    FOREIGN KEY (relay_peer_id) REFERENCES relay_communities(relay_peer_id) ON DELETE CASCADE
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 12 WHERE id = 1;
@@ -0,0 +1,8 @@
+-- Migration 013: Per-message nonce salt
+-- Messages encrypted with a salted nonce store the random salt alongside the
+-- ciphertext. NULL means the legacy counter-only nonce was used.
+
+ALTER TABLE messages ADD COLUMN nonce_salt BLOB;
+
+-- Update schema version
+UPDATE schema_version SET version = 13 WHERE id = 1;
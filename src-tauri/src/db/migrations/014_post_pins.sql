@@ -0,0 +1,10 @@
+-- Migration 014: Pinned wall posts
+-- An author can pin one of their posts to the top of their wall. The partial
+-- unique index keeps it to a single pinned post per author.
+
+ALTER TABLE posts ADD COLUMN pinned INTEGER NOT NULL DEFAULT 0;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_posts_pinned_author ON posts(author_peer_id) WHERE pinned = 1;
+
+-- Update schema version
+UPDATE schema_version SET version = 14 WHERE id = 1;
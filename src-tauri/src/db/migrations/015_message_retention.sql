@@ -0,0 +1,13 @@
+-- Migration 015: Per-conversation message retention overrides
+-- The global retention window lives in settings. A row here either sets a
+-- conversation-specific window or opts the conversation out of pruning.
+
+CREATE TABLE IF NOT EXISTS conversation_retention (
+    conversation_id TEXT PRIMARY KEY,
+    retention_days INTEGER,
+    never_delete INTEGER NOT NULL DEFAULT 0,
+    updated_at INTEGER NOT NULL
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 15 WHERE id = 1;
@@ -0,0 +1,23 @@
+-- Migration 016: Contact groups
+-- Groups are local labels (family, work, ...) for filtering the feed and
+-- granting permissions in bulk. Deleting a group or a contact only removes
+-- the membership rows.
+
+CREATE TABLE IF NOT EXISTS contact_groups (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    group_id TEXT NOT NULL UNIQUE,
+    name TEXT NOT NULL UNIQUE,
+    created_at INTEGER NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS contact_group_members (
+    group_id TEXT NOT NULL REFERENCES contact_groups(group_id) ON DELETE CASCADE,
+    peer_id TEXT NOT NULL REFERENCES contacts(peer_id) ON DELETE CASCADE,
+    added_at INTEGER NOT NULL,
+    PRIMARY KEY (group_id, peer_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_contact_group_members_peer ON contact_group_members(peer_id);
+
+-- Update schema version
+UPDATE schema_version SET version = 16 WHERE id = 1;
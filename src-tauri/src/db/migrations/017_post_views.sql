@@ -0,0 +1,15 @@
+-- Migration 017: Post view reports
+-- Followers who fetch one of our posts may send back a signed view report.
+-- One row per (post, viewer) so refetches don't inflate the count. viewed_at
+-- is only kept when the author has opted in to precise view timing.
+
+CREATE TABLE IF NOT EXISTS post_views (
+    post_id TEXT NOT NULL REFERENCES posts(post_id) ON DELETE CASCADE,
+    viewer_peer_id TEXT NOT NULL,
+    viewed_at INTEGER,
+    recorded_at INTEGER NOT NULL,
+    PRIMARY KEY (post_id, viewer_peer_id)
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 17 WHERE id = 1;
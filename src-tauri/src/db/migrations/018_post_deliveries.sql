@@ -0,0 +1,18 @@
+-- Migration 018: Post delivery tracking
+-- New posts are pushed to contacts who are online when the post is created.
+-- One row per (post, recipient) records whether that push reached them.
+-- Contacts who were offline get the post through the regular pull sync and
+-- have no row here.
+
+CREATE TABLE IF NOT EXISTS post_deliveries (
+    post_id TEXT NOT NULL REFERENCES posts(post_id) ON DELETE CASCADE,
+    recipient_peer_id TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempted_at INTEGER NOT NULL,
+    delivered_at INTEGER,
+    error TEXT,
+    PRIMARY KEY (post_id, recipient_peer_id)
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 18 WHERE id = 1;
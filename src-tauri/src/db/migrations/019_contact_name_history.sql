@@ -0,0 +1,21 @@
+-- Migration 019: Contact display name history
+-- Every display name observed for a contact, so a peer who renames (possibly
+-- to impersonate someone else) can still be recognized by what they were
+-- called before. Rows are kept when the contact is removed.
+
+CREATE TABLE IF NOT EXISTS contact_name_history (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    peer_id TEXT NOT NULL,
+    display_name TEXT NOT NULL,
+    observed_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_contact_name_history_peer
+    ON contact_name_history(peer_id, observed_at);
+
+-- Seed with the names of existing contacts
+INSERT INTO contact_name_history (peer_id, display_name, observed_at)
+SELECT peer_id, display_name, added_at FROM contacts;
+
+-- Update schema version
+UPDATE schema_version SET version = 19 WHERE id = 1;
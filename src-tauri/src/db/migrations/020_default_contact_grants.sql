@@ -0,0 +1,33 @@
+-- Migration 020: Explicit grants for previously implicit contact capabilities
+-- Incoming messages and media fetches used to be accepted from any contact.
+-- Those handlers now require a chat or file_transfer grant, so existing
+-- contacts get the grants they were implicitly relying on. Grants the user
+-- revoked are not re-issued, and blocked contacts get nothing.
+--
+-- The grants are local only (they are never sent to the peer), so they are
+-- stored without a signed payload and can be revoked like any other grant.
+
+INSERT INTO permissions_current
+    (grant_id, issuer_peer_id, subject_peer_id, capability, issued_at, expires_at, payload_cbor, signature)
+SELECT
+    'migration-020-' || defaults.capability || '-' || c.peer_id,
+    li.peer_id,
+    c.peer_id,
+    defaults.capability,
+    CAST(strftime('%s', 'now') AS INTEGER),
+    NULL,
+    X'',
+    X''
+FROM contacts c
+CROSS JOIN local_identity li
+CROSS JOIN (SELECT 'chat' AS capability UNION ALL SELECT 'file_transfer') AS defaults
+WHERE COALESCE(c.is_blocked, 0) = 0
+  AND NOT EXISTS (
+      SELECT 1 FROM permissions_current p
+      WHERE p.issuer_peer_id = li.peer_id
+        AND p.subject_peer_id = c.peer_id
+        AND p.capability = defaults.capability
+  );
+
+-- Update schema version
+UPDATE schema_version SET version = 20 WHERE id = 1;
@@ -0,0 +1,24 @@
+-- Migration 021: Lamport clocks of received messages
+-- Each sender's clock only moves forward, so a clock seen twice in the same
+-- conversation means a replayed or duplicated message. Clocks are kept per
+-- (conversation, sender) independently of the messages themselves, so the
+-- check still holds after history is cleared or pruned.
+
+CREATE TABLE IF NOT EXISTS received_message_clocks (
+    conversation_id TEXT NOT NULL,
+    sender_peer_id TEXT NOT NULL,
+    lamport_clock INTEGER NOT NULL,
+    message_id TEXT NOT NULL,
+    received_at INTEGER NOT NULL,
+    PRIMARY KEY (conversation_id, sender_peer_id, lamport_clock)
+);
+
+-- Messages received before this migration
+INSERT OR IGNORE INTO received_message_clocks
+    (conversation_id, sender_peer_id, lamport_clock, message_id, received_at)
+SELECT conversation_id, sender_peer_id, lamport_clock, message_id, COALESCE(received_at, sent_at)
+FROM messages
+WHERE sender_peer_id NOT IN (SELECT peer_id FROM local_identity);
+
+-- Update schema version
+UPDATE schema_version SET version = 21 WHERE id = 1;
@@ -0,0 +1,11 @@
+-- Migration 022: Acknowledged permission grants
+-- A grant only takes effect for the subject once they have stored it, and
+-- the message carrying it can be lost while they are offline. Grants we
+-- issued stay unacknowledged until the subject returns a signed ack, and
+-- are resent when they reconnect. Existing grants start unacknowledged, so
+-- any that never arrived are delivered too.
+
+ALTER TABLE permissions_current ADD COLUMN acknowledged_at INTEGER;
+
+-- Update schema version
+UPDATE schema_version SET version = 22 WHERE id = 1;
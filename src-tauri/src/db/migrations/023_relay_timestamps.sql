@@ -0,0 +1,11 @@
+-- Migration 023: Relay-anchored post timestamps
+-- A post's created_at is whatever its author claims. Community relays sign
+-- the time they received each board and wall post; once that signature
+-- checks out against the relay's key, the relay-observed time is kept here
+-- so the two can be shown side by side.
+
+ALTER TABLE board_posts ADD COLUMN relay_observed_at INTEGER;
+ALTER TABLE posts ADD COLUMN relay_observed_at INTEGER;
+
+-- Update schema version
+UPDATE schema_version SET version = 23 WHERE id = 1;
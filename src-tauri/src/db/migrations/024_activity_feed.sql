@@ -0,0 +1,19 @@
+-- Migration 024: Activity feed
+-- Likes, reactions, comments and views from other people on our content,
+-- kept so they can be shown as notifications after the live event has
+-- passed. read_at stays NULL until the user has seen the entry.
+
+CREATE TABLE IF NOT EXISTS activity_feed (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    kind TEXT NOT NULL,
+    actor_peer_id TEXT NOT NULL,
+    target_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    read_at INTEGER
+);
+
+CREATE INDEX IF NOT EXISTS idx_activity_feed_created ON activity_feed(created_at DESC, id DESC);
+CREATE INDEX IF NOT EXISTS idx_activity_feed_unread ON activity_feed(read_at) WHERE read_at IS NULL;
+
+-- Update schema version
+UPDATE schema_version SET version = 24 WHERE id = 1;
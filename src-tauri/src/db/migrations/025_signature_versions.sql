@@ -0,0 +1,11 @@
+-- Migration 025: Signature scheme versions
+-- Stored posts and message events record which signing scheme their
+-- signature was made with, so they keep verifying under that scheme's rules
+-- after the signing format changes. Everything stored so far was signed
+-- with scheme 1 (plain canonical CBOR).
+
+ALTER TABLE posts ADD COLUMN sig_version INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE message_events ADD COLUMN sig_version INTEGER NOT NULL DEFAULT 1;
+
+-- Update schema version
+UPDATE schema_version SET version = 25 WHERE id = 1;
@@ -0,0 +1,14 @@
+-- Migration 026: Conversation ratchets
+-- Ratchet session state for each direct message conversation whose peer
+-- speaks messaging protocol 2. The state holds live ratchet secrets, so it
+-- is stored encrypted under a key derived from our X25519 identity key.
+
+CREATE TABLE IF NOT EXISTS conversation_ratchets (
+    conversation_id TEXT PRIMARY KEY,
+    peer_id TEXT NOT NULL,
+    state BLOB NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 26 WHERE id = 1;
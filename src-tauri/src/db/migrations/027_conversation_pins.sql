@@ -0,0 +1,10 @@
+-- Migration 027: Pinned conversations
+-- Pinned conversations are listed before all others in the inbox.
+
+CREATE TABLE IF NOT EXISTS conversation_pins (
+    conversation_id TEXT PRIMARY KEY,
+    pinned_at INTEGER NOT NULL
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 27 WHERE id = 1;
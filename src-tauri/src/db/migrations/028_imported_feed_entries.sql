@@ -0,0 +1,12 @@
+-- Migration 028: Imported feed entries
+-- Remembers which RSS/Atom entries became posts, by guid or link, so
+-- importing the same feed again skips them.
+
+CREATE TABLE IF NOT EXISTS imported_feed_entries (
+    entry_key TEXT PRIMARY KEY,
+    post_id TEXT NOT NULL,
+    imported_at INTEGER NOT NULL
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 28 WHERE id = 1;
@@ -0,0 +1,12 @@
+-- Migration 029: Message cipher identifiers
+-- Stored messages record which cipher their content is encrypted with, so
+-- the algorithm can change without breaking old messages. Everything stored
+-- so far used AES-256-GCM. Contacts remember the ciphers they announced
+-- during identity exchange; NULL means they predate the announcement and
+-- only understand AES-256-GCM.
+
+ALTER TABLE messages ADD COLUMN cipher TEXT NOT NULL DEFAULT 'aes-256-gcm';
+ALTER TABLE contacts ADD COLUMN supported_ciphers TEXT;
+
+-- Update schema version
+UPDATE schema_version SET version = 29 WHERE id = 1;
@@ -0,0 +1,19 @@
+-- Migration 030: Voice note attachments
+-- A message with content type 'voice' references audio in media storage.
+-- Rows outlive their message on purpose: once the message is deleted
+-- (retention, clearing or deleting a conversation) the next media prune
+-- finds the row orphaned, removes it, and deletes the audio unless another
+-- message or post still uses it.
+
+CREATE TABLE IF NOT EXISTS message_media (
+    message_id TEXT PRIMARY KEY,
+    media_hash TEXT NOT NULL,
+    mime_type TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_message_media_hash ON message_media(media_hash);
+
+-- Update schema version
+UPDATE schema_version SET version = 30 WHERE id = 1;
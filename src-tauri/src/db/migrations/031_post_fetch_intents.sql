@@ -0,0 +1,18 @@
+-- Migration 031: Post fetch intents
+-- A manifest response names posts we still need from its sender. Each one is
+-- recorded here before the sync cursor moves past it, and removed once the
+-- post is stored, so fetches lost to an interrupted sync are re-issued the
+-- next time that peer connects instead of being skipped by the cursor.
+
+CREATE TABLE IF NOT EXISTS post_fetch_intents (
+    source_peer_id TEXT NOT NULL,
+    post_id TEXT NOT NULL,
+    created_at INTEGER NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    PRIMARY KEY (source_peer_id, post_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_post_fetch_intents_post ON post_fetch_intents(post_id);
+
+-- Update schema version
+UPDATE schema_version SET version = 31 WHERE id = 1;
@@ -0,0 +1,7 @@
+-- Add accent color column to local_identity table
+-- Local-only metadata used to tell accounts apart; never sent to peers
+
+ALTER TABLE local_identity ADD COLUMN accent_color TEXT;
+
+-- Update schema version
+UPDATE schema_version SET version = 32 WHERE id = 1;
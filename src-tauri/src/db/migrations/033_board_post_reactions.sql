@@ -0,0 +1,9 @@
+-- Migration 033: Board post reactions
+-- Reaction counts are aggregated by the relay; we cache the count and our
+-- own reaction alongside each board post.
+
+ALTER TABLE board_posts ADD COLUMN reaction_count INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE board_posts ADD COLUMN my_reaction TEXT;
+
+-- Update schema version
+UPDATE schema_version SET version = 33 WHERE id = 1;
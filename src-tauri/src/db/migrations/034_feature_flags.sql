@@ -0,0 +1,12 @@
+-- Migration 034: Per-identity feature flags
+-- Experimental features are opted into per account. A missing row means the
+-- flag is off.
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    name TEXT PRIMARY KEY,
+    enabled INTEGER NOT NULL,
+    updated_at INTEGER NOT NULL
+);
+
+-- Update schema version
+UPDATE schema_version SET version = 34 WHERE id = 1;
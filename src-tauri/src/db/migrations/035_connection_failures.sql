@@ -0,0 +1,18 @@
+-- Migration 035: Connection failure history
+-- Failed incoming and outgoing connections, classified by the network layer,
+-- so transport and handshake problems can be diagnosed after the fact. Only
+-- the most recent failures are kept.
+
+CREATE TABLE IF NOT EXISTS connection_failures (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    peer_id TEXT,
+    address TEXT,
+    category TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    occurred_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_connection_failures_peer ON connection_failures(peer_id);
+
+-- Update schema version
+UPDATE schema_version SET version = 35 WHERE id = 1;
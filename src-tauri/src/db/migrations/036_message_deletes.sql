@@ -0,0 +1,11 @@
+-- Migration 036: Signed message edits and deletes
+-- edit_lamport_clock is the sender's clock on the edit last applied, so
+-- edits delivered out of order resolve to the newest. A deleted message
+-- keeps its row as a tombstone with deleted_at set and its content wiped;
+-- its voice note audio is pruned like a deleted message's.
+
+ALTER TABLE messages ADD COLUMN edit_lamport_clock INTEGER;
+ALTER TABLE messages ADD COLUMN deleted_at INTEGER;
+
+-- Update schema version
+UPDATE schema_version SET version = 36 WHERE id = 1;
@@ -0,0 +1,21 @@
+-- Migration 037: Outbound message queue
+-- A direct message is queued here until its recipient confirms it, so a
+-- message sent while the peer is offline (or the app closes before the
+-- response) is retried when they reconnect instead of being left failed.
+-- `payload` is the encoded request as first sent; retries resend it as is.
+
+CREATE TABLE IF NOT EXISTS pending_outbound (
+    message_id TEXT PRIMARY KEY,
+    peer_id TEXT NOT NULL,
+    payload BLOB NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at INTEGER NOT NULL,
+    last_error TEXT,
+    created_at INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_pending_outbound_peer ON pending_outbound(peer_id);
+CREATE INDEX IF NOT EXISTS idx_pending_outbound_next ON pending_outbound(next_attempt_at);
+
+-- Update schema version
+UPDATE schema_version SET version = 37 WHERE id = 1;
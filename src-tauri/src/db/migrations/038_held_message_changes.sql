@@ -0,0 +1,21 @@
+-- Migration 038: Edits and deletes waiting for their message
+-- An edit or delete can overtake the message it changes, e.g. while the
+-- original is still queued for retry on the sender's side. It's verified,
+-- held here, and applied once the message arrives. Only the newest edit of
+-- a message is kept.
+
+CREATE TABLE IF NOT EXISTS held_message_changes (
+    message_id TEXT NOT NULL,
+    change_type TEXT NOT NULL,
+    sender_peer_id TEXT NOT NULL,
+    lamport_clock INTEGER NOT NULL,
+    payload_cbor BLOB NOT NULL,
+    signature BLOB NOT NULL,
+    received_at INTEGER NOT NULL,
+    PRIMARY KEY (message_id, change_type)
+);
+
+CREATE INDEX IF NOT EXISTS idx_held_message_changes_sender ON held_message_changes(sender_peer_id);
+
+-- Update schema version
+UPDATE schema_version SET version = 38 WHERE id = 1;
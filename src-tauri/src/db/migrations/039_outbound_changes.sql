@@ -0,0 +1,17 @@
+-- Migration 039: Queue edits, deletes and read receipts for retry
+-- They used to be sent once and lost if the peer was offline. Now they wait
+-- in pending_outbound like messages. `kind` says what a payload is, and
+-- message_id is the queue key: the message's own ID for a message,
+-- `edit:` or `delete:` and the ID of the message changed, or `receipt:` and
+-- a fresh ID. target_message_id is the message a payload is about, NULL
+-- for read receipts. A message edited while still queued has its payload
+-- re-sealed with the new content.
+
+ALTER TABLE pending_outbound ADD COLUMN kind TEXT NOT NULL DEFAULT 'message';
+ALTER TABLE pending_outbound ADD COLUMN target_message_id TEXT;
+UPDATE pending_outbound SET target_message_id = message_id;
+
+CREATE INDEX IF NOT EXISTS idx_pending_outbound_target ON pending_outbound(target_message_id);
+
+-- Update schema version
+UPDATE schema_version SET version = 39 WHERE id = 1;
@@ -2,12 +2,18 @@ pub mod connection;
 pub mod repositories;
 pub mod sql_utils;
 
-pub use connection::Database;
+pub use connection::{Database, DatabaseStartupStatus};
 pub use repositories::{
-    Board, BoardPost, BoardsRepository, Capability, CommentCount, CommentData, CommentsRepository,
-    Contact, ContactData, ContactsRepository, Conversation, GrantData, Message, MessageData,
-    MessageStatus, MessagesRepository, Permission, PermissionEvent, PermissionsRepository, Post,
-    PostComment, PostData, PostMedia, PostMediaData, PostVisibility, PostsRepository,
-    RecordMessageEventParams, RecordPermissionEventParams, RecordPostEventParams, RelayCommunity,
-    UpsertBoardPostParams,
+    ActivityItem, ActivityKind, ActivityRepository, Board, BoardPost, BoardSubscription,
+    BoardsRepository, Capability, CommentCount, CommentData, CommentsRepository, Contact,
+    ContactActivity, ContactData, ContactGroup, ContactGroupsRepository, ContactNameChange,
+    ContactsRepository, Conversation, ConversationRetention, DeliveryStatus,
+    FetchIntentsRepository, ForgottenPeer, GrantData, HeldMessageChange, Message, MessageCursor,
+    MessageData, MessageMedia, MessageStatus, MessagesRepository, PeerDataRepository,
+    PendingOutbound, PendingOutboundKind, PendingOutboundRepository, Permission, PermissionEvent,
+    PermissionsRepository, Post, PostComment, PostData, PostDeliveriesRepository, PostDelivery,
+    PostEvent, PostMedia, PostMediaData, PostViewSummary, PostViewer, PostViewsRepository,
+    PostVisibility, PostsRepository, RatchetRepository, RecordMessageEventParams,
+    RecordPermissionEventParams, RecordPostEventParams, RelayCommunity, UpsertBoardPostParams,
+    VERIFIED_TRUST_LEVEL,
 };
@@ -0,0 +1,216 @@
+//! Activity feed repository for notifications about our content
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+
+/// What someone did to our content
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ActivityKind {
+    Like,
+    Reaction,
+    Comment,
+    View,
+}
+
+impl ActivityKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::Like => "like",
+            ActivityKind::Reaction => "reaction",
+            ActivityKind::Comment => "comment",
+            ActivityKind::View => "view",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "like" => Some(ActivityKind::Like),
+            "reaction" => Some(ActivityKind::Reaction),
+            "comment" => Some(ActivityKind::Comment),
+            "view" => Some(ActivityKind::View),
+            _ => None,
+        }
+    }
+
+    /// Kind for a stored like, whose `reaction_type` is "like" for plain likes
+    pub fn for_reaction_type(reaction_type: &str) -> Self {
+        if reaction_type == "like" {
+            ActivityKind::Like
+        } else {
+            ActivityKind::Reaction
+        }
+    }
+}
+
+/// One entry in the activity feed
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActivityItem {
+    pub id: i64,
+    pub kind: ActivityKind,
+    pub actor_peer_id: String,
+    pub actor_display_name: Option<String>,
+    /// Post or comment the activity is about
+    pub target_id: String,
+    pub created_at: i64,
+    pub read_at: Option<i64>,
+}
+
+pub struct ActivityRepository;
+
+impl ActivityRepository {
+    /// Add an unread entry, returning its id
+    pub fn record(
+        db: &Database,
+        kind: ActivityKind,
+        actor_peer_id: &str,
+        target_id: &str,
+        created_at: i64,
+    ) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO activity_feed (kind, actor_peer_id, target_id, created_at)
+                 VALUES (?, ?, ?, ?)",
+                params![kind.as_str(), actor_peer_id, target_id, created_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Get the newest entries, optionally only those older than `before_timestamp`
+    pub fn get_activity(
+        db: &Database,
+        limit: i64,
+        before_timestamp: Option<i64>,
+    ) -> SqliteResult<Vec<ActivityItem>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT a.id, a.kind, a.actor_peer_id, c.display_name, a.target_id,
+                        a.created_at, a.read_at
+                 FROM activity_feed a
+                 LEFT JOIN contacts c ON c.peer_id = a.actor_peer_id
+                 WHERE ?1 IS NULL OR a.created_at < ?1
+                 ORDER BY a.created_at DESC, a.id DESC
+                 LIMIT ?2",
+            )?;
+            let items = stmt
+                .query_map(params![before_timestamp, limit], |row| {
+                    // Skip kinds written by a newer version of the app
+                    let kind: String = row.get(1)?;
+                    let Some(kind) = ActivityKind::parse(&kind) else {
+                        return Ok(None);
+                    };
+                    Ok(Some(ActivityItem {
+                        id: row.get(0)?,
+                        kind,
+                        actor_peer_id: row.get(2)?,
+                        actor_display_name: row.get(3)?,
+                        target_id: row.get(4)?,
+                        created_at: row.get(5)?,
+                        read_at: row.get(6)?,
+                    }))
+                })?
+                .filter_map(Result::transpose)
+                .collect::<SqliteResult<Vec<_>>>()?;
+            Ok(items)
+        })
+    }
+
+    /// Mark entries as read, or every unread entry when `ids` is `None`.
+    /// Returns how many entries changed.
+    pub fn mark_read(db: &Database, ids: Option<&[i64]>, read_at: i64) -> SqliteResult<usize> {
+        db.with_connection(|conn| match ids {
+            None => conn.execute(
+                "UPDATE activity_feed SET read_at = ? WHERE read_at IS NULL",
+                [read_at],
+            ),
+            Some(ids) => {
+                let mut stmt = conn.prepare(
+                    "UPDATE activity_feed SET read_at = ? WHERE id = ? AND read_at IS NULL",
+                )?;
+                let mut changed = 0;
+                for id in ids {
+                    changed += stmt.execute(params![read_at, id])?;
+                }
+                Ok(changed)
+            }
+        })
+    }
+
+    /// Number of entries the user hasn't seen yet
+    pub fn get_unread_count(db: &Database) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM activity_feed WHERE read_at IS NULL",
+                [],
+                |row| row.get(0),
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_activity_is_newest_first_and_paginates() {
+        let db = Database::in_memory().unwrap();
+        ActivityRepository::record(&db, ActivityKind::View, "peer-a", "post1", 1000).unwrap();
+        ActivityRepository::record(&db, ActivityKind::Like, "peer-b", "post1", 1100).unwrap();
+        ActivityRepository::record(&db, ActivityKind::Comment, "peer-a", "post2", 1200).unwrap();
+
+        let items = ActivityRepository::get_activity(&db, 10, None).unwrap();
+        let kinds: Vec<ActivityKind> = items.iter().map(|item| item.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                ActivityKind::Comment,
+                ActivityKind::Like,
+                ActivityKind::View
+            ]
+        );
+
+        let older = ActivityRepository::get_activity(&db, 1, Some(1200)).unwrap();
+        assert_eq!(older.len(), 1);
+        assert_eq!(older[0].kind, ActivityKind::Like);
+        assert_eq!(older[0].actor_peer_id, "peer-b");
+    }
+
+    #[test]
+    fn test_mark_read() {
+        let db = Database::in_memory().unwrap();
+        let first =
+            ActivityRepository::record(&db, ActivityKind::View, "peer-a", "post1", 1000).unwrap();
+        ActivityRepository::record(&db, ActivityKind::Reaction, "peer-b", "post1", 1100).unwrap();
+        ActivityRepository::record(&db, ActivityKind::Like, "peer-c", "post2", 1200).unwrap();
+        assert_eq!(ActivityRepository::get_unread_count(&db).unwrap(), 3);
+
+        assert_eq!(
+            ActivityRepository::mark_read(&db, Some(&[first]), 2000).unwrap(),
+            1
+        );
+        // Already read entries keep their original read time
+        assert_eq!(
+            ActivityRepository::mark_read(&db, Some(&[first]), 3000).unwrap(),
+            0
+        );
+        assert_eq!(ActivityRepository::get_unread_count(&db).unwrap(), 2);
+
+        assert_eq!(ActivityRepository::mark_read(&db, None, 4000).unwrap(), 2);
+        assert_eq!(ActivityRepository::get_unread_count(&db).unwrap(), 0);
+        let items = ActivityRepository::get_activity(&db, 10, None).unwrap();
+        assert_eq!(items.last().unwrap().read_at, Some(2000));
+    }
+
+    #[test]
+    fn test_reaction_type_kind() {
+        assert_eq!(ActivityKind::for_reaction_type("like"), ActivityKind::Like);
+        assert_eq!(
+            ActivityKind::for_reaction_type("heart"),
+            ActivityKind::Reaction
+        );
+    }
+}
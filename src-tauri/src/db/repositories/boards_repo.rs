@@ -1,7 +1,7 @@
 //! Board repository for storing and retrieving community board data
 
 use crate::db::Database;
-use rusqlite::{params, Result as SqliteResult};
+use rusqlite::{params, OptionalExtension, Result as SqliteResult};
 
 /// A cached relay community
 #[derive(Debug, Clone)]
@@ -39,6 +39,21 @@ pub struct BoardPost {
     pub deleted_at: Option<i64>,
     pub signature: Vec<u8>,
     pub cached_at: i64,
+    /// Relay-signed receive time, if the relay anchored it
+    pub relay_observed_at: Option<i64>,
+    /// Number of peers who reacted, as last reported by the relay
+    pub reaction_count: i64,
+    /// Our own reaction to the post
+    pub my_reaction: Option<String>,
+}
+
+/// A board the user is subscribed to
+#[derive(Debug, Clone)]
+pub struct BoardSubscription {
+    pub relay_peer_id: String,
+    pub board_id: String,
+    pub subscribed_at: i64,
+    pub last_seen_post_at: i64,
 }
 
 /// Parameters for upserting a board post
@@ -54,6 +69,10 @@ pub struct UpsertBoardPostParams<'a> {
     pub created_at: i64,
     pub deleted_at: Option<i64>,
     pub signature: &'a [u8],
+    /// Verified relay-observed time, if any
+    pub relay_observed_at: Option<i64>,
+    pub reaction_count: i64,
+    pub my_reaction: Option<&'a str>,
 }
 
 /// Repository for board operations
@@ -179,16 +198,24 @@ impl BoardsRepository {
         let created_at = params.created_at;
         let deleted_at = params.deleted_at;
         let signature = params.signature;
+        let relay_observed_at = params.relay_observed_at;
+        let reaction_count = params.reaction_count;
+        let my_reaction = params.my_reaction;
         let now = chrono::Utc::now().timestamp();
         db.with_connection(|conn| {
             conn.execute(
                 "INSERT INTO board_posts (post_id, board_id, relay_peer_id, author_peer_id,
                     author_display_name, content_type, content_text, lamport_clock,
-                    created_at, deleted_at, signature, cached_at)
-                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                    created_at, deleted_at, signature, cached_at, relay_observed_at,
+                    reaction_count, my_reaction)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                  ON CONFLICT(post_id, relay_peer_id) DO UPDATE SET
                      deleted_at = excluded.deleted_at,
-                     cached_at = excluded.cached_at",
+                     cached_at = excluded.cached_at,
+                     reaction_count = excluded.reaction_count,
+                     my_reaction = excluded.my_reaction,
+                     relay_observed_at = COALESCE(board_posts.relay_observed_at,
+                                                  excluded.relay_observed_at)",
                 params![
                     post_id,
                     board_id,
@@ -201,7 +228,10 @@ impl BoardsRepository {
                     created_at,
                     deleted_at,
                     signature,
-                    now
+                    now,
+                    relay_observed_at,
+                    reaction_count,
+                    my_reaction
                 ],
             )?;
             Ok(())
@@ -222,7 +252,8 @@ impl BoardsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT post_id, board_id, relay_peer_id, author_peer_id,
                             author_display_name, content_type, content_text, lamport_clock,
-                            created_at, deleted_at, signature, cached_at
+                            created_at, deleted_at, signature, cached_at, relay_observed_at,
+                            reaction_count, my_reaction
                      FROM board_posts
                      WHERE board_id = ? AND relay_peer_id = ? AND created_at < ? AND deleted_at IS NULL
                      ORDER BY created_at DESC LIMIT ?",
@@ -235,7 +266,8 @@ impl BoardsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT post_id, board_id, relay_peer_id, author_peer_id,
                             author_display_name, content_type, content_text, lamport_clock,
-                            created_at, deleted_at, signature, cached_at
+                            created_at, deleted_at, signature, cached_at, relay_observed_at,
+                            reaction_count, my_reaction
                      FROM board_posts
                      WHERE board_id = ? AND relay_peer_id = ? AND deleted_at IS NULL
                      ORDER BY created_at DESC LIMIT ?",
@@ -263,6 +295,9 @@ impl BoardsRepository {
             deleted_at: row.get(9)?,
             signature: row.get(10)?,
             cached_at: row.get(11)?,
+            relay_observed_at: row.get(12)?,
+            reaction_count: row.get(13)?,
+            my_reaction: row.get(14)?,
         })
     }
 
@@ -302,6 +337,12 @@ impl BoardsRepository {
         })
     }
 
+    /// Forget the sync cursor of every board so the next sync of each board
+    /// fetches from the start. Returns how many cursors were removed.
+    pub fn clear_board_sync_cursors(db: &Database) -> SqliteResult<usize> {
+        db.with_connection(|conn| conn.execute("DELETE FROM board_sync_cursors", []))
+    }
+
     /// Update last_sync_at for a community
     pub fn update_community_sync_time(db: &Database, relay_peer_id: &str) -> SqliteResult<()> {
         let now = chrono::Utc::now().timestamp();
@@ -314,6 +355,24 @@ impl BoardsRepository {
         })
     }
 
+    /// Update a cached post's reaction count and our own reaction
+    pub fn update_board_post_reactions(
+        db: &Database,
+        post_id: &str,
+        relay_peer_id: &str,
+        reaction_count: i64,
+        my_reaction: Option<&str>,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE board_posts SET reaction_count = ?, my_reaction = ?
+                 WHERE post_id = ? AND relay_peer_id = ?",
+                params![reaction_count, my_reaction, post_id, relay_peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
     /// Delete a board post locally
     pub fn delete_board_post(
         db: &Database,
@@ -329,4 +388,99 @@ impl BoardsRepository {
             Ok(rows > 0)
         })
     }
+
+    /// Subscribe to a board (no-op if already subscribed)
+    pub fn subscribe_board(
+        db: &Database,
+        relay_peer_id: &str,
+        board_id: &str,
+        subscribed_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "INSERT OR IGNORE INTO board_subscriptions
+                    (relay_peer_id, board_id, subscribed_at, last_seen_post_at)
+                 VALUES (?, ?, ?, ?)",
+                params![relay_peer_id, board_id, subscribed_at, subscribed_at],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Unsubscribe from a board
+    pub fn unsubscribe_board(
+        db: &Database,
+        relay_peer_id: &str,
+        board_id: &str,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "DELETE FROM board_subscriptions WHERE relay_peer_id = ? AND board_id = ?",
+                params![relay_peer_id, board_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get all board subscriptions
+    pub fn get_board_subscriptions(db: &Database) -> SqliteResult<Vec<BoardSubscription>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT relay_peer_id, board_id, subscribed_at, last_seen_post_at
+                 FROM board_subscriptions ORDER BY subscribed_at",
+            )?;
+            let mut subscriptions = Vec::new();
+            let mut rows = stmt.query([])?;
+            while let Some(row) = rows.next()? {
+                subscriptions.push(BoardSubscription {
+                    relay_peer_id: row.get(0)?,
+                    board_id: row.get(1)?,
+                    subscribed_at: row.get(2)?,
+                    last_seen_post_at: row.get(3)?,
+                });
+            }
+            Ok(subscriptions)
+        })
+    }
+
+    /// Get a single board subscription
+    pub fn get_board_subscription(
+        db: &Database,
+        relay_peer_id: &str,
+        board_id: &str,
+    ) -> SqliteResult<Option<BoardSubscription>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT relay_peer_id, board_id, subscribed_at, last_seen_post_at
+                 FROM board_subscriptions WHERE relay_peer_id = ? AND board_id = ?",
+                params![relay_peer_id, board_id],
+                |row| {
+                    Ok(BoardSubscription {
+                        relay_peer_id: row.get(0)?,
+                        board_id: row.get(1)?,
+                        subscribed_at: row.get(2)?,
+                        last_seen_post_at: row.get(3)?,
+                    })
+                },
+            )
+            .optional()
+        })
+    }
+
+    /// Advance the last-seen post timestamp for a subscription (never moves backwards)
+    pub fn update_subscription_last_seen(
+        db: &Database,
+        relay_peer_id: &str,
+        board_id: &str,
+        last_seen_post_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE board_subscriptions SET last_seen_post_at = MAX(last_seen_post_at, ?)
+                 WHERE relay_peer_id = ? AND board_id = ?",
+                params![last_seen_post_at, relay_peer_id, board_id],
+            )?;
+            Ok(())
+        })
+    }
 }
@@ -0,0 +1,150 @@
+//! Connection failures repository for diagnosing transport problems
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+
+/// Failures kept; older ones are dropped as new ones are recorded
+pub const MAX_CONNECTION_FAILURES: i64 = 500;
+
+/// A recorded connection failure
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConnectionFailure {
+    pub id: i64,
+    /// `None` for incoming connections that failed before authenticating
+    pub peer_id: Option<String>,
+    pub address: Option<String>,
+    pub category: String,
+    pub detail: String,
+    pub occurred_at: i64,
+}
+
+/// Data for recording a connection failure
+#[derive(Debug, Clone)]
+pub struct ConnectionFailureData {
+    pub peer_id: Option<String>,
+    pub address: Option<String>,
+    pub category: String,
+    pub detail: String,
+    pub occurred_at: i64,
+}
+
+pub struct ConnectionFailuresRepository;
+
+impl ConnectionFailuresRepository {
+    /// Record a failure, dropping the oldest beyond `MAX_CONNECTION_FAILURES`
+    pub fn record(db: &Database, failure: &ConnectionFailureData) -> SqliteResult<i64> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "INSERT INTO connection_failures (peer_id, address, category, detail, occurred_at)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    failure.peer_id,
+                    failure.address,
+                    failure.category,
+                    failure.detail,
+                    failure.occurred_at
+                ],
+            )?;
+            let id = tx.last_insert_rowid();
+            tx.execute(
+                "DELETE FROM connection_failures WHERE id <= ? - ?",
+                params![id, MAX_CONNECTION_FAILURES],
+            )?;
+            tx.commit()?;
+            Ok(id)
+        })
+    }
+
+    /// The most recent failures, newest first, optionally only for one peer
+    pub fn get_recent(
+        db: &Database,
+        peer_id: Option<&str>,
+        limit: i64,
+    ) -> SqliteResult<Vec<ConnectionFailure>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, peer_id, address, category, detail, occurred_at
+                 FROM connection_failures
+                 WHERE ?1 IS NULL OR peer_id = ?1
+                 ORDER BY id DESC
+                 LIMIT ?2",
+            )?;
+            let rows = stmt.query_map(params![peer_id, limit], |row| {
+                Ok(ConnectionFailure {
+                    id: row.get(0)?,
+                    peer_id: row.get(1)?,
+                    address: row.get(2)?,
+                    category: row.get(3)?,
+                    detail: row.get(4)?,
+                    occurred_at: row.get(5)?,
+                })
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Delete all recorded failures
+    pub fn clear(db: &Database) -> SqliteResult<usize> {
+        db.with_connection(|conn| conn.execute("DELETE FROM connection_failures", []))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn failure(peer_id: Option<&str>, category: &str) -> ConnectionFailureData {
+        ConnectionFailureData {
+            peer_id: peer_id.map(str::to_string),
+            address: Some("/ip4/192.168.1.5/tcp/9000".to_string()),
+            category: category.to_string(),
+            detail: "Connection refused (os error 111)".to_string(),
+            occurred_at: 1_700_000_000,
+        }
+    }
+
+    #[test]
+    fn test_record_and_get_recent() {
+        let db = Database::in_memory().unwrap();
+        ConnectionFailuresRepository::record(&db, &failure(Some("peer-a"), "timeout")).unwrap();
+        ConnectionFailuresRepository::record(&db, &failure(None, "handshake")).unwrap();
+        ConnectionFailuresRepository::record(
+            &db,
+            &failure(Some("peer-b"), "multiaddr_unreachable"),
+        )
+        .unwrap();
+
+        let all = ConnectionFailuresRepository::get_recent(&db, None, 10).unwrap();
+        let categories: Vec<&str> = all.iter().map(|f| f.category.as_str()).collect();
+        assert_eq!(
+            categories,
+            vec!["multiaddr_unreachable", "handshake", "timeout"]
+        );
+        assert_eq!(all[1].peer_id, None);
+
+        let peer_a = ConnectionFailuresRepository::get_recent(&db, Some("peer-a"), 10).unwrap();
+        assert_eq!(peer_a.len(), 1);
+        assert_eq!(peer_a[0].category, "timeout");
+
+        assert_eq!(
+            ConnectionFailuresRepository::get_recent(&db, None, 2)
+                .unwrap()
+                .len(),
+            2
+        );
+        assert_eq!(ConnectionFailuresRepository::clear(&db).unwrap(), 3);
+    }
+
+    #[test]
+    fn test_record_keeps_only_recent_failures() {
+        let db = Database::in_memory().unwrap();
+        for _ in 0..MAX_CONNECTION_FAILURES + 5 {
+            ConnectionFailuresRepository::record(&db, &failure(Some("peer-a"), "timeout")).unwrap();
+        }
+
+        let kept = ConnectionFailuresRepository::get_recent(&db, None, 1_000).unwrap();
+        assert_eq!(kept.len() as i64, MAX_CONNECTION_FAILURES);
+        assert_eq!(kept.last().unwrap().id, 6);
+    }
+}
@@ -0,0 +1,250 @@
+//! Contact groups repository for organizing contacts under local labels
+
+use crate::db::Database;
+use rusqlite::{params, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+
+/// A named group of contacts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactGroup {
+    pub group_id: String,
+    pub name: String,
+    pub member_count: i64,
+    pub created_at: i64,
+}
+
+pub struct ContactGroupsRepository;
+
+impl ContactGroupsRepository {
+    /// Create a new group
+    pub fn create_group(
+        db: &Database,
+        group_id: &str,
+        name: &str,
+        created_at: i64,
+    ) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO contact_groups (group_id, name, created_at) VALUES (?, ?, ?)",
+                params![group_id, name, created_at],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Get a group by its ID
+    pub fn get_group(db: &Database, group_id: &str) -> SqliteResult<Option<ContactGroup>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT g.group_id, g.name, COUNT(m.peer_id), g.created_at
+                 FROM contact_groups g
+                 LEFT JOIN contact_group_members m ON m.group_id = g.group_id
+                 WHERE g.group_id = ?
+                 GROUP BY g.group_id",
+                [group_id],
+                Self::row_to_group,
+            )
+            .optional()
+        })
+    }
+
+    /// Get all groups, ordered by name
+    pub fn get_all(db: &Database) -> SqliteResult<Vec<ContactGroup>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT g.group_id, g.name, COUNT(m.peer_id), g.created_at
+                 FROM contact_groups g
+                 LEFT JOIN contact_group_members m ON m.group_id = g.group_id
+                 GROUP BY g.group_id
+                 ORDER BY g.name COLLATE NOCASE",
+            )?;
+            let groups = stmt
+                .query_map([], Self::row_to_group)?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            Ok(groups)
+        })
+    }
+
+    /// Check if a group name is already taken (case-insensitive)
+    pub fn name_exists(db: &Database, name: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let count: i64 = conn.query_row(
+                "SELECT COUNT(*) FROM contact_groups WHERE name = ? COLLATE NOCASE",
+                [name],
+                |row| row.get(0),
+            )?;
+            Ok(count > 0)
+        })
+    }
+
+    /// Delete a group. Memberships go with it; the contacts are untouched.
+    pub fn delete_group(db: &Database, group_id: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute("DELETE FROM contact_groups WHERE group_id = ?", [group_id])?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Add a contact to a group. Returns `false` if they were already a member.
+    pub fn add_member(
+        db: &Database,
+        group_id: &str,
+        peer_id: &str,
+        added_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "INSERT OR IGNORE INTO contact_group_members (group_id, peer_id, added_at)
+                 VALUES (?, ?, ?)",
+                params![group_id, peer_id, added_at],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Remove a contact from a group
+    pub fn remove_member(db: &Database, group_id: &str, peer_id: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "DELETE FROM contact_group_members WHERE group_id = ? AND peer_id = ?",
+                params![group_id, peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get the peer IDs of a group's members
+    pub fn get_member_ids(db: &Database, group_id: &str) -> SqliteResult<Vec<String>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT peer_id FROM contact_group_members WHERE group_id = ? ORDER BY added_at",
+            )?;
+            let ids = stmt
+                .query_map([group_id], |row| row.get(0))?
+                .collect::<SqliteResult<Vec<String>>>()?;
+            Ok(ids)
+        })
+    }
+
+    /// Get the groups a contact belongs to
+    pub fn get_groups_for_contact(db: &Database, peer_id: &str) -> SqliteResult<Vec<ContactGroup>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT g.group_id, g.name,
+                        (SELECT COUNT(*) FROM contact_group_members c WHERE c.group_id = g.group_id),
+                        g.created_at
+                 FROM contact_groups g
+                 JOIN contact_group_members m ON m.group_id = g.group_id
+                 WHERE m.peer_id = ?
+                 ORDER BY g.name COLLATE NOCASE",
+            )?;
+            let groups = stmt
+                .query_map([peer_id], Self::row_to_group)?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            Ok(groups)
+        })
+    }
+
+    fn row_to_group(row: &rusqlite::Row<'_>) -> SqliteResult<ContactGroup> {
+        Ok(ContactGroup {
+            group_id: row.get(0)?,
+            name: row.get(1)?,
+            member_count: row.get(2)?,
+            created_at: row.get(3)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::{ContactData, ContactsRepository};
+
+    fn add_contact(db: &Database, peer_id: &str) {
+        ContactsRepository::add_contact(
+            db,
+            &ContactData {
+                peer_id: peer_id.to_string(),
+                public_key: vec![1, 2, 3],
+                x25519_public: vec![4, 5, 6],
+                display_name: peer_id.to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_group_membership() {
+        let db = Database::in_memory().unwrap();
+        add_contact(&db, "peer-a");
+        add_contact(&db, "peer-b");
+
+        ContactGroupsRepository::create_group(&db, "group-1", "Family", 1000).unwrap();
+        ContactGroupsRepository::create_group(&db, "group-2", "work", 1000).unwrap();
+        assert!(ContactGroupsRepository::name_exists(&db, "family").unwrap());
+
+        assert!(ContactGroupsRepository::add_member(&db, "group-1", "peer-a", 1001).unwrap());
+        assert!(ContactGroupsRepository::add_member(&db, "group-1", "peer-b", 1002).unwrap());
+        assert!(!ContactGroupsRepository::add_member(&db, "group-1", "peer-a", 1003).unwrap());
+        assert!(ContactGroupsRepository::add_member(&db, "group-2", "peer-a", 1004).unwrap());
+
+        assert_eq!(
+            ContactGroupsRepository::get_member_ids(&db, "group-1").unwrap(),
+            vec!["peer-a", "peer-b"]
+        );
+        let groups = ContactGroupsRepository::get_all(&db).unwrap();
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].name, "Family");
+        assert_eq!(groups[0].member_count, 2);
+
+        let for_a = ContactGroupsRepository::get_groups_for_contact(&db, "peer-a").unwrap();
+        assert_eq!(for_a.len(), 2);
+        assert_eq!(for_a[0].member_count, 2);
+
+        assert!(ContactGroupsRepository::remove_member(&db, "group-1", "peer-b").unwrap());
+        assert_eq!(
+            ContactGroupsRepository::get_group(&db, "group-1")
+                .unwrap()
+                .unwrap()
+                .member_count,
+            1
+        );
+    }
+
+    #[test]
+    fn test_delete_group_keeps_contacts() {
+        let db = Database::in_memory().unwrap();
+        add_contact(&db, "peer-a");
+
+        ContactGroupsRepository::create_group(&db, "group-1", "Family", 1000).unwrap();
+        ContactGroupsRepository::add_member(&db, "group-1", "peer-a", 1001).unwrap();
+
+        assert!(ContactGroupsRepository::delete_group(&db, "group-1").unwrap());
+        assert!(ContactGroupsRepository::get_group(&db, "group-1")
+            .unwrap()
+            .is_none());
+        assert!(
+            ContactGroupsRepository::get_groups_for_contact(&db, "peer-a")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(ContactsRepository::is_contact(&db, "peer-a").unwrap());
+    }
+
+    #[test]
+    fn test_removing_contact_drops_memberships() {
+        let db = Database::in_memory().unwrap();
+        add_contact(&db, "peer-a");
+
+        ContactGroupsRepository::create_group(&db, "group-1", "Family", 1000).unwrap();
+        ContactGroupsRepository::add_member(&db, "group-1", "peer-a", 1001).unwrap();
+
+        ContactsRepository::remove_contact(&db, "peer-a").unwrap();
+        assert!(ContactGroupsRepository::get_member_ids(&db, "group-1")
+            .unwrap()
+            .is_empty());
+    }
+}
@@ -2,6 +2,10 @@
 
 use crate::db::Database;
 use rusqlite::{params, OptionalExtension, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+
+/// Trust level at or above which the user has verified the contact's identity
+pub const VERIFIED_TRUST_LEVEL: i32 = 1;
 
 /// Represents a contact in the database
 #[derive(Debug, Clone)]
@@ -20,6 +24,13 @@ pub struct Contact {
     pub updated_at: i64,
 }
 
+impl Contact {
+    /// Whether the user has verified this contact's identity
+    pub fn is_verified(&self) -> bool {
+        self.trust_level >= VERIFIED_TRUST_LEVEL
+    }
+}
+
 /// Contact data for creating or updating contacts
 #[derive(Debug, Clone)]
 pub struct ContactData {
@@ -31,15 +42,34 @@ pub struct ContactData {
     pub bio: Option<String>,
 }
 
+/// Most recent message and post activity recorded for a contact
+#[derive(Debug, Clone)]
+pub struct ContactActivity {
+    pub peer_id: String,
+    /// Latest message sent to or received from the contact
+    pub last_message_at: Option<i64>,
+    /// Latest post authored by the contact that we hold locally
+    pub last_post_at: Option<i64>,
+}
+
+/// A display name observed for a contact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactNameChange {
+    pub display_name: String,
+    pub observed_at: i64,
+}
+
 /// Repository for contact operations
 pub struct ContactsRepository;
 
 impl ContactsRepository {
     /// Add a new contact
     pub fn add_contact(db: &Database, contact: &ContactData) -> SqliteResult<i64> {
-        db.with_connection(|conn| {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
             let now = chrono::Utc::now().timestamp();
-            conn.execute(
+            tx.execute(
                 "INSERT INTO contacts (peer_id, public_key, x25519_public, display_name, avatar_hash, bio, added_at, updated_at)
                  VALUES (?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
@@ -53,7 +83,14 @@ impl ContactsRepository {
                     now
                 ],
             )?;
-            Ok(conn.last_insert_rowid())
+            let id = tx.last_insert_rowid();
+            tx.execute(
+                "INSERT INTO contact_name_history (peer_id, display_name, observed_at)
+                 VALUES (?, ?, ?)",
+                params![contact.peer_id, contact.display_name, now],
+            )?;
+            tx.commit()?;
+            Ok(id)
         })
     }
 
@@ -149,7 +186,9 @@ impl ContactsRepository {
         })
     }
 
-    /// Update contact info (from identity exchange)
+    /// Update contact info (from identity exchange).
+    ///
+    /// A new display name is also appended to the contact's name history.
     pub fn update_contact_info(
         db: &Database,
         peer_id: &str,
@@ -157,17 +196,108 @@ impl ContactsRepository {
         avatar_hash: Option<&str>,
         bio: Option<&str>,
     ) -> SqliteResult<bool> {
-        db.with_connection(|conn| {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
             let now = chrono::Utc::now().timestamp();
-            let rows = conn.execute(
+            let previous_name: Option<String> = tx
+                .query_row(
+                    "SELECT display_name FROM contacts WHERE peer_id = ?",
+                    [peer_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            let rows = tx.execute(
                 "UPDATE contacts SET display_name = ?, avatar_hash = ?, bio = ?, updated_at = ?
                  WHERE peer_id = ?",
                 params![display_name, avatar_hash, bio, now, peer_id],
             )?;
+            if rows > 0 && previous_name.as_deref() != Some(display_name) {
+                tx.execute(
+                    "INSERT INTO contact_name_history (peer_id, display_name, observed_at)
+                     VALUES (?, ?, ?)",
+                    params![peer_id, display_name, now],
+                )?;
+            }
+            tx.commit()?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get every display name observed for a peer, oldest first
+    pub fn get_name_history(db: &Database, peer_id: &str) -> SqliteResult<Vec<ContactNameChange>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT display_name, observed_at FROM contact_name_history
+                 WHERE peer_id = ?
+                 ORDER BY observed_at ASC, id ASC",
+            )?;
+
+            let history = stmt.query_map([peer_id], |row| {
+                Ok(ContactNameChange {
+                    display_name: row.get(0)?,
+                    observed_at: row.get(1)?,
+                })
+            })?;
+
+            history.collect()
+        })
+    }
+
+    /// Replace a contact's stored public keys (after a verified key rotation)
+    pub fn update_contact_keys(
+        db: &Database,
+        peer_id: &str,
+        public_key: &[u8],
+        x25519_public: &[u8],
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let now = chrono::Utc::now().timestamp();
+            let rows = conn.execute(
+                "UPDATE contacts SET public_key = ?, x25519_public = ?, updated_at = ?
+                 WHERE peer_id = ?",
+                params![public_key, x25519_public, now, peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Record the message ciphers a contact announced in its identity response
+    pub fn set_supported_ciphers(
+        db: &Database,
+        peer_id: &str,
+        ciphers: &[String],
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE contacts SET supported_ciphers = ? WHERE peer_id = ?",
+                params![ciphers.join(","), peer_id],
+            )?;
             Ok(rows > 0)
         })
     }
 
+    /// Get the message ciphers a contact announced (None if it never did)
+    pub fn get_supported_ciphers(
+        db: &Database,
+        peer_id: &str,
+    ) -> SqliteResult<Option<Vec<String>>> {
+        db.with_connection(|conn| {
+            let ciphers: Option<Option<String>> = conn
+                .query_row(
+                    "SELECT supported_ciphers FROM contacts WHERE peer_id = ?",
+                    [peer_id],
+                    |row| row.get(0),
+                )
+                .optional()?;
+            Ok(ciphers.flatten().map(|list| {
+                list.split(',')
+                    .filter(|cipher| !cipher.is_empty())
+                    .map(String::from)
+                    .collect()
+            }))
+        })
+    }
+
     /// Update last seen timestamp
     pub fn update_last_seen(db: &Database, peer_id: &str) -> SqliteResult<bool> {
         db.with_connection(|conn| {
@@ -180,6 +310,19 @@ impl ContactsRepository {
         })
     }
 
+    /// Record a verified last-seen timestamp (never moves backwards)
+    pub fn update_last_seen_at(db: &Database, peer_id: &str, timestamp: i64) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let now = chrono::Utc::now().timestamp();
+            let rows = conn.execute(
+                "UPDATE contacts SET last_seen_at = ?, updated_at = ?
+                 WHERE peer_id = ? AND (last_seen_at IS NULL OR last_seen_at < ?)",
+                params![timestamp, now, peer_id, timestamp],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
     /// Block a contact
     pub fn block_contact(db: &Database, peer_id: &str) -> SqliteResult<bool> {
         db.with_connection(|conn| {
@@ -249,6 +392,30 @@ impl ContactsRepository {
             Ok(blocked.unwrap_or(0) != 0)
         })
     }
+
+    /// Get message and post activity timestamps for every contact
+    pub fn get_activity(db: &Database) -> SqliteResult<Vec<ContactActivity>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT c.peer_id,
+                        (SELECT MAX(COALESCE(m.received_at, m.sent_at)) FROM messages m
+                         WHERE m.sender_peer_id = c.peer_id OR m.recipient_peer_id = c.peer_id),
+                        (SELECT MAX(p.created_at) FROM posts p
+                         WHERE p.author_peer_id = c.peer_id)
+                 FROM contacts c",
+            )?;
+
+            let activity = stmt.query_map([], |row| {
+                Ok(ContactActivity {
+                    peer_id: row.get(0)?,
+                    last_message_at: row.get(1)?,
+                    last_post_at: row.get(2)?,
+                })
+            })?;
+
+            activity.collect()
+        })
+    }
 }
 
 #[cfg(test)]
@@ -375,4 +542,75 @@ mod tests {
 
         assert!(!ContactsRepository::is_contact(&db, "12D3KooWTest").unwrap());
     }
+
+    #[test]
+    fn test_supported_ciphers_roundtrip() {
+        let db = Database::in_memory().unwrap();
+
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: "12D3KooWTest".to_string(),
+                public_key: vec![1],
+                x25519_public: vec![2],
+                display_name: "Test".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        // Contacts that never announced ciphers have none recorded
+        assert_eq!(
+            ContactsRepository::get_supported_ciphers(&db, "12D3KooWTest").unwrap(),
+            None
+        );
+
+        let ciphers = vec!["aes-256-gcm".to_string(), "chacha20-poly1305".to_string()];
+        assert!(ContactsRepository::set_supported_ciphers(&db, "12D3KooWTest", &ciphers).unwrap());
+        assert_eq!(
+            ContactsRepository::get_supported_ciphers(&db, "12D3KooWTest").unwrap(),
+            Some(ciphers)
+        );
+    }
+
+    #[test]
+    fn test_name_history_records_renames() {
+        let db = Database::in_memory().unwrap();
+
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: "12D3KooWTest".to_string(),
+                public_key: vec![1],
+                x25519_public: vec![2],
+                display_name: "Alice".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        // Changing only the bio does not add a history entry
+        ContactsRepository::update_contact_info(&db, "12D3KooWTest", "Alice", None, Some("hi"))
+            .unwrap();
+        ContactsRepository::update_contact_info(&db, "12D3KooWTest", "Bob", None, Some("hi"))
+            .unwrap();
+
+        let names: Vec<String> = ContactsRepository::get_name_history(&db, "12D3KooWTest")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.display_name)
+            .collect();
+        assert_eq!(names, vec!["Alice".to_string(), "Bob".to_string()]);
+
+        // History outlives the contact
+        ContactsRepository::remove_contact(&db, "12D3KooWTest").unwrap();
+        assert_eq!(
+            ContactsRepository::get_name_history(&db, "12D3KooWTest")
+                .unwrap()
+                .len(),
+            2
+        );
+    }
 }
@@ -0,0 +1,58 @@
+//! Feature flags repository for per-identity opt-in toggles
+
+use crate::db::Database;
+use rusqlite::{params, OptionalExtension, Result as SqliteResult};
+
+pub struct FeatureFlagsRepository;
+
+impl FeatureFlagsRepository {
+    /// Get a flag's stored state, or `None` if it was never set
+    pub fn get(db: &Database, name: &str) -> SqliteResult<Option<bool>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT enabled FROM feature_flags WHERE name = ?",
+                [name],
+                |row| Ok(row.get::<_, i32>(0)? != 0),
+            )
+            .optional()
+        })
+    }
+
+    /// Turn a flag on or off
+    pub fn set(db: &Database, name: &str, enabled: bool) -> SqliteResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO feature_flags (name, enabled, updated_at) VALUES (?, ?, ?)
+                 ON CONFLICT(name) DO UPDATE SET enabled = excluded.enabled, updated_at = excluded.updated_at",
+                params![name, enabled as i32, now],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_and_get() {
+        let db = Database::in_memory().unwrap();
+
+        assert_eq!(FeatureFlagsRepository::get(&db, "flag").unwrap(), None);
+
+        FeatureFlagsRepository::set(&db, "flag", true).unwrap();
+        assert_eq!(
+            FeatureFlagsRepository::get(&db, "flag").unwrap(),
+            Some(true)
+        );
+
+        FeatureFlagsRepository::set(&db, "flag", false).unwrap();
+        assert_eq!(
+            FeatureFlagsRepository::get(&db, "flag").unwrap(),
+            Some(false)
+        );
+        assert_eq!(FeatureFlagsRepository::get(&db, "other").unwrap(), None);
+    }
+}
@@ -0,0 +1,127 @@
+//! Fetch intents repository for resuming interrupted content sync
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+
+pub struct FetchIntentsRepository;
+
+impl FetchIntentsRepository {
+    /// Record that these posts still need fetching from a peer, all or nothing.
+    ///
+    /// A post offered again by a later manifest starts over with no attempts.
+    pub fn record(
+        db: &Database,
+        source_peer_id: &str,
+        post_ids: &[String],
+        created_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO post_fetch_intents (source_peer_id, post_id, created_at)
+                     VALUES (?, ?, ?)
+                     ON CONFLICT(source_peer_id, post_id) DO UPDATE SET
+                        created_at = excluded.created_at, attempts = 0",
+                )?;
+                for post_id in post_ids {
+                    stmt.execute(params![source_peer_id, post_id, created_at])?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Claim a peer's outstanding intents for another fetch attempt.
+    ///
+    /// Intents that already used `max_attempts` are dropped instead, so a post
+    /// the peer no longer serves isn't requested forever. Returns the post IDs
+    /// to fetch, oldest first.
+    pub fn claim_for_retry(
+        db: &Database,
+        source_peer_id: &str,
+        max_attempts: u32,
+    ) -> SqliteResult<Vec<String>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM post_fetch_intents WHERE source_peer_id = ? AND attempts >= ?",
+                params![source_peer_id, max_attempts],
+            )?;
+            let post_ids = {
+                let mut stmt = tx.prepare(
+                    "SELECT post_id FROM post_fetch_intents
+                     WHERE source_peer_id = ?
+                     ORDER BY created_at, post_id",
+                )?;
+                let rows = stmt.query_map([source_peer_id], |row| row.get(0))?;
+                rows.collect::<SqliteResult<Vec<String>>>()?
+            };
+            tx.execute(
+                "UPDATE post_fetch_intents SET attempts = attempts + 1 WHERE source_peer_id = ?",
+                [source_peer_id],
+            )?;
+            tx.commit()?;
+            Ok(post_ids)
+        })
+    }
+
+    /// Clear every intent for a post once we hold it, whichever peer it came from
+    pub fn complete(db: &Database, post_id: &str) -> SqliteResult<usize> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "DELETE FROM post_fetch_intents WHERE post_id = ?",
+                [post_id],
+            )
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(post_ids: &[&str]) -> Vec<String> {
+        post_ids.iter().map(|id| id.to_string()).collect()
+    }
+
+    #[test]
+    fn test_intents_survive_until_completed() {
+        let db = Database::in_memory().unwrap();
+        FetchIntentsRepository::record(&db, "peer-a", &ids(&["post1", "post2"]), 1000).unwrap();
+        FetchIntentsRepository::record(&db, "peer-b", &ids(&["post3"]), 1000).unwrap();
+
+        assert_eq!(FetchIntentsRepository::complete(&db, "post1").unwrap(), 1);
+        assert_eq!(
+            FetchIntentsRepository::claim_for_retry(&db, "peer-a", 3).unwrap(),
+            ids(&["post2"])
+        );
+        assert_eq!(
+            FetchIntentsRepository::claim_for_retry(&db, "peer-b", 3).unwrap(),
+            ids(&["post3"])
+        );
+    }
+
+    #[test]
+    fn test_intents_dropped_after_max_attempts() {
+        let db = Database::in_memory().unwrap();
+        FetchIntentsRepository::record(&db, "peer-a", &ids(&["post1"]), 1000).unwrap();
+
+        for _ in 0..2 {
+            assert_eq!(
+                FetchIntentsRepository::claim_for_retry(&db, "peer-a", 2).unwrap(),
+                ids(&["post1"])
+            );
+        }
+        assert!(FetchIntentsRepository::claim_for_retry(&db, "peer-a", 2)
+            .unwrap()
+            .is_empty());
+
+        // Offered again by a new manifest, the post gets a fresh set of attempts
+        FetchIntentsRepository::record(&db, "peer-a", &ids(&["post1"]), 2000).unwrap();
+        assert_eq!(
+            FetchIntentsRepository::claim_for_retry(&db, "peer-a", 2).unwrap(),
+            ids(&["post1"])
+        );
+    }
+}
@@ -30,7 +30,8 @@ impl<'a> IdentityRepository<'a> {
         self.db.with_connection(|conn| {
             let mut stmt = conn.prepare(
                 "SELECT peer_id, public_key, x25519_public, private_key_encrypted,
-                        display_name, avatar_hash, bio, passphrase_hint, created_at, updated_at
+                        display_name, avatar_hash, bio, passphrase_hint, created_at, updated_at,
+                        accent_color
                  FROM local_identity WHERE id = 1",
             )?;
 
@@ -46,6 +47,7 @@ impl<'a> IdentityRepository<'a> {
                     passphrase_hint: row.get(7)?,
                     created_at: row.get(8)?,
                     updated_at: row.get(9)?,
+                    accent_color: row.get(10)?,
                 })
             });
 
@@ -63,8 +65,9 @@ impl<'a> IdentityRepository<'a> {
             conn.execute(
                 "INSERT INTO local_identity
                  (id, peer_id, public_key, x25519_public, private_key_encrypted,
-                  display_name, avatar_hash, bio, passphrase_hint, created_at, updated_at)
-                 VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10)",
+                  display_name, avatar_hash, bio, passphrase_hint, created_at, updated_at,
+                  accent_color)
+                 VALUES (1, ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
                 params![
                     identity.peer_id,
                     identity.public_key,
@@ -76,6 +79,7 @@ impl<'a> IdentityRepository<'a> {
                     identity.passphrase_hint,
                     identity.created_at,
                     identity.updated_at,
+                    identity.accent_color,
                 ],
             )?;
             info!("Created local identity: {}", identity.peer_id);
@@ -130,6 +134,30 @@ impl<'a> IdentityRepository<'a> {
             Ok(())
         })
     }
+
+    /// Update accent color
+    pub fn update_accent_color(&self, accent_color: Option<&str>) -> SqliteResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE local_identity SET accent_color = ?1, updated_at = ?2 WHERE id = 1",
+                params![accent_color, now],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Replace the encrypted private keys (after a passphrase change)
+    pub fn update_private_key_encrypted(&self, encrypted: &[u8]) -> SqliteResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        self.db.with_connection(|conn| {
+            conn.execute(
+                "UPDATE local_identity SET private_key_encrypted = ?1, updated_at = ?2 WHERE id = 1",
+                params![encrypted, now],
+            )?;
+            Ok(())
+        })
+    }
 }
 
 #[cfg(test)]
@@ -146,6 +174,7 @@ mod tests {
             avatar_hash: None,
             bio: Some("Test bio".to_string()),
             passphrase_hint: Some("My hint".to_string()),
+            accent_color: None,
             created_at: 1000,
             updated_at: 1000,
         }
@@ -1,7 +1,7 @@
 //! Messages repository for storing and retrieving direct messages
 
 use crate::db::Database;
-use rusqlite::{params, Connection, Result as SqliteResult};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
 
 /// Message status
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -50,6 +50,10 @@ pub struct Message {
     pub content_type: String,
     pub reply_to_message_id: Option<String>,
     pub nonce_counter: u64,
+    /// Random per-message nonce salt (None for legacy counter-only nonces)
+    pub nonce_salt: Option<Vec<u8>>,
+    /// Identifier of the cipher the content is encrypted with
+    pub cipher: String,
     pub lamport_clock: i64,
     pub sent_at: i64,
     pub received_at: Option<i64>,
@@ -57,6 +61,8 @@ pub struct Message {
     pub read_at: Option<i64>,
     pub status: String,
     pub edited_at: Option<i64>,
+    /// When the sender deleted the message; its content is gone
+    pub deleted_at: Option<i64>,
 }
 
 /// Data for inserting a new message
@@ -70,6 +76,8 @@ pub struct MessageData {
     pub content_type: String,
     pub reply_to_message_id: Option<String>,
     pub nonce_counter: u64,
+    pub nonce_salt: Option<Vec<u8>>,
+    pub cipher: String,
     pub lamport_clock: i64,
     pub sent_at: i64,
     pub received_at: Option<i64>,
@@ -82,8 +90,58 @@ pub struct Conversation {
     pub conversation_id: String,
     pub peer_id: String,
     pub last_message_at: i64,
+    /// Filled in by the messaging service, which can decrypt `last_message`
     pub last_message_preview: Option<String>,
     pub unread_count: i64,
+    pub pinned: bool,
+    pub last_message: Message,
+    /// The peer's X25519 key, if they are still a contact
+    pub peer_x25519_public: Option<Vec<u8>>,
+    /// Messages we sent to ourselves
+    pub is_note_to_self: bool,
+}
+
+/// Audio attached to a voice note message
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageMedia {
+    pub message_id: String,
+    pub media_hash: String,
+    pub mime_type: String,
+    pub size_bytes: i64,
+    pub duration_ms: i64,
+}
+
+/// A signed edit or delete held until the message it changes arrives
+#[derive(Debug, Clone)]
+pub struct HeldMessageChange {
+    pub message_id: String,
+    /// "edit" or "delete"
+    pub change_type: String,
+    pub sender_peer_id: String,
+    pub lamport_clock: i64,
+    /// The signed change as canonical CBOR
+    pub payload_cbor: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// Position in a conversation to page back from: messages sent before
+/// `sent_at`, or at `sent_at` with a smaller message ID
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MessageCursor {
+    pub sent_at: i64,
+    /// Tiebreak among messages sent in the same second; without it every
+    /// message at `sent_at` is skipped
+    pub message_id: Option<String>,
+}
+
+/// Retention override for a single conversation
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConversationRetention {
+    pub conversation_id: String,
+    /// Conversation-specific window; `None` falls back to the global setting
+    pub retention_days: Option<u32>,
+    /// Never prune this conversation, whatever the windows say
+    pub never_delete: bool,
 }
 
 /// Repository for message operations
@@ -99,6 +157,8 @@ pub struct RecordMessageEventParams<'a> {
     pub timestamp: i64,
     pub payload_cbor: &'a [u8],
     pub signature: &'a [u8],
+    /// Signature scheme version `signature` was made with
+    pub sig_version: u32,
 }
 
 pub struct MessagesRepository;
@@ -111,8 +171,8 @@ impl MessagesRepository {
                 "INSERT INTO messages (
                     message_id, conversation_id, sender_peer_id, recipient_peer_id,
                     content_encrypted, content_type, reply_to_message_id, nonce_counter,
-                    lamport_clock, sent_at, received_at, status
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    nonce_salt, cipher, lamport_clock, sent_at, received_at, status
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     msg.message_id,
                     msg.conversation_id,
@@ -122,6 +182,8 @@ impl MessagesRepository {
                     msg.content_type,
                     msg.reply_to_message_id,
                     msg.nonce_counter as i64,
+                    msg.nonce_salt,
+                    msg.cipher,
                     msg.lamport_clock,
                     msg.sent_at,
                     msg.received_at,
@@ -144,7 +206,8 @@ impl MessagesRepository {
         let mut stmt = conn.prepare(
             "SELECT id, message_id, conversation_id, sender_peer_id, recipient_peer_id,
                     content_encrypted, content_type, reply_to_message_id, nonce_counter,
-                    lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at
+                    lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at,
+                    nonce_salt, cipher, deleted_at
              FROM messages WHERE message_id = ?",
         )?;
 
@@ -168,6 +231,9 @@ impl MessagesRepository {
                 read_at: row.get(13)?,
                 status: row.get(14)?,
                 edited_at: row.get(15)?,
+                nonce_salt: row.get(16)?,
+                cipher: row.get(17)?,
+                deleted_at: row.get(18)?,
             }))
         } else {
             Ok(None)
@@ -179,43 +245,77 @@ impl MessagesRepository {
         db: &Database,
         conversation_id: &str,
         limit: i64,
-        before_timestamp: Option<i64>,
+        before: Option<&MessageCursor>,
     ) -> SqliteResult<Vec<Message>> {
         db.with_connection(|conn| {
-            // For pagination, we need to get the N most recent messages, then sort them ASC for display
-            // When paginating (before_timestamp provided), get messages before that time
-            let query = if before_timestamp.is_some() {
+            // For pagination, we need to get the N most recent messages, then sort them ASC for display.
+            // message_id breaks ties between messages sent in the same second so pages never
+            // overlap or skip.
+            let mut stmt = conn.prepare(
                 "SELECT id, message_id, conversation_id, sender_peer_id, recipient_peer_id,
                         content_encrypted, content_type, reply_to_message_id, nonce_counter,
-                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at
+                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at,
+                        nonce_salt, cipher, deleted_at
                  FROM (
                    SELECT * FROM messages
-                   WHERE conversation_id = ? AND sent_at < ?
-                   ORDER BY sent_at DESC
-                   LIMIT ?
-                 ) ORDER BY sent_at ASC"
-            } else {
+                   WHERE conversation_id = ?1
+                     AND (?2 IS NULL
+                          OR sent_at < ?2
+                          OR (sent_at = ?2 AND message_id < ?3))
+                   ORDER BY sent_at DESC, message_id DESC
+                   LIMIT ?4
+                 ) ORDER BY sent_at ASC, message_id ASC",
+            )?;
+
+            let rows = stmt.query_map(
+                params![
+                    conversation_id,
+                    before.map(|cursor| cursor.sent_at),
+                    before.and_then(|cursor| cursor.message_id.as_deref()),
+                    limit
+                ],
+                Self::row_to_message,
+            )?;
+
+            rows.collect()
+        })
+    }
+
+    /// Get messages to search, newest first, across every conversation or
+    /// just `conversation_id`. Content is encrypted, so matching happens
+    /// after decryption; this pages through candidates with `before` the
+    /// cursor of the last message returned. Deleted messages are skipped.
+    pub fn search(
+        db: &Database,
+        conversation_id: Option<&str>,
+        limit: i64,
+        before: Option<&MessageCursor>,
+    ) -> SqliteResult<Vec<Message>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
                 "SELECT id, message_id, conversation_id, sender_peer_id, recipient_peer_id,
                         content_encrypted, content_type, reply_to_message_id, nonce_counter,
-                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at
-                 FROM (
-                   SELECT * FROM messages
-                   WHERE conversation_id = ?
-                   ORDER BY sent_at DESC
-                   LIMIT ?
-                 ) ORDER BY sent_at ASC"
-            };
-
-            let mut stmt = conn.prepare(query)?;
+                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at,
+                        nonce_salt, cipher, deleted_at
+                 FROM messages
+                 WHERE (?1 IS NULL OR conversation_id = ?1)
+                   AND deleted_at IS NULL
+                   AND (?2 IS NULL
+                        OR sent_at < ?2
+                        OR (sent_at = ?2 AND message_id < ?3))
+                 ORDER BY sent_at DESC, message_id DESC
+                 LIMIT ?4",
+            )?;
 
-            let rows = if let Some(before) = before_timestamp {
-                stmt.query_map(
-                    params![conversation_id, before, limit],
-                    Self::row_to_message,
-                )?
-            } else {
-                stmt.query_map(params![conversation_id, limit], Self::row_to_message)?
-            };
+            let rows = stmt.query_map(
+                params![
+                    conversation_id,
+                    before.map(|cursor| cursor.sent_at),
+                    before.and_then(|cursor| cursor.message_id.as_deref()),
+                    limit
+                ],
+                Self::row_to_message,
+            )?;
 
             rows.collect()
         })
@@ -239,6 +339,9 @@ impl MessagesRepository {
             read_at: row.get(13)?,
             status: row.get(14)?,
             edited_at: row.get(15)?,
+            nonce_salt: row.get(16)?,
+            cipher: row.get(17)?,
+            deleted_at: row.get(18)?,
         })
     }
 
@@ -257,6 +360,23 @@ impl MessagesRepository {
         })
     }
 
+    /// Record whether a pending message reached the peer. Later states
+    /// (delivered, read) from a receipt that beat us here are left alone.
+    pub fn mark_send_outcome(db: &Database, message_id: &str, sent: bool) -> SqliteResult<bool> {
+        let status = if sent {
+            MessageStatus::Sent
+        } else {
+            MessageStatus::Failed
+        };
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE messages SET status = ? WHERE message_id = ? AND status = 'pending'",
+                params![status.as_str(), message_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
     /// Mark message as delivered
     pub fn mark_delivered(db: &Database, message_id: &str, timestamp: i64) -> SqliteResult<bool> {
         db.with_connection(|conn| {
@@ -281,57 +401,116 @@ impl MessagesRepository {
         })
     }
 
-    /// Mark all messages in a conversation as read
+    /// Mark all messages in a conversation as read, returning the IDs of
+    /// those that weren't already
     pub fn mark_conversation_read(
         db: &Database,
         conversation_id: &str,
         our_peer_id: &str,
         timestamp: i64,
-    ) -> SqliteResult<i64> {
+    ) -> SqliteResult<Vec<String>> {
         db.with_connection(|conn| {
-            let rows = conn.execute(
+            let mut stmt = conn.prepare(
                 "UPDATE messages SET status = 'read', read_at = ?
                  WHERE conversation_id = ? AND recipient_peer_id = ?
-                   AND status IN ('delivered', 'sent')",
-                params![timestamp, conversation_id, our_peer_id],
+                   AND status IN ('delivered', 'sent')
+                 RETURNING message_id",
             )?;
-            Ok(rows as i64)
+            let rows = stmt.query_map(params![timestamp, conversation_id, our_peer_id], |row| {
+                row.get(0)
+            })?;
+            rows.collect()
+        })
+    }
+
+    /// Mark messages we sent `reader_peer_id` in a conversation as read,
+    /// from their read receipt. Returns the IDs that changed; IDs of other
+    /// messages, or of messages already read, are ignored.
+    pub fn mark_messages_read(
+        db: &Database,
+        conversation_id: &str,
+        reader_peer_id: &str,
+        message_ids: &[String],
+        timestamp: i64,
+    ) -> SqliteResult<Vec<String>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            let mut marked = Vec::new();
+            {
+                let mut stmt = tx.prepare(
+                    "UPDATE messages SET status = 'read', read_at = ?
+                     WHERE message_id = ? AND conversation_id = ? AND recipient_peer_id = ?
+                       AND status IN ('pending', 'sent', 'delivered')",
+                )?;
+                for message_id in message_ids {
+                    if stmt.execute(params![
+                        timestamp,
+                        message_id,
+                        conversation_id,
+                        reader_peer_id
+                    ])? > 0
+                    {
+                        marked.push(message_id.clone());
+                    }
+                }
+            }
+            tx.commit()?;
+            Ok(marked)
         })
     }
 
     /// Get all conversations for a peer
     pub fn get_conversations(db: &Database, our_peer_id: &str) -> SqliteResult<Vec<Conversation>> {
+        // One pass over the messages picks each conversation's newest message
+        // and counts its unread ones, so the inbox costs a single query
         db.with_connection(|conn| {
             let mut stmt = conn.prepare(
-                "SELECT
-                    m.conversation_id,
-                    CASE
-                        WHEN m.sender_peer_id = ? THEN m.recipient_peer_id
-                        ELSE m.sender_peer_id
-                    END as peer_id,
-                    MAX(m.sent_at) as last_message_at,
-                    (SELECT COUNT(*) FROM messages m2
-                     WHERE m2.conversation_id = m.conversation_id
-                       AND m2.recipient_peer_id = ?
-                       AND m2.status IN ('sent', 'delivered')) as unread_count
-                 FROM messages m
-                 WHERE m.sender_peer_id = ? OR m.recipient_peer_id = ?
-                 GROUP BY m.conversation_id
-                 ORDER BY last_message_at DESC",
+                "WITH ranked AS (
+                    SELECT m.*,
+                        ROW_NUMBER() OVER (
+                            PARTITION BY m.conversation_id ORDER BY m.sent_at DESC, m.id DESC
+                        ) AS position,
+                        SUM(m.recipient_peer_id = ?1 AND m.status IN ('sent', 'delivered'))
+                            OVER (PARTITION BY m.conversation_id) AS unread_count
+                    FROM messages m
+                    WHERE m.sender_peer_id = ?1 OR m.recipient_peer_id = ?1
+                 )
+                 SELECT r.id, r.message_id, r.conversation_id, r.sender_peer_id,
+                        r.recipient_peer_id, r.content_encrypted, r.content_type,
+                        r.reply_to_message_id, r.nonce_counter, r.lamport_clock, r.sent_at,
+                        r.received_at, r.delivered_at, r.read_at, r.status, r.edited_at,
+                        r.nonce_salt, r.cipher, r.deleted_at, r.unread_count,
+                        p.pinned_at IS NOT NULL, c.x25519_public
+                 FROM ranked r
+                 LEFT JOIN conversation_pins p ON p.conversation_id = r.conversation_id
+                 LEFT JOIN contacts c ON c.peer_id = CASE
+                     WHEN r.sender_peer_id = ?1 THEN r.recipient_peer_id
+                     ELSE r.sender_peer_id
+                 END
+                 WHERE r.position = 1
+                 ORDER BY p.pinned_at IS NULL, r.sent_at DESC",
             )?;
 
-            let rows = stmt.query_map(
-                params![our_peer_id, our_peer_id, our_peer_id, our_peer_id],
-                |row| {
-                    Ok(Conversation {
-                        conversation_id: row.get(0)?,
-                        peer_id: row.get(1)?,
-                        last_message_at: row.get(2)?,
-                        last_message_preview: None, // We don't store decrypted content
-                        unread_count: row.get(3)?,
-                    })
-                },
-            )?;
+            let rows = stmt.query_map(params![our_peer_id], |row| {
+                let last_message = Self::row_to_message(row)?;
+                let peer_id = if last_message.sender_peer_id == our_peer_id {
+                    last_message.recipient_peer_id.clone()
+                } else {
+                    last_message.sender_peer_id.clone()
+                };
+                let is_note_to_self = peer_id == our_peer_id;
+                Ok(Conversation {
+                    conversation_id: last_message.conversation_id.clone(),
+                    peer_id,
+                    last_message_at: last_message.sent_at,
+                    last_message_preview: None,
+                    unread_count: row.get(19)?,
+                    pinned: row.get(20)?,
+                    peer_x25519_public: row.get(21)?,
+                    is_note_to_self,
+                    last_message,
+                })
+            })?;
 
             rows.collect()
         })
@@ -363,9 +542,10 @@ impl MessagesRepository {
             let mut stmt = conn.prepare(
                 "SELECT id, message_id, conversation_id, sender_peer_id, recipient_peer_id,
                         content_encrypted, content_type, reply_to_message_id, nonce_counter,
-                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at
+                        lamport_clock, sent_at, received_at, delivered_at, read_at, status, edited_at,
+                        nonce_salt, cipher, deleted_at
                  FROM messages
-                 WHERE recipient_peer_id = ? AND status = 'pending'
+                 WHERE recipient_peer_id = ? AND status = 'pending' AND deleted_at IS NULL
                  ORDER BY sent_at ASC",
             )?;
 
@@ -374,18 +554,68 @@ impl MessagesRepository {
         })
     }
 
-    /// Update message content and set edited_at timestamp
-    pub fn update_message_content(
+    /// Replace a message's content with an edit made at `lamport_clock`.
+    ///
+    /// Only applies when the edit is newer than the message and any edit
+    /// already applied, and the message isn't deleted, so edits delivered out
+    /// of order settle on the newest. Returns whether the edit was applied.
+    pub fn apply_edit(
         db: &Database,
         message_id: &str,
         new_content_encrypted: &[u8],
+        nonce_salt: Option<&[u8]>,
+        lamport_clock: i64,
         edited_at: i64,
     ) -> SqliteResult<bool> {
         db.with_connection(|conn| {
             let rows = conn.execute(
-                "UPDATE messages SET content_encrypted = ?, edited_at = ? WHERE message_id = ?",
-                params![new_content_encrypted, edited_at, message_id],
+                "UPDATE messages
+                 SET content_encrypted = ?1, nonce_salt = ?2, edit_lamport_clock = ?3,
+                     edited_at = ?4
+                 WHERE message_id = ?5 AND deleted_at IS NULL
+                   AND COALESCE(edit_lamport_clock, lamport_clock) < ?3",
+                params![
+                    new_content_encrypted,
+                    nonce_salt,
+                    lamport_clock,
+                    edited_at,
+                    message_id
+                ],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Turn a message into a tombstone: its row stays, marked deleted, with
+    /// the content wiped. Returns false if it was already deleted.
+    ///
+    /// The sent, received and edited events carry the message's ciphertext,
+    /// as do a copy and edits still queued for delivery, so they go with it;
+    /// only the deletion event, recorded afterwards, remains.
+    pub fn soft_delete_message(
+        db: &Database,
+        message_id: &str,
+        deleted_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            let rows = tx.execute(
+                "UPDATE messages SET deleted_at = ?, content_encrypted = X'', nonce_salt = NULL
+                 WHERE message_id = ? AND deleted_at IS NULL",
+                params![deleted_at, message_id],
             )?;
+            if rows > 0 {
+                tx.execute(
+                    "DELETE FROM message_events WHERE message_id = ? AND event_type != 'deleted'",
+                    [message_id],
+                )?;
+                tx.execute(
+                    "DELETE FROM pending_outbound
+                     WHERE target_message_id = ? AND kind IN ('message', 'edit')",
+                    [message_id],
+                )?;
+            }
+            tx.commit()?;
             Ok(rows > 0)
         })
     }
@@ -413,8 +643,8 @@ impl MessagesRepository {
                 "INSERT INTO message_events (
                     event_id, event_type, message_id, conversation_id,
                     sender_peer_id, recipient_peer_id, lamport_clock,
-                    timestamp, payload_cbor, signature, received_at
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    timestamp, payload_cbor, signature, received_at, sig_version
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     params.event_id,
                     params.event_type,
@@ -427,6 +657,7 @@ impl MessagesRepository {
                     params.payload_cbor,
                     params.signature,
                     received_at,
+                    params.sig_version,
                 ],
             )?;
             Ok(conn.last_insert_rowid())
@@ -456,6 +687,126 @@ impl MessagesRepository {
         })
     }
 
+    /// Get the ids of all conversations that have stored messages
+    pub fn get_conversation_ids(db: &Database) -> SqliteResult<Vec<String>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare("SELECT DISTINCT conversation_id FROM messages")?;
+            let ids = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<SqliteResult<Vec<String>>>()?;
+            Ok(ids)
+        })
+    }
+
+    /// Delete messages sent before `cutoff` in a conversation, along with their events
+    pub fn delete_messages_before(
+        db: &Database,
+        conversation_id: &str,
+        cutoff: i64,
+    ) -> SqliteResult<i64> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "DELETE FROM message_events WHERE message_id IN (
+                    SELECT message_id FROM messages WHERE conversation_id = ? AND sent_at < ?
+                 )",
+                params![conversation_id, cutoff],
+            )?;
+            let rows = tx.execute(
+                "DELETE FROM messages WHERE conversation_id = ? AND sent_at < ?",
+                params![conversation_id, cutoff],
+            )?;
+            tx.commit()?;
+            Ok(rows as i64)
+        })
+    }
+
+    /// Get the retention override for a conversation, if any
+    pub fn get_conversation_retention(
+        db: &Database,
+        conversation_id: &str,
+    ) -> SqliteResult<Option<ConversationRetention>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT conversation_id, retention_days, never_delete
+                 FROM conversation_retention WHERE conversation_id = ?",
+                [conversation_id],
+                |row| {
+                    Ok(ConversationRetention {
+                        conversation_id: row.get(0)?,
+                        retention_days: row.get(1)?,
+                        never_delete: row.get::<_, i32>(2)? != 0,
+                    })
+                },
+            )
+            .optional()
+        })
+    }
+
+    /// Set or replace the retention override for a conversation
+    pub fn set_conversation_retention(
+        db: &Database,
+        retention: &ConversationRetention,
+    ) -> SqliteResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO conversation_retention
+                    (conversation_id, retention_days, never_delete, updated_at)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT(conversation_id) DO UPDATE SET
+                    retention_days = excluded.retention_days,
+                    never_delete = excluded.never_delete,
+                    updated_at = excluded.updated_at",
+                params![
+                    retention.conversation_id,
+                    retention.retention_days,
+                    retention.never_delete as i32,
+                    now
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Pin a conversation to the top of the inbox, or unpin it
+    pub fn set_conversation_pinned(
+        db: &Database,
+        conversation_id: &str,
+        pinned: bool,
+        pinned_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            if pinned {
+                conn.execute(
+                    "INSERT OR IGNORE INTO conversation_pins (conversation_id, pinned_at)
+                     VALUES (?, ?)",
+                    params![conversation_id, pinned_at],
+                )?;
+            } else {
+                conn.execute(
+                    "DELETE FROM conversation_pins WHERE conversation_id = ?",
+                    [conversation_id],
+                )?;
+            }
+            Ok(())
+        })
+    }
+
+    /// Remove the retention override for a conversation. Returns `false` if none was set.
+    pub fn clear_conversation_retention(
+        db: &Database,
+        conversation_id: &str,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "DELETE FROM conversation_retention WHERE conversation_id = ?",
+                [conversation_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
     /// Delete a conversation and all its messages
     pub fn delete_conversation(db: &Database, conversation_id: &str) -> SqliteResult<i64> {
         db.with_connection(|conn| {
@@ -464,6 +815,10 @@ impl MessagesRepository {
                 "DELETE FROM message_events WHERE conversation_id = ?",
                 params![conversation_id],
             )?;
+            conn.execute(
+                "DELETE FROM conversation_pins WHERE conversation_id = ?",
+                params![conversation_id],
+            )?;
             // Delete all messages in the conversation
             let rows = conn.execute(
                 "DELETE FROM messages WHERE conversation_id = ?",
@@ -472,6 +827,196 @@ impl MessagesRepository {
             Ok(rows as i64)
         })
     }
+
+    /// Record the audio a voice note message references
+    pub fn insert_message_media(db: &Database, media: &MessageMedia) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT OR REPLACE INTO message_media
+                 (message_id, media_hash, mime_type, size_bytes, duration_ms)
+                 VALUES (?, ?, ?, ?, ?)",
+                params![
+                    media.message_id,
+                    media.media_hash,
+                    media.mime_type,
+                    media.size_bytes,
+                    media.duration_ms,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Get the audio a message references, if it is a voice note
+    pub fn get_message_media(
+        db: &Database,
+        message_id: &str,
+    ) -> SqliteResult<Option<MessageMedia>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT message_id, media_hash, mime_type, size_bytes, duration_ms
+                 FROM message_media WHERE message_id = ?",
+                [message_id],
+                Self::row_to_message_media,
+            )
+            .optional()
+        })
+    }
+
+    /// Get a voice note of a stored message that references `media_hash`
+    pub fn get_message_media_by_hash(
+        db: &Database,
+        media_hash: &str,
+    ) -> SqliteResult<Option<MessageMedia>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT mm.message_id, mm.media_hash, mm.mime_type, mm.size_bytes, mm.duration_ms
+                 FROM message_media mm
+                 JOIN messages m ON m.message_id = mm.message_id
+                 WHERE mm.media_hash = ?
+                 LIMIT 1",
+                [media_hash],
+                Self::row_to_message_media,
+            )
+            .optional()
+        })
+    }
+
+    /// Whether `media_hash` is the audio of a voice note exchanged with `peer_id`
+    pub fn message_media_shared_with(
+        db: &Database,
+        media_hash: &str,
+        peer_id: &str,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT EXISTS(
+                    SELECT 1 FROM message_media mm
+                    JOIN messages m ON m.message_id = mm.message_id
+                    WHERE mm.media_hash = ?1
+                      AND (m.sender_peer_id = ?2 OR m.recipient_peer_id = ?2)
+                 )",
+                params![media_hash, peer_id],
+                |row| row.get(0),
+            )
+        })
+    }
+
+    /// Remove voice note rows whose message was deleted or left as a
+    /// tombstone, returning the media hashes they referenced so unused audio
+    /// can be deleted
+    pub fn take_orphaned_message_media(db: &Database) -> SqliteResult<Vec<String>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            let mut stmt = tx.prepare(
+                "SELECT DISTINCT media_hash FROM message_media
+                 WHERE message_id NOT IN (SELECT message_id FROM messages WHERE deleted_at IS NULL)",
+            )?;
+            let hashes = stmt
+                .query_map([], |row| row.get(0))?
+                .collect::<SqliteResult<Vec<String>>>()?;
+            drop(stmt);
+            tx.execute(
+                "DELETE FROM message_media
+                 WHERE message_id NOT IN (SELECT message_id FROM messages WHERE deleted_at IS NULL)",
+                [],
+            )?;
+            tx.commit()?;
+            Ok(hashes)
+        })
+    }
+
+    /// Hold a change to a message that hasn't arrived yet. A newer edit
+    /// replaces an older one held for the same message.
+    pub fn hold_change(
+        db: &Database,
+        change: &HeldMessageChange,
+        received_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO held_message_changes (
+                    message_id, change_type, sender_peer_id, lamport_clock,
+                    payload_cbor, signature, received_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?)
+                ON CONFLICT(message_id, change_type) DO UPDATE SET
+                    sender_peer_id = excluded.sender_peer_id,
+                    lamport_clock = excluded.lamport_clock,
+                    payload_cbor = excluded.payload_cbor,
+                    signature = excluded.signature,
+                    received_at = excluded.received_at
+                WHERE excluded.lamport_clock > held_message_changes.lamport_clock",
+                params![
+                    change.message_id,
+                    change.change_type,
+                    change.sender_peer_id,
+                    change.lamport_clock,
+                    change.payload_cbor,
+                    change.signature,
+                    received_at,
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Number of changes held for messages from `sender_peer_id`
+    pub fn count_held_changes(db: &Database, sender_peer_id: &str) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM held_message_changes WHERE sender_peer_id = ?",
+                [sender_peer_id],
+                |row| row.get(0),
+            )
+        })
+    }
+
+    /// Remove and return the changes held for a message, edits before the
+    /// delete
+    pub fn take_held_changes(
+        db: &Database,
+        message_id: &str,
+    ) -> SqliteResult<Vec<HeldMessageChange>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            let mut stmt = tx.prepare(
+                "SELECT message_id, change_type, sender_peer_id, lamport_clock,
+                        payload_cbor, signature
+                 FROM held_message_changes
+                 WHERE message_id = ?
+                 ORDER BY change_type = 'delete', lamport_clock",
+            )?;
+            let changes = stmt
+                .query_map([message_id], |row| {
+                    Ok(HeldMessageChange {
+                        message_id: row.get(0)?,
+                        change_type: row.get(1)?,
+                        sender_peer_id: row.get(2)?,
+                        lamport_clock: row.get(3)?,
+                        payload_cbor: row.get(4)?,
+                        signature: row.get(5)?,
+                    })
+                })?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            drop(stmt);
+            tx.execute(
+                "DELETE FROM held_message_changes WHERE message_id = ?",
+                [message_id],
+            )?;
+            tx.commit()?;
+            Ok(changes)
+        })
+    }
+
+    fn row_to_message_media(row: &rusqlite::Row) -> SqliteResult<MessageMedia> {
+        Ok(MessageMedia {
+            message_id: row.get(0)?,
+            media_hash: row.get(1)?,
+            mime_type: row.get(2)?,
+            size_bytes: row.get(3)?,
+            duration_ms: row.get(4)?,
+        })
+    }
 }
 
 #[cfg(test)]
@@ -495,6 +1040,8 @@ mod tests {
             content_type: "text".to_string(),
             reply_to_message_id: None,
             nonce_counter: 1,
+            nonce_salt: None,
+            cipher: "aes-256-gcm".to_string(),
             lamport_clock: 1,
             sent_at: 1234567890,
             received_at: None,
@@ -512,6 +1059,113 @@ mod tests {
         assert_eq!(stored.status, "pending");
     }
 
+    #[test]
+    fn test_conversation_pages_break_timestamp_ties_by_message_id() {
+        let db = create_test_db();
+
+        // Five messages, three of them sent in the same second
+        for (message_id, sent_at) in [
+            ("msg-a", 100),
+            ("msg-b", 200),
+            ("msg-c", 200),
+            ("msg-d", 200),
+            ("msg-e", 300),
+        ] {
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: "conv-page".to_string(),
+                sender_peer_id: "peer-a".to_string(),
+                recipient_peer_id: "peer-b".to_string(),
+                content_encrypted: vec![1, 2, 3, 4],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at,
+                received_at: None,
+                status: MessageStatus::Sent,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+        }
+
+        let ids = |messages: Vec<Message>| -> Vec<String> {
+            messages.into_iter().map(|m| m.message_id).collect()
+        };
+
+        let newest =
+            MessagesRepository::get_conversation_messages(&db, "conv-page", 2, None).unwrap();
+        assert_eq!(ids(newest), vec!["msg-d", "msg-e"]);
+
+        let cursor = MessageCursor {
+            sent_at: 200,
+            message_id: Some("msg-d".to_string()),
+        };
+        let older =
+            MessagesRepository::get_conversation_messages(&db, "conv-page", 2, Some(&cursor))
+                .unwrap();
+        assert_eq!(ids(older), vec!["msg-b", "msg-c"]);
+
+        // A timestamp-only cursor skips everything sent in that second
+        let cursor = MessageCursor {
+            sent_at: 200,
+            message_id: None,
+        };
+        let oldest =
+            MessagesRepository::get_conversation_messages(&db, "conv-page", 10, Some(&cursor))
+                .unwrap();
+        assert_eq!(ids(oldest), vec!["msg-a"]);
+    }
+
+    #[test]
+    fn test_search_pages_newest_first_and_skips_deleted() {
+        let db = create_test_db();
+
+        for (message_id, conversation_id, sent_at) in [
+            ("msg-a", "conv-1", 100),
+            ("msg-b", "conv-2", 200),
+            ("msg-c", "conv-1", 300),
+            ("msg-d", "conv-1", 400),
+        ] {
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: conversation_id.to_string(),
+                sender_peer_id: "peer-a".to_string(),
+                recipient_peer_id: "peer-b".to_string(),
+                content_encrypted: vec![1, 2, 3, 4],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at,
+                received_at: None,
+                status: MessageStatus::Sent,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+        }
+        MessagesRepository::soft_delete_message(&db, "msg-c", 500).unwrap();
+
+        let ids = |messages: Vec<Message>| -> Vec<String> {
+            messages.into_iter().map(|m| m.message_id).collect()
+        };
+
+        let first = MessagesRepository::search(&db, None, 2, None).unwrap();
+        assert_eq!(ids(first), vec!["msg-d", "msg-b"]);
+
+        let cursor = MessageCursor {
+            sent_at: 200,
+            message_id: Some("msg-b".to_string()),
+        };
+        let rest = MessagesRepository::search(&db, None, 2, Some(&cursor)).unwrap();
+        assert_eq!(ids(rest), vec!["msg-a"]);
+
+        let scoped = MessagesRepository::search(&db, Some("conv-1"), 10, None).unwrap();
+        assert_eq!(ids(scoped), vec!["msg-d", "msg-a"]);
+    }
+
     #[test]
     fn test_mark_delivered_and_read() {
         let db = create_test_db();
@@ -525,6 +1179,8 @@ mod tests {
             content_type: "text".to_string(),
             reply_to_message_id: None,
             nonce_counter: 1,
+            nonce_salt: None,
+            cipher: "aes-256-gcm".to_string(),
             lamport_clock: 1,
             sent_at: 1234567890,
             received_at: None,
@@ -554,6 +1210,65 @@ mod tests {
         assert_eq!(stored.read_at, Some(1234567910));
     }
 
+    #[test]
+    fn test_read_receipt_marks_only_messages_sent_to_reader() {
+        let db = create_test_db();
+
+        for (message_id, recipient) in [
+            ("msg-1", "peer-b"),
+            ("msg-2", "peer-b"),
+            ("msg-3", "peer-a"),
+        ] {
+            let sender = if recipient == "peer-b" {
+                "peer-a"
+            } else {
+                "peer-b"
+            };
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: "conv-ab".to_string(),
+                sender_peer_id: sender.to_string(),
+                recipient_peer_id: recipient.to_string(),
+                content_encrypted: vec![1, 2, 3, 4],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: 1000,
+                received_at: None,
+                status: MessageStatus::Delivered,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+        }
+
+        // peer-b claims all three, but only sent msg-3 itself
+        let ids = vec![
+            "msg-1".to_string(),
+            "msg-2".to_string(),
+            "msg-3".to_string(),
+            "msg-missing".to_string(),
+        ];
+        let marked =
+            MessagesRepository::mark_messages_read(&db, "conv-ab", "peer-b", &ids, 2000).unwrap();
+        assert_eq!(marked, vec!["msg-1", "msg-2"]);
+
+        // Repeated receipts change nothing
+        let marked =
+            MessagesRepository::mark_messages_read(&db, "conv-ab", "peer-b", &ids, 3000).unwrap();
+        assert!(marked.is_empty());
+        let stored = MessagesRepository::get_by_message_id(&db, "msg-1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.read_at, Some(2000));
+
+        // peer-a reading the conversation marks the one message it received
+        let read =
+            MessagesRepository::mark_conversation_read(&db, "conv-ab", "peer-a", 4000).unwrap();
+        assert_eq!(read, vec!["msg-3"]);
+    }
+
     #[test]
     fn test_get_conversations() {
         let db = create_test_db();
@@ -568,6 +1283,8 @@ mod tests {
             content_type: "text".to_string(),
             reply_to_message_id: None,
             nonce_counter: 1,
+            nonce_salt: None,
+            cipher: "aes-256-gcm".to_string(),
             lamport_clock: 1,
             sent_at: 1000,
             received_at: None,
@@ -583,6 +1300,8 @@ mod tests {
             content_type: "text".to_string(),
             reply_to_message_id: None,
             nonce_counter: 1,
+            nonce_salt: None,
+            cipher: "aes-256-gcm".to_string(),
             lamport_clock: 1,
             sent_at: 2000,
             received_at: Some(2000),
@@ -603,4 +1322,313 @@ mod tests {
         assert_eq!(conversations[1].conversation_id, "conv-1");
         assert_eq!(conversations[1].peer_id, "peer-b");
     }
+
+    #[test]
+    fn test_get_conversations_last_message_and_pins() {
+        let db = create_test_db();
+
+        let message =
+            |id: &str, conv: &str, sender: &str, recipient: &str, sent_at: i64| MessageData {
+                message_id: id.to_string(),
+                conversation_id: conv.to_string(),
+                sender_peer_id: sender.to_string(),
+                recipient_peer_id: recipient.to_string(),
+                content_encrypted: id.as_bytes().to_vec(),
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at,
+                received_at: None,
+                status: MessageStatus::Delivered,
+            };
+        for msg in [
+            message("msg-1", "conv-1", "peer-b", "peer-a", 1000),
+            message("msg-2", "conv-1", "peer-a", "peer-b", 1100),
+            message("msg-3", "conv-2", "peer-c", "peer-a", 2000),
+        ] {
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+        }
+
+        let conversations = MessagesRepository::get_conversations(&db, "peer-a").unwrap();
+        assert_eq!(conversations[0].conversation_id, "conv-2");
+        assert_eq!(conversations[1].last_message.message_id, "msg-2");
+        assert_eq!(conversations[1].last_message_at, 1100);
+        assert_eq!(conversations[1].unread_count, 1);
+        assert!(conversations[1].peer_x25519_public.is_none());
+
+        MessagesRepository::set_conversation_pinned(&db, "conv-1", true, 3000).unwrap();
+        let conversations = MessagesRepository::get_conversations(&db, "peer-a").unwrap();
+        assert_eq!(conversations[0].conversation_id, "conv-1");
+        assert!(conversations[0].pinned);
+        assert!(!conversations[1].pinned);
+
+        // Deleting the conversation drops its pin
+        MessagesRepository::delete_conversation(&db, "conv-1").unwrap();
+        MessagesRepository::insert_message(
+            &db,
+            &message("msg-4", "conv-1", "peer-b", "peer-a", 1200),
+        )
+        .unwrap();
+        let conversations = MessagesRepository::get_conversations(&db, "peer-a").unwrap();
+        assert_eq!(conversations[0].conversation_id, "conv-2");
+        assert!(!conversations[1].pinned);
+    }
+
+    #[test]
+    fn test_delete_messages_before() {
+        let db = create_test_db();
+
+        for (message_id, conversation_id, sent_at) in [
+            ("msg-old", "conv-1", 1000),
+            ("msg-new", "conv-1", 3000),
+            ("msg-other", "conv-2", 1000),
+        ] {
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: conversation_id.to_string(),
+                sender_peer_id: "peer-a".to_string(),
+                recipient_peer_id: "peer-b".to_string(),
+                content_encrypted: vec![1],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at,
+                received_at: None,
+                status: MessageStatus::Sent,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+            MessagesRepository::record_message_event(
+                &db,
+                &RecordMessageEventParams {
+                    event_id: &format!("evt-{}", message_id),
+                    event_type: "message",
+                    message_id,
+                    conversation_id,
+                    sender_peer_id: "peer-a",
+                    recipient_peer_id: "peer-b",
+                    lamport_clock: 1,
+                    timestamp: sent_at,
+                    payload_cbor: &[1],
+                    signature: &[2],
+                    sig_version: 1,
+                },
+            )
+            .unwrap();
+        }
+
+        let mut ids = MessagesRepository::get_conversation_ids(&db).unwrap();
+        ids.sort();
+        assert_eq!(ids, vec!["conv-1", "conv-2"]);
+
+        let deleted = MessagesRepository::delete_messages_before(&db, "conv-1", 2000).unwrap();
+        assert_eq!(deleted, 1);
+        assert!(!MessagesRepository::message_exists(&db, "msg-old").unwrap());
+        assert!(!MessagesRepository::event_exists(&db, "evt-msg-old").unwrap());
+        assert!(MessagesRepository::message_exists(&db, "msg-new").unwrap());
+        // Other conversations are untouched
+        assert!(MessagesRepository::message_exists(&db, "msg-other").unwrap());
+        assert!(MessagesRepository::event_exists(&db, "evt-msg-other").unwrap());
+    }
+
+    #[test]
+    fn test_conversation_retention_crud() {
+        let db = create_test_db();
+        assert_eq!(
+            MessagesRepository::get_conversation_retention(&db, "conv-1").unwrap(),
+            None
+        );
+
+        let mut retention = ConversationRetention {
+            conversation_id: "conv-1".to_string(),
+            retention_days: Some(7),
+            never_delete: false,
+        };
+        MessagesRepository::set_conversation_retention(&db, &retention).unwrap();
+        assert_eq!(
+            MessagesRepository::get_conversation_retention(&db, "conv-1").unwrap(),
+            Some(retention.clone())
+        );
+
+        retention.retention_days = None;
+        retention.never_delete = true;
+        MessagesRepository::set_conversation_retention(&db, &retention).unwrap();
+        assert_eq!(
+            MessagesRepository::get_conversation_retention(&db, "conv-1").unwrap(),
+            Some(retention)
+        );
+
+        assert!(MessagesRepository::clear_conversation_retention(&db, "conv-1").unwrap());
+        assert!(!MessagesRepository::clear_conversation_retention(&db, "conv-1").unwrap());
+    }
+
+    #[test]
+    fn test_mark_send_outcome_only_updates_pending() {
+        let db = create_test_db();
+
+        for message_id in ["msg-sent", "msg-failed", "msg-acked"] {
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: "conv-1".to_string(),
+                sender_peer_id: "peer-a".to_string(),
+                recipient_peer_id: "peer-b".to_string(),
+                content_encrypted: vec![1],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: 1234567890,
+                received_at: None,
+                status: MessageStatus::Pending,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+        }
+
+        assert!(MessagesRepository::mark_send_outcome(&db, "msg-sent", true).unwrap());
+        assert!(MessagesRepository::mark_send_outcome(&db, "msg-failed", false).unwrap());
+
+        // A delivery receipt that arrived first isn't downgraded
+        MessagesRepository::mark_delivered(&db, "msg-acked", 1234567900).unwrap();
+        assert!(!MessagesRepository::mark_send_outcome(&db, "msg-acked", true).unwrap());
+
+        let status = |id| {
+            MessagesRepository::get_by_message_id(&db, id)
+                .unwrap()
+                .unwrap()
+                .status
+        };
+        assert_eq!(status("msg-sent"), "sent");
+        assert_eq!(status("msg-failed"), "failed");
+        assert_eq!(status("msg-acked"), "delivered");
+    }
+
+    #[test]
+    fn test_message_media_orphaned_after_message_deleted() {
+        let db = create_test_db();
+
+        for message_id in ["msg-voice", "msg-voice-copy"] {
+            let msg = MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: "conv-1".to_string(),
+                sender_peer_id: "peer-a".to_string(),
+                recipient_peer_id: "peer-b".to_string(),
+                content_encrypted: vec![1],
+                content_type: "voice".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: if message_id == "msg-voice" {
+                    1000
+                } else {
+                    3000
+                },
+                received_at: None,
+                status: MessageStatus::Sent,
+            };
+            MessagesRepository::insert_message(&db, &msg).unwrap();
+            MessagesRepository::insert_message_media(
+                &db,
+                &MessageMedia {
+                    message_id: message_id.to_string(),
+                    media_hash: "ab".repeat(32),
+                    mime_type: "audio/ogg".to_string(),
+                    size_bytes: 2048,
+                    duration_ms: 4000,
+                },
+            )
+            .unwrap();
+        }
+
+        let media = MessagesRepository::get_message_media(&db, "msg-voice")
+            .unwrap()
+            .unwrap();
+        assert_eq!(media.duration_ms, 4000);
+        assert!(MessagesRepository::take_orphaned_message_media(&db)
+            .unwrap()
+            .is_empty());
+
+        // Once the message is pruned its row is released, but the audio is
+        // still referenced by the other message
+        MessagesRepository::delete_messages_before(&db, "conv-1", 2000).unwrap();
+        assert_eq!(
+            MessagesRepository::take_orphaned_message_media(&db).unwrap(),
+            vec!["ab".repeat(32)]
+        );
+        assert!(MessagesRepository::get_message_media(&db, "msg-voice")
+            .unwrap()
+            .is_none());
+        assert!(
+            MessagesRepository::get_message_media_by_hash(&db, &"ab".repeat(32))
+                .unwrap()
+                .is_some()
+        );
+
+        // A tombstone releases its audio too
+        MessagesRepository::soft_delete_message(&db, "msg-voice-copy", 4000).unwrap();
+        assert_eq!(
+            MessagesRepository::take_orphaned_message_media(&db).unwrap(),
+            vec!["ab".repeat(32)]
+        );
+        assert!(
+            MessagesRepository::get_message_media_by_hash(&db, &"ab".repeat(32))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_edits_settle_on_newest_and_stop_at_tombstone() {
+        let db = create_test_db();
+        let msg = MessageData {
+            message_id: "msg-1".to_string(),
+            conversation_id: "conv-1".to_string(),
+            sender_peer_id: "peer-a".to_string(),
+            recipient_peer_id: "peer-b".to_string(),
+            content_encrypted: vec![1],
+            content_type: "text".to_string(),
+            reply_to_message_id: None,
+            nonce_counter: 1,
+            nonce_salt: None,
+            cipher: "aes-256-gcm".to_string(),
+            lamport_clock: 5,
+            sent_at: 1000,
+            received_at: None,
+            status: MessageStatus::Delivered,
+        };
+        MessagesRepository::insert_message(&db, &msg).unwrap();
+        let content = |db: &Database| {
+            MessagesRepository::get_by_message_id(db, "msg-1")
+                .unwrap()
+                .unwrap()
+                .content_encrypted
+        };
+
+        // Not newer than the message itself
+        assert!(!MessagesRepository::apply_edit(&db, "msg-1", &[9], None, 5, 1100).unwrap());
+
+        // The later edit arrives first; the earlier one is then stale
+        assert!(MessagesRepository::apply_edit(&db, "msg-1", &[3], None, 8, 1300).unwrap());
+        assert!(!MessagesRepository::apply_edit(&db, "msg-1", &[2], None, 7, 1200).unwrap());
+        assert_eq!(content(&db), vec![3]);
+
+        assert!(MessagesRepository::soft_delete_message(&db, "msg-1", 1400).unwrap());
+        assert!(!MessagesRepository::soft_delete_message(&db, "msg-1", 1500).unwrap());
+        assert!(!MessagesRepository::apply_edit(&db, "msg-1", &[4], None, 9, 1600).unwrap());
+
+        let tombstone = MessagesRepository::get_by_message_id(&db, "msg-1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(tombstone.deleted_at, Some(1400));
+        assert!(tombstone.content_encrypted.is_empty());
+        assert_eq!(tombstone.edited_at, Some(1300));
+    }
 }
@@ -1,27 +1,57 @@
+pub mod activity_repo;
 pub mod boards_repo;
 pub mod bootstrap_repo;
 pub mod comments_repo;
+pub mod connection_failures_repo;
+pub mod contact_groups_repo;
 pub mod contacts_repo;
+pub mod feature_flags_repo;
+pub mod fetch_intents_repo;
 pub mod identity_repo;
 pub mod likes_repo;
 pub mod messages_repo;
+pub mod peer_data_repo;
+pub mod pending_outbound_repo;
 pub mod permissions_repo;
+pub mod post_deliveries_repo;
+pub mod post_views_repo;
 pub mod posts_repo;
+pub mod ratchet_repo;
+pub mod settings_repo;
 
-pub use boards_repo::{Board, BoardPost, BoardsRepository, RelayCommunity, UpsertBoardPostParams};
+pub use activity_repo::{ActivityItem, ActivityKind, ActivityRepository};
+pub use boards_repo::{
+    Board, BoardPost, BoardSubscription, BoardsRepository, RelayCommunity, UpsertBoardPostParams,
+};
 pub use bootstrap_repo::{AddBootstrapNodeInput, BootstrapNodeConfig, BootstrapNodesRepo};
 pub use comments_repo::{CommentCount, CommentData, CommentsRepository, PostComment};
-pub use contacts_repo::{Contact, ContactData, ContactsRepository};
+pub use connection_failures_repo::{
+    ConnectionFailure, ConnectionFailureData, ConnectionFailuresRepository,
+};
+pub use contact_groups_repo::{ContactGroup, ContactGroupsRepository};
+pub use contacts_repo::{
+    Contact, ContactActivity, ContactData, ContactNameChange, ContactsRepository,
+    VERIFIED_TRUST_LEVEL,
+};
+pub use feature_flags_repo::FeatureFlagsRepository;
+pub use fetch_intents_repo::FetchIntentsRepository;
 pub use identity_repo::IdentityRepository;
 pub use likes_repo::{LikeData, LikeSummary, LikesRepository, PostLike};
 pub use messages_repo::{
-    Conversation, Message, MessageData, MessageStatus, MessagesRepository, RecordMessageEventParams,
+    Conversation, ConversationRetention, HeldMessageChange, Message, MessageCursor, MessageData,
+    MessageMedia, MessageStatus, MessagesRepository, RecordMessageEventParams,
 };
+pub use peer_data_repo::{ForgottenPeer, PeerDataRepository};
+pub use pending_outbound_repo::{PendingOutbound, PendingOutboundKind, PendingOutboundRepository};
 pub use permissions_repo::{
     Capability, GrantData, Permission, PermissionEvent, PermissionsRepository,
     RecordPermissionEventParams,
 };
+pub use post_deliveries_repo::{DeliveryStatus, PostDeliveriesRepository, PostDelivery};
+pub use post_views_repo::{PostViewSummary, PostViewer, PostViewsRepository};
 pub use posts_repo::{
-    Post, PostData, PostMedia, PostMediaData, PostVisibility, PostsRepository,
+    Post, PostData, PostEvent, PostMedia, PostMediaData, PostVisibility, PostsRepository,
     RecordPostEventParams, VisibilityCounts,
 };
+pub use ratchet_repo::RatchetRepository;
+pub use settings_repo::SettingsRepository;
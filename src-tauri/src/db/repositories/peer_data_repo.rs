@@ -0,0 +1,301 @@
+//! Peer data repository for forgetting everything stored about a peer
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+
+/// Deletes keyed by the peer's conversation with us (`?2`) or the peer (`?1`).
+/// Every statement is bound to both, whichever it uses.
+const CONVERSATION_DELETES: &[&str] = &[
+    // Voice note rows first, while their messages can still be found
+    "DELETE FROM message_media WHERE message_id IN (
+        SELECT message_id FROM messages
+        WHERE conversation_id = ?2 OR sender_peer_id = ?1 OR recipient_peer_id = ?1
+     )",
+    "DELETE FROM message_events
+     WHERE conversation_id = ?2 OR sender_peer_id = ?1 OR recipient_peer_id = ?1
+        OR author_peer_id = ?1 OR ack_sender_peer_id = ?1",
+    "DELETE FROM messages
+     WHERE conversation_id = ?2 OR sender_peer_id = ?1 OR recipient_peer_id = ?1",
+    "DELETE FROM conversation_ratchets WHERE conversation_id = ?2 OR peer_id = ?1",
+    "DELETE FROM received_nonces WHERE conversation_id = ?2 OR sender_peer_id = ?1",
+    "DELETE FROM received_message_clocks WHERE conversation_id = ?2 OR sender_peer_id = ?1",
+    "DELETE FROM conversation_counters WHERE conversation_id = ?2",
+    "DELETE FROM conversation_retention WHERE conversation_id = ?2",
+    "DELETE FROM conversation_pins WHERE conversation_id = ?2",
+];
+
+/// Deletes keyed by the peer (`?1`) alone
+const PEER_DELETES: &[&str] = &[
+    // Their posts; media, likes, comments and views on them cascade
+    "DELETE FROM post_events WHERE author_peer_id = ?1",
+    "DELETE FROM posts WHERE author_peer_id = ?1",
+    // What they did to other posts, including ours
+    "DELETE FROM post_likes WHERE liker_peer_id = ?1",
+    "DELETE FROM post_comments WHERE author_peer_id = ?1",
+    "DELETE FROM post_views WHERE viewer_peer_id = ?1",
+    "DELETE FROM post_deliveries WHERE recipient_peer_id = ?1",
+    "DELETE FROM board_posts WHERE author_peer_id = ?1",
+    "DELETE FROM activity_feed WHERE actor_peer_id = ?1",
+    // Permissions in both directions
+    "DELETE FROM permission_events
+     WHERE author_peer_id = ?1 OR issuer_peer_id = ?1 OR subject_peer_id = ?1",
+    "DELETE FROM permissions_current WHERE issuer_peer_id = ?1 OR subject_peer_id = ?1",
+    // Sync bookkeeping
+    "DELETE FROM sync_cursors WHERE source_peer_id = ?1 OR author_peer_id = ?1",
+    "DELETE FROM post_fetch_intents WHERE source_peer_id = ?1",
+    "DELETE FROM sync_state WHERE peer_id = ?1",
+    "DELETE FROM sync_queue WHERE target_peer_id = ?1",
+    "DELETE FROM pending_outbound WHERE peer_id = ?1",
+    "DELETE FROM lamport_clocks WHERE author_peer_id = ?1",
+    // Failed connections, including incoming ones only the address names them in
+    "DELETE FROM connection_failures
+     WHERE peer_id = ?1 OR instr(address, '/p2p/' || ?1) > 0",
+    // The contact itself
+    "DELETE FROM call_history WHERE peer_id = ?1",
+    "DELETE FROM contact_group_members WHERE peer_id = ?1",
+    "DELETE FROM contact_name_history WHERE peer_id = ?1",
+    "DELETE FROM peer_keys WHERE peer_id = ?1",
+    "DELETE FROM contacts WHERE peer_id = ?1",
+];
+
+/// What forgetting a peer removed
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ForgottenPeer {
+    /// Rows deleted across all tables, not counting cascades
+    pub rows_deleted: usize,
+    /// Media used by the peer's posts and voice notes. The rows are gone;
+    /// the files should be deleted unless something else still uses them.
+    pub media_hashes: Vec<String>,
+}
+
+pub struct PeerDataRepository;
+
+impl PeerDataRepository {
+    /// Delete everything stored about a peer in one transaction: the contact,
+    /// our conversation, their posts and the likes, comments and views they
+    /// left, permissions either way, and sync state.
+    ///
+    /// `conversation_id` is the id of our conversation with the peer.
+    /// Community relays and boards are kept; only posts the peer wrote on
+    /// them are removed.
+    pub fn forget_peer(
+        db: &Database,
+        peer_id: &str,
+        conversation_id: &str,
+    ) -> SqliteResult<ForgottenPeer> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+
+            let media_hashes = {
+                let mut stmt = tx.prepare(
+                    "SELECT media_hash FROM post_media
+                     WHERE post_id IN (SELECT post_id FROM posts WHERE author_peer_id = ?1)
+                     UNION
+                     SELECT media_hash FROM message_media
+                     WHERE message_id IN (
+                        SELECT message_id FROM messages
+                        WHERE conversation_id = ?2 OR sender_peer_id = ?1
+                           OR recipient_peer_id = ?1
+                     )",
+                )?;
+                let rows = stmt.query_map(params![peer_id, conversation_id], |row| row.get(0))?;
+                rows.collect::<SqliteResult<Vec<String>>>()?
+            };
+
+            let mut rows_deleted = 0;
+            for sql in CONVERSATION_DELETES {
+                rows_deleted += tx.execute(sql, params![peer_id, conversation_id])?;
+            }
+            for sql in PEER_DELETES {
+                rows_deleted += tx.execute(sql, [peer_id])?;
+            }
+
+            tx.commit()?;
+            Ok(ForgottenPeer {
+                rows_deleted,
+                media_hashes,
+            })
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const PEER: &str = "12D3KooWForget";
+    const CONVERSATION: &str = "conv-forget";
+    const OTHER: &str = "12D3KooWKeep";
+
+    /// Store a bit of everything about `peer`, in `conversation`
+    fn seed_peer(db: &Database, peer: &str, conversation: &str) {
+        let sql = "
+            INSERT INTO contacts (peer_id, public_key, x25519_public, display_name, added_at, updated_at)
+                VALUES (?1, X'00', X'00', 'Someone', 0, 0);
+            INSERT INTO contact_name_history (peer_id, display_name, observed_at)
+                VALUES (?1, 'Someone', 0);
+            INSERT INTO peer_keys (peer_id, ed25519_public, x25519_public, first_seen_at, last_seen_at)
+                VALUES (?1, X'00', X'00', 0, 0);
+            INSERT INTO contact_groups (group_id, name, created_at)
+                VALUES ('group-' || ?1, 'Friends of ' || ?1, 0);
+            INSERT INTO contact_group_members (group_id, peer_id, added_at)
+                VALUES ('group-' || ?1, ?1, 0);
+            INSERT INTO messages (message_id, conversation_id, sender_peer_id, recipient_peer_id,
+                    content_encrypted, content_type, lamport_clock, sent_at)
+                VALUES ('msg-' || ?1, ?2, ?1, 'me', X'00', 'voice', 1, 0);
+            INSERT INTO message_media (message_id, media_hash, mime_type, size_bytes, duration_ms)
+                VALUES ('msg-' || ?1, 'voice-' || ?1, 'audio/ogg', 1, 1);
+            INSERT INTO pending_outbound (message_id, peer_id, payload, next_attempt_at, created_at)
+                VALUES ('msg-to-' || ?1, ?1, X'00', 0, 0);
+            INSERT INTO message_events (event_id, event_type, message_id, conversation_id,
+                    sender_peer_id, recipient_peer_id, lamport_clock, timestamp, signature, received_at)
+                VALUES ('msg-event-' || ?1, 'sent', 'msg-' || ?1, ?2, ?1, 'me', 1, 0, X'00', 0);
+            INSERT INTO conversation_counters (conversation_id) VALUES (?2);
+            INSERT INTO conversation_retention (conversation_id, never_delete, updated_at)
+                VALUES (?2, 1, 0);
+            INSERT INTO conversation_pins (conversation_id, pinned_at) VALUES (?2, 0);
+            INSERT INTO conversation_ratchets (conversation_id, peer_id, state, updated_at)
+                VALUES (?2, ?1, X'00', 0);
+            INSERT INTO received_nonces (conversation_id, sender_peer_id, nonce_counter, received_at)
+                VALUES (?2, ?1, 1, 0);
+            INSERT INTO received_message_clocks (conversation_id, sender_peer_id, lamport_clock,
+                    message_id, received_at)
+                VALUES (?2, ?1, 1, 'msg-' || ?1, 0);
+            INSERT INTO posts (post_id, author_peer_id, content_type, visibility, lamport_clock,
+                    created_at, updated_at, signature)
+                VALUES ('post-' || ?1, ?1, 'text', 'contacts', 1, 0, 0, X'00');
+            INSERT INTO post_events (event_id, event_type, post_id, author_peer_id, lamport_clock,
+                    timestamp, signature, received_at)
+                VALUES ('post-event-' || ?1, 'created', 'post-' || ?1, ?1, 1, 0, X'00', 0);
+            INSERT INTO post_media (post_id, media_hash, media_type, mime_type, file_name, file_size)
+                VALUES ('post-' || ?1, 'image-' || ?1, 'image', 'image/png', 'a.png', 1);
+            INSERT INTO post_likes (post_id, liker_peer_id, timestamp, signature)
+                VALUES ('post-mine', ?1, 0, X'00');
+            INSERT INTO post_comments (comment_id, post_id, author_peer_id, author_name, content,
+                    created_at)
+                VALUES ('comment-' || ?1, 'post-mine', ?1, 'Someone', 'Hi', 0);
+            INSERT INTO post_views (post_id, viewer_peer_id, recorded_at) VALUES ('post-mine', ?1, 0);
+            INSERT INTO post_deliveries (post_id, recipient_peer_id, attempted_at)
+                VALUES ('post-mine', ?1, 0);
+            INSERT INTO post_fetch_intents (source_peer_id, post_id, created_at)
+                VALUES (?1, 'post-wanted', 0);
+            INSERT INTO activity_feed (kind, actor_peer_id, target_id, created_at)
+                VALUES ('like', ?1, 'post-mine', 0);
+            INSERT INTO permissions_current (grant_id, issuer_peer_id, subject_peer_id, capability,
+                    issued_at, payload_cbor, signature)
+                VALUES ('grant-to-' || ?1, 'me', ?1, 'wall_read', 0, X'00', X'00'),
+                       ('grant-from-' || ?1, ?1, 'me', 'wall_read', 0, X'00', X'00');
+            INSERT INTO permission_events (event_id, event_type, entity_id, author_peer_id,
+                    issuer_peer_id, subject_peer_id, capability, lamport_clock, payload_cbor,
+                    signature, received_at)
+                VALUES ('perm-event-' || ?1, 'grant', 'grant-from-' || ?1, ?1, ?1, 'me',
+                    'wall_read', 1, X'00', X'00', 0);
+            INSERT INTO sync_cursors (source_peer_id, sync_type, author_peer_id,
+                    highest_lamport_clock, last_sync_at)
+                VALUES (?1, 'posts', ?1, 1, 0);
+            INSERT INTO lamport_clocks (author_peer_id, current_value) VALUES (?1, 1);
+            INSERT INTO call_history (call_id, peer_id, direction, status)
+                VALUES ('call-' || ?1, ?1, 'incoming', 'ended');
+            INSERT INTO connection_failures (peer_id, address, category, detail, occurred_at)
+                VALUES (?1, '/ip4/1.2.3.4/tcp/9000', 'timeout', 'timed out', 0),
+                    (NULL, '/ip4/1.2.3.4/tcp/9000/p2p/' || ?1, 'handshake', 'noise', 0);
+            INSERT INTO relay_communities (relay_peer_id, relay_address, joined_at)
+                VALUES ('relay-1', '/ip4/1.2.3.4/tcp/9000', 0)
+                ON CONFLICT DO NOTHING;
+            INSERT INTO board_posts (post_id, board_id, relay_peer_id, author_peer_id,
+                    content_type, lamport_clock, created_at, signature, cached_at)
+                VALUES ('board-post-' || ?1, 'board-1', 'relay-1', ?1, 'text', 1, 0, X'00', 0);
+        ";
+        db.with_connection(|conn| {
+            for statement in sql.split(';').filter(|s| !s.trim().is_empty()) {
+                let mut stmt = conn.prepare(statement)?;
+                match stmt.parameter_count() {
+                    0 => stmt.execute([])?,
+                    1 => stmt.execute([peer])?,
+                    _ => stmt.execute(params![peer, conversation])?,
+                };
+            }
+            Ok(())
+        })
+        .unwrap();
+    }
+
+    /// Tables and columns holding `value`, across the whole schema
+    fn references_to(db: &Database, value: &str) -> Vec<String> {
+        db.with_connection(|conn| {
+            let tables: Vec<String> = conn
+                .prepare(
+                    "SELECT name FROM sqlite_master
+                     WHERE type = 'table' AND name NOT LIKE 'sqlite_%'",
+                )?
+                .query_map([], |row| row.get(0))?
+                .collect::<SqliteResult<_>>()?;
+
+            let mut found = Vec::new();
+            for table in tables {
+                let columns: Vec<String> = conn
+                    .prepare(&format!("SELECT name FROM pragma_table_info('{}')", table))?
+                    .query_map([], |row| row.get(0))?
+                    .collect::<SqliteResult<_>>()?;
+                for column in columns {
+                    let count: i64 = conn.query_row(
+                        &format!(
+                            "SELECT COUNT(*) FROM \"{}\" WHERE \"{}\" = ?",
+                            table, column
+                        ),
+                        [value],
+                        |row| row.get(0),
+                    )?;
+                    if count > 0 {
+                        found.push(format!("{}.{}", table, column));
+                    }
+                }
+            }
+            Ok(found)
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_forget_peer_leaves_nothing_behind() {
+        let db = Database::in_memory().unwrap();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO posts (post_id, author_peer_id, content_type, visibility,
+                    lamport_clock, created_at, updated_at, signature)
+                 VALUES ('post-mine', 'me', 'text', 'public', 1, 0, 0, X'00')",
+                [],
+            )
+        })
+        .unwrap();
+        seed_peer(&db, PEER, CONVERSATION);
+        seed_peer(&db, OTHER, "conv-keep");
+        assert!(!references_to(&db, PEER).is_empty());
+
+        let forgotten = PeerDataRepository::forget_peer(&db, PEER, CONVERSATION).unwrap();
+
+        assert!(forgotten.rows_deleted > 0);
+        let mut media = forgotten.media_hashes.clone();
+        media.sort();
+        assert_eq!(
+            media,
+            vec![format!("image-{}", PEER), format!("voice-{}", PEER)]
+        );
+        assert_eq!(references_to(&db, PEER), Vec::<String>::new());
+        assert_eq!(references_to(&db, CONVERSATION), Vec::<String>::new());
+
+        // Other peers and our own post are untouched
+        assert!(references_to(&db, OTHER).contains(&"contacts.peer_id".to_string()));
+        assert!(references_to(&db, OTHER).contains(&"post_likes.liker_peer_id".to_string()));
+        assert!(references_to(&db, OTHER).contains(&"connection_failures.peer_id".to_string()));
+        assert!(references_to(&db, "conv-keep").contains(&"messages.conversation_id".to_string()));
+        assert!(references_to(&db, "post-mine").contains(&"posts.post_id".to_string()));
+    }
+
+    #[test]
+    fn test_forget_unknown_peer_is_a_no_op() {
+        let db = Database::in_memory().unwrap();
+        let forgotten = PeerDataRepository::forget_peer(&db, PEER, CONVERSATION).unwrap();
+        assert_eq!(forgotten, ForgottenPeer::default());
+    }
+}
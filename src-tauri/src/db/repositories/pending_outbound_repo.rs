@@ -0,0 +1,432 @@
+//! Outbound message queue repository for retrying undelivered messages
+
+use crate::db::Database;
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult, Row};
+
+/// What a queued payload is
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PendingOutboundKind {
+    Message,
+    Edit,
+    Delete,
+    ReadReceipt,
+}
+
+impl PendingOutboundKind {
+    /// Convert to database string
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PendingOutboundKind::Message => "message",
+            PendingOutboundKind::Edit => "edit",
+            PendingOutboundKind::Delete => "delete",
+            PendingOutboundKind::ReadReceipt => "receipt",
+        }
+    }
+
+    /// Parse from database string
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Option<Self> {
+        match s {
+            "message" => Some(PendingOutboundKind::Message),
+            "edit" => Some(PendingOutboundKind::Edit),
+            "delete" => Some(PendingOutboundKind::Delete),
+            "receipt" => Some(PendingOutboundKind::ReadReceipt),
+            _ => None,
+        }
+    }
+}
+
+/// A direct message, or an edit, delete or read receipt, waiting for its
+/// recipient to confirm it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingOutbound {
+    /// Queue key: the message ID for a message, otherwise the kind and the
+    /// ID of the message changed (or a fresh ID for a read receipt)
+    pub message_id: String,
+    pub kind: PendingOutboundKind,
+    /// The message the payload is about, None for a read receipt
+    pub target_message_id: Option<String>,
+    pub peer_id: String,
+    /// The encoded messaging request, resent as is
+    pub payload: Vec<u8>,
+    /// Failed delivery attempts so far
+    pub attempts: u32,
+    pub next_attempt_at: i64,
+    pub last_error: Option<String>,
+    pub created_at: i64,
+}
+
+impl PendingOutbound {
+    fn from_row(row: &Row) -> SqliteResult<Self> {
+        Ok(Self {
+            message_id: row.get(0)?,
+            peer_id: row.get(1)?,
+            payload: row.get(2)?,
+            attempts: row.get(3)?,
+            next_attempt_at: row.get(4)?,
+            last_error: row.get(5)?,
+            created_at: row.get(6)?,
+            kind: PendingOutboundKind::from_str(&row.get::<_, String>(7)?)
+                .unwrap_or(PendingOutboundKind::Message),
+            target_message_id: row.get(8)?,
+        })
+    }
+}
+
+const SELECT_PENDING: &str = "SELECT message_id, peer_id, payload, attempts, next_attempt_at,
+        last_error, created_at, kind, target_message_id
+     FROM pending_outbound";
+
+pub struct PendingOutboundRepository;
+
+impl PendingOutboundRepository {
+    /// Queue a message for delivery, due straight away.
+    ///
+    /// Queueing a message that is already queued keeps its attempts.
+    pub fn enqueue(
+        db: &Database,
+        message_id: &str,
+        peer_id: &str,
+        payload: &[u8],
+        created_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO pending_outbound (
+                    message_id, peer_id, payload, next_attempt_at, created_at,
+                    target_message_id
+                 )
+                 VALUES (?1, ?2, ?3, ?4, ?4, ?1)
+                 ON CONFLICT(message_id) DO NOTHING",
+                params![message_id, peer_id, payload, created_at],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Queue an edit, delete or read receipt under `key`, due straight away.
+    ///
+    /// Queueing under a key that is already queued replaces its payload and
+    /// starts its attempts over, so only the newest edit of a message waits.
+    pub fn enqueue_change(
+        db: &Database,
+        key: &str,
+        kind: PendingOutboundKind,
+        target_message_id: Option<&str>,
+        peer_id: &str,
+        payload: &[u8],
+        created_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO pending_outbound (
+                    message_id, kind, target_message_id, peer_id, payload,
+                    next_attempt_at, created_at
+                 )
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?6)
+                 ON CONFLICT(message_id) DO UPDATE SET
+                    payload = excluded.payload, attempts = 0, last_error = NULL,
+                    next_attempt_at = excluded.next_attempt_at",
+                params![
+                    key,
+                    kind.as_str(),
+                    target_message_id,
+                    peer_id,
+                    payload,
+                    created_at
+                ],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Replace a queued payload, keeping its attempts. Returns false if
+    /// nothing is queued under `key`.
+    pub fn replace_payload(db: &Database, key: &str, payload: &[u8]) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE pending_outbound SET payload = ? WHERE message_id = ?",
+                params![payload, key],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get a queued message
+    pub fn get(db: &Database, message_id: &str) -> SqliteResult<Option<PendingOutbound>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                &format!("{} WHERE message_id = ?", SELECT_PENDING),
+                [message_id],
+                PendingOutbound::from_row,
+            )
+            .optional()
+        })
+    }
+
+    /// Every message queued for a peer, whether due or not, oldest first
+    pub fn get_for_peer(db: &Database, peer_id: &str) -> SqliteResult<Vec<PendingOutbound>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            Self::prune_orphans(&tx)?;
+            let pending = {
+                let mut stmt = tx.prepare(&format!(
+                    "{} WHERE peer_id = ? ORDER BY created_at, message_id",
+                    SELECT_PENDING
+                ))?;
+                let rows = stmt.query_map([peer_id], PendingOutbound::from_row)?;
+                rows.collect::<SqliteResult<Vec<_>>>()?
+            };
+            tx.commit()?;
+            Ok(pending)
+        })
+    }
+
+    /// Messages for any peer whose next attempt is due by `now`, oldest first
+    pub fn get_due(db: &Database, now: i64) -> SqliteResult<Vec<PendingOutbound>> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            Self::prune_orphans(&tx)?;
+            let pending = {
+                let mut stmt = tx.prepare(&format!(
+                    "{} WHERE next_attempt_at <= ? ORDER BY created_at, message_id",
+                    SELECT_PENDING
+                ))?;
+                let rows = stmt.query_map([now], PendingOutbound::from_row)?;
+                rows.collect::<SqliteResult<Vec<_>>>()?
+            };
+            tx.commit()?;
+            Ok(pending)
+        })
+    }
+
+    /// Count one more failed attempt and when to try again
+    pub fn record_failure(
+        db: &Database,
+        message_id: &str,
+        error: &str,
+        next_attempt_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE pending_outbound
+                 SET attempts = attempts + 1, last_error = ?, next_attempt_at = ?
+                 WHERE message_id = ?",
+                params![error, next_attempt_at, message_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Take a payload off the queue, once delivered or given up on
+    pub fn remove(db: &Database, message_id: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "DELETE FROM pending_outbound WHERE message_id = ?",
+                [message_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Count queued messages, for one peer or all of them. Edits, deletes
+    /// and read receipts aren't counted.
+    pub fn count(db: &Database, peer_id: Option<&str>) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM pending_outbound p
+                 JOIN messages m ON m.message_id = p.message_id AND m.deleted_at IS NULL
+                 WHERE p.kind = 'message' AND (?1 IS NULL OR p.peer_id = ?1)",
+                params![peer_id],
+                |row| row.get(0),
+            )
+        })
+    }
+
+    /// Drop queued messages and edits whose message was deleted, or cleared
+    /// with its conversation, before they were delivered. Deletes still go
+    /// out, and read receipts aren't about one message.
+    fn prune_orphans(conn: &Connection) -> SqliteResult<usize> {
+        conn.execute(
+            "DELETE FROM pending_outbound
+             WHERE kind IN ('message', 'edit') AND NOT EXISTS (
+                SELECT 1 FROM messages m
+                WHERE m.message_id = pending_outbound.target_message_id
+                  AND m.deleted_at IS NULL
+             )",
+            [],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::repositories::{MessageData, MessageStatus, MessagesRepository};
+
+    fn insert_message(db: &Database, message_id: &str, recipient: &str) {
+        MessagesRepository::insert_message(
+            db,
+            &MessageData {
+                message_id: message_id.to_string(),
+                conversation_id: format!("conv-{}", recipient),
+                sender_peer_id: "peer-self".to_string(),
+                recipient_peer_id: recipient.to_string(),
+                content_encrypted: vec![1],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: 1000,
+                received_at: None,
+                status: MessageStatus::Pending,
+            },
+        )
+        .unwrap();
+    }
+
+    fn queue(db: &Database, message_id: &str, peer_id: &str, created_at: i64) {
+        insert_message(db, message_id, peer_id);
+        PendingOutboundRepository::enqueue(db, message_id, peer_id, b"payload", created_at)
+            .unwrap();
+    }
+
+    fn message_ids(pending: &[PendingOutbound]) -> Vec<&str> {
+        pending.iter().map(|p| p.message_id.as_str()).collect()
+    }
+
+    #[test]
+    fn test_queue_until_removed() {
+        let db = Database::in_memory().unwrap();
+        queue(&db, "msg1", "peer-a", 1000);
+        queue(&db, "msg2", "peer-a", 1001);
+        queue(&db, "msg3", "peer-b", 1000);
+
+        assert_eq!(PendingOutboundRepository::count(&db, None).unwrap(), 3);
+        assert_eq!(
+            PendingOutboundRepository::count(&db, Some("peer-a")).unwrap(),
+            2
+        );
+
+        let pending = PendingOutboundRepository::get_for_peer(&db, "peer-a").unwrap();
+        assert_eq!(message_ids(&pending), vec!["msg1", "msg2"]);
+        assert_eq!(pending[0].payload, b"payload".to_vec());
+        assert_eq!(pending[0].attempts, 0);
+
+        assert!(PendingOutboundRepository::remove(&db, "msg1").unwrap());
+        assert!(!PendingOutboundRepository::remove(&db, "msg1").unwrap());
+        assert_eq!(
+            PendingOutboundRepository::count(&db, Some("peer-a")).unwrap(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_failures_push_back_next_attempt() {
+        let db = Database::in_memory().unwrap();
+        queue(&db, "msg1", "peer-a", 1000);
+        queue(&db, "msg2", "peer-b", 1000);
+
+        assert!(PendingOutboundRepository::record_failure(&db, "msg1", "timeout", 1060).unwrap());
+        let pending = PendingOutboundRepository::get(&db, "msg1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending.attempts, 1);
+        assert_eq!(pending.last_error.as_deref(), Some("timeout"));
+
+        assert_eq!(
+            message_ids(&PendingOutboundRepository::get_due(&db, 1030).unwrap()),
+            vec!["msg2"]
+        );
+        assert_eq!(
+            message_ids(&PendingOutboundRepository::get_due(&db, 1060).unwrap()),
+            vec!["msg1", "msg2"]
+        );
+
+        // Queueing again doesn't reset the attempts
+        PendingOutboundRepository::enqueue(&db, "msg1", "peer-a", b"payload", 2000).unwrap();
+        let pending = PendingOutboundRepository::get(&db, "msg1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(pending.attempts, 1);
+    }
+
+    #[test]
+    fn test_deleted_messages_leave_the_queue() {
+        let db = Database::in_memory().unwrap();
+        queue(&db, "msg1", "peer-a", 1000);
+        queue(&db, "msg2", "peer-a", 1001);
+        queue(&db, "msg3", "peer-b", 1000);
+
+        MessagesRepository::soft_delete_message(&db, "msg1", 1500).unwrap();
+        MessagesRepository::clear_conversation_messages(&db, "conv-peer-b").unwrap();
+        assert_eq!(PendingOutboundRepository::count(&db, None).unwrap(), 1);
+
+        assert_eq!(
+            message_ids(&PendingOutboundRepository::get_due(&db, 2000).unwrap()),
+            vec!["msg2"]
+        );
+        assert!(PendingOutboundRepository::get(&db, "msg1")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_changes_queue_beside_messages() {
+        let db = Database::in_memory().unwrap();
+        queue(&db, "msg1", "peer-a", 1000);
+        insert_message(&db, "msg2", "peer-a");
+        for (key, kind, target, payload) in [
+            (
+                "edit:msg1",
+                PendingOutboundKind::Edit,
+                Some("msg1"),
+                b"edit 1".as_slice(),
+            ),
+            (
+                "edit:msg1",
+                PendingOutboundKind::Edit,
+                Some("msg1"),
+                b"edit 2".as_slice(),
+            ),
+            (
+                "delete:msg2",
+                PendingOutboundKind::Delete,
+                Some("msg2"),
+                b"delete".as_slice(),
+            ),
+            (
+                "receipt:r1",
+                PendingOutboundKind::ReadReceipt,
+                None,
+                b"receipt".as_slice(),
+            ),
+        ] {
+            PendingOutboundRepository::enqueue_change(
+                &db, key, kind, target, "peer-a", payload, 1001,
+            )
+            .unwrap();
+        }
+        MessagesRepository::soft_delete_message(&db, "msg2", 1500).unwrap();
+
+        // Only messages count as pending, and the newest edit replaced the older
+        assert_eq!(PendingOutboundRepository::count(&db, None).unwrap(), 1);
+        let pending = PendingOutboundRepository::get_for_peer(&db, "peer-a").unwrap();
+        assert_eq!(
+            message_ids(&pending),
+            vec!["msg1", "delete:msg2", "edit:msg1", "receipt:r1"]
+        );
+        assert_eq!(pending[2].kind, PendingOutboundKind::Edit);
+        assert_eq!(pending[2].payload, b"edit 2".to_vec());
+        assert_eq!(pending[0].target_message_id.as_deref(), Some("msg1"));
+
+        // Deleting msg1 drops it and its edit, not the other changes
+        MessagesRepository::soft_delete_message(&db, "msg1", 1600).unwrap();
+        assert_eq!(
+            message_ids(&PendingOutboundRepository::get_due(&db, 2000).unwrap()),
+            vec!["delete:msg2", "receipt:r1"]
+        );
+    }
+}
@@ -10,16 +10,41 @@ pub enum Capability {
     Chat,
     /// Can view wall posts
     WallRead,
-    /// Can initiate voice calls
-    Call,
+    /// Can initiate voice calls. Stored as "call", its name before the
+    /// capability model was extended, so existing grants stay valid.
+    CallInitiate,
+    /// Can post to boards we host
+    BoardPost,
+    /// Can fetch files and media from us
+    FileTransfer,
+    /// Can fetch our contact list for contact suggestions
+    ShareContacts,
 }
 
 impl Capability {
+    /// Every capability, in the order they are shown and granted
+    pub const ALL: [Capability; 6] = [
+        Capability::Chat,
+        Capability::WallRead,
+        Capability::CallInitiate,
+        Capability::BoardPost,
+        Capability::FileTransfer,
+        Capability::ShareContacts,
+    ];
+
+    /// Capabilities every contact used to have implicitly, before each
+    /// protocol handler checked for a grant. New contacts are granted these
+    /// unless the user has set a different default grant policy.
+    pub const CONTACT_DEFAULTS: [Capability; 2] = [Capability::Chat, Capability::FileTransfer];
+
     pub fn as_str(&self) -> &'static str {
         match self {
             Capability::Chat => "chat",
             Capability::WallRead => "wall_read",
-            Capability::Call => "call",
+            Capability::CallInitiate => "call",
+            Capability::BoardPost => "board_post",
+            Capability::FileTransfer => "file_transfer",
+            Capability::ShareContacts => "share_contacts",
         }
     }
 
@@ -28,7 +53,10 @@ impl Capability {
         match s {
             "chat" => Some(Capability::Chat),
             "wall_read" => Some(Capability::WallRead),
-            "call" => Some(Capability::Call),
+            "call" => Some(Capability::CallInitiate),
+            "board_post" => Some(Capability::BoardPost),
+            "file_transfer" => Some(Capability::FileTransfer),
+            "share_contacts" => Some(Capability::ShareContacts),
             _ => None,
         }
     }
@@ -164,6 +192,49 @@ impl PermissionsRepository {
         })
     }
 
+    /// Grant and revoke events between two peers, issued by either one, in
+    /// the order they were recorded
+    pub fn get_grant_events_between(
+        db: &Database,
+        peer_a: &str,
+        peer_b: &str,
+    ) -> SqliteResult<Vec<PermissionEvent>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, event_id, event_type, entity_id, author_peer_id, issuer_peer_id,
+                        subject_peer_id, capability, scope_json, lamport_clock, issued_at,
+                        expires_at, payload_cbor, signature, received_at
+                 FROM permission_events
+                 WHERE event_type IN ('grant', 'revoke')
+                   AND ((issuer_peer_id = ?1 AND subject_peer_id = ?2)
+                     OR (issuer_peer_id = ?2 AND subject_peer_id = ?1))
+                 ORDER BY id",
+            )?;
+
+            let events = stmt.query_map(params![peer_a, peer_b], |row| {
+                Ok(PermissionEvent {
+                    id: row.get(0)?,
+                    event_id: row.get(1)?,
+                    event_type: row.get(2)?,
+                    entity_id: row.get(3)?,
+                    author_peer_id: row.get(4)?,
+                    issuer_peer_id: row.get(5)?,
+                    subject_peer_id: row.get(6)?,
+                    capability: row.get(7)?,
+                    scope_json: row.get(8)?,
+                    lamport_clock: row.get(9)?,
+                    issued_at: row.get(10)?,
+                    expires_at: row.get(11)?,
+                    payload_cbor: row.get(12)?,
+                    signature: row.get(13)?,
+                    received_at: row.get(14)?,
+                })
+            })?;
+
+            events.collect()
+        })
+    }
+
     // ============================================================
     // Materialized Permission State
     // ============================================================
@@ -382,6 +453,74 @@ impl PermissionsRepository {
         })
     }
 
+    /// Valid grants `issuer_peer_id` signed for `subject_peer_id` that the
+    /// subject hasn't acknowledged yet. Local-only grants carry no signature
+    /// and are never sent, so they are left out.
+    pub fn get_unacknowledged_grants(
+        db: &Database,
+        issuer_peer_id: &str,
+        subject_peer_id: &str,
+    ) -> SqliteResult<Vec<Permission>> {
+        db.with_connection(|conn| {
+            let now = chrono::Utc::now().timestamp();
+            let mut stmt = conn.prepare(
+                "SELECT id, grant_id, issuer_peer_id, subject_peer_id, capability,
+                        issued_at, expires_at, revoked_at, payload_cbor, signature
+                 FROM permissions_current
+                 WHERE issuer_peer_id = ?
+                   AND subject_peer_id = ?
+                   AND acknowledged_at IS NULL
+                   AND length(signature) > 0
+                   AND revoked_at IS NULL
+                   AND (expires_at IS NULL OR expires_at > ?)
+                 ORDER BY issued_at",
+            )?;
+
+            let perms = stmt.query_map(params![issuer_peer_id, subject_peer_id, now], |row| {
+                Ok(Permission {
+                    id: row.get(0)?,
+                    grant_id: row.get(1)?,
+                    issuer_peer_id: row.get(2)?,
+                    subject_peer_id: row.get(3)?,
+                    capability: row.get(4)?,
+                    issued_at: row.get(5)?,
+                    expires_at: row.get(6)?,
+                    revoked_at: row.get(7)?,
+                    payload_cbor: row.get(8)?,
+                    signature: row.get(9)?,
+                })
+            })?;
+
+            perms.collect()
+        })
+    }
+
+    /// Record that the subject acknowledged the given grants. Grant IDs that
+    /// weren't issued by `issuer_peer_id` to `subject_peer_id` are ignored.
+    /// Returns how many grants were newly acknowledged.
+    pub fn mark_grants_acknowledged(
+        db: &Database,
+        issuer_peer_id: &str,
+        subject_peer_id: &str,
+        grant_ids: &[String],
+        acknowledged_at: i64,
+    ) -> SqliteResult<usize> {
+        db.with_connection(|conn| {
+            let mut updated = 0;
+            for grant_id in grant_ids {
+                updated += conn.execute(
+                    "UPDATE permissions_current SET acknowledged_at = ?
+                     WHERE grant_id = ?
+                       AND issuer_peer_id = ?
+                       AND subject_peer_id = ?
+                       AND acknowledged_at IS NULL",
+                    params![acknowledged_at, grant_id, issuer_peer_id, subject_peer_id],
+                )?;
+            }
+            Ok(updated)
+        })
+    }
+
     /// Get all peers who have granted us a capability
     pub fn get_peers_who_granted_capability(
         db: &Database,
@@ -415,6 +554,15 @@ mod tests {
         assert_eq!(Capability::from_str("invalid"), None);
     }
 
+    #[test]
+    fn test_capability_round_trips() {
+        for capability in Capability::ALL {
+            assert_eq!(Capability::from_str(capability.as_str()), Some(capability));
+        }
+        // Grants stored before the rename still resolve
+        assert_eq!(Capability::from_str("call"), Some(Capability::CallInitiate));
+    }
+
     #[test]
     fn test_grant_and_check_capability() {
         let db = Database::in_memory().unwrap();
@@ -550,4 +698,45 @@ mod tests {
             PermissionsRepository::get_permissions_by_issuer(&db, "12D3KooWIssuer").unwrap();
         assert_eq!(perms.len(), 3);
     }
+
+    #[test]
+    fn test_grant_events_between_cover_both_directions() {
+        let db = Database::in_memory().unwrap();
+
+        let events = [
+            ("e1", "grant", "12D3KooWAlice", "12D3KooWBob"),
+            ("e2", "request", "12D3KooWBob", "12D3KooWAlice"),
+            ("e3", "grant", "12D3KooWBob", "12D3KooWAlice"),
+            ("e4", "grant", "12D3KooWAlice", "12D3KooWCarol"),
+            ("e5", "revoke", "12D3KooWAlice", "12D3KooWBob"),
+        ];
+        for (i, (event_id, event_type, issuer, subject)) in events.into_iter().enumerate() {
+            PermissionsRepository::record_event(
+                &db,
+                &RecordPermissionEventParams {
+                    event_id,
+                    event_type,
+                    entity_id: "grant-1",
+                    author_peer_id: issuer,
+                    issuer_peer_id: Some(issuer),
+                    subject_peer_id: subject,
+                    capability: "wall_read",
+                    scope_json: None,
+                    lamport_clock: i as i64,
+                    issued_at: None,
+                    expires_at: None,
+                    payload_cbor: &[1, 2, 3],
+                    signature: &[4, 5, 6],
+                },
+            )
+            .unwrap();
+        }
+
+        // Requests and grants to other peers are left out
+        let history =
+            PermissionsRepository::get_grant_events_between(&db, "12D3KooWBob", "12D3KooWAlice")
+                .unwrap();
+        let ids: Vec<&str> = history.iter().map(|e| e.event_id.as_str()).collect();
+        assert_eq!(ids, ["e1", "e3", "e5"]);
+    }
 }
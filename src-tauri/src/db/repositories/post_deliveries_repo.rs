@@ -0,0 +1,194 @@
+//! Post deliveries repository for tracking pushes of new posts to contacts
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+
+/// Whether a pushed post reached a recipient
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    Pending,
+    Delivered,
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(s: &str) -> Self {
+        match s {
+            "delivered" => DeliveryStatus::Delivered,
+            "failed" => DeliveryStatus::Failed,
+            _ => DeliveryStatus::Pending,
+        }
+    }
+}
+
+/// Push delivery state of a post for one contact
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostDelivery {
+    pub recipient_peer_id: String,
+    pub display_name: Option<String>,
+    pub status: DeliveryStatus,
+    pub attempted_at: i64,
+    pub delivered_at: Option<i64>,
+    pub error: Option<String>,
+}
+
+pub struct PostDeliveriesRepository;
+
+impl PostDeliveriesRepository {
+    /// Record a push to several recipients as pending, all or nothing.
+    ///
+    /// Recipients that already have a row (e.g. from an earlier push of the
+    /// same post) are reset to pending.
+    pub fn record_pending(
+        db: &Database,
+        post_id: &str,
+        recipient_peer_ids: &[String],
+        attempted_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            {
+                let mut stmt = tx.prepare(
+                    "INSERT INTO post_deliveries (post_id, recipient_peer_id, status, attempted_at)
+                     VALUES (?, ?, 'pending', ?)
+                     ON CONFLICT(post_id, recipient_peer_id) DO UPDATE SET
+                        status = 'pending', attempted_at = excluded.attempted_at,
+                        delivered_at = NULL, error = NULL",
+                )?;
+                for recipient in recipient_peer_ids {
+                    stmt.execute(params![post_id, recipient, attempted_at])?;
+                }
+            }
+            tx.commit()
+        })
+    }
+
+    /// Mark a pending push as delivered
+    pub fn mark_delivered(
+        db: &Database,
+        post_id: &str,
+        recipient_peer_id: &str,
+        delivered_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE post_deliveries SET status = 'delivered', delivered_at = ?, error = NULL
+                 WHERE post_id = ? AND recipient_peer_id = ? AND status = 'pending'",
+                params![delivered_at, post_id, recipient_peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Mark a pending push as failed
+    pub fn mark_failed(
+        db: &Database,
+        post_id: &str,
+        recipient_peer_id: &str,
+        error: &str,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE post_deliveries SET status = 'failed', error = ?
+                 WHERE post_id = ? AND recipient_peer_id = ? AND status = 'pending'",
+                params![error, post_id, recipient_peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get the delivery state of a post for every recipient it was pushed to
+    pub fn get_for_post(db: &Database, post_id: &str) -> SqliteResult<Vec<PostDelivery>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT d.recipient_peer_id, c.display_name, d.status, d.attempted_at,
+                        d.delivered_at, d.error
+                 FROM post_deliveries d
+                 LEFT JOIN contacts c ON c.peer_id = d.recipient_peer_id
+                 WHERE d.post_id = ?
+                 ORDER BY d.attempted_at, d.recipient_peer_id",
+            )?;
+            let deliveries = stmt
+                .query_map([post_id], |row| {
+                    let status: String = row.get(2)?;
+                    Ok(PostDelivery {
+                        recipient_peer_id: row.get(0)?,
+                        display_name: row.get(1)?,
+                        status: DeliveryStatus::parse(&status),
+                        attempted_at: row.get(3)?,
+                        delivered_at: row.get(4)?,
+                        error: row.get(5)?,
+                    })
+                })?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            Ok(deliveries)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_post(db: &Database, post_id: &str) {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO posts (post_id, author_peer_id, content_type, visibility, lamport_clock, created_at, updated_at, signature)
+                 VALUES (?, 'author1', 'text', 'contacts', 1, 1000, 1000, X'00')",
+                [post_id],
+            )
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_delivery_lifecycle() {
+        let db = Database::in_memory().unwrap();
+        insert_post(&db, "post1");
+
+        let recipients = vec!["peer-a".to_string(), "peer-b".to_string()];
+        PostDeliveriesRepository::record_pending(&db, "post1", &recipients, 1000).unwrap();
+
+        assert!(PostDeliveriesRepository::mark_delivered(&db, "post1", "peer-a", 1001).unwrap());
+        assert!(
+            PostDeliveriesRepository::mark_failed(&db, "post1", "peer-b", "timed out").unwrap()
+        );
+        // Only pending rows change
+        assert!(!PostDeliveriesRepository::mark_failed(&db, "post1", "peer-a", "late").unwrap());
+
+        let deliveries = PostDeliveriesRepository::get_for_post(&db, "post1").unwrap();
+        assert_eq!(deliveries.len(), 2);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Delivered);
+        assert_eq!(deliveries[0].delivered_at, Some(1001));
+        assert_eq!(deliveries[1].status, DeliveryStatus::Failed);
+        assert_eq!(deliveries[1].error.as_deref(), Some("timed out"));
+    }
+
+    #[test]
+    fn test_repush_resets_to_pending() {
+        let db = Database::in_memory().unwrap();
+        insert_post(&db, "post1");
+
+        let recipients = vec!["peer-a".to_string()];
+        PostDeliveriesRepository::record_pending(&db, "post1", &recipients, 1000).unwrap();
+        PostDeliveriesRepository::mark_failed(&db, "post1", "peer-a", "offline").unwrap();
+        PostDeliveriesRepository::record_pending(&db, "post1", &recipients, 2000).unwrap();
+
+        let deliveries = PostDeliveriesRepository::get_for_post(&db, "post1").unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].status, DeliveryStatus::Pending);
+        assert_eq!(deliveries[0].attempted_at, 2000);
+        assert_eq!(deliveries[0].error, None);
+    }
+}
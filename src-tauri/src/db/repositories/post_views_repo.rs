@@ -0,0 +1,134 @@
+//! Post views repository for view reports sent back by followers
+
+use crate::db::Database;
+use rusqlite::{params, Result as SqliteResult};
+use serde::{Deserialize, Serialize};
+
+/// A contact that reported viewing a post
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostViewer {
+    pub peer_id: String,
+    pub display_name: Option<String>,
+    /// Only set when precise view timing was enabled when the report arrived
+    pub viewed_at: Option<i64>,
+}
+
+/// Aggregated views for one of our posts
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostViewSummary {
+    pub post_id: String,
+    pub view_count: i64,
+    pub viewers: Vec<PostViewer>,
+}
+
+pub struct PostViewsRepository;
+
+impl PostViewsRepository {
+    /// Record a view. Returns `false` if this viewer was already counted.
+    pub fn record_view(
+        db: &Database,
+        post_id: &str,
+        viewer_peer_id: &str,
+        viewed_at: Option<i64>,
+        recorded_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "INSERT OR IGNORE INTO post_views (post_id, viewer_peer_id, viewed_at, recorded_at)
+                 VALUES (?, ?, ?, ?)",
+                params![post_id, viewer_peer_id, viewed_at, recorded_at],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get the number of distinct viewers of a post
+    pub fn get_view_count(db: &Database, post_id: &str) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT COUNT(*) FROM post_views WHERE post_id = ?",
+                [post_id],
+                |row| row.get(0),
+            )
+        })
+    }
+
+    /// Get everyone who viewed a post, in the order their reports arrived
+    pub fn get_viewers(db: &Database, post_id: &str) -> SqliteResult<Vec<PostViewer>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT v.viewer_peer_id, c.display_name, v.viewed_at
+                 FROM post_views v
+                 LEFT JOIN contacts c ON c.peer_id = v.viewer_peer_id
+                 WHERE v.post_id = ?
+                 ORDER BY v.recorded_at, v.viewer_peer_id",
+            )?;
+            let viewers = stmt
+                .query_map([post_id], |row| {
+                    Ok(PostViewer {
+                        peer_id: row.get(0)?,
+                        display_name: row.get(1)?,
+                        viewed_at: row.get(2)?,
+                    })
+                })?
+                .collect::<SqliteResult<Vec<_>>>()?;
+            Ok(viewers)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn insert_post(db: &Database, post_id: &str) {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO posts (post_id, author_peer_id, content_type, visibility, lamport_clock, created_at, updated_at, signature)
+                 VALUES (?, 'author1', 'text', 'contacts', 1, 1000, 1000, X'00')",
+                [post_id],
+            )
+        })
+        .unwrap();
+    }
+
+    #[test]
+    fn test_views_are_counted_once_per_viewer() {
+        let db = Database::in_memory().unwrap();
+        insert_post(&db, "post1");
+
+        assert!(PostViewsRepository::record_view(&db, "post1", "viewer-a", None, 1000).unwrap());
+        assert!(
+            PostViewsRepository::record_view(&db, "post1", "viewer-b", Some(1100), 1001).unwrap()
+        );
+        assert!(
+            !PostViewsRepository::record_view(&db, "post1", "viewer-a", Some(1200), 1002).unwrap()
+        );
+
+        assert_eq!(
+            PostViewsRepository::get_view_count(&db, "post1").unwrap(),
+            2
+        );
+        let viewers = PostViewsRepository::get_viewers(&db, "post1").unwrap();
+        assert_eq!(viewers.len(), 2);
+        assert_eq!(viewers[0].peer_id, "viewer-a");
+        assert_eq!(viewers[0].viewed_at, None);
+        assert_eq!(viewers[1].viewed_at, Some(1100));
+    }
+
+    #[test]
+    fn test_deleting_post_drops_views() {
+        let db = Database::in_memory().unwrap();
+        insert_post(&db, "post1");
+        PostViewsRepository::record_view(&db, "post1", "viewer-a", None, 1000).unwrap();
+
+        db.with_connection(|conn| conn.execute("DELETE FROM posts WHERE post_id = 'post1'", []))
+            .unwrap();
+        assert_eq!(
+            PostViewsRepository::get_view_count(&db, "post1").unwrap(),
+            0
+        );
+    }
+}
@@ -1,7 +1,7 @@
 //! Posts repository for storing and retrieving wall/blog posts
 
 use crate::db::Database;
-use rusqlite::{params, Connection, Result as SqliteResult};
+use rusqlite::{params, Connection, OptionalExtension, Result as SqliteResult};
 
 /// Post visibility
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -10,6 +10,8 @@ pub enum PostVisibility {
     Contacts,
     /// Visible to everyone (public)
     Public,
+    /// Visible only to the author; never served to peers or relays
+    Private,
 }
 
 impl PostVisibility {
@@ -17,14 +19,21 @@ impl PostVisibility {
         match self {
             PostVisibility::Contacts => "contacts",
             PostVisibility::Public => "public",
+            PostVisibility::Private => "private",
         }
     }
 
+    /// Whether posts with this visibility may ever leave this device
+    pub fn is_shareable(&self) -> bool {
+        !matches!(self, PostVisibility::Private)
+    }
+
     #[allow(clippy::should_implement_trait)]
     pub fn from_str(s: &str) -> Option<Self> {
         match s {
             "contacts" => Some(PostVisibility::Contacts),
             "public" => Some(PostVisibility::Public),
+            "private" => Some(PostVisibility::Private),
             _ => None,
         }
     }
@@ -51,6 +60,12 @@ pub struct Post {
     pub deleted_at: Option<i64>,
     pub is_local: bool,
     pub signature: Vec<u8>,
+    /// Pinned to the top of the author's wall (at most one per author)
+    pub pinned: bool,
+    /// When a community relay received the post, per its signed timestamp
+    pub relay_observed_at: Option<i64>,
+    /// Signature scheme version `signature` was made with
+    pub sig_version: u32,
 }
 
 /// Data for inserting a new post
@@ -64,6 +79,8 @@ pub struct PostData {
     pub lamport_clock: i64,
     pub created_at: i64,
     pub signature: Vec<u8>,
+    /// Signature scheme version `signature` was made with
+    pub sig_version: u32,
 }
 
 /// Post media metadata
@@ -97,6 +114,22 @@ pub struct PostMediaData {
     pub sort_order: i32,
 }
 
+/// A recorded post event (created, updated, deleted or received)
+#[derive(Debug, Clone)]
+pub struct PostEvent {
+    pub id: i64,
+    pub event_id: String,
+    pub event_type: String,
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub lamport_clock: i64,
+    pub timestamp: i64,
+    /// The signed payload (canonical CBOR)
+    pub payload_cbor: Option<Vec<u8>>,
+    pub signature: Vec<u8>,
+    pub received_at: i64,
+}
+
 /// Aggregated visibility counts for an author's posts.
 ///
 /// Computed entirely in SQL via `COUNT`/`GROUP BY` -- no post rows are
@@ -109,6 +142,8 @@ pub struct VisibilityCounts {
     pub public_posts: usize,
     /// Number of posts with `contacts` visibility
     pub contacts_only_posts: usize,
+    /// Number of posts with `private` (self-only) visibility
+    pub private_posts: usize,
 }
 
 /// Repository for post operations
@@ -134,8 +169,8 @@ impl PostsRepository {
                 "INSERT INTO posts (
                     post_id, author_peer_id, content_type, content_text,
                     visibility, lamport_clock, created_at, updated_at,
-                    is_local, signature
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    is_local, signature, sig_version
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     post.post_id,
                     post.author_peer_id,
@@ -147,6 +182,7 @@ impl PostsRepository {
                     post.created_at, // updated_at = created_at initially
                     1i32,            // is_local = true for posts we create
                     post.signature,
+                    post.sig_version,
                 ],
             )?;
             Ok(conn.last_insert_rowid())
@@ -160,8 +196,8 @@ impl PostsRepository {
                 "INSERT INTO posts (
                     post_id, author_peer_id, content_type, content_text,
                     visibility, lamport_clock, created_at, updated_at,
-                    is_local, signature
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                    is_local, signature, sig_version
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)",
                 params![
                     post.post_id,
                     post.author_peer_id,
@@ -173,6 +209,7 @@ impl PostsRepository {
                     post.created_at,
                     0i32, // is_local = false for remote posts
                     post.signature,
+                    post.sig_version,
                 ],
             )?;
             Ok(conn.last_insert_rowid())
@@ -188,7 +225,7 @@ impl PostsRepository {
         let mut stmt = conn.prepare(
             "SELECT id, post_id, author_peer_id, content_type, content_text,
                     visibility, lamport_clock, created_at, updated_at,
-                    deleted_at, is_local, signature
+                    deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
              FROM posts WHERE post_id = ?",
         )?;
 
@@ -219,10 +256,15 @@ impl PostsRepository {
             deleted_at: row.get(9)?,
             is_local: row.get::<_, i32>(10)? != 0,
             signature: row.get(11)?,
+            pinned: row.get::<_, i32>(12)? != 0,
+            relay_observed_at: row.get(13)?,
+            sig_version: row.get(14)?,
         })
     }
 
-    /// Get posts by author
+    /// Get posts by author.
+    ///
+    /// The pinned post leads the first page and is left out of later pages.
     pub fn get_by_author(
         db: &Database,
         author_peer_id: &str,
@@ -236,9 +278,10 @@ impl PostsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE author_peer_id = ? AND deleted_at IS NULL AND created_at < ?
+                       AND pinned = 0
                      ORDER BY created_at DESC
                      LIMIT ?",
                 )?;
@@ -250,10 +293,10 @@ impl PostsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE author_peer_id = ? AND deleted_at IS NULL
-                     ORDER BY created_at DESC
+                     ORDER BY pinned DESC, created_at DESC
                      LIMIT ?",
                 )?;
                 let mut rows = stmt.query(params![author_peer_id, limit])?;
@@ -269,6 +312,8 @@ impl PostsRepository {
     /// Get posts by author with lamport_clock greater than the given cursor value.
     /// Results are ordered by lamport_clock ascending so the caller receives posts
     /// in causal order, which is the expected ordering for sync cursor advancement.
+    ///
+    /// Private posts are excluded: this query backs what we offer to peers.
     pub fn get_by_author_after_cursor(
         db: &Database,
         author_peer_id: &str,
@@ -280,9 +325,10 @@ impl PostsRepository {
             let mut stmt = conn.prepare(
                 "SELECT id, post_id, author_peer_id, content_type, content_text,
                         visibility, lamport_clock, created_at, updated_at,
-                        deleted_at, is_local, signature
+                        deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                  FROM posts
                  WHERE author_peer_id = ? AND deleted_at IS NULL AND lamport_clock > ?
+                   AND visibility != 'private'
                  ORDER BY lamport_clock ASC
                  LIMIT ?",
             )?;
@@ -294,7 +340,7 @@ impl PostsRepository {
         })
     }
 
-    /// Get local posts (for own wall)
+    /// Get local posts (for own wall), pinned post first like [`Self::get_by_author`]
     pub fn get_local_posts(
         db: &Database,
         limit: i64,
@@ -307,9 +353,10 @@ impl PostsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE is_local = 1 AND deleted_at IS NULL AND created_at < ?
+                       AND pinned = 0
                      ORDER BY created_at DESC
                      LIMIT ?",
                 )?;
@@ -321,10 +368,10 @@ impl PostsRepository {
                 let mut stmt = conn.prepare(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE is_local = 1 AND deleted_at IS NULL
-                     ORDER BY created_at DESC
+                     ORDER BY pinned DESC, created_at DESC
                      LIMIT ?",
                 )?;
                 let mut rows = stmt.query(params![limit])?;
@@ -355,6 +402,51 @@ impl PostsRepository {
         })
     }
 
+    /// Replace a remote post with a newer signed version from the same author.
+    ///
+    /// Every signed field is overwritten together so the stored row always
+    /// matches its signature. Local posts are never touched.
+    pub fn replace_remote_version(db: &Database, post: &PostData) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE posts SET content_type = ?, content_text = ?, visibility = ?,
+                        lamport_clock = ?, updated_at = ?, signature = ?, sig_version = ?
+                 WHERE post_id = ? AND author_peer_id = ? AND is_local = 0",
+                params![
+                    post.content_type,
+                    post.content_text,
+                    post.visibility.as_str(),
+                    post.lamport_clock,
+                    post.created_at,
+                    post.signature,
+                    post.sig_version,
+                    post.post_id,
+                    post.author_peer_id,
+                ],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Record when a relay received a post, keeping the earliest verified time.
+    /// Returns `false` if the post is unknown or already has an earlier one.
+    pub fn set_relay_observed_at(
+        db: &Database,
+        post_id: &str,
+        author_peer_id: &str,
+        relay_observed_at: i64,
+    ) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE posts SET relay_observed_at = ?1
+                 WHERE post_id = ?2 AND author_peer_id = ?3
+                   AND (relay_observed_at IS NULL OR relay_observed_at > ?1)",
+                params![relay_observed_at, post_id, author_peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
     /// Soft delete a post
     pub fn delete_post(db: &Database, post_id: &str, deleted_at: i64) -> SqliteResult<bool> {
         db.with_connection(|conn| {
@@ -367,6 +459,50 @@ impl PostsRepository {
         })
     }
 
+    /// Pin a post to the top of its author's wall, unpinning any previous one.
+    ///
+    /// Returns `false` if the post does not exist, is deleted, or is not by `author_peer_id`.
+    pub fn pin_post(db: &Database, author_peer_id: &str, post_id: &str) -> SqliteResult<bool> {
+        db.with_connection_mut(|conn| {
+            let tx = conn.transaction()?;
+            tx.execute(
+                "UPDATE posts SET pinned = 0 WHERE author_peer_id = ? AND pinned = 1",
+                [author_peer_id],
+            )?;
+            let rows = tx.execute(
+                "UPDATE posts SET pinned = 1
+                 WHERE post_id = ? AND author_peer_id = ? AND deleted_at IS NULL",
+                params![post_id, author_peer_id],
+            )?;
+            tx.commit()?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Unpin a post. Returns `false` if it was not pinned.
+    pub fn unpin_post(db: &Database, author_peer_id: &str, post_id: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute(
+                "UPDATE posts SET pinned = 0
+                 WHERE post_id = ? AND author_peer_id = ? AND pinned = 1",
+                params![post_id, author_peer_id],
+            )?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Lamport clock of the author's most recent pin or unpin event, if any
+    pub fn get_latest_pin_clock(db: &Database, author_peer_id: &str) -> SqliteResult<Option<i64>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT MAX(lamport_clock) FROM post_events
+                 WHERE author_peer_id = ? AND event_type IN ('pinned', 'unpinned')",
+                [author_peer_id],
+                |row| row.get(0),
+            )
+        })
+    }
+
     /// Get feed posts from multiple authors, sorted by created_at DESC.
     ///
     /// This is more efficient than querying per-author and merging,
@@ -401,7 +537,7 @@ impl PostsRepository {
                 let sql = format!(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE author_peer_id IN ({}) AND deleted_at IS NULL AND created_at < ?
                      ORDER BY created_at DESC
@@ -429,7 +565,7 @@ impl PostsRepository {
                 let sql = format!(
                     "SELECT id, post_id, author_peer_id, content_type, content_text,
                             visibility, lamport_clock, created_at, updated_at,
-                            deleted_at, is_local, signature
+                            deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                      FROM posts
                      WHERE author_peer_id IN ({}) AND deleted_at IS NULL
                      ORDER BY created_at DESC
@@ -460,8 +596,8 @@ impl PostsRepository {
 
     /// Count posts by visibility for a given author.
     ///
-    /// Returns a [`VisibilityCounts`] with the total, public, and contacts-only
-    /// counts computed entirely in SQL -- no rows are transferred to Rust.
+    /// Returns a [`VisibilityCounts`] with the total, public, contacts-only and
+    /// private counts computed entirely in SQL -- no rows are transferred to Rust.
     pub fn count_by_visibility(
         db: &Database,
         author_peer_id: &str,
@@ -476,6 +612,7 @@ impl PostsRepository {
 
             let mut public_posts: usize = 0;
             let mut contacts_only_posts: usize = 0;
+            let mut private_posts: usize = 0;
 
             let mut rows = stmt.query(params![author_peer_id])?;
             while let Some(row) = rows.next()? {
@@ -484,16 +621,18 @@ impl PostsRepository {
                 match visibility.as_str() {
                     "public" => public_posts = count,
                     "contacts" => contacts_only_posts = count,
+                    "private" => private_posts = count,
                     _ => {} // ignore unknown visibility values
                 }
             }
 
-            let total_posts = public_posts + contacts_only_posts;
+            let total_posts = public_posts + contacts_only_posts + private_posts;
 
             Ok(VisibilityCounts {
                 total_posts,
                 public_posts,
                 contacts_only_posts,
+                private_posts,
             })
         })
     }
@@ -518,10 +657,10 @@ impl PostsRepository {
                     let mut stmt = conn.prepare(
                         "SELECT id, post_id, author_peer_id, content_type, content_text,
                                 visibility, lamport_clock, created_at, updated_at,
-                                deleted_at, is_local, signature
+                                deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                          FROM posts
                          WHERE author_peer_id = ? AND deleted_at IS NULL
-                               AND visibility = ? AND created_at < ?
+                               AND visibility = ? AND created_at < ? AND pinned = 0
                          ORDER BY created_at DESC
                          LIMIT ?",
                     )?;
@@ -535,11 +674,11 @@ impl PostsRepository {
                     let mut stmt = conn.prepare(
                         "SELECT id, post_id, author_peer_id, content_type, content_text,
                                 visibility, lamport_clock, created_at, updated_at,
-                                deleted_at, is_local, signature
+                                deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                          FROM posts
                          WHERE author_peer_id = ? AND deleted_at IS NULL
                                AND visibility = ?
-                         ORDER BY created_at DESC
+                         ORDER BY pinned DESC, created_at DESC
                          LIMIT ?",
                     )?;
                     let mut rows = stmt.query(params![author_peer_id, vis.as_str(), limit])?;
@@ -551,9 +690,10 @@ impl PostsRepository {
                     let mut stmt = conn.prepare(
                         "SELECT id, post_id, author_peer_id, content_type, content_text,
                                 visibility, lamport_clock, created_at, updated_at,
-                                deleted_at, is_local, signature
+                                deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                          FROM posts
                          WHERE author_peer_id = ? AND deleted_at IS NULL AND created_at < ?
+                           AND pinned = 0
                          ORDER BY created_at DESC
                          LIMIT ?",
                     )?;
@@ -566,10 +706,10 @@ impl PostsRepository {
                     let mut stmt = conn.prepare(
                         "SELECT id, post_id, author_peer_id, content_type, content_text,
                                 visibility, lamport_clock, created_at, updated_at,
-                                deleted_at, is_local, signature
+                                deleted_at, is_local, signature, pinned, relay_observed_at, sig_version
                          FROM posts
                          WHERE author_peer_id = ? AND deleted_at IS NULL
-                         ORDER BY created_at DESC
+                         ORDER BY pinned DESC, created_at DESC
                          LIMIT ?",
                     )?;
                     let mut rows = stmt.query(params![author_peer_id, limit])?;
@@ -595,6 +735,35 @@ impl PostsRepository {
         })
     }
 
+    /// Get the post a feed entry was imported as, if it was
+    pub fn get_imported_entry(db: &Database, entry_key: &str) -> SqliteResult<Option<String>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT post_id FROM imported_feed_entries WHERE entry_key = ?",
+                [entry_key],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+    }
+
+    /// Remember that a feed entry was imported as a post
+    pub fn record_imported_entry(
+        db: &Database,
+        entry_key: &str,
+        post_id: &str,
+        imported_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO imported_feed_entries (entry_key, post_id, imported_at)
+                 VALUES (?, ?, ?)",
+                params![entry_key, post_id, imported_at],
+            )?;
+            Ok(())
+        })
+    }
+
     /// Add media to a post
     pub fn add_media(db: &Database, media: &PostMediaData) -> SqliteResult<()> {
         db.with_connection(|conn| {
@@ -683,6 +852,67 @@ impl PostsRepository {
         })
     }
 
+    /// Record a post event in place of the one already stored for the same
+    /// author, post and lamport clock (used when a concurrent edit wins)
+    pub fn replace_post_event(
+        db: &Database,
+        params: &RecordPostEventParams<'_>,
+    ) -> SqliteResult<i64> {
+        db.with_connection(|conn| {
+            let received_at = chrono::Utc::now().timestamp();
+            conn.execute(
+                "INSERT OR REPLACE INTO post_events (
+                    event_id, event_type, post_id, author_peer_id,
+                    lamport_clock, timestamp, payload_cbor, signature, received_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)",
+                params![
+                    params.event_id,
+                    params.event_type,
+                    params.post_id,
+                    params.author_peer_id,
+                    params.lamport_clock,
+                    params.timestamp,
+                    params.payload_cbor,
+                    params.signature,
+                    received_at,
+                ],
+            )?;
+            Ok(conn.last_insert_rowid())
+        })
+    }
+
+    /// Get the recorded events for a post, oldest lamport clock first
+    pub fn get_post_events(db: &Database, post_id: &str) -> SqliteResult<Vec<PostEvent>> {
+        db.with_connection(|conn| {
+            let mut stmt = conn.prepare(
+                "SELECT id, event_id, event_type, post_id, author_peer_id,
+                        lamport_clock, timestamp, payload_cbor, signature, received_at
+                 FROM post_events
+                 WHERE post_id = ?
+                 ORDER BY lamport_clock ASC, id ASC",
+            )?;
+
+            let mut events = Vec::new();
+            let mut rows = stmt.query([post_id])?;
+            while let Some(row) = rows.next()? {
+                events.push(PostEvent {
+                    id: row.get(0)?,
+                    event_id: row.get(1)?,
+                    event_type: row.get(2)?,
+                    post_id: row.get(3)?,
+                    author_peer_id: row.get(4)?,
+                    lamport_clock: row.get(5)?,
+                    timestamp: row.get(6)?,
+                    payload_cbor: row.get(7)?,
+                    signature: row.get(8)?,
+                    received_at: row.get(9)?,
+                });
+            }
+
+            Ok(events)
+        })
+    }
+
     /// Check if a post event exists (for deduplication)
     pub fn event_exists(db: &Database, event_id: &str) -> SqliteResult<bool> {
         db.with_connection(|conn| {
@@ -732,6 +962,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1234567890,
             signature: vec![1, 2, 3, 4],
+            sig_version: 1,
         };
 
         let id = PostsRepository::insert_post(&db, &post).unwrap();
@@ -744,6 +975,7 @@ mod tests {
         assert_eq!(stored.content_text, Some("Hello, world!".to_string()));
         assert_eq!(stored.visibility, PostVisibility::Contacts);
         assert!(stored.is_local);
+        assert_eq!(stored.sig_version, 1);
     }
 
     #[test]
@@ -759,6 +991,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1234567890,
             signature: vec![1, 2, 3, 4],
+            sig_version: 1,
         };
 
         PostsRepository::insert_post(&db, &post).unwrap();
@@ -788,6 +1021,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1234567890,
             signature: vec![1, 2, 3, 4],
+            sig_version: 1,
         };
 
         PostsRepository::insert_post(&db, &post).unwrap();
@@ -805,6 +1039,42 @@ mod tests {
         assert!(posts.is_empty());
     }
 
+    #[test]
+    fn test_get_post_events_in_lamport_order() {
+        let db = create_test_db();
+
+        for (event_id, event_type, lamport_clock) in [
+            ("updated:post-ev:3", "updated", 3),
+            ("created:post-ev", "created", 1),
+        ] {
+            PostsRepository::record_post_event(
+                &db,
+                &RecordPostEventParams {
+                    event_id,
+                    event_type,
+                    post_id: "post-ev",
+                    author_peer_id: "peer-a",
+                    lamport_clock,
+                    timestamp: 1234567890 + lamport_clock,
+                    payload_cbor: &[0xa0],
+                    signature: &[1, 2, 3, 4],
+                },
+            )
+            .unwrap();
+        }
+
+        let events = PostsRepository::get_post_events(&db, "post-ev").unwrap();
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["created", "updated"]);
+        assert_eq!(events[1].lamport_clock, 3);
+        assert_eq!(events[1].signature, vec![1, 2, 3, 4]);
+        assert_eq!(events[1].payload_cbor, Some(vec![0xa0]));
+
+        assert!(PostsRepository::get_post_events(&db, "other-post")
+            .unwrap()
+            .is_empty());
+    }
+
     #[test]
     fn test_post_media() {
         let db = create_test_db();
@@ -818,6 +1088,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1234567890,
             signature: vec![1, 2, 3, 4],
+            sig_version: 1,
         };
 
         PostsRepository::insert_post(&db, &post).unwrap();
@@ -845,4 +1116,110 @@ mod tests {
         let hashes = PostsRepository::get_media_hashes(&db, "post-media").unwrap();
         assert_eq!(hashes, vec!["abc123"]);
     }
+
+    #[test]
+    fn test_private_posts_hidden_from_sync_but_visible_to_owner() {
+        let db = create_test_db();
+
+        for (post_id, visibility, clock) in [
+            ("post-public", PostVisibility::Public, 1),
+            ("post-private", PostVisibility::Private, 2),
+            ("post-contacts", PostVisibility::Contacts, 3),
+        ] {
+            let post = PostData {
+                post_id: post_id.to_string(),
+                author_peer_id: "peer-a".to_string(),
+                content_type: "text".to_string(),
+                content_text: Some(post_id.to_string()),
+                visibility,
+                lamport_clock: clock,
+                created_at: 1000 + clock,
+                signature: vec![1, 2, 3, 4],
+                sig_version: 1,
+            };
+            PostsRepository::insert_post(&db, &post).unwrap();
+        }
+
+        let synced = PostsRepository::get_by_author_after_cursor(&db, "peer-a", 0, 10).unwrap();
+        let synced_ids: Vec<&str> = synced.iter().map(|p| p.post_id.as_str()).collect();
+        assert_eq!(synced_ids, vec!["post-public", "post-contacts"]);
+
+        let wall = PostsRepository::get_by_author(&db, "peer-a", 10, None).unwrap();
+        assert_eq!(wall.len(), 3);
+        let own = PostsRepository::get_local_posts(&db, 10, None).unwrap();
+        assert!(own.iter().any(|p| p.visibility == PostVisibility::Private));
+
+        let counts = PostsRepository::count_by_visibility(&db, "peer-a").unwrap();
+        assert_eq!(counts.total_posts, 3);
+        assert_eq!(counts.private_posts, 1);
+    }
+
+    #[test]
+    fn test_pinned_post_leads_first_page_only() {
+        let db = create_test_db();
+
+        for i in 1..=4 {
+            let post = PostData {
+                post_id: format!("post-{}", i),
+                author_peer_id: "peer-a".to_string(),
+                content_type: "text".to_string(),
+                content_text: None,
+                visibility: PostVisibility::Contacts,
+                lamport_clock: i,
+                created_at: 1000 + i,
+                signature: vec![1],
+                sig_version: 1,
+            };
+            PostsRepository::insert_post(&db, &post).unwrap();
+        }
+
+        assert!(PostsRepository::pin_post(&db, "peer-a", "post-1").unwrap());
+        // Pinning another post replaces the previous pin
+        assert!(PostsRepository::pin_post(&db, "peer-a", "post-2").unwrap());
+        assert!(!PostsRepository::pin_post(&db, "peer-b", "post-3").unwrap());
+
+        let first = PostsRepository::get_local_posts(&db, 2, None).unwrap();
+        let ids: Vec<_> = first.iter().map(|p| p.post_id.as_str()).collect();
+        assert_eq!(ids, vec!["post-2", "post-4"]);
+        assert!(first[0].pinned);
+        assert!(!first[1].pinned);
+
+        let next =
+            PostsRepository::get_by_author(&db, "peer-a", 10, Some(first[1].created_at)).unwrap();
+        let ids: Vec<_> = next.iter().map(|p| p.post_id.as_str()).collect();
+        assert_eq!(ids, vec!["post-3", "post-1"]);
+
+        assert!(PostsRepository::unpin_post(&db, "peer-a", "post-2").unwrap());
+        assert!(!PostsRepository::unpin_post(&db, "peer-a", "post-2").unwrap());
+        let all = PostsRepository::get_by_author(&db, "peer-a", 10, None).unwrap();
+        assert_eq!(all[0].post_id, "post-4");
+        assert!(all.iter().all(|p| !p.pinned));
+    }
+
+    #[test]
+    fn test_relay_observed_at_keeps_earliest() {
+        let db = create_test_db();
+        let post = PostData {
+            post_id: "post-1".to_string(),
+            author_peer_id: "peer-a".to_string(),
+            content_type: "text".to_string(),
+            content_text: None,
+            visibility: PostVisibility::Contacts,
+            lamport_clock: 1,
+            created_at: 1000,
+            signature: vec![1],
+            sig_version: 1,
+        };
+        PostsRepository::insert_post(&db, &post).unwrap();
+
+        assert!(!PostsRepository::set_relay_observed_at(&db, "post-1", "peer-b", 1010).unwrap());
+        assert!(PostsRepository::set_relay_observed_at(&db, "post-1", "peer-a", 1010).unwrap());
+        assert!(!PostsRepository::set_relay_observed_at(&db, "post-1", "peer-a", 1020).unwrap());
+        assert!(PostsRepository::set_relay_observed_at(&db, "post-1", "peer-a", 1005).unwrap());
+
+        let stored = PostsRepository::get_by_post_id(&db, "post-1")
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.relay_observed_at, Some(1005));
+    }
 }
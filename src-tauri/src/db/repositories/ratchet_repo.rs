@@ -0,0 +1,61 @@
+//! Conversation ratchet repository for stored message ratchet sessions
+
+use crate::db::Database;
+use rusqlite::{params, OptionalExtension, Result as SqliteResult};
+
+pub struct RatchetRepository;
+
+impl RatchetRepository {
+    /// Get the encrypted session state for a conversation
+    pub fn get_state(db: &Database, conversation_id: &str) -> SqliteResult<Option<Vec<u8>>> {
+        db.with_connection(|conn| {
+            conn.query_row(
+                "SELECT state FROM conversation_ratchets WHERE conversation_id = ?",
+                [conversation_id],
+                |row| row.get(0),
+            )
+            .optional()
+        })
+    }
+
+    /// Store the encrypted session state, replacing any previous state
+    pub fn save_state(
+        db: &Database,
+        conversation_id: &str,
+        peer_id: &str,
+        state: &[u8],
+        updated_at: i64,
+    ) -> SqliteResult<()> {
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO conversation_ratchets (conversation_id, peer_id, state, updated_at)
+                 VALUES (?, ?, ?, ?)
+                 ON CONFLICT(conversation_id) DO UPDATE SET
+                    state = excluded.state,
+                    updated_at = excluded.updated_at",
+                params![conversation_id, peer_id, state, updated_at],
+            )?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_state_replaces_previous() {
+        let db = Database::in_memory().unwrap();
+        assert_eq!(RatchetRepository::get_state(&db, "conv-1").unwrap(), None);
+
+        RatchetRepository::save_state(&db, "conv-1", "peer-a", &[1, 2], 1000).unwrap();
+        RatchetRepository::save_state(&db, "conv-1", "peer-a", &[3, 4], 1100).unwrap();
+
+        assert_eq!(
+            RatchetRepository::get_state(&db, "conv-1").unwrap(),
+            Some(vec![3, 4])
+        );
+        assert_eq!(RatchetRepository::get_state(&db, "conv-2").unwrap(), None);
+    }
+}
@@ -0,0 +1,135 @@
+//! Settings repository for simple persisted key/value preferences
+
+use crate::db::Database;
+use rusqlite::{params, OptionalExtension, Result as SqliteResult};
+
+/// Setting key for the manual NAT status override
+pub const SETTING_NAT_OVERRIDE: &str = "network.nat_override";
+/// Setting key for whether AutoNAT probing is enabled
+pub const SETTING_AUTONAT_ENABLED: &str = "network.autonat_enabled";
+/// Setting key for which of our addresses are announced to remote peers
+pub const SETTING_ADDRESS_FILTER: &str = "network.address_filter";
+/// Setting key for whether newly connected non-contacts are asked for their identity
+pub const SETTING_AUTO_REQUEST_IDENTITY: &str = "network.auto_request_identity";
+/// Setting key for the TCP port to listen on (0 = OS-assigned)
+pub const SETTING_TCP_PORT: &str = "network.tcp_port";
+/// Setting key for the QUIC port to listen on (0 = OS-assigned)
+pub const SETTING_QUIC_PORT: &str = "network.quic_port";
+/// Setting key for whether a busy fixed port falls back to an OS-assigned one
+pub const SETTING_PORT_FALLBACK: &str = "network.port_fallback";
+/// Setting key for how outgoing message nonces are derived
+pub const SETTING_MESSAGE_NONCE_STRATEGY: &str = "messaging.nonce_strategy";
+/// Setting key for how many days messages are kept before being pruned
+pub const SETTING_MESSAGE_RETENTION_DAYS: &str = "messaging.retention_days";
+/// Setting key for the cipher used with contacts that announced support for it
+pub const SETTING_MESSAGE_CIPHER: &str = "messaging.cipher";
+/// Setting key for how many times an undelivered message is sent before giving up
+pub const SETTING_MESSAGE_MAX_SEND_ATTEMPTS: &str = "messaging.max_send_attempts";
+/// Setting key for whether we tell authors when we fetch their posts
+pub const SETTING_REPORT_POST_VIEWS: &str = "content.report_post_views";
+/// Setting key for whether view reports on our posts keep the time of the view
+pub const SETTING_POST_VIEW_TIMING: &str = "content.post_view_timing";
+/// Setting key for the oldest signature scheme version accepted on posts and messages
+pub const SETTING_MIN_SIGNATURE_VERSION: &str = "security.min_signature_version";
+/// Setting key for how feed sync picks which connected peers to ask, as JSON
+pub const SETTING_SYNC_STRATEGY: &str = "network.sync_strategy";
+/// Setting key for the seconds between reconnection passes over relays and
+/// bootstrap nodes (0 = off)
+pub const SETTING_RECONCILE_INTERVAL: &str = "network.reconcile_interval_secs";
+/// Setting key for whether presence heartbeats are exchanged with connected contacts
+pub const SETTING_HEARTBEAT_ENABLED: &str = "network.heartbeat_enabled";
+/// Setting key for the seconds between presence heartbeats
+pub const SETTING_HEARTBEAT_INTERVAL: &str = "network.heartbeat_interval_secs";
+/// Setting key for the most media attachments a post may carry, ours or a peer's
+pub const SETTING_MAX_POST_ATTACHMENTS: &str = "content.max_post_attachments";
+/// Setting key for the seconds between background syncs with connected peers (0 = off)
+pub const SETTING_AUTO_SYNC_INTERVAL: &str = "content.auto_sync_interval_secs";
+/// Setting key for whether contacts granted `ShareContacts` may fetch our contact list
+pub const SETTING_SHARE_CONTACT_LIST: &str = "contacts.share_contact_list";
+/// Setting key for the directory exported files are saved to
+pub const SETTING_DOWNLOAD_DIR: &str = "files.download_dir";
+/// Setting key for the capabilities granted to new contacts, comma separated
+pub const SETTING_DEFAULT_GRANT_POLICY: &str = "permissions.default_grant_policy";
+
+pub struct SettingsRepository;
+
+impl SettingsRepository {
+    /// Get a setting value
+    pub fn get(db: &Database, key: &str) -> SqliteResult<Option<String>> {
+        db.with_connection(|conn| {
+            conn.query_row("SELECT value FROM settings WHERE key = ?", [key], |row| {
+                row.get(0)
+            })
+            .optional()
+        })
+    }
+
+    /// Insert or update a setting value
+    pub fn set(db: &Database, key: &str, value: &str) -> SqliteResult<()> {
+        let now = chrono::Utc::now().timestamp();
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO settings (key, value, updated_at) VALUES (?, ?, ?)
+                 ON CONFLICT(key) DO UPDATE SET value = excluded.value, updated_at = excluded.updated_at",
+                params![key, value, now],
+            )?;
+            Ok(())
+        })
+    }
+
+    /// Remove a setting. Returns `false` if it was not set.
+    pub fn delete(db: &Database, key: &str) -> SqliteResult<bool> {
+        db.with_connection(|conn| {
+            let rows = conn.execute("DELETE FROM settings WHERE key = ?", [key])?;
+            Ok(rows > 0)
+        })
+    }
+
+    /// Get a boolean setting, falling back to `default` if unset or unparseable
+    pub fn get_bool(db: &Database, key: &str, default: bool) -> SqliteResult<bool> {
+        Ok(Self::get(db, key)?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_set_get_delete() {
+        let db = Database::in_memory().unwrap();
+
+        assert_eq!(SettingsRepository::get(&db, "key").unwrap(), None);
+
+        SettingsRepository::set(&db, "key", "one").unwrap();
+        assert_eq!(
+            SettingsRepository::get(&db, "key").unwrap(),
+            Some("one".to_string())
+        );
+
+        SettingsRepository::set(&db, "key", "two").unwrap();
+        assert_eq!(
+            SettingsRepository::get(&db, "key").unwrap(),
+            Some("two".to_string())
+        );
+
+        assert!(SettingsRepository::delete(&db, "key").unwrap());
+        assert!(!SettingsRepository::delete(&db, "key").unwrap());
+        assert_eq!(SettingsRepository::get(&db, "key").unwrap(), None);
+    }
+
+    #[test]
+    fn test_get_bool_default() {
+        let db = Database::in_memory().unwrap();
+
+        assert!(SettingsRepository::get_bool(&db, "flag", true).unwrap());
+
+        SettingsRepository::set(&db, "flag", "false").unwrap();
+        assert!(!SettingsRepository::get_bool(&db, "flag", true).unwrap());
+
+        SettingsRepository::set(&db, "flag", "garbage").unwrap();
+        assert!(SettingsRepository::get_bool(&db, "flag", true).unwrap());
+    }
+}
@@ -29,6 +29,8 @@ pub enum ErrorCode {
     NetworkServiceUnavailable,
     NetworkPeerUnreachable,
     NetworkTimeout,
+    NetworkPortInUse,
+    NotCommunityRelay,
     InternalError,
 }
 
@@ -60,6 +62,8 @@ impl ErrorCode {
             ErrorCode::NetworkServiceUnavailable => "Network service is unavailable",
             ErrorCode::NetworkPeerUnreachable => "Could not reach the peer",
             ErrorCode::NetworkTimeout => "The connection timed out",
+            ErrorCode::NetworkPortInUse => "The network port is already in use",
+            ErrorCode::NotCommunityRelay => "This relay doesn't host community boards",
             ErrorCode::InternalError => "An unexpected error occurred",
         }
     }
@@ -80,6 +84,12 @@ impl ErrorCode {
             }
             ErrorCode::NetworkPeerUnreachable => Some("The peer may be offline. Try again later"),
             ErrorCode::NetworkTimeout => Some("Try again or check your connection"),
+            ErrorCode::NetworkPortInUse => {
+                Some("Choose a different port, use port 0, or enable the random port fallback")
+            }
+            ErrorCode::NotCommunityRelay => {
+                Some("Connect to a relay running in community mode to use boards")
+            }
             _ => None,
         }
     }
@@ -182,6 +192,12 @@ pub enum AppError {
     #[error("Network error: {0}")]
     NetworkTimeout(String),
 
+    #[error("Port in use: {0}")]
+    NetworkPortInUse(String),
+
+    #[error("Not a community relay: {0}")]
+    NotCommunityRelay(String),
+
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -212,6 +228,8 @@ impl AppError {
             AppError::NetworkServiceUnavailable(_) => ErrorCode::NetworkServiceUnavailable,
             AppError::NetworkPeerUnreachable(_) => ErrorCode::NetworkPeerUnreachable,
             AppError::NetworkTimeout(_) => ErrorCode::NetworkTimeout,
+            AppError::NetworkPortInUse(_) => ErrorCode::NetworkPortInUse,
+            AppError::NotCommunityRelay(_) => ErrorCode::NotCommunityRelay,
             AppError::Internal(_) => ErrorCode::InternalError,
         }
     }
@@ -6,18 +6,23 @@ pub mod models;
 pub mod p2p;
 pub mod services;
 
-use commands::NetworkState;
-use db::Database;
+use commands::{NetworkState, NETWORK_SHUTDOWN_TIMEOUT};
+use db::{Database, DatabaseStartupStatus};
 use logging::{get_log_directory, LogConfig};
+use services::content_sync_service::{auto_sync_delay, DEFAULT_AUTO_SYNC_INTERVAL_SECS};
 use services::{
     AccountsService, BoardService, CallingService, ContactsService, ContentSyncService,
-    FeedService, IdentityService, MediaStorageService, MessagingService, PermissionsService,
-    PostsService,
+    FeatureFlag, FeatureFlagsService, FeedService, IdentityService, MediaBackendConfig,
+    MediaStorageService, MessagingService, PermissionsService, PostsService,
 };
 use std::path::PathBuf;
 use std::sync::Arc;
-use tauri::Manager;
-use tracing::info;
+use std::time::Duration;
+use tauri::{Emitter, Manager, RunEvent};
+use tracing::{error, info};
+
+/// How often expired messages are pruned in the background
+const MESSAGE_RETENTION_SWEEP_INTERVAL: Duration = Duration::from_secs(60 * 60);
 
 pub struct LogDirectory(pub PathBuf);
 
@@ -36,6 +41,113 @@ fn get_custom_data_dir() -> Option<PathBuf> {
         .map(PathBuf::from)
 }
 
+/// Get custom media storage directory from environment variable
+fn get_custom_media_dir() -> Option<PathBuf> {
+    std::env::var("HARBOR_MEDIA_DIR")
+        .ok()
+        .filter(|s| !s.is_empty())
+        .map(PathBuf::from)
+}
+
+/// Whether to run in ephemeral guest mode (nothing is written to disk)
+fn is_ephemeral_mode() -> bool {
+    std::env::var("HARBOR_EPHEMERAL")
+        .map(|v| v == "1" || v.eq_ignore_ascii_case("true"))
+        .unwrap_or(false)
+}
+
+/// Periodically prune messages past their retention window and report what was deleted.
+/// Audio of voice notes whose messages are gone, by retention or otherwise, is
+/// deleted on the same schedule.
+fn spawn_message_retention_sweep(
+    app: tauri::AppHandle,
+    messaging_service: Arc<MessagingService>,
+    media_service: Arc<MediaStorageService>,
+) {
+    tauri::async_runtime::spawn(async move {
+        let mut interval = tokio::time::interval(MESSAGE_RETENTION_SWEEP_INTERVAL);
+        loop {
+            interval.tick().await;
+            match messaging_service.sweep_expired_messages(messaging_service.clock().timestamp()) {
+                Ok(summary) if summary.messages_deleted > 0 => {
+                    if let Err(e) = app.emit("harbor:retention", &summary) {
+                        tracing::warn!("Failed to emit retention event: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => tracing::warn!("Message retention sweep failed: {}", e),
+            }
+            if let Err(e) = media_service.prune_message_media() {
+                tracing::warn!("Voice note media prune failed: {}", e);
+            }
+        }
+    });
+}
+
+/// Snapshot a database that opened cleanly, as the backup to restore if it
+/// is later found corrupted
+fn spawn_database_backup(db: Database) {
+    tauri::async_runtime::spawn_blocking(move || match db.write_backup() {
+        Ok(path) => info!("Database backed up to {:?}", path),
+        Err(e) => tracing::warn!("Failed to back up database: {}", e),
+    });
+}
+
+/// Periodically request manifests from connected peers while the
+/// `auto_sync_timer` feature flag is on. The interval comes from settings, is
+/// jittered so peers don't sync in lockstep, and takes effect immediately when
+/// changed. Skipped while the connection is metered.
+fn spawn_auto_sync(
+    app: tauri::AppHandle,
+    content_sync_service: Arc<ContentSyncService>,
+    feature_flags_service: Arc<FeatureFlagsService>,
+) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let enabled = feature_flags_service
+                .is_enabled(FeatureFlag::AutoSyncTimer)
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read auto-sync feature flag: {}", e);
+                    false
+                });
+            let interval_secs = content_sync_service
+                .get_auto_sync_interval()
+                .unwrap_or_else(|e| {
+                    tracing::warn!("Failed to read auto-sync interval: {}", e);
+                    DEFAULT_AUTO_SYNC_INTERVAL_SECS
+                });
+            if !enabled || interval_secs == 0 {
+                tokio::select! {
+                    _ = content_sync_service.auto_sync_interval_changed() => {}
+                    _ = feature_flags_service.changed() => {}
+                }
+                continue;
+            }
+
+            let jitter = rand::Rng::gen_range(&mut rand::thread_rng(), -1.0..=1.0);
+            tokio::select! {
+                _ = tokio::time::sleep(auto_sync_delay(interval_secs, jitter)) => {}
+                _ = content_sync_service.auto_sync_interval_changed() => continue,
+                _ = feature_flags_service.changed() => continue,
+            }
+
+            if content_sync_service.is_metered_connection() {
+                tracing::debug!("Skipping auto-sync on metered connection");
+                continue;
+            }
+            let Ok(handle) = app.state::<NetworkState>().get_handle().await else {
+                continue;
+            };
+            match commands::sync_connected_peers(&handle).await {
+                Ok(peers) => {
+                    tracing::debug!("Auto-sync requested manifests from {} peers", peers.len())
+                }
+                Err(e) => tracing::warn!("Auto-sync failed: {}", e),
+            }
+        }
+    });
+}
+
 /// Get the database path for the application
 fn get_db_path(app: &tauri::AppHandle) -> PathBuf {
     // Check for custom data directory first
@@ -64,6 +176,7 @@ fn get_db_path(app: &tauri::AppHandle) -> PathBuf {
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
     let profile = get_profile_name();
+    let ephemeral = is_ephemeral_mode();
 
     tauri::Builder::default()
         .plugin(tauri_plugin_opener::init())
@@ -113,22 +226,48 @@ pub fn run() {
             // Initialize accounts service (manages multi-account registry)
             let accounts_service = Arc::new(AccountsService::new(app_data_dir.clone()));
 
-            // Initialize database
-            let db_path = get_db_path(app.handle());
-            info!("Database path: {:?}", db_path);
+            // Initialize database (in-memory only for ephemeral guest sessions)
+            let (db, data_dir, db_status) = if ephemeral {
+                info!("Ephemeral mode: database and media are kept in memory only");
+                let db = Database::in_memory().expect("Failed to initialize database");
+                (
+                    Arc::new(db),
+                    app_data_dir.clone(),
+                    DatabaseStartupStatus::default(),
+                )
+            } else {
+                let db_path = get_db_path(app.handle());
+                info!("Database path: {:?}", db_path);
 
-            // Migrate legacy single-account setup if needed
-            if let Ok(Some(account)) = accounts_service.migrate_legacy_account(&db_path) {
-                info!("Migrated legacy account: {}", account.display_name);
-            }
+                // Migrate legacy single-account setup if needed
+                if let Ok(Some(account)) = accounts_service.migrate_legacy_account(&db_path) {
+                    info!("Migrated legacy account: {}", account.display_name);
+                }
 
-            // Save the data directory (parent of db file) before db_path is moved
-            let data_dir = db_path
-                .parent()
-                .map(|p| p.to_path_buf())
-                .unwrap_or_else(|| app_data_dir.clone());
+                // Save the data directory (parent of db file) before db_path is moved
+                let data_dir = db_path
+                    .parent()
+                    .map(|p| p.to_path_buf())
+                    .unwrap_or_else(|| app_data_dir.clone());
 
-            let db = Arc::new(Database::new(db_path).expect("Failed to initialize database"));
+                // A database that won't open must not crash startup. The app
+                // runs on a throwaway in-memory database and the UI offers
+                // recovery instead of anything else.
+                let (db, db_status) = match Database::open_or_recover(db_path) {
+                    Ok((db, db_status)) => {
+                        if !db_status.rebuilt {
+                            spawn_database_backup(db.clone());
+                        }
+                        (db, db_status)
+                    }
+                    Err(db_status) => {
+                        error!("Database unavailable, starting in recovery mode");
+                        let db = Database::in_memory().expect("Failed to initialize database");
+                        (db, db_status)
+                    }
+                };
+                (Arc::new(db), data_dir, db_status)
+            };
 
             // Initialize services
             let identity_service = Arc::new(IdentityService::new(db.clone()));
@@ -168,18 +307,40 @@ pub fn run() {
                 permissions_service.clone(),
             ));
             let board_service = Arc::new(BoardService::new(db.clone(), identity_service.clone()));
+            let feature_flags_service = Arc::new(FeatureFlagsService::new(db.clone()));
+            spawn_auto_sync(
+                app.handle().clone(),
+                content_sync_service.clone(),
+                feature_flags_service.clone(),
+            );
 
             // Initialize media storage service (content-addressed file storage)
+            // In recovery mode the database is empty, so a media prune against
+            // the real store would delete everything in it
+            let media_config = match get_custom_media_dir() {
+                _ if ephemeral || db_status.error.is_some() => MediaBackendConfig::Memory,
+                Some(root) => {
+                    info!("Using custom media directory: {:?}", root);
+                    MediaBackendConfig::Filesystem { root }
+                }
+                None => MediaBackendConfig::default_for(&data_dir),
+            };
             let media_service = Arc::new(
-                MediaStorageService::new(&data_dir, db.clone())
+                MediaStorageService::from_config(&media_config, db.clone())
                     .expect("Failed to initialize media storage"),
             );
+            spawn_message_retention_sweep(
+                app.handle().clone(),
+                messaging_service.clone(),
+                media_service.clone(),
+            );
 
             // Initialize network state (will be populated when identity is unlocked)
             let network_state = NetworkState::new();
 
             // Register state
             app.manage(db);
+            app.manage(db_status);
             app.manage(accounts_service);
             app.manage(identity_service);
             app.manage(contacts_service);
@@ -190,6 +351,7 @@ pub fn run() {
             app.manage(feed_service);
             app.manage(calling_service);
             app.manage(board_service);
+            app.manage(feature_flags_service);
             app.manage(media_service);
             app.manage(network_state);
 
@@ -210,11 +372,16 @@ pub fn run() {
             commands::is_identity_unlocked,
             commands::get_identity_info,
             commands::create_identity,
+            commands::create_ephemeral_identity,
+            commands::is_identity_ephemeral,
+            commands::export_identity_backup,
             commands::unlock_identity,
             commands::lock_identity,
             commands::update_display_name,
             commands::update_bio,
             commands::update_passphrase_hint,
+            commands::update_accent_color,
+            commands::change_passphrase,
             commands::get_peer_id,
             // Network commands
             commands::get_connected_peers,
@@ -230,9 +397,38 @@ pub fn run() {
             commands::get_shareable_addresses,
             commands::get_shareable_contact_string,
             commands::add_contact_from_string,
+            commands::get_identity_qr_payload,
+            commands::add_contact_from_qr_payload,
             commands::add_relay_server,
             commands::connect_to_public_relays,
+            commands::reconcile_connections,
             commands::get_nat_status,
+            commands::get_network_diagnostics,
+            commands::get_connection_failures,
+            commands::clear_connection_failures,
+            commands::run_health_check,
+            commands::get_database_status,
+            commands::restore_database_backup,
+            commands::get_feature_flag,
+            commands::set_feature_flag,
+            commands::list_feature_flags,
+            commands::set_nat_override,
+            commands::set_autonat_enabled,
+            commands::get_auto_request_identity,
+            commands::set_auto_request_identity,
+            commands::get_listen_ports,
+            commands::set_listen_ports,
+            commands::get_bound_ports,
+            commands::get_dht_routing_table,
+            commands::set_address_filter,
+            commands::get_sync_strategy,
+            commands::set_sync_strategy,
+            commands::get_heartbeat_enabled,
+            commands::set_heartbeat_enabled,
+            commands::get_heartbeat_interval,
+            commands::set_heartbeat_interval,
+            commands::get_reconcile_interval,
+            commands::set_reconcile_interval,
             // Bootstrap configuration commands
             commands::get_bootstrap_nodes,
             commands::add_bootstrap_node_config,
@@ -247,9 +443,21 @@ pub fn run() {
             commands::block_contact,
             commands::unblock_contact,
             commands::remove_contact,
+            commands::forget_contact,
             commands::is_contact,
             commands::is_contact_blocked,
+            commands::get_stale_contacts,
+            commands::get_contact_name_history,
+            commands::create_contact_group,
+            commands::delete_contact_group,
+            commands::get_contact_groups,
+            commands::add_contact_to_group,
+            commands::remove_contact_from_group,
+            commands::get_contact_group_members,
             commands::request_peer_identity,
+            commands::get_contact_suggestions,
+            commands::get_share_contact_list,
+            commands::set_share_contact_list,
             // Permission commands
             commands::grant_permission,
             commands::revoke_permission,
@@ -257,36 +465,65 @@ pub fn run() {
             commands::we_have_capability,
             commands::get_granted_permissions,
             commands::get_received_permissions,
+            commands::get_permission_history,
             commands::get_chat_peers,
             commands::grant_all_permissions,
+            commands::reconcile_permissions,
+            commands::grant_group_permission,
+            commands::get_default_grant_policy,
+            commands::set_default_grant_policy,
             // Messaging commands
             commands::send_message,
+            commands::send_voice_note,
+            commands::send_direct_message,
             commands::get_messages,
             commands::get_conversations,
+            commands::search_messages,
             commands::mark_conversation_read,
             commands::get_unread_count,
             commands::get_total_unread_count,
             commands::clear_conversation_history,
             commands::delete_conversation,
             commands::edit_message,
+            commands::delete_message,
+            commands::get_message_nonce_strategy,
+            commands::set_message_nonce_strategy,
+            commands::get_message_cipher,
+            commands::set_message_cipher,
+            commands::get_message_retention,
+            commands::set_message_retention,
+            commands::get_pending_message_count,
+            commands::get_max_send_attempts,
+            commands::set_max_send_attempts,
+            commands::get_conversation_retention,
+            commands::set_conversation_retention,
+            commands::set_conversation_pinned,
+            commands::run_message_retention_sweep,
             // Post commands
             commands::create_post,
             commands::update_post,
             commands::delete_post,
+            commands::pin_post,
+            commands::unpin_post,
             commands::get_post,
+            commands::get_post_events,
             commands::get_my_posts,
             commands::get_posts_by_author,
             commands::add_post_media,
             commands::get_post_media,
+            commands::get_max_post_attachments,
+            commands::set_max_post_attachments,
             // Feed commands
             commands::get_feed,
             commands::get_wall,
+            commands::get_contact_feed,
             commands::get_wall_preview,
             commands::get_wall_visibility_stats,
             // RSS commands
             commands::generate_rss_feed,
             commands::get_peer_rss_feed,
             commands::get_rss_feed_url,
+            commands::import_posts_from_feed,
             // Like commands
             commands::like_post,
             commands::unlike_post,
@@ -298,11 +535,16 @@ pub fn run() {
             commands::get_comments,
             commands::delete_comment,
             commands::get_comment_counts,
+            // Activity feed commands
+            commands::get_activity,
+            commands::mark_activity_read,
+            commands::get_unread_activity_count,
             // Calling commands
             commands::start_call,
             commands::answer_call,
             commands::send_ice_candidate,
             commands::hangup_call,
+            commands::send_call_data,
             commands::process_offer,
             commands::process_answer,
             commands::process_ice_candidate,
@@ -316,16 +558,33 @@ pub fn run() {
             commands::request_content_manifest_with_cursor,
             commands::request_content_fetch,
             commands::get_sync_cursor,
+            commands::resync_all,
+            commands::get_post_views,
+            commands::get_post_delivery_status,
+            commands::get_post_view_settings,
+            commands::set_post_view_settings,
+            commands::get_min_signature_version,
+            commands::set_min_signature_version,
             commands::sync_with_all_peers,
+            commands::get_auto_sync_interval,
+            commands::set_auto_sync_interval,
+            commands::set_metered_connection,
+            commands::sync_with_peer,
             // Board commands
             commands::get_communities,
             commands::join_community,
+            commands::join_community_from_invite,
+            commands::create_community_invite,
             commands::leave_community,
             commands::get_boards,
             commands::get_board_posts,
             commands::submit_board_post,
+            commands::create_board,
             commands::delete_board_post,
+            commands::react_to_board_post,
             commands::sync_board,
+            commands::subscribe_board,
+            commands::unsubscribe_board,
             // Media commands (content-addressed storage)
             commands::store_media,
             commands::store_media_bytes,
@@ -339,9 +598,19 @@ pub fn run() {
             commands::delete_wall_post_on_relay,
             // File commands
             commands::save_to_downloads,
+            commands::get_download_dir,
+            commands::set_download_dir,
             // Link preview commands
             commands::fetch_link_preview,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Let the network finish in-flight writes and close its
+            // connections before the runtime goes away
+            if let RunEvent::Exit = event {
+                let network = app.state::<NetworkState>();
+                tauri::async_runtime::block_on(network.shutdown(NETWORK_SHUTDOWN_TIMEOUT));
+            }
+        });
 }
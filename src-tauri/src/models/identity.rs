@@ -11,6 +11,8 @@ pub struct LocalIdentity {
     pub avatar_hash: Option<String>,
     pub bio: Option<String>,
     pub passphrase_hint: Option<String>,
+    /// Hex color for telling accounts apart (local only)
+    pub accent_color: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -26,6 +28,7 @@ pub struct IdentityInfo {
     pub avatar_hash: Option<String>,
     pub bio: Option<String>,
     pub passphrase_hint: Option<String>,
+    pub accent_color: Option<String>,
     pub created_at: i64,
     pub updated_at: i64,
 }
@@ -43,12 +46,29 @@ impl From<LocalIdentity> for IdentityInfo {
             avatar_hash: identity.avatar_hash,
             bio: identity.bio,
             passphrase_hint: identity.passphrase_hint,
+            accent_color: identity.accent_color,
             created_at: identity.created_at,
             updated_at: identity.updated_at,
         }
     }
 }
 
+/// Portable identity backup (private keys stay passphrase-encrypted)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IdentityBackup {
+    pub version: u32,
+    pub peer_id: String,
+    pub public_key: String,            // base64 encoded
+    pub x25519_public: String,         // base64 encoded
+    pub private_key_encrypted: String, // base64 encoded
+    pub display_name: String,
+    pub bio: Option<String>,
+    pub passphrase_hint: Option<String>,
+    pub created_at: i64,
+    pub exported_at: i64,
+}
+
 /// Request to create a new identity
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -57,6 +77,22 @@ pub struct CreateIdentityRequest {
     pub passphrase: String,
     pub bio: Option<String>,
     pub passphrase_hint: Option<String>,
+    /// Key derivation strength; defaults to standard
+    #[serde(default)]
+    pub kdf_profile: Option<KdfProfile>,
+}
+
+/// Strength profile for the passphrase key derivation function
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KdfProfile {
+    /// Faster unlock for low-memory devices
+    Light,
+    /// The argon2 defaults
+    #[default]
+    Standard,
+    /// Slower unlock, harder to brute-force
+    Strong,
 }
 
 /// Request to unlock identity
@@ -5,13 +5,18 @@ use libp2p::{
     StreamProtocol,
 };
 use std::collections::HashMap;
+use std::num::NonZeroUsize;
 use std::time::Duration;
 
+use super::config::{KademliaSettings, MdnsSettings, RequestTimeouts};
 use super::protocols::board_sync::{BoardSyncRequest, BoardSyncResponse};
+use super::protocols::call_data::{CallDataAck, CallDataFrame, CALL_DATA_PROTOCOL};
 use super::protocols::media_sync::{MediaFetchRequest, MediaFetchResponse, MEDIA_SYNC_PROTOCOL};
+use super::protocols::presence::{Heartbeat, PRESENCE_PROTOCOL};
 use super::protocols::{
     BOARD_SYNC_PROTOCOL, CONTENT_SYNC_PROTOCOL, IDENTITY_PROTOCOL, MESSAGING_PROTOCOL,
 };
+use super::types::AddressFilter;
 
 // Duration is used in ping configuration
 
@@ -31,8 +36,8 @@ pub struct ChatBehaviour {
     /// DCUtR for direct connection upgrade through relay (disabled by default —
     /// hole punching fails in most agent topologies and destabilises relay circuits)
     pub dcutr: Toggle<dcutr::Behaviour>,
-    /// AutoNAT for external address discovery
-    pub autonat: autonat::Behaviour,
+    /// AutoNAT for external address discovery (can be disabled via config)
+    pub autonat: Toggle<autonat::Behaviour>,
     /// Request-response for identity exchange
     pub identity_exchange:
         request_response::cbor::Behaviour<IdentityExchangeRequest, IdentityExchangeResponse>,
@@ -44,6 +49,10 @@ pub struct ChatBehaviour {
     pub board_sync: request_response::cbor::Behaviour<BoardSyncRequest, BoardSyncResponse>,
     /// Request-response for media sync (P2P image transfer)
     pub media_sync: request_response::cbor::Behaviour<MediaFetchRequest, MediaFetchResponse>,
+    /// Request-response for signed presence heartbeats
+    pub presence: request_response::cbor::Behaviour<Heartbeat, Heartbeat>,
+    /// Request-response for the in-call data channel
+    pub call_data: request_response::cbor::Behaviour<CallDataFrame, CallDataAck>,
 }
 
 /// Identity exchange request (simplified for request-response)
@@ -52,6 +61,43 @@ pub struct IdentityExchangeRequest {
     pub requester_peer_id: String,
     pub timestamp: i64,
     pub signature: Vec<u8>,
+    /// Also ask for the responder's shared contact list. Older peers omit
+    /// this and ignore it.
+    #[serde(default)]
+    pub contact_list: Option<ContactListRequest>,
+    /// Grants the requester issued to the responder that the responder
+    /// hasn't acknowledged yet, resent on reconnect
+    #[serde(default)]
+    pub permission_grants: Option<Vec<PermissionGrantProto>>,
+}
+
+/// A signed permission grant, exactly as the issuer signed it
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PermissionGrantProto {
+    pub payload_cbor: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+/// The responder's signed ack of the resent grants it stored
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PermissionAckProto {
+    pub grant_ids: Vec<String>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// Signed request for the responder's shared contact list
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct ContactListRequest {
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// A contact the responder is willing to share
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct SharedContactProto {
+    pub peer_id: String,
+    pub display_name: String,
 }
 
 /// Identity exchange response
@@ -65,6 +111,17 @@ pub struct IdentityExchangeResponse {
     pub bio: Option<String>,
     pub timestamp: i64,
     pub signature: Vec<u8>,
+    /// The responder's shared contacts, when `contact_list` was asked for and
+    /// the requester is allowed to see them
+    #[serde(default)]
+    pub shared_contacts: Option<Vec<SharedContactProto>>,
+    /// Ack of the request's `permission_grants`, when any were sent
+    #[serde(default)]
+    pub permission_ack: Option<PermissionAckProto>,
+    /// Message ciphers the responder can decrypt. Older peers omit this and
+    /// only understand AES-256-GCM.
+    #[serde(default)]
+    pub supported_ciphers: Option<Vec<String>>,
 }
 
 /// Messaging request
@@ -94,6 +151,13 @@ pub struct PostSummaryProto {
     pub created_at: i64,
 }
 
+/// Minimal post header for headers-only content sync manifests
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PostHeaderProto {
+    pub post_id: String,
+    pub lamport_clock: u64,
+}
+
 /// Content sync request (wire protocol)
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 #[serde(tag = "type", rename_all = "snake_case")]
@@ -105,6 +169,9 @@ pub enum ContentSyncRequest {
         limit: u32,
         timestamp: i64,
         signature: Vec<u8>,
+        /// Older peers omit this and always receive full summaries
+        #[serde(default)]
+        detail_level: crate::services::ManifestDetailLevel,
     },
     /// Fetch a full post by ID
     FetchPost {
@@ -114,6 +181,19 @@ pub enum ContentSyncRequest {
         timestamp: i64,
         signature: Vec<u8>,
     },
+    /// Tell an author we fetched one of their posts
+    PostView {
+        post_id: String,
+        viewer_peer_id: String,
+        viewed_at: i64,
+        signature: Vec<u8>,
+    },
+    /// Push a newly created post to an online contact
+    PostAnnouncement {
+        post: PostSummaryProto,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
 }
 
 /// Content sync response (wire protocol)
@@ -128,6 +208,9 @@ pub enum ContentSyncResponse {
         next_cursor: HashMap<String, u64>,
         timestamp: i64,
         signature: Vec<u8>,
+        /// Populated instead of `posts` for headers-only manifests
+        #[serde(default)]
+        headers: Vec<PostHeaderProto>,
     },
     /// Response with full post content
     Post {
@@ -139,36 +222,65 @@ pub enum ContentSyncResponse {
         lamport_clock: u64,
         created_at: i64,
         signature: Vec<u8>,
+        /// Signature scheme version; peers that predate versions omit it
+        #[serde(default = "crate::services::legacy_sig_version")]
+        sig_version: u32,
     },
+    /// Acknowledges a view report
+    ViewRecorded { post_id: String },
+    /// Acknowledges a post announcement
+    AnnouncementReceived { post_id: String },
     /// Error response
     Error { error: String },
 }
 
 impl ChatBehaviour {
     /// Create a new chat behaviour with the given local peer ID and keypair
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         local_peer_id: libp2p::PeerId,
         local_public_key: libp2p::identity::PublicKey,
         relay_client: relay::client::Behaviour,
+        enable_autonat: bool,
+        address_filter: AddressFilter,
+        request_timeouts: &RequestTimeouts,
+        kademlia_settings: &KademliaSettings,
+        mdns_settings: &MdnsSettings,
+        agent_version: String,
     ) -> Self {
         // Ping
         let ping = ping::Behaviour::new(ping::Config::new().with_interval(Duration::from_secs(15)));
 
-        // Identify
-        let identify = identify::Behaviour::new(identify::Config::new(
-            "/harbor/1.0.0".to_string(),
-            local_public_key.clone(),
-        ));
+        // Identify. With address filtering on, listen addresses are hidden and
+        // the network service promotes the allowed ones to external addresses.
+        let identify = identify::Behaviour::new(
+            identify::Config::new("/harbor/1.0.0".to_string(), local_public_key.clone())
+                .with_agent_version(agent_version)
+                .with_hide_listen_addrs(address_filter != AddressFilter::All),
+        );
 
         // Kademlia DHT — use custom protocol name matching bootstrap node
         // to avoid pollution from the public IPFS DHT
         let mut kad_config = kad::Config::new(StreamProtocol::new("/harbor/kad/1.0.0"));
         kad_config.set_query_timeout(Duration::from_secs(60));
+        kad_config
+            .set_replication_factor(
+                NonZeroUsize::new(kademlia_settings.replication_factor)
+                    .expect("replication factor is validated before the swarm is built"),
+            )
+            .set_record_ttl(Some(kademlia_settings.record_ttl))
+            .set_provider_publication_interval(Some(kademlia_settings.provider_publish_interval))
+            .set_provider_record_ttl(Some(kademlia_settings.provider_record_ttl));
         let store = kad::store::MemoryStore::new(local_peer_id);
         let kademlia = kad::Behaviour::with_config(local_peer_id, store, kad_config);
 
         // mDNS
-        let mdns = mdns::tokio::Behaviour::new(mdns::Config::default(), local_peer_id)
+        let mdns_config = mdns::Config {
+            query_interval: mdns_settings.query_interval,
+            ttl: mdns_settings.record_ttl,
+            ..Default::default()
+        };
+        let mdns = mdns::tokio::Behaviour::new(mdns_config, local_peer_id)
             .expect("Failed to create mDNS behaviour");
 
         // DCUtR for hole punching — disabled by default.
@@ -177,7 +289,10 @@ impl ChatBehaviour {
         let dcutr = Toggle::from(None::<dcutr::Behaviour>);
 
         // AutoNAT
-        let autonat = autonat::Behaviour::new(local_peer_id, autonat::Config::default());
+        let autonat = Toggle::from(
+            enable_autonat
+                .then(|| autonat::Behaviour::new(local_peer_id, autonat::Config::default())),
+        );
 
         // Identity exchange protocol
         let identity_exchange = request_response::cbor::Behaviour::new(
@@ -185,7 +300,7 @@ impl ChatBehaviour {
                 StreamProtocol::new(IDENTITY_PROTOCOL),
                 ProtocolSupport::Full,
             )],
-            request_response::Config::default(),
+            request_response::Config::default().with_request_timeout(request_timeouts.identity),
         );
 
         // Messaging protocol
@@ -194,7 +309,7 @@ impl ChatBehaviour {
                 StreamProtocol::new(MESSAGING_PROTOCOL),
                 ProtocolSupport::Full,
             )],
-            request_response::Config::default(),
+            request_response::Config::default().with_request_timeout(request_timeouts.messaging),
         );
 
         // Content sync protocol
@@ -203,7 +318,7 @@ impl ChatBehaviour {
                 StreamProtocol::new(CONTENT_SYNC_PROTOCOL),
                 ProtocolSupport::Full,
             )],
-            request_response::Config::default(),
+            request_response::Config::default().with_request_timeout(request_timeouts.content_sync),
         );
 
         // Board sync protocol
@@ -212,7 +327,7 @@ impl ChatBehaviour {
                 StreamProtocol::new(BOARD_SYNC_PROTOCOL),
                 ProtocolSupport::Full,
             )],
-            request_response::Config::default(),
+            request_response::Config::default().with_request_timeout(request_timeouts.board_sync),
         );
 
         // Media sync protocol (with larger response size for image transfers)
@@ -221,8 +336,25 @@ impl ChatBehaviour {
                 StreamProtocol::new(MEDIA_SYNC_PROTOCOL),
                 ProtocolSupport::Full,
             )],
-            request_response::Config::default()
-                .with_request_timeout(Duration::from_secs(60)),
+            request_response::Config::default().with_request_timeout(request_timeouts.media_sync),
+        );
+
+        // Presence protocol (signed heartbeats)
+        let presence = request_response::cbor::Behaviour::new(
+            [(
+                StreamProtocol::new(PRESENCE_PROTOCOL),
+                ProtocolSupport::Full,
+            )],
+            request_response::Config::default().with_request_timeout(request_timeouts.presence),
+        );
+
+        // Call data protocol (in-call text and small files)
+        let call_data = request_response::cbor::Behaviour::new(
+            [(
+                StreamProtocol::new(CALL_DATA_PROTOCOL),
+                ProtocolSupport::Full,
+            )],
+            request_response::Config::default().with_request_timeout(request_timeouts.call_data),
         );
 
         Self {
@@ -238,6 +370,8 @@ impl ChatBehaviour {
             content_sync,
             board_sync,
             media_sync,
+            presence,
+            call_data,
         }
     }
 }
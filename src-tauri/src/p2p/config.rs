@@ -1,6 +1,10 @@
 use libp2p::Multiaddr;
+use serde::{Deserialize, Serialize};
 use std::time::Duration;
 
+use super::types::{AddressFilter, NatStatus};
+use crate::error::{AppError, Result};
+
 /// Configuration for the P2P network
 #[derive(Debug, Clone)]
 pub struct NetworkConfig {
@@ -8,8 +12,12 @@ pub struct NetworkConfig {
     pub tcp_port: u16,
     /// Port to listen on for QUIC connections (0 = random)
     pub quic_port: u16,
+    /// Listen on a random port instead of failing when a fixed port is taken
+    pub port_fallback: bool,
     /// Enable mDNS for local peer discovery
     pub enable_mdns: bool,
+    /// mDNS query interval and record TTL
+    pub mdns: MdnsSettings,
     /// Enable the Kademlia DHT
     pub enable_dht: bool,
     /// Bootstrap nodes for the DHT
@@ -22,6 +30,327 @@ pub struct NetworkConfig {
     pub enable_dcutr: bool,
     /// Enable AutoNAT for external address discovery
     pub enable_autonat: bool,
+    /// Manual NAT status that takes precedence over AutoNAT results
+    pub nat_override: Option<NatStatus>,
+    /// Consecutive agreeing AutoNAT probe results needed before the NAT
+    /// status changes, so a single flaky probe doesn't toggle relay use
+    pub nat_confidence_threshold: usize,
+    /// Exchange signed presence heartbeats with connected contacts (opt-in)
+    pub enable_heartbeat: bool,
+    /// How often to send presence heartbeats
+    pub heartbeat_interval: Duration,
+    /// Ask newly connected non-contacts for their identity (opt-in). Results
+    /// are only reported to the app, never added as contacts.
+    pub auto_request_identity: bool,
+    /// How often queued automatic identity requests are sent
+    pub auto_identity_interval: Duration,
+    /// Most automatic identity requests sent per interval, so a large
+    /// discovery burst is spread out instead of sent all at once
+    pub auto_identity_batch_size: usize,
+    /// How long after storing a peer's identity repeat exchanges are skipped,
+    /// unless the peer advertises something new. Zero always exchanges.
+    pub identity_exchange_cooldown: Duration,
+    /// How often subscribed boards are synced in the background
+    pub board_sync_interval: Duration,
+    /// Upper bound for the board sync interval while relays are unreachable
+    pub board_sync_max_backoff: Duration,
+    /// How often configured relays and bootstrap nodes that dropped are
+    /// re-dialed. Zero turns reconnection off.
+    pub reconcile_interval: Duration,
+    /// Upper bound for the delay before re-dialing a node that keeps failing
+    pub reconcile_max_backoff: Duration,
+    /// Per-protocol request-response timeouts
+    pub request_timeouts: RequestTimeouts,
+    /// How long like changes are collected before one `LikesUpdated` event is emitted
+    pub likes_update_window: Duration,
+    /// Which of our addresses are announced to remote peers
+    pub address_filter: AddressFilter,
+    /// Connection count above which idle non-contact peers are evicted
+    pub max_connections: usize,
+    /// How far below `max_connections` an eviction trims to, so a single new
+    /// connection doesn't trigger another eviction straight away
+    pub connection_eviction_margin: usize,
+    /// Kademlia record storage and replication tuning
+    pub kademlia: KademliaSettings,
+    /// Circuit byte limit to assume for relays that never advertised one.
+    /// `None` treats them as unlimited.
+    pub assumed_relay_max_bytes: Option<u64>,
+    /// Capacities of the command and event channels
+    pub channels: ChannelCapacities,
+    /// Which connected peers a feed sync requests manifests from
+    pub sync_strategy: SyncStrategy,
+    /// Manifest and post fetch requests served at once, off the event loop.
+    /// Requests beyond this are answered with a "busy, retry later" error.
+    pub max_concurrent_serves: usize,
+    /// Agent version announced over Identify, `harbor/<version> (<platform>)`
+    /// by default so peers can tell Harbor builds apart when debugging
+    pub agent_version: String,
+}
+
+/// Software name at the start of a Harbor client's agent version
+pub const AGENT_NAME: &str = "harbor";
+
+/// Agent version announcing this build, e.g. `harbor/1.3.0 (macos)`
+pub fn default_agent_version() -> String {
+    format!(
+        "{}/{} ({})",
+        AGENT_NAME,
+        env!("CARGO_PKG_VERSION"),
+        std::env::consts::OS
+    )
+}
+
+/// App version and platform read from a Harbor client's agent version
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HarborAgent {
+    pub version: String,
+    pub platform: Option<String>,
+}
+
+impl HarborAgent {
+    /// Parse `harbor/<version> (<platform>)`; the platform is optional.
+    /// Returns `None` for other software, such as relays or older clients
+    /// that announce libp2p's default agent version.
+    pub fn parse(agent_version: &str) -> Option<Self> {
+        let rest = agent_version
+            .strip_prefix(AGENT_NAME)?
+            .strip_prefix('/')?
+            .trim();
+        let (version, platform) = match rest.split_once(' ') {
+            Some((version, platform)) => (
+                version,
+                platform
+                    .trim()
+                    .strip_prefix('(')
+                    .and_then(|platform| platform.strip_suffix(')')),
+            ),
+            None => (rest, None),
+        };
+        if version.is_empty() {
+            return None;
+        }
+        Some(Self {
+            version: version.to_string(),
+            platform: platform
+                .filter(|platform| !platform.is_empty())
+                .map(str::to_string),
+        })
+    }
+}
+
+/// Which connected peers a feed sync pulls from.
+///
+/// Peers hosting public content often serve overlapping posts, so asking
+/// every connection for a manifest mostly fetches the same headers again.
+/// The narrower strategies trade some freshness for bandwidth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum SyncStrategy {
+    /// Every connected peer
+    #[default]
+    All,
+    /// Only connected peers that are contacts
+    ContactsOnly,
+    /// The connected peers that have answered our sync requests most reliably
+    TopReputation { max_peers: usize },
+    /// A rotating subset of connected peers, so each is visited in turn
+    RoundRobin { max_peers: usize },
+}
+
+impl SyncStrategy {
+    /// Reject strategies that would never sync from anyone
+    pub fn validate(&self) -> Result<()> {
+        match self {
+            SyncStrategy::TopReputation { max_peers } | SyncStrategy::RoundRobin { max_peers }
+                if *max_peers == 0 =>
+            {
+                Err(AppError::Validation(
+                    "Sync strategy must allow at least one peer".to_string(),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// Capacities of the channels between the app and the network loop.
+///
+/// When the event channel is full, critical events wait for space while
+/// progress and diagnostic events are dropped and counted in the stats, so
+/// a slow consumer can't stall the swarm. A larger event channel drops less
+/// during sync bursts at the cost of memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChannelCapacities {
+    /// Commands queued from `NetworkHandle`s
+    pub commands: usize,
+    /// Events queued for the app
+    pub events: usize,
+}
+
+impl Default for ChannelCapacities {
+    fn default() -> Self {
+        Self {
+            commands: 256,
+            events: 256,
+        }
+    }
+}
+
+impl ChannelCapacities {
+    /// Reject capacities the channels can't be created with
+    pub fn validate(&self) -> Result<()> {
+        if self.commands == 0 {
+            return Err(AppError::Validation(
+                "Command channel capacity must be greater than zero".to_string(),
+            ));
+        }
+        if self.events == 0 {
+            return Err(AppError::Validation(
+                "Event channel capacity must be greater than zero".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Kademlia DHT tuning applied when the swarm is built.
+///
+/// Records and provider records live in a `MemoryStore`, so everything this
+/// node accepts stays in RAM until it expires. A higher replication factor
+/// or longer TTLs make records survive more churn, at the cost of every
+/// node holding more of them for longer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KademliaSettings {
+    /// Number of peers each record is stored on
+    pub replication_factor: usize,
+    /// How long stored records are kept before expiring
+    pub record_ttl: Duration,
+    /// How often provider records we publish are re-announced
+    pub provider_publish_interval: Duration,
+    /// How long provider records are kept before expiring
+    pub provider_record_ttl: Duration,
+}
+
+impl Default for KademliaSettings {
+    fn default() -> Self {
+        // Same values libp2p uses out of the box
+        Self {
+            replication_factor: 20,
+            record_ttl: Duration::from_secs(36 * 60 * 60),
+            provider_publish_interval: Duration::from_secs(12 * 60 * 60),
+            provider_record_ttl: Duration::from_secs(48 * 60 * 60),
+        }
+    }
+}
+
+impl KademliaSettings {
+    pub const MAX_REPLICATION_FACTOR: usize = 256;
+
+    /// Reject values that would leave the DHT unusable
+    pub fn validate(&self) -> Result<()> {
+        if !(1..=Self::MAX_REPLICATION_FACTOR).contains(&self.replication_factor) {
+            return Err(AppError::Validation(format!(
+                "Kademlia replication factor must be between 1 and {}",
+                Self::MAX_REPLICATION_FACTOR
+            )));
+        }
+        if self.record_ttl.is_zero() {
+            return Err(AppError::Validation(
+                "Kademlia record TTL must be greater than zero".to_string(),
+            ));
+        }
+        if self.provider_publish_interval.is_zero() {
+            return Err(AppError::Validation(
+                "Kademlia provider publish interval must be greater than zero".to_string(),
+            ));
+        }
+        // Otherwise provider records expire before they are re-announced
+        if self.provider_publish_interval >= self.provider_record_ttl {
+            return Err(AppError::Validation(
+                "Kademlia provider publish interval must be shorter than the provider record TTL"
+                    .to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// mDNS discovery tuning applied when the swarm is built.
+///
+/// Every query is multicast to the whole LAN, so a short interval speeds up
+/// local discovery at the cost of noise on shared networks. The TTL is what
+/// we advertise in our own records; peers expire us once it lapses without a
+/// fresh response, so it must outlast the query interval or discovered peers
+/// churn between queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MdnsSettings {
+    /// How often we query the LAN for other peers
+    pub query_interval: Duration,
+    /// How long peers may cache the records we respond with
+    pub record_ttl: Duration,
+}
+
+impl Default for MdnsSettings {
+    fn default() -> Self {
+        // Same values libp2p uses out of the box
+        Self {
+            query_interval: Duration::from_secs(5 * 60),
+            record_ttl: Duration::from_secs(6 * 60),
+        }
+    }
+}
+
+impl MdnsSettings {
+    /// Anything shorter floods the LAN with multicast queries
+    pub const MIN_QUERY_INTERVAL: Duration = Duration::from_secs(1);
+
+    /// Reject values that would flood the LAN or make peers flap
+    pub fn validate(&self) -> Result<()> {
+        if self.query_interval < Self::MIN_QUERY_INTERVAL {
+            return Err(AppError::Validation(format!(
+                "mDNS query interval must be at least {} second",
+                Self::MIN_QUERY_INTERVAL.as_secs()
+            )));
+        }
+        // Otherwise peers expire before the next query refreshes them
+        if self.record_ttl <= self.query_interval {
+            return Err(AppError::Validation(
+                "mDNS record TTL must be longer than the query interval".to_string(),
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// How long to wait for a response on each request-response protocol.
+///
+/// The defaults are tuned for relayed connections, where circuit setup and
+/// the extra hop easily exceed libp2p's 10 second default.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestTimeouts {
+    pub identity: Duration,
+    pub messaging: Duration,
+    pub content_sync: Duration,
+    pub board_sync: Duration,
+    pub media_sync: Duration,
+    /// Kept short: a late heartbeat is no better than a missing one
+    pub presence: Duration,
+    /// Kept short: call data is only useful while the call lasts
+    pub call_data: Duration,
+}
+
+impl Default for RequestTimeouts {
+    fn default() -> Self {
+        Self {
+            identity: Duration::from_secs(30),
+            messaging: Duration::from_secs(30),
+            content_sync: Duration::from_secs(45),
+            board_sync: Duration::from_secs(45),
+            media_sync: Duration::from_secs(120),
+            presence: Duration::from_secs(15),
+            call_data: Duration::from_secs(20),
+        }
+    }
 }
 
 impl Default for NetworkConfig {
@@ -29,13 +358,38 @@ impl Default for NetworkConfig {
         Self {
             tcp_port: 0,  // Random port
             quic_port: 0, // Random port
+            port_fallback: false,
             enable_mdns: true,
+            mdns: MdnsSettings::default(),
             enable_dht: true,
             bootstrap_nodes: Vec::new(),
             idle_connection_timeout: Duration::from_secs(86400), // 24 hours - chat apps stay connected
             enable_relay_client: true,
             enable_dcutr: true,
             enable_autonat: true,
+            nat_override: None,
+            nat_confidence_threshold: 3,
+            enable_heartbeat: false,
+            heartbeat_interval: Duration::from_secs(60),
+            auto_request_identity: false,
+            auto_identity_interval: Duration::from_secs(2),
+            auto_identity_batch_size: 4,
+            identity_exchange_cooldown: Duration::from_secs(60),
+            board_sync_interval: Duration::from_secs(120),
+            board_sync_max_backoff: Duration::from_secs(30 * 60),
+            reconcile_interval: Duration::from_secs(60),
+            reconcile_max_backoff: Duration::from_secs(15 * 60),
+            request_timeouts: RequestTimeouts::default(),
+            likes_update_window: Duration::from_millis(500),
+            address_filter: AddressFilter::default(),
+            max_connections: 64,
+            connection_eviction_margin: 8,
+            kademlia: KademliaSettings::default(),
+            assumed_relay_max_bytes: None,
+            channels: ChannelCapacities::default(),
+            sync_strategy: SyncStrategy::default(),
+            max_concurrent_serves: 8,
+            agent_version: default_agent_version(),
         }
     }
 }
@@ -49,6 +403,8 @@ impl NetworkConfig {
             enable_relay_client: false,
             enable_dcutr: false,
             enable_autonat: false,
+            // Private ranges are the whole point on a LAN
+            address_filter: AddressFilter::Routable,
             ..Default::default()
         }
     }
@@ -62,3 +418,118 @@ impl NetworkConfig {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_kademlia_settings_are_valid() {
+        assert!(KademliaSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_kademlia_settings_reject_nonsensical_values() {
+        let zero_replication = KademliaSettings {
+            replication_factor: 0,
+            ..Default::default()
+        };
+        assert!(zero_replication.validate().is_err());
+
+        let zero_ttl = KademliaSettings {
+            record_ttl: Duration::ZERO,
+            ..Default::default()
+        };
+        assert!(zero_ttl.validate().is_err());
+
+        let late_republish = KademliaSettings {
+            provider_publish_interval: Duration::from_secs(48 * 60 * 60),
+            ..Default::default()
+        };
+        assert!(late_republish.validate().is_err());
+    }
+
+    #[test]
+    fn test_default_mdns_settings_are_valid() {
+        assert!(MdnsSettings::default().validate().is_ok());
+    }
+
+    #[test]
+    fn test_mdns_settings_reject_flooding_and_flapping() {
+        let flooding = MdnsSettings {
+            query_interval: Duration::from_millis(100),
+            record_ttl: Duration::from_secs(60),
+        };
+        assert!(flooding.validate().is_err());
+
+        let flapping = MdnsSettings {
+            query_interval: Duration::from_secs(60),
+            record_ttl: Duration::from_secs(30),
+        };
+        assert!(flapping.validate().is_err());
+
+        let fast_local = MdnsSettings {
+            query_interval: Duration::from_secs(2),
+            record_ttl: Duration::from_secs(10),
+        };
+        assert!(fast_local.validate().is_ok());
+    }
+
+    #[test]
+    fn test_channel_capacities_must_be_positive() {
+        assert!(ChannelCapacities::default().validate().is_ok());
+
+        let no_events = ChannelCapacities {
+            events: 0,
+            ..Default::default()
+        };
+        assert!(no_events.validate().is_err());
+
+        let no_commands = ChannelCapacities {
+            commands: 0,
+            ..Default::default()
+        };
+        assert!(no_commands.validate().is_err());
+    }
+
+    #[test]
+    fn test_sync_strategy_needs_a_peer() {
+        assert!(SyncStrategy::All.validate().is_ok());
+        assert!(SyncStrategy::RoundRobin { max_peers: 3 }.validate().is_ok());
+        assert!(SyncStrategy::RoundRobin { max_peers: 0 }
+            .validate()
+            .is_err());
+        assert!(SyncStrategy::TopReputation { max_peers: 0 }
+            .validate()
+            .is_err());
+    }
+
+    #[test]
+    fn test_agent_version_round_trip() {
+        let agent = HarborAgent::parse(&default_agent_version()).unwrap();
+        assert_eq!(agent.version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(agent.platform.as_deref(), Some(std::env::consts::OS));
+
+        assert_eq!(
+            HarborAgent::parse("harbor/1.2.0"),
+            Some(HarborAgent {
+                version: "1.2.0".to_string(),
+                platform: None,
+            })
+        );
+        assert!(HarborAgent::parse("rust-libp2p/0.45.0").is_none());
+        assert!(HarborAgent::parse("harbor-relay/1.0.0").is_none());
+        assert!(HarborAgent::parse("harbor/").is_none());
+    }
+
+    #[test]
+    fn test_sync_strategy_wire_format() {
+        let strategy: SyncStrategy =
+            serde_json::from_str(r#"{"kind":"top_reputation","max_peers":5}"#).unwrap();
+        assert_eq!(strategy, SyncStrategy::TopReputation { max_peers: 5 });
+        assert_eq!(
+            serde_json::to_string(&SyncStrategy::ContactsOnly).unwrap(),
+            r#"{"kind":"contacts_only"}"#
+        );
+    }
+}
@@ -5,6 +5,9 @@ pub mod protocols;
 pub mod swarm;
 pub mod types;
 
-pub use config::NetworkConfig;
-pub use network::{NetworkHandle, NetworkService};
+pub use config::{
+    ChannelCapacities, HarborAgent, KademliaSettings, MdnsSettings, NetworkConfig, RequestTimeouts,
+    SyncStrategy,
+};
+pub use network::{MessageSend, NetworkHandle, NetworkService};
 pub use types::*;
@@ -3,14 +3,32 @@ use futures::StreamExt;
 use libp2p::{
     autonat, dcutr, identify, kad, mdns, ping, relay,
     request_response::{self, ResponseChannel},
-    swarm::SwarmEvent,
+    swarm::{dial_opts::DialOpts, ConnectionId, DialError, ListenError, ListenerId, SwarmEvent},
     Multiaddr, PeerId, Swarm,
 };
-use std::collections::HashMap;
-use std::time::Instant;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::time::{Duration, Instant};
 use tokio::sync::{mpsc, oneshot};
 use tracing::{debug, error, info, warn};
 
+/// How long `sync_with_peer` waits for the manifest and all fetches to finish
+const PEER_SYNC_TIMEOUT: Duration = Duration::from_secs(180);
+
+/// Consecutive unanswered pings after which a held relay is reported as
+/// degraded. Pings go out every 15 seconds, so this flags a silent relay well
+/// before its connection (and with it the reservation) is dropped.
+const RELAY_DEGRADED_AFTER_FAILED_PINGS: u32 = 2;
+
+/// How long shutdown keeps driving the swarm while connections close
+const SWARM_CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Error sent for content sync requests arriving while every serve slot is
+/// taken. Requesters treat it as a transient failure.
+const CONTENT_SYNC_BUSY_ERROR: &str = "busy, retry later";
+
+/// How often queued messages whose backoff ran out are sent again
+const OUTBOUND_RETRY_INTERVAL: Duration = Duration::from_secs(30);
+
 /// Public relay servers that support libp2p relay v2
 /// Only Harbor relay servers are listed here. IPFS bootstrap nodes use relay v1
 /// and RSA-based peer IDs that are incompatible with relay v2.
@@ -20,29 +38,105 @@ const PUBLIC_RELAYS: &[&str] = &[
 ];
 
 use super::behaviour::{
-    ChatBehaviour, ChatBehaviourEvent, ContentSyncRequest, ContentSyncResponse,
+    ChatBehaviour, ChatBehaviourEvent, ContactListRequest, ContentSyncRequest, ContentSyncResponse,
     IdentityExchangeRequest, IdentityExchangeResponse, MessagingRequest, MessagingResponse,
-    PostSummaryProto,
+    PermissionAckProto, PermissionGrantProto, PostHeaderProto, PostSummaryProto,
+    SharedContactProto,
 };
-use super::config::NetworkConfig;
+use super::config::{HarborAgent, NetworkConfig, SyncStrategy};
 use super::protocols::board_sync::{
     BoardSyncRequest as WireBoardSyncRequest, BoardSyncResponse as WireBoardSyncResponse,
 };
+use super::protocols::call_data::{CallDataAck, CallDataFrame, CALL_DATA_PROTOCOL};
+use super::protocols::media_sync::MEDIA_SYNC_PROTOCOL;
 use super::protocols::messaging::{MessagingCodec, MessagingMessage};
+use super::protocols::presence::Heartbeat;
+use super::protocols::{
+    BOARD_SYNC_PROTOCOL, CONTENT_SYNC_PROTOCOL, IDENTITY_PROTOCOL, MESSAGING_PROTOCOL,
+};
 use super::swarm::build_swarm;
 use super::types::*;
-use crate::db::Capability;
+use crate::db::{ActivityKind, Capability, PendingOutbound, PendingOutboundKind};
 use crate::error::{AppError, Result};
 use crate::services::board_service::StorableBoardPost;
+use crate::services::calling_service::IncomingCallDataParams;
 use crate::services::content_sync_service::RemotePostParams;
 use crate::services::messaging_service::IncomingMessageParams;
 use crate::services::{
-    BoardService, ContactsService, ContentSyncService, IdentityService, MediaStorageService,
-    MessagingService, PermissionsService, PostsService, SignableGetWallPosts,
-    SignableWallPostDelete, SignableWallPostSubmit,
+    BoardService, CallingService, ContactsService, ContentSyncService, IdentityService,
+    MediaStorageService, MessageCipher, MessagingService, PermissionAckMessage,
+    PermissionGrantMessage, PermissionsService, PostsService, SharedContact,
+    SignableContactListRequest, SignableGetWallPosts, SignableHeartbeat, SignableMessageDelete,
+    SignableMessageEdit, SignableReadReceipt, SignableWallPostDelete, SignableWallPostSubmit,
+    SIG_VERSION_CBOR,
 };
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
 
+/// Sends network events to the app.
+///
+/// `send` waits for channel space and is used for events the app must see.
+/// Progress and diagnostic events go through `send_droppable`, which drops
+/// them when the channel is full so a slow consumer can't stall the swarm.
+struct EventSender {
+    tx: mpsc::Sender<NetworkEvent>,
+    dropped: AtomicU64,
+}
+
+impl EventSender {
+    fn new(tx: mpsc::Sender<NetworkEvent>) -> Self {
+        Self {
+            tx,
+            dropped: AtomicU64::new(0),
+        }
+    }
+
+    async fn send(
+        &self,
+        event: NetworkEvent,
+    ) -> std::result::Result<(), mpsc::error::SendError<NetworkEvent>> {
+        self.tx.send(event).await
+    }
+
+    /// Send without waiting; a full channel drops the event and counts it
+    fn send_droppable(&self, event: NetworkEvent) {
+        if let Err(mpsc::error::TrySendError::Full(event)) = self.tx.try_send(event) {
+            let dropped = self.dropped.fetch_add(1, Ordering::Relaxed) + 1;
+            debug!(
+                "Event channel full, dropped {:?} ({} so far)",
+                event, dropped
+            );
+        }
+    }
+
+    /// Number of events dropped so far
+    fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+}
+
+/// A message request that has been queued on the swarm
+pub struct MessageSend {
+    /// Matches the `MessageSendResult` event emitted when the round-trip ends
+    pub correlation_id: String,
+    confirmation: oneshot::Receiver<MessageSendOutcome>,
+}
+
+impl MessageSend {
+    /// Wait until the peer responds or the request fails
+    pub async fn confirmed(self) -> Result<()> {
+        match self.confirmation.await {
+            Ok(MessageSendOutcome::Delivered) => Ok(()),
+            Ok(MessageSendOutcome::Rejected(e)) | Ok(MessageSendOutcome::Failed(e)) => {
+                Err(AppError::Network(e))
+            }
+            Err(_) => Err(AppError::NetworkServiceUnavailable(
+                "Network service stopped before the message was confirmed".into(),
+            )),
+        }
+    }
+}
+
 /// Handle to interact with the network service
 #[derive(Clone)]
 pub struct NetworkHandle {
@@ -118,6 +212,75 @@ impl NetworkHandle {
         }
     }
 
+    /// Sync content from a single peer and wait for the result.
+    ///
+    /// Resolves once the manifest response and every fetch it triggered have
+    /// completed (or failed).
+    pub async fn sync_with_peer(&self, peer_id: PeerId, limit: u32) -> Result<PeerSyncSummary> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::SyncWithPeer {
+                    peer_id,
+                    limit,
+                    reply: reply_tx,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => {}
+            Ok(NetworkResponse::Error(e)) => return Err(AppError::Network(e)),
+            _ => return Err(AppError::Internal("Unexpected response".into())),
+        }
+
+        match tokio::time::timeout(PEER_SYNC_TIMEOUT, reply_rx).await {
+            Ok(Ok(summary)) => Ok(summary),
+            Ok(Err(_)) => Err(AppError::NetworkServiceUnavailable(
+                "Network service stopped during sync".into(),
+            )),
+            Err(_) => Err(AppError::NetworkTimeout(format!(
+                "Sync with {} timed out",
+                peer_id
+            ))),
+        }
+    }
+
+    /// Report that like state changed for some posts.
+    ///
+    /// The network service coalesces these into a single `LikesUpdated` event.
+    pub async fn notify_likes_changed(&self, post_ids: Vec<String>) -> Result<()> {
+        self.command_tx
+            .send((NetworkCommand::NotifyLikesChanged { post_ids }, None))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+        Ok(())
+    }
+
+    /// Send a signed frame over a call's data channel
+    pub async fn send_call_data(&self, peer_id: PeerId, frame: CallDataFrame) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::SendCallData { peer_id, frame }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
     /// Shutdown the network service
     pub async fn shutdown(&self) -> Result<()> {
         self.command_tx
@@ -129,13 +292,19 @@ impl NetworkHandle {
         Ok(())
     }
 
-    /// Send a message to a peer
+    /// Send a message to a peer.
+    ///
+    /// Returns once the request is queued. Await [`MessageSend::confirmed`]
+    /// (or watch for the matching `MessageSendResult` event) to learn whether
+    /// the peer actually received it.
     pub async fn send_message(
         &self,
         peer_id: PeerId,
         protocol: String,
         payload: Vec<u8>,
-    ) -> Result<()> {
+    ) -> Result<MessageSend> {
+        let correlation_id = uuid::Uuid::new_v4().to_string();
+        let (confirmation_tx, confirmation_rx) = oneshot::channel();
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
@@ -143,6 +312,8 @@ impl NetworkHandle {
                     peer_id,
                     protocol,
                     payload,
+                    correlation_id: correlation_id.clone(),
+                    confirmation: confirmation_tx,
                 },
                 Some(tx),
             ))
@@ -152,7 +323,10 @@ impl NetworkHandle {
             })?;
 
         match rx.await {
-            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Ok) => Ok(MessageSend {
+                correlation_id,
+                confirmation: confirmation_rx,
+            }),
             Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
             _ => Err(AppError::Internal("Unexpected response".into())),
         }
@@ -175,6 +349,125 @@ impl NetworkHandle {
         }
     }
 
+    /// Request a peer's identity and wait for it, without adding the peer as
+    /// a contact. The identity is verified the same way as for contacts.
+    pub async fn fetch_identity(&self, peer_id: PeerId) -> Result<TransientIdentity> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::FetchIdentity {
+                    peer_id,
+                    reply: reply_tx,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => {}
+            Ok(NetworkResponse::Error(e)) => return Err(AppError::Network(e)),
+            _ => return Err(AppError::Internal("Unexpected response".into())),
+        }
+
+        match reply_rx.await {
+            Ok(Ok(identity)) => Ok(identity),
+            Ok(Err(e)) => Err(AppError::Network(e)),
+            Err(_) => Err(AppError::NetworkServiceUnavailable(
+                "Network service stopped before the identity arrived".into(),
+            )),
+        }
+    }
+
+    /// Ask a contact for the contacts it shares with us. Fails if the peer
+    /// has sharing turned off or hasn't granted us `ShareContacts`.
+    pub async fn fetch_shared_contacts(&self, peer_id: PeerId) -> Result<Vec<SharedContact>> {
+        let (reply_tx, reply_rx) = oneshot::channel();
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::FetchSharedContacts {
+                    peer_id,
+                    reply: reply_tx,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => {}
+            Ok(NetworkResponse::Error(e)) => return Err(AppError::Network(e)),
+            _ => return Err(AppError::Internal("Unexpected response".into())),
+        }
+
+        match reply_rx.await {
+            Ok(Ok(contacts)) => Ok(contacts),
+            Ok(Err(e)) => Err(AppError::Network(e)),
+            Err(_) => Err(AppError::NetworkServiceUnavailable(
+                "Network service stopped before the contact list arrived".into(),
+            )),
+        }
+    }
+
+    /// Resend any grants `peer_id` hasn't acknowledged, if it is a connected
+    /// contact. This also happens on its own whenever a contact connects.
+    pub async fn reconcile_permissions(&self, peer_id: PeerId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::ReconcilePermissions { peer_id }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Send every message queued for `peer_id` now, without waiting out the
+    /// backoff. This also happens on its own whenever the peer connects.
+    pub async fn flush_pending_messages(&self, peer_id: PeerId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::FlushPendingMessages { peer_id }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Close all connections to a peer
+    pub async fn disconnect(&self, peer_id: PeerId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::Disconnect { peer_id }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
     /// Get listening addresses (with peer ID appended)
     pub async fn get_listening_addresses(&self) -> Result<Vec<String>> {
         let (tx, rx) = oneshot::channel();
@@ -192,6 +485,43 @@ impl NetworkHandle {
         }
     }
 
+    /// Get the ports our TCP and QUIC listeners actually bound to
+    pub async fn get_bound_ports(&self) -> Result<BoundPorts> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::GetBoundPorts, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::BoundPorts(ports)) => Ok(ports),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Get the peers in the Kademlia routing table, grouped by k-bucket
+    pub async fn get_dht_routing_table(&self, include_addresses: bool) -> Result<DhtRoutingTable> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::GetDhtRoutingTable { include_addresses },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::DhtRoutingTable(table)) => Ok(table),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
     /// Add a bootstrap node and dial it
     pub async fn add_bootstrap_node(&self, address: Multiaddr) -> Result<()> {
         let (tx, rx) = oneshot::channel();
@@ -232,6 +562,7 @@ impl NetworkHandle {
         peer_id: PeerId,
         cursor: std::collections::HashMap<String, u64>,
         limit: u32,
+        detail_level: crate::services::ManifestDetailLevel,
     ) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
@@ -240,6 +571,7 @@ impl NetworkHandle {
                     peer_id,
                     cursor,
                     limit,
+                    detail_level,
                 },
                 Some(tx),
             ))
@@ -271,6 +603,24 @@ impl NetworkHandle {
                 AppError::NetworkServiceUnavailable("Network service unavailable".into())
             })?;
 
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            Ok(NetworkResponse::NotCommunityRelay(e)) => Err(AppError::NotCommunityRelay(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Leave a community: deregister from the relay and drop it locally
+    pub async fn leave_community(&self, relay_peer_id: PeerId) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::LeaveCommunity { relay_peer_id }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
         match rx.await {
             Ok(NetworkResponse::Ok) => Ok(()),
             Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
@@ -351,18 +701,25 @@ impl NetworkHandle {
         match rx.await {
             Ok(NetworkResponse::Ok) => Ok(()),
             Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            Ok(NetworkResponse::NotCommunityRelay(e)) => Err(AppError::NotCommunityRelay(e)),
             _ => Err(AppError::Internal("Unexpected response".into())),
         }
     }
 
-    /// Delete a board post on a relay
-    pub async fn delete_board_post(&self, relay_peer_id: PeerId, post_id: String) -> Result<()> {
+    /// Ask a relay to create a new board
+    pub async fn create_board(
+        &self,
+        relay_peer_id: PeerId,
+        name: String,
+        description: Option<String>,
+    ) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
-                NetworkCommand::DeleteBoardPost {
+                NetworkCommand::CreateBoard {
                     relay_peer_id,
-                    post_id,
+                    name,
+                    description,
                 },
                 Some(tx),
             ))
@@ -378,33 +735,21 @@ impl NetworkHandle {
         }
     }
 
-    /// Submit a wall post to a relay for offline availability
-    #[allow(clippy::too_many_arguments)]
-    pub async fn submit_wall_post_to_relay(
+    /// Ask a relay for a signed invite to its community. The invite arrives
+    /// as a `CommunityInviteCreated` event.
+    pub async fn create_community_invite(
         &self,
         relay_peer_id: PeerId,
-        post_id: String,
-        content_type: String,
-        content_text: Option<String>,
-        visibility: String,
-        lamport_clock: i64,
-        created_at: i64,
-        signature: Vec<u8>,
-        media_items: Vec<super::protocols::board_sync::WallPostMediaItem>,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
     ) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
-                NetworkCommand::SubmitWallPostToRelay {
+                NetworkCommand::CreateCommunityInvite {
                     relay_peer_id,
-                    post_id,
-                    content_type,
-                    content_text,
-                    visibility,
-                    lamport_clock,
-                    created_at,
-                    signature,
-                    media_items,
+                    expires_at,
+                    max_uses,
                 },
                 Some(tx),
             ))
@@ -420,14 +765,14 @@ impl NetworkHandle {
         }
     }
 
-    /// Fetch media by hash from a specific peer
-    pub async fn fetch_media(&self, peer_id: PeerId, media_hash: String) -> Result<()> {
+    /// Delete a board post on a relay
+    pub async fn delete_board_post(&self, relay_peer_id: PeerId, post_id: String) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
-                NetworkCommand::FetchMedia {
-                    peer_id,
-                    media_hash,
+                NetworkCommand::DeleteBoardPost {
+                    relay_peer_id,
+                    post_id,
                 },
                 Some(tx),
             ))
@@ -443,22 +788,22 @@ impl NetworkHandle {
         }
     }
 
-    /// Get wall posts for a specific author from a relay
-    pub async fn get_wall_posts_from_relay(
+    /// React to a board post on a relay, or withdraw our reaction
+    pub async fn react_to_board_post(
         &self,
         relay_peer_id: PeerId,
-        author_peer_id: String,
-        since_lamport_clock: i64,
-        limit: u32,
+        post_id: String,
+        reaction_type: String,
+        remove: bool,
     ) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
-                NetworkCommand::GetWallPostsFromRelay {
+                NetworkCommand::ReactToBoardPost {
                     relay_peer_id,
-                    author_peer_id,
-                    since_lamport_clock,
-                    limit,
+                    post_id,
+                    reaction_type,
+                    remove,
                 },
                 Some(tx),
             ))
@@ -474,20 +819,116 @@ impl NetworkHandle {
         }
     }
 
-    /// Delete a wall post on a relay
-    pub async fn delete_wall_post_on_relay(
+    /// Submit a wall post to a relay for offline availability
+    #[allow(clippy::too_many_arguments)]
+    pub async fn submit_wall_post_to_relay(
         &self,
         relay_peer_id: PeerId,
         post_id: String,
+        content_type: String,
+        content_text: Option<String>,
+        visibility: String,
+        lamport_clock: i64,
+        created_at: i64,
+        signature: Vec<u8>,
+        media_items: Vec<super::protocols::board_sync::WallPostMediaItem>,
     ) -> Result<()> {
         let (tx, rx) = oneshot::channel();
         self.command_tx
             .send((
-                NetworkCommand::DeleteWallPostOnRelay {
+                NetworkCommand::SubmitWallPostToRelay {
                     relay_peer_id,
                     post_id,
-                },
-                Some(tx),
+                    content_type,
+                    content_text,
+                    visibility,
+                    lamport_clock,
+                    created_at,
+                    signature,
+                    media_items,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Fetch media by hash from a specific peer
+    pub async fn fetch_media(&self, peer_id: PeerId, media_hash: String) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::FetchMedia {
+                    peer_id,
+                    media_hash,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Get wall posts for a specific author from a relay
+    pub async fn get_wall_posts_from_relay(
+        &self,
+        relay_peer_id: PeerId,
+        author_peer_id: String,
+        since_lamport_clock: i64,
+        limit: u32,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::GetWallPostsFromRelay {
+                    relay_peer_id,
+                    author_peer_id,
+                    since_lamport_clock,
+                    limit,
+                },
+                Some(tx),
+            ))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Delete a wall post on a relay
+    pub async fn delete_wall_post_on_relay(
+        &self,
+        relay_peer_id: PeerId,
+        post_id: String,
+    ) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((
+                NetworkCommand::DeleteWallPostOnRelay {
+                    relay_peer_id,
+                    post_id,
+                },
+                Some(tx),
             ))
             .await
             .map_err(|_| {
@@ -501,6 +942,41 @@ impl NetworkHandle {
         }
     }
 
+    /// Force the NAT status, or pass `None` to return to AutoNAT detection
+    pub async fn set_nat_override(&self, status: Option<NatStatus>) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::SetNatOverride { status }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
+    /// Re-dial dropped relays and bootstrap nodes now, e.g. after the OS
+    /// reports connectivity is back
+    pub async fn reconcile_connections(&self) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::ReconcileConnections, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
     /// Connect to public relay servers for NAT traversal
     pub async fn connect_to_public_relays(&self) -> Result<()> {
         let (tx, rx) = oneshot::channel();
@@ -547,6 +1023,24 @@ impl NetworkHandle {
         }
     }
 
+    /// Push a new post to the contacts that are online right now. Contacts
+    /// who are offline pick it up through the regular pull sync.
+    pub async fn announce_post(&self, post_id: String) -> Result<()> {
+        let (tx, rx) = oneshot::channel();
+        self.command_tx
+            .send((NetworkCommand::AnnouncePost { post_id }, Some(tx)))
+            .await
+            .map_err(|_| {
+                AppError::NetworkServiceUnavailable("Network service unavailable".into())
+            })?;
+
+        match rx.await {
+            Ok(NetworkResponse::Ok) => Ok(()),
+            Ok(NetworkResponse::Error(e)) => Err(AppError::Network(e)),
+            _ => Err(AppError::Internal("Unexpected response".into())),
+        }
+    }
+
     /// Trigger feed content sync from connected peers
     pub async fn sync_feed(&self, limit: u32) -> Result<()> {
         let (tx, rx) = oneshot::channel();
@@ -567,6 +1061,350 @@ impl NetworkHandle {
 
 use super::types::NatStatus;
 
+/// An in-flight `sync_with_peer` waiting on its content sync requests
+struct PeerSync {
+    summary: PeerSyncSummary,
+    /// Manifest and fetch requests that have not completed yet
+    outstanding: HashSet<request_response::OutboundRequestId>,
+    reply: oneshot::Sender<PeerSyncSummary>,
+}
+
+/// One address being tried by an outgoing dial, reported to the UI as a
+/// separate attempt so concurrent addresses of a peer can be told apart
+struct DialAttempt {
+    attempt_id: u64,
+    peer_id: PeerId,
+    /// `None` when the dial relies on addresses the behaviours know about
+    address: Option<Multiaddr>,
+}
+
+/// Outgoing dials that haven't connected or failed yet. At most one dial per
+/// peer is in flight, so relay, bootstrap and user dials don't race each other.
+#[derive(Default)]
+struct PendingDials {
+    attempts: HashMap<ConnectionId, Vec<DialAttempt>>,
+    /// The connection each peer is being dialed on
+    peers: HashMap<PeerId, ConnectionId>,
+}
+
+impl PendingDials {
+    fn insert(&mut self, connection_id: ConnectionId, peer_id: PeerId, attempts: Vec<DialAttempt>) {
+        self.peers.insert(peer_id, connection_id);
+        self.attempts.insert(connection_id, attempts);
+    }
+
+    fn is_dialing(&self, peer_id: &PeerId) -> bool {
+        self.peers.contains_key(peer_id)
+    }
+
+    /// Stop tracking a dial once it connected or finally failed, returning
+    /// its attempts
+    fn finish(&mut self, connection_id: ConnectionId) -> Vec<DialAttempt> {
+        self.peers.retain(|_, dialing| *dialing != connection_id);
+        self.attempts.remove(&connection_id).unwrap_or_default()
+    }
+}
+
+/// A relay or bootstrap node we stay connected to. Connection
+/// reconciliation re-dials it whenever it's found disconnected.
+struct DialTarget {
+    address: Multiaddr,
+    is_relay: bool,
+    /// Reconciliation passes in a row that found it disconnected
+    misses: u32,
+    /// When it may be dialed again
+    next_dial: Instant,
+}
+
+/// What `start_dial` did about a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DialOutcome {
+    Started,
+    /// An earlier dial is still in flight and will report the result
+    AlreadyDialing,
+    AlreadyConnected,
+}
+
+/// Peers waiting for an automatic identity request, handed out a few at a
+/// time so a burst of discoveries doesn't turn into a burst of requests
+#[derive(Default)]
+struct AutoIdentityQueue {
+    queue: VecDeque<PeerId>,
+    /// Every peer queued this session, so reconnects don't ask again
+    seen: HashSet<PeerId>,
+}
+
+impl AutoIdentityQueue {
+    /// Queue a peer unless it was already queued this session
+    fn push(&mut self, peer_id: PeerId) -> bool {
+        if !self.seen.insert(peer_id) {
+            return false;
+        }
+        self.queue.push_back(peer_id);
+        true
+    }
+
+    /// Drop a peer that disconnected before its turn, so it is queued again
+    /// the next time it connects
+    fn cancel(&mut self, peer_id: &PeerId) {
+        if let Some(position) = self.queue.iter().position(|queued| queued == peer_id) {
+            self.queue.remove(position);
+            self.seen.remove(peer_id);
+        }
+    }
+
+    /// Take the longest-waiting peer
+    fn pop(&mut self) -> Option<PeerId> {
+        self.queue.pop_front()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.queue.is_empty()
+    }
+}
+
+/// The fields a peer advertises in an identity response, compared to tell a
+/// profile update apart from a repeat of the same identity
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AdvertisedIdentity {
+    public_key: Vec<u8>,
+    x25519_public: Vec<u8>,
+    display_name: String,
+    avatar_hash: Option<String>,
+    bio: Option<String>,
+    supported_ciphers: Option<Vec<String>>,
+}
+
+impl From<&IdentityExchangeResponse> for AdvertisedIdentity {
+    fn from(response: &IdentityExchangeResponse) -> Self {
+        Self {
+            public_key: response.public_key.clone(),
+            x25519_public: response.x25519_public.clone(),
+            display_name: response.display_name.clone(),
+            avatar_hash: response.avatar_hash.clone(),
+            bio: response.bio.clone(),
+            supported_ciphers: response.supported_ciphers.clone(),
+        }
+    }
+}
+
+/// When each peer's identity was last stored, so a peer that keeps
+/// reconnecting doesn't rewrite its contact and re-announce it every time.
+/// A zero cooldown turns the cache off.
+struct IdentityExchangeCache {
+    cooldown: Duration,
+    exchanges: HashMap<PeerId, (Instant, AdvertisedIdentity)>,
+}
+
+impl IdentityExchangeCache {
+    fn new(cooldown: Duration) -> Self {
+        Self {
+            cooldown,
+            exchanges: HashMap::new(),
+        }
+    }
+
+    /// Whether the peer's identity was stored recently enough that asking
+    /// again would be redundant
+    fn is_cooling_down(&self, peer_id: &PeerId, now: Instant) -> bool {
+        self.exchanges
+            .get(peer_id)
+            .is_some_and(|(at, _)| now.duration_since(*at) < self.cooldown)
+    }
+
+    /// Record an identity about to be stored. Returns false when the same
+    /// identity was already stored within the cooldown and can be skipped.
+    fn record(&mut self, peer_id: PeerId, identity: AdvertisedIdentity, now: Instant) -> bool {
+        if self.cooldown.is_zero() {
+            return true;
+        }
+        if self.is_cooling_down(&peer_id, now)
+            && self
+                .exchanges
+                .get(&peer_id)
+                .is_some_and(|(_, stored)| *stored == identity)
+        {
+            return false;
+        }
+        let cooldown = self.cooldown;
+        self.exchanges
+            .retain(|_, (at, _)| now.duration_since(*at) < cooldown);
+        self.exchanges.insert(peer_id, (now, identity));
+        true
+    }
+}
+
+/// Recent AutoNAT probe outcomes. A status is only reported once enough
+/// consecutive probes agree on it.
+struct NatProbeTracker {
+    threshold: usize,
+    candidate: Option<NatStatus>,
+    streak: usize,
+}
+
+impl NatProbeTracker {
+    fn new(threshold: usize) -> Self {
+        Self {
+            threshold,
+            candidate: None,
+            streak: 0,
+        }
+    }
+
+    /// Record a probe outcome, returning the status once the threshold of
+    /// consecutive agreeing outcomes is met
+    fn record(&mut self, status: NatStatus) -> Option<NatStatus> {
+        if self.candidate == Some(status) {
+            self.streak = self.streak.saturating_add(1);
+        } else {
+            self.candidate = Some(status);
+            self.streak = 1;
+        }
+        (self.streak >= self.threshold).then_some(status)
+    }
+}
+
+/// The open connections to each peer and how each was made, so `PeerInfo`
+/// can tell a direct peer from a relayed one
+#[derive(Default)]
+struct PeerConnections {
+    /// Connection type and, for relayed connections, the relay
+    connections: HashMap<PeerId, HashMap<ConnectionId, (ConnectionType, Option<PeerId>)>>,
+}
+
+impl PeerConnections {
+    /// Record a new connection. One hole punching already reported stays
+    /// hole punched.
+    fn established(
+        &mut self,
+        peer_id: PeerId,
+        connection_id: ConnectionId,
+        remote_address: &Multiaddr,
+    ) {
+        let is_circuit = remote_address
+            .iter()
+            .any(|p| p == libp2p::multiaddr::Protocol::P2pCircuit);
+        let kind = if is_circuit {
+            (ConnectionType::Relayed, circuit_relay_peer(remote_address))
+        } else {
+            (ConnectionType::Direct, None)
+        };
+        self.connections
+            .entry(peer_id)
+            .or_default()
+            .entry(connection_id)
+            .or_insert(kind);
+    }
+
+    /// Mark the direct connection hole punching set up with a peer
+    fn hole_punched(&mut self, peer_id: PeerId, connection_id: ConnectionId) {
+        self.connections
+            .entry(peer_id)
+            .or_default()
+            .insert(connection_id, (ConnectionType::Holepunched, None));
+    }
+
+    fn closed(&mut self, peer_id: &PeerId, connection_id: ConnectionId) {
+        if let Some(connections) = self.connections.get_mut(peer_id) {
+            connections.remove(&connection_id);
+            if connections.is_empty() {
+                self.connections.remove(peer_id);
+            }
+        }
+    }
+
+    /// The best open connection to a peer: hole punched or direct before
+    /// relayed
+    fn connection_type(&self, peer_id: &PeerId) -> Option<(ConnectionType, Option<PeerId>)> {
+        self.connections
+            .get(peer_id)?
+            .values()
+            .min_by_key(|(connection_type, _)| match connection_type {
+                ConnectionType::Holepunched => 0,
+                ConnectionType::Direct => 1,
+                ConnectionType::Relayed => 2,
+            })
+            .copied()
+    }
+}
+
+/// How reliably a peer has answered our content sync requests this session
+#[derive(Debug, Clone, Copy, Default)]
+struct PeerSyncScore {
+    successes: u32,
+    failures: u32,
+}
+
+impl PeerSyncScore {
+    /// Failures weigh double: each one cost a full request timeout
+    fn reputation(&self) -> i64 {
+        i64::from(self.successes) - 2 * i64::from(self.failures)
+    }
+}
+
+/// A queued message request waiting for the peer's response
+struct PendingMessageSend {
+    correlation_id: String,
+    confirmation: oneshot::Sender<MessageSendOutcome>,
+}
+
+/// What a content sync response produced, used to complete a `sync_with_peer`
+enum ContentSyncOutcome {
+    /// A manifest was processed and these fetch requests were sent
+    Manifest {
+        fetch_requests: Vec<request_response::OutboundRequestId>,
+        has_more: bool,
+    },
+    /// A fetched post was stored
+    PostStored,
+    /// The author acknowledged our view report
+    ViewReported,
+    /// A contact acknowledged our post announcement
+    AnnouncementDelivered,
+    Failed(String),
+}
+
+/// A transport we listen on, with the address to bind on all interfaces
+#[derive(Debug, Clone, Copy)]
+enum ListenTransport {
+    Tcp,
+    Quic,
+}
+
+impl ListenTransport {
+    fn listen_addr(self, port: u16) -> Multiaddr {
+        let ip = libp2p::multiaddr::Protocol::Ip4(std::net::Ipv4Addr::UNSPECIFIED);
+        match self {
+            ListenTransport::Tcp => Multiaddr::empty()
+                .with(ip)
+                .with(libp2p::multiaddr::Protocol::Tcp(port)),
+            ListenTransport::Quic => Multiaddr::empty()
+                .with(ip)
+                .with(libp2p::multiaddr::Protocol::Udp(port))
+                .with(libp2p::multiaddr::Protocol::QuicV1),
+        }
+    }
+
+    /// Whether another socket already holds `port` for this transport
+    fn port_in_use(self, port: u16) -> bool {
+        let addr = (std::net::Ipv4Addr::UNSPECIFIED, port);
+        let bound = match self {
+            ListenTransport::Tcp => std::net::TcpListener::bind(addr).map(|_| ()),
+            ListenTransport::Quic => std::net::UdpSocket::bind(addr).map(|_| ()),
+        };
+        matches!(bound, Err(e) if e.kind() == std::io::ErrorKind::AddrInUse)
+    }
+}
+
+impl std::fmt::Display for ListenTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ListenTransport::Tcp => write!(f, "TCP"),
+            ListenTransport::Quic => write!(f, "QUIC"),
+        }
+    }
+}
+
 /// The network service manages the libp2p swarm
 pub struct NetworkService {
     swarm: Swarm<ChatBehaviour>,
@@ -579,15 +1417,26 @@ pub struct NetworkService {
     content_sync_service: Option<Arc<ContentSyncService>>,
     board_service: Option<Arc<BoardService>>,
     media_service: Option<Arc<MediaStorageService>>,
+    calling_service: Option<Arc<CallingService>>,
     command_rx: mpsc::Receiver<(NetworkCommand, Option<oneshot::Sender<NetworkResponse>>)>,
-    event_tx: mpsc::Sender<NetworkEvent>,
+    event_tx: EventSender,
     connected_peers: HashMap<PeerId, PeerInfo>,
     discovered_peers: HashMap<PeerId, Vec<Multiaddr>>,
     listening_addresses: Vec<Multiaddr>,
+    /// Listener started by `start_listening` for each transport
+    tcp_listener: Option<ListenerId>,
+    quic_listener: Option<ListenerId>,
+    bound_ports: BoundPorts,
     stats: NetworkStats,
     start_time: Instant,
-    /// Current NAT status
+    /// Current effective NAT status (manual override if set, else detected)
     nat_status: NatStatus,
+    /// NAT status as detected by AutoNAT and relay reservations
+    detected_nat_status: NatStatus,
+    /// Manual NAT status override that takes precedence over detection
+    nat_override: Option<NatStatus>,
+    /// Consecutive AutoNAT probe results, gating changes to `detected_nat_status`
+    nat_probes: NatProbeTracker,
     /// Relay addresses we're reachable at
     relay_addresses: Vec<Multiaddr>,
     /// External addresses discovered via AutoNAT
@@ -605,11 +1454,86 @@ pub struct NetworkService {
     pending_community_probes: HashMap<PeerId, String>,
     /// Relay peers that have been confirmed as community relays.
     community_relays: HashMap<PeerId, String>,
+    /// Community relays we left this session, so reservations on them don't
+    /// trigger another auto-join probe
+    left_community_relays: HashSet<PeerId>,
+    /// Circuit limits advertised by each relay we've used
+    relay_limits: HashMap<PeerId, RelayCircuitLimits>,
+    /// Ping liveness of each relay we hold a reservation on
+    relay_liveness: HashMap<PeerId, RelayLiveness>,
+    /// Relay peers whose community probe failed, i.e. they don't host boards.
+    /// Cleared again if a later probe confirms the relay as a community relay.
+    non_community_relays: HashSet<PeerId>,
     /// Relay peers where we've sent RegisterPeer and are waiting for PeerRegistered
     /// before sending ListBoards. This prevents the race condition where ListBoards
     /// arrives at the relay before RegisterPeer has been processed (which would fail
     /// signature verification since the peer's public key hasn't been stored yet).
     pending_board_registrations: std::collections::HashSet<PeerId>,
+    /// Delay before the next background sync of subscribed boards. Grows while
+    /// none of the subscribed relays are connected.
+    board_sync_backoff: Duration,
+    /// In-flight `sync_with_peer` calls, at most one per peer
+    peer_syncs: HashMap<PeerId, PeerSync>,
+    /// Content sync requests belonging to a `sync_with_peer`, by request ID
+    peer_sync_requests: HashMap<request_response::OutboundRequestId, PeerId>,
+    /// Content sync track record per peer, for `SyncStrategy::TopReputation`
+    peer_sync_scores: HashMap<PeerId, PeerSyncScore>,
+    /// Where the next `SyncStrategy::RoundRobin` feed sync starts
+    sync_round_robin_offset: usize,
+    /// Post announcements awaiting acknowledgement, by request ID
+    pending_post_deliveries: HashMap<request_response::OutboundRequestId, (String, PeerId)>,
+    /// Outgoing dials that haven't connected or failed yet
+    pending_dials: PendingDials,
+    /// Relays and bootstrap nodes kept connected by reconciliation, by peer
+    dial_targets: HashMap<PeerId, DialTarget>,
+    /// Source of `DialStarted` attempt IDs
+    next_dial_attempt_id: u64,
+    /// Posts whose like state changed since the last `LikesUpdated` event
+    pending_like_updates: BTreeSet<String>,
+    /// When the pending like updates are flushed (set by the first change)
+    likes_flush_at: Option<tokio::time::Instant>,
+    /// When each connected peer last connected or exchanged a request with us
+    peer_activity: HashMap<PeerId, Instant>,
+    /// Outgoing message requests awaiting a response, by request ID
+    pending_message_sends: HashMap<request_response::OutboundRequestId, PendingMessageSend>,
+    /// Queued payloads in flight, queue key and kind by request ID
+    pending_outbound_sends:
+        HashMap<request_response::OutboundRequestId, (String, PendingOutboundKind)>,
+    /// Identity requests whose result goes back to the caller instead of
+    /// into the contacts table, by request ID
+    pending_identity_fetches: HashMap<
+        request_response::OutboundRequestId,
+        oneshot::Sender<std::result::Result<TransientIdentity, String>>,
+    >,
+    /// Contact list requests awaiting a response, by request ID
+    pending_contact_list_fetches: HashMap<
+        request_response::OutboundRequestId,
+        oneshot::Sender<std::result::Result<Vec<SharedContact>, String>>,
+    >,
+    /// Newly connected non-contacts waiting for an automatic identity request
+    auto_identity_queue: AutoIdentityQueue,
+    /// Automatic identity requests in flight, by request ID
+    pending_auto_identity: HashMap<request_response::OutboundRequestId, PeerId>,
+    /// Identity requests resending unacknowledged grants, by request ID
+    pending_permission_reconciles: HashMap<request_response::OutboundRequestId, PeerId>,
+    /// Identities stored recently, to skip repeats on flaky connections
+    identity_exchanges: IdentityExchangeCache,
+    /// Where another device holding our identity key was seen, so each
+    /// origin is reported once
+    identity_conflicts: HashSet<String>,
+    /// How each open connection was made, for `PeerInfo::connection_type`
+    peer_connections: PeerConnections,
+    /// Manifest and fetch requests being served on the blocking pool
+    content_serves_in_flight: usize,
+    /// Finished serves, handed back to the event loop to answer the requester
+    served_tx: mpsc::Sender<ServedContentRequest>,
+    served_rx: mpsc::Receiver<ServedContentRequest>,
+}
+
+/// A content sync request served off the event loop, ready to be answered
+struct ServedContentRequest {
+    channel: ResponseChannel<ContentSyncResponse>,
+    response: ContentSyncResponse,
 }
 
 impl NetworkService {
@@ -619,12 +1543,47 @@ impl NetworkService {
         identity_service: Arc<IdentityService>,
         keypair: libp2p::identity::Keypair,
     ) -> Result<(Self, NetworkHandle, mpsc::Receiver<NetworkEvent>)> {
+        config.channels.validate()?;
+        config.sync_strategy.validate()?;
+        if config.nat_confidence_threshold == 0 {
+            return Err(AppError::Validation(
+                "NAT confidence threshold must be greater than zero".to_string(),
+            ));
+        }
+        if config.max_concurrent_serves == 0 {
+            return Err(AppError::Validation(
+                "Concurrent content serves must be greater than zero".to_string(),
+            ));
+        }
         let swarm = build_swarm(keypair, &config)?;
 
-        let (command_tx, command_rx) = mpsc::channel(256);
-        let (event_tx, event_rx) = mpsc::channel(256);
+        let (command_tx, command_rx) = mpsc::channel(config.channels.commands);
+        let (event_tx, event_rx) = mpsc::channel(config.channels.events);
+        // Every in-flight serve sends exactly one result, so this never fills
+        let (served_tx, served_rx) = mpsc::channel(config.max_concurrent_serves);
 
         let handle = NetworkHandle { command_tx };
+        let board_sync_backoff = config.board_sync_interval;
+        let nat_override = config.nat_override;
+        let nat_probes = NatProbeTracker::new(config.nat_confidence_threshold);
+        let identity_exchanges = IdentityExchangeCache::new(config.identity_exchange_cooldown);
+        let dial_targets = config
+            .bootstrap_nodes
+            .iter()
+            .filter_map(|address| {
+                let peer_id = address.iter().find_map(|proto| match proto {
+                    libp2p::multiaddr::Protocol::P2p(peer_id) => Some(peer_id),
+                    _ => None,
+                })?;
+                let target = DialTarget {
+                    address: address.clone(),
+                    is_relay: false,
+                    misses: 0,
+                    next_dial: Instant::now(),
+                };
+                Some((peer_id, target))
+            })
+            .collect();
 
         let service = Self {
             swarm,
@@ -637,21 +1596,57 @@ impl NetworkService {
             content_sync_service: None,
             board_service: None,
             media_service: None,
+            calling_service: None,
             command_rx,
-            event_tx,
+            event_tx: EventSender::new(event_tx),
             connected_peers: HashMap::new(),
             discovered_peers: HashMap::new(),
             listening_addresses: Vec::new(),
+            tcp_listener: None,
+            quic_listener: None,
+            bound_ports: BoundPorts::default(),
             stats: NetworkStats::default(),
             start_time: Instant::now(),
-            nat_status: NatStatus::Unknown,
+            nat_status: nat_override.unwrap_or_default(),
+            detected_nat_status: NatStatus::Unknown,
+            nat_override,
+            nat_probes,
             relay_addresses: Vec::new(),
             external_addresses: Vec::new(),
             relay_connection_attempted: false,
             pending_relay_reservations: HashMap::new(),
             pending_community_probes: HashMap::new(),
             community_relays: HashMap::new(),
+            left_community_relays: HashSet::new(),
+            relay_limits: HashMap::new(),
+            relay_liveness: HashMap::new(),
+            non_community_relays: HashSet::new(),
             pending_board_registrations: std::collections::HashSet::new(),
+            board_sync_backoff,
+            peer_syncs: HashMap::new(),
+            peer_sync_requests: HashMap::new(),
+            peer_sync_scores: HashMap::new(),
+            sync_round_robin_offset: 0,
+            pending_post_deliveries: HashMap::new(),
+            pending_dials: PendingDials::default(),
+            dial_targets,
+            next_dial_attempt_id: 0,
+            pending_like_updates: BTreeSet::new(),
+            likes_flush_at: None,
+            peer_activity: HashMap::new(),
+            pending_message_sends: HashMap::new(),
+            pending_outbound_sends: HashMap::new(),
+            pending_identity_fetches: HashMap::new(),
+            pending_contact_list_fetches: HashMap::new(),
+            auto_identity_queue: AutoIdentityQueue::default(),
+            pending_auto_identity: HashMap::new(),
+            pending_permission_reconciles: HashMap::new(),
+            identity_exchanges,
+            identity_conflicts: HashSet::new(),
+            peer_connections: PeerConnections::default(),
+            content_serves_in_flight: 0,
+            served_tx,
+            served_rx,
         };
 
         Ok((service, handle, event_rx))
@@ -692,6 +1687,11 @@ impl NetworkService {
         self.media_service = Some(service);
     }
 
+    /// Set calling service for validating in-call data
+    pub fn set_calling_service(&mut self, service: Arc<CallingService>) {
+        self.calling_service = Some(service);
+    }
+
     /// Get the local peer ID
     pub fn local_peer_id(&self) -> &PeerId {
         self.swarm.local_peer_id()
@@ -713,41 +1713,264 @@ impl NetworkService {
             requester_peer_id: info.peer_id,
             timestamp,
             signature,
+            contact_list: None,
+            permission_grants: None,
         })
     }
 
-    /// Start listening on configured addresses
-    pub fn start_listening(&mut self) -> Result<()> {
-        // Listen on TCP
-        let tcp_addr: Multiaddr = format!("/ip4/0.0.0.0/tcp/{}", self.config.tcp_port)
-            .parse()
-            .map_err(|e| AppError::Network(format!("Invalid TCP address: {}", e)))?;
-        self.swarm.listen_on(tcp_addr.clone())?;
-        info!("Listening on TCP: {}", tcp_addr);
-
-        // Listen on QUIC
-        let quic_addr: Multiaddr = format!("/ip4/0.0.0.0/udp/{}/quic-v1", self.config.quic_port)
-            .parse()
-            .map_err(|e| AppError::Network(format!("Invalid QUIC address: {}", e)))?;
-        self.swarm.listen_on(quic_addr.clone())?;
-        info!("Listening on QUIC: {}", quic_addr);
-
-        Ok(())
+    /// Create a signed identity request that also asks `peer_id` for its
+    /// shared contact list
+    fn create_contact_list_request(&self, peer_id: &PeerId) -> Result<IdentityExchangeRequest> {
+        let mut request = self.create_identity_request()?;
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self.identity_service.sign(&SignableContactListRequest {
+            requester_peer_id: request.requester_peer_id.clone(),
+            responder_peer_id: peer_id.to_string(),
+            timestamp,
+        })?;
+        request.contact_list = Some(ContactListRequest {
+            timestamp,
+            signature,
+        });
+        Ok(request)
     }
 
-    /// Run the network event loop
-    pub async fn run(mut self) {
-        info!("Network service starting...");
-
-        if let Err(e) = self.start_listening() {
-            error!("Failed to start listening: {}", e);
+    /// Resend the grants `peer` hasn't acknowledged, riding on an identity
+    /// request. Does nothing for non-contacts or when everything is acked.
+    fn reconcile_permissions(&mut self, peer: PeerId) {
+        let (Some(contacts_service), Some(permissions_service)) =
+            (&self.contacts_service, &self.permissions_service)
+        else {
             return;
-        }
+        };
+        match contacts_service.is_contact(&peer.to_string()) {
+            Ok(true) => {}
+            Ok(false) => return,
+            Err(e) => {
+                warn!("Failed to check contact status of {}: {}", peer, e);
+                return;
+            }
+        }
+
+        let grants = match permissions_service.reconcile_permissions(&peer.to_string()) {
+            Ok(grants) if grants.is_empty() => return,
+            Ok(grants) => grants,
+            Err(e) => {
+                warn!("Failed to load unacknowledged grants for {}: {}", peer, e);
+                return;
+            }
+        };
+
+        let mut request = match self.create_identity_request() {
+            Ok(request) => request,
+            Err(e) => {
+                warn!("Failed to create permission reconcile request: {}", e);
+                return;
+            }
+        };
+        debug!(
+            "Resending {} unacknowledged grants to {}",
+            grants.len(),
+            peer
+        );
+        request.permission_grants = Some(
+            grants
+                .into_iter()
+                .map(|grant| PermissionGrantProto {
+                    payload_cbor: grant.payload_cbor,
+                    signature: grant.signature,
+                })
+                .collect(),
+        );
+
+        let request_id = self
+            .swarm
+            .behaviour_mut()
+            .identity_exchange
+            .send_request(&peer, request);
+        self.pending_permission_reconciles.insert(request_id, peer);
+    }
+
+    /// Re-issue fetches a sync with this peer identified but never completed,
+    /// e.g. because the app closed before the posts arrived
+    fn resume_fetch_intents(&mut self, peer: PeerId) {
+        let Some(ref content_sync_service) = self.content_sync_service else {
+            return;
+        };
+        let post_ids = match content_sync_service.claim_fetch_intents(&peer.to_string()) {
+            Ok(post_ids) if post_ids.is_empty() => return,
+            Ok(post_ids) => post_ids,
+            Err(e) => {
+                warn!("Failed to load pending post fetches for {}: {}", peer, e);
+                return;
+            }
+        };
+
+        debug!(
+            "Resuming {} pending post fetches from {}",
+            post_ids.len(),
+            peer
+        );
+        let mut requests = Vec::with_capacity(post_ids.len());
+        for post_id in post_ids {
+            match content_sync_service.create_fetch_request(post_id.clone(), false) {
+                Ok(fetch_req) => requests.push(ContentSyncRequest::FetchPost {
+                    post_id: fetch_req.post_id,
+                    include_media: fetch_req.include_media,
+                    requester_peer_id: fetch_req.requester_peer_id,
+                    timestamp: fetch_req.timestamp,
+                    signature: fetch_req.signature,
+                }),
+                Err(e) => warn!("Failed to create fetch request for {}: {}", post_id, e),
+            }
+        }
+        for request in requests {
+            self.swarm
+                .behaviour_mut()
+                .content_sync
+                .send_request(&peer, request);
+        }
+    }
+
+    /// Send every message queued for `peer`, ignoring the backoff since the
+    /// peer is evidently reachable
+    fn flush_pending_messages(&mut self, peer: PeerId) {
+        let Some(ref messaging_service) = self.messaging_service else {
+            return;
+        };
+        match messaging_service.pending_outbound_for(&peer.to_string()) {
+            Ok(pending) => self.send_pending_messages(pending),
+            Err(e) => warn!("Failed to load queued messages for {}: {}", peer, e),
+        }
+    }
+
+    /// Send queued messages whose backoff ran out, to whichever peers
+    fn retry_due_messages(&mut self) {
+        let Some(ref messaging_service) = self.messaging_service else {
+            return;
+        };
+        match messaging_service.due_outbound() {
+            Ok(pending) => self.send_pending_messages(pending),
+            Err(e) => warn!("Failed to load queued messages: {}", e),
+        }
+    }
+
+    /// Send queued messages, skipping any still waiting for a response
+    fn send_pending_messages(&mut self, pending: Vec<PendingOutbound>) {
+        for message in pending {
+            if self
+                .pending_outbound_sends
+                .values()
+                .any(|(key, _)| *key == message.message_id)
+            {
+                continue;
+            }
+            let Ok(peer) = message.peer_id.parse::<PeerId>() else {
+                warn!(
+                    "Queued message {} has an invalid peer ID {}",
+                    message.message_id, message.peer_id
+                );
+                continue;
+            };
+
+            debug!(
+                "Sending queued message {} to {} (attempt {})",
+                message.message_id,
+                peer,
+                message.attempts + 1
+            );
+            let request_id = self.swarm.behaviour_mut().messaging.send_request(
+                &peer,
+                MessagingRequest {
+                    message_type: "message".to_string(),
+                    payload: message.payload,
+                },
+            );
+            self.pending_outbound_sends
+                .insert(request_id, (message.message_id, message.kind));
+        }
+    }
+
+    /// Create a signed presence heartbeat
+    fn create_heartbeat(&self) -> Result<Heartbeat> {
+        let info = self
+            .identity_service
+            .get_identity_info()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = self.identity_service.sign(&SignableHeartbeat {
+            peer_id: info.peer_id.clone(),
+            timestamp,
+        })?;
+
+        Ok(Heartbeat {
+            peer_id: info.peer_id,
+            timestamp,
+            signature,
+        })
+    }
+
+    /// Start listening on configured addresses
+    ///
+    /// Fails with `AppError::NetworkPortInUse` when a fixed port is taken,
+    /// unless `port_fallback` is set. Call before `run` so the caller sees
+    /// the error.
+    pub fn start_listening(&mut self) -> Result<()> {
+        self.tcp_listener = Some(self.listen_on_port(ListenTransport::Tcp, self.config.tcp_port)?);
+        self.quic_listener =
+            Some(self.listen_on_port(ListenTransport::Quic, self.config.quic_port)?);
+        Ok(())
+    }
+
+    fn listen_on_port(&mut self, transport: ListenTransport, port: u16) -> Result<ListenerId> {
+        let addr = transport.listen_addr(port);
+        match self.swarm.listen_on(addr.clone()) {
+            Ok(listener_id) => {
+                info!("Listening on {}: {}", transport, addr);
+                Ok(listener_id)
+            }
+            // The transport error doesn't reliably say why the bind failed,
+            // so check whether the port is taken ourselves
+            Err(e) if port != 0 && transport.port_in_use(port) => {
+                if !self.config.port_fallback {
+                    return Err(AppError::NetworkPortInUse(format!(
+                        "{} port {} is already in use",
+                        transport, port
+                    )));
+                }
+                warn!(
+                    "{} port {} is in use ({}), falling back to a random port",
+                    transport, port, e
+                );
+                self.listen_on_port(transport, 0)
+            }
+            Err(e) => Err(AppError::Network(format!(
+                "Failed to listen on {}: {}",
+                addr, e
+            ))),
+        }
+    }
+
+    /// Run the network event loop
+    pub async fn run(mut self) {
+        info!("Network service starting...");
 
         // Auto-connect to relay on start (don't wait for AutoNAT)
         info!("Auto-connecting to Harbor relay...");
         self.connect_to_relays().await;
 
+        let mut heartbeat_timer = tokio::time::interval(self.config.heartbeat_interval);
+        let mut auto_identity_timer = tokio::time::interval(self.config.auto_identity_interval);
+        let board_sync_timer = tokio::time::sleep(self.board_sync_backoff);
+        tokio::pin!(board_sync_timer);
+        // `interval` panics on zero; a zero interval disables the branch instead
+        let mut reconcile_timer =
+            tokio::time::interval(self.config.reconcile_interval.max(Duration::from_secs(1)));
+        reconcile_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+        let mut outbound_retry_timer = tokio::time::interval(OUTBOUND_RETRY_INTERVAL);
+        outbound_retry_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Delay);
+
         loop {
             tokio::select! {
                 // Handle swarm events
@@ -755,6 +1978,47 @@ impl NetworkService {
                     self.handle_swarm_event(event).await;
                 }
 
+                // Answer content sync requests served on the blocking pool
+                Some(served) = self.served_rx.recv() => {
+                    self.finish_content_serve(served);
+                }
+
+                // Send presence heartbeats to connected contacts (opt-in)
+                _ = heartbeat_timer.tick(), if self.config.enable_heartbeat => {
+                    self.send_heartbeats();
+                }
+
+                // Ask newly discovered peers who they are, a batch at a time (opt-in)
+                _ = auto_identity_timer.tick(), if self.config.auto_request_identity
+                    && !self.auto_identity_queue.is_empty() => {
+                    self.send_auto_identity_requests();
+                }
+
+                // Sync subscribed boards in the background
+                _ = &mut board_sync_timer => {
+                    let delay = self.sync_subscribed_boards();
+                    board_sync_timer
+                        .as_mut()
+                        .reset(tokio::time::Instant::now() + delay);
+                }
+
+                // Re-dial relays and bootstrap nodes that dropped
+                _ = reconcile_timer.tick(), if !self.config.reconcile_interval.is_zero() => {
+                    self.reconcile_connections(false).await;
+                }
+
+                // Retry queued messages once their backoff runs out
+                _ = outbound_retry_timer.tick() => {
+                    self.retry_due_messages();
+                }
+
+                // Emit coalesced like changes once the window closes
+                _ = tokio::time::sleep_until(
+                    self.likes_flush_at.unwrap_or_else(tokio::time::Instant::now)
+                ), if self.likes_flush_at.is_some() => {
+                    self.flush_likes_updated().await;
+                }
+
                 // Handle commands from the application
                 Some((command, response_tx)) = self.command_rx.recv() => {
                     let should_shutdown = matches!(command, NetworkCommand::Shutdown);
@@ -769,13 +2033,59 @@ impl NetworkService {
                 }
             }
         }
+
+        // Deliver anything still batched before the event channel closes
+        if self.likes_flush_at.is_some() {
+            self.flush_likes_updated().await;
+        }
+        self.close_connections().await;
+    }
+
+    /// Disconnect from every peer and keep polling the swarm until the
+    /// connections are closed, so remotes see a clean close rather than a reset
+    async fn close_connections(&mut self) {
+        let peers: Vec<PeerId> = self.swarm.connected_peers().copied().collect();
+        if peers.is_empty() {
+            return;
+        }
+        for peer in &peers {
+            let _ = self.swarm.disconnect_peer_id(*peer);
+        }
+
+        let deadline = tokio::time::sleep(SWARM_CLOSE_TIMEOUT);
+        tokio::pin!(deadline);
+        while self.swarm.connected_peers().next().is_some() {
+            tokio::select! {
+                _ = &mut deadline => {
+                    warn!("Timed out waiting for peer connections to close");
+                    break;
+                }
+                _ = self.swarm.select_next_some() => {}
+            }
+        }
+        info!("Closed connections to {} peers", peers.len());
     }
 
     async fn handle_swarm_event(&mut self, event: SwarmEvent<ChatBehaviourEvent>) {
         match event {
-            SwarmEvent::NewListenAddr { address, .. } => {
+            SwarmEvent::NewListenAddr {
+                listener_id,
+                address,
+            } => {
                 info!("Listening on: {}", address);
+                if Some(listener_id) == self.tcp_listener {
+                    self.bound_ports.tcp = listen_port(&address);
+                } else if Some(listener_id) == self.quic_listener {
+                    self.bound_ports.quic = listen_port(&address);
+                }
                 self.listening_addresses.push(address.clone());
+
+                // Identify hides listen addresses while filtering is on, so
+                // announce the ones the policy allows as external addresses
+                let filter = self.config.address_filter;
+                if filter != AddressFilter::All && filter.allows(&address) {
+                    self.swarm.add_external_address(address.clone());
+                }
                 let _ = self
                     .event_tx
                     .send(NetworkEvent::ListeningOn {
@@ -785,19 +2095,46 @@ impl NetworkService {
             }
 
             SwarmEvent::ConnectionEstablished {
-                peer_id, endpoint, ..
+                peer_id,
+                connection_id,
+                endpoint,
+                num_established,
+                ..
             } => {
                 info!("Connected to peer: {} at {:?}", peer_id, endpoint);
+                // The PeerConnected event below completes any dial attempts
+                self.pending_dials.finish(connection_id);
+                self.peer_connections.established(
+                    peer_id,
+                    connection_id,
+                    endpoint.get_remote_address(),
+                );
                 let peer_info = PeerInfo {
                     peer_id: peer_id.to_string(),
                     addresses: vec![endpoint.get_remote_address().to_string()],
                     protocol_version: None,
                     agent_version: None,
+                    app_version: None,
+                    platform: None,
                     is_connected: true,
                     last_seen: Some(chrono::Utc::now().timestamp()),
+                    connection_type: ConnectionType::Direct,
+                    relay_peer_id: None,
                 };
                 self.connected_peers.insert(peer_id, peer_info);
+                self.refresh_connection_type(&peer_id);
+                self.peer_activity.insert(peer_id, Instant::now());
                 self.stats.connected_peers = self.connected_peers.len();
+                if self.config.auto_request_identity {
+                    self.auto_identity_queue.push(peer_id);
+                }
+                // Grants and messages sent while the contact was offline may
+                // never have arrived; one reconcile per (re)connect is enough
+                if num_established.get() == 1 {
+                    self.reconcile_permissions(peer_id);
+                    self.resume_fetch_intents(peer_id);
+                    self.flush_pending_messages(peer_id);
+                }
 
                 let _ = self
                     .event_tx
@@ -805,11 +2142,36 @@ impl NetworkService {
                         peer_id: peer_id.to_string(),
                     })
                     .await;
+
+                self.enforce_connection_limit().await;
             }
 
-            SwarmEvent::ConnectionClosed { peer_id, cause, .. } => {
+            SwarmEvent::ConnectionClosed {
+                peer_id,
+                connection_id,
+                num_established,
+                cause,
+                ..
+            } => {
+                self.peer_connections.closed(&peer_id, connection_id);
+                // A relayed connection closing after hole punching succeeded
+                // leaves the peer connected directly
+                if num_established > 0 {
+                    debug!(
+                        "Closed a connection to {} ({} remaining, cause: {:?})",
+                        peer_id, num_established, cause
+                    );
+                    self.refresh_connection_type(&peer_id);
+                    return;
+                }
+
                 info!("Disconnected from peer: {} (cause: {:?})", peer_id, cause);
                 self.connected_peers.remove(&peer_id);
+                self.peer_activity.remove(&peer_id);
+                self.auto_identity_queue.cancel(&peer_id);
+                // Limits are re-advertised with the next reservation
+                self.relay_limits.remove(&peer_id);
+                self.relay_liveness.remove(&peer_id);
                 self.stats.connected_peers = self.connected_peers.len();
 
                 let _ = self
@@ -820,22 +2182,86 @@ impl NetworkService {
                     .await;
             }
 
+            SwarmEvent::ExpiredListenAddr { address, .. } => {
+                info!("No longer listening on: {}", address);
+                self.listening_addresses.retain(|a| a != &address);
+                if self.config.address_filter != AddressFilter::All {
+                    self.swarm.remove_external_address(&address);
+                }
+            }
+
             SwarmEvent::ExternalAddrConfirmed { address } => {
                 info!("External address confirmed: {}", address);
-                let _ = self
-                    .event_tx
-                    .send(NetworkEvent::ExternalAddressDiscovered {
+                // Our own listen addresses promoted above aren't discoveries
+                if self.listening_addresses.contains(&address) {
+                    return;
+                }
+                self.event_tx
+                    .send_droppable(NetworkEvent::ExternalAddressDiscovered {
                         address: address.to_string(),
-                    })
-                    .await;
+                    });
             }
 
-            SwarmEvent::OutgoingConnectionError { peer_id, error, .. } => {
-                if let Some(peer_id) = peer_id {
-                    warn!("Failed to connect to peer {}: {}", peer_id, error);
-                } else {
-                    warn!("Outgoing connection error: {}", error);
+            SwarmEvent::OutgoingConnectionError {
+                connection_id,
+                peer_id,
+                error,
+            } => {
+                for (address, category, detail) in classify_dial_error(&error) {
+                    warn!(
+                        "Failed to connect to {} at {} ({}): {}",
+                        peer_id.map_or_else(|| "unknown peer".to_string(), |p| p.to_string()),
+                        address
+                            .as_ref()
+                            .map_or_else(|| "no address".to_string(), |a| a.to_string()),
+                        category.as_str(),
+                        detail
+                    );
+                    self.event_tx
+                        .send_droppable(NetworkEvent::ConnectionFailed {
+                            peer_id: peer_id.map(|p| p.to_string()),
+                            address: address.map(|a| a.to_string()),
+                            category,
+                            detail,
+                        });
+                }
+                if let DialError::LocalPeerId { address } = &error {
+                    self.check_identity_conflict(address).await;
+                }
+
+                for attempt in self.pending_dials.finish(connection_id) {
+                    self.event_tx.send_droppable(NetworkEvent::DialFailed {
+                        attempt_id: attempt.attempt_id,
+                        peer_id: attempt.peer_id.to_string(),
+                        address: attempt.address.as_ref().map(|a| a.to_string()),
+                        error: dial_error_for_address(&error, attempt.address.as_ref()),
+                    });
+                }
+            }
+
+            SwarmEvent::IncomingConnectionError {
+                send_back_addr,
+                error,
+                ..
+            } => {
+                // Scanners and stale dials fail here routinely, so only debug
+                let (category, detail) = classify_listen_error(&error);
+                debug!(
+                    "Incoming connection from {} failed ({}): {}",
+                    send_back_addr,
+                    category.as_str(),
+                    detail
+                );
+                if let ListenError::LocalPeerId { .. } = &error {
+                    self.check_identity_conflict(&send_back_addr).await;
                 }
+                self.event_tx
+                    .send_droppable(NetworkEvent::ConnectionFailed {
+                        peer_id: None,
+                        address: Some(send_back_addr.to_string()),
+                        category,
+                        detail,
+                    });
             }
 
             SwarmEvent::Behaviour(behaviour_event) => {
@@ -846,6 +2272,97 @@ impl NetworkService {
         }
     }
 
+    /// A connection at `address` authenticated as our own peer ID. Dialing
+    /// one of our own addresses (a loopback, mDNS or relay echo) does that
+    /// too; anywhere else it means another device holds our identity key,
+    /// and messages meant for us may be going to it.
+    async fn check_identity_conflict(&mut self, address: &Multiaddr) {
+        let own_addresses: Vec<Multiaddr> = self
+            .listening_addresses
+            .iter()
+            .chain(&self.external_addresses)
+            .chain(&self.relay_addresses)
+            .cloned()
+            .collect();
+        if is_own_address(address, &own_addresses) {
+            debug!("Connection to our own address {} ignored", address);
+            return;
+        }
+
+        let origin = address_ip(address)
+            .map(|ip| ip.to_string())
+            .unwrap_or_else(|| address.to_string());
+        if !self.identity_conflicts.insert(origin) {
+            return;
+        }
+        error!(
+            "IDENTITY CONFLICT: another device at {} is using our identity key ({}). \
+             Messages for this identity may be delivered to it instead of us.",
+            address,
+            self.swarm.local_peer_id()
+        );
+        let _ = self
+            .event_tx
+            .send(NetworkEvent::IdentityConflictDetected {
+                address: address.to_string(),
+            })
+            .await;
+    }
+
+    /// Serve a manifest or post fetch on the blocking pool, so signature
+    /// checks and queries don't stall the swarm. With
+    /// `max_concurrent_serves` already in flight the requester is told to
+    /// retry later instead.
+    fn spawn_content_serve(
+        &mut self,
+        peer: PeerId,
+        channel: ResponseChannel<ContentSyncResponse>,
+        serve: impl FnOnce() -> ContentSyncResponse + Send + 'static,
+    ) {
+        if self.content_serves_in_flight >= self.config.max_concurrent_serves {
+            debug!(
+                "Content sync request from {} refused: serving is busy",
+                peer
+            );
+            let _ = self.swarm.behaviour_mut().content_sync.send_response(
+                channel,
+                ContentSyncResponse::Error {
+                    error: CONTENT_SYNC_BUSY_ERROR.to_string(),
+                },
+            );
+            return;
+        }
+
+        self.content_serves_in_flight += 1;
+        let served_tx = self.served_tx.clone();
+        tokio::spawn(async move {
+            let response = tokio::task::spawn_blocking(serve)
+                .await
+                .unwrap_or_else(|e| {
+                    error!("Serving content sync request from {} failed: {}", peer, e);
+                    ContentSyncResponse::Error {
+                        error: "Internal error".to_string(),
+                    }
+                });
+            let _ = served_tx
+                .send(ServedContentRequest { channel, response })
+                .await;
+        });
+    }
+
+    /// Send the response to a content sync request served off the event loop
+    fn finish_content_serve(&mut self, served: ServedContentRequest) {
+        self.content_serves_in_flight = self.content_serves_in_flight.saturating_sub(1);
+        if let Err(e) = self
+            .swarm
+            .behaviour_mut()
+            .content_sync
+            .send_response(served.channel, served.response)
+        {
+            warn!("Failed to send content sync response: {:?}", e);
+        }
+    }
+
     async fn handle_content_sync_request(
         &mut self,
         peer: PeerId,
@@ -870,6 +2387,7 @@ impl NetworkService {
                 limit,
                 timestamp,
                 signature,
+                detail_level,
             } => {
                 // Ensure peer id matches claimed requester
                 if requester_peer_id != peer.to_string() {
@@ -882,15 +2400,17 @@ impl NetworkService {
                     return;
                 }
 
-                match content_sync_service.process_manifest_request(
-                    &requester_peer_id,
-                    &cursor,
-                    limit,
-                    timestamp,
-                    &signature,
-                ) {
-                    Ok(resp) => {
-                        let response = ContentSyncResponse::Manifest {
+                let content_sync_service = content_sync_service.clone();
+                self.spawn_content_serve(peer, channel, move || {
+                    match content_sync_service.process_manifest_request(
+                        &requester_peer_id,
+                        &cursor,
+                        limit,
+                        detail_level,
+                        timestamp,
+                        &signature,
+                    ) {
+                        Ok(resp) => ContentSyncResponse::Manifest {
                             responder_peer_id: resp.responder_peer_id,
                             posts: resp
                                 .posts
@@ -909,26 +2429,20 @@ impl NetworkService {
                             next_cursor: resp.next_cursor,
                             timestamp: resp.timestamp,
                             signature: resp.signature,
-                        };
-
-                        if let Err(e) = self
-                            .swarm
-                            .behaviour_mut()
-                            .content_sync
-                            .send_response(channel, response)
-                        {
-                            warn!("Failed to send content manifest response: {:?}", e);
-                        }
-                    }
-                    Err(e) => {
-                        let _ = self.swarm.behaviour_mut().content_sync.send_response(
-                            channel,
-                            ContentSyncResponse::Error {
-                                error: e.to_string(),
-                            },
-                        );
+                            headers: resp
+                                .headers
+                                .into_iter()
+                                .map(|h| PostHeaderProto {
+                                    post_id: h.post_id,
+                                    lamport_clock: h.lamport_clock,
+                                })
+                                .collect(),
+                        },
+                        Err(e) => ContentSyncResponse::Error {
+                            error: e.to_string(),
+                        },
                     }
-                }
+                });
             }
             ContentSyncRequest::FetchPost {
                 post_id,
@@ -948,15 +2462,16 @@ impl NetworkService {
                     return;
                 }
 
-                match content_sync_service.process_fetch_request(
-                    &requester_peer_id,
-                    &post_id,
-                    include_media,
-                    timestamp,
-                    &signature,
-                ) {
-                    Ok(resp) => {
-                        let response = ContentSyncResponse::Post {
+                let content_sync_service = content_sync_service.clone();
+                self.spawn_content_serve(peer, channel, move || {
+                    match content_sync_service.process_fetch_request(
+                        &requester_peer_id,
+                        &post_id,
+                        include_media,
+                        timestamp,
+                        &signature,
+                    ) {
+                        Ok(resp) => ContentSyncResponse::Post {
                             post_id: resp.post_id,
                             author_peer_id: resp.author_peer_id,
                             content_type: resp.content_type,
@@ -965,39 +2480,141 @@ impl NetworkService {
                             lamport_clock: resp.lamport_clock,
                             created_at: resp.created_at,
                             signature: resp.signature,
-                        };
-
-                        if let Err(e) = self
-                            .swarm
-                            .behaviour_mut()
-                            .content_sync
-                            .send_response(channel, response)
-                        {
-                            warn!("Failed to send fetch post response: {:?}", e);
-                        }
-                    }
-                    Err(e) => {
-                        warn!("Failed to process fetch request from {}: {}", peer, e);
-                        let _ = self.swarm.behaviour_mut().content_sync.send_response(
-                            channel,
+                            sig_version: resp.sig_version,
+                        },
+                        Err(e) => {
+                            warn!("Failed to process fetch request from {}: {}", peer, e);
                             ContentSyncResponse::Error {
                                 error: e.to_string(),
-                            },
-                        );
+                            }
+                        }
                     }
-                }
+                });
             }
-        }
-    }
+            ContentSyncRequest::PostView {
+                post_id,
+                viewer_peer_id,
+                viewed_at,
+                signature,
+            } => {
+                if viewer_peer_id != peer.to_string() {
+                    let _ = self.swarm.behaviour_mut().content_sync.send_response(
+                        channel,
+                        ContentSyncResponse::Error {
+                            error: "viewer_peer_id mismatch".to_string(),
+                        },
+                    );
+                    return;
+                }
 
-    async fn handle_content_sync_response(
-        &mut self,
-        peer: PeerId,
+                let response = match content_sync_service.process_post_view(
+                    &viewer_peer_id,
+                    &post_id,
+                    viewed_at,
+                    &signature,
+                ) {
+                    Ok(is_new) => {
+                        debug!("Recorded view of post {} by {}", post_id, peer);
+                        if is_new {
+                            self.event_tx
+                                .send_droppable(NetworkEvent::ActivityReceived {
+                                    kind: ActivityKind::View,
+                                    actor_peer_id: viewer_peer_id,
+                                    target_id: post_id.clone(),
+                                });
+                        }
+                        ContentSyncResponse::ViewRecorded { post_id }
+                    }
+                    Err(e) => {
+                        warn!("Failed to process view report from {}: {}", peer, e);
+                        ContentSyncResponse::Error {
+                            error: e.to_string(),
+                        }
+                    }
+                };
+                let _ = self
+                    .swarm
+                    .behaviour_mut()
+                    .content_sync
+                    .send_response(channel, response);
+            }
+            ContentSyncRequest::PostAnnouncement {
+                post,
+                timestamp,
+                signature,
+            } => {
+                let post_id = post.post_id.clone();
+                let summary = crate::services::PostSummary {
+                    post_id: post.post_id,
+                    author_peer_id: post.author_peer_id,
+                    lamport_clock: post.lamport_clock,
+                    content_type: post.content_type,
+                    has_media: post.has_media,
+                    media_hashes: post.media_hashes,
+                    created_at: post.created_at,
+                };
+
+                let needs_fetch = match content_sync_service.process_post_announcement(
+                    &peer.to_string(),
+                    &summary,
+                    timestamp,
+                    &signature,
+                ) {
+                    Ok(needs_fetch) => needs_fetch,
+                    Err(e) => {
+                        warn!("Rejected post announcement from {}: {}", peer, e);
+                        let _ = self.swarm.behaviour_mut().content_sync.send_response(
+                            channel,
+                            ContentSyncResponse::Error {
+                                error: e.to_string(),
+                            },
+                        );
+                        return;
+                    }
+                };
+
+                // Fetch the full post the same way a manifest entry would be
+                let fetch_request = if needs_fetch {
+                    match content_sync_service.create_fetch_request(post_id.clone(), false) {
+                        Ok(fetch_req) => Some(ContentSyncRequest::FetchPost {
+                            post_id: fetch_req.post_id,
+                            include_media: fetch_req.include_media,
+                            requester_peer_id: fetch_req.requester_peer_id,
+                            timestamp: fetch_req.timestamp,
+                            signature: fetch_req.signature,
+                        }),
+                        Err(e) => {
+                            warn!("Failed to create fetch request for {}: {}", post_id, e);
+                            None
+                        }
+                    }
+                } else {
+                    None
+                };
+
+                debug!("Received announcement of post {} from {}", post_id, peer);
+                let _ = self.swarm.behaviour_mut().content_sync.send_response(
+                    channel,
+                    ContentSyncResponse::AnnouncementReceived { post_id },
+                );
+                if let Some(request) = fetch_request {
+                    self.swarm
+                        .behaviour_mut()
+                        .content_sync
+                        .send_request(&peer, request);
+                }
+            }
+        }
+    }
+
+    async fn handle_content_sync_response(
+        &mut self,
+        peer: PeerId,
         _request_id: request_response::OutboundRequestId,
         response: ContentSyncResponse,
-    ) {
+    ) -> ContentSyncOutcome {
         let Some(ref content_sync_service) = self.content_sync_service else {
-            return;
+            return ContentSyncOutcome::Failed("Content sync service unavailable".to_string());
         };
 
         match response {
@@ -1008,13 +2625,16 @@ impl NetworkService {
                 next_cursor,
                 timestamp,
                 signature,
+                headers,
             } => {
                 if responder_peer_id != peer.to_string() {
                     warn!(
                         "Content manifest responder mismatch: expected {}, got {}",
                         peer, responder_peer_id
                     );
-                    return;
+                    return ContentSyncOutcome::Failed(
+                        "Content manifest responder mismatch".to_string(),
+                    );
                 }
 
                 // Convert wire format to service format
@@ -1030,10 +2650,18 @@ impl NetworkService {
                         created_at: p.created_at,
                     })
                     .collect();
+                let service_headers: Vec<crate::services::PostHeader> = headers
+                    .into_iter()
+                    .map(|h| crate::services::PostHeader {
+                        post_id: h.post_id,
+                        lamport_clock: h.lamport_clock,
+                    })
+                    .collect();
 
                 match content_sync_service.process_manifest_response(
                     &responder_peer_id,
                     &service_posts,
+                    &service_headers,
                     has_more,
                     &next_cursor,
                     timestamp,
@@ -1041,16 +2669,15 @@ impl NetworkService {
                 ) {
                     Ok(posts_to_fetch) => {
                         // Emit manifest received event
-                        let _ = self
-                            .event_tx
-                            .send(NetworkEvent::ContentManifestReceived {
+                        self.event_tx
+                            .send_droppable(NetworkEvent::ContentManifestReceived {
                                 peer_id: peer.to_string(),
                                 post_count: posts_to_fetch.len(),
                                 has_more,
-                            })
-                            .await;
+                            });
 
                         // Issue fetch requests for posts we need
+                        let mut fetch_requests = Vec::with_capacity(posts_to_fetch.len());
                         for post_id in posts_to_fetch {
                             match content_sync_service.create_fetch_request(post_id.clone(), false)
                             {
@@ -1062,10 +2689,12 @@ impl NetworkService {
                                         timestamp: fetch_req.timestamp,
                                         signature: fetch_req.signature,
                                     };
-                                    self.swarm
+                                    let request_id = self
+                                        .swarm
                                         .behaviour_mut()
                                         .content_sync
                                         .send_request(&peer, request);
+                                    fetch_requests.push(request_id);
                                     debug!("Sent fetch request for post {} to {}", post_id, peer);
                                 }
                                 Err(e) => {
@@ -1073,6 +2702,10 @@ impl NetworkService {
                                 }
                             }
                         }
+                        ContentSyncOutcome::Manifest {
+                            fetch_requests,
+                            has_more,
+                        }
                     }
                     Err(e) => {
                         warn!("Failed to process manifest response: {}", e);
@@ -1083,6 +2716,7 @@ impl NetworkService {
                                 error: e.to_string(),
                             })
                             .await;
+                        ContentSyncOutcome::Failed(e.to_string())
                     }
                 }
             }
@@ -1095,6 +2729,7 @@ impl NetworkService {
                 lamport_clock,
                 created_at,
                 signature,
+                sig_version,
             } => {
                 info!("Received post {} from {}", post_id, peer);
 
@@ -1104,7 +2739,7 @@ impl NetworkService {
                         "Post author mismatch: expected {}, got {}",
                         peer, author_peer_id
                     );
-                    return;
+                    return ContentSyncOutcome::Failed(format!("Post {} author mismatch", post_id));
                 }
 
                 // Store the remote post
@@ -1117,9 +2752,27 @@ impl NetworkService {
                     lamport_clock,
                     created_at,
                     signature: &signature,
+                    sig_version,
                 }) {
                     Ok(_) => {
                         info!("Stored remote post {} from {}", post_id, peer);
+                        // Let the author know, unless we've opted out
+                        match content_sync_service.create_post_view(&post_id, &author_peer_id) {
+                            Ok(Some(view)) => {
+                                let request = ContentSyncRequest::PostView {
+                                    post_id: view.post_id,
+                                    viewer_peer_id: view.viewer_peer_id,
+                                    viewed_at: view.viewed_at,
+                                    signature: view.signature,
+                                };
+                                self.swarm
+                                    .behaviour_mut()
+                                    .content_sync
+                                    .send_request(&peer, request);
+                            }
+                            Ok(None) => {}
+                            Err(e) => warn!("Failed to create view report for {}: {}", post_id, e),
+                        }
                         // Emit event for UI to refresh feed
                         let _ = self
                             .event_tx
@@ -1128,6 +2781,7 @@ impl NetworkService {
                                 post_id,
                             })
                             .await;
+                        ContentSyncOutcome::PostStored
                     }
                     Err(e) => {
                         warn!("Failed to store remote post {}: {}", post_id, e);
@@ -1138,16 +2792,114 @@ impl NetworkService {
                                 error: e.to_string(),
                             })
                             .await;
+                        ContentSyncOutcome::Failed(format!("Post {}: {}", post_id, e))
                     }
                 }
             }
+            ContentSyncResponse::ViewRecorded { post_id } => {
+                debug!("{} recorded our view of post {}", peer, post_id);
+                ContentSyncOutcome::ViewReported
+            }
+            ContentSyncResponse::AnnouncementReceived { post_id } => {
+                debug!("{} received our announcement of post {}", peer, post_id);
+                ContentSyncOutcome::AnnouncementDelivered
+            }
             ContentSyncResponse::Error { error } => {
                 warn!("Content sync error from {}: {}", peer, error);
+                ContentSyncOutcome::Failed(error)
             }
         }
     }
 
+    /// Fold a content sync result into the `sync_with_peer` it belongs to, if
+    /// any, and reply once nothing is outstanding
+    fn record_peer_sync_outcome(
+        &mut self,
+        request_id: request_response::OutboundRequestId,
+        outcome: ContentSyncOutcome,
+    ) {
+        let Some(peer) = self.peer_sync_requests.remove(&request_id) else {
+            return;
+        };
+        let Some(sync) = self.peer_syncs.get_mut(&peer) else {
+            return;
+        };
+
+        sync.outstanding.remove(&request_id);
+        match outcome {
+            ContentSyncOutcome::Manifest {
+                fetch_requests,
+                has_more,
+            } => {
+                sync.summary.has_more = has_more;
+                for fetch_request in fetch_requests {
+                    sync.outstanding.insert(fetch_request);
+                    self.peer_sync_requests.insert(fetch_request, peer);
+                }
+            }
+            ContentSyncOutcome::PostStored => sync.summary.posts_fetched += 1,
+            ContentSyncOutcome::ViewReported | ContentSyncOutcome::AnnouncementDelivered => {}
+            ContentSyncOutcome::Failed(error) => sync.summary.errors.push(error),
+        }
+
+        if sync.outstanding.is_empty() {
+            if let Some(sync) = self.peer_syncs.remove(&peer) {
+                info!(
+                    "Sync with {} finished: {} posts fetched, {} errors",
+                    peer,
+                    sync.summary.posts_fetched,
+                    sync.summary.errors.len()
+                );
+                let _ = sync.reply.send(sync.summary);
+            }
+        }
+    }
+
+    /// Record whether a post announcement reached its recipient
+    fn record_post_delivery(
+        &mut self,
+        request_id: request_response::OutboundRequestId,
+        outcome: &ContentSyncOutcome,
+    ) {
+        let Some((post_id, peer)) = self.pending_post_deliveries.remove(&request_id) else {
+            return;
+        };
+        let Some(ref content_sync_service) = self.content_sync_service else {
+            return;
+        };
+
+        let result = match outcome {
+            ContentSyncOutcome::AnnouncementDelivered => Ok(()),
+            ContentSyncOutcome::Failed(error) => Err(error.clone()),
+            _ => Err("Unexpected response to post announcement".to_string()),
+        };
+        if let Err(e) =
+            content_sync_service.record_delivery_result(&post_id, &peer.to_string(), result)
+        {
+            warn!(
+                "Failed to record delivery of post {} to {}: {}",
+                post_id, peer, e
+            );
+        }
+    }
+
     async fn handle_behaviour_event(&mut self, event: ChatBehaviourEvent) {
+        let active_peer = match &event {
+            ChatBehaviourEvent::IdentityExchange(event) => request_response_peer(event),
+            ChatBehaviourEvent::Messaging(event) => request_response_peer(event),
+            ChatBehaviourEvent::ContentSync(event) => request_response_peer(event),
+            ChatBehaviourEvent::BoardSync(event) => request_response_peer(event),
+            ChatBehaviourEvent::MediaSync(event) => request_response_peer(event),
+            ChatBehaviourEvent::Presence(event) => request_response_peer(event),
+            ChatBehaviourEvent::CallData(event) => request_response_peer(event),
+            _ => None,
+        };
+        if let Some(peer) = active_peer {
+            if let Some(last_active) = self.peer_activity.get_mut(&peer) {
+                *last_active = Instant::now();
+            }
+        }
+
         match event {
             ChatBehaviourEvent::Mdns(event) => {
                 self.handle_mdns_event(event).await;
@@ -1162,7 +2914,7 @@ impl NetworkService {
             }
 
             ChatBehaviourEvent::Ping(event) => {
-                self.handle_ping_event(event);
+                self.handle_ping_event(event).await;
             }
 
             ChatBehaviourEvent::IdentityExchange(event) => {
@@ -1185,6 +2937,14 @@ impl NetworkService {
                 self.handle_media_sync_event(event).await;
             }
 
+            ChatBehaviourEvent::Presence(event) => {
+                self.handle_presence_event(event);
+            }
+
+            ChatBehaviourEvent::CallData(event) => {
+                self.handle_call_data_event(event).await;
+            }
+
             ChatBehaviourEvent::RelayClient(event) => {
                 self.handle_relay_client_event(event).await;
             }
@@ -1199,11 +2959,101 @@ impl NetworkService {
         }
     }
 
+    /// Disconnect the least recently active unprotected peers once we're over
+    /// `max_connections`, trimming down to the eviction margin below it
+    async fn enforce_connection_limit(&mut self) {
+        if self.connected_peers.len() <= self.config.max_connections {
+            return;
+        }
+
+        let candidates: Vec<(PeerId, Instant)> = self
+            .connected_peers
+            .keys()
+            .filter(|peer_id| !self.is_protected_peer(peer_id))
+            .map(|peer_id| {
+                let last_active = self
+                    .peer_activity
+                    .get(peer_id)
+                    .copied()
+                    .unwrap_or(self.start_time);
+                (*peer_id, last_active)
+            })
+            .collect();
+
+        let evicted = peers_to_evict(
+            self.connected_peers.len(),
+            self.config.max_connections,
+            self.config.connection_eviction_margin,
+            candidates,
+        );
+        if evicted.is_empty() {
+            warn!(
+                "{} connections exceed the limit of {} but all peers are protected",
+                self.connected_peers.len(),
+                self.config.max_connections
+            );
+            return;
+        }
+
+        for peer_id in evicted {
+            info!(
+                "Evicting idle peer {} to stay under the connection limit",
+                peer_id
+            );
+            // Forget the peer now so the count is right before ConnectionClosed arrives
+            self.connected_peers.remove(&peer_id);
+            self.peer_activity.remove(&peer_id);
+            let _ = self.swarm.disconnect_peer_id(peer_id);
+            let _ = self
+                .event_tx
+                .send(NetworkEvent::PeerEvicted {
+                    peer_id: peer_id.to_string(),
+                })
+                .await;
+        }
+        self.stats.connected_peers = self.connected_peers.len();
+    }
+
+    /// Contacts, relays and peers we're in the middle of syncing with are
+    /// never evicted
+    fn is_protected_peer(&self, peer_id: &PeerId) -> bool {
+        if self.community_relays.contains_key(peer_id)
+            || self.pending_relay_reservations.contains_key(peer_id)
+            || self.pending_community_probes.contains_key(peer_id)
+            || self.peer_syncs.contains_key(peer_id)
+        {
+            return true;
+        }
+
+        let names_peer = |addr: &Multiaddr| {
+            addr.iter()
+                .any(|p| p == libp2p::multiaddr::Protocol::P2p(*peer_id))
+        };
+        if self.relay_addresses.iter().any(names_peer)
+            || self.config.bootstrap_nodes.iter().any(names_peer)
+        {
+            return true;
+        }
+
+        // If the contact lookup fails, err on the side of keeping the connection
+        self.contacts_service
+            .as_ref()
+            .is_some_and(|contacts_service| {
+                contacts_service
+                    .is_contact(&peer_id.to_string())
+                    .unwrap_or(true)
+            })
+    }
+
     /// Handle mDNS discovery and expiry events
     async fn handle_mdns_event(&mut self, event: mdns::Event) {
         match event {
             mdns::Event::Discovered(peers) => {
                 for (peer_id, addr) in peers {
+                    if peer_id == *self.swarm.local_peer_id() {
+                        self.check_identity_conflict(&addr).await;
+                        continue;
+                    }
                     info!("mDNS discovered peer: {} at {}", peer_id, addr);
                     self.discovered_peers
                         .entry(peer_id)
@@ -1216,12 +3066,9 @@ impl NetworkService {
                         .kademlia
                         .add_address(&peer_id, addr);
 
-                    let _ = self
-                        .event_tx
-                        .send(NetworkEvent::PeerDiscovered {
-                            peer_id: peer_id.to_string(),
-                        })
-                        .await;
+                    self.event_tx.send_droppable(NetworkEvent::PeerDiscovered {
+                        peer_id: peer_id.to_string(),
+                    });
                 }
             }
 
@@ -1232,12 +3079,9 @@ impl NetworkService {
                         addrs.retain(|a| a != &addr);
                         if addrs.is_empty() {
                             self.discovered_peers.remove(&peer_id);
-                            let _ = self
-                                .event_tx
-                                .send(NetworkEvent::PeerExpired {
-                                    peer_id: peer_id.to_string(),
-                                })
-                                .await;
+                            self.event_tx.send_droppable(NetworkEvent::PeerExpired {
+                                peer_id: peer_id.to_string(),
+                            });
                         }
                     }
                 }
@@ -1248,10 +3092,21 @@ impl NetworkService {
     /// Handle libp2p Identify protocol events
     async fn handle_identify_event(&mut self, event: identify::Event) {
         if let identify::Event::Received { peer_id, info, .. } = event {
-            debug!("Identified peer: {} - {}", peer_id, info.agent_version);
+            let agent = HarborAgent::parse(&info.agent_version);
+            match &agent {
+                Some(agent) => debug!(
+                    "Identified peer: {} - Harbor {} on {}",
+                    peer_id,
+                    agent.version,
+                    agent.platform.as_deref().unwrap_or("unknown platform")
+                ),
+                None => debug!("Identified peer: {} - {}", peer_id, info.agent_version),
+            }
             if let Some(peer_info) = self.connected_peers.get_mut(&peer_id) {
                 peer_info.protocol_version = Some(info.protocol_version);
                 peer_info.agent_version = Some(info.agent_version);
+                peer_info.app_version = agent.as_ref().map(|agent| agent.version.clone());
+                peer_info.platform = agent.and_then(|agent| agent.platform);
             }
 
             // Add addresses to Kademlia
@@ -1266,175 +3121,926 @@ impl NetworkService {
             // This is the correct timing — the connection is fully negotiated and
             // the relay client transport knows about it.
             if let Some(relay_addr) = self.pending_relay_reservations.remove(&peer_id) {
-                let circuit_listen_addr: Multiaddr = relay_addr
-                    .clone()
-                    .with(libp2p::multiaddr::Protocol::P2pCircuit);
+                self.request_relay_reservation(relay_addr);
+            }
+        }
+    }
+
+    /// Listen on a relay's circuit address, which requests a reservation
+    fn request_relay_reservation(&mut self, relay_addr: Multiaddr) {
+        let circuit_listen_addr: Multiaddr =
+            relay_addr.with(libp2p::multiaddr::Protocol::P2pCircuit);
+        info!(
+            "Requesting relay reservation on {} (post-identify)",
+            circuit_listen_addr
+        );
+        match self.swarm.listen_on(circuit_listen_addr.clone()) {
+            Ok(id) => {
                 info!(
-                    "Requesting relay reservation on {} (post-identify)",
-                    circuit_listen_addr
+                    "Relay listener registered: {:?} on {}",
+                    id, circuit_listen_addr
                 );
-                match self.swarm.listen_on(circuit_listen_addr.clone()) {
-                    Ok(id) => {
-                        info!(
-                            "Relay listener registered: {:?} on {}",
-                            id, circuit_listen_addr
-                        );
+            }
+            Err(e) => {
+                warn!(
+                    "Failed to request relay reservation {}: {}",
+                    circuit_listen_addr, e
+                );
+            }
+        }
+    }
+
+    /// Request a relay reservation once the relay's connection is negotiated.
+    /// listen_on must be called AFTER Identify::Received, not immediately
+    /// after dial — otherwise the relay client transport doesn't know about
+    /// the connection yet. A relay we're already connected to and identified
+    /// won't identify again, so it is asked right away.
+    fn queue_relay_reservation(&mut self, relay_peer_id: PeerId, relay_addr: Multiaddr) {
+        let identified = self
+            .connected_peers
+            .get(&relay_peer_id)
+            .is_some_and(|peer| peer.agent_version.is_some());
+        if identified {
+            self.request_relay_reservation(relay_addr);
+            return;
+        }
+        self.pending_relay_reservations
+            .insert(relay_peer_id, relay_addr);
+        info!(
+            "Relay reservation queued for {} (will request after identify)",
+            relay_peer_id
+        );
+    }
+
+    /// Handle Kademlia DHT events
+    async fn handle_kademlia_event(&mut self, event: kad::Event) {
+        if let kad::Event::RoutingUpdated { peer, .. } = event {
+            debug!("Kademlia routing updated for peer: {}", peer);
+        }
+    }
+
+    /// Handle ping protocol events. Results for relays we hold a reservation
+    /// on update their liveness, and health changes are reported to the app.
+    async fn handle_ping_event(&mut self, event: ping::Event) {
+        let rtt = match event.result {
+            Ok(rtt) => {
+                debug!("Ping to {} succeeded: {:?}", event.peer, rtt);
+                Some(rtt)
+            }
+            Err(ref e) => {
+                debug!("Ping to {} failed: {}", event.peer, e);
+                None
+            }
+        };
+
+        let is_held_relay = self
+            .relay_addresses
+            .iter()
+            .any(|addr| circuit_relay_peer(addr) == Some(event.peer));
+        if !is_held_relay {
+            return;
+        }
+
+        let liveness = self
+            .relay_liveness
+            .entry(event.peer)
+            .or_insert_with(|| RelayLiveness {
+                relay_peer_id: event.peer.to_string(),
+                health: RelayHealth::Alive,
+                rtt_ms: None,
+                last_pong_at: None,
+                consecutive_failures: 0,
+            });
+        let previous_health = liveness.health;
+        apply_relay_ping_result(liveness, rtt, chrono::Utc::now().timestamp());
+        if liveness.health == previous_health {
+            return;
+        }
+
+        match liveness.health {
+            RelayHealth::Degraded => warn!(
+                "Relay {} missed {} pings in a row; reachability through it is at risk",
+                event.peer, liveness.consecutive_failures
+            ),
+            RelayHealth::Alive => info!("Relay {} is answering pings again", event.peer),
+        }
+        let health_event = NetworkEvent::RelayHealthChanged {
+            relay_peer_id: liveness.relay_peer_id.clone(),
+            health: liveness.health,
+            rtt_ms: liveness.rtt_ms,
+        };
+        let _ = self.event_tx.send(health_event).await;
+    }
+
+    /// Handle identity exchange request/response events
+    async fn handle_identity_exchange_event(
+        &mut self,
+        event: request_response::Event<IdentityExchangeRequest, IdentityExchangeResponse>,
+    ) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request_id,
+                    request,
+                    channel,
+                } => {
+                    info!("Received identity request from {}", peer);
+                    self.handle_identity_request(peer, request_id, request, channel)
+                        .await;
+                }
+                request_response::Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    info!("Received identity response from {}", peer);
+                    self.handle_identity_response(peer, request_id, response)
+                        .await;
+                }
+            },
+            request_response::Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            } => {
+                // Reconciles are retried on the next connect
+                if self
+                    .pending_permission_reconciles
+                    .remove(&request_id)
+                    .is_some()
+                {
+                    debug!("Permission reconcile with {} failed: {}", peer, error);
+                    return;
+                }
+                // Automatic requests are best-effort; relays and other peers
+                // without the protocol are expected to refuse them
+                if self.pending_auto_identity.remove(&request_id).is_some() {
+                    debug!("Automatic identity request to {} failed: {}", peer, error);
+                    return;
+                }
+                warn!("Identity request to peer {} failed: {}", peer, error);
+                if let Some(reply) = self.pending_identity_fetches.remove(&request_id) {
+                    let _ = reply.send(Err(error.to_string()));
+                }
+                if let Some(reply) = self.pending_contact_list_fetches.remove(&request_id) {
+                    let _ = reply.send(Err(error.to_string()));
+                }
+                self.emit_request_failed(peer, IDENTITY_PROTOCOL, &error)
+                    .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Handle messaging protocol request/response events
+    async fn handle_messaging_event(
+        &mut self,
+        event: request_response::Event<MessagingRequest, MessagingResponse>,
+    ) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request_id,
+                    request,
+                    channel,
+                } => {
+                    debug!("Received message request from {}", peer);
+                    self.handle_messaging_request(peer, request_id, request, channel)
+                        .await;
+                }
+                request_response::Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    debug!("Received message response from {}", peer);
+                    let outcome = if response.success {
+                        MessageSendOutcome::Delivered
+                    } else {
+                        MessageSendOutcome::Rejected(
+                            response
+                                .error
+                                .unwrap_or_else(|| "Message rejected by peer".to_string()),
+                        )
+                    };
+                    self.complete_message_send(peer, request_id, outcome).await;
+                }
+            },
+            request_response::Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            } => {
+                warn!("Message request to peer {} failed: {}", peer, error);
+                self.emit_request_failed(peer, MESSAGING_PROTOCOL, &error)
+                    .await;
+                self.complete_message_send(
+                    peer,
+                    request_id,
+                    MessageSendOutcome::Failed(error.to_string()),
+                )
+                .await;
+            }
+            _ => {}
+        }
+    }
+
+    /// Resolve a queued message send and tell the UI how it went
+    async fn complete_message_send(
+        &mut self,
+        peer: PeerId,
+        request_id: request_response::OutboundRequestId,
+        outcome: MessageSendOutcome,
+    ) {
+        if let Some((key, kind)) = self.pending_outbound_sends.remove(&request_id) {
+            self.complete_outbound_send(peer, key, kind, outcome).await;
+            return;
+        }
+        let Some(pending) = self.pending_message_sends.remove(&request_id) else {
+            return;
+        };
+
+        let error = match &outcome {
+            MessageSendOutcome::Delivered => None,
+            MessageSendOutcome::Rejected(e) | MessageSendOutcome::Failed(e) => Some(e.clone()),
+        };
+        let _ = self
+            .event_tx
+            .send(NetworkEvent::MessageSendResult {
+                correlation_id: pending.correlation_id,
+                peer_id: peer.to_string(),
+                delivered: error.is_none(),
+                error,
+            })
+            .await;
+        // The sender may have stopped waiting; the event above still reports it
+        let _ = pending.confirmation.send(outcome);
+    }
+
+    /// Take a delivered payload off the queue, or back off before retrying
+    /// it, and tell the UI how the attempt went for a message. Edits,
+    /// deletes and read receipts are only logged.
+    async fn complete_outbound_send(
+        &mut self,
+        peer: PeerId,
+        message_id: String,
+        kind: PendingOutboundKind,
+        outcome: MessageSendOutcome,
+    ) {
+        let is_message = kind == PendingOutboundKind::Message;
+        let error = match outcome {
+            MessageSendOutcome::Delivered => None,
+            MessageSendOutcome::Rejected(e) | MessageSendOutcome::Failed(e) => Some(e),
+        };
+
+        if let Some(ref messaging_service) = self.messaging_service {
+            match &error {
+                None => {
+                    if let Err(e) = messaging_service.outbound_delivered(&message_id) {
+                        warn!("Failed to record delivery of {}: {}", message_id, e);
                     }
-                    Err(e) => {
+                }
+                Some(error) => match messaging_service.outbound_failed(&message_id, error) {
+                    Ok(Some(attempts)) if !is_message => warn!(
+                        "Giving up on {} to {} after {} attempts: {}",
+                        message_id, peer, attempts, error
+                    ),
+                    Ok(Some(attempts)) => {
                         warn!(
-                            "Failed to request relay reservation {}: {}",
-                            circuit_listen_addr, e
+                            "Giving up on message {} to {} after {} attempts: {}",
+                            message_id, peer, attempts, error
                         );
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::MessageDeliveryFailed {
+                                message_id: message_id.clone(),
+                                peer_id: peer.to_string(),
+                                attempts,
+                                error: error.clone(),
+                            })
+                            .await;
                     }
+                    Ok(None) => debug!(
+                        "Message {} to {} will be retried: {}",
+                        message_id, peer, error
+                    ),
+                    Err(e) => warn!("Failed to record failed delivery of {}: {}", message_id, e),
+                },
+            }
+        }
+
+        if !is_message {
+            return;
+        }
+        let _ = self
+            .event_tx
+            .send(NetworkEvent::MessageSendResult {
+                correlation_id: message_id,
+                peer_id: peer.to_string(),
+                delivered: error.is_none(),
+                error,
+            })
+            .await;
+    }
+
+    /// Handle content sync protocol request/response events
+    async fn handle_content_sync_event(
+        &mut self,
+        event: request_response::Event<ContentSyncRequest, ContentSyncResponse>,
+    ) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request {
+                    request_id,
+                    request,
+                    channel,
+                } => {
+                    debug!("Received content sync request from {}", peer);
+                    self.handle_content_sync_request(peer, request_id, request, channel)
+                        .await;
                 }
+                request_response::Message::Response {
+                    request_id,
+                    response,
+                } => {
+                    debug!("Received content sync response from {}", peer);
+                    let outcome = self
+                        .handle_content_sync_response(peer, request_id, response)
+                        .await;
+                    self.record_sync_score(peer, &outcome);
+                    self.record_post_delivery(request_id, &outcome);
+                    self.record_peer_sync_outcome(request_id, outcome);
+                }
+            },
+            request_response::Event::OutboundFailure {
+                peer,
+                request_id,
+                error,
+                ..
+            } => {
+                warn!("Content sync request to peer {} failed: {}", peer, error);
+                self.emit_request_failed(peer, CONTENT_SYNC_PROTOCOL, &error)
+                    .await;
+                let outcome = ContentSyncOutcome::Failed(error.to_string());
+                self.record_sync_score(peer, &outcome);
+                self.record_post_delivery(request_id, &outcome);
+                self.record_peer_sync_outcome(request_id, outcome);
             }
+            _ => {}
+        }
+    }
+
+    /// Update a peer's sync track record from a manifest or fetch outcome
+    fn record_sync_score(&mut self, peer: PeerId, outcome: &ContentSyncOutcome) {
+        let score = self.peer_sync_scores.entry(peer).or_default();
+        match outcome {
+            ContentSyncOutcome::Manifest { .. } | ContentSyncOutcome::PostStored => {
+                score.successes = score.successes.saturating_add(1);
+            }
+            // A busy peer is still a good source, just not right now
+            ContentSyncOutcome::Failed(error) if error == CONTENT_SYNC_BUSY_ERROR => {}
+            ContentSyncOutcome::Failed(_) => score.failures = score.failures.saturating_add(1),
+            ContentSyncOutcome::ViewReported | ContentSyncOutcome::AnnouncementDelivered => {}
+        }
+    }
+
+    /// Tell the app an outbound request failed so the UI can offer a retry
+    async fn emit_request_failed(
+        &self,
+        peer: PeerId,
+        protocol: &str,
+        error: &request_response::OutboundFailure,
+    ) {
+        let _ = self
+            .event_tx
+            .send(NetworkEvent::RequestFailed {
+                peer_id: peer.to_string(),
+                protocol: protocol.to_string(),
+                reason: error.to_string(),
+            })
+            .await;
+    }
+
+    /// Handle board sync protocol events (messages and outbound failures)
+    async fn handle_board_sync_event(
+        &mut self,
+        event: request_response::Event<WireBoardSyncRequest, WireBoardSyncResponse>,
+    ) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
+                request_response::Message::Request { channel, .. } => {
+                    // Client doesn't serve board requests; send error
+                    let _ = self.swarm.behaviour_mut().board_sync.send_response(
+                        channel,
+                        WireBoardSyncResponse::Error {
+                            error: "Not a relay server".to_string(),
+                        },
+                    );
+                }
+                request_response::Message::Response { response, .. } => {
+                    self.handle_board_sync_response(peer, response).await;
+                }
+            },
+
+            request_response::Event::OutboundFailure { peer, error, .. } => {
+                // Clean up any pending community probe / registration state.
+                // This happens when the relay doesn't support the board sync protocol.
+                let was_probe = self.pending_community_probes.remove(&peer).is_some();
+                let was_registration = self.pending_board_registrations.remove(&peer);
+                if was_probe {
+                    self.non_community_relays.insert(peer);
+                }
+                if was_probe || was_registration {
+                    debug!(
+                        "Relay {} does not support board sync protocol (outbound failure: {})",
+                        peer, error
+                    );
+                } else {
+                    warn!("Board sync outbound failure to peer {}: {}", peer, error);
+                    self.emit_request_failed(peer, BOARD_SYNC_PROTOCOL, &error)
+                        .await;
+                    let _ = self
+                        .event_tx
+                        .send(NetworkEvent::BoardSyncError {
+                            relay_peer_id: peer.to_string(),
+                            error: format!("Failed to reach relay: {}", error),
+                        })
+                        .await;
+                }
+            }
+
+            _ => {}
+        }
+    }
+
+    /// Remember the circuit limits a relay advertised
+    fn record_relay_limits(
+        &mut self,
+        relay_peer_id: PeerId,
+        max_duration: Option<Duration>,
+        max_bytes: Option<u64>,
+    ) {
+        let limits = RelayCircuitLimits {
+            relay_peer_id: relay_peer_id.to_string(),
+            max_duration_secs: max_duration.map(|d| d.as_secs()),
+            max_bytes,
+        };
+        if self.relay_limits.get(&relay_peer_id) != Some(&limits) {
+            info!(
+                "Relay {} circuit limits: {} bytes, {} seconds",
+                relay_peer_id,
+                max_bytes.map_or("unlimited".to_string(), |b| b.to_string()),
+                limits
+                    .max_duration_secs
+                    .map_or("unlimited".to_string(), |s| s.to_string())
+            );
+            self.relay_limits.insert(relay_peer_id, limits);
+        }
+    }
+
+    /// Check a transfer of `len` bytes to `peer` fits the byte limit of the
+    /// relay circuit it is connected through, if any
+    fn check_relay_byte_limit(&self, peer: &PeerId, len: usize) -> std::result::Result<(), String> {
+        let Some(relay_peer_id) = self
+            .connected_peers
+            .get(peer)
+            .and_then(|info| info.addresses.first())
+            .and_then(|addr| addr.parse::<Multiaddr>().ok())
+            .and_then(|addr| circuit_relay_peer(&addr))
+        else {
+            return Ok(());
+        };
+
+        let max_bytes = match self.relay_limits.get(&relay_peer_id) {
+            Some(limits) => limits.max_bytes,
+            None => self.config.assumed_relay_max_bytes,
+        };
+        match max_bytes {
+            Some(max_bytes) if len as u64 > max_bytes => Err(format!(
+                "Transfer of {} bytes exceeds the {} byte circuit limit of relay {}; \
+                 a direct connection is needed",
+                len, max_bytes, relay_peer_id
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    /// Refuse board operations against a relay whose community probe failed,
+    /// so the UI can tell "doesn't host boards" apart from other failures
+    fn reject_non_community_relay(&self, relay_peer_id: &PeerId) -> Option<NetworkResponse> {
+        self.non_community_relays.contains(relay_peer_id).then(|| {
+            NetworkResponse::NotCommunityRelay(format!(
+                "Relay {} does not host community boards",
+                relay_peer_id
+            ))
+        })
+    }
+
+    /// Send a signed heartbeat to every connected peer that is a contact
+    fn send_heartbeats(&mut self) {
+        let Some(ref contacts_service) = self.contacts_service else {
+            return;
+        };
+
+        let heartbeat = match self.create_heartbeat() {
+            Ok(heartbeat) => heartbeat,
+            Err(e) => {
+                debug!("Skipping heartbeat round: {}", e);
+                return;
+            }
+        };
+
+        let peers: Vec<PeerId> = self
+            .connected_peers
+            .keys()
+            .filter(|peer| {
+                contacts_service
+                    .is_contact(&peer.to_string())
+                    .unwrap_or(false)
+            })
+            .copied()
+            .collect();
+
+        for peer in peers {
+            self.swarm
+                .behaviour_mut()
+                .presence
+                .send_request(&peer, heartbeat.clone());
+        }
+    }
+
+    /// Send the next batch of automatic identity requests.
+    ///
+    /// Peers that disconnected, became contacts or turned out to be relays
+    /// while queued are skipped without counting against the batch.
+    fn send_auto_identity_requests(&mut self) {
+        let Some(contacts_service) = self.contacts_service.clone() else {
+            return;
+        };
+
+        let request = match self.create_identity_request() {
+            Ok(request) => request,
+            Err(e) => {
+                debug!("Skipping automatic identity requests: {}", e);
+                return;
+            }
+        };
+
+        let mut sent = 0;
+        while sent < self.config.auto_identity_batch_size {
+            let Some(peer_id) = self.auto_identity_queue.pop() else {
+                break;
+            };
+            if !self.connected_peers.contains_key(&peer_id) || self.is_known_relay(&peer_id) {
+                continue;
+            }
+            // Blocked peers are contacts too, so this also leaves them alone
+            match contacts_service.is_contact(&peer_id.to_string()) {
+                Ok(false) => {}
+                Ok(true) => continue,
+                Err(e) => {
+                    warn!("Failed to check contact status of {}: {}", peer_id, e);
+                    continue;
+                }
+            }
+
+            let request_id = self
+                .swarm
+                .behaviour_mut()
+                .identity_exchange
+                .send_request(&peer_id, request.clone());
+            self.pending_auto_identity.insert(request_id, peer_id);
+            sent += 1;
+        }
+
+        if sent > 0 {
+            debug!("Sent {} automatic identity requests", sent);
+        }
+    }
+
+    /// Whether the peer's identity was stored within the cooldown and it is
+    /// still a contact, so asking again would only repeat the exchange
+    fn identity_recently_exchanged(&self, peer_id: &PeerId) -> bool {
+        if !self
+            .identity_exchanges
+            .is_cooling_down(peer_id, Instant::now())
+        {
+            return false;
+        }
+        self.contacts_service
+            .as_ref()
+            .is_some_and(|contacts_service| {
+                contacts_service
+                    .is_contact(&peer_id.to_string())
+                    .unwrap_or(false)
+            })
+    }
+
+    /// Whether the peer is a relay we reserved on, probed or registered with
+    fn is_known_relay(&self, peer_id: &PeerId) -> bool {
+        self.relay_limits.contains_key(peer_id)
+            || self.relay_liveness.contains_key(peer_id)
+            || self.pending_relay_reservations.contains_key(peer_id)
+            || self.pending_community_probes.contains_key(peer_id)
+            || self.community_relays.contains_key(peer_id)
+            || self.non_community_relays.contains(peer_id)
+    }
+
+    /// Request new posts for every subscribed board whose relay is connected.
+    ///
+    /// Returns the delay until the next round: the normal interval if a sync
+    /// was sent (or there is nothing to sync), otherwise an exponentially
+    /// growing backoff so an unreachable relay isn't polled in a tight loop.
+    fn sync_subscribed_boards(&mut self) -> Duration {
+        let interval = self.config.board_sync_interval;
+        let Some(ref board_service) = self.board_service else {
+            return interval;
+        };
+
+        let subscriptions = match board_service.get_subscriptions() {
+            Ok(subscriptions) => subscriptions,
+            Err(e) => {
+                warn!("Failed to load board subscriptions: {}", e);
+                return interval;
+            }
+        };
+        if subscriptions.is_empty() {
+            self.board_sync_backoff = interval;
+            return interval;
+        }
+
+        let mut synced = 0;
+        for subscription in &subscriptions {
+            let Ok(relay_peer_id) = subscription.relay_peer_id.parse::<PeerId>() else {
+                continue;
+            };
+            if !self.connected_peers.contains_key(&relay_peer_id) {
+                continue;
+            }
+            match self.send_board_sync_request(relay_peer_id, &subscription.board_id) {
+                Ok(()) => synced += 1,
+                Err(e) => warn!(
+                    "Failed to sync subscribed board {} on {}: {}",
+                    subscription.board_id, relay_peer_id, e
+                ),
+            }
+        }
+
+        if synced > 0 {
+            debug!("Background sync requested for {} subscribed boards", synced);
+            self.board_sync_backoff = interval;
+        } else {
+            self.board_sync_backoff =
+                (self.board_sync_backoff * 2).min(self.config.board_sync_max_backoff);
+            debug!(
+                "No subscribed relays reachable, next board sync in {:?}",
+                self.board_sync_backoff
+            );
+        }
+        self.board_sync_backoff
+    }
+
+    /// Request posts newer than the local sync cursor for a board
+    fn send_board_sync_request(&mut self, relay_peer_id: PeerId, board_id: &str) -> Result<()> {
+        let Some(ref board_service) = self.board_service else {
+            return Err(AppError::Internal("Board service unavailable".to_string()));
+        };
+
+        let after_timestamp = board_service
+            .get_sync_cursor(&relay_peer_id.to_string(), board_id)
+            .unwrap_or(None);
+
+        let req = board_service.create_get_board_posts_request(board_id, after_timestamp, 50)?;
+        let request = WireBoardSyncRequest::GetBoardPosts {
+            requester_peer_id: req.requester_peer_id,
+            board_id: req.board_id,
+            after_timestamp: req.after_timestamp,
+            limit: req.limit,
+            timestamp: req.timestamp,
+            signature: req.signature,
+        };
+        self.swarm
+            .behaviour_mut()
+            .board_sync
+            .send_request(&relay_peer_id, request);
+        Ok(())
+    }
+
+    /// Show the best open connection to a peer in its `PeerInfo`
+    fn refresh_connection_type(&mut self, peer_id: &PeerId) {
+        let Some((connection_type, relay)) = self.peer_connections.connection_type(peer_id) else {
+            return;
+        };
+        if let Some(peer_info) = self.connected_peers.get_mut(peer_id) {
+            peer_info.connection_type = connection_type;
+            peer_info.relay_peer_id = relay.map(|relay| relay.to_string());
         }
     }
 
-    /// Handle Kademlia DHT events
-    async fn handle_kademlia_event(&mut self, event: kad::Event) {
-        if let kad::Event::RoutingUpdated { peer, .. } = event {
-            debug!("Kademlia routing updated for peer: {}", peer);
-        }
+    /// Ask `peer_id` for a media file by hash; the response is stored once verified
+    fn send_media_fetch_request(&mut self, peer_id: PeerId, media_hash: String) -> Result<()> {
+        use super::protocols::media_sync::MediaFetchRequest;
+
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity available".to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let signable = crate::services::SignableMediaFetchRequest {
+            media_hash: media_hash.clone(),
+            requester_peer_id: identity.peer_id.clone(),
+            timestamp: now,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        let request = MediaFetchRequest {
+            media_hash,
+            requester_peer_id: identity.peer_id,
+            timestamp: now,
+            signature,
+        };
+        self.swarm
+            .behaviour_mut()
+            .media_sync
+            .send_request(&peer_id, request);
+        Ok(())
     }
 
-    /// Handle ping protocol events
-    fn handle_ping_event(&mut self, event: ping::Event) {
-        if let Ok(rtt) = event.result {
-            debug!("Ping to {} succeeded: {:?}", event.peer, rtt);
+    /// Fetch the audio of a voice note just received from `peer` unless it's
+    /// already stored. Failures are retried by the media preloader.
+    fn fetch_voice_note_audio(&mut self, peer: PeerId, voice: &crate::services::VoiceNote) {
+        let already_stored = self
+            .media_service
+            .as_ref()
+            .is_none_or(|media_service| media_service.has_media(&voice.media_hash));
+        if already_stored {
+            return;
+        }
+        if let Err(e) = self.send_media_fetch_request(peer, voice.media_hash.clone()) {
+            warn!(
+                "Failed to request voice note audio {} from {}: {}",
+                voice.media_hash, peer, e
+            );
         }
     }
 
-    /// Handle identity exchange request/response events
-    async fn handle_identity_exchange_event(
-        &mut self,
-        event: request_response::Event<IdentityExchangeRequest, IdentityExchangeResponse>,
-    ) {
-        if let request_response::Event::Message { peer, message, .. } = event {
-            match message {
-                request_response::Message::Request {
-                    request_id,
-                    request,
-                    channel,
-                } => {
-                    info!("Received identity request from {}", peer);
-                    self.handle_identity_request(peer, request_id, request, channel)
-                        .await;
-                }
-                request_response::Message::Response {
-                    request_id,
-                    response,
-                } => {
-                    info!("Received identity response from {}", peer);
-                    self.handle_identity_response(peer, request_id, response)
-                        .await;
-                }
-            }
+    /// Verify a heartbeat received from `peer` and update its last-seen time
+    fn process_heartbeat(&self, peer: PeerId, heartbeat: &Heartbeat) {
+        let Some(ref contacts_service) = self.contacts_service else {
+            return;
+        };
+
+        if heartbeat.peer_id != peer.to_string() {
+            warn!(
+                "Heartbeat peer ID mismatch: expected {}, got {}",
+                peer, heartbeat.peer_id
+            );
+            return;
         }
-    }
 
-    /// Handle messaging protocol request/response events
-    async fn handle_messaging_event(
-        &mut self,
-        event: request_response::Event<MessagingRequest, MessagingResponse>,
-    ) {
-        if let request_response::Event::Message { peer, message, .. } = event {
-            match message {
-                request_response::Message::Request {
-                    request_id,
-                    request,
-                    channel,
-                } => {
-                    debug!("Received message request from {}", peer);
-                    self.handle_messaging_request(peer, request_id, request, channel)
-                        .await;
-                }
-                request_response::Message::Response {
-                    request_id: _,
-                    response: _,
-                } => {
-                    debug!("Received message response from {}", peer);
-                    // Handle response (e.g., update message delivery status)
-                }
-            }
+        if let Err(e) = contacts_service.process_heartbeat(
+            &heartbeat.peer_id,
+            heartbeat.timestamp,
+            &heartbeat.signature,
+        ) {
+            debug!("Rejected heartbeat from {}: {}", peer, e);
         }
     }
 
-    /// Handle content sync protocol request/response events
-    async fn handle_content_sync_event(
-        &mut self,
-        event: request_response::Event<ContentSyncRequest, ContentSyncResponse>,
-    ) {
-        if let request_response::Event::Message { peer, message, .. } = event {
-            match message {
+    /// Handle presence heartbeat events
+    fn handle_presence_event(&mut self, event: request_response::Event<Heartbeat, Heartbeat>) {
+        match event {
+            request_response::Event::Message { peer, message, .. } => match message {
                 request_response::Message::Request {
-                    request_id,
-                    request,
-                    channel,
+                    request, channel, ..
                 } => {
-                    debug!("Received content sync request from {}", peer);
-                    self.handle_content_sync_request(peer, request_id, request, channel)
-                        .await;
+                    // With heartbeats disabled we neither record nor answer,
+                    // so we don't reveal our presence
+                    if !self.config.enable_heartbeat {
+                        return;
+                    }
+                    self.process_heartbeat(peer, &request);
+
+                    match self.create_heartbeat() {
+                        Ok(heartbeat) => {
+                            if let Err(e) = self
+                                .swarm
+                                .behaviour_mut()
+                                .presence
+                                .send_response(channel, heartbeat)
+                            {
+                                debug!("Failed to send heartbeat response: {:?}", e);
+                            }
+                        }
+                        Err(e) => {
+                            debug!("Failed to create heartbeat response: {}", e);
+                        }
+                    }
                 }
-                request_response::Message::Response {
-                    request_id,
-                    response,
-                } => {
-                    debug!("Received content sync response from {}", peer);
-                    self.handle_content_sync_response(peer, request_id, response)
-                        .await;
+                request_response::Message::Response { response, .. } => {
+                    self.process_heartbeat(peer, &response);
                 }
+            },
+            request_response::Event::OutboundFailure { peer, error, .. } => {
+                debug!("Heartbeat to peer {} failed: {}", peer, error);
             }
+            _ => {}
         }
     }
 
-    /// Handle board sync protocol events (messages and outbound failures)
-    async fn handle_board_sync_event(
+    /// Handle in-call data channel events
+    async fn handle_call_data_event(
         &mut self,
-        event: request_response::Event<WireBoardSyncRequest, WireBoardSyncResponse>,
+        event: request_response::Event<CallDataFrame, CallDataAck>,
     ) {
         match event {
             request_response::Event::Message { peer, message, .. } => match message {
-                request_response::Message::Request { channel, .. } => {
-                    // Client doesn't serve board requests; send error
-                    let _ = self.swarm.behaviour_mut().board_sync.send_response(
-                        channel,
-                        WireBoardSyncResponse::Error {
-                            error: "Not a relay server".to_string(),
-                        },
-                    );
+                request_response::Message::Request {
+                    request, channel, ..
+                } => {
+                    let ack = match self.process_call_data(peer, &request) {
+                        Ok(()) => {
+                            let _ = self
+                                .event_tx
+                                .send(NetworkEvent::CallDataReceived {
+                                    call_id: request.call_id,
+                                    peer_id: peer.to_string(),
+                                    payload: request.payload,
+                                    timestamp: request.timestamp,
+                                })
+                                .await;
+                            CallDataAck {
+                                accepted: true,
+                                error: None,
+                            }
+                        }
+                        Err(e) => {
+                            debug!("Rejected call data from {}: {}", peer, e);
+                            CallDataAck {
+                                accepted: false,
+                                error: Some(e.to_string()),
+                            }
+                        }
+                    };
+
+                    if let Err(e) = self
+                        .swarm
+                        .behaviour_mut()
+                        .call_data
+                        .send_response(channel, ack)
+                    {
+                        debug!("Failed to send call data ack: {:?}", e);
+                    }
                 }
                 request_response::Message::Response { response, .. } => {
-                    self.handle_board_sync_response(peer, response).await;
+                    if !response.accepted {
+                        let reason = response
+                            .error
+                            .unwrap_or_else(|| "Call data rejected".to_string());
+                        warn!("Peer {} rejected call data: {}", peer, reason);
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::RequestFailed {
+                                peer_id: peer.to_string(),
+                                protocol: CALL_DATA_PROTOCOL.to_string(),
+                                reason,
+                            })
+                            .await;
+                    }
                 }
             },
-
             request_response::Event::OutboundFailure { peer, error, .. } => {
-                // Clean up any pending community probe / registration state.
-                // This happens when the relay doesn't support the board sync protocol.
-                let was_probe = self.pending_community_probes.remove(&peer).is_some();
-                let was_registration = self.pending_board_registrations.remove(&peer);
-                if was_probe || was_registration {
-                    debug!(
-                        "Relay {} does not support board sync protocol (outbound failure: {})",
-                        peer, error
-                    );
-                } else {
-                    warn!("Board sync outbound failure to peer {}: {}", peer, error);
-                    let _ = self
-                        .event_tx
-                        .send(NetworkEvent::BoardSyncError {
-                            relay_peer_id: peer.to_string(),
-                            error: format!("Failed to reach relay: {}", error),
-                        })
-                        .await;
-                }
+                warn!("Call data to peer {} failed: {}", peer, error);
+                self.emit_request_failed(peer, CALL_DATA_PROTOCOL, &error)
+                    .await;
             }
-
             _ => {}
         }
     }
 
+    /// Validate an inbound data frame against the call it claims to belong to
+    fn process_call_data(&self, peer: PeerId, frame: &CallDataFrame) -> Result<()> {
+        if frame.sender_peer_id != peer.to_string() {
+            return Err(AppError::PermissionDenied(
+                "Sender does not match connection peer".to_string(),
+            ));
+        }
+
+        let calling_service = self
+            .calling_service
+            .as_ref()
+            .ok_or_else(|| AppError::Internal("Calling service not available".to_string()))?;
+
+        calling_service.process_incoming_call_data(&IncomingCallDataParams {
+            call_id: &frame.call_id,
+            sender_peer_id: &frame.sender_peer_id,
+            payload: &frame.payload,
+            timestamp: frame.timestamp,
+            signature: &frame.signature,
+        })
+    }
+
     /// Handle media sync events (P2P image transfer)
     async fn handle_media_sync_event(
         &mut self,
@@ -1466,6 +4072,8 @@ impl NetworkService {
             },
             request_response::Event::OutboundFailure { peer, error, .. } => {
                 warn!("Media fetch outbound failure to peer {}: {}", peer, error);
+                self.emit_request_failed(peer, MEDIA_SYNC_PROTOCOL, &error)
+                    .await;
             }
             request_response::Event::InboundFailure { peer, error, .. } => {
                 warn!("Media fetch inbound failure from peer {}: {}", peer, error);
@@ -1482,21 +4090,31 @@ impl NetworkService {
     ) -> super::protocols::media_sync::MediaFetchResponse {
         use super::protocols::media_sync::MediaFetchResponse;
 
-        // Verify requester is in contacts
-        if let Some(ref contacts_service) = self.contacts_service {
-            match contacts_service.is_contact(&request.requester_peer_id) {
+        // Chat partners may fetch the voice notes we exchanged with them
+        let shared_voice_note = self.messaging_service.as_ref().is_some_and(|service| {
+            service
+                .voice_note_shared_with(&request.media_hash, &request.requester_peer_id)
+                .unwrap_or(false)
+        });
+
+        // Otherwise verify we granted the requester file transfers
+        if let Some(ref permissions_service) = self.permissions_service {
+            match permissions_service
+                .peer_has_capability(&request.requester_peer_id, Capability::FileTransfer)
+            {
+                _ if shared_voice_note => {}
                 Ok(true) => {}
                 Ok(false) => {
                     info!(
-                        "Media fetch denied: {} is not a contact",
+                        "Media fetch denied: {} has no file transfer permission",
                         request.requester_peer_id
                     );
                     return MediaFetchResponse::Error {
-                        error: "Not a contact".to_string(),
+                        error: "No file transfer permission".to_string(),
                     };
                 }
                 Err(e) => {
-                    warn!("Error checking contact status: {}", e);
+                    warn!("Error checking file transfer permission: {}", e);
                     return MediaFetchResponse::Error {
                         error: "Internal error".to_string(),
                     };
@@ -1534,19 +4152,28 @@ impl NetworkService {
                     .get_media_path(&request.media_hash)
                     .ok()
                     .and_then(|p| {
-                        p.extension()
-                            .and_then(|e| e.to_str())
-                            .map(|ext| match ext {
-                                "jpg" | "jpeg" => "image/jpeg",
-                                "png" => "image/png",
-                                "gif" => "image/gif",
-                                "webp" => "image/webp",
-                                _ => "application/octet-stream",
-                            })
+                        p.extension().and_then(|e| e.to_str()).map(|ext| match ext {
+                            "jpg" | "jpeg" => "image/jpeg",
+                            "png" => "image/png",
+                            "gif" => "image/gif",
+                            "webp" => "image/webp",
+                            "ogg" => "audio/ogg",
+                            "weba" => "audio/webm",
+                            "m4a" => "audio/mp4",
+                            "mp3" => "audio/mpeg",
+                            "wav" => "audio/wav",
+                            _ => "application/octet-stream",
+                        })
                     })
                     .unwrap_or("application/octet-stream")
                     .to_string();
 
+                // Fail clearly rather than have the relay cut the transfer short
+                if let Err(error) = self.check_relay_byte_limit(&peer, data.len()) {
+                    warn!("Not serving media {}: {}", request.media_hash, error);
+                    return MediaFetchResponse::Error { error };
+                }
+
                 info!(
                     "Serving media {} ({} bytes, {}) to peer {}",
                     request.media_hash,
@@ -1595,6 +4222,21 @@ impl NetworkService {
                     return;
                 }
 
+                // Audio for a voice note must be what the signed message announced
+                let voice_note = self
+                    .messaging_service
+                    .as_ref()
+                    .and_then(|service| service.voice_note_for_media(&media_hash).ok().flatten());
+                if let Some(voice) = voice_note {
+                    if let Err(e) = voice.validate_audio(&data) {
+                        warn!(
+                            "Rejected voice note audio {} from {}: {}",
+                            media_hash, peer, e
+                        );
+                        return;
+                    }
+                }
+
                 // Store via MediaStorageService
                 if let Some(ref media_service) = self.media_service {
                     match media_service.store_media(&data, &mime_type) {
@@ -1635,13 +4277,17 @@ impl NetworkService {
             relay::client::Event::ReservationReqAccepted {
                 relay_peer_id,
                 renewal,
-                limit: _,
+                limit,
             } => {
                 let local_peer_id = *self.swarm.local_peer_id();
                 info!(
                     "Relay reservation accepted by {} (renewal: {})",
                     relay_peer_id, renewal
                 );
+                let (max_duration, max_bytes) = limit
+                    .map(|limit| (limit.duration(), limit.data_in_bytes()))
+                    .unwrap_or_default();
+                self.record_relay_limits(relay_peer_id, max_duration, max_bytes);
 
                 // Build full relay circuit address WITH transport prefix.
                 // Look up the relay peer's transport address from connected peers
@@ -1723,14 +4369,8 @@ impl NetworkService {
                 }
 
                 // Update NAT status to Private (we're behind NAT but reachable via relay)
-                if self.nat_status != NatStatus::Public {
-                    self.nat_status = NatStatus::Private;
-                    let _ = self
-                        .event_tx
-                        .send(NetworkEvent::NatStatusChanged {
-                            status: self.nat_status,
-                        })
-                        .await;
+                if self.detected_nat_status != NatStatus::Public {
+                    self.set_detected_nat_status(NatStatus::Private).await;
                 }
 
                 // Probe the relay for community support.
@@ -1738,7 +4378,9 @@ impl NetworkService {
                 // Step 2 (after PeerRegistered response): Send ListBoards to detect boards.
                 // If the relay responds with a BoardList, it's a community relay and we auto-join.
                 // If it returns an error (non-community relay), the probe silently fails.
-                if !self.community_relays.contains_key(&relay_peer_id) {
+                if !self.community_relays.contains_key(&relay_peer_id)
+                    && !self.left_community_relays.contains(&relay_peer_id)
+                {
                     if let Some(ref board_service) = self.board_service {
                         // Reconstruct the relay's original multiaddr for storing later
                         let relay_addr_str =
@@ -1785,9 +4427,13 @@ impl NetworkService {
 
             relay::client::Event::OutboundCircuitEstablished {
                 relay_peer_id,
-                limit: _,
+                limit,
             } => {
                 debug!("Outbound circuit established via relay {}", relay_peer_id);
+                let (max_duration, max_bytes) = limit
+                    .map(|limit| (limit.duration(), limit.data_in_bytes()))
+                    .unwrap_or_default();
+                self.record_relay_limits(relay_peer_id, max_duration, max_bytes);
             }
 
             relay::client::Event::InboundCircuitEstablished {
@@ -1804,18 +4450,19 @@ impl NetworkService {
     async fn handle_dcutr_event(&mut self, event: dcutr::Event) {
         let remote_peer_id = event.remote_peer_id;
         match event.result {
-            Ok(_connection_id) => {
+            Ok(connection_id) => {
                 info!(
                     "Direct connection upgrade succeeded with {}",
                     remote_peer_id
                 );
+                self.peer_connections
+                    .hole_punched(remote_peer_id, connection_id);
+                self.refresh_connection_type(&remote_peer_id);
                 // Emit event to frontend
-                let _ = self
-                    .event_tx
-                    .send(NetworkEvent::HolePunchSucceeded {
+                self.event_tx
+                    .send_droppable(NetworkEvent::HolePunchSucceeded {
                         peer_id: remote_peer_id.to_string(),
-                    })
-                    .await;
+                    });
             }
             Err(error) => {
                 debug!(
@@ -1828,50 +4475,211 @@ impl NetworkService {
     }
 
     /// Handle AutoNAT events
+    ///
+    /// The NAT status only follows AutoNAT once `nat_confidence_threshold`
+    /// consecutive outbound probes agree, so a single flaky probe doesn't
+    /// start or stop relay use.
     async fn handle_autonat_event(&mut self, event: autonat::Event) {
         match event {
             autonat::Event::StatusChanged { old, new } => {
                 info!("AutoNAT status changed from {:?} to {:?}", old, new);
 
-                let new_nat_status = match new {
-                    autonat::NatStatus::Public(addr) => {
-                        info!("AutoNAT: We have a public address: {}", addr);
-                        // Store the external address
-                        if !self.external_addresses.contains(&addr) {
-                            self.external_addresses.push(addr.clone());
-                            let _ = self
-                                .event_tx
-                                .send(NetworkEvent::ExternalAddressDiscovered {
-                                    address: addr.to_string(),
-                                })
-                                .await;
-                        }
+                if let autonat::NatStatus::Public(addr) = new {
+                    info!("AutoNAT: We have a public address: {}", addr);
+                    // Store the external address
+                    if !self.external_addresses.contains(&addr) {
+                        self.external_addresses.push(addr.clone());
+                        self.event_tx
+                            .send_droppable(NetworkEvent::ExternalAddressDiscovered {
+                                address: addr.to_string(),
+                            });
+                    }
+                }
+            }
+
+            autonat::Event::OutboundProbe(probe) => {
+                let outcome = match probe {
+                    autonat::OutboundProbeEvent::Response { address, .. } => {
+                        debug!("AutoNAT probe reached us at {}", address);
                         NatStatus::Public
                     }
-                    autonat::NatStatus::Private => {
-                        info!("AutoNAT: We are behind NAT, attempting relay connection...");
-                        // Try to connect to relays if we haven't already
-                        if !self.relay_connection_attempted {
-                            self.connect_to_relays().await;
-                        }
+                    autonat::OutboundProbeEvent::Error {
+                        error:
+                            autonat::OutboundProbeError::Response(autonat::ResponseError::DialError),
+                        ..
+                    } => {
+                        debug!("AutoNAT probe could not dial us back");
                         NatStatus::Private
                     }
-                    autonat::NatStatus::Unknown => NatStatus::Unknown,
+                    // Requests and inconclusive failures say nothing about reachability
+                    _ => return,
                 };
 
-                if self.nat_status != new_nat_status {
-                    self.nat_status = new_nat_status;
-                    let _ = self
-                        .event_tx
-                        .send(NetworkEvent::NatStatusChanged {
-                            status: self.nat_status,
-                        })
-                        .await;
+                let Some(confirmed) = self.nat_probes.record(outcome) else {
+                    return;
+                };
+                if confirmed == self.detected_nat_status {
+                    return;
+                }
+                if confirmed == NatStatus::Private {
+                    info!("AutoNAT: We are behind NAT");
                 }
+                if let Some(nat_override) = self.nat_override {
+                    info!(
+                        "AutoNAT result {:?} ignored, NAT status manually set to {:?}",
+                        confirmed, nat_override
+                    );
+                }
+                self.set_detected_nat_status(confirmed).await;
+                self.connect_to_relays_if_private().await;
+            }
+
+            autonat::Event::InboundProbe(_) => {
+                // Probes we serve for other peers, no action needed
+            }
+        }
+    }
+
+    /// Record a detected NAT status, keeping any manual override in effect
+    async fn set_detected_nat_status(&mut self, status: NatStatus) {
+        self.detected_nat_status = status;
+        self.refresh_nat_status().await;
+    }
+
+    /// Recompute the effective NAT status and notify the frontend if it changed
+    async fn refresh_nat_status(&mut self) {
+        let effective = self.nat_override.unwrap_or(self.detected_nat_status);
+        if self.nat_status != effective {
+            self.nat_status = effective;
+            let _ = self
+                .event_tx
+                .send(NetworkEvent::NatStatusChanged {
+                    status: self.nat_status,
+                })
+                .await;
+        }
+    }
+
+    /// Try to connect to relays if we're behind NAT and haven't already
+    async fn connect_to_relays_if_private(&mut self) {
+        let behind_nat = matches!(self.nat_status, NatStatus::Private | NatStatus::BehindNat);
+        if behind_nat && !self.relay_connection_attempted {
+            info!("Behind NAT, attempting relay connection...");
+            self.connect_to_relays().await;
+        }
+    }
+
+    /// Start an outgoing dial and emit a `DialStarted` event for each address
+    /// it tries. Failures are reported as `DialFailed` with the same attempt
+    /// IDs; success shows up as the usual `PeerConnected`.
+    ///
+    /// Nothing is dialed for a peer that is already connected or being
+    /// dialed, which is reported as a benign outcome rather than an error.
+    async fn start_dial(
+        &mut self,
+        opts: DialOpts,
+        peer_id: PeerId,
+        addresses: &[Multiaddr],
+    ) -> std::result::Result<DialOutcome, DialError> {
+        if self.swarm.is_connected(&peer_id) {
+            debug!("Not dialing {}: already connected", peer_id);
+            return Ok(DialOutcome::AlreadyConnected);
+        }
+        if self.pending_dials.is_dialing(&peer_id) {
+            debug!("Not dialing {}: already connecting", peer_id);
+            return Ok(DialOutcome::AlreadyDialing);
+        }
+
+        let connection_id = opts.connection_id();
+        self.swarm.dial(opts)?;
+
+        let addresses: Vec<Option<Multiaddr>> = if addresses.is_empty() {
+            vec![None]
+        } else {
+            addresses.iter().cloned().map(Some).collect()
+        };
+        let mut attempts = Vec::with_capacity(addresses.len());
+        for address in addresses {
+            self.next_dial_attempt_id += 1;
+            let attempt_id = self.next_dial_attempt_id;
+            self.event_tx.send_droppable(NetworkEvent::DialStarted {
+                attempt_id,
+                peer_id: peer_id.to_string(),
+                address: address.as_ref().map(|a| a.to_string()),
+            });
+            attempts.push(DialAttempt {
+                attempt_id,
+                peer_id,
+                address,
+            });
+        }
+        self.pending_dials.insert(connection_id, peer_id, attempts);
+        Ok(DialOutcome::Started)
+    }
+
+    /// Remember a relay or bootstrap node to keep connected to
+    fn add_dial_target(&mut self, peer_id: PeerId, address: Multiaddr, is_relay: bool) {
+        self.dial_targets.insert(
+            peer_id,
+            DialTarget {
+                address,
+                is_relay,
+                misses: 0,
+                next_dial: Instant::now(),
+            },
+        );
+    }
+
+    /// Re-dial the relays and bootstrap nodes that aren't connected.
+    ///
+    /// A target still disconnected at the next pass waits twice as long
+    /// before it's dialed again, up to `reconcile_max_backoff`, so a node that
+    /// is down isn't dialed every pass. `reset` clears the backoff, for when
+    /// connectivity has just come back.
+    async fn reconcile_connections(&mut self, reset: bool) {
+        let now = Instant::now();
+        let mut due = Vec::new();
+        for (peer_id, target) in &mut self.dial_targets {
+            if self.swarm.is_connected(peer_id) {
+                target.misses = 0;
+                continue;
+            }
+            if reset {
+                target.misses = 0;
+                target.next_dial = now;
             }
+            if target.next_dial > now || self.pending_dials.is_dialing(peer_id) {
+                continue;
+            }
+            target.next_dial = now
+                + reconcile_backoff(
+                    self.config.reconcile_interval,
+                    self.config.reconcile_max_backoff,
+                    target.misses,
+                );
+            target.misses = target.misses.saturating_add(1);
+            due.push((*peer_id, target.address.clone(), target.is_relay));
+        }
+        if due.is_empty() {
+            return;
+        }
 
-            autonat::Event::InboundProbe(_) | autonat::Event::OutboundProbe(_) => {
-                // These are just probe events, no action needed
+        info!("Reconnecting to {} relays and bootstrap nodes", due.len());
+        for (peer_id, address, is_relay) in due {
+            match self
+                .start_dial(
+                    address.clone().into(),
+                    peer_id,
+                    std::slice::from_ref(&address),
+                )
+                .await
+            {
+                Ok(_) => {
+                    if is_relay {
+                        self.queue_relay_reservation(peer_id, address);
+                    }
+                }
+                Err(e) => warn!("Failed to redial {}: {}", address, e),
             }
         }
     }
@@ -1909,25 +4717,26 @@ impl NetworkService {
                             .add_address(&relay_peer_id, addr_without_peer.clone());
 
                         // Dial the relay
-                        if let Err(e) = self.swarm.dial(relay_addr.clone()) {
-                            warn!("Failed to dial relay {}: {}", relay_addr, e);
-                        } else {
-                            info!(
+                        match self
+                            .start_dial(
+                                relay_addr.clone().into(),
+                                relay_peer_id,
+                                std::slice::from_ref(&relay_addr),
+                            )
+                            .await
+                        {
+                            Ok(DialOutcome::Started) => info!(
                                 "Dial initiated to relay: {} (waiting for connection...)",
                                 relay_peer_id
-                            );
+                            ),
+                            Ok(outcome) => {
+                                info!("Relay {} not dialed: {:?}", relay_peer_id, outcome)
+                            }
+                            Err(e) => warn!("Failed to dial relay {}: {}", relay_addr, e),
                         }
 
-                        // Queue relay reservation for after Identify completes.
-                        // listen_on must be called AFTER the connection is fully negotiated
-                        // (Identify::Received), not immediately after dial — otherwise the
-                        // relay client transport doesn't know about the connection yet.
-                        self.pending_relay_reservations
-                            .insert(relay_peer_id, relay_addr.clone());
-                        info!(
-                            "Relay reservation queued for {} (will request after identify)",
-                            relay_peer_id
-                        );
+                        self.queue_relay_reservation(relay_peer_id, relay_addr.clone());
+                        self.add_dial_target(relay_peer_id, relay_addr, true);
                     }
                 }
                 Err(e) => {
@@ -1972,6 +4781,7 @@ impl NetworkService {
 
                     // Mark as community relay
                     self.community_relays.insert(peer, relay_addr.clone());
+                    self.non_community_relays.remove(&peer);
 
                     // Auto-join: store community locally
                     if let Err(e) = board_service.join_community(&relay_peer_id, &relay_addr, None)
@@ -2040,11 +4850,36 @@ impl NetworkService {
                         created_at: p.created_at,
                         deleted_at: p.deleted_at,
                         signature: p.signature.clone(),
+                        relay_observed_at: p.relay_observed_at,
+                        relay_signature: p.relay_signature.clone(),
+                        reaction_count: p.reaction_count,
+                        requester_reaction: p.requester_reaction.clone(),
                     })
                     .collect();
                 let post_count = storable.len();
                 match board_service.store_board_posts(&relay_peer_id, &storable) {
                     Ok(()) => {
+                        match board_service.record_new_subscribed_posts(
+                            &relay_peer_id,
+                            &board_id,
+                            &storable,
+                        ) {
+                            Ok(Some(count)) if count > 0 => {
+                                let _ = self
+                                    .event_tx
+                                    .send(NetworkEvent::NewBoardPosts {
+                                        relay_peer_id: relay_peer_id.clone(),
+                                        board_id: board_id.clone(),
+                                        count,
+                                    })
+                                    .await;
+                            }
+                            Ok(_) => {}
+                            Err(e) => {
+                                warn!("Failed to record new posts for board {}: {}", board_id, e);
+                            }
+                        }
+
                         let _ = self
                             .event_tx
                             .send(NetworkEvent::BoardPostsReceived {
@@ -2102,9 +4937,75 @@ impl NetworkService {
                     }
                 }
             }
+            WireBoardSyncResponse::PeerDeregistered { peer_id } => {
+                info!("Deregistered from relay {} as {}", peer, peer_id);
+            }
             WireBoardSyncResponse::PostDeleted { post_id } => {
                 info!("Board post {} deleted on relay {}", post_id, peer);
             }
+            WireBoardSyncResponse::ReactionRecorded {
+                post_id,
+                reaction_count,
+                requester_reaction,
+            } => {
+                if let Err(e) = board_service.store_post_reactions(
+                    &relay_peer_id,
+                    &post_id,
+                    reaction_count,
+                    requester_reaction.as_deref(),
+                ) {
+                    warn!(
+                        "Failed to store reactions for board post {}: {}",
+                        post_id, e
+                    );
+                }
+                let _ = self
+                    .event_tx
+                    .send(NetworkEvent::BoardPostReactionsUpdated {
+                        relay_peer_id,
+                        post_id,
+                        reaction_count,
+                        my_reaction: requester_reaction,
+                    })
+                    .await;
+            }
+            WireBoardSyncResponse::BoardCreated { board } => {
+                info!(
+                    "Board {} ({}) created on relay {}",
+                    board.name, board.board_id, peer
+                );
+                let board_data = vec![(
+                    board.board_id.clone(),
+                    board.name.clone(),
+                    board.description,
+                    board.is_default,
+                )];
+                match board_service.store_boards(&relay_peer_id, &board_data) {
+                    Ok(()) => {
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::BoardCreated {
+                                relay_peer_id,
+                                board_id: board.board_id,
+                                name: board.name,
+                            })
+                            .await;
+                    }
+                    Err(e) => {
+                        warn!("Failed to store created board from {}: {}", peer, e);
+                    }
+                }
+            }
+            WireBoardSyncResponse::InviteCreated { token } => {
+                info!("Community invite issued by relay {}", peer);
+                let _ = self
+                    .event_tx
+                    .send(NetworkEvent::CommunityInviteCreated {
+                        relay_peer_id,
+                        token,
+                    })
+                    .await;
+            }
             WireBoardSyncResponse::WallPostStored { post_id } => {
                 info!("Wall post {} stored on relay {}", post_id, peer);
                 let _ = self
@@ -2132,7 +5033,16 @@ impl NetworkService {
                 // Store received posts in local SQLite via content_sync_service
                 if let Some(ref content_sync_service) = self.content_sync_service {
                     for post in &posts {
-                        match content_sync_service.store_remote_post(&RemotePostParams {
+                        // Each attachment may be fetched later, so a post
+                        // naming too many is dropped whole
+                        if let Err(e) = crate::services::ensure_attachment_count(
+                            content_sync_service.db(),
+                            post.media_items.len(),
+                        ) {
+                            warn!("Skipping wall post {} from relay: {}", post.post_id, e);
+                            continue;
+                        }
+                        let params = RemotePostParams {
                             post_id: &post.post_id,
                             author_peer_id: &post.author_peer_id,
                             content_type: &post.content_type,
@@ -2141,12 +5051,29 @@ impl NetworkService {
                             lamport_clock: post.lamport_clock as u64,
                             created_at: post.created_at,
                             signature: &post.signature,
-                        }) {
+                            // Relay wall posts don't carry a scheme version;
+                            // they were all signed with the original one
+                            sig_version: SIG_VERSION_CBOR,
+                        };
+                        match content_sync_service.store_remote_post(&params) {
                             Ok(_) => {
                                 debug!(
                                     "Stored wall post {} from {} via relay",
                                     post.post_id, post.author_peer_id
                                 );
+                                if let Some(ref relay_signature) = post.relay_signature {
+                                    if let Err(e) = content_sync_service.record_relay_timestamp(
+                                        &relay_peer_id,
+                                        &params,
+                                        post.stored_at,
+                                        relay_signature,
+                                    ) {
+                                        warn!(
+                                            "Ignoring relay timestamp on wall post {}: {}",
+                                            post.post_id, e
+                                        );
+                                    }
+                                }
                             }
                             Err(e) => {
                                 warn!(
@@ -2169,7 +5096,10 @@ impl NetworkService {
                                     );
                                     let already_exists = existing
                                         .as_ref()
-                                        .map(|list| list.iter().any(|m| m.media_hash == media_item.media_hash))
+                                        .map(|list| {
+                                            list.iter()
+                                                .any(|m| m.media_hash == media_item.media_hash)
+                                        })
                                         .unwrap_or(false);
 
                                     if !already_exists {
@@ -2237,6 +5167,9 @@ impl NetworkService {
                 // an error and that's expected.
                 let was_probe = self.pending_community_probes.remove(&peer).is_some();
                 let was_registration = self.pending_board_registrations.remove(&peer);
+                if was_probe {
+                    self.non_community_relays.insert(peer);
+                }
                 if was_probe || was_registration {
                     debug!(
                         "Relay {} is not a community relay (probe returned error: {})",
@@ -2258,14 +5191,23 @@ impl NetworkService {
 
     async fn handle_identity_request(
         &mut self,
-        _peer: PeerId,
+        peer: PeerId,
         _request_id: request_response::InboundRequestId,
-        _request: IdentityExchangeRequest,
+        request: IdentityExchangeRequest,
         channel: ResponseChannel<IdentityExchangeResponse>,
     ) {
         // Get our libp2p peer ID (this is what other peers see us as)
         let local_peer_id = *self.swarm.local_peer_id();
 
+        let shared_contacts = match request.contact_list {
+            Some(ref contact_list) => self.shared_contacts_for(peer, &request, contact_list),
+            None => None,
+        };
+        let permission_ack = match request.permission_grants {
+            Some(ref grants) => self.permission_ack_for(peer, &request, grants),
+            None => None,
+        };
+
         // Get our identity info to respond with
         match self.identity_service.get_identity_info() {
             Ok(Some(info)) => {
@@ -2308,6 +5250,9 @@ impl NetworkService {
                     bio: info.bio,
                     timestamp,
                     signature,
+                    shared_contacts,
+                    permission_ack,
+                    supported_ciphers: Some(MessageCipher::supported_identifiers()),
                 };
 
                 if let Err(e) = self
@@ -2328,10 +5273,195 @@ impl NetworkService {
         }
     }
 
+    /// Decide which of our contacts, if any, to share with a peer asking for
+    /// contact suggestions. The request must be signed by the connected peer,
+    /// who must hold `ShareContacts`, and sharing must be turned on.
+    fn shared_contacts_for(
+        &self,
+        peer: PeerId,
+        request: &IdentityExchangeRequest,
+        contact_list: &ContactListRequest,
+    ) -> Option<Vec<SharedContactProto>> {
+        let contacts_service = self.contacts_service.as_ref()?;
+        let permissions_service = self.permissions_service.as_ref()?;
+
+        if request.requester_peer_id != peer.to_string() {
+            warn!(
+                "Contact list request from {} claims to be from {}",
+                peer, request.requester_peer_id
+            );
+            return None;
+        }
+
+        let public_key = match contacts_service.get_public_key(&request.requester_peer_id) {
+            Ok(Some(key)) => key,
+            Ok(None) => {
+                debug!("Contact list request from non-contact {}", peer);
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to look up contact {}: {}", peer, e);
+                return None;
+            }
+        };
+        let signable = SignableContactListRequest {
+            requester_peer_id: request.requester_peer_id.clone(),
+            responder_peer_id: self.swarm.local_peer_id().to_string(),
+            timestamp: contact_list.timestamp,
+        };
+        let verified = <[u8; 32]>::try_from(public_key.as_slice())
+            .ok()
+            .and_then(|bytes| ed25519_dalek::VerifyingKey::from_bytes(&bytes).ok())
+            .map(|key| {
+                crate::services::verify(&key, &signable, &contact_list.signature).unwrap_or(false)
+            })
+            .unwrap_or(false);
+        if !verified {
+            warn!("Invalid contact list request signature from {}", peer);
+            return None;
+        }
+
+        match permissions_service
+            .peer_has_capability(&request.requester_peer_id, Capability::ShareContacts)
+        {
+            Ok(true) => {}
+            Ok(false) => {
+                debug!("{} asked for our contacts without ShareContacts", peer);
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to check ShareContacts for {}: {}", peer, e);
+                return None;
+            }
+        }
+
+        match contacts_service.get_shareable_contacts(&request.requester_peer_id) {
+            Ok(shared) => shared.map(|contacts| {
+                contacts
+                    .into_iter()
+                    .map(|c| SharedContactProto {
+                        peer_id: c.peer_id,
+                        display_name: c.display_name,
+                    })
+                    .collect()
+            }),
+            Err(e) => {
+                warn!("Failed to load shareable contacts: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Store grants a contact resent to us and sign an ack for them. Only
+    /// the connected contact can resend its own grants.
+    fn permission_ack_for(
+        &self,
+        peer: PeerId,
+        request: &IdentityExchangeRequest,
+        grants: &[PermissionGrantProto],
+    ) -> Option<PermissionAckProto> {
+        let contacts_service = self.contacts_service.as_ref()?;
+        let permissions_service = self.permissions_service.as_ref()?;
+
+        if request.requester_peer_id != peer.to_string() {
+            warn!(
+                "Grants from {} claim to be from {}",
+                peer, request.requester_peer_id
+            );
+            return None;
+        }
+
+        let public_key = match contacts_service.get_public_key(&request.requester_peer_id) {
+            Ok(Some(key)) => key,
+            Ok(None) => {
+                debug!("Ignoring grants from non-contact {}", peer);
+                return None;
+            }
+            Err(e) => {
+                warn!("Failed to look up contact {}: {}", peer, e);
+                return None;
+            }
+        };
+
+        let grants: Vec<PermissionGrantMessage> = grants
+            .iter()
+            .filter_map(|grant| {
+                PermissionGrantMessage::from_signed_payload(
+                    grant.payload_cbor.clone(),
+                    grant.signature.clone(),
+                )
+                .map_err(|e| warn!("Malformed grant from {}: {}", peer, e))
+                .ok()
+            })
+            .collect();
+
+        match permissions_service.accept_reconciled_grants(
+            &request.requester_peer_id,
+            &grants,
+            &public_key,
+        ) {
+            Ok(ack) => {
+                debug!("Acknowledging {} grants from {}", ack.grant_ids.len(), peer);
+                Some(PermissionAckProto {
+                    grant_ids: ack.grant_ids,
+                    timestamp: ack.timestamp,
+                    signature: ack.signature,
+                })
+            }
+            Err(e) => {
+                warn!("Failed to accept grants from {}: {}", peer, e);
+                None
+            }
+        }
+    }
+
+    /// Mark the grants a contact acknowledged so they aren't resent
+    fn process_permission_ack(&self, peer: PeerId, ack: Option<PermissionAckProto>) {
+        let (Some(contacts_service), Some(permissions_service)) =
+            (&self.contacts_service, &self.permissions_service)
+        else {
+            return;
+        };
+        let Some(ack) = ack else {
+            debug!("{} did not acknowledge resent grants", peer);
+            return;
+        };
+
+        let public_key = match contacts_service.get_public_key(&peer.to_string()) {
+            Ok(Some(key)) => key,
+            Ok(None) => return,
+            Err(e) => {
+                warn!("Failed to look up contact {}: {}", peer, e);
+                return;
+            }
+        };
+        // The ack names us the way our identity requests did
+        let issuer_peer_id = match self.identity_service.get_peer_id() {
+            Ok(peer_id) => peer_id,
+            Err(e) => {
+                warn!("Failed to get our peer ID: {}", e);
+                return;
+            }
+        };
+        let ack = PermissionAckMessage {
+            issuer_peer_id,
+            subject_peer_id: peer.to_string(),
+            grant_ids: ack.grant_ids,
+            timestamp: ack.timestamp,
+            signature: ack.signature,
+        };
+        match permissions_service.process_permission_ack(&ack, &public_key) {
+            Ok(acknowledged) => {
+                info!("{} acknowledged {} grants", peer, acknowledged)
+            }
+            Err(e) => warn!("Invalid permission ack from {}: {}", peer, e),
+        }
+    }
+
     async fn handle_identity_response(
         &mut self,
         peer: PeerId,
-        _request_id: request_response::OutboundRequestId,
+        request_id: request_response::OutboundRequestId,
         response: IdentityExchangeResponse,
     ) {
         info!(
@@ -2339,100 +5469,73 @@ impl NetworkService {
             peer, response.display_name, response.peer_id
         );
 
-        // Store in contacts database if we have the contacts service
-        if let Some(ref contacts_service) = self.contacts_service {
-            // Verify the response peer ID matches the peer we received from
-            if response.peer_id != peer.to_string() {
-                warn!(
-                    "Identity response peer ID mismatch: expected {}, got {}",
-                    peer, response.peer_id
-                );
-                return;
-            }
+        // Reconciles only want the ack; the contact is already known
+        if self
+            .pending_permission_reconciles
+            .remove(&request_id)
+            .is_some()
+        {
+            self.process_permission_ack(peer, response.permission_ack);
+            return;
+        }
 
-            // Step 1: Parse the Ed25519 public key from the response.
-            let public_key_bytes: [u8; 32] = match response.public_key.clone().try_into() {
-                Ok(bytes) => bytes,
-                Err(_) => {
-                    warn!(
-                        "Identity response from {} has invalid public key length (expected 32, got {})",
-                        peer,
-                        response.public_key.len()
-                    );
-                    return;
-                }
-            };
+        // Contact list requests only want the shared list; the identity is
+        // checked so the list is known to come from that contact
+        if let Some(reply) = self.pending_contact_list_fetches.remove(&request_id) {
+            let result = verify_identity_response(&peer, &response).and_then(|()| {
+                response
+                    .shared_contacts
+                    .map(|contacts| {
+                        contacts
+                            .into_iter()
+                            .map(|c| SharedContact {
+                                peer_id: c.peer_id,
+                                display_name: c.display_name,
+                            })
+                            .collect()
+                    })
+                    .ok_or_else(|| format!("{} does not share its contact list with us", peer))
+            });
+            let _ = reply.send(result);
+            return;
+        }
 
-            let verifying_key = match ed25519_dalek::VerifyingKey::from_bytes(&public_key_bytes) {
-                Ok(key) => key,
-                Err(error) => {
-                    warn!(
-                        "Identity response from {} has invalid Ed25519 public key: {}",
-                        peer, error
-                    );
-                    return;
-                }
-            };
+        // Transient lookups (e.g. one-off messages) only need the verified
+        // keys and must not create a contact
+        if let Some(reply) = self.pending_identity_fetches.remove(&request_id) {
+            let result = verify_identity_response(&peer, &response).map(|()| TransientIdentity {
+                peer_id: response.peer_id,
+                public_key: response.public_key,
+                x25519_public: response.x25519_public,
+                display_name: response.display_name,
+            });
+            let _ = reply.send(result);
+            return;
+        }
 
-            // Step 2: Verify that the Ed25519 public key actually derives
-            // the claimed peer ID. Without this check, an attacker could
-            // include an arbitrary public key and sign the payload with
-            // the corresponding private key while claiming someone else's
-            // peer ID. The transport-level peer ID check (above) mitigates
-            // this for direct connections, but this provides defense-in-depth.
-            let derived_peer_id =
-                match crate::services::CryptoService::derive_peer_id_from_verifying_key(
-                    &verifying_key,
-                ) {
-                    Ok(id) => id,
-                    Err(e) => {
-                        warn!(
-                        "Identity response from {}: failed to derive peer ID from public key: {}",
-                        peer, e
-                    );
-                        return;
-                    }
-                };
-            if derived_peer_id != response.peer_id {
-                warn!(
-                    "Identity response from {}: public key derives peer ID {} but response claims {} - rejecting identity",
-                    peer, derived_peer_id, response.peer_id
-                );
+        // Automatic requests only report who is nearby. Adding the peer as a
+        // contact (and applying the default grant policy) stays an explicit user action.
+        if let Some(requested_peer) = self.pending_auto_identity.remove(&request_id) {
+            if let Err(e) = verify_identity_response(&requested_peer, &response) {
+                warn!("{}", e);
                 return;
             }
+            let _ = self
+                .event_tx
+                .send(NetworkEvent::NearbyPeerIdentified {
+                    peer_id: response.peer_id,
+                    display_name: response.display_name,
+                    avatar_hash: response.avatar_hash,
+                    bio: response.bio,
+                })
+                .await;
+            return;
+        }
 
-            // Step 3: Verify the Ed25519 signature on the identity response.
-            // The sender signs the string "{peer_id}:{display_name}:{timestamp}"
-            // using their Ed25519 signing key. We reconstruct that payload and
-            // verify against the public key included in the response.
-            let signature_is_valid = {
-                let signed_payload = format!(
-                    "{}:{}:{}",
-                    response.peer_id, response.display_name, response.timestamp
-                );
-
-                let signature = match ed25519_dalek::Signature::from_slice(&response.signature) {
-                    Ok(sig) => sig,
-                    Err(error) => {
-                        warn!(
-                            "Identity response from {} has invalid signature format: {}",
-                            peer, error
-                        );
-                        return;
-                    }
-                };
-
-                use ed25519_dalek::Verifier;
-                verifying_key
-                    .verify(signed_payload.as_bytes(), &signature)
-                    .is_ok()
-            };
-
-            if !signature_is_valid {
-                warn!(
-                    "Identity response from {} failed signature verification - rejecting identity",
-                    peer
-                );
+        // Store in contacts database if we have the contacts service
+        if let Some(ref contacts_service) = self.contacts_service {
+            if let Err(e) = verify_identity_response(&peer, &response) {
+                warn!("{}", e);
                 return;
             }
 
@@ -2441,6 +5544,20 @@ impl NetworkService {
                 peer
             );
 
+            // A peer reconnecting over and over sends the same identity each
+            // time; only a changed one is worth writing and announcing again
+            if !self.identity_exchanges.record(
+                peer,
+                AdvertisedIdentity::from(&response),
+                Instant::now(),
+            ) {
+                debug!(
+                    "Identity from {} unchanged since the last exchange, skipping",
+                    peer
+                );
+                return;
+            }
+
             match contacts_service.add_contact(
                 &response.peer_id,
                 &response.public_key,
@@ -2449,33 +5566,85 @@ impl NetworkService {
                 response.avatar_hash.as_deref(),
                 response.bio.as_deref(),
             ) {
-                Ok(contact_id) => {
-                    info!(
-                        "Added contact {} with ID {}",
-                        response.display_name, contact_id
-                    );
+                Ok(upsert) => {
+                    if upsert.created {
+                        info!(
+                            "Added contact {} with ID {}",
+                            response.display_name, upsert.contact_id
+                        );
+                    } else if upsert.is_updated() {
+                        info!(
+                            "Updated contact {} ({:?} changed)",
+                            response.display_name, upsert.changed_fields
+                        );
+                    }
+
+                    if upsert.is_unverified_rename() {
+                        warn!(
+                            "Unverified contact {} renamed from {:?} to {:?}",
+                            response.peer_id, upsert.previous_display_name, response.display_name
+                        );
+                    }
+
+                    // Remember which ciphers we may use when messaging them
+                    if let Some(ref ciphers) = response.supported_ciphers {
+                        if let Err(e) =
+                            contacts_service.set_supported_ciphers(&response.peer_id, ciphers)
+                        {
+                            warn!("Failed to record supported ciphers: {}", e);
+                        }
+                    }
 
-                    // Grant chat permission to the new contact
+                    // Grant the default contact permissions (chat, file transfer)
                     if let Some(ref permissions_service) = self.permissions_service {
-                        match permissions_service.create_permission_grant(
-                            &response.peer_id,
-                            Capability::Chat,
-                            None, // No expiration
-                        ) {
-                            Ok(_) => {
-                                info!("Granted chat permission to {}", response.peer_id);
+                        match permissions_service.grant_contact_defaults(&response.peer_id) {
+                            Ok(granted) if !granted.is_empty() => {
+                                info!("Granted {:?} to {}", granted, response.peer_id);
                             }
+                            Ok(_) => {}
                             Err(e) => {
-                                warn!("Failed to grant chat permission: {}", e);
+                                warn!("Failed to grant default permissions: {}", e);
                             }
                         }
                     }
 
                     // Emit event to notify frontend
-                    drop(self.event_tx.send(NetworkEvent::ContactAdded {
-                        peer_id: response.peer_id.clone(),
-                        display_name: response.display_name.clone(),
-                    }));
+                    if upsert.created {
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::ContactAdded {
+                                peer_id: response.peer_id.clone(),
+                                display_name: response.display_name.clone(),
+                            })
+                            .await;
+                    } else if upsert.is_updated() {
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::ContactUpdated {
+                                peer_id: response.peer_id.clone(),
+                                display_name: response.display_name.clone(),
+                                bio: response.bio.clone(),
+                                avatar_hash: response.avatar_hash.clone(),
+                                changed_fields: upsert
+                                    .changed_fields
+                                    .iter()
+                                    .map(|f| f.as_str().to_string())
+                                    .collect(),
+                            })
+                            .await;
+                    }
+
+                    if let Some(previous_name) = upsert.previous_display_name {
+                        let _ = self
+                            .event_tx
+                            .send(NetworkEvent::ContactRenamed {
+                                peer_id: response.peer_id.clone(),
+                                previous_name,
+                                display_name: response.display_name.clone(),
+                                verified: upsert.verified,
+                            })
+                            .await;
+                    }
                 }
                 Err(e) => {
                     warn!("Failed to add contact: {}", e);
@@ -2517,9 +5686,18 @@ impl NetworkService {
                         lamport_clock: direct_msg.lamport_clock,
                         timestamp: direct_msg.timestamp,
                         signature: &direct_msg.signature,
+                        nonce_salt: direct_msg.nonce_salt.as_deref(),
+                        sig_version: direct_msg.sig_version,
+                        protocol_version: direct_msg.protocol_version,
+                        ratchet: direct_msg.ratchet.as_ref(),
+                        cipher: &direct_msg.cipher,
+                        voice: direct_msg.voice.as_ref(),
                     }) {
                         Ok(_) => {
                             info!("Message {} processed successfully", direct_msg.message_id);
+                            if let Some(ref voice) = direct_msg.voice {
+                                self.fetch_voice_note_audio(peer, voice);
+                            }
                             (true, Some(direct_msg.message_id.clone()), None)
                         }
                         Err(e) => {
@@ -2592,32 +5770,123 @@ impl NetworkService {
                     )
                 }
             }
-            Ok(MessagingMessage::EditMessage {
-                message_id,
-                new_content,
-                edited_at,
-            }) => {
+            Ok(MessagingMessage::Edit(edit)) => {
                 info!(
-                    "Received edit for message {} from {} at {}",
-                    message_id, peer, edited_at
+                    "Received edit for message {} from {} (clock {})",
+                    edit.message_id, peer, edit.lamport_clock
                 );
 
                 if let Some(ref messaging_service) = self.messaging_service {
-                    match messaging_service.apply_incoming_edit(&message_id, &new_content) {
-                        Ok(()) => {
-                            info!("Successfully applied edit for message {}", message_id);
-                            (true, Some(message_id), None)
+                    let signable = SignableMessageEdit {
+                        message_id: edit.message_id.clone(),
+                        conversation_id: edit.conversation_id,
+                        sender_peer_id: edit.sender_peer_id,
+                        content_encrypted: edit.content_encrypted,
+                        nonce_salt: edit.nonce_salt,
+                        lamport_clock: edit.lamport_clock,
+                        edited_at: edit.edited_at,
+                        ratchet: edit.ratchet,
+                    };
+                    match messaging_service.process_incoming_edit(&signable, &edit.signature) {
+                        Ok(applied) => {
+                            if applied {
+                                info!("Applied edit for message {}", edit.message_id);
+                            } else {
+                                debug!(
+                                    "Edit for message {} is stale or held for its message",
+                                    edit.message_id
+                                );
+                            }
+                            (true, Some(edit.message_id), None)
+                        }
+                        Err(e) => {
+                            warn!(
+                                "Failed to apply edit for message {}: {}",
+                                edit.message_id, e
+                            );
+                            (false, Some(edit.message_id), Some(e.to_string()))
                         }
+                    }
+                } else {
+                    warn!("No messaging service configured, cannot process edit");
+                    (
+                        false,
+                        Some(edit.message_id),
+                        Some("Messaging service not available".to_string()),
+                    )
+                }
+            }
+            Ok(MessagingMessage::ReadReceipt(receipt)) => {
+                debug!(
+                    "Received read receipt for {} messages from {}",
+                    receipt.message_ids.len(),
+                    peer
+                );
+
+                if let Some(ref messaging_service) = self.messaging_service {
+                    let signable = SignableReadReceipt {
+                        conversation_id: receipt.conversation_id.clone(),
+                        reader_peer_id: receipt.reader_peer_id.clone(),
+                        message_ids: receipt.message_ids,
+                        read_at: receipt.read_at,
+                    };
+                    match messaging_service
+                        .process_incoming_read_receipt(&signable, &receipt.signature)
+                    {
+                        Ok(seen) => {
+                            if !seen.is_empty() {
+                                let _ = self
+                                    .event_tx
+                                    .send(NetworkEvent::MessagesSeen {
+                                        peer_id: receipt.reader_peer_id,
+                                        conversation_id: receipt.conversation_id,
+                                        message_ids: seen,
+                                        read_at: receipt.read_at,
+                                    })
+                                    .await;
+                            }
+                            (true, None, None)
+                        }
+                        Err(e) => {
+                            warn!("Failed to process read receipt from {}: {}", peer, e);
+                            (false, None, Some(e.to_string()))
+                        }
+                    }
+                } else {
+                    warn!("No messaging service configured, cannot process read receipt");
+                    (
+                        false,
+                        None,
+                        Some("Messaging service not available".to_string()),
+                    )
+                }
+            }
+            Ok(MessagingMessage::Delete(delete)) => {
+                info!(
+                    "Received delete for message {} from {}",
+                    delete.message_id, peer
+                );
+
+                if let Some(ref messaging_service) = self.messaging_service {
+                    let signable = SignableMessageDelete {
+                        message_id: delete.message_id.clone(),
+                        conversation_id: delete.conversation_id,
+                        sender_peer_id: delete.sender_peer_id,
+                        lamport_clock: delete.lamport_clock,
+                        deleted_at: delete.deleted_at,
+                    };
+                    match messaging_service.process_incoming_delete(&signable, &delete.signature) {
+                        Ok(_) => (true, Some(delete.message_id), None),
                         Err(e) => {
-                            warn!("Failed to apply edit for message {}: {}", message_id, e);
-                            (false, Some(message_id), Some(e.to_string()))
+                            warn!("Failed to delete message {}: {}", delete.message_id, e);
+                            (false, Some(delete.message_id), Some(e.to_string()))
                         }
                     }
                 } else {
-                    warn!("No messaging service configured, cannot process edit");
+                    warn!("No messaging service configured, cannot process delete");
                     (
                         false,
-                        Some(message_id),
+                        Some(delete.message_id),
                         Some("Messaging service not available".to_string()),
                     )
                 }
@@ -2658,14 +5927,21 @@ impl NetworkService {
     async fn handle_command(&mut self, command: NetworkCommand) -> NetworkResponse {
         match command {
             NetworkCommand::Dial { peer_id, addresses } => {
-                for addr in addresses {
+                for addr in &addresses {
                     self.swarm
                         .behaviour_mut()
                         .kademlia
                         .add_address(&peer_id, addr.clone());
                 }
-                match self.swarm.dial(peer_id) {
-                    Ok(_) => NetworkResponse::Ok,
+                match self
+                    .start_dial(DialOpts::peer_id(peer_id).build(), peer_id, &addresses)
+                    .await
+                {
+                    Ok(DialOutcome::Started) => NetworkResponse::Ok,
+                    Ok(outcome) => {
+                        debug!("Dial to {} suppressed: {:?}", peer_id, outcome);
+                        NetworkResponse::Ok
+                    }
                     Err(e) => NetworkResponse::Error(format!("Failed to dial: {}", e)),
                 }
             }
@@ -2681,19 +5957,36 @@ impl NetworkService {
                 peer_id,
                 protocol,
                 payload,
+                correlation_id,
+                confirmation,
             } => {
                 let request = MessagingRequest {
                     message_type: protocol,
                     payload,
                 };
-                self.swarm
+                let request_id = self
+                    .swarm
                     .behaviour_mut()
                     .messaging
                     .send_request(&peer_id, request);
+                self.pending_message_sends.insert(
+                    request_id,
+                    PendingMessageSend {
+                        correlation_id,
+                        confirmation,
+                    },
+                );
                 NetworkResponse::Ok
             }
 
             NetworkCommand::RequestIdentity { peer_id } => {
+                if self.identity_recently_exchanged(&peer_id) {
+                    debug!(
+                        "Identity of {} exchanged recently, not asking again",
+                        peer_id
+                    );
+                    return NetworkResponse::Ok;
+                }
                 // Create identity request
                 match self.create_identity_request() {
                     Ok(request) => {
@@ -2709,12 +6002,68 @@ impl NetworkService {
                 }
             }
 
+            NetworkCommand::FetchIdentity { peer_id, reply } => {
+                match self.create_identity_request() {
+                    Ok(request) => {
+                        let request_id = self
+                            .swarm
+                            .behaviour_mut()
+                            .identity_exchange
+                            .send_request(&peer_id, request);
+                        self.pending_identity_fetches.insert(request_id, reply);
+                        NetworkResponse::Ok
+                    }
+                    Err(e) => {
+                        NetworkResponse::Error(format!("Failed to create identity request: {}", e))
+                    }
+                }
+            }
+
+            NetworkCommand::FetchSharedContacts { peer_id, reply } => {
+                match self.create_contact_list_request(&peer_id) {
+                    Ok(request) => {
+                        let request_id = self
+                            .swarm
+                            .behaviour_mut()
+                            .identity_exchange
+                            .send_request(&peer_id, request);
+                        self.pending_contact_list_fetches.insert(request_id, reply);
+                        NetworkResponse::Ok
+                    }
+                    Err(e) => NetworkResponse::Error(format!(
+                        "Failed to create contact list request: {}",
+                        e
+                    )),
+                }
+            }
+
+            NetworkCommand::ReconcilePermissions { peer_id } => {
+                if !self.connected_peers.contains_key(&peer_id) {
+                    NetworkResponse::Error(format!("Not connected to {}", peer_id))
+                } else {
+                    self.reconcile_permissions(peer_id);
+                    NetworkResponse::Ok
+                }
+            }
+
+            NetworkCommand::FlushPendingMessages { peer_id } => {
+                self.flush_pending_messages(peer_id);
+                NetworkResponse::Ok
+            }
+
             NetworkCommand::GetStats => {
                 let mut stats = self.stats.clone();
                 stats.uptime_seconds = self.start_time.elapsed().as_secs();
                 stats.nat_status = self.nat_status;
+                stats.detected_nat_status = self.detected_nat_status;
+                stats.nat_override = self.nat_override;
+                stats.autonat_enabled = self.config.enable_autonat;
+                stats.address_filter = self.config.address_filter;
                 stats.relay_addresses =
                     self.relay_addresses.iter().map(|a| a.to_string()).collect();
+                stats.relay_limits = self.relay_limits.values().cloned().collect();
+                stats.relay_liveness = self.relay_liveness.values().cloned().collect();
+                stats.dropped_events = self.event_tx.dropped();
                 stats.external_addresses = self
                     .external_addresses
                     .iter()
@@ -2728,6 +6077,12 @@ impl NetworkService {
                 NetworkResponse::Peers(peers)
             }
 
+            NetworkCommand::GetBoundPorts => NetworkResponse::BoundPorts(self.bound_ports),
+
+            NetworkCommand::GetDhtRoutingTable { include_addresses } => {
+                NetworkResponse::DhtRoutingTable(self.dht_routing_table(include_addresses))
+            }
+
             NetworkCommand::GetListeningAddresses => {
                 let local_peer_id = self.swarm.local_peer_id();
                 let mut addresses: Vec<String> = Vec::new();
@@ -2742,9 +6097,16 @@ impl NetworkService {
                     addresses.push(format!("{}/p2p/{}", addr, local_peer_id));
                 }
 
-                // Add local listening addresses
+                // Add local listening addresses. Private ranges stay in even
+                // under `PublicOnly` so the list remains usable on a LAN.
+                let local_filter = match self.config.address_filter {
+                    AddressFilter::All => AddressFilter::All,
+                    _ => AddressFilter::Routable,
+                };
                 for addr in &self.listening_addresses {
-                    addresses.push(format!("{}/p2p/{}", addr, local_peer_id));
+                    if local_filter.allows(addr) {
+                        addresses.push(format!("{}/p2p/{}", addr, local_peer_id));
+                    }
                 }
 
                 NetworkResponse::Addresses(addresses)
@@ -2769,13 +6131,25 @@ impl NetworkService {
                         .kademlia
                         .add_address(&peer_id, addr_without_peer);
                     info!("Added bootstrap node: {} at {}", peer_id, address);
+                    self.add_dial_target(peer_id, address.clone(), false);
 
                     // Try to dial the bootstrap node
-                    match self.swarm.dial(address.clone()) {
-                        Ok(_) => {
+                    match self
+                        .start_dial(
+                            address.clone().into(),
+                            peer_id,
+                            std::slice::from_ref(&address),
+                        )
+                        .await
+                    {
+                        Ok(DialOutcome::Started) => {
                             info!("Dialing bootstrap node: {}", address);
                             NetworkResponse::Ok
                         }
+                        Ok(outcome) => {
+                            info!("Bootstrap node {} not dialed: {:?}", peer_id, outcome);
+                            NetworkResponse::Ok
+                        }
                         Err(e) => {
                             NetworkResponse::Error(format!("Failed to dial bootstrap node: {}", e))
                         }
@@ -2825,10 +6199,20 @@ impl NetworkService {
                     }
 
                     // Try to dial the relay server using the full multiaddr (including /p2p)
-                    match self.swarm.dial(address.clone()) {
-                        Ok(_) => {
+                    match self
+                        .start_dial(
+                            address.clone().into(),
+                            relay_peer_id,
+                            std::slice::from_ref(&address),
+                        )
+                        .await
+                    {
+                        Ok(DialOutcome::Started) => {
                             info!("Dialing relay server: {}", address);
                         }
+                        Ok(outcome) => {
+                            info!("Relay server {} not dialed: {:?}", relay_peer_id, outcome);
+                        }
                         Err(e) => {
                             return NetworkResponse::Error(format!(
                                 "Failed to dial relay server: {}",
@@ -2837,15 +6221,8 @@ impl NetworkService {
                         }
                     }
 
-                    // Queue relay reservation for after Identify completes.
-                    // listen_on must be called AFTER the connection is fully negotiated
-                    // (Identify::Received), not immediately after dial.
-                    self.pending_relay_reservations
-                        .insert(relay_peer_id, address.clone());
-                    info!(
-                        "Relay reservation queued for {} (will request after identify)",
-                        relay_peer_id
-                    );
+                    self.queue_relay_reservation(relay_peer_id, address.clone());
+                    self.add_dial_target(relay_peer_id, address, true);
 
                     NetworkResponse::Ok
                 } else {
@@ -2863,6 +6240,22 @@ impl NetworkService {
                 NetworkResponse::Ok
             }
 
+            NetworkCommand::ReconcileConnections => {
+                self.reconcile_connections(true).await;
+                NetworkResponse::Ok
+            }
+
+            NetworkCommand::SetNatOverride { status } => {
+                match status {
+                    Some(status) => info!("NAT status manually set to {:?}", status),
+                    None => info!("NAT override cleared, using AutoNAT detection"),
+                }
+                self.nat_override = status;
+                self.refresh_nat_status().await;
+                self.connect_to_relays_if_private().await;
+                NetworkResponse::Ok
+            }
+
             NetworkCommand::SyncFeed { limit } => {
                 // Clamp the limit to avoid pathological or abusive requests.
                 const MAX_MANIFEST_LIMIT: u32 = 1000;
@@ -2875,9 +6268,23 @@ impl NetworkService {
                 // Avoid borrow issues: collect peer ids first.
                 let connected_peer_ids: Vec<PeerId> =
                     self.connected_peers.keys().cloned().collect();
+                let contacts_service = self.contacts_service.clone();
+                let selected_peer_ids = select_sync_peers(
+                    self.config.sync_strategy,
+                    connected_peer_ids,
+                    |peer_id| {
+                        contacts_service.as_ref().is_some_and(|contacts_service| {
+                            contacts_service
+                                .is_contact(&peer_id.to_string())
+                                .unwrap_or(false)
+                        })
+                    },
+                    &self.peer_sync_scores,
+                    &mut self.sync_round_robin_offset,
+                );
 
-                // Request manifest from each connected peer (excluding ourselves, if present).
-                for peer_id in connected_peer_ids {
+                // Request a manifest from each peer the sync strategy picked
+                for peer_id in selected_peer_ids {
                     let peer_id_string = peer_id.to_string();
                     let cursor = match content_sync_service.get_sync_cursor(&peer_id_string) {
                         Ok(cursor_value) => cursor_value,
@@ -2887,17 +6294,20 @@ impl NetworkService {
                         }
                     };
 
-                    let manifest_request =
-                        match content_sync_service.create_manifest_request(cursor, clamped_limit) {
-                            Ok(request_value) => request_value,
-                            Err(error) => {
-                                warn!(
-                                    "Failed to create manifest request for {}: {}",
-                                    peer_id, error
-                                );
-                                continue;
-                            }
-                        };
+                    let manifest_request = match content_sync_service.create_manifest_request(
+                        cursor,
+                        clamped_limit,
+                        crate::services::ManifestDetailLevel::Full,
+                    ) {
+                        Ok(request_value) => request_value,
+                        Err(error) => {
+                            warn!(
+                                "Failed to create manifest request for {}: {}",
+                                peer_id, error
+                            );
+                            continue;
+                        }
+                    };
 
                     let wire_message = ContentSyncRequest::Manifest {
                         requester_peer_id: manifest_request.requester_peer_id,
@@ -2905,6 +6315,7 @@ impl NetworkService {
                         limit: manifest_request.limit,
                         timestamp: manifest_request.timestamp,
                         signature: manifest_request.signature,
+                        detail_level: manifest_request.detail_level,
                     };
 
                     self.swarm
@@ -2916,10 +6327,82 @@ impl NetworkService {
                 NetworkResponse::Ok
             }
 
+            NetworkCommand::SyncWithPeer {
+                peer_id,
+                limit,
+                reply,
+            } => {
+                const MAX_MANIFEST_LIMIT: u32 = 1000;
+                let clamped_limit = limit.min(MAX_MANIFEST_LIMIT);
+
+                if self.peer_syncs.contains_key(&peer_id) {
+                    return NetworkResponse::Error(format!(
+                        "A sync with {} is already in progress",
+                        peer_id
+                    ));
+                }
+
+                let Some(ref content_sync_service) = self.content_sync_service else {
+                    return NetworkResponse::Error("Content sync service unavailable".to_string());
+                };
+
+                let cursor = match content_sync_service.get_sync_cursor(&peer_id.to_string()) {
+                    Ok(cursor_value) => cursor_value,
+                    Err(error) => {
+                        warn!("Failed to load sync cursor for {}: {}", peer_id, error);
+                        HashMap::new()
+                    }
+                };
+
+                let manifest_request = match content_sync_service.create_manifest_request(
+                    cursor,
+                    clamped_limit,
+                    crate::services::ManifestDetailLevel::Full,
+                ) {
+                    Ok(request_value) => request_value,
+                    Err(error) => {
+                        return NetworkResponse::Error(format!(
+                            "Failed to create manifest request: {}",
+                            error
+                        ));
+                    }
+                };
+
+                let wire_message = ContentSyncRequest::Manifest {
+                    requester_peer_id: manifest_request.requester_peer_id,
+                    cursor: manifest_request.cursor,
+                    limit: manifest_request.limit,
+                    timestamp: manifest_request.timestamp,
+                    signature: manifest_request.signature,
+                    detail_level: manifest_request.detail_level,
+                };
+
+                let request_id = self
+                    .swarm
+                    .behaviour_mut()
+                    .content_sync
+                    .send_request(&peer_id, wire_message);
+                self.peer_sync_requests.insert(request_id, peer_id);
+                self.peer_syncs.insert(
+                    peer_id,
+                    PeerSync {
+                        summary: PeerSyncSummary {
+                            peer_id: peer_id.to_string(),
+                            ..Default::default()
+                        },
+                        outstanding: HashSet::from([request_id]),
+                        reply,
+                    },
+                );
+
+                NetworkResponse::Ok
+            }
+
             NetworkCommand::RequestContentManifest {
                 peer_id,
                 cursor,
                 limit,
+                detail_level,
             } => {
                 const MAX_MANIFEST_LIMIT: u32 = 1000;
                 let clamped_limit = limit.min(MAX_MANIFEST_LIMIT);
@@ -2928,16 +6411,19 @@ impl NetworkService {
                     return NetworkResponse::Error("Content sync service unavailable".to_string());
                 };
 
-                let manifest_request =
-                    match content_sync_service.create_manifest_request(cursor, clamped_limit) {
-                        Ok(request_value) => request_value,
-                        Err(error) => {
-                            return NetworkResponse::Error(format!(
-                                "Failed to create manifest request: {}",
-                                error
-                            ));
-                        }
-                    };
+                let manifest_request = match content_sync_service.create_manifest_request(
+                    cursor,
+                    clamped_limit,
+                    detail_level,
+                ) {
+                    Ok(request_value) => request_value,
+                    Err(error) => {
+                        return NetworkResponse::Error(format!(
+                            "Failed to create manifest request: {}",
+                            error
+                        ));
+                    }
+                };
 
                 let wire_message = ContentSyncRequest::Manifest {
                     requester_peer_id: manifest_request.requester_peer_id,
@@ -2945,6 +6431,7 @@ impl NetworkService {
                     limit: manifest_request.limit,
                     timestamp: manifest_request.timestamp,
                     signature: manifest_request.signature,
+                    detail_level: manifest_request.detail_level,
                 };
 
                 self.swarm
@@ -2992,13 +6479,92 @@ impl NetworkService {
                 NetworkResponse::Ok
             }
 
+            NetworkCommand::AnnouncePost { post_id } => {
+                let Some(ref content_sync_service) = self.content_sync_service else {
+                    return NetworkResponse::Error("Content sync service unavailable".to_string());
+                };
+
+                let announcement = match content_sync_service.create_post_announcement(&post_id) {
+                    Ok(Some(announcement)) => announcement,
+                    Ok(None) => return NetworkResponse::Ok,
+                    Err(e) => {
+                        return NetworkResponse::Error(format!(
+                            "Failed to create post announcement: {}",
+                            e
+                        ));
+                    }
+                };
+
+                let connected: Vec<String> =
+                    self.connected_peers.keys().map(|p| p.to_string()).collect();
+                let recipients = match content_sync_service.announcement_recipients(&connected) {
+                    Ok(recipients) => recipients,
+                    Err(e) => {
+                        return NetworkResponse::Error(format!(
+                            "Failed to pick announcement recipients: {}",
+                            e
+                        ));
+                    }
+                };
+                if recipients.is_empty() {
+                    return NetworkResponse::Ok;
+                }
+
+                // Record every recipient before sending, so a response can
+                // never arrive for a delivery we haven't stored
+                if let Err(e) =
+                    content_sync_service.record_pending_deliveries(&post_id, &recipients)
+                {
+                    return NetworkResponse::Error(format!(
+                        "Failed to record post deliveries: {}",
+                        e
+                    ));
+                }
+
+                let post = PostSummaryProto {
+                    post_id: announcement.post.post_id,
+                    author_peer_id: announcement.post.author_peer_id,
+                    lamport_clock: announcement.post.lamport_clock,
+                    content_type: announcement.post.content_type,
+                    has_media: announcement.post.has_media,
+                    media_hashes: announcement.post.media_hashes,
+                    created_at: announcement.post.created_at,
+                };
+                for recipient in &recipients {
+                    let Ok(peer_id) = recipient.parse::<PeerId>() else {
+                        continue;
+                    };
+                    let request_id = self.swarm.behaviour_mut().content_sync.send_request(
+                        &peer_id,
+                        ContentSyncRequest::PostAnnouncement {
+                            post: post.clone(),
+                            timestamp: announcement.timestamp,
+                            signature: announcement.signature.clone(),
+                        },
+                    );
+                    self.pending_post_deliveries
+                        .insert(request_id, (post_id.clone(), peer_id));
+                }
+
+                info!(
+                    "Announced post {} to {} online contacts",
+                    post_id,
+                    recipients.len()
+                );
+                NetworkResponse::Ok
+            }
+
             NetworkCommand::JoinCommunity {
                 relay_peer_id,
                 relay_address,
             } => {
+                if let Some(response) = self.reject_non_community_relay(&relay_peer_id) {
+                    return response;
+                }
                 let Some(ref board_service) = self.board_service else {
                     return NetworkResponse::Error("Board service unavailable".to_string());
                 };
+                self.left_community_relays.remove(&relay_peer_id);
 
                 // Store community locally
                 if let Err(e) =
@@ -3036,6 +6602,44 @@ impl NetworkService {
                 }
             }
 
+            NetworkCommand::LeaveCommunity { relay_peer_id } => {
+                let Some(ref board_service) = self.board_service else {
+                    return NetworkResponse::Error("Board service unavailable".to_string());
+                };
+
+                // Deregistration is best-effort: if the relay can't be reached
+                // it keeps our registration, but we still leave locally
+                match board_service.create_peer_deregistration() {
+                    Ok(dereg) => {
+                        let request = WireBoardSyncRequest::DeregisterPeer {
+                            peer_id: dereg.peer_id,
+                            timestamp: dereg.timestamp,
+                            signature: dereg.signature,
+                        };
+                        self.swarm
+                            .behaviour_mut()
+                            .board_sync
+                            .send_request(&relay_peer_id, request);
+                    }
+                    Err(e) => {
+                        warn!(
+                            "Failed to create deregistration for relay {}: {}",
+                            relay_peer_id, e
+                        );
+                    }
+                }
+
+                self.community_relays.remove(&relay_peer_id);
+                self.pending_community_probes.remove(&relay_peer_id);
+                self.pending_board_registrations.remove(&relay_peer_id);
+                self.left_community_relays.insert(relay_peer_id);
+
+                match board_service.leave_community(&relay_peer_id.to_string()) {
+                    Ok(()) => NetworkResponse::Ok,
+                    Err(e) => NetworkResponse::Error(format!("Failed to leave community: {}", e)),
+                }
+            }
+
             NetworkCommand::ListBoards { relay_peer_id } => {
                 let Some(ref board_service) = self.board_service else {
                     return NetworkResponse::Error("Board service unavailable".to_string());
@@ -3098,26 +6702,87 @@ impl NetworkService {
                 }
             }
 
-            NetworkCommand::SubmitBoardPost {
+            NetworkCommand::SubmitBoardPost {
+                relay_peer_id,
+                board_id,
+                content_text,
+            } => {
+                if let Some(response) = self.reject_non_community_relay(&relay_peer_id) {
+                    return response;
+                }
+                let Some(ref board_service) = self.board_service else {
+                    return NetworkResponse::Error("Board service unavailable".to_string());
+                };
+
+                match board_service.create_board_post(&board_id, &content_text) {
+                    Ok(post) => {
+                        let request = WireBoardSyncRequest::SubmitPost {
+                            post_id: post.post_id,
+                            board_id: post.board_id,
+                            author_peer_id: post.author_peer_id,
+                            content_type: post.content_type,
+                            content_text: post.content_text,
+                            lamport_clock: post.lamport_clock,
+                            created_at: post.created_at,
+                            signature: post.signature,
+                        };
+                        self.swarm
+                            .behaviour_mut()
+                            .board_sync
+                            .send_request(&relay_peer_id, request);
+                        NetworkResponse::Ok
+                    }
+                    Err(e) => NetworkResponse::Error(format!("Failed to create board post: {}", e)),
+                }
+            }
+
+            NetworkCommand::CreateBoard {
+                relay_peer_id,
+                name,
+                description,
+            } => {
+                let Some(ref board_service) = self.board_service else {
+                    return NetworkResponse::Error("Board service unavailable".to_string());
+                };
+
+                match board_service.create_board_request(&name, description.as_deref()) {
+                    Ok(req) => {
+                        let request = WireBoardSyncRequest::CreateBoard {
+                            requester_peer_id: req.requester_peer_id,
+                            name: req.name,
+                            description: req.description,
+                            timestamp: req.timestamp,
+                            signature: req.signature,
+                        };
+                        self.swarm
+                            .behaviour_mut()
+                            .board_sync
+                            .send_request(&relay_peer_id, request);
+                        NetworkResponse::Ok
+                    }
+                    Err(e) => {
+                        NetworkResponse::Error(format!("Failed to create board request: {}", e))
+                    }
+                }
+            }
+
+            NetworkCommand::CreateCommunityInvite {
                 relay_peer_id,
-                board_id,
-                content_text,
+                expires_at,
+                max_uses,
             } => {
                 let Some(ref board_service) = self.board_service else {
                     return NetworkResponse::Error("Board service unavailable".to_string());
                 };
 
-                match board_service.create_board_post(&board_id, &content_text) {
-                    Ok(post) => {
-                        let request = WireBoardSyncRequest::SubmitPost {
-                            post_id: post.post_id,
-                            board_id: post.board_id,
-                            author_peer_id: post.author_peer_id,
-                            content_type: post.content_type,
-                            content_text: post.content_text,
-                            lamport_clock: post.lamport_clock,
-                            created_at: post.created_at,
-                            signature: post.signature,
+                match board_service.create_invite_request(expires_at, max_uses) {
+                    Ok(req) => {
+                        let request = WireBoardSyncRequest::CreateInvite {
+                            requester_peer_id: req.requester_peer_id,
+                            expires_at: req.expires_at,
+                            max_uses: req.max_uses,
+                            timestamp: req.timestamp,
+                            signature: req.signature,
                         };
                         self.swarm
                             .behaviour_mut()
@@ -3125,7 +6790,9 @@ impl NetworkService {
                             .send_request(&relay_peer_id, request);
                         NetworkResponse::Ok
                     }
-                    Err(e) => NetworkResponse::Error(format!("Failed to create board post: {}", e)),
+                    Err(e) => {
+                        NetworkResponse::Error(format!("Failed to create invite request: {}", e))
+                    }
                 }
             }
 
@@ -3157,25 +6824,23 @@ impl NetworkService {
                 }
             }
 
-            NetworkCommand::SyncBoard {
+            NetworkCommand::ReactToBoardPost {
                 relay_peer_id,
-                board_id,
+                post_id,
+                reaction_type,
+                remove,
             } => {
                 let Some(ref board_service) = self.board_service else {
                     return NetworkResponse::Error("Board service unavailable".to_string());
                 };
 
-                let after_timestamp = board_service
-                    .get_sync_cursor(&relay_peer_id.to_string(), &board_id)
-                    .unwrap_or(None);
-
-                match board_service.create_get_board_posts_request(&board_id, after_timestamp, 50) {
+                match board_service.create_react_to_post_request(&post_id, &reaction_type, remove) {
                     Ok(req) => {
-                        let request = WireBoardSyncRequest::GetBoardPosts {
-                            requester_peer_id: req.requester_peer_id,
-                            board_id: req.board_id,
-                            after_timestamp: req.after_timestamp,
-                            limit: req.limit,
+                        let request = WireBoardSyncRequest::ReactToPost {
+                            post_id: req.post_id,
+                            reactor_peer_id: req.reactor_peer_id,
+                            reaction_type: req.reaction_type,
+                            remove: req.remove,
                             timestamp: req.timestamp,
                             signature: req.signature,
                         };
@@ -3186,11 +6851,19 @@ impl NetworkService {
                         NetworkResponse::Ok
                     }
                     Err(e) => {
-                        NetworkResponse::Error(format!("Failed to create sync request: {}", e))
+                        NetworkResponse::Error(format!("Failed to create reaction request: {}", e))
                     }
                 }
             }
 
+            NetworkCommand::SyncBoard {
+                relay_peer_id,
+                board_id,
+            } => match self.send_board_sync_request(relay_peer_id, &board_id) {
+                Ok(()) => NetworkResponse::Ok,
+                Err(e) => NetworkResponse::Error(format!("Failed to create sync request: {}", e)),
+            },
+
             NetworkCommand::SubmitWallPostToRelay {
                 relay_peer_id,
                 post_id,
@@ -3256,46 +6929,10 @@ impl NetworkService {
             NetworkCommand::FetchMedia {
                 peer_id,
                 media_hash,
-            } => {
-                use super::protocols::media_sync::MediaFetchRequest;
-
-                let identity = match self.identity_service.get_identity() {
-                    Ok(Some(id)) => id,
-                    Ok(None) => {
-                        return NetworkResponse::Error("No identity available".to_string());
-                    }
-                    Err(e) => {
-                        return NetworkResponse::Error(format!("Identity error: {}", e));
-                    }
-                };
-
-                let now = chrono::Utc::now().timestamp();
-                let signable = crate::services::SignableMediaFetchRequest {
-                    media_hash: media_hash.clone(),
-                    requester_peer_id: identity.peer_id.clone(),
-                    timestamp: now,
-                };
-
-                match self.identity_service.sign(&signable) {
-                    Ok(signature) => {
-                        let request = MediaFetchRequest {
-                            media_hash,
-                            requester_peer_id: identity.peer_id,
-                            timestamp: now,
-                            signature,
-                        };
-                        self.swarm
-                            .behaviour_mut()
-                            .media_sync
-                            .send_request(&peer_id, request);
-                        NetworkResponse::Ok
-                    }
-                    Err(e) => NetworkResponse::Error(format!(
-                        "Failed to sign media fetch request: {}",
-                        e
-                    )),
-                }
-            }
+            } => match self.send_media_fetch_request(peer_id, media_hash) {
+                Ok(()) => NetworkResponse::Ok,
+                Err(e) => NetworkResponse::Error(e.to_string()),
+            },
 
             NetworkCommand::GetWallPostsFromRelay {
                 relay_peer_id,
@@ -3386,10 +7023,92 @@ impl NetworkService {
                 }
             }
 
+            NetworkCommand::NotifyLikesChanged { post_ids } => {
+                self.queue_likes_updated(post_ids);
+                NetworkResponse::Ok
+            }
+
+            NetworkCommand::SendCallData { peer_id, frame } => {
+                if let Err(error) = self.check_relay_byte_limit(&peer_id, frame.payload.len()) {
+                    return NetworkResponse::Error(error);
+                }
+                self.swarm
+                    .behaviour_mut()
+                    .call_data
+                    .send_request(&peer_id, frame);
+                NetworkResponse::Ok
+            }
+
             NetworkCommand::Shutdown => NetworkResponse::Ok,
         }
     }
 
+    /// Snapshot the Kademlia k-buckets, skipping empty ones
+    fn dht_routing_table(&mut self, include_addresses: bool) -> DhtRoutingTable {
+        let local_peer_id = self.swarm.local_peer_id().to_string();
+        let mut buckets = Vec::new();
+        for bucket in self.swarm.behaviour_mut().kademlia.kbuckets() {
+            let Some(index) = bucket.range().0.ilog2() else {
+                continue;
+            };
+            let peers: Vec<DhtRoutingPeer> = bucket
+                .iter()
+                .map(|entry| DhtRoutingPeer {
+                    peer_id: entry.node.key.preimage().to_string(),
+                    connected: matches!(entry.status, kad::NodeStatus::Connected),
+                    addresses: if include_addresses {
+                        entry
+                            .node
+                            .value
+                            .iter()
+                            .map(|addr| addr.to_string())
+                            .collect()
+                    } else {
+                        Vec::new()
+                    },
+                })
+                .collect();
+            if !peers.is_empty() {
+                buckets.push(DhtBucket { index, peers });
+            }
+        }
+
+        DhtRoutingTable {
+            local_peer_id,
+            total_peers: buckets.iter().map(|bucket| bucket.peers.len()).sum(),
+            buckets,
+        }
+    }
+
+    /// Collect posts whose like state changed.
+    ///
+    /// The window starts at the first change and is not extended by later
+    /// ones, so a steady stream of likes still produces regular updates.
+    fn queue_likes_updated(&mut self, post_ids: impl IntoIterator<Item = String>) {
+        self.pending_like_updates.extend(post_ids);
+        if self.likes_flush_at.is_none() && !self.pending_like_updates.is_empty() {
+            self.likes_flush_at =
+                Some(tokio::time::Instant::now() + self.config.likes_update_window);
+        }
+    }
+
+    /// Emit one `LikesUpdated` event for all pending like changes
+    async fn flush_likes_updated(&mut self) {
+        self.likes_flush_at = None;
+        if self.pending_like_updates.is_empty() {
+            return;
+        }
+
+        let post_ids: Vec<String> = std::mem::take(&mut self.pending_like_updates)
+            .into_iter()
+            .collect();
+        debug!("Like state changed for {} posts", post_ids.len());
+        let _ = self
+            .event_tx
+            .send(NetworkEvent::LikesUpdated { post_ids })
+            .await;
+    }
+
     /// Attempt to connect to public relay servers
     /// This is called when we detect we're behind NAT or when manually requested
     pub async fn try_connect_to_relays(&mut self) {
@@ -3400,3 +7119,888 @@ impl NetworkService {
         self.connect_to_relays().await;
     }
 }
+
+/// The TCP or UDP port of a listen address
+fn listen_port(address: &Multiaddr) -> Option<u16> {
+    address.iter().find_map(|protocol| match protocol {
+        libp2p::multiaddr::Protocol::Tcp(port) | libp2p::multiaddr::Protocol::Udp(port) => {
+            Some(port)
+        }
+        _ => None,
+    })
+}
+
+/// The remote peer behind a request-response message, for activity tracking
+fn request_response_peer<Req, Resp>(event: &request_response::Event<Req, Resp>) -> Option<PeerId> {
+    match event {
+        request_response::Event::Message { peer, .. } => Some(*peer),
+        _ => None,
+    }
+}
+
+/// Delay before re-dialing a target that reconciliation has found
+/// disconnected `misses` times in a row: the interval, doubling per miss
+fn reconcile_backoff(interval: Duration, max: Duration, misses: u32) -> Duration {
+    interval
+        .saturating_mul(2u32.saturating_pow(misses))
+        .min(max)
+}
+
+/// Pick which candidates to disconnect when `connected` exceeds `max`: the
+/// least recently active ones, enough to get back down to `max - margin`
+fn peers_to_evict(
+    connected: usize,
+    max: usize,
+    margin: usize,
+    mut candidates: Vec<(PeerId, Instant)>,
+) -> Vec<PeerId> {
+    if connected <= max {
+        return Vec::new();
+    }
+    let target = max.saturating_sub(margin);
+    candidates.sort_by_key(|(_, last_active)| *last_active);
+    candidates
+        .into_iter()
+        .take(connected - target)
+        .map(|(peer_id, _)| peer_id)
+        .collect()
+}
+
+/// Pick which connected peers a feed sync asks for manifests, per `strategy`.
+/// Round-robin walks the peers in a stable order, starting at `offset` and
+/// advancing it past the peers taken.
+fn select_sync_peers(
+    strategy: SyncStrategy,
+    mut connected: Vec<PeerId>,
+    is_contact: impl Fn(&PeerId) -> bool,
+    scores: &HashMap<PeerId, PeerSyncScore>,
+    offset: &mut usize,
+) -> Vec<PeerId> {
+    match strategy {
+        SyncStrategy::All => connected,
+        SyncStrategy::ContactsOnly => connected.into_iter().filter(|p| is_contact(p)).collect(),
+        SyncStrategy::TopReputation { max_peers } => {
+            let reputation =
+                |peer: &PeerId| scores.get(peer).copied().unwrap_or_default().reputation();
+            connected.sort_by_key(|peer| (std::cmp::Reverse(reputation(peer)), peer.to_bytes()));
+            connected.truncate(max_peers);
+            connected
+        }
+        SyncStrategy::RoundRobin { max_peers } => {
+            if connected.is_empty() {
+                return connected;
+            }
+            connected.sort_by_key(|peer| peer.to_bytes());
+            let start = *offset % connected.len();
+            let take = max_peers.min(connected.len());
+            *offset = start + take;
+            connected.rotate_left(start);
+            connected.truncate(take);
+            connected
+        }
+    }
+}
+
+/// Check an identity response is bound to the peer that sent it: the claimed
+/// peer ID matches the transport peer, the public key derives that peer ID,
+/// and the signature over `{peer_id}:{display_name}:{timestamp}` verifies.
+fn verify_identity_response(
+    peer: &PeerId,
+    response: &IdentityExchangeResponse,
+) -> std::result::Result<(), String> {
+    // Verify the response peer ID matches the peer we received from
+    if response.peer_id != peer.to_string() {
+        return Err(format!(
+            "Identity response peer ID mismatch: expected {}, got {}",
+            peer, response.peer_id
+        ));
+    }
+
+    // Step 1: Parse the Ed25519 public key from the response.
+    let public_key_bytes: [u8; 32] = response.public_key.clone().try_into().map_err(|_| {
+        format!(
+            "Identity response from {} has invalid public key length (expected 32, got {})",
+            peer,
+            response.public_key.len()
+        )
+    })?;
+
+    let verifying_key =
+        ed25519_dalek::VerifyingKey::from_bytes(&public_key_bytes).map_err(|error| {
+            format!(
+                "Identity response from {} has invalid Ed25519 public key: {}",
+                peer, error
+            )
+        })?;
+
+    // Step 2: Verify that the Ed25519 public key actually derives
+    // the claimed peer ID. Without this check, an attacker could
+    // include an arbitrary public key and sign the payload with
+    // the corresponding private key while claiming someone else's
+    // peer ID. The transport-level peer ID check (above) mitigates
+    // this for direct connections, but this provides defense-in-depth.
+    let derived_peer_id = crate::services::CryptoService::derive_peer_id_from_verifying_key(
+        &verifying_key,
+    )
+    .map_err(|e| {
+        format!(
+            "Identity response from {}: failed to derive peer ID from public key: {}",
+            peer, e
+        )
+    })?;
+    if derived_peer_id != response.peer_id {
+        return Err(format!(
+            "Identity response from {}: public key derives peer ID {} but response claims {} - rejecting identity",
+            peer, derived_peer_id, response.peer_id
+        ));
+    }
+
+    // Step 3: Verify the Ed25519 signature on the identity response.
+    // The sender signs the string "{peer_id}:{display_name}:{timestamp}"
+    // using their Ed25519 signing key. We reconstruct that payload and
+    // verify against the public key included in the response.
+    let signed_payload = format!(
+        "{}:{}:{}",
+        response.peer_id, response.display_name, response.timestamp
+    );
+    let signature = ed25519_dalek::Signature::from_slice(&response.signature).map_err(|error| {
+        format!(
+            "Identity response from {} has invalid signature format: {}",
+            peer, error
+        )
+    })?;
+
+    use ed25519_dalek::Verifier;
+    verifying_key
+        .verify(signed_payload.as_bytes(), &signature)
+        .map_err(|_| {
+            format!(
+                "Identity response from {} failed signature verification - rejecting identity",
+                peer
+            )
+        })
+}
+
+/// Fold one ping result (`None` for a failed ping) into a relay's liveness
+fn apply_relay_ping_result(liveness: &mut RelayLiveness, rtt: Option<Duration>, now: i64) {
+    match rtt {
+        Some(rtt) => {
+            liveness.health = RelayHealth::Alive;
+            liveness.rtt_ms = Some(rtt.as_millis() as u64);
+            liveness.last_pong_at = Some(now);
+            liveness.consecutive_failures = 0;
+        }
+        None => {
+            liveness.consecutive_failures += 1;
+            if liveness.consecutive_failures >= RELAY_DEGRADED_AFTER_FAILED_PINGS {
+                liveness.health = RelayHealth::Degraded;
+            }
+        }
+    }
+}
+
+/// The error for one address of a failed dial. Transport errors are reported
+/// per address (with or without a trailing `/p2p/...`); anything else failed
+/// the dial as a whole.
+fn dial_error_for_address(error: &DialError, address: Option<&Multiaddr>) -> String {
+    let without_peer = |addr: &Multiaddr| -> Multiaddr {
+        addr.iter()
+            .filter(|p| !matches!(p, libp2p::multiaddr::Protocol::P2p(_)))
+            .collect()
+    };
+    if let (DialError::Transport(errors), Some(address)) = (error, address) {
+        let wanted = without_peer(address);
+        if let Some((_, transport_error)) =
+            errors.iter().find(|(addr, _)| without_peer(addr) == wanted)
+        {
+            return transport_error.to_string();
+        }
+    }
+    error.to_string()
+}
+
+/// Classify a failed dial, one entry per address that was tried. A dial
+/// skipped by its peer condition didn't fail and gives none.
+fn classify_dial_error(
+    error: &DialError,
+) -> Vec<(Option<Multiaddr>, ConnectionFailureCategory, String)> {
+    match error {
+        DialError::Transport(errors) => errors
+            .iter()
+            .map(|(address, error)| {
+                (
+                    Some(address.clone()),
+                    classify_transport_error(error),
+                    error_chain(error),
+                )
+            })
+            .collect(),
+        DialError::DialPeerConditionFalse(_) => Vec::new(),
+        DialError::LocalPeerId { address } | DialError::WrongPeerId { address, .. } => vec![(
+            Some(address.clone()),
+            ConnectionFailureCategory::Handshake,
+            error.to_string(),
+        )],
+        DialError::NoAddresses => vec![(
+            None,
+            ConnectionFailureCategory::MultiaddrUnreachable,
+            error.to_string(),
+        )],
+        DialError::Denied { .. } => {
+            vec![(None, ConnectionFailureCategory::Denied, error_chain(error))]
+        }
+        _ => vec![(None, ConnectionFailureCategory::Other, error.to_string())],
+    }
+}
+
+/// Classify a failed incoming connection
+fn classify_listen_error(error: &ListenError) -> (ConnectionFailureCategory, String) {
+    let category = match error {
+        ListenError::Transport(error) => classify_transport_error(error),
+        ListenError::LocalPeerId { .. } | ListenError::WrongPeerId { .. } => {
+            ConnectionFailureCategory::Handshake
+        }
+        ListenError::Denied { .. } => ConnectionFailureCategory::Denied,
+        _ => ConnectionFailureCategory::Other,
+    };
+    (category, error_chain(error))
+}
+
+/// Classify a transport error by the I/O error kinds and messages along its
+/// source chain. Upgrade failures (noise, yamux, multistream-select) only
+/// reach us as boxed I/O errors, so their messages are all there is to go on.
+fn classify_transport_error(
+    error: &libp2p::TransportError<std::io::Error>,
+) -> ConnectionFailureCategory {
+    use std::io::ErrorKind;
+
+    let io_error = match error {
+        libp2p::TransportError::MultiaddrNotSupported(_) => {
+            return ConnectionFailureCategory::TransportUnsupported
+        }
+        libp2p::TransportError::Other(io_error) => io_error,
+    };
+    match io_error.kind() {
+        ErrorKind::TimedOut => return ConnectionFailureCategory::Timeout,
+        ErrorKind::ConnectionRefused
+        | ErrorKind::HostUnreachable
+        | ErrorKind::NetworkUnreachable
+        | ErrorKind::AddrNotAvailable => return ConnectionFailureCategory::MultiaddrUnreachable,
+        _ => {}
+    }
+
+    let detail = error_chain(io_error).to_lowercase();
+    let mentions = |words: &[&str]| words.iter().any(|w| detail.contains(w));
+    if mentions(&["timed out", "timeout"]) {
+        ConnectionFailureCategory::Timeout
+    } else if mentions(&["refused", "unreachable", "no route"]) {
+        ConnectionFailureCategory::MultiaddrUnreachable
+    } else if mentions(&[
+        "handshake",
+        "noise",
+        "tls",
+        "certificate",
+        "decrypt",
+        "upgrade",
+        "multistream",
+        "negotiat",
+    ]) {
+        ConnectionFailureCategory::Handshake
+    } else {
+        ConnectionFailureCategory::Other
+    }
+}
+
+/// An error and its sources as one line, skipping sources whose message the
+/// error before them already includes
+fn error_chain(error: &(dyn std::error::Error + 'static)) -> String {
+    let mut detail = error.to_string();
+    let mut source = error.source();
+    while let Some(error) = source {
+        let message = error.to_string();
+        if !detail.contains(&message) {
+            detail = format!("{}: {}", detail, message);
+        }
+        source = error.source();
+    }
+    detail
+}
+
+/// Whether `address` is this device: loopback or unspecified, one of our own
+/// IPs, or a circuit through a relay we're reachable at. DNS addresses can't
+/// be told apart and count as ours, so they never raise a conflict.
+fn is_own_address(address: &Multiaddr, own_addresses: &[Multiaddr]) -> bool {
+    if let Some(relay) = circuit_relay_peer(address) {
+        return own_addresses
+            .iter()
+            .any(|own| circuit_relay_peer(own) == Some(relay));
+    }
+    match address_ip(address) {
+        Some(ip) => {
+            ip.is_loopback()
+                || ip.is_unspecified()
+                || own_addresses
+                    .iter()
+                    .any(|own| circuit_relay_peer(own).is_none() && address_ip(own) == Some(ip))
+        }
+        None => true,
+    }
+}
+
+/// The IP an address starts from, if it names one
+fn address_ip(address: &Multiaddr) -> Option<std::net::IpAddr> {
+    address.iter().find_map(|protocol| match protocol {
+        libp2p::multiaddr::Protocol::Ip4(ip) => Some(ip.into()),
+        libp2p::multiaddr::Protocol::Ip6(ip) => Some(ip.into()),
+        _ => None,
+    })
+}
+
+/// The relay a circuit address goes through, i.e. the peer ID right before
+/// `/p2p-circuit`. `None` for direct addresses.
+fn circuit_relay_peer(addr: &Multiaddr) -> Option<PeerId> {
+    let mut last_peer = None;
+    for protocol in addr.iter() {
+        match protocol {
+            libp2p::multiaddr::Protocol::P2p(peer_id) => last_peer = Some(peer_id),
+            libp2p::multiaddr::Protocol::P2pCircuit => return last_peer,
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reconcile_backoff_doubles_up_to_max() {
+        let interval = Duration::from_secs(60);
+        let max = Duration::from_secs(15 * 60);
+
+        assert_eq!(reconcile_backoff(interval, max, 0), interval);
+        assert_eq!(reconcile_backoff(interval, max, 1), interval * 2);
+        assert_eq!(reconcile_backoff(interval, max, 3), interval * 8);
+        assert_eq!(reconcile_backoff(interval, max, 4), max);
+        assert_eq!(reconcile_backoff(interval, max, u32::MAX), max);
+    }
+
+    #[test]
+    fn test_circuit_relay_peer() {
+        let relay = PeerId::random();
+        let target = PeerId::random();
+
+        let circuit: Multiaddr =
+            format!("/ip4/1.2.3.4/tcp/4001/p2p/{relay}/p2p-circuit/p2p/{target}")
+                .parse()
+                .unwrap();
+        assert_eq!(circuit_relay_peer(&circuit), Some(relay));
+
+        let direct: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{target}")
+            .parse()
+            .unwrap();
+        assert_eq!(circuit_relay_peer(&direct), None);
+    }
+
+    #[test]
+    fn test_own_address_echo_is_not_a_conflict() {
+        let relay = PeerId::random();
+        let other_relay = PeerId::random();
+        let me = PeerId::random();
+        let own: Vec<Multiaddr> = vec![
+            "/ip4/192.168.1.20/tcp/9000".parse().unwrap(),
+            format!("/ip4/5.6.7.8/tcp/4001/p2p/{relay}/p2p-circuit")
+                .parse()
+                .unwrap(),
+        ];
+        let addr = |s: String| -> Multiaddr { s.parse().unwrap() };
+
+        // Our own LAN address on another port, loopback, or our relay circuit
+        assert!(is_own_address(
+            &addr("/ip4/192.168.1.20/tcp/51234".into()),
+            &own
+        ));
+        assert!(is_own_address(
+            &addr("/ip4/127.0.0.1/udp/9000/quic-v1".into()),
+            &own
+        ));
+        assert!(is_own_address(
+            &addr(format!(
+                "/ip4/5.6.7.8/tcp/4001/p2p/{relay}/p2p-circuit/p2p/{me}"
+            )),
+            &own
+        ));
+        assert!(is_own_address(
+            &addr("/dns4/example.com/tcp/9000".into()),
+            &own
+        ));
+
+        // Another machine on the LAN, or a circuit through a relay we don't use
+        assert!(!is_own_address(
+            &addr("/ip4/192.168.1.31/tcp/9000".into()),
+            &own
+        ));
+        assert!(!is_own_address(
+            &addr(format!(
+                "/ip4/5.6.7.9/tcp/4001/p2p/{other_relay}/p2p-circuit/p2p/{me}"
+            )),
+            &own
+        ));
+    }
+
+    #[test]
+    fn test_relay_ping_results_degrade_and_recover() {
+        let mut liveness = RelayLiveness {
+            relay_peer_id: PeerId::random().to_string(),
+            health: RelayHealth::Alive,
+            rtt_ms: None,
+            last_pong_at: None,
+            consecutive_failures: 0,
+        };
+
+        apply_relay_ping_result(&mut liveness, Some(Duration::from_millis(42)), 100);
+        assert_eq!(liveness.rtt_ms, Some(42));
+        assert_eq!(liveness.last_pong_at, Some(100));
+
+        // A single missed ping is tolerated
+        apply_relay_ping_result(&mut liveness, None, 115);
+        assert_eq!(liveness.health, RelayHealth::Alive);
+
+        for _ in 1..RELAY_DEGRADED_AFTER_FAILED_PINGS {
+            apply_relay_ping_result(&mut liveness, None, 130);
+        }
+        assert_eq!(liveness.health, RelayHealth::Degraded);
+        // The last known RTT is kept while degraded
+        assert_eq!(liveness.rtt_ms, Some(42));
+
+        apply_relay_ping_result(&mut liveness, Some(Duration::from_millis(60)), 145);
+        assert_eq!(liveness.health, RelayHealth::Alive);
+        assert_eq!(liveness.consecutive_failures, 0);
+        assert_eq!(liveness.rtt_ms, Some(60));
+    }
+
+    #[test]
+    fn test_peers_to_evict_under_limit() {
+        let now = Instant::now();
+        let candidates = vec![(PeerId::random(), now)];
+        assert!(peers_to_evict(10, 10, 2, candidates).is_empty());
+    }
+
+    #[test]
+    fn test_peers_to_evict_trims_below_limit_oldest_first() {
+        let now = Instant::now();
+        let oldest = PeerId::random();
+        let older = PeerId::random();
+        let newest = PeerId::random();
+        let candidates = vec![
+            (newest, now),
+            (oldest, now - Duration::from_secs(30)),
+            (older, now - Duration::from_secs(10)),
+        ];
+
+        // One over a limit of 10 with a margin of 1 evicts two peers
+        assert_eq!(peers_to_evict(11, 10, 1, candidates), vec![oldest, older]);
+    }
+
+    #[test]
+    fn test_peers_to_evict_limited_by_candidates() {
+        let now = Instant::now();
+        let peer = PeerId::random();
+        assert_eq!(peers_to_evict(20, 10, 5, vec![(peer, now)]), vec![peer]);
+    }
+
+    #[test]
+    fn test_select_sync_peers_contacts_and_reputation() {
+        let contact = PeerId::random();
+        let reliable = PeerId::random();
+        let flaky = PeerId::random();
+        let connected = vec![contact, reliable, flaky];
+        let scores = HashMap::from([
+            (
+                reliable,
+                PeerSyncScore {
+                    successes: 5,
+                    failures: 0,
+                },
+            ),
+            (
+                flaky,
+                PeerSyncScore {
+                    successes: 3,
+                    failures: 2,
+                },
+            ),
+        ]);
+        let mut offset = 0;
+
+        let contacts_only = select_sync_peers(
+            SyncStrategy::ContactsOnly,
+            connected.clone(),
+            |peer| *peer == contact,
+            &scores,
+            &mut offset,
+        );
+        assert_eq!(contacts_only, vec![contact]);
+
+        // Unknown peers (reputation 0) rank above ones that failed more than they served
+        let top = select_sync_peers(
+            SyncStrategy::TopReputation { max_peers: 2 },
+            connected,
+            |_| false,
+            &scores,
+            &mut offset,
+        );
+        assert_eq!(top, vec![reliable, contact]);
+    }
+
+    #[test]
+    fn test_select_sync_peers_round_robin_visits_everyone() {
+        let connected: Vec<PeerId> = (0..5).map(|_| PeerId::random()).collect();
+        let scores = HashMap::new();
+        let mut offset = 0;
+        let mut seen = HashSet::new();
+
+        for _ in 0..3 {
+            let batch = select_sync_peers(
+                SyncStrategy::RoundRobin { max_peers: 2 },
+                connected.clone(),
+                |_| false,
+                &scores,
+                &mut offset,
+            );
+            assert_eq!(batch.len(), 2);
+            seen.extend(batch);
+        }
+        assert_eq!(seen.len(), connected.len());
+        assert_eq!(
+            select_sync_peers(
+                SyncStrategy::RoundRobin { max_peers: 2 },
+                Vec::new(),
+                |_| false,
+                &scores,
+                &mut offset,
+            ),
+            Vec::<PeerId>::new()
+        );
+    }
+
+    #[test]
+    fn test_dial_error_for_address() {
+        let peer = PeerId::random();
+        let refused: Multiaddr = "/ip4/10.0.0.1/tcp/4001".parse().unwrap();
+        let timed_out: Multiaddr = format!("/ip4/1.2.3.4/tcp/4001/p2p/{peer}").parse().unwrap();
+        let error = DialError::Transport(vec![
+            (
+                refused.clone(),
+                libp2p::TransportError::Other(std::io::Error::other("connection refused")),
+            ),
+            (
+                timed_out.clone(),
+                libp2p::TransportError::Other(std::io::Error::other("timed out")),
+            ),
+        ]);
+
+        assert!(dial_error_for_address(&error, Some(&refused)).contains("connection refused"));
+        // Matches even though the attempt was recorded without the /p2p suffix
+        let timed_out_bare: Multiaddr = "/ip4/1.2.3.4/tcp/4001".parse().unwrap();
+        assert!(dial_error_for_address(&error, Some(&timed_out_bare)).contains("timed out"));
+        // Unknown addresses and dials without one get the whole error
+        assert_eq!(dial_error_for_address(&error, None), error.to_string());
+
+        let aborted = DialError::Aborted;
+        assert_eq!(
+            dial_error_for_address(&aborted, Some(&refused)),
+            aborted.to_string()
+        );
+    }
+
+    #[test]
+    fn test_auto_identity_queue_deduplicates_and_keeps_order() {
+        let mut queue = AutoIdentityQueue::default();
+        let first = PeerId::random();
+        let second = PeerId::random();
+
+        assert!(queue.push(first));
+        assert!(queue.push(second));
+        // A second connection to the same peer doesn't queue it twice
+        assert!(!queue.push(first));
+
+        assert_eq!(queue.pop(), Some(first));
+        assert_eq!(queue.pop(), Some(second));
+        assert_eq!(queue.pop(), None);
+
+        // Peers already asked this session are not asked again on reconnect
+        assert!(!queue.push(first));
+        assert!(queue.is_empty());
+    }
+
+    #[test]
+    fn test_pending_dials_track_one_dial_per_peer() {
+        let mut dials = PendingDials::default();
+        let peer = PeerId::random();
+        let connection = ConnectionId::new_unchecked(1);
+        let attempts = vec![
+            DialAttempt {
+                attempt_id: 1,
+                peer_id: peer,
+                address: Some("/ip4/192.168.1.20/tcp/4001".parse().unwrap()),
+            },
+            DialAttempt {
+                attempt_id: 2,
+                peer_id: peer,
+                address: None,
+            },
+        ];
+
+        assert!(!dials.is_dialing(&peer));
+        dials.insert(connection, peer, attempts);
+        assert!(dials.is_dialing(&peer));
+
+        // An unrelated connection finishing leaves the dial in place
+        assert!(dials.finish(ConnectionId::new_unchecked(2)).is_empty());
+        assert!(dials.is_dialing(&peer));
+
+        let finished = dials.finish(connection);
+        assert_eq!(
+            finished.iter().map(|a| a.attempt_id).collect::<Vec<_>>(),
+            vec![1, 2]
+        );
+        assert!(!dials.is_dialing(&peer));
+    }
+
+    #[test]
+    fn test_peer_connections_prefer_direct_over_relayed() {
+        let mut connections = PeerConnections::default();
+        let peer = PeerId::random();
+        let relay = PeerId::random();
+        let relayed = ConnectionId::new_unchecked(1);
+        let punched = ConnectionId::new_unchecked(2);
+        let circuit: Multiaddr = format!("/ip4/203.0.113.5/tcp/4001/p2p/{}/p2p-circuit", relay)
+            .parse()
+            .unwrap();
+
+        assert_eq!(connections.connection_type(&peer), None);
+        connections.established(peer, relayed, &circuit);
+        assert_eq!(
+            connections.connection_type(&peer),
+            Some((ConnectionType::Relayed, Some(relay)))
+        );
+
+        // DCUtR reports the upgrade on the new direct connection
+        connections.established(
+            peer,
+            punched,
+            &"/ip4/198.51.100.7/udp/4001/quic-v1".parse().unwrap(),
+        );
+        connections.hole_punched(peer, punched);
+        assert_eq!(
+            connections.connection_type(&peer),
+            Some((ConnectionType::Holepunched, None))
+        );
+
+        // The relayed connection going idle doesn't change that
+        connections.closed(&peer, relayed);
+        assert_eq!(
+            connections.connection_type(&peer),
+            Some((ConnectionType::Holepunched, None))
+        );
+
+        connections.closed(&peer, punched);
+        assert_eq!(connections.connection_type(&peer), None);
+        assert!(connections.connections.is_empty());
+    }
+
+    #[test]
+    fn test_nat_probe_tracker_needs_consecutive_agreement() {
+        let mut probes = NatProbeTracker::new(3);
+        assert_eq!(probes.record(NatStatus::Private), None);
+        assert_eq!(probes.record(NatStatus::Private), None);
+        // A single disagreeing probe resets the streak
+        assert_eq!(probes.record(NatStatus::Public), None);
+        assert_eq!(probes.record(NatStatus::Private), None);
+        assert_eq!(probes.record(NatStatus::Private), None);
+        assert_eq!(probes.record(NatStatus::Private), Some(NatStatus::Private));
+        // Further agreeing probes keep confirming the status
+        assert_eq!(probes.record(NatStatus::Private), Some(NatStatus::Private));
+
+        let mut eager = NatProbeTracker::new(1);
+        assert_eq!(eager.record(NatStatus::Public), Some(NatStatus::Public));
+    }
+
+    fn advertised_identity(display_name: &str) -> AdvertisedIdentity {
+        AdvertisedIdentity {
+            public_key: vec![1; 32],
+            x25519_public: vec![2; 32],
+            display_name: display_name.to_string(),
+            avatar_hash: None,
+            bio: None,
+            supported_ciphers: None,
+        }
+    }
+
+    #[test]
+    fn test_identity_exchange_cache_skips_repeats_within_cooldown() {
+        let mut cache = IdentityExchangeCache::new(Duration::from_secs(60));
+        let peer = PeerId::random();
+        let start = Instant::now();
+
+        assert!(!cache.is_cooling_down(&peer, start));
+        assert!(cache.record(peer, advertised_identity("Alice"), start));
+        assert!(cache.is_cooling_down(&peer, start + Duration::from_secs(30)));
+
+        // The same identity again is skipped
+        assert!(!cache.record(
+            peer,
+            advertised_identity("Alice"),
+            start + Duration::from_secs(30)
+        ));
+        // A profile change is stored despite the cooldown
+        assert!(cache.record(
+            peer,
+            advertised_identity("Alice B"),
+            start + Duration::from_secs(40)
+        ));
+
+        // Once the cooldown is over the identity is stored again
+        let later = start + Duration::from_secs(101);
+        assert!(!cache.is_cooling_down(&peer, later));
+        assert!(cache.record(peer, advertised_identity("Alice B"), later));
+    }
+
+    #[test]
+    fn test_identity_exchange_cache_disabled_with_zero_cooldown() {
+        let mut cache = IdentityExchangeCache::new(Duration::ZERO);
+        let peer = PeerId::random();
+        let now = Instant::now();
+
+        assert!(cache.record(peer, advertised_identity("Alice"), now));
+        assert!(cache.record(peer, advertised_identity("Alice"), now));
+        assert!(!cache.is_cooling_down(&peer, now));
+    }
+
+    #[test]
+    fn test_auto_identity_queue_cancel_allows_requeue() {
+        let mut queue = AutoIdentityQueue::default();
+        let peer = PeerId::random();
+        let other = PeerId::random();
+
+        queue.push(peer);
+        queue.push(other);
+        queue.cancel(&peer);
+        assert_eq!(queue.pop(), Some(other));
+        assert!(queue.is_empty());
+
+        // Disconnecting before the request went out doesn't count as asked
+        assert!(queue.push(peer));
+
+        // Cancelling a peer that was already asked keeps it marked as asked
+        assert_eq!(queue.pop(), Some(peer));
+        queue.cancel(&peer);
+        assert!(!queue.push(peer));
+    }
+
+    #[test]
+    fn test_listen_port() {
+        let tcp: Multiaddr = "/ip4/192.168.1.20/tcp/4001".parse().unwrap();
+        let quic: Multiaddr = "/ip4/192.168.1.20/udp/4002/quic-v1".parse().unwrap();
+        assert_eq!(listen_port(&tcp), Some(4001));
+        assert_eq!(listen_port(&quic), Some(4002));
+        assert_eq!(listen_port(&"/ip4/127.0.0.1".parse().unwrap()), None);
+
+        assert_eq!(
+            ListenTransport::Quic.listen_addr(4002).to_string(),
+            "/ip4/0.0.0.0/udp/4002/quic-v1"
+        );
+    }
+
+    #[test]
+    fn test_port_in_use_detects_taken_ports() {
+        let tcp = std::net::TcpListener::bind("0.0.0.0:0").unwrap();
+        let tcp_port = tcp.local_addr().unwrap().port();
+        assert!(ListenTransport::Tcp.port_in_use(tcp_port));
+
+        let udp = std::net::UdpSocket::bind("0.0.0.0:0").unwrap();
+        let udp_port = udp.local_addr().unwrap().port();
+        assert!(ListenTransport::Quic.port_in_use(udp_port));
+    }
+
+    #[test]
+    fn test_droppable_events_are_counted_when_channel_is_full() {
+        let (tx, mut rx) = mpsc::channel(1);
+        let events = EventSender::new(tx);
+
+        events.send_droppable(NetworkEvent::PeerDiscovered {
+            peer_id: "first".to_string(),
+        });
+        events.send_droppable(NetworkEvent::PeerDiscovered {
+            peer_id: "second".to_string(),
+        });
+        assert_eq!(events.dropped(), 1);
+
+        // The queued event is the one that fit, and draining makes room again
+        match rx.try_recv() {
+            Ok(NetworkEvent::PeerDiscovered { peer_id }) => assert_eq!(peer_id, "first"),
+            other => panic!("unexpected event: {:?}", other),
+        }
+        events.send_droppable(NetworkEvent::PeerExpired {
+            peer_id: "third".to_string(),
+        });
+        assert_eq!(events.dropped(), 1);
+    }
+
+    #[test]
+    fn test_classify_dial_error_per_address() {
+        use libp2p::TransportError;
+        use std::io;
+
+        let tcp: Multiaddr = "/ip4/192.168.1.5/tcp/9000".parse().unwrap();
+        let quic: Multiaddr = "/ip4/192.168.1.5/udp/9000/quic-v1".parse().unwrap();
+        let relay: Multiaddr = "/ip4/5.6.7.8/tcp/4001/ws".parse().unwrap();
+        let upgrade: Multiaddr = "/ip4/10.0.0.2/tcp/9000".parse().unwrap();
+        let error = DialError::Transport(vec![
+            (
+                tcp.clone(),
+                TransportError::Other(io::Error::from(io::ErrorKind::ConnectionRefused)),
+            ),
+            (
+                quic.clone(),
+                TransportError::Other(io::Error::other("Handshake with the remote timed out")),
+            ),
+            (
+                relay.clone(),
+                TransportError::MultiaddrNotSupported(relay.clone()),
+            ),
+            (
+                upgrade.clone(),
+                TransportError::Other(io::Error::other("Handshake failed: noise: decrypt error")),
+            ),
+        ]);
+
+        let failures = classify_dial_error(&error);
+        let categories: Vec<_> = failures
+            .iter()
+            .map(|(address, category, _)| (address.clone().unwrap(), *category))
+            .collect();
+        assert_eq!(
+            categories,
+            vec![
+                (tcp, ConnectionFailureCategory::MultiaddrUnreachable),
+                (quic, ConnectionFailureCategory::Timeout),
+                (relay, ConnectionFailureCategory::TransportUnsupported),
+                (upgrade, ConnectionFailureCategory::Handshake),
+            ]
+        );
+        assert!(failures[3].2.contains("noise: decrypt error"));
+
+        // A dial skipped by its condition isn't a failure
+        let skipped = DialError::DialPeerConditionFalse(
+            libp2p::swarm::dial_opts::PeerCondition::Disconnected,
+        );
+        assert!(classify_dial_error(&skipped).is_empty());
+        assert_eq!(
+            classify_dial_error(&DialError::NoAddresses)[0].1,
+            ConnectionFailureCategory::MultiaddrUnreachable
+        );
+    }
+}
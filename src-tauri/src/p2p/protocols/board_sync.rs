@@ -65,6 +65,15 @@ pub enum BoardSyncRequest {
         timestamp: i64,
         signature: Vec<u8>,
     },
+    /// React to a board post, or withdraw our reaction when `remove` is set
+    ReactToPost {
+        post_id: String,
+        reactor_peer_id: String,
+        reaction_type: String,
+        remove: bool,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
     /// Submit a wall post to the relay for offline availability
     SubmitWallPost {
         author_peer_id: String,
@@ -96,6 +105,28 @@ pub enum BoardSyncRequest {
         timestamp: i64,
         signature: Vec<u8>,
     },
+    /// Create a new board (only accepted by relays run with --allow-peer-boards)
+    CreateBoard {
+        requester_peer_id: String,
+        name: String,
+        description: Option<String>,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
+    /// Remove our registration from the relay when leaving its community
+    DeregisterPeer {
+        peer_id: String,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
+    /// Ask for a signed invite to share (only accepted by relays run with --allow-member-invites)
+    CreateInvite {
+        requester_peer_id: String,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
+        timestamp: i64,
+        signature: Vec<u8>,
+    },
 }
 
 /// Board info in responses
@@ -120,6 +151,18 @@ pub struct BoardPostInfo {
     pub created_at: i64,
     pub deleted_at: Option<i64>,
     pub signature: Vec<u8>,
+    /// When the relay received the post (absent from older relays)
+    #[serde(default)]
+    pub relay_observed_at: Option<i64>,
+    /// Relay signature anchoring `relay_observed_at` to this post
+    #[serde(default)]
+    pub relay_signature: Option<Vec<u8>>,
+    /// Number of peers who reacted to the post (absent from older relays)
+    #[serde(default)]
+    pub reaction_count: u64,
+    /// Our own reaction to the post
+    #[serde(default)]
+    pub requester_reaction: Option<String>,
 }
 
 /// Wall post data in responses
@@ -136,6 +179,9 @@ pub struct WallPostData {
     pub stored_at: i64,
     #[serde(default)]
     pub media_items: Vec<WallPostMediaItem>,
+    /// Relay signature anchoring `stored_at` to this post (absent from older relays)
+    #[serde(default)]
+    pub relay_signature: Option<Vec<u8>>,
 }
 
 /// Board sync response (wire protocol)
@@ -157,8 +203,16 @@ pub enum BoardSyncResponse {
     PostAccepted { post_id: String },
     /// Peer was registered
     PeerRegistered { peer_id: String },
+    /// Peer was deregistered
+    PeerDeregistered { peer_id: String },
     /// Post was deleted
     PostDeleted { post_id: String },
+    /// Reaction was recorded; carries the post's new count and our reaction
+    ReactionRecorded {
+        post_id: String,
+        reaction_count: u64,
+        requester_reaction: Option<String>,
+    },
     /// Wall posts for a specific author
     WallPosts {
         posts: Vec<WallPostData>,
@@ -168,6 +222,10 @@ pub enum BoardSyncResponse {
     WallPostStored { post_id: String },
     /// Wall post was deleted from the relay
     WallPostDeleted { post_id: String },
+    /// Board was created on the relay
+    BoardCreated { board: BoardInfo },
+    /// Signed invite to the relay's community, in its text form
+    InviteCreated { token: String },
     /// Error response
     Error { error: String },
 }
@@ -0,0 +1,27 @@
+//! Call data protocol types
+//!
+//! Carries the in-call data channel: short text or small files sent between
+//! the two parties of a connected call, outside the messaging protocol.
+
+use serde::{Deserialize, Serialize};
+
+/// Protocol version string for in-call data
+pub const CALL_DATA_PROTOCOL: &str = "/harbor/call-data/1.0.0";
+
+/// A signed frame on a call's data channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallDataFrame {
+    pub call_id: String,
+    pub sender_peer_id: String,
+    pub payload: Vec<u8>,
+    pub timestamp: i64,
+    /// Signature over `SignableCallData { call_id, sender_peer_id, payload, timestamp }`
+    pub signature: Vec<u8>,
+}
+
+/// Acknowledgement for a data frame
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CallDataAck {
+    pub accepted: bool,
+    pub error: Option<String>,
+}
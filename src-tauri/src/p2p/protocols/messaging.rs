@@ -21,6 +21,31 @@ use serde::{Deserialize, Serialize};
 /// 4. The nonce is permanently recorded to prevent future replay
 ///
 /// This prevents attackers from re-sending captured messages.
+///
+/// # Nonce Salt
+///
+/// Newer senders also include a random 12-byte `nonce_salt` that is mixed
+/// into the nonce, so nonces stay unique even if the counter resets. It is
+/// absent for messages from older clients, which use the counter alone.
+///
+/// # Protocol Version
+///
+/// `protocol_version` announces what the sender understands. Clients on
+/// version 2 encrypt with a per-conversation ratchet and send a `ratchet`
+/// header, but only after the peer has announced version 2 itself. Older
+/// clients omit both fields and get statically keyed messages.
+///
+/// # Cipher
+///
+/// `cipher` names the AEAD the content is encrypted with. Older clients
+/// omit it and always use AES-256-GCM. Receivers reject identifiers they
+/// don't know instead of guessing.
+///
+/// # Voice Notes
+///
+/// Messages with content type `voice` carry a `voice` note naming audio in
+/// the sender's media storage; the encrypted content is an optional caption.
+/// The recipient fetches the audio over media sync.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct DirectMessage {
     /// Unique message ID (UUID v4)
@@ -31,7 +56,7 @@ pub struct DirectMessage {
     pub sender_peer_id: String,
     /// Recipient's peer ID
     pub recipient_peer_id: String,
-    /// Encrypted message content (AES-256-GCM with counter-based nonce)
+    /// Encrypted message content (under `cipher`, with counter-based nonce)
     pub content_encrypted: Vec<u8>,
     /// Content type (text, image, etc.)
     pub content_type: String,
@@ -46,6 +71,24 @@ pub struct DirectMessage {
     pub timestamp: i64,
     /// Signature over all fields above (excluding signature itself)
     pub signature: Vec<u8>,
+    /// Random per-message salt mixed into the AES-GCM nonce (signed)
+    #[serde(default)]
+    pub nonce_salt: Option<Vec<u8>>,
+    /// Signature scheme version; peers that predate versions omit it
+    #[serde(default = "crate::services::legacy_sig_version")]
+    pub sig_version: u32,
+    /// Messaging protocol version of the sender (signed)
+    #[serde(default = "crate::services::legacy_protocol_version")]
+    pub protocol_version: u32,
+    /// Ratchet header when the content uses a ratcheted key (signed)
+    #[serde(default)]
+    pub ratchet: Option<crate::services::RatchetHeader>,
+    /// Identifier of the cipher the content is encrypted with (signed)
+    #[serde(default = "crate::services::legacy_message_cipher")]
+    pub cipher: String,
+    /// Voice note audio for `voice` messages (signed)
+    #[serde(default)]
+    pub voice: Option<crate::services::VoiceNote>,
 }
 
 /// Acknowledgment of message delivery/read
@@ -65,6 +108,70 @@ pub struct MessageAck {
     pub signature: Vec<u8>,
 }
 
+/// A signed edit to a message, replacing its content.
+///
+/// The new content is encrypted the way a new message would be. Under the
+/// conversation's ratchet it carries a `ratchet` header and an empty
+/// `nonce_salt`, and the receiver re-encrypts it for storage. Otherwise it's
+/// encrypted under the static conversation key with the original message's
+/// cipher and nonce counter and a fresh `nonce_salt`, so it can be stored as
+/// is. Receivers apply an edit only if its `lamport_clock` is newer than the
+/// last one applied.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageEdit {
+    /// ID of the message being edited
+    pub message_id: String,
+    pub conversation_id: String,
+    /// Must be the original message's sender
+    pub sender_peer_id: String,
+    pub content_encrypted: Vec<u8>,
+    pub nonce_salt: Vec<u8>,
+    /// Sender's lamport clock at the edit
+    pub lamport_clock: u64,
+    pub edited_at: i64,
+    /// Ratchet header when the content uses a ratcheted key (signed)
+    #[serde(default)]
+    pub ratchet: Option<crate::services::RatchetHeader>,
+    /// Signature over all fields above
+    pub signature: Vec<u8>,
+}
+
+/// A signed tombstone deleting a message for both parties. Deleting an
+/// already deleted message changes nothing, and edits after it are ignored.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MessageDelete {
+    /// ID of the message being deleted
+    pub message_id: String,
+    pub conversation_id: String,
+    /// Must be the original message's sender
+    pub sender_peer_id: String,
+    /// Sender's lamport clock at the deletion
+    pub lamport_clock: u64,
+    pub deleted_at: i64,
+    /// Signature over all fields above
+    pub signature: Vec<u8>,
+}
+
+/// A signed notice that the reader has seen messages in a conversation.
+///
+/// One receipt covers up to `MAX_READ_RECEIPT_MESSAGES` messages, so marking
+/// a conversation read costs a single frame instead of one ack per message.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadReceipt {
+    pub conversation_id: String,
+    /// Peer ID of the reader, the recipient of the messages
+    pub reader_peer_id: String,
+    /// IDs of the messages seen
+    pub message_ids: Vec<String>,
+    /// Unix timestamp when the messages were marked read
+    pub read_at: i64,
+    /// Signature over all fields above
+    pub signature: Vec<u8>,
+}
+
+/// Most messages a single read receipt may cover
+pub const MAX_READ_RECEIPT_MESSAGES: usize = 500;
+
 /// Message acknowledgment status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 #[serde(rename_all = "snake_case")]
@@ -81,15 +188,12 @@ pub enum MessagingMessage {
     Message(DirectMessage),
     /// An acknowledgment
     Ack(MessageAck),
-    /// An edit to a previously sent message
-    EditMessage {
-        /// The ID of the message being edited
-        message_id: String,
-        /// The new plaintext content (will be encrypted by the receiver's local store)
-        new_content: String,
-        /// Timestamp of the edit
-        edited_at: i64,
-    },
+    /// An edit to a message the sender sent earlier
+    Edit(MessageEdit),
+    /// A tombstone for a message the sender sent earlier
+    Delete(MessageDelete),
+    /// The recipient has seen a batch of messages
+    ReadReceipt(ReadReceipt),
 }
 
 /// Codec for messaging protocol
@@ -148,6 +252,17 @@ mod tests {
             lamport_clock: 1,
             timestamp: 1234567890,
             signature: vec![5, 6, 7, 8],
+            nonce_salt: Some(vec![9u8; 12]),
+            sig_version: 1,
+            protocol_version: 2,
+            ratchet: Some(crate::services::RatchetHeader {
+                sender_key: vec![1u8; 32],
+                generation: 3,
+                recipient_key: vec![2u8; 32],
+                index: 7,
+            }),
+            cipher: "chacha20-poly1305".to_string(),
+            voice: None,
         };
 
         let wrapped = MessagingMessage::Message(msg.clone());
@@ -157,6 +272,10 @@ mod tests {
         if let MessagingMessage::Message(decoded_msg) = decoded {
             assert_eq!(decoded_msg.message_id, msg.message_id);
             assert_eq!(decoded_msg.content_encrypted, msg.content_encrypted);
+            assert_eq!(decoded_msg.nonce_salt, msg.nonce_salt);
+            assert_eq!(decoded_msg.protocol_version, 2);
+            assert_eq!(decoded_msg.ratchet, msg.ratchet);
+            assert_eq!(decoded_msg.cipher, msg.cipher);
         } else {
             panic!("Expected Message variant");
         }
@@ -185,6 +304,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_message_delete_roundtrip() {
+        let delete = MessageDelete {
+            message_id: "msg-123".to_string(),
+            conversation_id: "conv-456".to_string(),
+            sender_peer_id: "peer-a".to_string(),
+            lamport_clock: 9,
+            deleted_at: 1234567890,
+            signature: vec![1, 2, 3],
+        };
+
+        let encoded = MessagingCodec::encode(&MessagingMessage::Delete(delete)).unwrap();
+        match MessagingCodec::decode(&encoded).unwrap() {
+            MessagingMessage::Delete(decoded) => {
+                assert_eq!(decoded.message_id, "msg-123");
+                assert_eq!(decoded.lamport_clock, 9);
+            }
+            other => panic!("Expected Delete variant, got {:?}", other),
+        }
+    }
+
     #[test]
     fn test_conversation_id_deterministic() {
         let id1 = derive_conversation_id("peer-a", "peer-b");
@@ -195,4 +335,14 @@ mod tests {
             "Conversation ID should be the same regardless of order"
         );
     }
+
+    #[test]
+    fn test_conversation_id_sorts_peer_ids() {
+        let a = "12D3KooWAaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa";
+        let b = "12D3KooWBbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb";
+
+        assert_eq!(derive_conversation_id(a, b), derive_conversation_id(b, a));
+        assert_eq!(derive_conversation_id(a, b).len(), 32);
+        assert_ne!(derive_conversation_id(a, a), derive_conversation_id(a, b));
+    }
 }
@@ -1,14 +1,18 @@
 pub mod board_sync;
+pub mod call_data;
 pub mod content_sync;
 pub mod identity_exchange;
 pub mod media_sync;
 pub mod messaging;
+pub mod presence;
 
 pub use board_sync::*;
+pub use call_data::*;
 pub use content_sync::*;
 pub use identity_exchange::*;
 pub use media_sync::*;
 pub use messaging::*;
+pub use presence::*;
 
 /// Protocol version string for identity exchange
 pub const IDENTITY_PROTOCOL: &str = "/harbor/identity/1.0.0";
@@ -25,4 +29,4 @@ pub const SIGNALING_PROTOCOL: &str = "/harbor/signaling/1.0.0";
 /// Protocol version string for board sync (community boards)
 pub const BOARD_SYNC_PROTOCOL: &str = "/harbor/board/1.0.0";
 
-// MEDIA_SYNC_PROTOCOL is defined in media_sync.rs and re-exported via pub use
+// CALL_DATA_PROTOCOL, MEDIA_SYNC_PROTOCOL and PRESENCE_PROTOCOL are defined in their modules and re-exported via pub use
@@ -0,0 +1,19 @@
+//! Presence protocol types
+//!
+//! Connected contacts periodically exchange a small signed heartbeat so
+//! that `last_seen` reflects a verified timestamp rather than raw
+//! connection events (which are noisy over relayed connections).
+
+use serde::{Deserialize, Serialize};
+
+/// Protocol version string for presence heartbeats
+pub const PRESENCE_PROTOCOL: &str = "/harbor/presence/1.0.0";
+
+/// Signed heartbeat, sent as the request and echoed back as the response
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Heartbeat {
+    pub peer_id: String,
+    pub timestamp: i64,
+    /// Signature over `SignableHeartbeat { peer_id, timestamp }`
+    pub signature: Vec<u8>,
+}
@@ -7,6 +7,9 @@ use crate::error::{AppError, Result};
 
 /// Build a libp2p swarm with all configured protocols
 pub fn build_swarm(keypair: Keypair, config: &NetworkConfig) -> Result<Swarm<ChatBehaviour>> {
+    config.kademlia.validate()?;
+    config.mdns.validate()?;
+
     let local_peer_id = PeerId::from(keypair.public());
 
     info!("Building swarm with peer ID: {}", local_peer_id);
@@ -27,6 +30,12 @@ pub fn build_swarm(keypair: Keypair, config: &NetworkConfig) -> Result<Swarm<Cha
                 PeerId::from(keypair.public()),
                 keypair.public(),
                 relay_behaviour,
+                config.enable_autonat,
+                config.address_filter,
+                &config.request_timeouts,
+                &config.kademlia,
+                &config.mdns,
+                config.agent_version.clone(),
             ))
         })
         .map_err(|e| AppError::Network(format!("Behaviour error: {}", e)))?
@@ -1,8 +1,14 @@
+use libp2p::multiaddr::Protocol;
 use libp2p::{Multiaddr, PeerId};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::net::{Ipv4Addr, Ipv6Addr};
+use tokio::sync::oneshot;
 
 use super::protocols::board_sync::WallPostMediaItem;
+use super::protocols::call_data::CallDataFrame;
+use crate::db::ActivityKind;
+use crate::services::SharedContact;
 
 /// Network connection status
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
@@ -28,6 +34,115 @@ pub enum NatStatus {
     BehindNat,
 }
 
+impl NatStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NatStatus::Unknown => "unknown",
+            NatStatus::Public => "public",
+            NatStatus::Private => "private",
+            NatStatus::BehindNat => "behind_nat",
+        }
+    }
+}
+
+impl std::str::FromStr for NatStatus {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unknown" => Ok(NatStatus::Unknown),
+            "public" => Ok(NatStatus::Public),
+            "private" => Ok(NatStatus::Private),
+            "behind_nat" => Ok(NatStatus::BehindNat),
+            _ => Err(format!("Unknown NAT status: {}", s)),
+        }
+    }
+}
+
+/// Which of our addresses are announced to remote peers, via Identify and
+/// shareable addresses. mDNS still announces every listen address on the
+/// local network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AddressFilter {
+    /// Announce every address, including loopback
+    All,
+    /// Drop loopback, link-local and unspecified addresses
+    #[default]
+    Routable,
+    /// Also drop private ranges (RFC 1918, carrier-grade NAT, IPv6 unique local)
+    PublicOnly,
+}
+
+impl AddressFilter {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AddressFilter::All => "all",
+            AddressFilter::Routable => "routable",
+            AddressFilter::PublicOnly => "public_only",
+        }
+    }
+
+    /// Whether `addr` may be announced under this policy. Addresses that
+    /// don't start with an IP (e.g. DNS names) are always allowed.
+    pub fn allows(&self, addr: &Multiaddr) -> bool {
+        if *self == AddressFilter::All {
+            return true;
+        }
+        let (local, private) = match addr.iter().next() {
+            Some(Protocol::Ip4(ip)) => (is_local_ipv4(&ip), is_private_ipv4(&ip)),
+            Some(Protocol::Ip6(ip)) => (is_local_ipv6(&ip), is_private_ipv6(&ip)),
+            _ => return true,
+        };
+        !local && !(private && *self == AddressFilter::PublicOnly)
+    }
+}
+
+impl std::str::FromStr for AddressFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "all" => Ok(AddressFilter::All),
+            "routable" => Ok(AddressFilter::Routable),
+            "public_only" => Ok(AddressFilter::PublicOnly),
+            _ => Err(format!("Unknown address filter: {}", s)),
+        }
+    }
+}
+
+/// Loopback, link-local or unspecified: never reachable from another host
+fn is_local_ipv4(ip: &Ipv4Addr) -> bool {
+    ip.is_loopback() || ip.is_link_local() || ip.is_unspecified()
+}
+
+fn is_private_ipv4(ip: &Ipv4Addr) -> bool {
+    let [a, b, ..] = ip.octets();
+    // 100.64.0.0/10 is shared address space behind carrier-grade NAT
+    ip.is_private() || (a == 100 && (b & 0xc0) == 64)
+}
+
+fn is_local_ipv6(ip: &Ipv6Addr) -> bool {
+    ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xffc0) == 0xfe80
+}
+
+fn is_private_ipv6(ip: &Ipv6Addr) -> bool {
+    // fc00::/7 unique local addresses
+    (ip.segments()[0] & 0xfe00) == 0xfc00
+}
+
+/// How we reach a connected peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionType {
+    /// A direct connection dialed by either side
+    Direct,
+    /// Through a relay circuit
+    Relayed,
+    /// A direct connection that hole punching (DCUtR) set up from a relayed one
+    Holepunched,
+}
+
 /// Information about a discovered or connected peer
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -36,8 +151,80 @@ pub struct PeerInfo {
     pub addresses: Vec<String>,
     pub protocol_version: Option<String>,
     pub agent_version: Option<String>,
+    /// Harbor version read from `agent_version`; `None` for other software
+    pub app_version: Option<String>,
+    /// Platform read from `agent_version`
+    pub platform: Option<String>,
     pub is_connected: bool,
     pub last_seen: Option<i64>,
+    /// Best of the open connections: hole punched or direct before relayed
+    pub connection_type: ConnectionType,
+    /// The relay carrying the connection when it is relayed
+    pub relay_peer_id: Option<String>,
+}
+
+/// Result of syncing content with a single peer
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PeerSyncSummary {
+    pub peer_id: String,
+    /// Posts fetched and stored during this sync
+    pub posts_fetched: usize,
+    /// The peer has more posts beyond this batch
+    pub has_more: bool,
+    /// Failures from the manifest request or individual post fetches
+    pub errors: Vec<String>,
+}
+
+/// How a message request round-trip ended
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MessageSendOutcome {
+    /// The peer accepted the message
+    Delivered,
+    /// The peer responded but rejected the message
+    Rejected(String),
+    /// No response arrived (timeout, dial failure, closed connection, ...)
+    Failed(String),
+}
+
+/// Ports our listeners actually bound to, which differ from the configured
+/// ones when a port is 0 or the random port fallback kicked in
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BoundPorts {
+    /// `None` until the TCP listener reports its address
+    pub tcp: Option<u16>,
+    /// `None` until the QUIC listener reports its address
+    pub quic: Option<u16>,
+}
+
+/// A peer known to the Kademlia routing table
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DhtRoutingPeer {
+    pub peer_id: String,
+    /// Whether Kademlia currently considers the peer connected
+    pub connected: bool,
+    /// Left empty unless addresses were asked for (debug builds only)
+    pub addresses: Vec<String>,
+}
+
+/// A non-empty k-bucket
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DhtBucket {
+    /// Bucket index: peers here are between 2^index and 2^(index+1) away
+    pub index: u32,
+    pub peers: Vec<DhtRoutingPeer>,
+}
+
+/// Snapshot of the Kademlia routing table, for debugging DHT connectivity
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DhtRoutingTable {
+    pub local_peer_id: String,
+    pub total_peers: usize,
+    pub buckets: Vec<DhtBucket>,
 }
 
 /// Network statistics
@@ -48,12 +235,92 @@ pub struct NetworkStats {
     pub total_bytes_in: u64,
     pub total_bytes_out: u64,
     pub uptime_seconds: u64,
-    /// Current NAT status
+    /// Current NAT status (the manual override if one is set)
     pub nat_status: NatStatus,
+    /// NAT status as detected by AutoNAT and relay reservations
+    pub detected_nat_status: NatStatus,
+    /// Manual NAT status override, if set
+    pub nat_override: Option<NatStatus>,
+    /// Whether AutoNAT probing is enabled
+    pub autonat_enabled: bool,
     /// Relay addresses we can be reached at (via relay)
     pub relay_addresses: Vec<String>,
     /// External addresses discovered via AutoNAT
     pub external_addresses: Vec<String>,
+    /// Which of our addresses are announced to remote peers
+    pub address_filter: AddressFilter,
+    /// Circuit limits advertised by the relays we hold reservations on
+    pub relay_limits: Vec<RelayCircuitLimits>,
+    /// Ping liveness of the relays we hold reservations on
+    pub relay_liveness: Vec<RelayLiveness>,
+    /// Progress and diagnostic events dropped because the app fell behind
+    pub dropped_events: u64,
+}
+
+/// Circuit limits a relay advertised when accepting a reservation or
+/// opening a circuit. `None` means the relay set no limit.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayCircuitLimits {
+    pub relay_peer_id: String,
+    pub max_duration_secs: Option<u64>,
+    pub max_bytes: Option<u64>,
+}
+
+/// Whether a relay we hold a reservation on is answering pings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RelayHealth {
+    /// The last ping was answered
+    Alive,
+    /// Several pings in a row went unanswered. The reservation may still be
+    /// held, but reachability through this relay is at risk.
+    Degraded,
+}
+
+/// Why a connection failed, as far as the error lets us tell
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionFailureCategory {
+    /// The transport connected but the security or multiplexer upgrade
+    /// failed, or the remote turned out to be a different peer
+    Handshake,
+    /// The dial or handshake took too long
+    Timeout,
+    /// None of our transports can dial the address
+    TransportUnsupported,
+    /// Nothing answered at the address (refused, no route, no addresses)
+    MultiaddrUnreachable,
+    /// A connection limit or behaviour refused the connection
+    Denied,
+    Other,
+}
+
+impl ConnectionFailureCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ConnectionFailureCategory::Handshake => "handshake",
+            ConnectionFailureCategory::Timeout => "timeout",
+            ConnectionFailureCategory::TransportUnsupported => "transport_unsupported",
+            ConnectionFailureCategory::MultiaddrUnreachable => "multiaddr_unreachable",
+            ConnectionFailureCategory::Denied => "denied",
+            ConnectionFailureCategory::Other => "other",
+        }
+    }
+}
+
+/// Ping liveness of a relay we hold a reservation on
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RelayLiveness {
+    pub relay_peer_id: String,
+    pub health: RelayHealth,
+    /// Round-trip time of the last answered ping
+    pub rtt_ms: Option<u64>,
+    /// When the relay last answered a ping
+    pub last_pong_at: Option<i64>,
+    /// Pings that failed since the last answered one
+    pub consecutive_failures: u32,
 }
 
 /// Events emitted by the network layer to the application
@@ -64,10 +331,36 @@ pub enum NetworkEvent {
     PeerDiscovered { peer_id: String },
     /// A peer went offline/expired
     PeerExpired { peer_id: String },
+    /// An outgoing dial started trying an address. Concurrent attempts to
+    /// the same peer have distinct `attempt_id`s.
+    DialStarted {
+        attempt_id: u64,
+        peer_id: String,
+        /// `None` when the address comes from peer discovery
+        address: Option<String>,
+    },
+    /// A dial attempt failed; success is reported as `PeerConnected`
+    DialFailed {
+        attempt_id: u64,
+        peer_id: String,
+        address: Option<String>,
+        error: String,
+    },
+    /// An incoming or outgoing connection failed. Outgoing dials also
+    /// report `DialFailed`; this adds the classification for diagnostics.
+    ConnectionFailed {
+        /// `None` for incoming connections that failed before authenticating
+        peer_id: Option<String>,
+        address: Option<String>,
+        category: ConnectionFailureCategory,
+        detail: String,
+    },
     /// Successfully connected to a peer
     PeerConnected { peer_id: String },
     /// Disconnected from a peer
     PeerDisconnected { peer_id: String },
+    /// Disconnected from an idle peer to stay under the connection limit
+    PeerEvicted { peer_id: String },
     /// Our external address was discovered
     ExternalAddressDiscovered { address: String },
     /// Listening on a new address
@@ -85,6 +378,39 @@ pub enum NetworkEvent {
         peer_id: String,
         display_name: String,
     },
+    /// Another device is using our identity key (seen at `address`), so
+    /// messages for us may be misrouted to it
+    IdentityConflictDetected { address: String },
+    /// A known contact re-identified with changed profile info or keys
+    ContactUpdated {
+        peer_id: String,
+        display_name: String,
+        bio: Option<String>,
+        avatar_hash: Option<String>,
+        changed_fields: Vec<String>,
+    },
+    /// A known contact re-identified under a different display name.
+    /// Renames of unverified contacts should be shown as a warning.
+    ContactRenamed {
+        peer_id: String,
+        previous_name: String,
+        display_name: String,
+        verified: bool,
+    },
+    /// A connected non-contact answered an automatic identity request.
+    /// The peer is not added as a contact.
+    NearbyPeerIdentified {
+        peer_id: String,
+        display_name: String,
+        avatar_hash: Option<String>,
+        bio: Option<String>,
+    },
+    /// A relay we hold a reservation on stopped or resumed answering pings
+    RelayHealthChanged {
+        relay_peer_id: String,
+        health: RelayHealth,
+        rtt_ms: Option<u64>,
+    },
     /// NAT status changed
     NatStatusChanged { status: NatStatus },
     /// Successfully connected to a relay and have a relay address
@@ -112,11 +438,51 @@ pub enum NetworkEvent {
         board_id: String,
         post_count: usize,
     },
+    /// New posts arrived on a subscribed board
+    NewBoardPosts {
+        relay_peer_id: String,
+        board_id: String,
+        count: usize,
+    },
     /// Board post submitted successfully
     BoardPostSubmitted {
         relay_peer_id: String,
         post_id: String,
     },
+    /// A relay recorded our reaction to a board post
+    BoardPostReactionsUpdated {
+        relay_peer_id: String,
+        post_id: String,
+        reaction_count: u64,
+        my_reaction: Option<String>,
+    },
+    /// A board we requested was created on a relay
+    BoardCreated {
+        relay_peer_id: String,
+        board_id: String,
+        name: String,
+    },
+    /// A relay issued the community invite we asked for
+    CommunityInviteCreated {
+        relay_peer_id: String,
+        token: String,
+    },
+    /// Like state changed for these posts. Changes within a short window are
+    /// coalesced so the UI can re-query the batch once.
+    LikesUpdated { post_ids: Vec<String> },
+    /// Someone liked, reacted to, commented on or viewed our content. The
+    /// entry is already stored in the activity feed.
+    ActivityReceived {
+        kind: ActivityKind,
+        actor_peer_id: String,
+        target_id: String,
+    },
+    /// An outbound request got no response (timeout, dial failure, ...)
+    RequestFailed {
+        peer_id: String,
+        protocol: String,
+        reason: String,
+    },
     /// Board sync error
     BoardSyncError {
         relay_peer_id: String,
@@ -129,6 +495,20 @@ pub enum NetworkEvent {
         community_name: Option<String>,
         board_count: usize,
     },
+    /// A queued message request completed, successfully or not
+    MessageSendResult {
+        correlation_id: String,
+        peer_id: String,
+        delivered: bool,
+        error: Option<String>,
+    },
+    /// A queued message used up its delivery attempts and was marked failed
+    MessageDeliveryFailed {
+        message_id: String,
+        peer_id: String,
+        attempts: u32,
+        error: String,
+    },
     /// A message acknowledgment was received (delivery or read receipt)
     MessageAckReceived {
         message_id: String,
@@ -136,6 +516,13 @@ pub enum NetworkEvent {
         status: String,
         timestamp: i64,
     },
+    /// A peer's read receipt marked messages we sent them as seen
+    MessagesSeen {
+        peer_id: String,
+        conversation_id: String,
+        message_ids: Vec<String>,
+        read_at: i64,
+    },
     /// A wall post was successfully stored on a relay
     WallPostSynced {
         relay_peer_id: String,
@@ -157,6 +544,13 @@ pub enum NetworkEvent {
         peer_id: String,
         media_hash: String,
     },
+    /// Text or a small file arrived on a call's data channel
+    CallDataReceived {
+        call_id: String,
+        peer_id: String,
+        payload: Vec<u8>,
+        timestamp: i64,
+    },
 }
 
 /// Commands that can be sent to the network service
@@ -174,15 +568,39 @@ pub enum NetworkCommand {
         peer_id: PeerId,
         protocol: String,
         payload: Vec<u8>,
+        /// Identifies this send in the `MessageSendResult` event
+        correlation_id: String,
+        /// Resolved once the peer responds or the request fails
+        confirmation: oneshot::Sender<MessageSendOutcome>,
     },
     /// Request identity from a peer
     RequestIdentity { peer_id: PeerId },
+    /// Request a peer's identity without storing them as a contact; `reply`
+    /// receives the verified identity or why it was rejected
+    FetchIdentity {
+        peer_id: PeerId,
+        reply: oneshot::Sender<std::result::Result<TransientIdentity, String>>,
+    },
+    /// Ask a contact for the contacts it shares with us; `reply` receives
+    /// the list or why it was not shared
+    FetchSharedContacts {
+        peer_id: PeerId,
+        reply: oneshot::Sender<std::result::Result<Vec<SharedContact>, String>>,
+    },
+    /// Resend the grants a connected contact hasn't acknowledged
+    ReconcilePermissions { peer_id: PeerId },
+    /// Send every message queued for a peer, without waiting out the backoff
+    FlushPendingMessages { peer_id: PeerId },
     /// Get current network stats
     GetStats,
     /// Get list of connected peers
     GetConnectedPeers,
     /// Get listening addresses
     GetListeningAddresses,
+    /// Get the ports our listeners bound to
+    GetBoundPorts,
+    /// Get the Kademlia routing table, optionally with each peer's addresses
+    GetDhtRoutingTable { include_addresses: bool },
     /// Add a bootstrap node address
     AddBootstrapNode { address: Multiaddr },
     /// Bootstrap the DHT
@@ -191,11 +609,16 @@ pub enum NetworkCommand {
     AddRelayServer { address: Multiaddr },
     /// Connect to public relay servers
     ConnectToPublicRelays,
+    /// Re-dial dropped relays and bootstrap nodes now, ignoring their backoff
+    ReconcileConnections,
+    /// Force the NAT status (`None` returns to AutoNAT detection)
+    SetNatOverride { status: Option<NatStatus> },
     /// Request content manifest from a peer
     RequestContentManifest {
         peer_id: PeerId,
         cursor: HashMap<String, u64>,
         limit: u32,
+        detail_level: crate::services::ManifestDetailLevel,
     },
     /// Request content fetch from a peer
     RequestContentFetch {
@@ -203,13 +626,24 @@ pub enum NetworkCommand {
         post_id: String,
         include_media: bool,
     },
+    /// Push one of our new posts to connected contacts with WallRead
+    AnnouncePost { post_id: String },
     /// Sync feed content from connected peers
     SyncFeed { limit: u32 },
+    /// Sync content from one peer; `reply` receives the summary once the
+    /// manifest and every fetch it triggered have completed
+    SyncWithPeer {
+        peer_id: PeerId,
+        limit: u32,
+        reply: oneshot::Sender<PeerSyncSummary>,
+    },
     /// Join a community (register peer + list boards)
     JoinCommunity {
         relay_peer_id: PeerId,
         relay_address: String,
     },
+    /// Leave a community (deregister peer + drop it locally)
+    LeaveCommunity { relay_peer_id: PeerId },
     /// List boards on a relay
     ListBoards { relay_peer_id: PeerId },
     /// Get board posts from a relay
@@ -230,11 +664,30 @@ pub enum NetworkCommand {
         relay_peer_id: PeerId,
         post_id: String,
     },
+    /// React to a board post on a relay, or withdraw our reaction
+    ReactToBoardPost {
+        relay_peer_id: PeerId,
+        post_id: String,
+        reaction_type: String,
+        remove: bool,
+    },
     /// Sync a board (get latest posts)
     SyncBoard {
         relay_peer_id: PeerId,
         board_id: String,
     },
+    /// Ask a relay to create a new board
+    CreateBoard {
+        relay_peer_id: PeerId,
+        name: String,
+        description: Option<String>,
+    },
+    /// Ask a relay for a signed invite to its community
+    CreateCommunityInvite {
+        relay_peer_id: PeerId,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
+    },
     /// Submit a wall post to a relay for offline availability
     SubmitWallPostToRelay {
         relay_peer_id: PeerId,
@@ -264,10 +717,26 @@ pub enum NetworkCommand {
         relay_peer_id: PeerId,
         post_id: String,
     },
+    /// Like state changed for these posts (coalesced into `LikesUpdated`)
+    NotifyLikesChanged { post_ids: Vec<String> },
+    /// Send a signed frame over a call's data channel
+    SendCallData {
+        peer_id: PeerId,
+        frame: CallDataFrame,
+    },
     /// Shutdown the network
     Shutdown,
 }
 
+/// A peer identity that passed verification but is only held in memory
+#[derive(Debug, Clone)]
+pub struct TransientIdentity {
+    pub peer_id: String,
+    pub public_key: Vec<u8>,
+    pub x25519_public: Vec<u8>,
+    pub display_name: String,
+}
+
 /// Response to network commands
 #[derive(Debug)]
 pub enum NetworkResponse {
@@ -275,5 +744,59 @@ pub enum NetworkResponse {
     Stats(NetworkStats),
     Peers(Vec<PeerInfo>),
     Addresses(Vec<String>),
+    BoundPorts(BoundPorts),
+    DhtRoutingTable(DhtRoutingTable),
+    /// The target relay was probed and does not speak the board sync protocol
+    NotCommunityRelay(String),
     Error(String),
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn allows(filter: AddressFilter, addr: &str) -> bool {
+        filter.allows(&addr.parse().unwrap())
+    }
+
+    #[test]
+    fn test_address_filter_routable() {
+        let filter = AddressFilter::Routable;
+        assert!(!allows(filter, "/ip4/127.0.0.1/tcp/4001"));
+        assert!(!allows(filter, "/ip4/169.254.10.2/tcp/4001"));
+        assert!(!allows(filter, "/ip4/0.0.0.0/tcp/4001"));
+        assert!(!allows(filter, "/ip6/::1/udp/4001/quic-v1"));
+        assert!(!allows(filter, "/ip6/fe80::1/tcp/4001"));
+        assert!(allows(filter, "/ip4/192.168.1.20/tcp/4001"));
+        assert!(allows(filter, "/ip6/fd00::1/tcp/4001"));
+        assert!(allows(filter, "/ip4/100.49.236.191/tcp/4001"));
+        assert!(allows(filter, "/dns4/relay.example.com/tcp/4001"));
+    }
+
+    #[test]
+    fn test_address_filter_public_only() {
+        let filter = AddressFilter::PublicOnly;
+        assert!(!allows(filter, "/ip4/127.0.0.1/tcp/4001"));
+        assert!(!allows(filter, "/ip4/10.0.0.5/tcp/4001"));
+        assert!(!allows(filter, "/ip4/172.20.1.1/tcp/4001"));
+        assert!(!allows(filter, "/ip4/192.168.1.20/tcp/4001"));
+        assert!(!allows(filter, "/ip4/100.64.3.4/tcp/4001"));
+        assert!(!allows(filter, "/ip6/fd00::1/tcp/4001"));
+        assert!(allows(filter, "/ip4/100.49.236.191/tcp/4001"));
+        assert!(allows(filter, "/ip6/2001:db8::1/tcp/4001"));
+    }
+
+    #[test]
+    fn test_address_filter_all_and_parsing() {
+        assert!(allows(AddressFilter::All, "/ip4/127.0.0.1/tcp/4001"));
+        for filter in [
+            AddressFilter::All,
+            AddressFilter::Routable,
+            AddressFilter::PublicOnly,
+        ] {
+            assert_eq!(filter.as_str().parse::<AddressFilter>().unwrap(), filter);
+        }
+        assert!("everything".parse::<AddressFilter>().is_err());
+        assert_eq!(AddressFilter::default(), AddressFilter::Routable);
+    }
+}
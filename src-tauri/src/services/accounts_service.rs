@@ -1,4 +1,5 @@
 use crate::error::{AppError, Result};
+use crate::services::identity_service::normalize_accent_color;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
@@ -17,6 +18,9 @@ pub struct AccountInfo {
     pub avatar_hash: Option<String>,
     /// Short bio
     pub bio: Option<String>,
+    /// Hex accent color for telling accounts apart
+    #[serde(default)]
+    pub accent_color: Option<String>,
     /// Peer ID for this account
     pub peer_id: String,
     /// When the account was created (timestamp)
@@ -148,6 +152,7 @@ impl AccountsService {
             display_name,
             avatar_hash,
             bio,
+            accent_color: None,
             peer_id,
             created_at: now,
             last_accessed_at: Some(now),
@@ -165,14 +170,18 @@ impl AccountsService {
         Ok(account)
     }
 
-    /// Update account metadata (display name, bio, avatar)
+    /// Update account metadata (display name, bio, avatar, accent color)
     pub fn update_account(
         &self,
         account_id: &str,
         display_name: Option<String>,
         bio: Option<Option<String>>,
         avatar_hash: Option<Option<String>>,
+        accent_color: Option<Option<String>>,
     ) -> Result<AccountInfo> {
+        let accent_color = accent_color
+            .map(|color| color.as_deref().map(normalize_accent_color).transpose())
+            .transpose()?;
         let mut registry = self.load_registry()?;
 
         let account = registry
@@ -189,6 +198,9 @@ impl AccountsService {
         if let Some(new_avatar) = avatar_hash {
             account.avatar_hash = new_avatar;
         }
+        if let Some(new_color) = accent_color {
+            account.accent_color = new_color;
+        }
 
         let updated = account.clone();
         self.save_registry(&registry)?;
@@ -313,6 +325,7 @@ impl AccountsService {
                     display_name,
                     avatar_hash,
                     bio,
+                    accent_color: None,
                     peer_id,
                     created_at,
                     last_accessed_at: Some(chrono::Utc::now().timestamp()),
@@ -429,6 +442,74 @@ mod tests {
         cleanup_temp_dir(&temp);
     }
 
+    #[test]
+    fn test_update_account_accent_color() {
+        let temp = create_temp_dir();
+        let service = AccountsService::new(temp.clone());
+
+        let account = service
+            .register_account(
+                "12D3KooWTestPeer1".to_string(),
+                "Alice".to_string(),
+                None,
+                None,
+            )
+            .unwrap();
+        assert_eq!(account.accent_color, None);
+
+        let updated = service
+            .update_account(
+                &account.id,
+                None,
+                None,
+                None,
+                Some(Some("#F97316".to_string())),
+            )
+            .unwrap();
+        assert_eq!(updated.accent_color.as_deref(), Some("#f97316"));
+        assert_eq!(updated.display_name, "Alice");
+        assert_eq!(
+            service.list_accounts().unwrap()[0].accent_color.as_deref(),
+            Some("#f97316")
+        );
+
+        assert!(service
+            .update_account(
+                &account.id,
+                None,
+                None,
+                None,
+                Some(Some("orange".to_string()))
+            )
+            .is_err());
+
+        let cleared = service
+            .update_account(&account.id, None, None, None, Some(None))
+            .unwrap();
+        assert_eq!(cleared.accent_color, None);
+
+        cleanup_temp_dir(&temp);
+    }
+
+    #[test]
+    fn test_registry_without_accent_color_still_loads() {
+        let temp = create_temp_dir();
+        let service = AccountsService::new(temp.clone());
+        std::fs::write(
+            temp.join("accounts.json"),
+            r#"{"accounts":{"peer1":{"id":"peer1","displayName":"Alice","avatarHash":null,
+            "bio":null,"peerId":"peer1","createdAt":1000,"lastAccessedAt":null,
+            "dataPath":"profile-peer1"}},"activeAccountId":"peer1"}"#,
+        )
+        .unwrap();
+
+        let accounts = service.list_accounts().unwrap();
+        assert_eq!(accounts.len(), 1);
+        assert_eq!(accounts[0].accent_color, None);
+
+        cleanup_temp_dir(&temp);
+    }
+
     #[test]
     fn test_remove_account() {
         let temp = create_temp_dir();
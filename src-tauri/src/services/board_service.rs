@@ -3,11 +3,13 @@
 use std::sync::Arc;
 use uuid::Uuid;
 
-use crate::db::{BoardsRepository, Database, UpsertBoardPostParams};
+use crate::db::{BoardSubscription, BoardsRepository, Database, UpsertBoardPostParams};
 use crate::error::{AppError, Result};
 use crate::services::{
-    IdentityService, SignableBoardListRequest, SignableBoardPost, SignableBoardPostDelete,
-    SignableBoardPostsRequest, SignableGetWallPosts, SignablePeerRegistration,
+    verify, CryptoService, IdentityService, SignableBoardCreate, SignableBoardListRequest,
+    SignableBoardPost, SignableBoardPostDelete, SignableBoardPostReaction,
+    SignableBoardPostsRequest, SignableGetWallPosts, SignableInviteRequest,
+    SignablePeerDeregistration, SignablePeerRegistration, SignableRelayTimestamp,
     SignableWallPostDelete, SignableWallPostSubmit,
 };
 
@@ -40,6 +42,14 @@ pub struct OutgoingPeerRegistration {
     pub signature: Vec<u8>,
 }
 
+/// A peer deregistration request ready to be sent to the relay
+#[derive(Debug, Clone)]
+pub struct OutgoingPeerDeregistration {
+    pub peer_id: String,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
 /// A board list request ready to be sent
 #[derive(Debug, Clone)]
 pub struct OutgoingBoardListRequest {
@@ -48,6 +58,26 @@ pub struct OutgoingBoardListRequest {
     pub signature: Vec<u8>,
 }
 
+/// A board creation request ready to be sent
+#[derive(Debug, Clone)]
+pub struct OutgoingBoardCreate {
+    pub requester_peer_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// A community invite request ready to be sent
+#[derive(Debug, Clone)]
+pub struct OutgoingInviteRequest {
+    pub requester_peer_id: String,
+    pub expires_at: Option<i64>,
+    pub max_uses: Option<u32>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
 /// A board posts request ready to be sent
 #[derive(Debug, Clone)]
 pub struct OutgoingBoardPostsRequest {
@@ -68,6 +98,17 @@ pub struct OutgoingBoardPostDelete {
     pub signature: Vec<u8>,
 }
 
+/// A board post reaction request
+#[derive(Debug, Clone)]
+pub struct OutgoingBoardPostReaction {
+    pub post_id: String,
+    pub reactor_peer_id: String,
+    pub reaction_type: String,
+    pub remove: bool,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
 /// A wall post submission request ready to be sent to the relay
 #[derive(Debug, Clone)]
 pub struct OutgoingWallPostSubmit {
@@ -181,6 +222,27 @@ impl BoardService {
         })
     }
 
+    /// Create a signed peer deregistration for leaving a relay's community
+    pub fn create_peer_deregistration(&self) -> Result<OutgoingPeerDeregistration> {
+        let info = self
+            .identity_service
+            .get_identity_info()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let signable = SignablePeerDeregistration {
+            peer_id: info.peer_id.clone(),
+            timestamp: now,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(OutgoingPeerDeregistration {
+            peer_id: info.peer_id,
+            timestamp: now,
+            signature,
+        })
+    }
+
     /// Create a signed board list request
     pub fn create_list_boards_request(&self) -> Result<OutgoingBoardListRequest> {
         let info = self
@@ -202,6 +264,77 @@ impl BoardService {
         })
     }
 
+    /// Create a signed request asking a relay to create a new board.
+    ///
+    /// The relay only accepts this when its operator enabled peer-created
+    /// boards; it performs the authoritative name validation.
+    pub fn create_board_request(
+        &self,
+        name: &str,
+        description: Option<&str>,
+    ) -> Result<OutgoingBoardCreate> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "Board name cannot be empty".to_string(),
+            ));
+        }
+
+        let info = self
+            .identity_service
+            .get_identity_info()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let signable = SignableBoardCreate {
+            requester_peer_id: info.peer_id.clone(),
+            name: name.to_string(),
+            description: description.map(String::from),
+            timestamp: now,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(OutgoingBoardCreate {
+            requester_peer_id: info.peer_id,
+            name: signable.name,
+            description: signable.description,
+            timestamp: now,
+            signature,
+        })
+    }
+
+    /// Create a signed request asking a relay for an invite to its community.
+    ///
+    /// The relay only issues member invites when its operator allows them and
+    /// may shorten `expires_at`.
+    pub fn create_invite_request(
+        &self,
+        expires_at: Option<i64>,
+        max_uses: Option<u32>,
+    ) -> Result<OutgoingInviteRequest> {
+        let info = self
+            .identity_service
+            .get_identity_info()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let signable = SignableInviteRequest {
+            requester_peer_id: info.peer_id.clone(),
+            expires_at,
+            max_uses,
+            timestamp: now,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(OutgoingInviteRequest {
+            requester_peer_id: info.peer_id,
+            expires_at,
+            max_uses,
+            timestamp: now,
+            signature,
+        })
+    }
+
     /// Create a signed board posts request
     pub fn create_get_board_posts_request(
         &self,
@@ -255,6 +388,39 @@ impl BoardService {
         })
     }
 
+    /// Create a signed request to react to a board post, or to withdraw our
+    /// reaction when `remove` is set
+    pub fn create_react_to_post_request(
+        &self,
+        post_id: &str,
+        reaction_type: &str,
+        remove: bool,
+    ) -> Result<OutgoingBoardPostReaction> {
+        let info = self
+            .identity_service
+            .get_identity_info()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let now = chrono::Utc::now().timestamp();
+        let signable = SignableBoardPostReaction {
+            post_id: post_id.to_string(),
+            reactor_peer_id: info.peer_id.clone(),
+            reaction_type: reaction_type.to_string(),
+            remove,
+            timestamp: now,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(OutgoingBoardPostReaction {
+            post_id: post_id.to_string(),
+            reactor_peer_id: info.peer_id,
+            reaction_type: reaction_type.to_string(),
+            remove,
+            timestamp: now,
+            signature,
+        })
+    }
+
     // ===== Wall post relay operations =====
 
     /// Create a signed wall post submission for a relay
@@ -441,6 +607,28 @@ impl BoardService {
         posts: &[StorableBoardPost],
     ) -> Result<()> {
         for post in posts {
+            let relay_observed_at = match (post.relay_observed_at, &post.relay_signature) {
+                (Some(observed_at), Some(relay_signature))
+                    if verify_relay_timestamp(
+                        relay_peer_id,
+                        &post.post_id,
+                        &post.author_peer_id,
+                        &post.signature,
+                        observed_at,
+                        relay_signature,
+                    ) =>
+                {
+                    Some(observed_at)
+                }
+                (Some(_), _) => {
+                    tracing::warn!(
+                        "Dropping unverifiable relay timestamp on board post {}",
+                        post.post_id
+                    );
+                    None
+                }
+                (None, _) => None,
+            };
             BoardsRepository::upsert_board_post(
                 &self.db,
                 &UpsertBoardPostParams {
@@ -455,6 +643,9 @@ impl BoardService {
                     created_at: post.created_at,
                     deleted_at: post.deleted_at,
                     signature: &post.signature,
+                    relay_observed_at,
+                    reaction_count: post.reaction_count as i64,
+                    my_reaction: post.requester_reaction.as_deref(),
                 },
             )
             .map_err(AppError::Database)?;
@@ -476,11 +667,124 @@ impl BoardService {
         Ok(())
     }
 
+    /// Store a post's reaction count and our own reaction as confirmed by
+    /// the relay
+    pub fn store_post_reactions(
+        &self,
+        relay_peer_id: &str,
+        post_id: &str,
+        reaction_count: u64,
+        my_reaction: Option<&str>,
+    ) -> Result<()> {
+        BoardsRepository::update_board_post_reactions(
+            &self.db,
+            post_id,
+            relay_peer_id,
+            reaction_count as i64,
+            my_reaction,
+        )
+        .map_err(AppError::Database)?;
+        Ok(())
+    }
+
     /// Get sync cursor for a board
     pub fn get_sync_cursor(&self, relay_peer_id: &str, board_id: &str) -> Result<Option<i64>> {
         BoardsRepository::get_board_sync_cursor(&self.db, relay_peer_id, board_id)
             .map_err(AppError::Database)
     }
+
+    /// Forget all board sync cursors so boards are re-fetched from the start.
+    /// Stored board posts are kept.
+    pub fn reset_sync_cursors(&self) -> Result<usize> {
+        BoardsRepository::clear_board_sync_cursors(&self.db).map_err(AppError::Database)
+    }
+
+    // ===== Board subscriptions =====
+
+    /// Subscribe to a board so it is synced in the background.
+    /// Only posts created after subscribing are reported as new.
+    pub fn subscribe_board(&self, relay_peer_id: &str, board_id: &str) -> Result<()> {
+        let communities = self.get_communities()?;
+        if !communities.iter().any(|c| c.relay_peer_id == relay_peer_id) {
+            return Err(AppError::NotFound(format!(
+                "Not a member of community {}",
+                relay_peer_id
+            )));
+        }
+
+        let now = chrono::Utc::now().timestamp();
+        BoardsRepository::subscribe_board(&self.db, relay_peer_id, board_id, now)
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Unsubscribe from a board
+    pub fn unsubscribe_board(&self, relay_peer_id: &str, board_id: &str) -> Result<()> {
+        BoardsRepository::unsubscribe_board(&self.db, relay_peer_id, board_id)
+            .map_err(AppError::Database)?;
+        Ok(())
+    }
+
+    /// Get all board subscriptions
+    pub fn get_subscriptions(&self) -> Result<Vec<BoardSubscription>> {
+        BoardsRepository::get_board_subscriptions(&self.db).map_err(AppError::Database)
+    }
+
+    /// Check whether a board is subscribed
+    pub fn is_subscribed(&self, relay_peer_id: &str, board_id: &str) -> Result<bool> {
+        Ok(
+            BoardsRepository::get_board_subscription(&self.db, relay_peer_id, board_id)
+                .map_err(AppError::Database)?
+                .is_some(),
+        )
+    }
+
+    /// Count posts on a subscribed board that are newer than the last ones seen,
+    /// and advance the last-seen marker past them.
+    ///
+    /// Deleted posts and our own posts are not counted. Returns `None` if the
+    /// board is not subscribed.
+    pub fn record_new_subscribed_posts(
+        &self,
+        relay_peer_id: &str,
+        board_id: &str,
+        posts: &[StorableBoardPost],
+    ) -> Result<Option<usize>> {
+        let Some(subscription) =
+            BoardsRepository::get_board_subscription(&self.db, relay_peer_id, board_id)
+                .map_err(AppError::Database)?
+        else {
+            return Ok(None);
+        };
+
+        let our_peer_id = self
+            .identity_service
+            .get_identity_info()?
+            .map(|info| info.peer_id);
+
+        let newer: Vec<&StorableBoardPost> = posts
+            .iter()
+            .filter(|p| p.board_id == board_id && p.created_at > subscription.last_seen_post_at)
+            .collect();
+
+        let count = newer
+            .iter()
+            .filter(|p| p.deleted_at.is_none())
+            .filter(|p| our_peer_id.as_deref() != Some(p.author_peer_id.as_str()))
+            .count();
+
+        if let Some(latest) = newer.iter().map(|p| p.created_at).max() {
+            BoardsRepository::update_subscription_last_seen(
+                &self.db,
+                relay_peer_id,
+                board_id,
+                latest,
+            )
+            .map_err(AppError::Database)?;
+        }
+
+        Ok(Some(count))
+    }
 }
 
 /// A board post to be stored locally (from relay response)
@@ -496,6 +800,37 @@ pub struct StorableBoardPost {
     pub created_at: i64,
     pub deleted_at: Option<i64>,
     pub signature: Vec<u8>,
+    /// When the relay says it received the post (unverified)
+    pub relay_observed_at: Option<i64>,
+    /// Relay signature over the post's `SignableRelayTimestamp`
+    pub relay_signature: Option<Vec<u8>>,
+    /// Number of peers who reacted, as counted by the relay
+    pub reaction_count: u64,
+    /// Our own reaction to the post
+    pub requester_reaction: Option<String>,
+}
+
+/// Check a relay's signed receive time for a post against the key embedded
+/// in the relay's peer ID
+pub fn verify_relay_timestamp(
+    relay_peer_id: &str,
+    post_id: &str,
+    author_peer_id: &str,
+    author_signature: &[u8],
+    relay_observed_at: i64,
+    relay_signature: &[u8],
+) -> bool {
+    let Ok(relay_key) = CryptoService::verifying_key_from_peer_id(relay_peer_id) else {
+        return false;
+    };
+    let signable = SignableRelayTimestamp {
+        relay_peer_id: relay_peer_id.to_string(),
+        post_id: post_id.to_string(),
+        author_peer_id: author_peer_id.to_string(),
+        author_signature: author_signature.to_vec(),
+        relay_observed_at,
+    };
+    verify(&relay_key, &signable, relay_signature).unwrap_or(false)
 }
 
 #[cfg(test)]
@@ -520,6 +855,7 @@ mod tests {
                 passphrase: "test-pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
 
@@ -637,6 +973,10 @@ mod tests {
                 created_at: 1000,
                 deleted_at: None,
                 signature: vec![0u8; 64],
+                relay_observed_at: None,
+                relay_signature: None,
+                reaction_count: 0,
+                requester_reaction: None,
             },
             StorableBoardPost {
                 post_id: "bp-2".to_string(),
@@ -649,6 +989,10 @@ mod tests {
                 created_at: 2000,
                 deleted_at: None,
                 signature: vec![0u8; 64],
+                relay_observed_at: None,
+                relay_signature: None,
+                reaction_count: 0,
+                requester_reaction: None,
             },
         ];
 
@@ -696,12 +1040,30 @@ mod tests {
             created_at: 5000,
             deleted_at: None,
             signature: vec![0u8; 64],
+            relay_observed_at: None,
+            relay_signature: None,
+            reaction_count: 0,
+            requester_reaction: None,
         }];
 
         service.store_board_posts("relay-1", &posts).unwrap();
 
         let cursor = service.get_sync_cursor("relay-1", "board-1").unwrap();
         assert_eq!(cursor, Some(5000));
+
+        // Resetting forgets the cursor but keeps the stored post
+        assert_eq!(service.reset_sync_cursors().unwrap(), 1);
+        assert!(service
+            .get_sync_cursor("relay-1", "board-1")
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            service
+                .get_board_posts("relay-1", "board-1", 10, None)
+                .unwrap()
+                .len(),
+            1
+        );
     }
 
     #[test]
@@ -742,6 +1104,28 @@ mod tests {
         assert!(!reg.signature.is_empty());
     }
 
+    #[test]
+    fn test_create_peer_deregistration() {
+        let (service, _db, identity, peer_id) = create_test_env();
+
+        let dereg = service.create_peer_deregistration().unwrap();
+        assert_eq!(dereg.peer_id, peer_id);
+
+        // The relay verifies against the key we registered with
+        let info = identity.get_identity_info().unwrap().unwrap();
+        let public_key =
+            base64::Engine::decode(&base64::engine::general_purpose::STANDARD, &info.public_key)
+                .unwrap();
+        let verifying_key =
+            ed25519_dalek::VerifyingKey::from_bytes(public_key.as_slice().try_into().unwrap())
+                .unwrap();
+        let signable = SignablePeerDeregistration {
+            peer_id: dereg.peer_id.clone(),
+            timestamp: dereg.timestamp,
+        };
+        assert!(crate::services::verify(&verifying_key, &signable, &dereg.signature).unwrap());
+    }
+
     #[test]
     fn test_create_list_boards_request() {
         let (service, _db, _identity, peer_id) = create_test_env();
@@ -752,6 +1136,25 @@ mod tests {
         assert!(!req.signature.is_empty());
     }
 
+    #[test]
+    fn test_create_board_request() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let req = service
+            .create_board_request("  Announcements ", Some("News"))
+            .unwrap();
+
+        assert_eq!(req.requester_peer_id, peer_id);
+        assert_eq!(req.name, "Announcements");
+        assert_eq!(req.description.as_deref(), Some("News"));
+        assert!(!req.signature.is_empty());
+
+        assert!(matches!(
+            service.create_board_request("   ", None),
+            Err(AppError::Validation(_))
+        ));
+    }
+
     #[test]
     fn test_create_get_board_posts_request() {
         let (service, _db, _identity, peer_id) = create_test_env();
@@ -778,6 +1181,60 @@ mod tests {
         assert!(!req.signature.is_empty());
     }
 
+    #[test]
+    fn test_create_react_to_post_request() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let req = service
+            .create_react_to_post_request("post-123", "like", false)
+            .unwrap();
+
+        assert_eq!(req.post_id, "post-123");
+        assert_eq!(req.reactor_peer_id, peer_id);
+        assert_eq!(req.reaction_type, "like");
+        assert!(!req.remove);
+        assert!(!req.signature.is_empty());
+    }
+
+    #[test]
+    fn test_reaction_counts_follow_the_relay() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+        service
+            .join_community("relay-1", "/ip4/1.2.3.4/tcp/9000", None)
+            .unwrap();
+        let boards = vec![("board-1".to_string(), "General".to_string(), None, true)];
+        service.store_boards("relay-1", &boards).unwrap();
+
+        let mut post = board_post("bp-1", "author-1", 1000);
+        post.reaction_count = 3;
+        service
+            .store_board_posts("relay-1", &[post.clone()])
+            .unwrap();
+        let stored = &service
+            .get_board_posts("relay-1", "board-1", 10, None)
+            .unwrap()[0];
+        assert_eq!(stored.reaction_count, 3);
+        assert_eq!(stored.my_reaction, None);
+
+        service
+            .store_post_reactions("relay-1", "bp-1", 4, Some("like"))
+            .unwrap();
+        let stored = &service
+            .get_board_posts("relay-1", "board-1", 10, None)
+            .unwrap()[0];
+        assert_eq!(stored.reaction_count, 4);
+        assert_eq!(stored.my_reaction.as_deref(), Some("like"));
+
+        // A later sync replaces the cached count, including going down
+        post.reaction_count = 2;
+        service.store_board_posts("relay-1", &[post]).unwrap();
+        let stored = &service
+            .get_board_posts("relay-1", "board-1", 10, None)
+            .unwrap()[0];
+        assert_eq!(stored.reaction_count, 2);
+        assert_eq!(stored.my_reaction, None);
+    }
+
     #[test]
     fn test_upsert_community() {
         let (service, _db, _identity, _peer_id) = create_test_env();
@@ -797,4 +1254,156 @@ mod tests {
         // Address should be updated
         assert_eq!(communities[0].relay_address, "/ip4/1.2.3.4/tcp/9001");
     }
+
+    fn board_post(post_id: &str, author: &str, created_at: i64) -> StorableBoardPost {
+        StorableBoardPost {
+            post_id: post_id.to_string(),
+            board_id: "board-1".to_string(),
+            author_peer_id: author.to_string(),
+            author_display_name: None,
+            content_type: "text".to_string(),
+            content_text: Some("Post".to_string()),
+            lamport_clock: 1,
+            created_at,
+            deleted_at: None,
+            signature: vec![0u8; 64],
+            relay_observed_at: None,
+            relay_signature: None,
+            reaction_count: 0,
+            requester_reaction: None,
+        }
+    }
+
+    #[test]
+    fn test_subscribe_requires_joined_community() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+
+        let result = service.subscribe_board("relay-1", "board-1");
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_subscribe_and_unsubscribe_board() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+        service
+            .join_community("relay-1", "/ip4/1.2.3.4/tcp/9000", None)
+            .unwrap();
+
+        service.subscribe_board("relay-1", "board-1").unwrap();
+        // Subscribing twice is a no-op
+        service.subscribe_board("relay-1", "board-1").unwrap();
+
+        assert!(service.is_subscribed("relay-1", "board-1").unwrap());
+        assert_eq!(service.get_subscriptions().unwrap().len(), 1);
+
+        service.unsubscribe_board("relay-1", "board-1").unwrap();
+        assert!(!service.is_subscribed("relay-1", "board-1").unwrap());
+        assert!(service.get_subscriptions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_leave_community_removes_subscriptions() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+        service
+            .join_community("relay-1", "/ip4/1.2.3.4/tcp/9000", None)
+            .unwrap();
+        service.subscribe_board("relay-1", "board-1").unwrap();
+
+        service.leave_community("relay-1").unwrap();
+
+        assert!(service.get_subscriptions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_record_new_posts_unsubscribed_board() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+
+        let posts = vec![board_post("bp-1", "author-1", i64::MAX)];
+        let count = service
+            .record_new_subscribed_posts("relay-1", "board-1", &posts)
+            .unwrap();
+        assert_eq!(count, None);
+    }
+
+    #[test]
+    fn test_record_new_posts_counts_only_unseen() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+        service
+            .join_community("relay-1", "/ip4/1.2.3.4/tcp/9000", None)
+            .unwrap();
+        service.subscribe_board("relay-1", "board-1").unwrap();
+
+        let subscribed_at = service.get_subscriptions().unwrap()[0].subscribed_at;
+        let mut deleted = board_post("bp-4", "author-2", subscribed_at + 30);
+        deleted.deleted_at = Some(subscribed_at + 40);
+
+        let posts = vec![
+            // Posted before subscribing
+            board_post("bp-1", "author-1", subscribed_at - 10),
+            board_post("bp-2", "author-1", subscribed_at + 10),
+            board_post("bp-3", "author-2", subscribed_at + 20),
+            deleted,
+            // Our own post
+            board_post("bp-5", &peer_id, subscribed_at + 50),
+        ];
+
+        let count = service
+            .record_new_subscribed_posts("relay-1", "board-1", &posts)
+            .unwrap();
+        assert_eq!(count, Some(2));
+
+        // Same posts again are no longer new
+        let count = service
+            .record_new_subscribed_posts("relay-1", "board-1", &posts)
+            .unwrap();
+        assert_eq!(count, Some(0));
+
+        let subscription = &service.get_subscriptions().unwrap()[0];
+        assert_eq!(subscription.last_seen_post_at, subscribed_at + 50);
+    }
+
+    #[test]
+    fn test_only_verified_relay_timestamps_are_stored() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+        let (relay_key, _) = CryptoService::generate_ed25519_keypair();
+        let relay_peer_id = CryptoService::derive_peer_id_from_signing_key(&relay_key).unwrap();
+        service
+            .join_community(&relay_peer_id, "/ip4/1.2.3.4/tcp/9000", None)
+            .unwrap();
+
+        let anchored = |post_id: &str, observed_at: i64| {
+            let mut post = board_post(post_id, "author-1", 1000);
+            let signable = SignableRelayTimestamp {
+                relay_peer_id: relay_peer_id.clone(),
+                post_id: post.post_id.clone(),
+                author_peer_id: post.author_peer_id.clone(),
+                author_signature: post.signature.clone(),
+                relay_observed_at: observed_at,
+            };
+            post.relay_signature = Some(crate::services::sign(&relay_key, &signable).unwrap());
+            post.relay_observed_at = Some(observed_at);
+            post
+        };
+        let valid = anchored("bp-1", 1005);
+        // Relay signature over a different time than the one served
+        let mut forged = anchored("bp-2", 1005);
+        forged.relay_observed_at = Some(900);
+
+        service
+            .store_board_posts(&relay_peer_id, &[valid, forged])
+            .unwrap();
+
+        let stored = service
+            .get_board_posts(&relay_peer_id, "board-1", 10, None)
+            .unwrap();
+        let observed = |post_id: &str| {
+            stored
+                .iter()
+                .find(|p| p.post_id == post_id)
+                .unwrap()
+                .relay_observed_at
+        };
+        assert_eq!(observed("bp-1"), Some(1005));
+        assert_eq!(observed("bp-2"), None);
+    }
 }
@@ -1,16 +1,23 @@
 //! Voice calling service using WebRTC signaling
 
 use ed25519_dalek::VerifyingKey;
-use std::sync::Arc;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 use uuid::Uuid;
 
 use crate::db::Capability;
 use crate::error::{AppError, Result};
 use crate::services::{
-    verify, ContactsService, IdentityService, PermissionsService, SignableSignalingAnswer,
-    SignableSignalingHangup, SignableSignalingIce, SignableSignalingOffer,
+    verify, ContactsService, IdentityService, PermissionsService, SignableCallData,
+    SignableSignalingAnswer, SignableSignalingHangup, SignableSignalingIce, SignableSignalingOffer,
 };
 
+/// Calls that haven't connected within this many seconds are dropped
+const CALL_SETUP_TIMEOUT_SECS: i64 = 60;
+
+/// Largest payload accepted on the in-call data channel
+pub const MAX_CALL_DATA_SIZE: usize = 256 * 1024;
+
 /// Call state
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CallState {
@@ -45,6 +52,19 @@ pub struct Call {
     pub started_at: i64,
     pub ended_at: Option<i64>,
     pub end_reason: Option<String>,
+    /// Whether both the offer and the answer negotiated a data channel
+    pub data_channel: bool,
+}
+
+impl Call {
+    /// The other party of the call, as seen from `local_peer_id`
+    pub fn remote_peer_id(&self, local_peer_id: &str) -> &str {
+        if self.caller_peer_id == local_peer_id {
+            &self.callee_peer_id
+        } else {
+            &self.caller_peer_id
+        }
+    }
 }
 
 /// Service for managing voice calls
@@ -52,6 +72,8 @@ pub struct CallingService {
     identity_service: Arc<IdentityService>,
     contacts_service: Arc<ContactsService>,
     permissions_service: Arc<PermissionsService>,
+    /// Calls in progress, by call ID. Removed on hangup or setup timeout.
+    calls: Mutex<HashMap<String, Call>>,
 }
 
 /// An outgoing signaling offer
@@ -98,6 +120,26 @@ pub struct OutgoingHangup {
     pub signature: Vec<u8>,
 }
 
+/// An outgoing frame on the in-call data channel
+#[derive(Debug, Clone)]
+pub struct OutgoingCallData {
+    pub call_id: String,
+    pub sender_peer_id: String,
+    pub recipient_peer_id: String,
+    pub payload: Vec<u8>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// Parameters for processing an incoming data channel frame
+pub struct IncomingCallDataParams<'a> {
+    pub call_id: &'a str,
+    pub sender_peer_id: &'a str,
+    pub payload: &'a [u8],
+    pub timestamp: i64,
+    pub signature: &'a [u8],
+}
+
 /// Parameters for processing an incoming ICE candidate
 pub struct IncomingIceParams<'a> {
     pub call_id: &'a str,
@@ -120,9 +162,52 @@ impl CallingService {
             identity_service,
             contacts_service,
             permissions_service,
+            calls: Mutex::new(HashMap::new()),
         }
     }
 
+    fn calls(&self) -> MutexGuard<'_, HashMap<String, Call>> {
+        self.calls
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Get a call in progress
+    pub fn get_call(&self, call_id: &str) -> Option<Call> {
+        self.calls().get(call_id).cloned()
+    }
+
+    /// Drop calls that are still ringing after the setup timeout, so a call
+    /// that never connects doesn't keep its data channel slot. Returns the
+    /// number of calls dropped.
+    pub fn expire_unconnected_calls(&self, now: i64) -> usize {
+        let mut calls = self.calls();
+        let before = calls.len();
+        calls.retain(|_, call| {
+            call.state == CallState::Connected || now - call.started_at < CALL_SETUP_TIMEOUT_SECS
+        });
+        before - calls.len()
+    }
+
+    fn track_call(&self, call: Call) {
+        self.expire_unconnected_calls(call.started_at);
+        self.calls().insert(call.call_id.clone(), call);
+    }
+
+    /// Mark a tracked call connected once the answer is through. The data
+    /// channel stays open only if the answer accepted it too.
+    fn connect_call(&self, call_id: &str, answer_sdp: &str) {
+        if let Some(call) = self.calls().get_mut(call_id) {
+            call.state = CallState::Connected;
+            call.data_channel = call.data_channel && sdp_has_data_channel(answer_sdp);
+        }
+    }
+
+    /// Stop tracking a call, closing its data channel
+    fn end_call(&self, call_id: &str) {
+        self.calls().remove(call_id);
+    }
+
     /// Start a call to a peer
     pub fn create_offer(&self, callee_peer_id: &str, sdp: &str) -> Result<OutgoingOffer> {
         let identity = self
@@ -133,7 +218,7 @@ impl CallingService {
         // Check we have call permission with this peer
         if !self
             .permissions_service
-            .peer_has_capability(callee_peer_id, Capability::Call)?
+            .peer_has_capability(callee_peer_id, Capability::CallInitiate)?
         {
             return Err(AppError::PermissionDenied(
                 "No call permission with this peer".to_string(),
@@ -153,6 +238,17 @@ impl CallingService {
 
         let signature = self.identity_service.sign(&signable)?;
 
+        self.track_call(Call {
+            call_id: call_id.clone(),
+            caller_peer_id: identity.peer_id.clone(),
+            callee_peer_id: callee_peer_id.to_string(),
+            state: CallState::Ringing,
+            started_at: timestamp,
+            ended_at: None,
+            end_reason: None,
+            data_channel: sdp_has_data_channel(sdp),
+        });
+
         Ok(OutgoingOffer {
             call_id,
             caller_peer_id: identity.peer_id,
@@ -212,13 +308,24 @@ impl CallingService {
         // Check caller has call permission from us
         if !self
             .permissions_service
-            .we_have_capability(caller_peer_id, Capability::Call)?
+            .we_have_capability(caller_peer_id, Capability::CallInitiate)?
         {
             return Err(AppError::PermissionDenied(
                 "Caller doesn't have call permission".to_string(),
             ));
         }
 
+        self.track_call(Call {
+            call_id: call_id.to_string(),
+            caller_peer_id: caller_peer_id.to_string(),
+            callee_peer_id: callee_peer_id.to_string(),
+            state: CallState::Incoming,
+            started_at: chrono::Utc::now().timestamp(),
+            ended_at: None,
+            end_reason: None,
+            data_channel: sdp_has_data_channel(sdp),
+        });
+
         Ok(())
     }
 
@@ -234,6 +341,16 @@ impl CallingService {
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
+        // The grant may have been revoked since the offer arrived
+        if !self
+            .permissions_service
+            .we_have_capability(caller_peer_id, Capability::CallInitiate)?
+        {
+            return Err(AppError::PermissionDenied(
+                "Caller doesn't have call permission".to_string(),
+            ));
+        }
+
         let timestamp = chrono::Utc::now().timestamp();
 
         let signable = SignableSignalingAnswer {
@@ -246,6 +363,8 @@ impl CallingService {
 
         let signature = self.identity_service.sign(&signable)?;
 
+        self.connect_call(call_id, sdp);
+
         Ok(OutgoingAnswer {
             call_id: call_id.to_string(),
             caller_peer_id: caller_peer_id.to_string(),
@@ -302,6 +421,15 @@ impl CallingService {
             return Err(AppError::Crypto("Invalid answer signature".to_string()));
         }
 
+        if let Some(call) = self.get_call(call_id) {
+            if call.callee_peer_id != callee_peer_id {
+                return Err(AppError::Validation(
+                    "Answer not from the callee".to_string(),
+                ));
+            }
+        }
+        self.connect_call(call_id, sdp);
+
         Ok(())
     }
 
@@ -401,6 +529,8 @@ impl CallingService {
 
         let signature = self.identity_service.sign(&signable)?;
 
+        self.end_call(call_id);
+
         Ok(OutgoingHangup {
             call_id: call_id.to_string(),
             sender_peer_id: identity.peer_id,
@@ -444,10 +574,130 @@ impl CallingService {
             return Err(AppError::Crypto("Invalid hangup signature".to_string()));
         }
 
+        // Only a party to the call can end it
+        if let Some(call) = self.get_call(call_id) {
+            if call.caller_peer_id == sender_peer_id || call.callee_peer_id == sender_peer_id {
+                self.end_call(call_id);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Look up a call that can carry data right now
+    fn open_data_channel(&self, call_id: &str) -> Result<Call> {
+        let call = self
+            .get_call(call_id)
+            .ok_or_else(|| AppError::NotFound(format!("Call {} not found", call_id)))?;
+
+        if call.state != CallState::Connected {
+            return Err(AppError::Validation("Call is not connected".to_string()));
+        }
+        if !call.data_channel {
+            return Err(AppError::Validation(
+                "No data channel was negotiated for this call".to_string(),
+            ));
+        }
+
+        Ok(call)
+    }
+
+    /// Send a text note or small file over a connected call's data channel
+    pub fn create_call_data(&self, call_id: &str, payload: &[u8]) -> Result<OutgoingCallData> {
+        validate_call_data_size(payload)?;
+
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let call = self.open_data_channel(call_id)?;
+        let recipient_peer_id = call.remote_peer_id(&identity.peer_id).to_string();
+
+        let timestamp = chrono::Utc::now().timestamp();
+
+        let signable = SignableCallData {
+            call_id: call_id.to_string(),
+            sender_peer_id: identity.peer_id.clone(),
+            payload: payload.to_vec(),
+            timestamp,
+        };
+
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(OutgoingCallData {
+            call_id: call_id.to_string(),
+            sender_peer_id: identity.peer_id,
+            recipient_peer_id,
+            payload: payload.to_vec(),
+            timestamp,
+            signature,
+        })
+    }
+
+    /// Process a frame received on a call's data channel
+    pub fn process_incoming_call_data(&self, params: &IncomingCallDataParams<'_>) -> Result<()> {
+        validate_call_data_size(params.payload)?;
+
+        let call = self.open_data_channel(params.call_id)?;
+        if call.caller_peer_id != params.sender_peer_id
+            && call.callee_peer_id != params.sender_peer_id
+        {
+            return Err(AppError::PermissionDenied(
+                "Sender is not part of this call".to_string(),
+            ));
+        }
+
+        let sender_public_key = self
+            .contacts_service
+            .get_public_key(params.sender_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Sender not in contacts".to_string()))?;
+
+        let signable = SignableCallData {
+            call_id: params.call_id.to_string(),
+            sender_peer_id: params.sender_peer_id.to_string(),
+            payload: params.payload.to_vec(),
+            timestamp: params.timestamp,
+        };
+
+        let verifying_key = VerifyingKey::from_bytes(
+            sender_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        if !verify(&verifying_key, &signable, params.signature)? {
+            return Err(AppError::Crypto("Invalid call data signature".to_string()));
+        }
+
         Ok(())
     }
 }
 
+/// Whether an SDP blob negotiates a WebRTC data channel, i.e. has an
+/// `m=application` section using the `webrtc-datachannel` format
+fn sdp_has_data_channel(sdp: &str) -> bool {
+    sdp.lines()
+        .any(|line| line.starts_with("m=application") && line.contains("webrtc-datachannel"))
+}
+
+fn validate_call_data_size(payload: &[u8]) -> Result<()> {
+    if payload.is_empty() {
+        return Err(AppError::Validation(
+            "Call data cannot be empty".to_string(),
+        ));
+    }
+    if payload.len() > MAX_CALL_DATA_SIZE {
+        return Err(AppError::Validation(format!(
+            "Call data exceeds {} bytes",
+            MAX_CALL_DATA_SIZE
+        )));
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -480,6 +730,7 @@ mod tests {
                 passphrase: "test-pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
 
@@ -516,7 +767,7 @@ mod tests {
         ContactsRepository::add_contact(db, &contact_data).unwrap();
 
         permissions
-            .create_permission_grant(peer_id, Capability::Call, None)
+            .create_permission_grant(peer_id, Capability::CallInitiate, None)
             .unwrap();
     }
 
@@ -572,9 +823,30 @@ mod tests {
         assert!(result.is_err());
     }
 
+    /// Helper to store a call grant from a caller to us
+    fn grant_call_from(db: &Database, caller_peer_id: &str, our_peer_id: &str) {
+        PermissionsRepository::upsert_grant(
+            db,
+            &GrantData {
+                grant_id: format!("grant-call-{}", caller_peer_id),
+                issuer_peer_id: caller_peer_id.to_string(),
+                subject_peer_id: our_peer_id.to_string(),
+                capability: Capability::CallInitiate.as_str().to_string(),
+                scope_json: None,
+                lamport_clock: 1,
+                issued_at: 1000,
+                expires_at: None,
+                payload_cbor: vec![0],
+                signature: vec![0],
+            },
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_create_answer_success() {
-        let (service, _db, _identity, _permissions, peer_id) = create_test_env();
+        let (service, db, _identity, _permissions, peer_id) = create_test_env();
+        grant_call_from(&db, "12D3KooWCaller", &peer_id);
 
         let answer = service
             .create_answer("call-123", "12D3KooWCaller", "v=0\r\nsdp-answer")
@@ -587,6 +859,15 @@ mod tests {
         assert!(!answer.signature.is_empty());
     }
 
+    #[test]
+    fn test_create_answer_without_call_permission_fails() {
+        let (service, _db, _identity, _permissions, _peer_id) = create_test_env();
+
+        let result = service.create_answer("call-123", "12D3KooWCaller", "v=0\r\nsdp-answer");
+
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
     #[test]
     fn test_create_ice_candidate() {
         let (service, _db, _identity, _permissions, peer_id) = create_test_env();
@@ -868,4 +1149,198 @@ mod tests {
         let result = service.create_hangup("call-123", "normal");
         assert!(result.is_err());
     }
+
+    const SDP_WITH_DATA_CHANNEL: &str = "v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 111\r\n\
+        m=application 9 UDP/DTLS/SCTP webrtc-datachannel\r\n";
+
+    #[test]
+    fn test_sdp_has_data_channel() {
+        assert!(sdp_has_data_channel(SDP_WITH_DATA_CHANNEL));
+        assert!(!sdp_has_data_channel(
+            "v=0\r\nm=audio 9 UDP/TLS/RTP/SAVPF 111\r\n"
+        ));
+    }
+
+    #[test]
+    fn test_call_data_over_connected_call() {
+        let (service, db, _identity, permissions, peer_id) = create_test_env();
+
+        let (callee_signing, callee_verifying) = CryptoService::generate_ed25519_keypair();
+        let callee = "12D3KooWCallee123";
+        add_peer_with_call_permission(&db, &permissions, callee, &callee_verifying.to_bytes());
+
+        let offer = service.create_offer(callee, SDP_WITH_DATA_CHANNEL).unwrap();
+        let call = service.get_call(&offer.call_id).unwrap();
+        assert_eq!(call.state, CallState::Ringing);
+        assert!(call.data_channel);
+
+        // Nothing goes over the channel until the call connects
+        assert!(matches!(
+            service.create_call_data(&offer.call_id, b"hello"),
+            Err(AppError::Validation(_))
+        ));
+
+        let answer = SignableSignalingAnswer {
+            call_id: offer.call_id.clone(),
+            caller_peer_id: peer_id.clone(),
+            callee_peer_id: callee.to_string(),
+            sdp: SDP_WITH_DATA_CHANNEL.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        let sig = crate::services::sign(&callee_signing, &answer).unwrap();
+        service
+            .process_incoming_answer(
+                &offer.call_id,
+                &peer_id,
+                callee,
+                SDP_WITH_DATA_CHANNEL,
+                answer.timestamp,
+                &sig,
+            )
+            .unwrap();
+
+        let data = service
+            .create_call_data(&offer.call_id, b"https://example.com")
+            .unwrap();
+        assert_eq!(data.sender_peer_id, peer_id);
+        assert_eq!(data.recipient_peer_id, callee);
+        assert_eq!(data.payload, b"https://example.com");
+
+        assert!(matches!(
+            service.create_call_data(&offer.call_id, &vec![0u8; MAX_CALL_DATA_SIZE + 1]),
+            Err(AppError::Validation(_))
+        ));
+
+        // Hanging up tears the channel down
+        service.create_hangup(&offer.call_id, "normal").unwrap();
+        assert!(service.get_call(&offer.call_id).is_none());
+        assert!(matches!(
+            service.create_call_data(&offer.call_id, b"late"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_incoming_call_data() {
+        let (service, db, _identity, _permissions, peer_id) = create_test_env();
+
+        let (caller_signing, caller_verifying) = CryptoService::generate_ed25519_keypair();
+        let caller_id = "12D3KooWCaller123";
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: caller_id.to_string(),
+                public_key: caller_verifying.to_bytes().to_vec(),
+                x25519_public: vec![0u8; 32],
+                display_name: "Caller".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+        PermissionsRepository::upsert_grant(
+            &db,
+            &GrantData {
+                grant_id: "grant-call-1".to_string(),
+                issuer_peer_id: caller_id.to_string(),
+                subject_peer_id: peer_id.clone(),
+                capability: "call".to_string(),
+                scope_json: None,
+                lamport_clock: 1,
+                issued_at: 1000,
+                expires_at: None,
+                payload_cbor: vec![0],
+                signature: vec![0],
+            },
+        )
+        .unwrap();
+
+        let offer = SignableSignalingOffer {
+            call_id: "call-1".to_string(),
+            caller_peer_id: caller_id.to_string(),
+            callee_peer_id: peer_id.clone(),
+            sdp: SDP_WITH_DATA_CHANNEL.to_string(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        let sig = crate::services::sign(&caller_signing, &offer).unwrap();
+        service
+            .process_incoming_offer(
+                "call-1",
+                caller_id,
+                &peer_id,
+                SDP_WITH_DATA_CHANNEL,
+                offer.timestamp,
+                &sig,
+            )
+            .unwrap();
+        assert_eq!(
+            service.get_call("call-1").unwrap().state,
+            CallState::Incoming
+        );
+
+        service
+            .create_answer("call-1", caller_id, SDP_WITH_DATA_CHANNEL)
+            .unwrap();
+
+        let frame = SignableCallData {
+            call_id: "call-1".to_string(),
+            sender_peer_id: caller_id.to_string(),
+            payload: b"a note".to_vec(),
+            timestamp: chrono::Utc::now().timestamp(),
+        };
+        let sig = crate::services::sign(&caller_signing, &frame).unwrap();
+        let params = IncomingCallDataParams {
+            call_id: "call-1",
+            sender_peer_id: caller_id,
+            payload: &frame.payload,
+            timestamp: frame.timestamp,
+            signature: &sig,
+        };
+        assert!(service.process_incoming_call_data(&params).is_ok());
+
+        let forged = IncomingCallDataParams {
+            signature: &[0u8; 64],
+            ..params
+        };
+        assert!(service.process_incoming_call_data(&forged).is_err());
+
+        let stranger = IncomingCallDataParams {
+            sender_peer_id: "12D3KooWStranger",
+            ..params
+        };
+        assert!(matches!(
+            service.process_incoming_call_data(&stranger),
+            Err(AppError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_offer_without_data_channel() {
+        let (service, db, _identity, permissions, _peer_id) = create_test_env();
+
+        let (_, callee_verifying) = CryptoService::generate_ed25519_keypair();
+        let callee = "12D3KooWCallee123";
+        add_peer_with_call_permission(&db, &permissions, callee, &callee_verifying.to_bytes());
+
+        let offer = service.create_offer(callee, "v=0\r\nsdp-data").unwrap();
+        assert!(!service.get_call(&offer.call_id).unwrap().data_channel);
+    }
+
+    #[test]
+    fn test_unconnected_calls_expire() {
+        let (service, db, _identity, permissions, _peer_id) = create_test_env();
+
+        let (_, callee_verifying) = CryptoService::generate_ed25519_keypair();
+        let callee = "12D3KooWCallee123";
+        add_peer_with_call_permission(&db, &permissions, callee, &callee_verifying.to_bytes());
+
+        let offer = service.create_offer(callee, SDP_WITH_DATA_CHANNEL).unwrap();
+
+        assert_eq!(service.expire_unconnected_calls(offer.timestamp + 1), 0);
+        assert_eq!(
+            service.expire_unconnected_calls(offer.timestamp + CALL_SETUP_TIMEOUT_SECS),
+            1
+        );
+        assert!(service.get_call(&offer.call_id).is_none());
+    }
 }
@@ -0,0 +1,104 @@
+//! Clock sources for time-dependent service logic
+//!
+//! Services read the time through a `Clock` instead of calling
+//! `chrono::Utc::now()` directly, so tests can pin it with a `MockClock` and
+//! step through expiry, skew and retention windows without sleeping.
+
+use chrono::{DateTime, Duration, Utc};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A source of the current time
+pub trait Clock: Send + Sync {
+    /// The current time
+    fn now(&self) -> DateTime<Utc>;
+
+    /// The current time as Unix seconds
+    fn timestamp(&self) -> i64 {
+        self.now().timestamp()
+    }
+}
+
+/// The system clock
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> DateTime<Utc> {
+        Utc::now()
+    }
+}
+
+/// The clock services use unless given another
+pub fn system_clock() -> Arc<dyn Clock> {
+    Arc::new(SystemClock)
+}
+
+/// A clock that stands still until advanced
+#[derive(Debug)]
+pub struct MockClock {
+    now: Mutex<DateTime<Utc>>,
+}
+
+impl MockClock {
+    /// A clock stopped at `now`
+    pub fn new(now: DateTime<Utc>) -> Self {
+        Self {
+            now: Mutex::new(now),
+        }
+    }
+
+    /// A clock stopped at the Unix time `timestamp`
+    pub fn at_timestamp(timestamp: i64) -> Self {
+        Self::new(DateTime::from_timestamp(timestamp, 0).unwrap_or_default())
+    }
+
+    /// Move the clock forward (or back, with a negative duration)
+    pub fn advance(&self, by: Duration) {
+        *self.current() += by;
+    }
+
+    /// Set the clock to `now`
+    pub fn set(&self, now: DateTime<Utc>) {
+        *self.current() = now;
+    }
+
+    fn current(&self) -> MutexGuard<'_, DateTime<Utc>> {
+        self.now
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Clock for MockClock {
+    fn now(&self) -> DateTime<Utc> {
+        *self.current()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mock_clock_only_moves_when_told() {
+        let clock = MockClock::at_timestamp(1_700_000_000);
+        assert_eq!(clock.timestamp(), 1_700_000_000);
+        assert_eq!(clock.timestamp(), 1_700_000_000);
+
+        clock.advance(Duration::days(1));
+        assert_eq!(clock.timestamp(), 1_700_086_400);
+
+        clock.advance(Duration::seconds(-400));
+        assert_eq!(clock.timestamp(), 1_700_086_000);
+
+        clock.set(DateTime::from_timestamp(42, 0).unwrap());
+        assert_eq!(clock.timestamp(), 42);
+    }
+
+    #[test]
+    fn test_system_clock_tracks_real_time() {
+        let before = Utc::now().timestamp();
+        let now = SystemClock.timestamp();
+        assert!(now >= before && now <= Utc::now().timestamp());
+    }
+}
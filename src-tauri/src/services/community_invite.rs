@@ -0,0 +1,165 @@
+//! Signed community invitations
+//!
+//! A relay (or a member, where the relay allows it) issues an invite naming
+//! the relay's address, community name and peer ID, signed with the relay's
+//! identity key and encoded as `harbor://invite/<base64url CBOR>`. The
+//! signature is checked against the key embedded in the relay's peer ID, so
+//! a valid invite can only lead to the relay that issued it.
+
+use base64::Engine;
+use libp2p::multiaddr::Protocol;
+use libp2p::Multiaddr;
+use serde::{Deserialize, Serialize};
+
+use crate::error::{AppError, Result};
+use crate::services::signing::{verify, SignableCommunityInvite};
+use crate::services::CryptoService;
+
+/// Prefix of the text form of an invite
+pub const INVITE_PREFIX: &str = "harbor://invite/";
+
+/// An invite as encoded in the token.
+/// Must match `CommunityInviteToken` in the relay server.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommunityInviteToken {
+    pub invite: SignableCommunityInvite,
+    pub signature: Vec<u8>,
+}
+
+impl CommunityInviteToken {
+    /// Whether `text` looks like an invite rather than a relay address
+    pub fn is_invite(text: &str) -> bool {
+        text.trim().starts_with(INVITE_PREFIX)
+    }
+
+    /// Decode the text form of an invite without checking it
+    pub fn decode(text: &str) -> Result<Self> {
+        let encoded = text
+            .trim()
+            .strip_prefix(INVITE_PREFIX)
+            .ok_or_else(|| AppError::Validation("Not a Harbor invite".to_string()))?;
+        let bytes = base64::engine::general_purpose::URL_SAFE_NO_PAD
+            .decode(encoded)
+            .map_err(|e| AppError::Validation(format!("Invalid invite encoding: {}", e)))?;
+        ciborium::from_reader(bytes.as_slice())
+            .map_err(|e| AppError::Validation(format!("Invalid invite: {}", e)))
+    }
+
+    /// Encode to the text form of an invite
+    pub fn encode(&self) -> Result<String> {
+        let mut bytes = Vec::new();
+        ciborium::into_writer(self, &mut bytes)
+            .map_err(|e| AppError::Serialization(format!("CBOR encoding failed: {}", e)))?;
+        Ok(format!(
+            "{}{}",
+            INVITE_PREFIX,
+            base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
+        ))
+    }
+
+    /// Check the relay's signature, that the address leads to the signing
+    /// relay, and that the invite hasn't expired at `now`
+    pub fn verify(&self, now: i64) -> Result<&SignableCommunityInvite> {
+        let invite = &self.invite;
+        let relay_key =
+            CryptoService::verifying_key_from_peer_id(&invite.relay_peer_id).map_err(|_| {
+                AppError::Validation(format!(
+                    "Invite names an invalid relay peer ID: {}",
+                    invite.relay_peer_id
+                ))
+            })?;
+        if !verify(&relay_key, invite, &self.signature).unwrap_or(false) {
+            return Err(AppError::Validation(
+                "Invite signature is not valid for its relay".to_string(),
+            ));
+        }
+
+        let address: Multiaddr = invite
+            .relay_address
+            .parse()
+            .map_err(|e| AppError::Validation(format!("Invalid relay address in invite: {}", e)))?;
+        let address_peer_id = address.iter().find_map(|protocol| match protocol {
+            Protocol::P2p(peer_id) => Some(peer_id.to_string()),
+            _ => None,
+        });
+        if address_peer_id.as_deref() != Some(invite.relay_peer_id.as_str()) {
+            return Err(AppError::Validation(
+                "Invite address does not lead to the relay that signed it".to_string(),
+            ));
+        }
+
+        if let Some(expires_at) = invite.expires_at {
+            if expires_at <= now {
+                return Err(AppError::Validation("Invite has expired".to_string()));
+            }
+        }
+        Ok(invite)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::services::sign;
+    use ed25519_dalek::SigningKey;
+
+    fn relay() -> (SigningKey, String) {
+        let (signing_key, _) = CryptoService::generate_ed25519_keypair();
+        let peer_id = CryptoService::derive_peer_id_from_signing_key(&signing_key).unwrap();
+        (signing_key, peer_id)
+    }
+
+    fn signed_invite(signing_key: &SigningKey, peer_id: &str, expires_at: Option<i64>) -> String {
+        let invite = SignableCommunityInvite {
+            relay_address: format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", peer_id),
+            community_name: "Harbor Community".to_string(),
+            relay_peer_id: peer_id.to_string(),
+            issued_at: 1_700_000_000,
+            expires_at,
+            max_uses: Some(10),
+        };
+        let signature = sign(signing_key, &invite).unwrap();
+        CommunityInviteToken { invite, signature }.encode().unwrap()
+    }
+
+    #[test]
+    fn test_invite_roundtrip_verifies() {
+        let (signing_key, peer_id) = relay();
+        let text = signed_invite(&signing_key, &peer_id, Some(1_800_000_000));
+        assert!(CommunityInviteToken::is_invite(&text));
+
+        let token = CommunityInviteToken::decode(&text).unwrap();
+        let invite = token.verify(1_700_000_100).unwrap();
+        assert_eq!(invite.community_name, "Harbor Community");
+        assert_eq!(invite.max_uses, Some(10));
+    }
+
+    #[test]
+    fn test_expired_invite_rejected() {
+        let (signing_key, peer_id) = relay();
+        let text = signed_invite(&signing_key, &peer_id, Some(1_700_000_050));
+
+        let token = CommunityInviteToken::decode(&text).unwrap();
+        assert!(token.verify(1_700_000_100).is_err());
+    }
+
+    #[test]
+    fn test_tampered_invite_rejected() {
+        let (signing_key, peer_id) = relay();
+        let mut token =
+            CommunityInviteToken::decode(&signed_invite(&signing_key, &peer_id, None)).unwrap();
+        token.invite.community_name = "Somewhere Else".to_string();
+        assert!(token.verify(1_700_000_100).is_err());
+
+        // Signed by one relay, pointing at another
+        let (other_key, other_peer_id) = relay();
+        let mut token =
+            CommunityInviteToken::decode(&signed_invite(&other_key, &other_peer_id, None)).unwrap();
+        token.invite.relay_address = format!("/ip4/203.0.113.7/tcp/4001/p2p/{}", peer_id);
+        token.signature = sign(&other_key, &token.invite).unwrap();
+        assert!(token.verify(1_700_000_100).is_err());
+
+        assert!(CommunityInviteToken::decode("/ip4/203.0.113.7/tcp/4001").is_err());
+        assert!(CommunityInviteToken::decode("harbor://invite/!!!").is_err());
+    }
+}
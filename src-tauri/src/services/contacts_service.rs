@@ -1,10 +1,119 @@
 //! Contacts service for managing peer relationships
 
-use crate::db::{Contact, ContactData, ContactsRepository, Database};
+use crate::db::repositories::settings_repo::SETTING_SHARE_CONTACT_LIST;
+use crate::db::repositories::{ContactGroup, ContactGroupsRepository, SettingsRepository};
+use crate::db::{
+    Contact, ContactData, ContactNameChange, ContactsRepository, Database, ForgottenPeer,
+    PeerDataRepository,
+};
 use crate::error::{AppError, Result};
-use crate::services::IdentityService;
+use crate::p2p::protocols::messaging::derive_conversation_id;
+use crate::services::{verify, IdentityQrPayload, IdentityService, SignableHeartbeat};
+use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::sync::Arc;
 
+/// Maximum clock skew tolerated for heartbeat timestamps in the future (seconds)
+const MAX_HEARTBEAT_SKEW_SECS: i64 = 300;
+
+/// A stored contact field that can change when a peer re-identifies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContactField {
+    DisplayName,
+    AvatarHash,
+    Bio,
+    PublicKey,
+    X25519Public,
+}
+
+impl ContactField {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContactField::DisplayName => "display_name",
+            ContactField::AvatarHash => "avatar_hash",
+            ContactField::Bio => "bio",
+            ContactField::PublicKey => "public_key",
+            ContactField::X25519Public => "x25519_public",
+        }
+    }
+}
+
+/// Result of adding or refreshing a contact
+#[derive(Debug, Clone)]
+pub struct ContactUpsert {
+    pub contact_id: i64,
+    /// True if the contact did not exist before
+    pub created: bool,
+    /// Fields of an existing contact that differ from what was stored
+    pub changed_fields: Vec<ContactField>,
+    /// The stored display name, if this upsert renamed an existing contact
+    pub previous_display_name: Option<String>,
+    /// Whether the user has verified the contact's identity
+    pub verified: bool,
+}
+
+impl ContactUpsert {
+    /// Whether an existing contact's stored info was modified
+    pub fn is_updated(&self) -> bool {
+        !self.created && !self.changed_fields.is_empty()
+    }
+
+    /// Whether this upsert renamed a contact the user has not verified.
+    ///
+    /// A sudden rename is how a peer would impersonate someone else, so
+    /// callers should surface it rather than silently update the name.
+    pub fn is_unverified_rename(&self) -> bool {
+        self.previous_display_name.is_some() && !self.verified
+    }
+}
+
+/// Why a contact is considered stale, one entry per activity signal
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum StaleReason {
+    /// We have never connected to the peer
+    NeverConnected,
+    /// The last successful connection is older than the window
+    NoRecentConnection,
+    /// No messages have ever been exchanged
+    NeverMessaged,
+    /// The last message is older than the window
+    NoRecentMessages,
+    /// We hold no posts from the peer
+    NoPostsSynced,
+    /// The newest post we hold from the peer is older than the window
+    NoRecentPosts,
+}
+
+/// A contact with no activity inside the inactivity window
+#[derive(Debug, Clone)]
+pub struct StaleContact {
+    pub contact: Contact,
+    pub last_message_at: Option<i64>,
+    pub last_post_at: Option<i64>,
+    pub reasons: Vec<StaleReason>,
+}
+
+/// A contact as shown to a peer allowed to see our contact list
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SharedContact {
+    pub peer_id: String,
+    pub display_name: String,
+}
+
+/// A peer some of our contacts know but we don't. Only a candidate; it is
+/// never added automatically.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSuggestion {
+    pub peer_id: String,
+    pub display_name: String,
+    /// The contacts whose lists include this peer
+    pub known_via: Vec<SharedContact>,
+}
+
 /// Service for managing contacts
 pub struct ContactsService {
     db: Arc<Database>,
@@ -20,7 +129,10 @@ impl ContactsService {
         }
     }
 
-    /// Add a new contact from identity exchange data
+    /// Add a contact from identity exchange data, or refresh an existing one.
+    ///
+    /// Existing contacts are only written when something actually changed, and
+    /// the returned `ContactUpsert` reports which fields differ.
     pub fn add_contact(
         &self,
         peer_id: &str,
@@ -29,7 +141,7 @@ impl ContactsService {
         display_name: &str,
         avatar_hash: Option<&str>,
         bio: Option<&str>,
-    ) -> Result<i64> {
+    ) -> Result<ContactUpsert> {
         // Don't add ourselves as a contact
         if let Some(identity) = self.identity_service.get_identity()? {
             if identity.peer_id == peer_id {
@@ -39,25 +151,66 @@ impl ContactsService {
             }
         }
 
-        // Check if already a contact
-        if ContactsRepository::is_contact(&self.db, peer_id)
+        if let Some(existing) = ContactsRepository::get_by_peer_id(&self.db, peer_id)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?
         {
-            // Update existing contact info instead
-            ContactsRepository::update_contact_info(
-                &self.db,
-                peer_id,
-                display_name,
-                avatar_hash,
-                bio,
-            )
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            let mut changed_fields = Vec::new();
+            if existing.display_name != display_name {
+                changed_fields.push(ContactField::DisplayName);
+            }
+            if existing.avatar_hash.as_deref() != avatar_hash {
+                changed_fields.push(ContactField::AvatarHash);
+            }
+            if existing.bio.as_deref() != bio {
+                changed_fields.push(ContactField::Bio);
+            }
+            if existing.public_key != public_key {
+                changed_fields.push(ContactField::PublicKey);
+            }
+            if existing.x25519_public != x25519_public {
+                changed_fields.push(ContactField::X25519Public);
+            }
 
-            // Return existing contact's ID
-            let contact = ContactsRepository::get_by_peer_id(&self.db, peer_id)
-                .map_err(|e| AppError::DatabaseString(e.to_string()))?
-                .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
-            return Ok(contact.id);
+            if changed_fields.iter().any(|f| {
+                matches!(
+                    f,
+                    ContactField::DisplayName | ContactField::AvatarHash | ContactField::Bio
+                )
+            }) {
+                ContactsRepository::update_contact_info(
+                    &self.db,
+                    peer_id,
+                    display_name,
+                    avatar_hash,
+                    bio,
+                )
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            }
+
+            if changed_fields
+                .iter()
+                .any(|f| matches!(f, ContactField::PublicKey | ContactField::X25519Public))
+            {
+                ContactsRepository::update_contact_keys(
+                    &self.db,
+                    peer_id,
+                    public_key,
+                    x25519_public,
+                )
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            }
+
+            let previous_display_name = changed_fields
+                .contains(&ContactField::DisplayName)
+                .then(|| existing.display_name.clone());
+
+            return Ok(ContactUpsert {
+                contact_id: existing.id,
+                created: false,
+                changed_fields,
+                previous_display_name,
+                verified: existing.is_verified(),
+            });
         }
 
         let contact_data = ContactData {
@@ -69,8 +222,32 @@ impl ContactsService {
             bio: bio.map(String::from),
         };
 
-        ContactsRepository::add_contact(&self.db, &contact_data)
-            .map_err(|e| AppError::DatabaseString(e.to_string()))
+        let contact_id = ContactsRepository::add_contact(&self.db, &contact_data)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        Ok(ContactUpsert {
+            contact_id,
+            created: true,
+            changed_fields: Vec::new(),
+            previous_display_name: None,
+            verified: false,
+        })
+    }
+
+    /// Add a contact from a decoded identity QR payload.
+    ///
+    /// The payload carries no bio or avatar, so an existing contact keeps
+    /// whatever it already had for those.
+    pub fn add_contact_from_qr(&self, payload: &IdentityQrPayload) -> Result<ContactUpsert> {
+        let existing = self.get_contact(&payload.peer_id)?;
+        self.add_contact(
+            &payload.peer_id,
+            &payload.public_key,
+            &payload.x25519_public,
+            &payload.display_name,
+            existing.as_ref().and_then(|c| c.avatar_hash.as_deref()),
+            existing.as_ref().and_then(|c| c.bio.as_deref()),
+        )
     }
 
     /// Get a contact by peer ID
@@ -102,12 +279,63 @@ impl ContactsService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Get every display name observed for a peer, oldest first
+    pub fn get_name_history(&self, peer_id: &str) -> Result<Vec<ContactNameChange>> {
+        ContactsRepository::get_name_history(&self.db, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Update last seen timestamp for a contact
     pub fn update_last_seen(&self, peer_id: &str) -> Result<bool> {
         ContactsRepository::update_last_seen(&self.db, peer_id)
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Verify a signed presence heartbeat and record its timestamp as last seen.
+    ///
+    /// Returns `Ok(false)` if the heartbeat is valid but not newer than the
+    /// stored value, or if the contact is blocked.
+    pub fn process_heartbeat(
+        &self,
+        peer_id: &str,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let contact = self
+            .get_contact(peer_id)?
+            .ok_or_else(|| AppError::NotFound("Heartbeat from unknown contact".to_string()))?;
+
+        if contact.is_blocked {
+            return Ok(false);
+        }
+
+        if timestamp > chrono::Utc::now().timestamp() + MAX_HEARTBEAT_SKEW_SECS {
+            return Err(AppError::Validation(
+                "Heartbeat timestamp is in the future".to_string(),
+            ));
+        }
+
+        let verifying_key = VerifyingKey::from_bytes(
+            contact
+                .public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        let signable = SignableHeartbeat {
+            peer_id: peer_id.to_string(),
+            timestamp,
+        };
+        if !verify(&verifying_key, &signable, signature)? {
+            return Err(AppError::Crypto("Invalid heartbeat signature".to_string()));
+        }
+
+        ContactsRepository::update_last_seen_at(&self.db, peer_id, timestamp)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Block a contact
     pub fn block_contact(&self, peer_id: &str) -> Result<bool> {
         ContactsRepository::block_contact(&self.db, peer_id)
@@ -126,6 +354,30 @@ impl ContactsService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Forget a peer: delete the contact and everything stored about them in
+    /// one transaction, including our conversation, their posts, likes and
+    /// comments, and permissions either way.
+    ///
+    /// Unlike `remove_contact` nothing is left behind, and unlike
+    /// `block_contact` nothing is kept. The returned media hashes are no
+    /// longer referenced by the peer's data and can be deleted.
+    pub fn forget_contact(&self, peer_id: &str) -> Result<ForgottenPeer> {
+        let our_peer_id = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?
+            .peer_id;
+        if peer_id == our_peer_id {
+            return Err(AppError::Validation(
+                "Can't forget our own identity".to_string(),
+            ));
+        }
+
+        let conversation_id = derive_conversation_id(&our_peer_id, peer_id);
+        PeerDataRepository::forget_peer(&self.db, peer_id, &conversation_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Check if peer is a contact
     pub fn is_contact(&self, peer_id: &str) -> Result<bool> {
         ContactsRepository::is_contact(&self.db, peer_id)
@@ -138,12 +390,274 @@ impl ContactsService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Find contacts with no connection, message or post activity in the
+    /// last `inactive_days` days.
+    ///
+    /// This is read-only: candidates are returned with their reasons and it is
+    /// up to the user to remove them. Contacts added inside the window are
+    /// never reported.
+    pub fn get_stale_contacts(&self, inactive_days: u32) -> Result<Vec<StaleContact>> {
+        self.get_stale_contacts_at(inactive_days, chrono::Utc::now().timestamp())
+    }
+
+    fn get_stale_contacts_at(&self, inactive_days: u32, now: i64) -> Result<Vec<StaleContact>> {
+        if inactive_days == 0 {
+            return Err(AppError::Validation(
+                "Inactivity window must be at least one day".to_string(),
+            ));
+        }
+        let cutoff = now - i64::from(inactive_days) * 86_400;
+
+        let mut activity: HashMap<String, (Option<i64>, Option<i64>)> =
+            ContactsRepository::get_activity(&self.db)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .into_iter()
+                .map(|a| (a.peer_id, (a.last_message_at, a.last_post_at)))
+                .collect();
+
+        // Each signal is either missing entirely or too old; any recent one
+        // means the contact is still active.
+        let check = |at: Option<i64>, never: StaleReason, old: StaleReason| match at {
+            None => Some(never),
+            Some(at) if at < cutoff => Some(old),
+            Some(_) => None,
+        };
+
+        let mut stale = Vec::new();
+        for contact in self.get_all_contacts()? {
+            if contact.added_at >= cutoff {
+                continue;
+            }
+            let (last_message_at, last_post_at) =
+                activity.remove(&contact.peer_id).unwrap_or_default();
+
+            let reasons: Option<Vec<StaleReason>> = [
+                check(
+                    contact.last_seen_at,
+                    StaleReason::NeverConnected,
+                    StaleReason::NoRecentConnection,
+                ),
+                check(
+                    last_message_at,
+                    StaleReason::NeverMessaged,
+                    StaleReason::NoRecentMessages,
+                ),
+                check(
+                    last_post_at,
+                    StaleReason::NoPostsSynced,
+                    StaleReason::NoRecentPosts,
+                ),
+            ]
+            .into_iter()
+            .collect();
+
+            if let Some(reasons) = reasons {
+                stale.push(StaleContact {
+                    contact,
+                    last_message_at,
+                    last_post_at,
+                    reasons,
+                });
+            }
+        }
+
+        Ok(stale)
+    }
+
+    /// Create a contact group
+    pub fn create_group(&self, name: &str) -> Result<ContactGroup> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::Validation(
+                "Group name cannot be empty".to_string(),
+            ));
+        }
+        if ContactGroupsRepository::name_exists(&self.db, name)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        {
+            return Err(AppError::AlreadyExists(format!(
+                "A group named \"{}\" already exists",
+                name
+            )));
+        }
+
+        let group_id = uuid::Uuid::new_v4().to_string();
+        let created_at = chrono::Utc::now().timestamp();
+        ContactGroupsRepository::create_group(&self.db, &group_id, name, created_at)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        Ok(ContactGroup {
+            group_id,
+            name: name.to_string(),
+            member_count: 0,
+            created_at,
+        })
+    }
+
+    /// Get all contact groups
+    pub fn get_groups(&self) -> Result<Vec<ContactGroup>> {
+        ContactGroupsRepository::get_all(&self.db)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Delete a group. Its contacts are kept; only the memberships are removed.
+    pub fn delete_group(&self, group_id: &str) -> Result<bool> {
+        ContactGroupsRepository::delete_group(&self.db, group_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Add a contact to a group
+    pub fn add_contact_to_group(&self, group_id: &str, peer_id: &str) -> Result<bool> {
+        self.require_group(group_id)?;
+        if !self.is_contact(peer_id)? {
+            return Err(AppError::NotFound(format!("Contact {} not found", peer_id)));
+        }
+
+        ContactGroupsRepository::add_member(
+            &self.db,
+            group_id,
+            peer_id,
+            chrono::Utc::now().timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Remove a contact from a group
+    pub fn remove_contact_from_group(&self, group_id: &str, peer_id: &str) -> Result<bool> {
+        ContactGroupsRepository::remove_member(&self.db, group_id, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the peer IDs of a group's members
+    pub fn get_group_member_ids(&self, group_id: &str) -> Result<Vec<String>> {
+        self.require_group(group_id)?;
+        ContactGroupsRepository::get_member_ids(&self.db, group_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the contacts in a group
+    pub fn get_group_members(&self, group_id: &str) -> Result<Vec<Contact>> {
+        let mut members = Vec::new();
+        for peer_id in self.get_group_member_ids(group_id)? {
+            if let Some(contact) = self.get_contact(&peer_id)? {
+                members.push(contact);
+            }
+        }
+        Ok(members)
+    }
+
+    /// Get the groups a contact belongs to
+    pub fn get_contact_groups(&self, peer_id: &str) -> Result<Vec<ContactGroup>> {
+        ContactGroupsRepository::get_groups_for_contact(&self.db, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    fn require_group(&self, group_id: &str) -> Result<ContactGroup> {
+        ContactGroupsRepository::get_group(&self.db, group_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound(format!("Group {} not found", group_id)))
+    }
+
+    /// Whether we share our contact list with contacts granted `ShareContacts`
+    pub fn get_share_contact_list(&self) -> Result<bool> {
+        SettingsRepository::get_bool(&self.db, SETTING_SHARE_CONTACT_LIST, false)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Turn sharing our contact list on or off
+    pub fn set_share_contact_list(&self, enabled: bool) -> Result<()> {
+        SettingsRepository::set(&self.db, SETTING_SHARE_CONTACT_LIST, &enabled.to_string())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the contacts we show `requester_peer_id`, or `None` when sharing
+    /// is turned off. Blocked contacts and the requester are left out.
+    ///
+    /// The caller is responsible for checking the requester holds
+    /// `ShareContacts`.
+    pub fn get_shareable_contacts(
+        &self,
+        requester_peer_id: &str,
+    ) -> Result<Option<Vec<SharedContact>>> {
+        if !self.get_share_contact_list()? {
+            return Ok(None);
+        }
+
+        Ok(Some(
+            self.get_active_contacts()?
+                .into_iter()
+                .filter(|c| c.peer_id != requester_peer_id)
+                .map(|c| SharedContact {
+                    peer_id: c.peer_id,
+                    display_name: c.display_name,
+                })
+                .collect(),
+        ))
+    }
+
+    /// Turn contact lists shared with us into suggestions.
+    ///
+    /// Each entry pairs the contact that shared a list with the list itself.
+    /// Ourselves and anyone already in our contacts (blocked included) are
+    /// skipped. Peers known via more contacts come first.
+    pub fn suggest_contacts(
+        &self,
+        shared_lists: Vec<(SharedContact, Vec<SharedContact>)>,
+    ) -> Result<Vec<ContactSuggestion>> {
+        let own_peer_id = self.identity_service.get_identity()?.map(|i| i.peer_id);
+
+        let mut suggestions: Vec<ContactSuggestion> = Vec::new();
+        let mut index: HashMap<String, usize> = HashMap::new();
+        for (source, shared) in shared_lists {
+            for candidate in shared {
+                if own_peer_id.as_deref() == Some(candidate.peer_id.as_str())
+                    || candidate.peer_id == source.peer_id
+                    || self.is_contact(&candidate.peer_id)?
+                {
+                    continue;
+                }
+
+                let i = *index.entry(candidate.peer_id.clone()).or_insert_with(|| {
+                    suggestions.push(ContactSuggestion {
+                        peer_id: candidate.peer_id,
+                        display_name: candidate.display_name,
+                        known_via: Vec::new(),
+                    });
+                    suggestions.len() - 1
+                });
+                if !suggestions[i].known_via.contains(&source) {
+                    suggestions[i].known_via.push(source.clone());
+                }
+            }
+        }
+
+        suggestions.sort_by(|a, b| {
+            b.known_via
+                .len()
+                .cmp(&a.known_via.len())
+                .then_with(|| a.display_name.cmp(&b.display_name))
+        });
+        Ok(suggestions)
+    }
+
     /// Get X25519 public key for a contact (needed for encryption)
     pub fn get_x25519_public(&self, peer_id: &str) -> Result<Option<Vec<u8>>> {
         let contact = self.get_contact(peer_id)?;
         Ok(contact.map(|c| c.x25519_public))
     }
 
+    /// Record the message ciphers a contact announced during identity exchange
+    pub fn set_supported_ciphers(&self, peer_id: &str, ciphers: &[String]) -> Result<bool> {
+        ContactsRepository::set_supported_ciphers(&self.db, peer_id, ciphers)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the message ciphers a contact announced (None if it never did)
+    pub fn get_supported_ciphers(&self, peer_id: &str) -> Result<Option<Vec<String>>> {
+        ContactsRepository::get_supported_ciphers(&self.db, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Get Ed25519 public key for a contact (needed for signature verification)
     pub fn get_public_key(&self, peer_id: &str) -> Result<Option<Vec<u8>>> {
         let contact = self.get_contact(peer_id)?;
@@ -154,6 +668,7 @@ impl ContactsService {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::CURRENT_SIG_VERSION;
     use std::sync::Arc;
 
     fn create_test_services() -> (Arc<Database>, Arc<IdentityService>, ContactsService) {
@@ -167,7 +682,7 @@ mod tests {
     fn test_add_and_get_contact() {
         let (_, _, service) = create_test_services();
 
-        let id = service
+        let upsert = service
             .add_contact(
                 "12D3KooWTest",
                 &[1, 2, 3, 4],
@@ -178,7 +693,8 @@ mod tests {
             )
             .unwrap();
 
-        assert!(id > 0);
+        assert!(upsert.contact_id > 0);
+        assert!(upsert.created);
 
         let contact = service.get_contact("12D3KooWTest").unwrap().unwrap();
         assert_eq!(contact.display_name, "Test User");
@@ -209,4 +725,556 @@ mod tests {
         let active = service.get_active_contacts().unwrap();
         assert!(active.is_empty());
     }
+
+    #[test]
+    fn test_add_existing_contact_reports_changed_fields() {
+        let (_, _, service) = create_test_services();
+
+        let first = service
+            .add_contact(
+                "12D3KooWTest",
+                &[1, 2, 3, 4],
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+
+        let second = service
+            .add_contact(
+                "12D3KooWTest",
+                &[1, 2, 3, 4],
+                &[9, 9, 9, 9],
+                "Renamed User",
+                None,
+                Some("New bio"),
+            )
+            .unwrap();
+
+        assert_eq!(second.contact_id, first.contact_id);
+        assert!(!second.created);
+        assert!(second.is_updated());
+        assert_eq!(
+            second.changed_fields,
+            vec![
+                ContactField::DisplayName,
+                ContactField::Bio,
+                ContactField::X25519Public
+            ]
+        );
+
+        let contact = service.get_contact("12D3KooWTest").unwrap().unwrap();
+        assert_eq!(contact.display_name, "Renamed User");
+        assert_eq!(contact.bio, Some("New bio".to_string()));
+        assert_eq!(contact.x25519_public, vec![9, 9, 9, 9]);
+    }
+
+    #[test]
+    fn test_rename_flags_unverified_contacts_and_records_history() {
+        let (db, _, service) = create_test_services();
+
+        service
+            .add_contact("12D3KooWTest", &[1], &[2], "Alice", None, None)
+            .unwrap();
+
+        let renamed = service
+            .add_contact("12D3KooWTest", &[1], &[2], "Bob", None, None)
+            .unwrap();
+        assert_eq!(renamed.previous_display_name, Some("Alice".to_string()));
+        assert!(renamed.is_unverified_rename());
+
+        ContactsRepository::set_trust_level(&db, "12D3KooWTest", crate::db::VERIFIED_TRUST_LEVEL)
+            .unwrap();
+        let verified = service
+            .add_contact("12D3KooWTest", &[1], &[2], "Carol", None, None)
+            .unwrap();
+        assert!(verified.verified);
+        assert!(!verified.is_unverified_rename());
+
+        // A bio-only change is not a rename
+        let bio_only = service
+            .add_contact("12D3KooWTest", &[1], &[2], "Carol", None, Some("hi"))
+            .unwrap();
+        assert_eq!(bio_only.previous_display_name, None);
+
+        let names: Vec<String> = service
+            .get_name_history("12D3KooWTest")
+            .unwrap()
+            .into_iter()
+            .map(|entry| entry.display_name)
+            .collect();
+        assert_eq!(names, vec!["Alice", "Bob", "Carol"]);
+    }
+
+    #[test]
+    fn test_add_existing_contact_unchanged() {
+        let (_, _, service) = create_test_services();
+
+        service
+            .add_contact(
+                "12D3KooWTest",
+                &[1, 2, 3, 4],
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+
+        let again = service
+            .add_contact(
+                "12D3KooWTest",
+                &[1, 2, 3, 4],
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+        assert!(!again.created);
+        assert!(!again.is_updated());
+        assert!(again.changed_fields.is_empty());
+    }
+
+    #[test]
+    fn test_process_heartbeat_updates_last_seen() {
+        use crate::services::{sign, CryptoService};
+
+        let (_, _, service) = create_test_services();
+        let (signing_key, verifying_key) = CryptoService::generate_ed25519_keypair();
+        service
+            .add_contact(
+                "12D3KooWTest",
+                &verifying_key.to_bytes(),
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let heartbeat = SignableHeartbeat {
+            peer_id: "12D3KooWTest".to_string(),
+            timestamp,
+        };
+        let signature = sign(&signing_key, &heartbeat).unwrap();
+
+        assert!(service
+            .process_heartbeat("12D3KooWTest", timestamp, &signature)
+            .unwrap());
+        let contact = service.get_contact("12D3KooWTest").unwrap().unwrap();
+        assert_eq!(contact.last_seen_at, Some(timestamp));
+
+        // Replaying an older heartbeat never moves last_seen backwards
+        let older = SignableHeartbeat {
+            peer_id: "12D3KooWTest".to_string(),
+            timestamp: timestamp - 60,
+        };
+        let older_signature = sign(&signing_key, &older).unwrap();
+        assert!(!service
+            .process_heartbeat("12D3KooWTest", timestamp - 60, &older_signature)
+            .unwrap());
+        let contact = service.get_contact("12D3KooWTest").unwrap().unwrap();
+        assert_eq!(contact.last_seen_at, Some(timestamp));
+    }
+
+    #[test]
+    fn test_process_heartbeat_rejects_bad_signature() {
+        let (_, _, service) = create_test_services();
+        let (_signing_key, verifying_key) =
+            crate::services::CryptoService::generate_ed25519_keypair();
+        service
+            .add_contact(
+                "12D3KooWTest",
+                &verifying_key.to_bytes(),
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let result = service.process_heartbeat("12D3KooWTest", timestamp, &[0u8; 64]);
+        assert!(result.is_err());
+
+        let contact = service.get_contact("12D3KooWTest").unwrap().unwrap();
+        assert_eq!(contact.last_seen_at, None);
+    }
+
+    #[test]
+    fn test_forget_contact_removes_conversation() {
+        let (db, identity_service, service) = create_test_services();
+        let our_peer_id = identity_service
+            .create_identity(crate::models::CreateIdentityRequest {
+                display_name: "Me".to_string(),
+                passphrase: "test-pass".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap()
+            .peer_id;
+        service
+            .add_contact(
+                "12D3KooWTest",
+                &[1, 2, 3, 4],
+                &[5, 6, 7, 8],
+                "Test User",
+                None,
+                None,
+            )
+            .unwrap();
+        let conversation_id = derive_conversation_id(&our_peer_id, "12D3KooWTest");
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO messages (message_id, conversation_id, sender_peer_id,
+                    recipient_peer_id, content_encrypted, lamport_clock, sent_at)
+                 VALUES ('msg-1', ?, ?, '12D3KooWTest', X'00', 1, 0)",
+                rusqlite::params![conversation_id, our_peer_id],
+            )
+        })
+        .unwrap();
+
+        let forgotten = service.forget_contact("12D3KooWTest").unwrap();
+        assert_eq!(forgotten.rows_deleted, 3); // contact, name history, message
+        assert!(service.get_contact("12D3KooWTest").unwrap().is_none());
+
+        let messages: i64 = db
+            .with_connection(|conn| {
+                conn.query_row("SELECT COUNT(*) FROM messages", [], |row| row.get(0))
+            })
+            .unwrap();
+        assert_eq!(messages, 0);
+
+        assert!(service.forget_contact(&our_peer_id).is_err());
+    }
+
+    fn create_identity(display_name: &str) -> Arc<IdentityService> {
+        let db = Arc::new(Database::in_memory().unwrap());
+        let identity_service = Arc::new(IdentityService::new(db));
+        identity_service
+            .create_identity(crate::models::CreateIdentityRequest {
+                display_name: display_name.to_string(),
+                passphrase: "test-pass".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+        identity_service
+    }
+
+    #[test]
+    fn test_add_contact_from_qr_payload() {
+        let (_, _, service) = create_test_services();
+        let alice = create_identity("Alice");
+
+        let bytes = alice.get_identity_qr_payload().unwrap();
+        let payload = IdentityQrPayload::decode(&bytes).unwrap();
+        let upsert = service.add_contact_from_qr(&payload).unwrap();
+        assert!(upsert.created);
+
+        let alice_identity = alice.get_identity().unwrap().unwrap();
+        let contact = service
+            .get_contact(&alice_identity.peer_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(contact.display_name, "Alice");
+        assert_eq!(contact.public_key, alice_identity.public_key);
+        assert_eq!(contact.x25519_public, alice_identity.x25519_public);
+    }
+
+    #[test]
+    fn test_add_contact_from_qr_keeps_existing_bio() {
+        let (_, _, service) = create_test_services();
+        let alice = create_identity("Alice");
+        let identity = alice.get_identity().unwrap().unwrap();
+
+        service
+            .add_contact(
+                &identity.peer_id,
+                &identity.public_key,
+                &identity.x25519_public,
+                "Alice",
+                Some("avatar-hash"),
+                Some("Hello!"),
+            )
+            .unwrap();
+
+        let payload = IdentityQrPayload::decode(&alice.get_identity_qr_payload().unwrap()).unwrap();
+        let upsert = service.add_contact_from_qr(&payload).unwrap();
+        assert!(!upsert.created);
+        assert!(upsert.changed_fields.is_empty());
+
+        let contact = service.get_contact(&identity.peer_id).unwrap().unwrap();
+        assert_eq!(contact.bio, Some("Hello!".to_string()));
+        assert_eq!(contact.avatar_hash, Some("avatar-hash".to_string()));
+    }
+
+    #[test]
+    fn test_get_stale_contacts_reports_reasons() {
+        use crate::db::{
+            MessageData, MessageStatus, MessagesRepository, PostData, PostVisibility,
+            PostsRepository,
+        };
+
+        let (db, _, service) = create_test_services();
+        for peer_id in ["12D3KooWQuiet", "12D3KooWOld", "12D3KooWChatty"] {
+            service
+                .add_contact(peer_id, &[1], &[2], peer_id, None, None)
+                .unwrap();
+        }
+
+        let day = 86_400;
+        let now = chrono::Utc::now().timestamp() + 100 * day;
+        let long_ago = now - 90 * day;
+        let recently = now - 2 * day;
+
+        ContactsRepository::update_last_seen_at(&db, "12D3KooWOld", long_ago).unwrap();
+        ContactsRepository::update_last_seen_at(&db, "12D3KooWChatty", long_ago).unwrap();
+        PostsRepository::insert_remote_post(
+            &db,
+            &PostData {
+                post_id: "post-old".to_string(),
+                author_peer_id: "12D3KooWOld".to_string(),
+                content_type: "text".to_string(),
+                content_text: Some("hi".to_string()),
+                visibility: PostVisibility::Contacts,
+                lamport_clock: 1,
+                created_at: long_ago,
+                signature: vec![0u8; 64],
+                sig_version: CURRENT_SIG_VERSION,
+            },
+        )
+        .unwrap();
+        MessagesRepository::insert_message(
+            &db,
+            &MessageData {
+                message_id: "msg-1".to_string(),
+                conversation_id: "conv-1".to_string(),
+                sender_peer_id: "12D3KooWChatty".to_string(),
+                recipient_peer_id: "12D3KooWMe".to_string(),
+                content_encrypted: vec![1, 2, 3],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: recently,
+                received_at: Some(recently),
+                status: MessageStatus::Delivered,
+            },
+        )
+        .unwrap();
+
+        let stale = service.get_stale_contacts_at(30, now).unwrap();
+        let mut found: Vec<(&str, &[StaleReason])> = stale
+            .iter()
+            .map(|s| (s.contact.peer_id.as_str(), s.reasons.as_slice()))
+            .collect();
+        found.sort_by_key(|(peer_id, _)| *peer_id);
+
+        assert_eq!(
+            found,
+            vec![
+                (
+                    "12D3KooWOld",
+                    &[
+                        StaleReason::NoRecentConnection,
+                        StaleReason::NeverMessaged,
+                        StaleReason::NoRecentPosts,
+                    ][..]
+                ),
+                (
+                    "12D3KooWQuiet",
+                    &[
+                        StaleReason::NeverConnected,
+                        StaleReason::NeverMessaged,
+                        StaleReason::NoPostsSynced,
+                    ][..]
+                ),
+            ]
+        );
+
+        // Nothing is removed by the analysis
+        assert_eq!(service.get_all_contacts().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_get_stale_contacts_skips_recently_added() {
+        let (_, _, service) = create_test_services();
+        service
+            .add_contact("12D3KooWNew", &[1], &[2], "New", None, None)
+            .unwrap();
+
+        assert!(service.get_stale_contacts(30).unwrap().is_empty());
+        assert!(matches!(
+            service.get_stale_contacts(0),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_contact_groups() {
+        let (_, _, service) = create_test_services();
+        service
+            .add_contact("12D3KooWAlice", &[1], &[2], "Alice", None, None)
+            .unwrap();
+        service
+            .add_contact("12D3KooWBob", &[3], &[4], "Bob", None, None)
+            .unwrap();
+
+        let family = service.create_group(" Family ").unwrap();
+        assert_eq!(family.name, "Family");
+        assert!(matches!(
+            service.create_group("family"),
+            Err(AppError::AlreadyExists(_))
+        ));
+        assert!(matches!(
+            service.create_group("  "),
+            Err(AppError::Validation(_))
+        ));
+
+        assert!(service
+            .add_contact_to_group(&family.group_id, "12D3KooWAlice")
+            .unwrap());
+        assert!(service
+            .add_contact_to_group(&family.group_id, "12D3KooWBob")
+            .unwrap());
+        assert!(matches!(
+            service.add_contact_to_group(&family.group_id, "12D3KooWStranger"),
+            Err(AppError::NotFound(_))
+        ));
+        assert!(matches!(
+            service.add_contact_to_group("missing", "12D3KooWAlice"),
+            Err(AppError::NotFound(_))
+        ));
+
+        let members = service.get_group_members(&family.group_id).unwrap();
+        let names: Vec<_> = members.iter().map(|c| c.display_name.as_str()).collect();
+        assert_eq!(names, vec!["Alice", "Bob"]);
+        assert_eq!(service.get_groups().unwrap()[0].member_count, 2);
+        assert_eq!(service.get_contact_groups("12D3KooWBob").unwrap().len(), 1);
+
+        assert!(service
+            .remove_contact_from_group(&family.group_id, "12D3KooWBob")
+            .unwrap());
+        assert_eq!(
+            service.get_group_member_ids(&family.group_id).unwrap(),
+            vec!["12D3KooWAlice"]
+        );
+
+        // Deleting the group keeps the contacts
+        assert!(service.delete_group(&family.group_id).unwrap());
+        assert!(service.get_groups().unwrap().is_empty());
+        assert!(service.is_contact("12D3KooWAlice").unwrap());
+    }
+
+    fn shared(peer_id: &str, display_name: &str) -> SharedContact {
+        SharedContact {
+            peer_id: peer_id.to_string(),
+            display_name: display_name.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_shareable_contacts_respect_setting() {
+        let (_, _, service) = create_test_services();
+        for (peer_id, name) in [
+            ("12D3KooWAlice", "Alice"),
+            ("12D3KooWBob", "Bob"),
+            ("12D3KooWCarol", "Carol"),
+        ] {
+            service
+                .add_contact(peer_id, &[1; 32], &[2; 32], name, None, None)
+                .unwrap();
+        }
+        service.block_contact("12D3KooWCarol").unwrap();
+
+        // Sharing is off until the user opts in
+        assert!(!service.get_share_contact_list().unwrap());
+        assert_eq!(
+            service.get_shareable_contacts("12D3KooWAlice").unwrap(),
+            None
+        );
+
+        service.set_share_contact_list(true).unwrap();
+        let contacts = service
+            .get_shareable_contacts("12D3KooWAlice")
+            .unwrap()
+            .unwrap();
+        // Neither the requester nor blocked contacts are shared
+        assert_eq!(contacts, vec![shared("12D3KooWBob", "Bob")]);
+    }
+
+    #[test]
+    fn test_suggest_contacts_skips_known_peers_and_groups_sources() {
+        let (_, identity_service, service) = create_test_services();
+        let own_peer_id = identity_service
+            .create_identity(crate::models::CreateIdentityRequest {
+                display_name: "Me".to_string(),
+                passphrase: "test-pass".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap()
+            .peer_id;
+        service
+            .add_contact("12D3KooWAlice", &[1; 32], &[2; 32], "Alice", None, None)
+            .unwrap();
+        service
+            .add_contact("12D3KooWBob", &[1; 32], &[2; 32], "Bob", None, None)
+            .unwrap();
+        service
+            .add_contact("12D3KooWMallory", &[1; 32], &[2; 32], "Mallory", None, None)
+            .unwrap();
+        service.block_contact("12D3KooWMallory").unwrap();
+
+        let alice = shared("12D3KooWAlice", "Alice");
+        let bob = shared("12D3KooWBob", "Bob");
+        let suggestions = service
+            .suggest_contacts(vec![
+                (
+                    alice.clone(),
+                    vec![
+                        shared(&own_peer_id, "Me"),
+                        shared("12D3KooWBob", "Bob"),
+                        shared("12D3KooWDave", "Dave"),
+                        shared("12D3KooWMallory", "Mallory"),
+                    ],
+                ),
+                (
+                    bob.clone(),
+                    vec![
+                        shared("12D3KooWAlice", "Alice"),
+                        shared("12D3KooWErin", "Erin"),
+                        shared("12D3KooWDave", "Dave"),
+                    ],
+                ),
+            ])
+            .unwrap();
+
+        assert_eq!(
+            suggestions,
+            vec![
+                ContactSuggestion {
+                    peer_id: "12D3KooWDave".to_string(),
+                    display_name: "Dave".to_string(),
+                    known_via: vec![alice, bob.clone()],
+                },
+                ContactSuggestion {
+                    peer_id: "12D3KooWErin".to_string(),
+                    display_name: "Erin".to_string(),
+                    known_via: vec![bob],
+                },
+            ]
+        );
+        // Suggestions are only candidates
+        assert!(!service.is_contact("12D3KooWDave").unwrap());
+    }
 }
@@ -1,23 +1,60 @@
 //! Content sync service for synchronizing posts between peers
 
 use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use std::time::Duration;
 
 use ed25519_dalek::VerifyingKey;
+use serde::{Deserialize, Serialize};
+use tokio::sync::Notify;
 
-use crate::db::{Capability, Database, PostData, PostVisibility, PostsRepository};
+use crate::db::repositories::settings_repo::{
+    SETTING_AUTO_SYNC_INTERVAL, SETTING_POST_VIEW_TIMING, SETTING_REPORT_POST_VIEWS,
+};
+use crate::db::repositories::SettingsRepository;
+use crate::db::{
+    ActivityKind, ActivityRepository, Capability, Database, FetchIntentsRepository, PostData,
+    PostDeliveriesRepository, PostDelivery, PostViewSummary, PostViewsRepository, PostVisibility,
+    PostsRepository,
+};
 use crate::error::{AppError, Result};
+use crate::services::board_service::verify_relay_timestamp;
+use crate::services::clock::{system_clock, Clock};
 use crate::services::{
-    verify, ContactsService, IdentityService, PermissionsService, PostSummary,
-    SignableContentManifestRequest, SignableContentManifestResponse, SignablePost,
+    markdown, verify, ContactsService, IdentityService, ManifestDetailLevel, PermissionsService,
+    PostHeader, PostSummary, SignableContentManifestRequest, SignableContentManifestResponse,
+    SignablePost, SignablePostAnnouncement, SignablePostView, SignaturePolicy,
 };
 
+/// Default seconds between background syncs with connected peers
+pub const DEFAULT_AUTO_SYNC_INTERVAL_SECS: u64 = 5 * 60;
+
+/// Shortest background sync interval that can be configured
+pub const MIN_AUTO_SYNC_INTERVAL_SECS: u64 = 60;
+
+/// Longest background sync interval that can be configured (one day)
+pub const MAX_AUTO_SYNC_INTERVAL_SECS: u64 = 24 * 60 * 60;
+
+/// Each wait between background syncs is shifted by up to this fraction of
+/// the interval, so peers that came online together don't sync in lockstep
+const AUTO_SYNC_JITTER: f64 = 0.2;
+
+/// Times an interrupted post fetch is re-issued on reconnect before giving up
+pub const MAX_FETCH_INTENT_ATTEMPTS: u32 = 5;
+
 /// Service for syncing content between peers
 pub struct ContentSyncService {
     db: Arc<Database>,
     identity_service: Arc<IdentityService>,
     contacts_service: Arc<ContactsService>,
     permissions_service: Arc<PermissionsService>,
+    /// Wakes the background sync task when its interval changes
+    auto_sync_changed: Notify,
+    /// Whether the UI reported a metered connection (pauses background sync)
+    metered_connection: AtomicBool,
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
 }
 
 /// A request for content manifest
@@ -27,6 +64,7 @@ pub struct OutgoingManifestRequest {
     pub cursor: HashMap<String, u64>,
     pub limit: u32,
     pub timestamp: i64,
+    pub detail_level: ManifestDetailLevel,
     pub signature: Vec<u8>,
 }
 
@@ -34,7 +72,10 @@ pub struct OutgoingManifestRequest {
 #[derive(Debug, Clone)]
 pub struct OutgoingManifestResponse {
     pub responder_peer_id: String,
+    /// Full summaries (empty for headers-only responses)
     pub posts: Vec<PostSummary>,
+    /// Post headers (only populated for headers-only responses)
+    pub headers: Vec<PostHeader>,
     pub has_more: bool,
     pub next_cursor: HashMap<String, u64>,
     pub timestamp: i64,
@@ -62,6 +103,34 @@ pub struct OutgoingFetchResponse {
     pub lamport_clock: u64,
     pub created_at: i64,
     pub signature: Vec<u8>,
+    pub sig_version: u32,
+}
+
+/// A view report to send to a post's author
+#[derive(Debug, Clone)]
+pub struct OutgoingPostView {
+    pub post_id: String,
+    pub viewer_peer_id: String,
+    pub viewed_at: i64,
+    pub signature: Vec<u8>,
+}
+
+/// A new post pushed to contacts who are online when it is created
+#[derive(Debug, Clone)]
+pub struct OutgoingPostAnnouncement {
+    pub post: PostSummary,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// Privacy settings for post view reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PostViewSettings {
+    /// Tell authors when we fetch their posts
+    pub report_views: bool,
+    /// Keep the time of each view reported on our own posts
+    pub record_view_times: bool,
 }
 
 /// Parameters for storing a remote post received from a peer
@@ -74,6 +143,8 @@ pub struct RemotePostParams<'a> {
     pub lamport_clock: u64,
     pub created_at: i64,
     pub signature: &'a [u8],
+    /// Signature scheme version the author signed with
+    pub sig_version: u32,
 }
 
 impl ContentSyncService {
@@ -89,9 +160,18 @@ impl ContentSyncService {
             identity_service,
             contacts_service,
             permissions_service,
+            auto_sync_changed: Notify::new(),
+            metered_connection: AtomicBool::new(false),
+            clock: system_clock(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Get a reference to the underlying database (used for direct post_media writes)
     pub fn db(&self) -> &Database {
         &self.db
@@ -102,19 +182,21 @@ impl ContentSyncService {
         &self,
         cursor: HashMap<String, u64>,
         limit: u32,
+        detail_level: ManifestDetailLevel,
     ) -> Result<OutgoingManifestRequest> {
         let identity = self
             .identity_service
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
-        let timestamp = chrono::Utc::now().timestamp();
+        let timestamp = self.clock.timestamp();
 
         let signable = SignableContentManifestRequest {
             requester_peer_id: identity.peer_id.clone(),
             cursor: cursor.clone(),
             limit,
             timestamp,
+            detail_level,
         };
 
         let signature = self.identity_service.sign(&signable)?;
@@ -124,6 +206,7 @@ impl ContentSyncService {
             cursor,
             limit,
             timestamp,
+            detail_level,
             signature,
         })
     }
@@ -139,7 +222,7 @@ impl ContentSyncService {
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
-        let timestamp = chrono::Utc::now().timestamp();
+        let timestamp = self.clock.timestamp();
 
         // Sign the fetch request parameters
         let sign_data = format!(
@@ -172,7 +255,7 @@ impl ContentSyncService {
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
         // Validate timestamp is within acceptable window (5 minutes)
-        let now = chrono::Utc::now().timestamp();
+        let now = self.clock.timestamp();
         let time_diff = (now - timestamp).abs();
         if time_diff > 300 {
             return Err(AppError::Crypto(format!(
@@ -234,7 +317,11 @@ impl ContentSyncService {
         // Check visibility - for Contacts visibility, requester must be in contacts
         // (which we already verified above via WallRead permission check)
         // For Public, anyone with WallRead can access
-        // Note: We don't serve posts with other visibility levels
+        // Private posts are never served; report them as missing so their
+        // existence isn't revealed
+        if !post.visibility.is_shareable() {
+            return Err(AppError::NotFound(format!("Post {} not found", post_id)));
+        }
 
         Ok(OutgoingFetchResponse {
             post_id: post.post_id,
@@ -245,15 +332,20 @@ impl ContentSyncService {
             lamport_clock: post.lamport_clock as u64,
             created_at: post.created_at,
             signature: post.signature,
+            sig_version: post.sig_version,
         })
     }
 
-    /// Process an incoming manifest request and create a response
+    /// Process an incoming manifest request and create a response.
+    ///
+    /// With [`ManifestDetailLevel::HeadersOnly`] the response carries only
+    /// post IDs and lamport clocks, skipping the per-post media lookups.
     pub fn process_manifest_request(
         &self,
         requester_peer_id: &str,
         cursor: &HashMap<String, u64>,
         limit: u32,
+        detail_level: ManifestDetailLevel,
         timestamp: i64,
         signature: &[u8],
     ) -> Result<OutgoingManifestResponse> {
@@ -273,6 +365,7 @@ impl ContentSyncService {
             cursor: cursor.clone(),
             limit,
             timestamp,
+            detail_level,
         };
 
         let verifying_key = VerifyingKey::from_bytes(
@@ -303,27 +396,23 @@ impl ContentSyncService {
         // The cursor maps our peer_id to the highest lamport clock they've seen
         let our_cursor = cursor.get(&identity.peer_id).copied().unwrap_or(0);
 
-        // Get posts newer than the cursor
+        // Get posts newer than the cursor (private posts are never offered)
         let posts = self.get_posts_after_cursor(&identity.peer_id, our_cursor, limit)?;
 
-        // Build post summaries
-        let post_summaries: Vec<PostSummary> = posts
-            .iter()
-            .map(|post| {
-                let media_hashes =
-                    PostsRepository::get_media_hashes(&self.db, &post.post_id).unwrap_or_default();
-
-                PostSummary {
-                    post_id: post.post_id.clone(),
-                    author_peer_id: post.author_peer_id.clone(),
-                    lamport_clock: post.lamport_clock as u64,
-                    content_type: post.content_type.clone(),
-                    has_media: !media_hashes.is_empty(),
-                    media_hashes,
-                    created_at: post.created_at,
-                }
-            })
-            .collect();
+        // Build post summaries, or just headers if that's all the requester wants
+        let (post_summaries, headers): (Vec<PostSummary>, Vec<PostHeader>) = match detail_level {
+            ManifestDetailLevel::Full => (self.build_post_summaries(&posts), Vec::new()),
+            ManifestDetailLevel::HeadersOnly => (
+                Vec::new(),
+                posts
+                    .iter()
+                    .map(|post| PostHeader {
+                        post_id: post.post_id.clone(),
+                        lamport_clock: post.lamport_clock as u64,
+                    })
+                    .collect(),
+            ),
+        };
 
         // Calculate next cursor
         let mut next_cursor = cursor.clone();
@@ -333,7 +422,7 @@ impl ContentSyncService {
 
         let has_more = posts.len() as u32 >= limit;
 
-        let response_timestamp = chrono::Utc::now().timestamp();
+        let response_timestamp = self.clock.timestamp();
 
         let response_signable = SignableContentManifestResponse {
             responder_peer_id: identity.peer_id.clone(),
@@ -341,6 +430,7 @@ impl ContentSyncService {
             has_more,
             next_cursor: next_cursor.clone(),
             timestamp: response_timestamp,
+            headers: headers.clone(),
         };
 
         let response_signature = self.identity_service.sign(&response_signable)?;
@@ -348,6 +438,7 @@ impl ContentSyncService {
         Ok(OutgoingManifestResponse {
             responder_peer_id: identity.peer_id,
             posts: post_summaries,
+            headers,
             has_more,
             next_cursor,
             timestamp: response_timestamp,
@@ -355,11 +446,36 @@ impl ContentSyncService {
         })
     }
 
-    /// Process an incoming manifest response
+    /// Build full manifest summaries, including media hashes, for posts
+    fn build_post_summaries(&self, posts: &[crate::db::Post]) -> Vec<PostSummary> {
+        posts
+            .iter()
+            .map(|post| {
+                let media_hashes =
+                    PostsRepository::get_media_hashes(&self.db, &post.post_id).unwrap_or_default();
+
+                PostSummary {
+                    post_id: post.post_id.clone(),
+                    author_peer_id: post.author_peer_id.clone(),
+                    lamport_clock: post.lamport_clock as u64,
+                    content_type: post.content_type.clone(),
+                    has_media: !media_hashes.is_empty(),
+                    media_hashes,
+                    created_at: post.created_at,
+                }
+            })
+            .collect()
+    }
+
+    /// Process an incoming manifest response.
+    ///
+    /// Accepts either full summaries or headers-only entries and returns the
+    /// IDs of posts we are missing or hold an older version of.
     pub fn process_manifest_response(
         &self,
         responder_peer_id: &str,
         posts: &[PostSummary],
+        headers: &[PostHeader],
         has_more: bool,
         next_cursor: &HashMap<String, u64>,
         timestamp: i64,
@@ -377,6 +493,7 @@ impl ContentSyncService {
             has_more,
             next_cursor: next_cursor.clone(),
             timestamp,
+            headers: headers.to_vec(),
         };
 
         let verifying_key = VerifyingKey::from_bytes(
@@ -396,16 +513,37 @@ impl ContentSyncService {
         // Return list of post IDs we need to fetch
         let mut posts_to_fetch = Vec::new();
 
-        for summary in posts {
+        let offered = posts
+            .iter()
+            .map(|summary| (&summary.post_id, summary.lamport_clock))
+            .chain(
+                headers
+                    .iter()
+                    .map(|header| (&header.post_id, header.lamport_clock)),
+            );
+
+        for (post_id, lamport_clock) in offered {
             // Check if we already have this post with the same or newer lamport clock
-            if let Some(existing) = PostsRepository::get_by_post_id(&self.db, &summary.post_id)
+            if let Some(existing) = PostsRepository::get_by_post_id(&self.db, post_id)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))?
             {
-                if existing.lamport_clock as u64 >= summary.lamport_clock {
+                if existing.lamport_clock as u64 >= lamport_clock {
                     continue; // We have a newer or same version
                 }
             }
-            posts_to_fetch.push(summary.post_id.clone());
+            posts_to_fetch.push(post_id.clone());
+        }
+
+        // Record what we still need before the cursor moves past it, so an
+        // interrupted sync can re-issue these fetches later
+        if !posts_to_fetch.is_empty() {
+            FetchIntentsRepository::record(
+                &self.db,
+                responder_peer_id,
+                &posts_to_fetch,
+                self.clock.timestamp(),
+            )
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
         }
 
         // Store the cursor for future requests
@@ -449,41 +587,48 @@ impl ContentSyncService {
         )
         .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
 
-        if !verify(&verifying_key, &signable, signature)? {
+        if !SignaturePolicy::from_settings(&self.db)?.verify(
+            &verifying_key,
+            &signable,
+            signature,
+            params.sig_version,
+        )? {
             return Err(AppError::Crypto("Invalid post signature".to_string()));
         }
+        markdown::ensure_sanitized(content_type, content_text)?;
+
+        let vis = PostVisibility::from_str(visibility).unwrap_or(PostVisibility::Contacts);
+        let post_data = PostData {
+            post_id: post_id.to_string(),
+            author_peer_id: author_peer_id.to_string(),
+            content_type: content_type.to_string(),
+            content_text: content_text.map(String::from),
+            visibility: vis,
+            lamport_clock: lamport_clock as i64,
+            created_at,
+            signature: signature.to_vec(),
+            sig_version: params.sig_version,
+        };
 
-        // Check for existing post
+        // The same post can arrive from the author directly and from a relay's
+        // wall store, in either order. Whichever source it came from, keep one
+        // row per post_id holding the winning signed version.
         if let Some(existing) = PostsRepository::get_by_post_id(&self.db, post_id)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?
         {
-            if existing.lamport_clock as u64 >= lamport_clock {
-                return Ok(()); // We have a newer or same version
+            if existing.author_peer_id != author_peer_id {
+                return Err(AppError::Validation(format!(
+                    "Post {} belongs to another author",
+                    post_id
+                )));
             }
-            // Update existing post
-            PostsRepository::update_post(
-                &self.db,
-                post_id,
-                content_text,
-                created_at,
-                lamport_clock as i64,
-            )
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            if !incoming_version_wins(&existing, lamport_clock, signature) {
+                // We already hold this or a newer version
+                return self.complete_fetch_intents(post_id);
+            }
+            PostsRepository::replace_remote_version(&self.db, &post_data)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
         } else {
-            // Insert new post
-            let vis = PostVisibility::from_str(visibility).unwrap_or(PostVisibility::Contacts);
-
-            let post_data = PostData {
-                post_id: post_id.to_string(),
-                author_peer_id: author_peer_id.to_string(),
-                content_type: content_type.to_string(),
-                content_text: content_text.map(String::from),
-                visibility: vis,
-                lamport_clock: lamport_clock as i64,
-                created_at,
-                signature: signature.to_vec(),
-            };
-
             PostsRepository::insert_remote_post(&self.db, &post_data)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))?;
         }
@@ -493,9 +638,54 @@ impl ContentSyncService {
             .update_lamport_clock(author_peer_id, lamport_clock as i64)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
+        self.complete_fetch_intents(post_id)
+    }
+
+    /// Posts a peer's earlier manifests named that were never stored, to be
+    /// fetched again now that the peer is connected. Each call counts as an
+    /// attempt; intents past [`MAX_FETCH_INTENT_ATTEMPTS`] are dropped.
+    pub fn claim_fetch_intents(&self, peer_id: &str) -> Result<Vec<String>> {
+        FetchIntentsRepository::claim_for_retry(&self.db, peer_id, MAX_FETCH_INTENT_ATTEMPTS)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Forget pending fetches of a post we now hold
+    fn complete_fetch_intents(&self, post_id: &str) -> Result<()> {
+        FetchIntentsRepository::complete(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
         Ok(())
     }
 
+    /// Record a community relay's signed receive time for a stored post.
+    /// Rejected unless the signature verifies against the relay's peer ID.
+    pub fn record_relay_timestamp(
+        &self,
+        relay_peer_id: &str,
+        params: &RemotePostParams<'_>,
+        relay_observed_at: i64,
+        relay_signature: &[u8],
+    ) -> Result<bool> {
+        if !verify_relay_timestamp(
+            relay_peer_id,
+            params.post_id,
+            params.author_peer_id,
+            params.signature,
+            relay_observed_at,
+            relay_signature,
+        ) {
+            return Err(AppError::Crypto(
+                "Invalid relay timestamp signature".to_string(),
+            ));
+        }
+        PostsRepository::set_relay_observed_at(
+            &self.db,
+            params.post_id,
+            params.author_peer_id,
+            relay_observed_at,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Get posts after a certain lamport clock cursor
     fn get_posts_after_cursor(
         &self,
@@ -538,123 +728,521 @@ impl ContentSyncService {
         Ok(())
     }
 
-    /// Get stored sync cursor for a peer
-    pub fn get_sync_cursor(&self, peer_id: &str) -> Result<HashMap<String, u64>> {
-        self.db
-            .get_sync_cursor(peer_id, "posts")
-            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    /// Get the post view privacy settings
+    pub fn get_post_view_settings(&self) -> Result<PostViewSettings> {
+        Ok(PostViewSettings {
+            report_views: SettingsRepository::get_bool(&self.db, SETTING_REPORT_POST_VIEWS, true)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?,
+            record_view_times: SettingsRepository::get_bool(
+                &self.db,
+                SETTING_POST_VIEW_TIMING,
+                false,
+            )
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?,
+        })
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use crate::db::{ContactData, ContactsRepository};
-    use crate::models::CreateIdentityRequest;
-    use crate::services::{ContactsService, IdentityService, PermissionsService};
-    use std::sync::Arc;
+    /// Persist the post view privacy settings
+    pub fn set_post_view_settings(&self, settings: PostViewSettings) -> Result<()> {
+        SettingsRepository::set(
+            &self.db,
+            SETTING_REPORT_POST_VIEWS,
+            &settings.report_views.to_string(),
+        )
+        .and_then(|_| {
+            SettingsRepository::set(
+                &self.db,
+                SETTING_POST_VIEW_TIMING,
+                &settings.record_view_times.to_string(),
+            )
+        })
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
 
-    fn create_test_env() -> (
-        ContentSyncService,
-        Arc<Database>,
-        Arc<IdentityService>,
-        String, // our peer_id
-    ) {
-        let db = Arc::new(Database::in_memory().unwrap());
-        let identity_service = Arc::new(IdentityService::new(db.clone()));
-        let contacts_service = Arc::new(ContactsService::new(db.clone(), identity_service.clone()));
-        let permissions_service = Arc::new(PermissionsService::new(
-            db.clone(),
-            identity_service.clone(),
-        ));
+    /// Seconds between background syncs with connected peers (0 = off)
+    pub fn get_auto_sync_interval(&self) -> Result<u64> {
+        let value = SettingsRepository::get(&self.db, SETTING_AUTO_SYNC_INTERVAL)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(value
+            .and_then(|value| value.parse().ok())
+            .unwrap_or(DEFAULT_AUTO_SYNC_INTERVAL_SECS))
+    }
 
-        let info = identity_service
-            .create_identity(CreateIdentityRequest {
-                display_name: "Sync User".to_string(),
-                passphrase: "test-pass".to_string(),
-                bio: None,
-                passphrase_hint: None,
-            })
-            .unwrap();
+    /// Set the background sync interval in seconds; 0 turns it off
+    pub fn set_auto_sync_interval(&self, secs: u64) -> Result<()> {
+        if secs != 0 && !(MIN_AUTO_SYNC_INTERVAL_SECS..=MAX_AUTO_SYNC_INTERVAL_SECS).contains(&secs)
+        {
+            return Err(AppError::Validation(format!(
+                "Auto-sync interval must be 0 (off) or between {} and {} seconds",
+                MIN_AUTO_SYNC_INTERVAL_SECS, MAX_AUTO_SYNC_INTERVAL_SECS
+            )));
+        }
+        SettingsRepository::set(&self.db, SETTING_AUTO_SYNC_INTERVAL, &secs.to_string())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        self.auto_sync_changed.notify_one();
+        Ok(())
+    }
 
-        let service = ContentSyncService::new(
-            db.clone(),
-            identity_service.clone(),
-            contacts_service,
-            permissions_service,
-        );
+    /// Wait until the background sync interval is changed
+    pub async fn auto_sync_interval_changed(&self) {
+        self.auto_sync_changed.notified().await
+    }
 
-        (service, db, identity_service, info.peer_id)
+    /// Record whether the current connection is metered. The backend can't
+    /// tell on its own, so the UI reports it; background sync pauses while
+    /// it is set.
+    pub fn set_metered_connection(&self, metered: bool) {
+        self.metered_connection.store(metered, Ordering::Relaxed);
     }
 
-    #[test]
-    fn test_create_manifest_request() {
-        let (service, _db, _identity, peer_id) = create_test_env();
+    /// Whether the UI reported a metered connection
+    pub fn is_metered_connection(&self) -> bool {
+        self.metered_connection.load(Ordering::Relaxed)
+    }
 
-        let mut cursor = HashMap::new();
-        cursor.insert("12D3KooWPeer1".to_string(), 5u64);
+    /// Create a view report for a post we just fetched from its author.
+    ///
+    /// Returns `None` when the user has turned view reporting off.
+    pub fn create_post_view(
+        &self,
+        post_id: &str,
+        author_peer_id: &str,
+    ) -> Result<Option<OutgoingPostView>> {
+        if !self.get_post_view_settings()?.report_views {
+            return Ok(None);
+        }
 
-        let request = service.create_manifest_request(cursor.clone(), 50).unwrap();
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
-        assert_eq!(request.requester_peer_id, peer_id);
-        assert_eq!(request.cursor, cursor);
-        assert_eq!(request.limit, 50);
-        assert!(!request.signature.is_empty());
-    }
+        let viewed_at = self.clock.timestamp();
+        let signable = SignablePostView {
+            post_id: post_id.to_string(),
+            author_peer_id: author_peer_id.to_string(),
+            viewer_peer_id: identity.peer_id.clone(),
+            viewed_at,
+        };
+        let signature = self.identity_service.sign(&signable)?;
 
-    #[test]
-    fn test_create_manifest_request_empty_cursor() {
-        let (service, _db, _identity, peer_id) = create_test_env();
+        Ok(Some(OutgoingPostView {
+            post_id: post_id.to_string(),
+            viewer_peer_id: identity.peer_id,
+            viewed_at,
+            signature,
+        }))
+    }
 
-        let request = service
-            .create_manifest_request(HashMap::new(), 100)
-            .unwrap();
+    /// Record a view report from a contact for one of our posts.
+    ///
+    /// The time of the view is dropped unless precise view timing is
+    /// enabled. A first view also lands in the activity feed. Returns
+    /// `false` if this viewer was already counted.
+    pub fn process_post_view(
+        &self,
+        viewer_peer_id: &str,
+        post_id: &str,
+        viewed_at: i64,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
-        assert_eq!(request.requester_peer_id, peer_id);
-        assert!(request.cursor.is_empty());
-    }
+        let now = self.clock.timestamp();
+        let time_diff = (now - viewed_at).abs();
+        if time_diff > 300 {
+            return Err(AppError::Crypto(format!(
+                "View report timestamp too old or in future: {} seconds difference",
+                time_diff
+            )));
+        }
 
-    #[test]
-    fn test_create_manifest_request_requires_identity() {
-        let db = Arc::new(Database::in_memory().unwrap());
-        let identity_service = Arc::new(IdentityService::new(db.clone()));
-        let contacts_service = Arc::new(ContactsService::new(db.clone(), identity_service.clone()));
-        let permissions_service = Arc::new(PermissionsService::new(
-            db.clone(),
-            identity_service.clone(),
-        ));
+        let viewer_public_key = self
+            .contacts_service
+            .get_public_key(viewer_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Viewer not in contacts".to_string()))?;
 
-        let service =
-            ContentSyncService::new(db, identity_service, contacts_service, permissions_service);
+        let verifying_key = VerifyingKey::from_bytes(
+            viewer_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
 
-        let result = service.create_manifest_request(HashMap::new(), 50);
-        assert!(result.is_err());
-    }
+        let signable = SignablePostView {
+            post_id: post_id.to_string(),
+            author_peer_id: identity.peer_id.clone(),
+            viewer_peer_id: viewer_peer_id.to_string(),
+            viewed_at,
+        };
+        if !verify(&verifying_key, &signable, signature)? {
+            return Err(AppError::Crypto(
+                "Invalid view report signature".to_string(),
+            ));
+        }
 
-    #[test]
-    fn test_create_fetch_request() {
-        let (service, _db, _identity, peer_id) = create_test_env();
+        self.require_own_post(post_id, &identity.peer_id)?;
 
-        let request = service
-            .create_fetch_request("post-123".to_string(), true)
-            .unwrap();
+        let keep_timing = self.get_post_view_settings()?.record_view_times;
 
-        assert_eq!(request.requester_peer_id, peer_id);
-        assert_eq!(request.post_id, "post-123");
-        assert!(request.include_media);
-        assert!(!request.signature.is_empty());
+        let is_new = PostViewsRepository::record_view(
+            &self.db,
+            post_id,
+            viewer_peer_id,
+            keep_timing.then_some(viewed_at),
+            now,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if is_new {
+            ActivityRepository::record(&self.db, ActivityKind::View, viewer_peer_id, post_id, now)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        }
+        Ok(is_new)
     }
 
-    #[test]
-    fn test_create_fetch_request_no_media() {
-        let (service, _db, _identity, _peer_id) = create_test_env();
+    /// Get the view count and viewers of one of our posts
+    pub fn get_post_views(&self, post_id: &str) -> Result<PostViewSummary> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
-        let request = service
-            .create_fetch_request("post-456".to_string(), false)
-            .unwrap();
+        self.require_own_post(post_id, &identity.peer_id)?;
 
-        assert!(!request.include_media);
-    }
+        let viewers = PostViewsRepository::get_viewers(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        Ok(PostViewSummary {
+            post_id: post_id.to_string(),
+            view_count: viewers.len() as i64,
+            viewers,
+        })
+    }
+
+    /// Create an announcement of one of our new posts.
+    ///
+    /// Returns `None` for private posts, which never leave this device.
+    pub fn create_post_announcement(
+        &self,
+        post_id: &str,
+    ) -> Result<Option<OutgoingPostAnnouncement>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let post = PostsRepository::get_by_post_id(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .filter(|post| post.author_peer_id == identity.peer_id)
+            .ok_or_else(|| AppError::NotFound(format!("Post {} not found", post_id)))?;
+
+        if !post.visibility.is_shareable() {
+            return Ok(None);
+        }
+
+        let post = self
+            .build_post_summaries(std::slice::from_ref(&post))
+            .remove(0);
+        let timestamp = self.clock.timestamp();
+        let signable = SignablePostAnnouncement {
+            post: post.clone(),
+            timestamp,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(Some(OutgoingPostAnnouncement {
+            post,
+            timestamp,
+            signature,
+        }))
+    }
+
+    /// Pick the connected peers a new post should be pushed to: contacts we
+    /// have granted WallRead
+    pub fn announcement_recipients(&self, connected_peer_ids: &[String]) -> Result<Vec<String>> {
+        let mut recipients = Vec::new();
+        for peer_id in connected_peer_ids {
+            if self.contacts_service.get_public_key(peer_id)?.is_none() {
+                continue;
+            }
+            if self
+                .permissions_service
+                .peer_has_capability(peer_id, Capability::WallRead)?
+            {
+                recipients.push(peer_id.clone());
+            }
+        }
+        Ok(recipients)
+    }
+
+    /// Record that a post is being pushed to these recipients
+    pub fn record_pending_deliveries(&self, post_id: &str, recipients: &[String]) -> Result<()> {
+        PostDeliveriesRepository::record_pending(
+            &self.db,
+            post_id,
+            recipients,
+            self.clock.timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Record how a push of a post to one recipient ended
+    pub fn record_delivery_result(
+        &self,
+        post_id: &str,
+        recipient_peer_id: &str,
+        result: std::result::Result<(), String>,
+    ) -> Result<()> {
+        match result {
+            Ok(()) => PostDeliveriesRepository::mark_delivered(
+                &self.db,
+                post_id,
+                recipient_peer_id,
+                self.clock.timestamp(),
+            ),
+            Err(error) => {
+                PostDeliveriesRepository::mark_failed(&self.db, post_id, recipient_peer_id, &error)
+            }
+        }
+        .map(|_| ())
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Verify a post announcement from a contact.
+    ///
+    /// Returns `true` if we are missing the post or hold an older version,
+    /// in which case it should be fetched from the author.
+    pub fn process_post_announcement(
+        &self,
+        author_peer_id: &str,
+        post: &PostSummary,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let now = self.clock.timestamp();
+        let time_diff = (now - timestamp).abs();
+        if time_diff > 300 {
+            return Err(AppError::Crypto(format!(
+                "Announcement timestamp too old or in future: {} seconds difference",
+                time_diff
+            )));
+        }
+
+        if post.author_peer_id != author_peer_id {
+            return Err(AppError::PermissionDenied(
+                "Can only announce own posts".to_string(),
+            ));
+        }
+
+        let author_public_key = self
+            .contacts_service
+            .get_public_key(author_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Author not in contacts".to_string()))?;
+
+        let verifying_key = VerifyingKey::from_bytes(
+            author_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        let signable = SignablePostAnnouncement {
+            post: post.clone(),
+            timestamp,
+        };
+        if !verify(&verifying_key, &signable, signature)? {
+            return Err(AppError::Crypto(
+                "Invalid post announcement signature".to_string(),
+            ));
+        }
+
+        let existing = PostsRepository::get_by_post_id(&self.db, &post.post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(match existing {
+            Some(existing) => (existing.lamport_clock as u64) < post.lamport_clock,
+            None => true,
+        })
+    }
+
+    /// Get which contacts one of our pushed posts reached
+    pub fn get_post_delivery_status(&self, post_id: &str) -> Result<Vec<PostDelivery>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        self.require_own_post(post_id, &identity.peer_id)?;
+
+        PostDeliveriesRepository::get_for_post(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Views and deliveries are only tracked for our own posts
+    fn require_own_post(&self, post_id: &str, our_peer_id: &str) -> Result<()> {
+        match PostsRepository::get_by_post_id(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        {
+            Some(post) if post.author_peer_id == our_peer_id => Ok(()),
+            _ => Err(AppError::NotFound(format!("Post {} not found", post_id))),
+        }
+    }
+
+    /// Get stored sync cursor for a peer
+    pub fn get_sync_cursor(&self, peer_id: &str) -> Result<HashMap<String, u64>> {
+        self.db
+            .get_sync_cursor(peer_id, "posts")
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Forget the inbound sync cursors for every peer, so their next
+    /// manifests report everything they have. Used to catch up after
+    /// restoring an old backup; local posts are never touched.
+    pub fn reset_sync_cursors(&self) -> Result<usize> {
+        self.db
+            .clear_sync_cursors()
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+}
+
+/// Whether a verified incoming version of a post should replace the stored one.
+///
+/// The higher lamport clock wins. Two different versions signed at the same
+/// clock are broken by comparing signatures, so every peer converges on the
+/// same version no matter which source delivered it first.
+fn incoming_version_wins(existing: &crate::db::Post, lamport_clock: u64, signature: &[u8]) -> bool {
+    let existing_clock = existing.lamport_clock as u64;
+    lamport_clock > existing_clock
+        || (lamport_clock == existing_clock && signature > existing.signature.as_slice())
+}
+
+/// How long to wait before the next background sync. `jitter` is a random
+/// sample in `-1.0..=1.0` that shifts the wait by up to `AUTO_SYNC_JITTER`
+/// of the interval either way.
+pub fn auto_sync_delay(interval_secs: u64, jitter: f64) -> Duration {
+    let jitter = jitter.clamp(-1.0, 1.0) * AUTO_SYNC_JITTER;
+    Duration::from_secs_f64(interval_secs as f64 * (1.0 + jitter))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{ContactData, ContactsRepository};
+    use crate::models::CreateIdentityRequest;
+    use crate::services::{
+        ContactsService, IdentityService, PermissionsService, CURRENT_SIG_VERSION,
+    };
+    use std::sync::Arc;
+
+    fn create_test_env() -> (
+        ContentSyncService,
+        Arc<Database>,
+        Arc<IdentityService>,
+        String, // our peer_id
+    ) {
+        let db = Arc::new(Database::in_memory().unwrap());
+        let identity_service = Arc::new(IdentityService::new(db.clone()));
+        let contacts_service = Arc::new(ContactsService::new(db.clone(), identity_service.clone()));
+        let permissions_service = Arc::new(PermissionsService::new(
+            db.clone(),
+            identity_service.clone(),
+        ));
+
+        let info = identity_service
+            .create_identity(CreateIdentityRequest {
+                display_name: "Sync User".to_string(),
+                passphrase: "test-pass".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+
+        let service = ContentSyncService::new(
+            db.clone(),
+            identity_service.clone(),
+            contacts_service,
+            permissions_service,
+        );
+
+        (service, db, identity_service, info.peer_id)
+    }
+
+    #[test]
+    fn test_create_manifest_request() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let mut cursor = HashMap::new();
+        cursor.insert("12D3KooWPeer1".to_string(), 5u64);
+
+        let request = service
+            .create_manifest_request(cursor.clone(), 50, ManifestDetailLevel::Full)
+            .unwrap();
+
+        assert_eq!(request.requester_peer_id, peer_id);
+        assert_eq!(request.cursor, cursor);
+        assert_eq!(request.limit, 50);
+        assert!(!request.signature.is_empty());
+    }
+
+    #[test]
+    fn test_create_manifest_request_empty_cursor() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let request = service
+            .create_manifest_request(HashMap::new(), 100, ManifestDetailLevel::HeadersOnly)
+            .unwrap();
+
+        assert_eq!(request.requester_peer_id, peer_id);
+        assert!(request.cursor.is_empty());
+        assert_eq!(request.detail_level, ManifestDetailLevel::HeadersOnly);
+    }
+
+    #[test]
+    fn test_create_manifest_request_requires_identity() {
+        let db = Arc::new(Database::in_memory().unwrap());
+        let identity_service = Arc::new(IdentityService::new(db.clone()));
+        let contacts_service = Arc::new(ContactsService::new(db.clone(), identity_service.clone()));
+        let permissions_service = Arc::new(PermissionsService::new(
+            db.clone(),
+            identity_service.clone(),
+        ));
+
+        let service =
+            ContentSyncService::new(db, identity_service, contacts_service, permissions_service);
+
+        let result = service.create_manifest_request(HashMap::new(), 50, ManifestDetailLevel::Full);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_create_fetch_request() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let request = service
+            .create_fetch_request("post-123".to_string(), true)
+            .unwrap();
+
+        assert_eq!(request.requester_peer_id, peer_id);
+        assert_eq!(request.post_id, "post-123");
+        assert!(request.include_media);
+        assert!(!request.signature.is_empty());
+    }
+
+    #[test]
+    fn test_create_fetch_request_no_media() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+
+        let request = service
+            .create_fetch_request("post-456".to_string(), false)
+            .unwrap();
+
+        assert!(!request.include_media);
+    }
 
     #[test]
     fn test_get_sync_cursor_empty() {
@@ -664,6 +1252,25 @@ mod tests {
         assert!(cursor.is_empty());
     }
 
+    #[test]
+    fn test_reset_sync_cursors_keeps_local_posts() {
+        let (service, db, _identity, peer_id) = create_test_env();
+        insert_own_post(&db, &peer_id, "post-local", PostVisibility::Public, 7);
+
+        let mut cursor = HashMap::new();
+        cursor.insert("12D3KooWAuthor".to_string(), 12u64);
+        service.store_sync_cursor("12D3KooWPeer1", &cursor).unwrap();
+        assert!(!service.get_sync_cursor("12D3KooWPeer1").unwrap().is_empty());
+
+        assert!(service.reset_sync_cursors().unwrap() > 0);
+        assert!(service.get_sync_cursor("12D3KooWPeer1").unwrap().is_empty());
+
+        let local = PostsRepository::get_by_post_id(&db, "post-local")
+            .unwrap()
+            .unwrap();
+        assert_eq!(local.lamport_clock, 7);
+    }
+
     #[test]
     fn test_store_remote_post_new() {
         let (service, db, _identity_service, _peer_id) = create_test_env();
@@ -707,6 +1314,7 @@ mod tests {
                 lamport_clock: 1,
                 created_at: 1000,
                 signature: &signature,
+                sig_version: CURRENT_SIG_VERSION,
             })
             .unwrap();
 
@@ -745,6 +1353,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1000,
             signature: &vec![0u8; 64], // Invalid signature
+            sig_version: CURRENT_SIG_VERSION,
         });
 
         assert!(result.is_err());
@@ -763,6 +1372,7 @@ mod tests {
             lamport_clock: 1,
             created_at: 1000,
             signature: &vec![0u8; 64],
+            sig_version: CURRENT_SIG_VERSION,
         });
 
         assert!(result.is_err());
@@ -809,6 +1419,7 @@ mod tests {
                 lamport_clock: 1,
                 created_at: 1000,
                 signature: &sig1,
+                sig_version: CURRENT_SIG_VERSION,
             })
             .unwrap();
 
@@ -835,6 +1446,7 @@ mod tests {
                 lamport_clock: 2,
                 created_at: 1000,
                 signature: &sig2,
+                sig_version: CURRENT_SIG_VERSION,
             })
             .unwrap();
 
@@ -887,6 +1499,7 @@ mod tests {
                 lamport_clock: 5,
                 created_at: 1000,
                 signature: &sig1,
+                sig_version: CURRENT_SIG_VERSION,
             })
             .unwrap();
 
@@ -914,6 +1527,7 @@ mod tests {
                 lamport_clock: 3,
                 created_at: 1000,
                 signature: &sig2,
+                sig_version: CURRENT_SIG_VERSION,
             })
             .unwrap();
 
@@ -924,4 +1538,694 @@ mod tests {
         assert_eq!(post.content_text, Some("Newer version".to_string()));
         assert_eq!(post.lamport_clock, 5);
     }
+
+    /// Add a contact with a real key and grant them WallRead from us
+    fn add_wall_reader(
+        db: &Arc<Database>,
+        identity_service: &Arc<IdentityService>,
+    ) -> (ed25519_dalek::SigningKey, String) {
+        let (signing_key, verifying_key) =
+            crate::services::CryptoService::generate_ed25519_keypair();
+        let requester = "12D3KooWRequester".to_string();
+
+        ContactsRepository::add_contact(
+            db,
+            &ContactData {
+                peer_id: requester.clone(),
+                public_key: verifying_key.to_bytes().to_vec(),
+                x25519_public: vec![0u8; 32],
+                display_name: "Requester".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        PermissionsService::new(db.clone(), identity_service.clone())
+            .create_permission_grant(&requester, Capability::WallRead, None)
+            .unwrap();
+
+        (signing_key, requester)
+    }
+
+    fn insert_own_post(
+        db: &Database,
+        author_peer_id: &str,
+        post_id: &str,
+        visibility: PostVisibility,
+        lamport_clock: i64,
+    ) {
+        PostsRepository::insert_post(
+            db,
+            &PostData {
+                post_id: post_id.to_string(),
+                author_peer_id: author_peer_id.to_string(),
+                content_type: "text".to_string(),
+                content_text: Some(post_id.to_string()),
+                visibility,
+                lamport_clock,
+                created_at: 1000 + lamport_clock,
+                signature: vec![0u8; 64],
+                sig_version: CURRENT_SIG_VERSION,
+            },
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_manifest_excludes_private_posts() {
+        let (service, db, identity_service, peer_id) = create_test_env();
+        let (requester_key, requester) = add_wall_reader(&db, &identity_service);
+
+        insert_own_post(&db, &peer_id, "post-public", PostVisibility::Public, 1);
+        insert_own_post(&db, &peer_id, "post-private", PostVisibility::Private, 2);
+        insert_own_post(&db, &peer_id, "post-contacts", PostVisibility::Contacts, 3);
+        insert_own_post(&db, &peer_id, "post-private-2", PostVisibility::Private, 4);
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignableContentManifestRequest {
+            requester_peer_id: requester.clone(),
+            cursor: HashMap::new(),
+            limit: 50,
+            timestamp,
+            detail_level: ManifestDetailLevel::Full,
+        };
+        let signature = crate::services::sign(&requester_key, &signable).unwrap();
+
+        let response = service
+            .process_manifest_request(
+                &requester,
+                &HashMap::new(),
+                50,
+                ManifestDetailLevel::Full,
+                timestamp,
+                &signature,
+            )
+            .unwrap();
+
+        let offered: Vec<&str> = response.posts.iter().map(|p| p.post_id.as_str()).collect();
+        assert_eq!(offered, vec!["post-public", "post-contacts"]);
+        assert!(response.headers.is_empty());
+        assert!(!response.has_more);
+    }
+
+    #[test]
+    fn test_headers_only_manifest_omits_summaries() {
+        let (service, db, identity_service, peer_id) = create_test_env();
+        let (requester_key, requester) = add_wall_reader(&db, &identity_service);
+
+        insert_own_post(&db, &peer_id, "post-1", PostVisibility::Public, 1);
+        insert_own_post(&db, &peer_id, "post-2", PostVisibility::Private, 2);
+        insert_own_post(&db, &peer_id, "post-3", PostVisibility::Contacts, 3);
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignableContentManifestRequest {
+            requester_peer_id: requester.clone(),
+            cursor: HashMap::new(),
+            limit: 50,
+            timestamp,
+            detail_level: ManifestDetailLevel::HeadersOnly,
+        };
+        let signature = crate::services::sign(&requester_key, &signable).unwrap();
+
+        // A request signed as headers-only must not verify as a full request
+        assert!(service
+            .process_manifest_request(
+                &requester,
+                &HashMap::new(),
+                50,
+                ManifestDetailLevel::Full,
+                timestamp,
+                &signature,
+            )
+            .is_err());
+
+        let response = service
+            .process_manifest_request(
+                &requester,
+                &HashMap::new(),
+                50,
+                ManifestDetailLevel::HeadersOnly,
+                timestamp,
+                &signature,
+            )
+            .unwrap();
+
+        assert!(response.posts.is_empty());
+        let headers: Vec<(&str, u64)> = response
+            .headers
+            .iter()
+            .map(|h| (h.post_id.as_str(), h.lamport_clock))
+            .collect();
+        assert_eq!(headers, vec![("post-1", 1), ("post-3", 3)]);
+        assert_eq!(response.next_cursor.get(&peer_id), Some(&3));
+    }
+
+    #[test]
+    fn test_manifest_response_headers_select_missing_posts() {
+        let (service, db, identity_service, _peer_id) = create_test_env();
+        let (responder_key, responder) = add_wall_reader(&db, &identity_service);
+
+        insert_own_post(&db, &responder, "post-known", PostVisibility::Public, 5);
+
+        let headers = vec![
+            PostHeader {
+                post_id: "post-known".to_string(),
+                lamport_clock: 5,
+            },
+            PostHeader {
+                post_id: "post-new".to_string(),
+                lamport_clock: 6,
+            },
+        ];
+        let mut next_cursor = HashMap::new();
+        next_cursor.insert(responder.clone(), 6u64);
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignableContentManifestResponse {
+            responder_peer_id: responder.clone(),
+            posts: vec![],
+            has_more: false,
+            next_cursor: next_cursor.clone(),
+            timestamp,
+            headers: headers.clone(),
+        };
+        let signature = crate::services::sign(&responder_key, &signable).unwrap();
+
+        let to_fetch = service
+            .process_manifest_response(
+                &responder,
+                &[],
+                &headers,
+                false,
+                &next_cursor,
+                timestamp,
+                &signature,
+            )
+            .unwrap();
+
+        assert_eq!(to_fetch, vec!["post-new".to_string()]);
+    }
+
+    #[test]
+    fn test_unfetched_manifest_posts_are_resumed_until_stored() {
+        let (service, db, identity_service, _peer_id) = create_test_env();
+        let (responder_key, responder) = add_wall_reader(&db, &identity_service);
+
+        let headers = vec![PostHeader {
+            post_id: "post-new".to_string(),
+            lamport_clock: 1,
+        }];
+        let mut next_cursor = HashMap::new();
+        next_cursor.insert(responder.clone(), 1u64);
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignableContentManifestResponse {
+            responder_peer_id: responder.clone(),
+            posts: vec![],
+            has_more: false,
+            next_cursor: next_cursor.clone(),
+            timestamp,
+            headers: headers.clone(),
+        };
+        let signature = crate::services::sign(&responder_key, &signable).unwrap();
+        service
+            .process_manifest_response(
+                &responder,
+                &[],
+                &headers,
+                false,
+                &next_cursor,
+                timestamp,
+                &signature,
+            )
+            .unwrap();
+
+        // The cursor has moved past the post, but the fetch is still owed
+        assert_eq!(service.get_sync_cursor(&responder).unwrap(), next_cursor);
+        assert_eq!(
+            service.claim_fetch_intents(&responder).unwrap(),
+            vec!["post-new".to_string()]
+        );
+
+        let post = SignablePost {
+            post_id: "post-new".to_string(),
+            author_peer_id: responder.clone(),
+            content_type: "text".to_string(),
+            content_text: Some("Finally here".to_string()),
+            media_hashes: vec![],
+            visibility: "public".to_string(),
+            lamport_clock: 1,
+            created_at: 1000,
+        };
+        let post_signature = crate::services::sign(&responder_key, &post).unwrap();
+        service
+            .store_remote_post(&RemotePostParams {
+                post_id: "post-new",
+                author_peer_id: &responder,
+                content_type: "text",
+                content_text: Some("Finally here"),
+                visibility: "public",
+                lamport_clock: 1,
+                created_at: 1000,
+                signature: &post_signature,
+                sig_version: CURRENT_SIG_VERSION,
+            })
+            .unwrap();
+
+        assert!(service.claim_fetch_intents(&responder).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_fetch_never_serves_private_post() {
+        use ed25519_dalek::Signer;
+
+        let (service, db, identity_service, peer_id) = create_test_env();
+        let (requester_key, requester) = add_wall_reader(&db, &identity_service);
+
+        insert_own_post(&db, &peer_id, "post-public", PostVisibility::Public, 1);
+        insert_own_post(&db, &peer_id, "post-private", PostVisibility::Private, 2);
+
+        let fetch = |post_id: &str| {
+            let timestamp = chrono::Utc::now().timestamp();
+            let sign_data = format!("fetch:{}:{}:{}:{}", requester, post_id, false, timestamp);
+            let signature = requester_key.sign(sign_data.as_bytes()).to_bytes();
+            service.process_fetch_request(&requester, post_id, false, timestamp, &signature)
+        };
+
+        assert_eq!(fetch("post-public").unwrap().post_id, "post-public");
+        assert!(matches!(fetch("post-private"), Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_fetch_request_rejected_outside_skew_window() {
+        use crate::services::MockClock;
+        use ed25519_dalek::Signer;
+
+        let (service, db, identity_service, peer_id) = create_test_env();
+        let clock = Arc::new(MockClock::at_timestamp(1_700_000_000));
+        let service = service.with_clock(clock.clone());
+        let (requester_key, requester) = add_wall_reader(&db, &identity_service);
+        insert_own_post(&db, &peer_id, "post-public", PostVisibility::Public, 1);
+
+        let timestamp = 1_700_000_000;
+        let sign_data = format!(
+            "fetch:{}:{}:{}:{}",
+            requester, "post-public", false, timestamp
+        );
+        let signature = requester_key.sign(sign_data.as_bytes()).to_bytes();
+        let fetch = || {
+            service.process_fetch_request(&requester, "post-public", false, timestamp, &signature)
+        };
+
+        clock.advance(chrono::Duration::seconds(300));
+        assert!(fetch().is_ok());
+
+        clock.advance(chrono::Duration::seconds(1));
+        assert!(fetch().is_err());
+    }
+
+    #[test]
+    fn test_create_post_view_respects_opt_out() {
+        let (service, _db, _identity, peer_id) = create_test_env();
+
+        let report = service
+            .create_post_view("post-1", "12D3KooWAuthor")
+            .unwrap()
+            .unwrap();
+        assert_eq!(report.viewer_peer_id, peer_id);
+        assert!(!report.signature.is_empty());
+
+        service
+            .set_post_view_settings(PostViewSettings {
+                report_views: false,
+                record_view_times: false,
+            })
+            .unwrap();
+        assert!(service
+            .create_post_view("post-1", "12D3KooWAuthor")
+            .unwrap()
+            .is_none());
+    }
+
+    #[test]
+    fn test_process_post_view() {
+        let (service, db, _identity, peer_id) = create_test_env();
+
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO posts (post_id, author_peer_id, content_type, visibility, lamport_clock, created_at, updated_at, signature)
+                 VALUES ('my-post', ?, 'text', 'contacts', 1, 1000, 1000, X'00')",
+                [&peer_id],
+            )
+        })
+        .unwrap();
+
+        let (viewer_signing, viewer_verifying) =
+            crate::services::CryptoService::generate_ed25519_keypair();
+        let viewer_peer_id = "12D3KooWViewer".to_string();
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: viewer_peer_id.clone(),
+                public_key: viewer_verifying.to_bytes().to_vec(),
+                x25519_public: vec![0u8; 32],
+                display_name: "Viewer".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        let viewed_at = chrono::Utc::now().timestamp();
+        let signature = crate::services::sign(
+            &viewer_signing,
+            &SignablePostView {
+                post_id: "my-post".to_string(),
+                author_peer_id: peer_id.clone(),
+                viewer_peer_id: viewer_peer_id.clone(),
+                viewed_at,
+            },
+        )
+        .unwrap();
+
+        // Forged signature is rejected
+        assert!(service
+            .process_post_view(&viewer_peer_id, "my-post", viewed_at, &[0u8; 64])
+            .is_err());
+
+        assert!(service
+            .process_post_view(&viewer_peer_id, "my-post", viewed_at, &signature)
+            .unwrap());
+        // A repeat report from the same viewer isn't counted twice
+        assert!(!service
+            .process_post_view(&viewer_peer_id, "my-post", viewed_at, &signature)
+            .unwrap());
+
+        let views = service.get_post_views("my-post").unwrap();
+        assert_eq!(views.view_count, 1);
+        assert_eq!(views.viewers[0].peer_id, viewer_peer_id);
+        assert_eq!(views.viewers[0].display_name, Some("Viewer".to_string()));
+        // Precise timing is off by default
+        assert_eq!(views.viewers[0].viewed_at, None);
+
+        // Only the first report shows up as activity
+        let activity = ActivityRepository::get_activity(&db, 10, None).unwrap();
+        assert_eq!(activity.len(), 1);
+        assert_eq!(activity[0].kind, ActivityKind::View);
+        assert_eq!(activity[0].actor_peer_id, viewer_peer_id);
+        assert_eq!(activity[0].target_id, "my-post");
+
+        // Posts by other authors have no view data
+        assert!(service.get_post_views("someone-elses-post").is_err());
+    }
+
+    #[test]
+    fn test_process_post_view_keeps_timing_when_enabled() {
+        let (service, db, _identity, peer_id) = create_test_env();
+        service
+            .set_post_view_settings(PostViewSettings {
+                report_views: true,
+                record_view_times: true,
+            })
+            .unwrap();
+
+        db.with_connection(|conn| {
+            conn.execute(
+                "INSERT INTO posts (post_id, author_peer_id, content_type, visibility, lamport_clock, created_at, updated_at, signature)
+                 VALUES ('my-post', ?, 'text', 'contacts', 1, 1000, 1000, X'00')",
+                [&peer_id],
+            )
+        })
+        .unwrap();
+
+        let (viewer_signing, viewer_verifying) =
+            crate::services::CryptoService::generate_ed25519_keypair();
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: "12D3KooWViewer".to_string(),
+                public_key: viewer_verifying.to_bytes().to_vec(),
+                x25519_public: vec![0u8; 32],
+                display_name: "Viewer".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        let viewed_at = chrono::Utc::now().timestamp();
+        let signature = crate::services::sign(
+            &viewer_signing,
+            &SignablePostView {
+                post_id: "my-post".to_string(),
+                author_peer_id: peer_id,
+                viewer_peer_id: "12D3KooWViewer".to_string(),
+                viewed_at,
+            },
+        )
+        .unwrap();
+        service
+            .process_post_view("12D3KooWViewer", "my-post", viewed_at, &signature)
+            .unwrap();
+
+        let views = service.get_post_views("my-post").unwrap();
+        assert_eq!(views.viewers[0].viewed_at, Some(viewed_at));
+    }
+
+    #[test]
+    fn test_post_announcement_recipients_and_delivery_status() {
+        let (service, db, identity_service, peer_id) = create_test_env();
+        let (_reader_key, reader) = add_wall_reader(&db, &identity_service);
+
+        insert_own_post(&db, &peer_id, "post-contacts", PostVisibility::Contacts, 1);
+        insert_own_post(&db, &peer_id, "post-private", PostVisibility::Private, 2);
+
+        let announcement = service
+            .create_post_announcement("post-contacts")
+            .unwrap()
+            .unwrap();
+        assert_eq!(announcement.post.post_id, "post-contacts");
+        assert!(service
+            .create_post_announcement("post-private")
+            .unwrap()
+            .is_none());
+
+        // Strangers are never pushed to
+        let connected = vec![reader.clone(), "12D3KooWStranger".to_string()];
+        let recipients = service.announcement_recipients(&connected).unwrap();
+        assert_eq!(recipients, vec![reader.clone()]);
+
+        service
+            .record_pending_deliveries("post-contacts", &recipients)
+            .unwrap();
+        service
+            .record_delivery_result("post-contacts", &reader, Ok(()))
+            .unwrap();
+
+        let deliveries = service.get_post_delivery_status("post-contacts").unwrap();
+        assert_eq!(deliveries.len(), 1);
+        assert_eq!(deliveries[0].recipient_peer_id, reader);
+        assert_eq!(deliveries[0].status, crate::db::DeliveryStatus::Delivered);
+    }
+
+    #[test]
+    fn test_process_post_announcement() {
+        let (service, db, identity_service, _peer_id) = create_test_env();
+        let (author_key, author) = add_wall_reader(&db, &identity_service);
+
+        let post = PostSummary {
+            post_id: "their-post".to_string(),
+            author_peer_id: author.clone(),
+            lamport_clock: 3,
+            content_type: "text".to_string(),
+            has_media: false,
+            media_hashes: Vec::new(),
+            created_at: 1003,
+        };
+        let timestamp = chrono::Utc::now().timestamp();
+        let signature = crate::services::sign(
+            &author_key,
+            &SignablePostAnnouncement {
+                post: post.clone(),
+                timestamp,
+            },
+        )
+        .unwrap();
+
+        assert!(service
+            .process_post_announcement(&author, &post, timestamp, &[0u8; 64])
+            .is_err());
+        // Only the author may announce their post
+        assert!(service
+            .process_post_announcement("12D3KooWOther", &post, timestamp, &signature)
+            .is_err());
+
+        assert!(service
+            .process_post_announcement(&author, &post, timestamp, &signature)
+            .unwrap());
+
+        // Nothing to fetch once we hold this version
+        insert_own_post(&db, &author, "their-post", PostVisibility::Contacts, 3);
+        assert!(!service
+            .process_post_announcement(&author, &post, timestamp, &signature)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_store_remote_post_rejects_other_authors_post_id() {
+        let (service, db, _identity, _peer_id) = create_test_env();
+
+        let mut authors = Vec::new();
+        for name in ["Alice", "Mallory"] {
+            let (signing, verifying) = crate::services::CryptoService::generate_ed25519_keypair();
+            let peer_id = format!("12D3KooW{}", name);
+            ContactsRepository::add_contact(
+                &db,
+                &ContactData {
+                    peer_id: peer_id.clone(),
+                    public_key: verifying.to_bytes().to_vec(),
+                    x25519_public: vec![0u8; 32],
+                    display_name: name.to_string(),
+                    avatar_hash: None,
+                    bio: None,
+                },
+            )
+            .unwrap();
+            authors.push((signing, peer_id));
+        }
+
+        let store = |signing: &ed25519_dalek::SigningKey, author: &str, text: &str, clock: u64| {
+            let signable = crate::services::SignablePost {
+                post_id: "shared-id".to_string(),
+                author_peer_id: author.to_string(),
+                content_type: "text".to_string(),
+                content_text: Some(text.to_string()),
+                media_hashes: vec![],
+                visibility: "public".to_string(),
+                lamport_clock: clock,
+                created_at: 1000,
+            };
+            let sig = crate::services::sign(signing, &signable).unwrap();
+            service.store_remote_post(&RemotePostParams {
+                post_id: "shared-id",
+                author_peer_id: author,
+                content_type: "text",
+                content_text: Some(text),
+                visibility: "public",
+                lamport_clock: clock,
+                created_at: 1000,
+                signature: &sig,
+                sig_version: CURRENT_SIG_VERSION,
+            })
+        };
+
+        store(&authors[0].0, &authors[0].1, "Alice's post", 1).unwrap();
+
+        // A validly signed post from someone else cannot take over the post_id
+        let result = store(&authors[1].0, &authors[1].1, "Hijacked", 5);
+        assert!(matches!(result, Err(AppError::Validation(_))));
+
+        let post = PostsRepository::get_by_post_id(&db, "shared-id")
+            .unwrap()
+            .unwrap();
+        assert_eq!(post.author_peer_id, authors[0].1);
+        assert_eq!(post.content_text, Some("Alice's post".to_string()));
+    }
+
+    #[test]
+    fn test_store_remote_post_equal_clock_converges() {
+        let (peer_signing, peer_verifying) =
+            crate::services::CryptoService::generate_ed25519_keypair();
+        let author = "12D3KooWRemotePeer".to_string();
+        let version = |text: &str| {
+            let signable = crate::services::SignablePost {
+                post_id: "remote-post-1".to_string(),
+                author_peer_id: author.clone(),
+                content_type: "text".to_string(),
+                content_text: Some(text.to_string()),
+                media_hashes: vec![],
+                visibility: "public".to_string(),
+                lamport_clock: 2,
+                created_at: 1000,
+            };
+            let signature = crate::services::sign(&peer_signing, &signable).unwrap();
+            (text.to_string(), signature)
+        };
+        let versions = [version("From device A"), version("From device B")];
+
+        // Both orders must end with the same stored version
+        let mut results = Vec::new();
+        for order in [[0, 1], [1, 0]] {
+            let (service, db, _identity, _peer_id) = create_test_env();
+            ContactsRepository::add_contact(
+                &db,
+                &ContactData {
+                    peer_id: author.clone(),
+                    public_key: peer_verifying.to_bytes().to_vec(),
+                    x25519_public: vec![0u8; 32],
+                    display_name: "Remote Peer".to_string(),
+                    avatar_hash: None,
+                    bio: None,
+                },
+            )
+            .unwrap();
+
+            for index in order {
+                let (text, signature) = &versions[index];
+                service
+                    .store_remote_post(&RemotePostParams {
+                        post_id: "remote-post-1",
+                        author_peer_id: &author,
+                        content_type: "text",
+                        content_text: Some(text),
+                        visibility: "public",
+                        lamport_clock: 2,
+                        created_at: 1000,
+                        signature,
+                        sig_version: CURRENT_SIG_VERSION,
+                    })
+                    .unwrap();
+            }
+            let post = PostsRepository::get_by_post_id(&db, "remote-post-1")
+                .unwrap()
+                .unwrap();
+            results.push((post.content_text, post.signature));
+        }
+
+        assert_eq!(results[0], results[1]);
+    }
+
+    #[test]
+    fn test_auto_sync_interval_setting() {
+        let (service, _db, _identity, _peer_id) = create_test_env();
+
+        assert_eq!(
+            service.get_auto_sync_interval().unwrap(),
+            DEFAULT_AUTO_SYNC_INTERVAL_SECS
+        );
+        service.set_auto_sync_interval(600).unwrap();
+        assert_eq!(service.get_auto_sync_interval().unwrap(), 600);
+        service.set_auto_sync_interval(0).unwrap();
+        assert_eq!(service.get_auto_sync_interval().unwrap(), 0);
+
+        for invalid in [
+            1,
+            MIN_AUTO_SYNC_INTERVAL_SECS - 1,
+            MAX_AUTO_SYNC_INTERVAL_SECS + 1,
+        ] {
+            assert!(matches!(
+                service.set_auto_sync_interval(invalid),
+                Err(AppError::Validation(_))
+            ));
+        }
+        assert_eq!(service.get_auto_sync_interval().unwrap(), 0);
+    }
+
+    #[test]
+    fn test_auto_sync_delay_jitter_is_bounded() {
+        assert_eq!(auto_sync_delay(300, 0.0), Duration::from_secs(300));
+        assert_eq!(auto_sync_delay(300, -1.0), Duration::from_secs(240));
+        assert_eq!(auto_sync_delay(300, 1.0), Duration::from_secs(360));
+        // Out-of-range samples are clamped
+        assert_eq!(auto_sync_delay(300, 5.0), Duration::from_secs(360));
+    }
 }
@@ -1,16 +1,188 @@
 use crate::error::{AppError, Result};
-use crate::models::EncryptedKeys;
+use crate::models::{EncryptedKeys, KdfProfile};
 
 use aes_gcm::{
     aead::{Aead, KeyInit, OsRng},
     Aes256Gcm, Nonce,
 };
-use argon2::{password_hash::SaltString, Argon2, PasswordHasher};
+use argon2::{password_hash::SaltString, Algorithm, Argon2, Params, PasswordHasher, Version};
+use chacha20poly1305::ChaCha20Poly1305;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use rand::RngCore;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 use x25519_dalek::{PublicKey as X25519Public, StaticSecret as X25519Secret};
 
+/// Length of the random per-message nonce salt
+pub const NONCE_SALT_LEN: usize = 12;
+
+/// AEAD cipher that message content is encrypted with
+///
+/// Messages carry the cipher's identifier so the algorithm can change
+/// without breaking ones already sent or stored. Both ciphers take the same
+/// 32-byte key and 96-bit nonce, so key and nonce derivation are shared.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MessageCipher {
+    /// Used by every client that predates cipher identifiers
+    #[default]
+    #[serde(rename = "aes-256-gcm")]
+    Aes256Gcm,
+    #[serde(rename = "chacha20-poly1305")]
+    ChaCha20Poly1305,
+}
+
+impl MessageCipher {
+    /// Every cipher this build can decrypt, in order of preference
+    pub const SUPPORTED: [MessageCipher; 2] =
+        [MessageCipher::Aes256Gcm, MessageCipher::ChaCha20Poly1305];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MessageCipher::Aes256Gcm => "aes-256-gcm",
+            MessageCipher::ChaCha20Poly1305 => "chacha20-poly1305",
+        }
+    }
+
+    /// Identifiers of every supported cipher, as announced to peers
+    pub fn supported_identifiers() -> Vec<String> {
+        Self::SUPPORTED
+            .iter()
+            .map(|cipher| cipher.as_str().to_string())
+            .collect()
+    }
+}
+
+impl std::str::FromStr for MessageCipher {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "aes-256-gcm" => Ok(MessageCipher::Aes256Gcm),
+            "chacha20-poly1305" => Ok(MessageCipher::ChaCha20Poly1305),
+            _ => Err(format!("Unknown message cipher: {}", s)),
+        }
+    }
+}
+
+/// Serde default for cipher identifier fields, for peers that predate them
+pub fn legacy_message_cipher() -> String {
+    MessageCipher::Aes256Gcm.as_str().to_string()
+}
+
+/// Whether a cipher identifier can be left out of signed bytes
+pub fn is_legacy_message_cipher(cipher: &str) -> bool {
+    cipher == MessageCipher::Aes256Gcm.as_str()
+}
+
+/// First byte of encrypted keys that carry their own KDF parameters.
+///
+/// Legacy blobs start with the salt length, which is never above 64.
+const KDF_HEADER_MARKER: u8 = 0xFF;
+
+/// Marker byte + memory, iterations and parallelism as little-endian u32s
+const KDF_HEADER_LEN: usize = 13;
+
+/// Argon2id parameters used to derive the identity key encryption key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KdfParams {
+    /// Memory cost in KiB
+    pub memory_kib: u32,
+    /// Number of passes over memory
+    pub iterations: u32,
+    /// Degree of parallelism (lanes)
+    pub parallelism: u32,
+}
+
+impl KdfParams {
+    /// Bounds accepted when encrypting and when reading stored parameters, so a
+    /// tampered header cannot make unlocking hang or exhaust memory.
+    pub const MIN_MEMORY_KIB: u32 = 8 * 1024;
+    pub const MAX_MEMORY_KIB: u32 = 1024 * 1024;
+    pub const MAX_ITERATIONS: u32 = 10;
+    pub const MAX_PARALLELISM: u32 = 16;
+
+    /// Parameters for a strength profile
+    pub fn for_profile(profile: KdfProfile) -> Self {
+        match profile {
+            KdfProfile::Light => Self {
+                memory_kib: 12 * 1024,
+                iterations: 3,
+                parallelism: 1,
+            },
+            KdfProfile::Standard => Self::default(),
+            KdfProfile::Strong => Self {
+                memory_kib: 64 * 1024,
+                iterations: 3,
+                parallelism: 4,
+            },
+        }
+    }
+
+    /// Check the parameters are within the accepted bounds
+    pub fn validate(&self) -> Result<()> {
+        if !(Self::MIN_MEMORY_KIB..=Self::MAX_MEMORY_KIB).contains(&self.memory_kib) {
+            return Err(AppError::Validation(format!(
+                "KDF memory must be between {} and {} KiB",
+                Self::MIN_MEMORY_KIB,
+                Self::MAX_MEMORY_KIB
+            )));
+        }
+        if !(1..=Self::MAX_ITERATIONS).contains(&self.iterations) {
+            return Err(AppError::Validation(format!(
+                "KDF iterations must be between 1 and {}",
+                Self::MAX_ITERATIONS
+            )));
+        }
+        if !(1..=Self::MAX_PARALLELISM).contains(&self.parallelism) {
+            return Err(AppError::Validation(format!(
+                "KDF parallelism must be between 1 and {}",
+                Self::MAX_PARALLELISM
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Default for KdfParams {
+    /// The argon2 crate defaults, used for every identity created before
+    /// parameters were configurable
+    fn default() -> Self {
+        Self {
+            memory_kib: Params::DEFAULT_M_COST,
+            iterations: Params::DEFAULT_T_COST,
+            parallelism: Params::DEFAULT_P_COST,
+        }
+    }
+}
+
+/// Derive the 32-byte AES key for encrypted identity keys
+fn derive_key_bytes(
+    passphrase: &str,
+    salt: &SaltString,
+    params: &KdfParams,
+) -> std::result::Result<[u8; 32], String> {
+    let argon2_params = Params::new(
+        params.memory_kib,
+        params.iterations,
+        params.parallelism,
+        None,
+    )
+    .map_err(|e| e.to_string())?;
+    let argon2 = Argon2::new(Algorithm::Argon2id, Version::V0x13, argon2_params);
+
+    let password_hash = argon2
+        .hash_password(passphrase.as_bytes(), salt)
+        .map_err(|e| e.to_string())?;
+    let hash_bytes = password_hash
+        .hash
+        .ok_or_else(|| "Failed to get hash bytes".to_string())?;
+
+    // Use first 32 bytes of hash as AES key
+    hash_bytes.as_bytes()[..32]
+        .try_into()
+        .map_err(|_| "Invalid key length".to_string())
+}
+
 /// Cryptographic operations service
 pub struct CryptoService;
 
@@ -60,6 +232,26 @@ impl CryptoService {
         Ok(peer_id.to_string())
     }
 
+    /// Recover the Ed25519 public key embedded in a libp2p peer ID. Peer IDs
+    /// of Ed25519 keys inline the key itself, so no lookup is needed.
+    pub fn verifying_key_from_peer_id(peer_id: &str) -> Result<VerifyingKey> {
+        let peer_id: libp2p::PeerId = peer_id
+            .parse()
+            .map_err(|e| AppError::Crypto(format!("Invalid peer ID: {}", e)))?;
+        let multihash = peer_id.as_ref();
+        if multihash.code() != 0 {
+            return Err(AppError::Crypto(
+                "Peer ID does not embed its public key".to_string(),
+            ));
+        }
+        let public_key = libp2p::identity::PublicKey::try_decode_protobuf(multihash.digest())
+            .map_err(|e| AppError::Crypto(format!("Invalid public key in peer ID: {}", e)))?
+            .try_into_ed25519()
+            .map_err(|_| AppError::Crypto("Peer ID key is not Ed25519".to_string()))?;
+        VerifyingKey::from_bytes(&public_key.to_bytes())
+            .map_err(|e| AppError::Crypto(format!("Invalid Ed25519 public key: {}", e)))
+    }
+
     /// Derive a peer ID from an Ed25519 public key (DEPRECATED - use derive_peer_id_from_signing_key)
     /// This uses a simplified hash-based approach that is NOT compatible with libp2p
     #[deprecated(note = "Use derive_peer_id_from_signing_key instead for libp2p compatibility")]
@@ -73,29 +265,37 @@ impl CryptoService {
         format!("12D3KooW{}", hex::encode(&hash[..16]))
     }
 
-    /// Encrypt private keys using a passphrase
+    /// Encrypt private keys using a passphrase with the standard KDF parameters
     pub fn encrypt_keys(
         ed25519_private: &[u8],
         x25519_private: &[u8],
         passphrase: &str,
     ) -> Result<Vec<u8>> {
+        Self::encrypt_keys_with_params(
+            ed25519_private,
+            x25519_private,
+            passphrase,
+            &KdfParams::default(),
+        )
+    }
+
+    /// Encrypt private keys using a passphrase and the given Argon2id parameters.
+    ///
+    /// The parameters are stored in a header in front of the encrypted keys so
+    /// `decrypt_keys` can derive the same key later.
+    pub fn encrypt_keys_with_params(
+        ed25519_private: &[u8],
+        x25519_private: &[u8],
+        passphrase: &str,
+        params: &KdfParams,
+    ) -> Result<Vec<u8>> {
+        params.validate()?;
+
         // Derive encryption key from passphrase using Argon2id
         let salt = SaltString::generate(&mut OsRng);
-        let argon2 = Argon2::default();
-
-        let password_hash = argon2
-            .hash_password(passphrase.as_bytes(), &salt)
+        let key_bytes = derive_key_bytes(passphrase, &salt, params)
             .map_err(|e| AppError::Crypto(format!("Failed to hash passphrase: {}", e)))?;
 
-        let hash_bytes = password_hash
-            .hash
-            .ok_or_else(|| AppError::Crypto("Failed to get hash bytes".to_string()))?;
-
-        // Use first 32 bytes of hash as AES key
-        let key_bytes: [u8; 32] = hash_bytes.as_bytes()[..32]
-            .try_into()
-            .map_err(|_| AppError::Crypto("Invalid key length".to_string()))?;
-
         let cipher = Aes256Gcm::new_from_slice(&key_bytes)
             .map_err(|e| AppError::CryptoEncryption(format!("Failed to create cipher: {}", e)))?;
 
@@ -117,9 +317,15 @@ impl CryptoService {
             .encrypt(nonce, plaintext.as_ref())
             .map_err(|e| AppError::CryptoEncryption(format!("Encryption failed: {}", e)))?;
 
-        // Combine: salt (22 bytes as string) + nonce (12 bytes) + ciphertext
+        // Combine: KDF header (13 bytes) + salt_len (1 byte) + salt (22 bytes as string)
+        // + nonce (12 bytes) + ciphertext
         let salt_bytes = salt.as_str().as_bytes();
-        let mut result = Vec::with_capacity(salt_bytes.len() + 1 + 12 + ciphertext.len());
+        let mut result =
+            Vec::with_capacity(KDF_HEADER_LEN + 1 + salt_bytes.len() + 12 + ciphertext.len());
+        result.push(KDF_HEADER_MARKER);
+        result.extend_from_slice(&params.memory_kib.to_le_bytes());
+        result.extend_from_slice(&params.iterations.to_le_bytes());
+        result.extend_from_slice(&params.parallelism.to_le_bytes());
         result.push(salt_bytes.len() as u8);
         result.extend_from_slice(salt_bytes);
         result.extend_from_slice(&nonce_bytes);
@@ -128,6 +334,44 @@ impl CryptoService {
         Ok(result)
     }
 
+    /// Read the KDF parameters stored with encrypted keys.
+    ///
+    /// Keys encrypted before parameters were stored used the standard ones.
+    pub fn key_kdf_params(encrypted: &[u8]) -> Result<KdfParams> {
+        Self::split_kdf_header(encrypted).map(|(params, _)| params)
+    }
+
+    /// Split encrypted keys into their KDF parameters and the legacy-format body
+    fn split_kdf_header(encrypted: &[u8]) -> Result<(KdfParams, &[u8])> {
+        if encrypted.first() != Some(&KDF_HEADER_MARKER) {
+            return Ok((KdfParams::default(), encrypted));
+        }
+        if encrypted.len() < KDF_HEADER_LEN {
+            return Err(AppError::CryptoDecryption(
+                "Invalid KDF parameter header".to_string(),
+            ));
+        }
+
+        let read_u32 = |offset: usize| {
+            u32::from_le_bytes([
+                encrypted[offset],
+                encrypted[offset + 1],
+                encrypted[offset + 2],
+                encrypted[offset + 3],
+            ])
+        };
+        let params = KdfParams {
+            memory_kib: read_u32(1),
+            iterations: read_u32(5),
+            parallelism: read_u32(9),
+        };
+        params
+            .validate()
+            .map_err(|e| AppError::CryptoDecryption(format!("Stored KDF parameters: {}", e)))?;
+
+        Ok((params, &encrypted[KDF_HEADER_LEN..]))
+    }
+
     /// Decrypt private keys using a passphrase
     pub fn decrypt_keys(encrypted: &[u8], passphrase: &str) -> Result<EncryptedKeys> {
         if encrypted.is_empty() {
@@ -136,6 +380,13 @@ impl CryptoService {
             ));
         }
 
+        let (params, encrypted) = Self::split_kdf_header(encrypted)?;
+        if encrypted.is_empty() {
+            return Err(AppError::CryptoDecryption(
+                "Invalid encrypted data format".to_string(),
+            ));
+        }
+
         // Parse: salt_len (1 byte) + salt + nonce (12 bytes) + ciphertext
         let salt_len = encrypted[0] as usize;
         if encrypted.len() < 1 + salt_len + 12 {
@@ -158,19 +409,9 @@ impl CryptoService {
         let ciphertext = &encrypted[nonce_start + 12..];
 
         // Derive key from passphrase
-        let argon2 = Argon2::default();
-        let password_hash = argon2
-            .hash_password(passphrase.as_bytes(), &salt)
+        let key_bytes = derive_key_bytes(passphrase, &salt, &params)
             .map_err(|e| AppError::CryptoDecryption(format!("Failed to hash passphrase: {}", e)))?;
 
-        let hash_bytes = password_hash
-            .hash
-            .ok_or_else(|| AppError::CryptoDecryption("Failed to get hash bytes".to_string()))?;
-
-        let key_bytes: [u8; 32] = hash_bytes.as_bytes()[..32]
-            .try_into()
-            .map_err(|_| AppError::CryptoDecryption("Invalid key length".to_string()))?;
-
         // Decrypt
         let cipher = Aes256Gcm::new_from_slice(&key_bytes)
             .map_err(|e| AppError::CryptoDecryption(format!("Failed to create cipher: {}", e)))?;
@@ -321,6 +562,28 @@ impl CryptoService {
         nonce
     }
 
+    /// Generate a random per-message nonce salt
+    pub fn generate_nonce_salt() -> [u8; NONCE_SALT_LEN] {
+        let mut salt = [0u8; NONCE_SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+        salt
+    }
+
+    /// Derive a nonce from a random per-message salt and the send counter
+    ///
+    /// The counter is XORed into the last 8 bytes of the salt, so two messages
+    /// only share a nonce if both salt and counter collide. Unlike
+    /// `nonce_from_counter`, this stays safe if the counter ever resets (e.g. a
+    /// fresh install restoring an old key) and when both peers of a
+    /// conversation use the same counter value under the shared key.
+    pub fn nonce_from_salted_counter(salt: &[u8; NONCE_SALT_LEN], counter: u64) -> [u8; 12] {
+        let mut nonce = *salt;
+        for (byte, counter_byte) in nonce[4..12].iter_mut().zip(counter.to_be_bytes()) {
+            *byte ^= counter_byte;
+        }
+        nonce
+    }
+
     /// Encrypt a message using AES-256-GCM with a counter-based nonce
     ///
     /// IMPORTANT: The counter MUST be unique for each message in a conversation.
@@ -329,12 +592,23 @@ impl CryptoService {
         key: &[u8; 32],
         plaintext: &[u8],
         counter: u64,
+    ) -> Result<Vec<u8>> {
+        Self::encrypt_message_with_nonce(key, plaintext, &Self::nonce_from_counter(counter))
+    }
+
+    /// Encrypt a message using AES-256-GCM with an explicit 96-bit nonce
+    ///
+    /// Only the ciphertext is returned; the caller transmits whatever the nonce
+    /// was derived from (counter and optional salt) alongside it.
+    pub fn encrypt_message_with_nonce(
+        key: &[u8; 32],
+        plaintext: &[u8],
+        nonce_bytes: &[u8; 12],
     ) -> Result<Vec<u8>> {
         let cipher = Aes256Gcm::new_from_slice(key)
             .map_err(|e| AppError::CryptoEncryption(format!("Failed to create cipher: {}", e)))?;
 
-        let nonce_bytes = Self::nonce_from_counter(counter);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+        let nonce = Nonce::from_slice(nonce_bytes);
 
         let ciphertext = cipher
             .encrypt(nonce, plaintext)
@@ -352,12 +626,20 @@ impl CryptoService {
         key: &[u8; 32],
         ciphertext: &[u8],
         counter: u64,
+    ) -> Result<Vec<u8>> {
+        Self::decrypt_message_with_nonce(key, ciphertext, &Self::nonce_from_counter(counter))
+    }
+
+    /// Decrypt a message using AES-256-GCM with an explicit 96-bit nonce
+    pub fn decrypt_message_with_nonce(
+        key: &[u8; 32],
+        ciphertext: &[u8],
+        nonce_bytes: &[u8; 12],
     ) -> Result<Vec<u8>> {
         let cipher = Aes256Gcm::new_from_slice(key)
             .map_err(|e| AppError::CryptoDecryption(format!("Failed to create cipher: {}", e)))?;
 
-        let nonce_bytes = Self::nonce_from_counter(counter);
-        let nonce = Nonce::from_slice(&nonce_bytes);
+        let nonce = Nonce::from_slice(nonce_bytes);
 
         let plaintext = cipher
             .decrypt(nonce, ciphertext)
@@ -365,6 +647,50 @@ impl CryptoService {
 
         Ok(plaintext)
     }
+
+    /// Encrypt a message with the given cipher and an explicit 96-bit nonce
+    pub fn encrypt_with_cipher(
+        cipher: MessageCipher,
+        key: &[u8; 32],
+        plaintext: &[u8],
+        nonce_bytes: &[u8; 12],
+    ) -> Result<Vec<u8>> {
+        match cipher {
+            MessageCipher::Aes256Gcm => {
+                Self::encrypt_message_with_nonce(key, plaintext, nonce_bytes)
+            }
+            MessageCipher::ChaCha20Poly1305 => {
+                let cipher = ChaCha20Poly1305::new_from_slice(key).map_err(|e| {
+                    AppError::CryptoEncryption(format!("Failed to create cipher: {}", e))
+                })?;
+                cipher
+                    .encrypt(chacha20poly1305::Nonce::from_slice(nonce_bytes), plaintext)
+                    .map_err(|e| AppError::CryptoEncryption(format!("Encryption failed: {}", e)))
+            }
+        }
+    }
+
+    /// Decrypt a message with the given cipher and an explicit 96-bit nonce
+    pub fn decrypt_with_cipher(
+        cipher: MessageCipher,
+        key: &[u8; 32],
+        ciphertext: &[u8],
+        nonce_bytes: &[u8; 12],
+    ) -> Result<Vec<u8>> {
+        match cipher {
+            MessageCipher::Aes256Gcm => {
+                Self::decrypt_message_with_nonce(key, ciphertext, nonce_bytes)
+            }
+            MessageCipher::ChaCha20Poly1305 => {
+                let cipher = ChaCha20Poly1305::new_from_slice(key).map_err(|e| {
+                    AppError::CryptoDecryption(format!("Failed to create cipher: {}", e))
+                })?;
+                cipher
+                    .decrypt(chacha20poly1305::Nonce::from_slice(nonce_bytes), ciphertext)
+                    .map_err(|_| AppError::CryptoDecryption("Decryption failed".to_string()))
+            }
+        }
+    }
 }
 
 #[cfg(test)]
@@ -427,6 +753,56 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_key_encryption_with_kdf_params() {
+        let params = KdfParams::for_profile(KdfProfile::Light);
+        let encrypted =
+            CryptoService::encrypt_keys_with_params(&[1u8; 32], &[2u8; 32], "passphrase", &params)
+                .unwrap();
+
+        assert_eq!(CryptoService::key_kdf_params(&encrypted).unwrap(), params);
+        let decrypted = CryptoService::decrypt_keys(&encrypted, "passphrase").unwrap();
+        assert_eq!(decrypted.ed25519_private, vec![1u8; 32]);
+        assert_eq!(decrypted.x25519_private, vec![2u8; 32]);
+    }
+
+    #[test]
+    fn test_key_decryption_legacy_format() {
+        // Keys written before parameters were stored have no header and used
+        // the argon2 defaults
+        let encrypted = CryptoService::encrypt_keys(&[1u8; 32], &[2u8; 32], "passphrase").unwrap();
+        let legacy = &encrypted[KDF_HEADER_LEN..];
+
+        assert_eq!(
+            CryptoService::key_kdf_params(legacy).unwrap(),
+            KdfParams::default()
+        );
+        let decrypted = CryptoService::decrypt_keys(legacy, "passphrase").unwrap();
+        assert_eq!(decrypted.ed25519_private, vec![1u8; 32]);
+    }
+
+    #[test]
+    fn test_key_decryption_rejects_out_of_range_params() {
+        let params = KdfParams::for_profile(KdfProfile::Light);
+        let mut encrypted =
+            CryptoService::encrypt_keys_with_params(&[1u8; 32], &[2u8; 32], "passphrase", &params)
+                .unwrap();
+
+        // Tamper with the stored memory cost
+        encrypted[1..5].copy_from_slice(&u32::MAX.to_le_bytes());
+        let err = CryptoService::decrypt_keys(&encrypted, "passphrase").unwrap_err();
+        assert!(matches!(err, AppError::CryptoDecryption(_)));
+
+        let invalid = KdfParams {
+            iterations: 0,
+            ..params
+        };
+        assert!(
+            CryptoService::encrypt_keys_with_params(&[1u8; 32], &[2u8; 32], "pass", &invalid)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_message_encryption() {
         let key = [0u8; 32];
@@ -496,6 +872,11 @@ mod tests {
 
         // Also verify it starts with the expected libp2p prefix
         assert!(from_verifying.starts_with("12D3KooW"));
+
+        // And that the key can be read back out of the peer ID
+        let recovered = CryptoService::verifying_key_from_peer_id(&from_verifying).unwrap();
+        assert_eq!(recovered, verifying_key);
+        assert!(CryptoService::verifying_key_from_peer_id("12D3KooWAlice").is_err());
         assert!(
             from_verifying.len() >= 50,
             "Peer ID should be a full libp2p PeerId: {}",
@@ -568,6 +949,84 @@ mod tests {
         assert_eq!(decrypted2, message);
     }
 
+    #[test]
+    fn test_salted_nonce_differs_for_same_counter() {
+        let salt_a = CryptoService::generate_nonce_salt();
+        let salt_b = CryptoService::generate_nonce_salt();
+        assert_ne!(salt_a, salt_b);
+
+        // A reset counter no longer yields the same nonce
+        assert_ne!(
+            CryptoService::nonce_from_salted_counter(&salt_a, 1),
+            CryptoService::nonce_from_salted_counter(&salt_b, 1)
+        );
+        // Different counters under one salt stay distinct
+        assert_ne!(
+            CryptoService::nonce_from_salted_counter(&salt_a, 1),
+            CryptoService::nonce_from_salted_counter(&salt_a, 2)
+        );
+        // An all-zero salt degenerates to the legacy counter nonce
+        assert_eq!(
+            CryptoService::nonce_from_salted_counter(&[0u8; NONCE_SALT_LEN], 7),
+            CryptoService::nonce_from_counter(7)
+        );
+    }
+
+    #[test]
+    fn test_salted_nonce_encryption_roundtrip() {
+        let key = [42u8; 32];
+        let salt = CryptoService::generate_nonce_salt();
+        let nonce = CryptoService::nonce_from_salted_counter(&salt, 3);
+
+        let ciphertext =
+            CryptoService::encrypt_message_with_nonce(&key, b"salted", &nonce).unwrap();
+        let decrypted =
+            CryptoService::decrypt_message_with_nonce(&key, &ciphertext, &nonce).unwrap();
+        assert_eq!(decrypted, b"salted");
+
+        // The counter alone is not enough to decrypt
+        assert!(CryptoService::decrypt_message_with_counter(&key, &ciphertext, 3).is_err());
+    }
+
+    #[test]
+    fn test_cipher_roundtrip_and_separation() {
+        let key = [42u8; 32];
+        let nonce = CryptoService::nonce_from_counter(9);
+
+        for cipher in MessageCipher::SUPPORTED {
+            let ciphertext =
+                CryptoService::encrypt_with_cipher(cipher, &key, b"agile", &nonce).unwrap();
+            let decrypted =
+                CryptoService::decrypt_with_cipher(cipher, &key, &ciphertext, &nonce).unwrap();
+            assert_eq!(decrypted, b"agile");
+        }
+
+        // A message only opens under the cipher it was sealed with
+        let ciphertext = CryptoService::encrypt_with_cipher(
+            MessageCipher::ChaCha20Poly1305,
+            &key,
+            b"agile",
+            &nonce,
+        )
+        .unwrap();
+        assert!(CryptoService::decrypt_with_cipher(
+            MessageCipher::Aes256Gcm,
+            &key,
+            &ciphertext,
+            &nonce
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_message_cipher_identifiers() {
+        for cipher in MessageCipher::SUPPORTED {
+            assert_eq!(cipher.as_str().parse::<MessageCipher>(), Ok(cipher));
+        }
+        assert!("rot13".parse::<MessageCipher>().is_err());
+        assert!(is_legacy_message_cipher(&legacy_message_cipher()));
+    }
+
     #[test]
     fn test_derive_conversation_key_deterministic() {
         let shared_secret = [0x42u8; 32];
@@ -0,0 +1,151 @@
+//! Per-identity feature flags
+//!
+//! Experimental features are opted into per account: each flag is stored in
+//! that account's database and stays off until the user turns it on.
+
+use std::sync::Arc;
+use tokio::sync::Notify;
+
+use crate::db::repositories::FeatureFlagsRepository;
+use crate::db::Database;
+use crate::error::{AppError, Result};
+
+/// A feature that can be toggled per identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureFlag {
+    /// Sync with connected peers in the background on the auto-sync interval
+    AutoSyncTimer,
+    /// Encrypt outgoing messages under the per-conversation ratchet for
+    /// contacts that support it. Ratcheted messages are read either way.
+    RatchetEncryption,
+}
+
+impl FeatureFlag {
+    /// Every known flag, in the order they are listed
+    pub const ALL: [FeatureFlag; 2] = [FeatureFlag::AutoSyncTimer, FeatureFlag::RatchetEncryption];
+
+    /// Name the flag is stored and addressed under
+    pub fn name(self) -> &'static str {
+        match self {
+            FeatureFlag::AutoSyncTimer => "auto_sync_timer",
+            FeatureFlag::RatchetEncryption => "ratchet_encryption",
+        }
+    }
+
+    /// What turning the flag on does, for settings screens
+    pub fn description(self) -> &'static str {
+        match self {
+            FeatureFlag::AutoSyncTimer => {
+                "Request new posts from connected peers in the background on the auto-sync interval"
+            }
+            FeatureFlag::RatchetEncryption => {
+                "Encrypt messages to contacts that support it with keys that change as the conversation goes on"
+            }
+        }
+    }
+
+    /// Look up a flag by name
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|flag| flag.name() == name)
+    }
+}
+
+/// A flag and whether it is on for this identity
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FeatureFlagState {
+    pub flag: FeatureFlag,
+    pub enabled: bool,
+}
+
+/// Service for reading and toggling feature flags
+pub struct FeatureFlagsService {
+    db: Arc<Database>,
+    /// Woken whenever a flag is toggled
+    changed: Notify,
+}
+
+impl FeatureFlagsService {
+    /// Create a new feature flags service
+    pub fn new(db: Arc<Database>) -> Self {
+        Self {
+            db,
+            changed: Notify::new(),
+        }
+    }
+
+    /// Look up a flag by name, failing for names no flag uses
+    pub fn parse(name: &str) -> Result<FeatureFlag> {
+        FeatureFlag::from_name(name)
+            .ok_or_else(|| AppError::Validation(format!("Unknown feature flag: {}", name)))
+    }
+
+    /// Whether a flag is on; flags that were never set are off
+    pub fn is_enabled(&self, flag: FeatureFlag) -> Result<bool> {
+        Self::is_enabled_in(&self.db, flag)
+    }
+
+    /// Whether a flag is on in an account's database, for services that
+    /// only hold the database
+    pub fn is_enabled_in(db: &Database, flag: FeatureFlag) -> Result<bool> {
+        Ok(FeatureFlagsRepository::get(db, flag.name())
+            .map_err(AppError::Database)?
+            .unwrap_or(false))
+    }
+
+    /// Turn a flag on or off
+    pub fn set_enabled(&self, flag: FeatureFlag, enabled: bool) -> Result<()> {
+        FeatureFlagsRepository::set(&self.db, flag.name(), enabled).map_err(AppError::Database)?;
+        self.changed.notify_one();
+        Ok(())
+    }
+
+    /// Every known flag with its current state
+    pub fn list(&self) -> Result<Vec<FeatureFlagState>> {
+        FeatureFlag::ALL
+            .into_iter()
+            .map(|flag| {
+                Ok(FeatureFlagState {
+                    flag,
+                    enabled: self.is_enabled(flag)?,
+                })
+            })
+            .collect()
+    }
+
+    /// Wait until a flag is toggled
+    pub async fn changed(&self) {
+        self.changed.notified().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_default_off_and_toggle() {
+        let service = FeatureFlagsService::new(Arc::new(Database::in_memory().unwrap()));
+
+        assert!(service.list().unwrap().iter().all(|state| !state.enabled));
+        assert!(!service.is_enabled(FeatureFlag::AutoSyncTimer).unwrap());
+
+        service
+            .set_enabled(FeatureFlag::AutoSyncTimer, true)
+            .unwrap();
+        assert!(service.is_enabled(FeatureFlag::AutoSyncTimer).unwrap());
+
+        service
+            .set_enabled(FeatureFlag::AutoSyncTimer, false)
+            .unwrap();
+        assert!(!service.is_enabled(FeatureFlag::AutoSyncTimer).unwrap());
+    }
+
+    #[test]
+    fn test_flag_names_round_trip() {
+        for flag in FeatureFlag::ALL {
+            assert_eq!(FeatureFlagsService::parse(flag.name()).unwrap(), flag);
+            assert!(!flag.description().is_empty());
+        }
+        assert!(FeatureFlagsService::parse("no_such_flag").is_err());
+    }
+}
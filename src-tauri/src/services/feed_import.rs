@@ -0,0 +1,565 @@
+//! Parsing RSS 2.0 and Atom feeds for importing blog posts.
+//!
+//! This is the inverse of the RSS feed generated for a wall: every `<item>`
+//! or `<entry>` becomes a [`FeedEntry`] with plain text content, a
+//! publication time and any media enclosures. Entries that can't be turned
+//! into a post are counted in [`ParsedFeed::skipped`] instead of failing the
+//! whole feed.
+
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use scraper::{ElementRef, Html, Node};
+
+use crate::error::{AppError, Result};
+
+/// HTML elements that end a paragraph when flattened to text
+const BLOCK_ELEMENTS: [&str; 16] = [
+    "p",
+    "div",
+    "li",
+    "ul",
+    "ol",
+    "blockquote",
+    "pre",
+    "h1",
+    "h2",
+    "h3",
+    "h4",
+    "h5",
+    "h6",
+    "table",
+    "tr",
+    "figure",
+];
+
+/// An entry read from a feed
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEntry {
+    /// The entry's guid, Atom id or link; identifies it across imports
+    pub key: String,
+    pub title: Option<String>,
+    pub link: Option<String>,
+    /// Plain text of the entry's content or summary
+    pub body: String,
+    /// Unix timestamp of the publication date, if the entry has one
+    pub published_at: Option<i64>,
+    pub enclosures: Vec<FeedEnclosure>,
+}
+
+impl FeedEntry {
+    /// Text for the imported post: the title followed by the body. The
+    /// title is left out when the body already starts with it, as it does in
+    /// feeds generated from Harbor walls.
+    pub fn post_text(&self) -> String {
+        match &self.title {
+            Some(title) if !self.body.starts_with(title.as_str()) => {
+                if self.body.is_empty() {
+                    title.clone()
+                } else {
+                    format!("{}\n\n{}", title, self.body)
+                }
+            }
+            _ => self.body.clone(),
+        }
+    }
+}
+
+/// A media file attached to a feed entry
+#[derive(Debug, Clone, PartialEq)]
+pub struct FeedEnclosure {
+    pub url: String,
+    pub mime_type: Option<String>,
+}
+
+/// The usable entries of a feed and how many were skipped as malformed
+#[derive(Debug, Clone, Default)]
+pub struct ParsedFeed {
+    pub entries: Vec<FeedEntry>,
+    pub skipped: usize,
+}
+
+/// Fields of an entry as they appear in the feed, before validation
+#[derive(Default)]
+struct RawEntry {
+    guid: Option<String>,
+    id: Option<String>,
+    title: Option<String>,
+    link: Option<String>,
+    description: Option<String>,
+    summary: Option<String>,
+    content: Option<String>,
+    encoded_content: Option<String>,
+    pub_date: Option<String>,
+    published: Option<String>,
+    updated: Option<String>,
+    dc_date: Option<String>,
+    enclosures: Vec<FeedEnclosure>,
+}
+
+impl RawEntry {
+    /// Where text inside the child element `name` is collected
+    fn field(&mut self, name: &str) -> Option<&mut Option<String>> {
+        Some(match name {
+            "guid" => &mut self.guid,
+            "id" => &mut self.id,
+            "title" => &mut self.title,
+            "link" => &mut self.link,
+            "description" => &mut self.description,
+            "summary" => &mut self.summary,
+            "content" => &mut self.content,
+            "content:encoded" => &mut self.encoded_content,
+            "pubDate" => &mut self.pub_date,
+            "published" => &mut self.published,
+            "updated" => &mut self.updated,
+            "dc:date" => &mut self.dc_date,
+            _ => return None,
+        })
+    }
+
+    /// Record the link or enclosure described by an element's attributes
+    fn add_attributes(&mut self, element: &BytesStart<'_>) {
+        let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+        let attribute = |key: &str| {
+            element
+                .attributes()
+                .flatten()
+                .find(|attr| attr.key.as_ref() == key.as_bytes())
+                .map(|attr| unescape(&String::from_utf8_lossy(&attr.value)))
+        };
+
+        match name.as_str() {
+            // RSS enclosures and Media RSS attachments
+            "enclosure" | "media:content" => {
+                if let Some(url) = attribute("url") {
+                    self.enclosures.push(FeedEnclosure {
+                        url,
+                        mime_type: attribute("type"),
+                    });
+                }
+            }
+            // Atom links carry their target in `href`
+            "link" => {
+                let Some(href) = attribute("href") else {
+                    return;
+                };
+                match attribute("rel").as_deref() {
+                    None | Some("alternate") => {
+                        self.link.get_or_insert(href);
+                    }
+                    Some("enclosure") => self.enclosures.push(FeedEnclosure {
+                        url: href,
+                        mime_type: attribute("type"),
+                    }),
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Validate the entry, returning `None` if it can't become a post
+    fn finish(self) -> Option<FeedEntry> {
+        let non_empty = |value: Option<String>| {
+            value
+                .map(|v| v.trim().to_string())
+                .filter(|v| !v.is_empty())
+        };
+
+        let link = non_empty(self.link);
+        let key = non_empty(self.guid)
+            .or(non_empty(self.id))
+            .or(link.clone())?;
+
+        let title = non_empty(self.title.map(|t| html_to_text(&t)));
+        let body = non_empty(self.encoded_content)
+            .or(non_empty(self.content))
+            .or(non_empty(self.description))
+            .or(non_empty(self.summary))
+            .map(|html| html_to_text(&html))
+            .unwrap_or_default();
+        if title.is_none() && body.is_empty() {
+            return None;
+        }
+
+        // A date that is present but unreadable makes the entry malformed
+        let published_at = match non_empty(self.pub_date) {
+            Some(date) => Some(parse_rfc2822(&date)?),
+            None => match non_empty(self.published)
+                .or(non_empty(self.updated))
+                .or(non_empty(self.dc_date))
+            {
+                Some(date) => Some(parse_rfc3339(&date)?),
+                None => None,
+            },
+        };
+
+        Some(FeedEntry {
+            key,
+            title,
+            link,
+            body,
+            published_at,
+            enclosures: self.enclosures,
+        })
+    }
+}
+
+/// Parse an RSS 2.0 or Atom document.
+///
+/// Fails only if the document isn't a feed at all. Broken XML after the
+/// first complete entry ends the parse early, counting the entry that was
+/// being read as skipped.
+pub fn parse_feed(xml: &str) -> Result<ParsedFeed> {
+    let mut reader = Reader::from_str(xml);
+    let mut feed = ParsedFeed::default();
+    let mut seen_feed_root = false;
+
+    let mut path: Vec<String> = Vec::new();
+    // The entry being read and the depth of its element
+    let mut entry: Option<(RawEntry, usize)> = None;
+
+    loop {
+        let event = match reader.read_event() {
+            Ok(event) => event,
+            Err(e) if feed.entries.is_empty() => {
+                return Err(AppError::Validation(format!("Invalid feed XML: {}", e)));
+            }
+            Err(_) => {
+                if entry.is_some() {
+                    feed.skipped += 1;
+                }
+                break;
+            }
+        };
+
+        match event {
+            Event::Start(element) => {
+                let name = String::from_utf8_lossy(element.name().as_ref()).into_owned();
+                if path.is_empty() {
+                    seen_feed_root = matches!(name.as_str(), "rss" | "feed" | "rdf:RDF");
+                }
+                path.push(name);
+                match &mut entry {
+                    Some((raw, _)) => raw.add_attributes(&element),
+                    None if is_entry_element(&path) => {
+                        entry = Some((RawEntry::default(), path.len()));
+                    }
+                    None => {}
+                }
+            }
+            Event::Empty(element) => {
+                if let Some((raw, _)) = &mut entry {
+                    raw.add_attributes(&element);
+                }
+            }
+            Event::End(_) => {
+                if let Some((_, depth)) = &entry {
+                    if path.len() == *depth {
+                        let (raw, _) = entry.take().expect("entry is being read");
+                        match raw.finish() {
+                            Some(finished) => feed.entries.push(finished),
+                            None => feed.skipped += 1,
+                        }
+                    }
+                }
+                path.pop();
+            }
+            Event::Text(text) => {
+                append_text(&mut entry, &path, &String::from_utf8_lossy(&text));
+            }
+            Event::CData(data) => {
+                append_text(&mut entry, &path, &String::from_utf8_lossy(&data));
+            }
+            Event::GeneralRef(reference) => {
+                let name = String::from_utf8_lossy(&reference);
+                let resolved = resolve_entity(&name).unwrap_or_else(|| format!("&{};", name));
+                append_text(&mut entry, &path, &resolved);
+            }
+            Event::Eof => {
+                // A document that ends mid-entry lost the rest of that entry
+                if entry.is_some() {
+                    feed.skipped += 1;
+                }
+                break;
+            }
+            _ => {}
+        }
+    }
+
+    if !seen_feed_root {
+        return Err(AppError::Validation("Not an RSS or Atom feed".to_string()));
+    }
+    Ok(feed)
+}
+
+/// Whether the element at the end of `path` is a feed entry
+fn is_entry_element(path: &[String]) -> bool {
+    match path.last().map(String::as_str) {
+        // RSS 2.0 nests items in the channel; RSS 1.0 puts them at the root
+        Some("item") => path.len() <= 3,
+        Some("entry") => path.len() == 2,
+        _ => false,
+    }
+}
+
+/// Add text to the entry field named by the entry's child element on `path`
+fn append_text(entry: &mut Option<(RawEntry, usize)>, path: &[String], text: &str) {
+    let Some((raw, depth)) = entry else {
+        return;
+    };
+    // Text nested deeper (e.g. Atom XHTML content) belongs to the child
+    let Some(child) = path.get(*depth) else {
+        return;
+    };
+    if let Some(field) = raw.field(child) {
+        field.get_or_insert_with(String::new).push_str(text);
+    }
+}
+
+/// Resolve a predefined XML entity or character reference, given its name
+fn resolve_entity(name: &str) -> Option<String> {
+    if let Some(number) = name.strip_prefix('#') {
+        let code = match number.strip_prefix('x').or(number.strip_prefix('X')) {
+            Some(hex) => u32::from_str_radix(hex, 16).ok()?,
+            None => number.parse().ok()?,
+        };
+        return char::from_u32(code).map(String::from);
+    }
+    Some(
+        match name {
+            "amp" => "&",
+            "lt" => "<",
+            "gt" => ">",
+            "quot" => "\"",
+            "apos" => "'",
+            _ => return None,
+        }
+        .to_string(),
+    )
+}
+
+/// Replace entity references in an attribute value
+fn unescape(value: &str) -> String {
+    let mut out = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find('&') {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        match after
+            .find(';')
+            .and_then(|end| Some((resolve_entity(&after[..end])?, end)))
+        {
+            Some((resolved, end)) => {
+                out.push_str(&resolved);
+                rest = &after[end + 1..];
+            }
+            None => {
+                out.push('&');
+                rest = after;
+            }
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Flatten HTML (or plain text) to text, keeping paragraph breaks
+pub fn html_to_text(html: &str) -> String {
+    let fragment = Html::parse_fragment(html);
+    let mut text = String::new();
+    push_text(fragment.root_element(), &mut text);
+
+    // Collapse runs of whitespace, keeping single line breaks within a
+    // paragraph and one blank line between paragraphs
+    let mut paragraphs: Vec<Vec<String>> = vec![Vec::new()];
+    for line in text.lines() {
+        let line = line.split_whitespace().collect::<Vec<_>>().join(" ");
+        if !line.is_empty() {
+            paragraphs.last_mut().expect("never empty").push(line);
+        } else if !paragraphs.last().expect("never empty").is_empty() {
+            paragraphs.push(Vec::new());
+        }
+    }
+    paragraphs
+        .into_iter()
+        .filter(|lines| !lines.is_empty())
+        .map(|lines| lines.join("\n"))
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+fn push_text(element: ElementRef<'_>, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(el) => {
+                let name = el.name();
+                if name == "br" {
+                    out.push('\n');
+                    continue;
+                }
+                if matches!(name, "script" | "style") {
+                    continue;
+                }
+                if let Some(child) = ElementRef::wrap(child) {
+                    push_text(child, out);
+                }
+                if BLOCK_ELEMENTS.contains(&name) {
+                    out.push_str("\n\n");
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn parse_rfc2822(date: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc2822(date)
+        .ok()
+        .map(|dt| dt.timestamp())
+}
+
+fn parse_rfc3339(date: &str) -> Option<i64> {
+    chrono::DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|dt| dt.timestamp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_rss_items() {
+        let xml = r#"<?xml version="1.0" encoding="UTF-8"?>
+<rss version="2.0" xmlns:content="http://purl.org/rss/1.0/modules/content/">
+  <channel>
+    <title>My Blog</title>
+    <item>
+      <title>Hello &amp; welcome</title>
+      <link>https://blog.example.com/hello</link>
+      <guid>post-1</guid>
+      <pubDate>Mon, 02 Jan 2023 10:00:00 GMT</pubDate>
+      <description>Short summary</description>
+      <content:encoded><![CDATA[<p>First paragraph.</p><p>Second<br/>line.</p>]]></content:encoded>
+      <enclosure url="https://blog.example.com/cat.jpg?w=1&amp;h=2" type="image/jpeg" length="1234"/>
+    </item>
+    <item>
+      <title>Untimed</title>
+      <link>https://blog.example.com/untimed</link>
+    </item>
+  </channel>
+</rss>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.skipped, 0);
+        assert_eq!(feed.entries.len(), 2);
+
+        let first = &feed.entries[0];
+        assert_eq!(first.key, "post-1");
+        assert_eq!(first.title.as_deref(), Some("Hello & welcome"));
+        assert_eq!(first.body, "First paragraph.\n\nSecond\nline.");
+        assert_eq!(first.published_at, Some(1672653600));
+        assert_eq!(
+            first.enclosures,
+            vec![FeedEnclosure {
+                url: "https://blog.example.com/cat.jpg?w=1&h=2".to_string(),
+                mime_type: Some("image/jpeg".to_string()),
+            }]
+        );
+        assert_eq!(
+            first.post_text(),
+            "Hello & welcome\n\nFirst paragraph.\n\nSecond\nline."
+        );
+
+        // Without a guid the link identifies the entry
+        let second = &feed.entries[1];
+        assert_eq!(second.key, "https://blog.example.com/untimed");
+        assert_eq!(second.published_at, None);
+        assert_eq!(second.post_text(), "Untimed");
+    }
+
+    #[test]
+    fn test_parse_atom_entries() {
+        let xml = r#"<?xml version="1.0" encoding="utf-8"?>
+<feed xmlns="http://www.w3.org/2005/Atom">
+  <title>Atom Blog</title>
+  <link href="https://atom.example.com/"/>
+  <entry>
+    <title type="html">Atom &lt;b&gt;post&lt;/b&gt;</title>
+    <id>urn:uuid:1225c695</id>
+    <link rel="alternate" href="https://atom.example.com/post"/>
+    <link rel="enclosure" type="video/mp4" href="https://atom.example.com/clip.mp4"/>
+    <published>2024-03-01T12:30:00Z</published>
+    <updated>2024-03-02T12:30:00Z</updated>
+    <summary>Entry summary</summary>
+  </entry>
+</feed>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.entries.len(), 1);
+        let entry = &feed.entries[0];
+        assert_eq!(entry.key, "urn:uuid:1225c695");
+        assert_eq!(entry.title.as_deref(), Some("Atom post"));
+        assert_eq!(entry.link.as_deref(), Some("https://atom.example.com/post"));
+        assert_eq!(entry.body, "Entry summary");
+        assert_eq!(entry.published_at, Some(1709296200));
+        assert_eq!(entry.enclosures.len(), 1);
+        assert_eq!(entry.enclosures[0].mime_type.as_deref(), Some("video/mp4"));
+    }
+
+    #[test]
+    fn test_malformed_entries_are_skipped() {
+        let xml = r#"<rss version="2.0"><channel>
+  <item><title>No identifier</title></item>
+  <item><guid>empty</guid></item>
+  <item><guid>bad-date</guid><title>Bad date</title><pubDate>yesterday</pubDate></item>
+  <item><guid>good</guid><title>Good</title></item>
+</channel></rss>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.skipped, 3);
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.entries[0].key, "good");
+    }
+
+    #[test]
+    fn test_truncated_feed_keeps_complete_entries() {
+        let xml = r#"<rss version="2.0"><channel>
+  <item><guid>one</guid><title>One</title></item>
+  <item><guid>two</guid><title>Two</wrong>"#;
+
+        let feed = parse_feed(xml).unwrap();
+        assert_eq!(feed.entries.len(), 1);
+        assert_eq!(feed.skipped, 1);
+    }
+
+    #[test]
+    fn test_rejects_non_feeds() {
+        assert!(parse_feed("<html><body>Hi</body></html>").is_err());
+        assert!(parse_feed("not xml <<<").is_err());
+    }
+
+    #[test]
+    fn test_title_not_repeated_when_body_starts_with_it() {
+        let entry = FeedEntry {
+            key: "k".to_string(),
+            title: Some("First line".to_string()),
+            link: None,
+            body: "First line\nand more".to_string(),
+            published_at: None,
+            enclosures: Vec::new(),
+        };
+        assert_eq!(entry.post_text(), "First line\nand more");
+    }
+
+    #[test]
+    fn test_html_to_text() {
+        assert_eq!(html_to_text("plain  text"), "plain text");
+        assert_eq!(
+            html_to_text("<h1>Title</h1><p>A <b>bold</b> move</p><script>x()</script>"),
+            "Title\n\nA bold move"
+        );
+        assert_eq!(html_to_text("a &amp; b"), "a & b");
+    }
+}
@@ -5,6 +5,7 @@ use std::sync::Arc;
 
 use crate::db::{Capability, Database, Post, PostVisibility, PostsRepository};
 use crate::error::{AppError, Result};
+use crate::models::LocalIdentity;
 use crate::services::{ContactsService, IdentityService, PermissionsService};
 
 /// Service for managing the user's feed
@@ -45,7 +46,14 @@ impl FeedService {
     /// - Posts from contacts who granted us WallRead permission
     /// - Only non-deleted posts
     /// - Sorted by creation time, newest first
-    pub fn get_feed(&self, limit: i64, before_timestamp: Option<i64>) -> Result<Vec<FeedItem>> {
+    ///
+    /// When `group_id` is set, only authors in that contact group are included.
+    pub fn get_feed(
+        &self,
+        limit: i64,
+        before_timestamp: Option<i64>,
+        group_id: Option<&str>,
+    ) -> Result<Vec<FeedItem>> {
         let identity = self
             .identity_service
             .get_identity()?
@@ -72,17 +80,64 @@ impl FeedService {
         allowed_authors.sort();
         allowed_authors.dedup();
 
+        if let Some(group_id) = group_id {
+            let members = self.contacts_service.get_group_member_ids(group_id)?;
+            allowed_authors.retain(|id| members.contains(id));
+        }
+
         // Get posts from all allowed authors in a single efficient query
         // sorted by created_at DESC with proper limit applied globally
         let all_posts =
             PostsRepository::get_feed_posts(&self.db, &allowed_authors, limit, before_timestamp)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
+        Ok(self.to_feed_items(&identity, all_posts))
+    }
+
+    /// Get a single contact's posts as a feed (their timeline)
+    ///
+    /// Unlike `get_wall`, pinned posts stay in timeline order and posts go
+    /// through the same visibility filtering and enrichment as `get_feed`.
+    /// Requires WallRead permission if not our own posts.
+    pub fn get_contact_feed(
+        &self,
+        peer_id: &str,
+        limit: i64,
+        before_timestamp: Option<i64>,
+    ) -> Result<Vec<FeedItem>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        if peer_id != identity.peer_id
+            && !self
+                .permissions_service
+                .we_have_capability(peer_id, Capability::WallRead)?
+        {
+            return Err(AppError::PermissionDenied(
+                "No permission to view this user's feed".to_string(),
+            ));
+        }
+
+        let posts = PostsRepository::get_feed_posts(
+            &self.db,
+            &[peer_id.to_string()],
+            limit,
+            before_timestamp,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        Ok(self.to_feed_items(&identity, posts))
+    }
+
+    /// Apply feed visibility rules and attach author display names
+    fn to_feed_items(&self, identity: &LocalIdentity, posts: Vec<Post>) -> Vec<FeedItem> {
         // Build a cache of display names for authors
         let mut display_name_cache: HashMap<String, Option<String>> = HashMap::new();
 
         // Convert to FeedItems with visibility filtering
-        let feed_items: Vec<FeedItem> = all_posts
+        posts
             .into_iter()
             .filter(|post| {
                 // Our own posts are always visible
@@ -124,9 +179,7 @@ impl FeedService {
                     author_display_name,
                 }
             })
-            .collect();
-
-        Ok(feed_items)
+            .collect()
     }
 
     /// Get posts from a specific author (their wall)
@@ -186,7 +239,11 @@ mod tests {
     use super::*;
     use crate::db::{ContactData, ContactsRepository, PostData, PostsRepository};
     use crate::models::CreateIdentityRequest;
-    use crate::services::{ContactsService, IdentityService, PermissionsService};
+    use crate::services::content_sync_service::RemotePostParams;
+    use crate::services::{
+        sign, ContactsService, ContentSyncService, CryptoService, IdentityService,
+        PermissionsService, SignablePost, CURRENT_SIG_VERSION,
+    };
     use std::sync::Arc;
 
     fn create_test_env() -> (
@@ -210,6 +267,7 @@ mod tests {
                 passphrase: "test-pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
 
@@ -247,6 +305,7 @@ mod tests {
             lamport_clock: 1,
             created_at,
             signature: vec![0u8; 64],
+            sig_version: CURRENT_SIG_VERSION,
         };
         PostsRepository::insert_post(db, &post_data).unwrap();
     }
@@ -273,7 +332,7 @@ mod tests {
             PostVisibility::Contacts,
         );
 
-        let feed = service.get_feed(10, None).unwrap();
+        let feed = service.get_feed(10, None, None).unwrap();
         assert_eq!(feed.len(), 2);
 
         // Most recent first
@@ -285,7 +344,7 @@ mod tests {
     fn test_get_feed_empty() {
         let (service, _db, _identity, _perms, _peer_id) = create_test_env();
 
-        let feed = service.get_feed(10, None).unwrap();
+        let feed = service.get_feed(10, None, None).unwrap();
         assert!(feed.is_empty());
     }
 
@@ -304,7 +363,7 @@ mod tests {
             );
         }
 
-        let feed = service.get_feed(3, None).unwrap();
+        let feed = service.get_feed(3, None, None).unwrap();
         assert_eq!(feed.len(), 3);
     }
 
@@ -321,7 +380,7 @@ mod tests {
         let feed_service =
             FeedService::new(db, identity_service, permissions_service, contacts_service);
 
-        let result = feed_service.get_feed(10, None);
+        let result = feed_service.get_feed(10, None, None);
         assert!(result.is_err());
     }
 
@@ -338,7 +397,7 @@ mod tests {
             PostVisibility::Public,
         );
 
-        let feed = service.get_feed(10, None).unwrap();
+        let feed = service.get_feed(10, None, None).unwrap();
         assert_eq!(feed.len(), 1);
         assert_eq!(feed[0].author_display_name, Some("Feed User".to_string()));
     }
@@ -456,10 +515,273 @@ mod tests {
             PostVisibility::Public,
         );
 
-        let feed = service.get_feed(10, None).unwrap();
+        let feed = service.get_feed(10, None, None).unwrap();
         assert_eq!(feed.len(), 3);
         assert_eq!(feed[0].post.post_id, "post-new");
         assert_eq!(feed[1].post.post_id, "post-mid");
         assert_eq!(feed[2].post.post_id, "post-old");
     }
+
+    #[test]
+    fn test_get_feed_filtered_by_group() {
+        let (service, db, _identity, _perms, _peer_id) = create_test_env();
+
+        for (peer_id, name) in [("12D3KooWAlice", "Alice"), ("12D3KooWBob", "Bob")] {
+            ContactsRepository::add_contact(
+                &db,
+                &ContactData {
+                    peer_id: peer_id.to_string(),
+                    public_key: vec![1u8; 32],
+                    x25519_public: vec![2u8; 32],
+                    display_name: name.to_string(),
+                    avatar_hash: None,
+                    bio: None,
+                },
+            )
+            .unwrap();
+        }
+        insert_test_post(
+            &db,
+            "alice-post",
+            "12D3KooWAlice",
+            "From Alice",
+            1000,
+            PostVisibility::Public,
+        );
+        insert_test_post(
+            &db,
+            "bob-post",
+            "12D3KooWBob",
+            "From Bob",
+            2000,
+            PostVisibility::Public,
+        );
+
+        let group = service.contacts_service.create_group("Family").unwrap();
+        service
+            .contacts_service
+            .add_contact_to_group(&group.group_id, "12D3KooWAlice")
+            .unwrap();
+
+        assert_eq!(service.get_feed(10, None, None).unwrap().len(), 2);
+
+        let feed = service.get_feed(10, None, Some(&group.group_id)).unwrap();
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].post.post_id, "alice-post");
+
+        assert!(service.get_feed(10, None, Some("missing")).is_err());
+    }
+
+    /// Add a contact with a real signing key and return a content sync service
+    /// that stores their posts, as both direct sync and relay wall sync do
+    fn add_signing_contact(
+        service: &FeedService,
+        db: &Arc<Database>,
+        identity: &Arc<IdentityService>,
+        perms: &Arc<PermissionsService>,
+    ) -> (ContentSyncService, ed25519_dalek::SigningKey, String) {
+        let (signing_key, verifying_key) = CryptoService::generate_ed25519_keypair();
+        let author = "12D3KooWAuthor".to_string();
+        ContactsRepository::add_contact(
+            db,
+            &ContactData {
+                peer_id: author.clone(),
+                public_key: verifying_key.to_bytes().to_vec(),
+                x25519_public: vec![0u8; 32],
+                display_name: "Author".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        let content_sync = ContentSyncService::new(
+            db.clone(),
+            identity.clone(),
+            service.contacts_service.clone(),
+            perms.clone(),
+        );
+        (content_sync, signing_key, author)
+    }
+
+    /// Sign a version of a post and store it, returning its signature
+    fn store_signed_version(
+        content_sync: &ContentSyncService,
+        signing_key: &ed25519_dalek::SigningKey,
+        author: &str,
+        content: &str,
+        lamport_clock: u64,
+    ) -> Vec<u8> {
+        let signable = SignablePost {
+            post_id: "shared-post".to_string(),
+            author_peer_id: author.to_string(),
+            content_type: "text".to_string(),
+            content_text: Some(content.to_string()),
+            media_hashes: vec![],
+            visibility: "public".to_string(),
+            lamport_clock,
+            created_at: 1000,
+        };
+        let signature = sign(signing_key, &signable).unwrap();
+        content_sync
+            .store_remote_post(&RemotePostParams {
+                post_id: "shared-post",
+                author_peer_id: author,
+                content_type: "text",
+                content_text: Some(content),
+                visibility: "public",
+                lamport_clock,
+                created_at: 1000,
+                signature: &signature,
+                sig_version: CURRENT_SIG_VERSION,
+            })
+            .unwrap();
+        signature
+    }
+
+    #[test]
+    fn test_feed_dedups_newer_relay_copy_then_older_direct_copy() {
+        let (service, db, identity, perms, _peer_id) = create_test_env();
+        let (content_sync, key, author) = add_signing_contact(&service, &db, &identity, &perms);
+
+        // The relay's wall store has the edited version, the author's direct
+        // sync then serves a stale one
+        let relay_sig = store_signed_version(&content_sync, &key, &author, "Edited", 3);
+        store_signed_version(&content_sync, &key, &author, "Original", 2);
+
+        let feed = service.get_feed(10, None, None).unwrap();
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].post.content_text, Some("Edited".to_string()));
+        assert_eq!(feed[0].post.lamport_clock, 3);
+        assert_eq!(feed[0].post.signature, relay_sig);
+    }
+
+    #[test]
+    fn test_feed_dedups_older_direct_copy_then_newer_relay_copy() {
+        let (service, db, identity, perms, _peer_id) = create_test_env();
+        let (content_sync, key, author) = add_signing_contact(&service, &db, &identity, &perms);
+
+        store_signed_version(&content_sync, &key, &author, "Original", 1);
+        let relay_sig = store_signed_version(&content_sync, &key, &author, "Edited", 4);
+
+        let feed = service.get_feed(10, None, None).unwrap();
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].post.content_text, Some("Edited".to_string()));
+        assert_eq!(feed[0].post.lamport_clock, 4);
+        // The stored signature belongs to the version being shown
+        assert_eq!(feed[0].post.signature, relay_sig);
+    }
+
+    #[test]
+    fn test_feed_ignores_unverified_copy_of_post() {
+        let (service, db, identity, perms, _peer_id) = create_test_env();
+        let (content_sync, key, author) = add_signing_contact(&service, &db, &identity, &perms);
+
+        store_signed_version(&content_sync, &key, &author, "Original", 1);
+
+        // A higher-clock copy with a bad signature must not replace it
+        let result = content_sync.store_remote_post(&RemotePostParams {
+            post_id: "shared-post",
+            author_peer_id: &author,
+            content_type: "text",
+            content_text: Some("Forged"),
+            visibility: "public",
+            lamport_clock: 9,
+            created_at: 1000,
+            signature: &[0u8; 64],
+            sig_version: CURRENT_SIG_VERSION,
+        });
+        assert!(result.is_err());
+
+        let feed = service.get_feed(10, None, None).unwrap();
+        assert_eq!(feed.len(), 1);
+        assert_eq!(feed[0].post.content_text, Some("Original".to_string()));
+    }
+
+    #[test]
+    fn test_get_contact_feed_no_permission() {
+        let (service, db, _identity, _perms, _peer_id) = create_test_env();
+
+        insert_test_post(
+            &db,
+            "other-post-1",
+            "12D3KooWOtherPeer",
+            "Other post",
+            1000,
+            PostVisibility::Public,
+        );
+
+        let result = service.get_contact_feed("12D3KooWOtherPeer", 10, None);
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn test_get_contact_feed_timeline() {
+        let (service, db, _identity, _perms, peer_id) = create_test_env();
+        let other_peer = "12D3KooWOtherPeer".to_string();
+
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: other_peer.clone(),
+                public_key: vec![1u8; 32],
+                x25519_public: vec![2u8; 32],
+                display_name: "Other Peer".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        use crate::db::{GrantData, PermissionsRepository};
+        PermissionsRepository::upsert_grant(
+            &db,
+            &GrantData {
+                grant_id: "grant-wr-1".to_string(),
+                issuer_peer_id: other_peer.clone(),
+                subject_peer_id: peer_id.clone(),
+                capability: "wall_read".to_string(),
+                scope_json: None,
+                lamport_clock: 1,
+                issued_at: 1000,
+                expires_at: None,
+                payload_cbor: vec![0],
+                signature: vec![0],
+            },
+        )
+        .unwrap();
+
+        insert_test_post(&db, "old", &other_peer, "Old", 1000, PostVisibility::Public);
+        insert_test_post(
+            &db,
+            "pinned",
+            &other_peer,
+            "Pinned",
+            2000,
+            PostVisibility::Contacts,
+        );
+        insert_test_post(
+            &db,
+            "hidden",
+            &other_peer,
+            "Hidden",
+            2500,
+            PostVisibility::Private,
+        );
+        insert_test_post(&db, "new", &other_peer, "New", 3000, PostVisibility::Public);
+        insert_test_post(&db, "mine", &peer_id, "Mine", 2200, PostVisibility::Public);
+        PostsRepository::pin_post(&db, &other_peer, "pinned").unwrap();
+
+        // Only this contact's visible posts, pinned ones in timeline order
+        let feed = service.get_contact_feed(&other_peer, 10, None).unwrap();
+        let ids: Vec<&str> = feed.iter().map(|item| item.post.post_id.as_str()).collect();
+        assert_eq!(ids, vec!["new", "pinned", "old"]);
+        assert_eq!(feed[0].author_display_name, Some("Other Peer".to_string()));
+
+        let page = service
+            .get_contact_feed(&other_peer, 10, Some(2000))
+            .unwrap();
+        assert_eq!(page.len(), 1);
+        assert_eq!(page[0].post.post_id, "old");
+    }
 }
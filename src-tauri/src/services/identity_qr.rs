@@ -0,0 +1,248 @@
+//! Compact binary identity payload for QR codes
+//!
+//! The text contact string (`harbor://<base64 json>`) is convenient to paste
+//! but wastes QR capacity. This payload packs the same identity into a
+//! version-tagged binary layout so it fits in a small, easy-to-scan code:
+//!
+//! ```text
+//! [version: u8]
+//! [ed25519 public key: 32 bytes]
+//! [x25519 public key: 32 bytes]
+//! [peer id length: u8][peer id: multihash bytes]
+//! [display name length: u8][display name: UTF-8]
+//! ```
+
+use ed25519_dalek::VerifyingKey;
+use libp2p::PeerId;
+use std::str::FromStr;
+
+use crate::error::{AppError, Result};
+use crate::services::CryptoService;
+
+/// Current payload version
+pub const IDENTITY_QR_VERSION: u8 = 1;
+
+/// Display names longer than this (in bytes) are truncated in the payload
+pub const MAX_QR_DISPLAY_NAME_BYTES: usize = 64;
+
+const KEY_LEN: usize = 32;
+
+/// Identity data carried in a QR payload
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IdentityQrPayload {
+    pub peer_id: String,
+    pub public_key: [u8; KEY_LEN],
+    pub x25519_public: [u8; KEY_LEN],
+    pub display_name: String,
+}
+
+impl IdentityQrPayload {
+    /// Build a payload from raw key bytes
+    pub fn new(
+        peer_id: &str,
+        public_key: &[u8],
+        x25519_public: &[u8],
+        display_name: &str,
+    ) -> Result<Self> {
+        let public_key: [u8; KEY_LEN] = public_key
+            .try_into()
+            .map_err(|_| AppError::Validation("Ed25519 public key must be 32 bytes".to_string()))?;
+        let x25519_public: [u8; KEY_LEN] = x25519_public
+            .try_into()
+            .map_err(|_| AppError::Validation("X25519 public key must be 32 bytes".to_string()))?;
+
+        Ok(Self {
+            peer_id: peer_id.to_string(),
+            public_key,
+            x25519_public,
+            display_name: truncate_utf8(display_name, MAX_QR_DISPLAY_NAME_BYTES).to_string(),
+        })
+    }
+
+    /// Encode to the binary QR layout
+    pub fn encode(&self) -> Result<Vec<u8>> {
+        let peer_id = PeerId::from_str(&self.peer_id)
+            .map_err(|e| AppError::Validation(format!("Invalid peer ID: {}", e)))?
+            .to_bytes();
+        let name = truncate_utf8(&self.display_name, MAX_QR_DISPLAY_NAME_BYTES).as_bytes();
+
+        let mut out = Vec::with_capacity(1 + 2 * KEY_LEN + 2 + peer_id.len() + name.len());
+        out.push(IDENTITY_QR_VERSION);
+        out.extend_from_slice(&self.public_key);
+        out.extend_from_slice(&self.x25519_public);
+        out.push(peer_id.len() as u8);
+        out.extend_from_slice(&peer_id);
+        out.push(name.len() as u8);
+        out.extend_from_slice(name);
+        Ok(out)
+    }
+
+    /// Decode and validate a binary QR payload.
+    ///
+    /// Rejects unknown versions, truncated or trailing data, and payloads whose
+    /// peer ID does not derive from the included Ed25519 key.
+    pub fn decode(bytes: &[u8]) -> Result<Self> {
+        let mut reader = Reader { bytes, pos: 0 };
+
+        let version = reader.take(1)?[0];
+        if version != IDENTITY_QR_VERSION {
+            return Err(AppError::Validation(format!(
+                "Unsupported identity QR payload version {}",
+                version
+            )));
+        }
+
+        let public_key = reader.take_key()?;
+        let x25519_public = reader.take_key()?;
+
+        let peer_id_len = reader.take(1)?[0] as usize;
+        let peer_id = PeerId::from_bytes(reader.take(peer_id_len)?)
+            .map_err(|e| AppError::Validation(format!("Invalid peer ID in QR payload: {}", e)))?
+            .to_string();
+
+        let name_len = reader.take(1)?[0] as usize;
+        let display_name = std::str::from_utf8(reader.take(name_len)?)
+            .map_err(|_| AppError::Validation("Display name is not valid UTF-8".to_string()))?
+            .to_string();
+
+        if reader.pos != bytes.len() {
+            return Err(AppError::Validation(
+                "Unexpected trailing data in QR payload".to_string(),
+            ));
+        }
+
+        let verifying_key = VerifyingKey::from_bytes(&public_key)
+            .map_err(|e| AppError::Validation(format!("Invalid Ed25519 public key: {}", e)))?;
+        let derived_peer_id = CryptoService::derive_peer_id_from_verifying_key(&verifying_key)?;
+        if derived_peer_id != peer_id {
+            return Err(AppError::Validation(
+                "Peer ID does not match public key in QR payload".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            peer_id,
+            public_key,
+            x25519_public,
+            display_name,
+        })
+    }
+}
+
+/// Bounds-checked cursor over the payload bytes
+struct Reader<'a> {
+    bytes: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self.pos + len;
+        let slice = self
+            .bytes
+            .get(self.pos..end)
+            .ok_or_else(|| AppError::Validation("Truncated QR payload".to_string()))?;
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn take_key(&mut self) -> Result<[u8; KEY_LEN]> {
+        let mut key = [0u8; KEY_LEN];
+        key.copy_from_slice(self.take(KEY_LEN)?);
+        Ok(key)
+    }
+}
+
+/// Truncate to at most `max_bytes` without splitting a UTF-8 character
+fn truncate_utf8(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = max_bytes;
+    while !s.is_char_boundary(end) {
+        end -= 1;
+    }
+    &s[..end]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn test_payload(display_name: &str) -> IdentityQrPayload {
+        let (signing_key, verifying_key) = CryptoService::generate_ed25519_keypair();
+        let (_, x25519_public) = CryptoService::generate_x25519_keypair();
+        let peer_id = CryptoService::derive_peer_id_from_signing_key(&signing_key).unwrap();
+
+        IdentityQrPayload::new(
+            &peer_id,
+            verifying_key.as_bytes(),
+            x25519_public.as_bytes(),
+            display_name,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let payload = test_payload("Alice");
+        let bytes = payload.encode().unwrap();
+
+        assert_eq!(bytes[0], IDENTITY_QR_VERSION);
+        assert_eq!(IdentityQrPayload::decode(&bytes).unwrap(), payload);
+    }
+
+    #[test]
+    fn test_payload_is_compact() {
+        let payload = test_payload("Alice");
+        let bytes = payload.encode().unwrap();
+
+        // version + 2 keys + peer id (38) + name, with 2 length bytes
+        assert_eq!(bytes.len(), 1 + 32 + 32 + 1 + 38 + 1 + 5);
+    }
+
+    #[test]
+    fn test_unknown_version_rejected() {
+        let mut bytes = test_payload("Alice").encode().unwrap();
+        bytes[0] = 99;
+
+        let err = IdentityQrPayload::decode(&bytes).unwrap_err();
+        assert!(matches!(err, AppError::Validation(ref msg) if msg.contains("version 99")));
+    }
+
+    #[test]
+    fn test_truncated_and_trailing_data_rejected() {
+        let bytes = test_payload("Alice").encode().unwrap();
+
+        assert!(IdentityQrPayload::decode(&[]).is_err());
+        assert!(IdentityQrPayload::decode(&bytes[..bytes.len() - 1]).is_err());
+
+        let mut extra = bytes.clone();
+        extra.push(0);
+        assert!(IdentityQrPayload::decode(&extra).is_err());
+    }
+
+    #[test]
+    fn test_mismatched_peer_id_rejected() {
+        let alice = test_payload("Alice");
+        let bob = test_payload("Bob");
+
+        let forged = IdentityQrPayload {
+            peer_id: bob.peer_id,
+            ..alice
+        };
+        let bytes = forged.encode().unwrap();
+
+        assert!(IdentityQrPayload::decode(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_long_display_name_truncated_on_char_boundary() {
+        let name = "é".repeat(MAX_QR_DISPLAY_NAME_BYTES);
+        let payload = test_payload(&name);
+
+        assert!(payload.display_name.len() <= MAX_QR_DISPLAY_NAME_BYTES);
+        let decoded = IdentityQrPayload::decode(&payload.encode().unwrap()).unwrap();
+        assert_eq!(decoded.display_name, payload.display_name);
+    }
+}
@@ -1,10 +1,17 @@
 use crate::db::repositories::IdentityRepository;
 use crate::db::Database;
 use crate::error::{AppError, Result};
-use crate::models::{CreateIdentityRequest, IdentityInfo, LocalIdentity};
-use crate::services::{sign as signing_sign, CryptoService, Signable};
-
+use crate::models::{
+    CreateIdentityRequest, IdentityBackup, IdentityInfo, KdfProfile, LocalIdentity,
+};
+use crate::services::{
+    sign as signing_sign, CryptoService, IdentityQrPayload, KdfParams, Signable,
+};
+
+use base64::Engine;
 use ed25519_dalek::SigningKey;
+use rand::RngCore;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
 use tracing::{error, info};
 use x25519_dalek::StaticSecret as X25519Secret;
@@ -14,8 +21,29 @@ pub struct IdentityService {
     db: Arc<Database>,
     /// Cached unlocked keys (only available after unlock)
     unlocked_keys: Arc<RwLock<Option<UnlockedKeys>>>,
+    /// Set when the identity is a throwaway guest identity held only in memory
+    ephemeral: Arc<AtomicBool>,
 }
 
+/// Check an accent color is a `#rgb` or `#rrggbb` hex string and return it
+/// in lowercase
+pub fn normalize_accent_color(color: &str) -> Result<String> {
+    let color = color.trim();
+    let valid = color.strip_prefix('#').is_some_and(|hex| {
+        matches!(hex.len(), 3 | 6) && hex.chars().all(|c| c.is_ascii_hexdigit())
+    });
+    if !valid {
+        return Err(AppError::Validation(format!(
+            "Accent color must be a hex color like #3b82f6, got {:?}",
+            color
+        )));
+    }
+    Ok(color.to_ascii_lowercase())
+}
+
+/// Current format version of exported identity backups
+pub const IDENTITY_BACKUP_VERSION: u32 = 1;
+
 /// Keys that are available after unlocking with passphrase
 #[derive(Clone)]
 pub struct UnlockedKeys {
@@ -28,6 +56,7 @@ impl IdentityService {
         Self {
             db,
             unlocked_keys: Arc::new(RwLock::new(None)),
+            ephemeral: Arc::new(AtomicBool::new(false)),
         }
     }
 
@@ -99,10 +128,12 @@ impl IdentityService {
         );
 
         // Encrypt private keys
-        let encrypted_keys = CryptoService::encrypt_keys(
+        let kdf_params = KdfParams::for_profile(request.kdf_profile.unwrap_or_default());
+        let encrypted_keys = CryptoService::encrypt_keys_with_params(
             ed25519_signing.to_bytes().as_ref(),
             x25519_secret.as_bytes(),
             &request.passphrase,
+            &kdf_params,
         )?;
 
         let now = chrono::Utc::now().timestamp();
@@ -116,6 +147,7 @@ impl IdentityService {
             avatar_hash: None,
             bio: request.bio,
             passphrase_hint: request.passphrase_hint,
+            accent_color: None,
             created_at: now,
             updated_at: now,
         };
@@ -135,6 +167,41 @@ impl IdentityService {
         Ok(identity.into())
     }
 
+    /// Create a throwaway guest identity that never touches disk.
+    ///
+    /// Only allowed on an in-memory database. The keys are encrypted with a
+    /// random passphrase that is discarded immediately, so the identity
+    /// stays unlocked for the session and vanishes when the app closes.
+    pub fn create_ephemeral_identity(&self, display_name: &str) -> Result<IdentityInfo> {
+        if !self.db.is_in_memory() {
+            return Err(AppError::Validation(
+                "Ephemeral identities require an in-memory database".to_string(),
+            ));
+        }
+
+        let mut passphrase_bytes = [0u8; 32];
+        rand::rngs::OsRng.fill_bytes(&mut passphrase_bytes);
+        let passphrase = base64::engine::general_purpose::STANDARD.encode(passphrase_bytes);
+
+        let info = self.create_identity(CreateIdentityRequest {
+            display_name: display_name.to_string(),
+            passphrase,
+            bio: None,
+            passphrase_hint: None,
+            // Nobody ever types this passphrase, so skip the expensive derivation
+            kdf_profile: Some(KdfProfile::Light),
+        })?;
+        self.ephemeral.store(true, Ordering::SeqCst);
+
+        info!("Created ephemeral identity: {}", info.peer_id);
+        Ok(info)
+    }
+
+    /// Check if the identity is an ephemeral guest identity
+    pub fn is_ephemeral(&self) -> bool {
+        self.ephemeral.load(Ordering::SeqCst)
+    }
+
     /// Unlock the identity with the passphrase
     pub fn unlock(&self, passphrase: &str) -> Result<IdentityInfo> {
         let repo = IdentityRepository::new(&self.db);
@@ -175,6 +242,11 @@ impl IdentityService {
 
     /// Lock the identity (clear unlocked keys from memory)
     pub fn lock(&self) {
+        if self.is_ephemeral() {
+            // The passphrase was never kept, so locking would lose the keys for good
+            info!("Ephemeral identity stays unlocked until the app closes");
+            return;
+        }
         let mut unlocked = self.write_keys();
         *unlocked = None;
         info!("Identity locked");
@@ -207,6 +279,78 @@ impl IdentityService {
         repo.get().map_err(Into::into)
     }
 
+    /// Export a backup of the identity with its keys still passphrase-encrypted
+    pub fn export_backup(&self) -> Result<IdentityBackup> {
+        if self.is_ephemeral() {
+            return Err(AppError::PermissionDenied(
+                "Cannot back up an ephemeral identity: it only exists for this session".to_string(),
+            ));
+        }
+
+        let identity = self
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        Ok(IdentityBackup {
+            version: IDENTITY_BACKUP_VERSION,
+            peer_id: identity.peer_id,
+            public_key: engine.encode(&identity.public_key),
+            x25519_public: engine.encode(&identity.x25519_public),
+            private_key_encrypted: engine.encode(&identity.private_key_encrypted),
+            display_name: identity.display_name,
+            bio: identity.bio,
+            passphrase_hint: identity.passphrase_hint,
+            created_at: identity.created_at,
+            exported_at: chrono::Utc::now().timestamp(),
+        })
+    }
+
+    /// Change the passphrase protecting the private keys.
+    ///
+    /// The keys are re-encrypted under the new passphrase. Passing a profile
+    /// also changes the key derivation parameters; otherwise the current
+    /// parameters are kept.
+    pub fn change_passphrase(
+        &self,
+        current_passphrase: &str,
+        new_passphrase: &str,
+        kdf_profile: Option<KdfProfile>,
+    ) -> Result<()> {
+        if self.is_ephemeral() {
+            return Err(AppError::PermissionDenied(
+                "Cannot change the passphrase of an ephemeral identity".to_string(),
+            ));
+        }
+        if new_passphrase.is_empty() {
+            return Err(AppError::Validation(
+                "Passphrase cannot be empty".to_string(),
+            ));
+        }
+
+        let repo = IdentityRepository::new(&self.db);
+        let identity = repo
+            .get()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
+
+        let keys =
+            CryptoService::decrypt_keys(&identity.private_key_encrypted, current_passphrase)?;
+        let kdf_params = match kdf_profile {
+            Some(profile) => KdfParams::for_profile(profile),
+            None => CryptoService::key_kdf_params(&identity.private_key_encrypted)?,
+        };
+        let encrypted_keys = CryptoService::encrypt_keys_with_params(
+            &keys.ed25519_private,
+            &keys.x25519_private,
+            new_passphrase,
+            &kdf_params,
+        )?;
+
+        repo.update_private_key_encrypted(&encrypted_keys)?;
+        info!("Changed identity passphrase");
+        Ok(())
+    }
+
     /// Update display name
     pub fn update_display_name(&self, display_name: &str) -> Result<()> {
         let repo = IdentityRepository::new(&self.db);
@@ -228,6 +372,14 @@ impl IdentityService {
         Ok(())
     }
 
+    /// Update accent color (`None` clears it)
+    pub fn update_accent_color(&self, accent_color: Option<&str>) -> Result<()> {
+        let accent_color = accent_color.map(normalize_accent_color).transpose()?;
+        let repo = IdentityRepository::new(&self.db);
+        repo.update_accent_color(accent_color.as_deref())?;
+        Ok(())
+    }
+
     /// Get the local peer ID
     pub fn get_peer_id(&self) -> Result<String> {
         let repo = IdentityRepository::new(&self.db);
@@ -236,6 +388,21 @@ impl IdentityService {
             .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
         Ok(identity.peer_id)
     }
+
+    /// Encode our public identity as a compact binary payload for a QR code
+    pub fn get_identity_qr_payload(&self) -> Result<Vec<u8>> {
+        let identity = self
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity found".to_string()))?;
+
+        IdentityQrPayload::new(
+            &identity.peer_id,
+            &identity.public_key,
+            &identity.x25519_public,
+            &identity.display_name,
+        )?
+        .encode()
+    }
 }
 
 impl Clone for IdentityService {
@@ -243,6 +410,7 @@ impl Clone for IdentityService {
         Self {
             db: Arc::clone(&self.db),
             unlocked_keys: Arc::clone(&self.unlocked_keys),
+            ephemeral: Arc::clone(&self.ephemeral),
         }
     }
 }
@@ -272,6 +440,7 @@ mod tests {
             passphrase: "test-passphrase".to_string(),
             bio: Some("Test bio".to_string()),
             passphrase_hint: Some("Test hint".to_string()),
+            kdf_profile: None,
         };
 
         let info = service.create_identity(request).unwrap();
@@ -286,6 +455,39 @@ mod tests {
         assert!(service.has_identity().unwrap());
     }
 
+    #[test]
+    fn test_update_accent_color() {
+        let service = create_test_service();
+        service
+            .create_identity(CreateIdentityRequest {
+                display_name: "Test User".to_string(),
+                passphrase: "test-passphrase".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: Some(KdfProfile::Light),
+            })
+            .unwrap();
+        assert_eq!(
+            service.get_identity_info().unwrap().unwrap().accent_color,
+            None
+        );
+
+        service.update_accent_color(Some("#3B82F6")).unwrap();
+        let info = service.get_identity_info().unwrap().unwrap();
+        assert_eq!(info.accent_color.as_deref(), Some("#3b82f6"));
+
+        for invalid in ["3b82f6", "#3b82f", "#ggg", "red", "#3b82f6ff"] {
+            assert!(service.update_accent_color(Some(invalid)).is_err());
+        }
+        assert_eq!(normalize_accent_color(" #AbC ").unwrap(), "#abc");
+
+        service.update_accent_color(None).unwrap();
+        assert_eq!(
+            service.get_identity_info().unwrap().unwrap().accent_color,
+            None
+        );
+    }
+
     #[test]
     fn test_lock_unlock() {
         let service = create_test_service();
@@ -295,6 +497,7 @@ mod tests {
             passphrase: "test-passphrase".to_string(),
             bio: None,
             passphrase_hint: None,
+            kdf_profile: None,
         };
 
         service.create_identity(request).unwrap();
@@ -318,6 +521,7 @@ mod tests {
             passphrase: "correct-passphrase".to_string(),
             bio: None,
             passphrase_hint: None,
+            kdf_profile: None,
         };
 
         service.create_identity(request).unwrap();
@@ -337,6 +541,7 @@ mod tests {
             passphrase: "test-passphrase".to_string(),
             bio: None,
             passphrase_hint: None,
+            kdf_profile: None,
         };
 
         service.create_identity(request).unwrap();
@@ -352,4 +557,163 @@ mod tests {
         let result = service.sign_raw(b"test data");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_identity_qr_payload() {
+        let service = create_test_service();
+        assert!(service.get_identity_qr_payload().is_err());
+
+        let request = CreateIdentityRequest {
+            display_name: "Test User".to_string(),
+            passphrase: "test-passphrase".to_string(),
+            bio: None,
+            passphrase_hint: None,
+            kdf_profile: None,
+        };
+        let info = service.create_identity(request).unwrap();
+
+        // Available while locked: only public data is encoded
+        service.lock();
+        let bytes = service.get_identity_qr_payload().unwrap();
+        let payload = IdentityQrPayload::decode(&bytes).unwrap();
+
+        assert_eq!(payload.peer_id, info.peer_id);
+        assert_eq!(payload.display_name, "Test User");
+    }
+
+    #[test]
+    fn test_ephemeral_identity_stays_unlocked_and_blocks_backup() {
+        let service = create_test_service();
+
+        let info = service.create_ephemeral_identity("Guest").unwrap();
+        assert!(info.peer_id.starts_with("12D3KooW"));
+        assert_eq!(info.display_name, "Guest");
+        assert!(service.is_ephemeral());
+        assert!(service.clone().is_ephemeral());
+
+        // Locking would lose the keys for good, so it is a no-op
+        service.lock();
+        assert!(service.is_unlocked());
+        assert!(service.sign_raw(b"hello").is_ok());
+
+        let err = service.export_backup().unwrap_err();
+        assert!(matches!(err, AppError::PermissionDenied(_)));
+    }
+
+    #[test]
+    fn test_ephemeral_identity_requires_in_memory_database() {
+        let tmp = tempfile::tempdir().unwrap();
+        let db = Arc::new(Database::new(tmp.path().join("harbor.db")).unwrap());
+        let service = IdentityService::new(db);
+
+        let err = service.create_ephemeral_identity("Guest").unwrap_err();
+        assert!(matches!(err, AppError::Validation(_)));
+        assert!(!service.has_identity().unwrap());
+        assert!(!service.is_ephemeral());
+    }
+
+    #[test]
+    fn test_export_backup() {
+        let service = create_test_service();
+        assert!(service.export_backup().is_err());
+
+        let request = CreateIdentityRequest {
+            display_name: "Test User".to_string(),
+            passphrase: "test-passphrase".to_string(),
+            bio: None,
+            passphrase_hint: Some("hint".to_string()),
+            kdf_profile: None,
+        };
+        let info = service.create_identity(request).unwrap();
+        assert!(!service.is_ephemeral());
+
+        let backup = service.export_backup().unwrap();
+        assert_eq!(backup.version, IDENTITY_BACKUP_VERSION);
+        assert_eq!(backup.peer_id, info.peer_id);
+        assert_eq!(backup.public_key, info.public_key);
+        assert_eq!(backup.passphrase_hint, Some("hint".to_string()));
+
+        // Keys in the backup are still protected by the passphrase
+        let encrypted = base64::engine::general_purpose::STANDARD
+            .decode(&backup.private_key_encrypted)
+            .unwrap();
+        assert!(CryptoService::decrypt_keys(&encrypted, "test-passphrase").is_ok());
+        assert!(CryptoService::decrypt_keys(&encrypted, "wrong").is_err());
+    }
+
+    #[test]
+    fn test_create_identity_with_kdf_profile() {
+        let service = create_test_service();
+
+        let request = CreateIdentityRequest {
+            display_name: "Test User".to_string(),
+            passphrase: "test-passphrase".to_string(),
+            bio: None,
+            passphrase_hint: None,
+            kdf_profile: Some(KdfProfile::Light),
+        };
+        service.create_identity(request).unwrap();
+
+        let identity = service.get_identity().unwrap().unwrap();
+        let params = CryptoService::key_kdf_params(&identity.private_key_encrypted).unwrap();
+        assert_eq!(params, KdfParams::for_profile(KdfProfile::Light));
+
+        service.lock();
+        assert!(service.unlock("test-passphrase").is_ok());
+    }
+
+    #[test]
+    fn test_change_passphrase() {
+        let service = create_test_service();
+
+        let request = CreateIdentityRequest {
+            display_name: "Test User".to_string(),
+            passphrase: "old-passphrase".to_string(),
+            bio: None,
+            passphrase_hint: None,
+            kdf_profile: Some(KdfProfile::Light),
+        };
+        let info = service.create_identity(request).unwrap();
+
+        let err = service
+            .change_passphrase("wrong-passphrase", "new-passphrase", None)
+            .unwrap_err();
+        assert!(matches!(err, AppError::IdentityInvalidPassphrase(_)));
+
+        // Without a profile the existing parameters are kept
+        service
+            .change_passphrase("old-passphrase", "new-passphrase", None)
+            .unwrap();
+        let identity = service.get_identity().unwrap().unwrap();
+        assert_eq!(
+            CryptoService::key_kdf_params(&identity.private_key_encrypted).unwrap(),
+            KdfParams::for_profile(KdfProfile::Light)
+        );
+
+        service.lock();
+        assert!(service.unlock("old-passphrase").is_err());
+        assert_eq!(
+            service.unlock("new-passphrase").unwrap().peer_id,
+            info.peer_id
+        );
+
+        // A profile re-encrypts with new parameters under the same keys
+        service
+            .change_passphrase(
+                "new-passphrase",
+                "new-passphrase",
+                Some(KdfProfile::Standard),
+            )
+            .unwrap();
+        let identity = service.get_identity().unwrap().unwrap();
+        assert_eq!(
+            CryptoService::key_kdf_params(&identity.private_key_encrypted).unwrap(),
+            KdfParams::default()
+        );
+        service.lock();
+        assert_eq!(
+            service.unlock("new-passphrase").unwrap().peer_id,
+            info.peer_id
+        );
+    }
 }
@@ -0,0 +1,218 @@
+//! Markdown content handling for posts and messages.
+//!
+//! Content with the `markdown` content type is rendered by the UI, so it is
+//! restricted to plain markdown: raw HTML, comments and script-capable link
+//! targets are removed before it is signed. Tags are stripped inside code
+//! spans too; literal markup there has to be escaped. Content from peers is
+//! checked again on receipt, since a modified client can sign whatever it
+//! likes.
+
+use regex::{Captures, Regex};
+use std::borrow::Cow;
+use std::sync::OnceLock;
+
+use crate::error::{AppError, Result};
+
+/// Content type for text the UI renders as markdown
+pub const CONTENT_TYPE_MARKDOWN: &str = "markdown";
+
+/// Link schemes that can run code or smuggle a document when followed
+const UNSAFE_SCHEMES: [&str; 4] = ["javascript:", "vbscript:", "data:", "file:"];
+
+/// Replacement target for links with an unsafe scheme
+const NEUTRALIZED_LINK: &str = "#";
+
+/// Whether content of this type is rendered as markdown
+pub fn is_markdown(content_type: &str) -> bool {
+    content_type == CONTENT_TYPE_MARKDOWN
+}
+
+/// Sanitize `text` if `content_type` is markdown; other types are returned
+/// unchanged.
+pub fn sanitize_content<'a>(content_type: &str, text: &'a str) -> Cow<'a, str> {
+    if is_markdown(content_type) {
+        Cow::Owned(sanitize_markdown(text))
+    } else {
+        Cow::Borrowed(text)
+    }
+}
+
+/// Reject received markdown that the sanitizer would change.
+///
+/// Signed content can't be rewritten without invalidating the author's
+/// signature, so anything a well-behaved sender would have stripped is
+/// refused instead of stored.
+pub fn ensure_sanitized(content_type: &str, text: Option<&str>) -> Result<()> {
+    match text {
+        Some(text) if sanitize_content(content_type, text) != text => Err(AppError::Validation(
+            "Markdown content contains raw HTML or unsafe links".to_string(),
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Strip raw HTML, comments and unsafe link targets from markdown.
+///
+/// Passes repeat until nothing changes, so removing one tag can't assemble
+/// another (`<<b>script>`). Every change shortens the text, so this ends.
+pub fn sanitize_markdown(text: &str) -> String {
+    let mut text = text.to_string();
+    loop {
+        let next = sanitize_pass(&text);
+        if next == text {
+            return text;
+        }
+        text = next;
+    }
+}
+
+fn sanitize_pass(text: &str) -> String {
+    static COMMENT: OnceLock<Regex> = OnceLock::new();
+    static SCRIPT_BLOCK: OnceLock<Regex> = OnceLock::new();
+    static TAG: OnceLock<Regex> = OnceLock::new();
+    static INLINE_LINK: OnceLock<Regex> = OnceLock::new();
+    static AUTOLINK: OnceLock<Regex> = OnceLock::new();
+    static REFERENCE_LINK: OnceLock<Regex> = OnceLock::new();
+
+    // Unterminated comments and script blocks run to the end of the text,
+    // which is how a browser would treat them too
+    let comment = COMMENT.get_or_init(|| Regex::new(r"(?s)<!--.*?(-->|\z)").unwrap());
+    let script_block = SCRIPT_BLOCK.get_or_init(|| {
+        Regex::new(
+            r"(?is)<(script|style|iframe|object|embed|textarea|title)\b.*?(</(script|style|iframe|object|embed|textarea|title)\s*>|\z)",
+        )
+        .unwrap()
+    });
+    let tag = TAG.get_or_init(|| {
+        Regex::new(r"(?i)</?[a-z][a-z0-9-]*(\s[^<>]*)?/?>|<![a-z][^<>]*>|<\?[^<>]*\?>").unwrap()
+    });
+    // Destinations may contain one level of balanced parentheses
+    let inline_link = INLINE_LINK
+        .get_or_init(|| Regex::new(r"(\]\(\s*<?)((?:[^()<>\s]|\([^()<>\s]*\))*)").unwrap());
+    let autolink = AUTOLINK.get_or_init(|| Regex::new(r"<([a-zA-Z][^<>\s]*)>").unwrap());
+    let reference_link =
+        REFERENCE_LINK.get_or_init(|| Regex::new(r"(?m)^(\s{0,3}\[[^\]]+\]:\s*<?)(\S*)").unwrap());
+
+    let text = comment.replace_all(text, "");
+    let text = script_block.replace_all(&text, "");
+    let text = tag.replace_all(&text, "");
+
+    let neutralize = |caps: &Captures| {
+        if is_unsafe_destination(&caps[2]) {
+            format!("{}{}", &caps[1], NEUTRALIZED_LINK)
+        } else {
+            caps[0].to_string()
+        }
+    };
+    let text = inline_link.replace_all(&text, neutralize);
+    let text = reference_link.replace_all(&text, neutralize);
+    let text = autolink.replace_all(&text, |caps: &Captures| {
+        if is_unsafe_destination(&caps[1]) {
+            String::new()
+        } else {
+            caps[0].to_string()
+        }
+    });
+
+    text.into_owned()
+}
+
+/// Whether a link destination uses an unsafe scheme once the entity and
+/// whitespace tricks renderers undo are undone here as well
+fn is_unsafe_destination(destination: &str) -> bool {
+    let normalized: String = decode_entities(destination)
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect::<String>()
+        .to_ascii_lowercase();
+    UNSAFE_SCHEMES
+        .iter()
+        .any(|scheme| normalized.starts_with(scheme))
+}
+
+/// Decode the numeric and named character references that can spell out a
+/// scheme (`&#106;`, `&#x6A;`, `&colon;`, ...)
+fn decode_entities(text: &str) -> Cow<'_, str> {
+    static ENTITY: OnceLock<Regex> = OnceLock::new();
+    let entity = ENTITY.get_or_init(|| Regex::new(r"(?i)&(#x[0-9a-f]+|#[0-9]+|[a-z]+);?").unwrap());
+
+    entity.replace_all(text, |caps: &Captures| {
+        let name = &caps[1];
+        let decoded = if let Some(hex) = name.strip_prefix("#x").or(name.strip_prefix("#X")) {
+            u32::from_str_radix(hex, 16).ok().and_then(char::from_u32)
+        } else if let Some(dec) = name.strip_prefix('#') {
+            dec.parse().ok().and_then(char::from_u32)
+        } else {
+            match name.to_ascii_lowercase().as_str() {
+                "colon" => Some(':'),
+                "tab" => Some('\t'),
+                "newline" => Some('\n'),
+                _ => None,
+            }
+        };
+        decoded.map_or_else(|| caps[0].to_string(), String::from)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_markdown_is_unchanged() {
+        let text = "# Title\n\n*emphasis* and **strong**, `a < b`, [link](https://example.com) \
+                    and <https://example.com>\n\n[ref]: https://example.com";
+        assert_eq!(sanitize_markdown(text), text);
+    }
+
+    #[test]
+    fn test_raw_html_and_scripts_are_removed() {
+        assert_eq!(
+            sanitize_markdown("hi <script>alert(1)</script>there"),
+            "hi there"
+        );
+        assert_eq!(
+            sanitize_markdown("<img src=x onerror=alert(1)>ok <b>bold</b>"),
+            "ok bold"
+        );
+        assert_eq!(sanitize_markdown("a<!-- hidden -->b"), "ab");
+        assert_eq!(sanitize_markdown("before<script>never closed"), "before");
+        // Stripping one tag must not leave another behind
+        assert_eq!(sanitize_markdown("<<b>script>alert(1)"), "");
+    }
+
+    #[test]
+    fn test_unsafe_link_targets_are_neutralized() {
+        assert_eq!(
+            sanitize_markdown("[click](javascript:alert(1))"),
+            "[click](#)"
+        );
+        assert_eq!(
+            sanitize_markdown("[x](&#106;avascript&colon;alert(1))"),
+            "[x](#)"
+        );
+        assert_eq!(
+            sanitize_markdown("![img](DATA:text/html;base64,xyz)"),
+            "![img](#)"
+        );
+        assert_eq!(sanitize_markdown("go <javascript:alert(1)>"), "go ");
+        assert_eq!(sanitize_markdown("[ref]: vbscript:msgbox"), "[ref]: #");
+    }
+
+    #[test]
+    fn test_only_markdown_content_is_sanitized() {
+        assert_eq!(sanitize_content("text", "<b>hi</b>"), "<b>hi</b>");
+        assert_eq!(sanitize_content(CONTENT_TYPE_MARKDOWN, "<b>hi</b>"), "hi");
+    }
+
+    #[test]
+    fn test_ensure_sanitized_rejects_unsafe_markdown() {
+        assert!(ensure_sanitized(CONTENT_TYPE_MARKDOWN, Some("**safe**")).is_ok());
+        assert!(ensure_sanitized(CONTENT_TYPE_MARKDOWN, None).is_ok());
+        assert!(ensure_sanitized("text", Some("<script></script>")).is_ok());
+        assert!(matches!(
+            ensure_sanitized(CONTENT_TYPE_MARKDOWN, Some("<script></script>")),
+            Err(AppError::Validation(_))
+        ));
+    }
+}
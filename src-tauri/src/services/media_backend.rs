@@ -0,0 +1,277 @@
+//! Storage backends for content-addressed media
+//!
+//! `MediaStorageService` handles hashing, chunking and reference counting;
+//! a `MediaBackend` only has to store and return bytes keyed by their
+//! SHA256 hash. The filesystem backend is the default.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::error::{AppError, Result};
+
+/// Byte storage keyed by hex-encoded SHA256 hash
+pub trait MediaBackend: Send + Sync {
+    /// Store data under `hash`. Returns `false` if it was already present.
+    fn put(&self, hash: &str, data: &[u8], mime_type: &str) -> Result<bool>;
+
+    /// Read the full data stored under `hash`
+    fn get(&self, hash: &str) -> Result<Vec<u8>>;
+
+    /// Check whether data is stored under `hash`
+    fn exists(&self, hash: &str) -> bool;
+
+    /// Remove the data stored under `hash`. Returns `false` if it was absent.
+    fn delete(&self, hash: &str) -> Result<bool>;
+
+    /// Local filesystem path for `hash`, if this backend stores files locally
+    fn local_path(&self, _hash: &str) -> Option<PathBuf> {
+        None
+    }
+}
+
+/// Which media backend to use
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MediaBackendConfig {
+    /// Store files under `root` using the `{first-2-chars}/{hash}.{ext}` layout
+    Filesystem { root: PathBuf },
+    /// Keep files in memory only; everything is lost when the process exits
+    Memory,
+}
+
+impl MediaBackendConfig {
+    /// The default backend: a `media/` directory inside the app data dir
+    pub fn default_for(app_data_dir: &Path) -> Self {
+        MediaBackendConfig::Filesystem {
+            root: app_data_dir.join("media"),
+        }
+    }
+
+    /// Build the configured backend
+    pub fn build(&self) -> Result<Arc<dyn MediaBackend>> {
+        match self {
+            MediaBackendConfig::Filesystem { root } => {
+                Ok(Arc::new(FilesystemMediaBackend::new(root.clone())?))
+            }
+            MediaBackendConfig::Memory => Ok(Arc::new(MemoryMediaBackend::default())),
+        }
+    }
+}
+
+/// Media backend storing files on a local (or mounted) filesystem.
+///
+/// File layout: `{root}/{first-2-chars-of-hash}/{hash}.{ext}`
+pub struct FilesystemMediaBackend {
+    root: PathBuf,
+}
+
+impl FilesystemMediaBackend {
+    /// Create the backend, creating `root` if it does not already exist
+    pub fn new(root: PathBuf) -> Result<Self> {
+        std::fs::create_dir_all(&root)?;
+        Ok(Self { root })
+    }
+
+    /// Resolve the on-disk path for a hash, trying known extensions.
+    fn resolve_path(&self, hash: &str) -> Option<PathBuf> {
+        if hash.len() < 2 {
+            return None;
+        }
+        let dir_path = self.root.join(&hash[..2]);
+
+        KNOWN_EXTENSIONS
+            .iter()
+            .map(|ext| dir_path.join(format!("{}.{}", hash, ext)))
+            .find(|candidate| candidate.exists())
+    }
+}
+
+impl MediaBackend for FilesystemMediaBackend {
+    fn put(&self, hash: &str, data: &[u8], mime_type: &str) -> Result<bool> {
+        if self.resolve_path(hash).is_some() {
+            return Ok(false);
+        }
+
+        let dir_path = self.root.join(&hash[..2]);
+        std::fs::create_dir_all(&dir_path)?;
+
+        let file_path = dir_path.join(format!("{}.{}", hash, mime_to_extension(mime_type)));
+        std::fs::write(&file_path, data)?;
+        Ok(true)
+    }
+
+    fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        let file_path = self.resolve_path(hash).ok_or_else(|| {
+            AppError::NotFound(format!("Media file not found for hash: {}", hash))
+        })?;
+        Ok(std::fs::read(&file_path)?)
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.resolve_path(hash).is_some()
+    }
+
+    fn delete(&self, hash: &str) -> Result<bool> {
+        let Some(file_path) = self.resolve_path(hash) else {
+            return Ok(false);
+        };
+        std::fs::remove_file(&file_path)?;
+
+        // Try to remove the parent sub-directory if it is now empty
+        if let Some(parent) = file_path.parent() {
+            let _ = std::fs::remove_dir(parent); // ignore error (dir may not be empty)
+        }
+        Ok(true)
+    }
+
+    fn local_path(&self, hash: &str) -> Option<PathBuf> {
+        self.resolve_path(hash)
+    }
+}
+
+/// Media backend holding files in memory, used for ephemeral sessions
+#[derive(Default)]
+pub struct MemoryMediaBackend {
+    files: Mutex<HashMap<String, Vec<u8>>>,
+}
+
+impl MemoryMediaBackend {
+    fn files(&self) -> std::sync::MutexGuard<'_, HashMap<String, Vec<u8>>> {
+        self.files
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl MediaBackend for MemoryMediaBackend {
+    fn put(&self, hash: &str, data: &[u8], _mime_type: &str) -> Result<bool> {
+        let mut files = self.files();
+        if files.contains_key(hash) {
+            return Ok(false);
+        }
+        files.insert(hash.to_string(), data.to_vec());
+        Ok(true)
+    }
+
+    fn get(&self, hash: &str) -> Result<Vec<u8>> {
+        self.files()
+            .get(hash)
+            .cloned()
+            .ok_or_else(|| AppError::NotFound(format!("Media file not found for hash: {}", hash)))
+    }
+
+    fn exists(&self, hash: &str) -> bool {
+        self.files().contains_key(hash)
+    }
+
+    fn delete(&self, hash: &str) -> Result<bool> {
+        Ok(self.files().remove(hash).is_some())
+    }
+}
+
+/// Known file extensions to try when resolving a hash to a path.
+const KNOWN_EXTENSIONS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "svg", "bmp", "ico", // images
+    "mp4", "webm", "mov", "avi", "mkv", // video
+    "ogg", "weba", "m4a", "mp3", "wav", // audio
+    "bin", // fallback
+];
+
+/// Map a MIME type to a file extension.
+pub(crate) fn mime_to_extension(mime_type: &str) -> &'static str {
+    match mime_type {
+        "image/jpeg" => "jpg",
+        "image/png" => "png",
+        "image/gif" => "gif",
+        "image/webp" => "webp",
+        "image/svg+xml" => "svg",
+        "image/bmp" => "bmp",
+        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
+        "video/mp4" => "mp4",
+        "video/webm" => "webm",
+        "video/quicktime" => "mov",
+        "video/x-msvideo" => "avi",
+        "video/x-matroska" => "mkv",
+        "audio/ogg" => "ogg",
+        // Distinct from video so the MIME type survives a round trip
+        "audio/webm" => "weba",
+        "audio/mp4" => "m4a",
+        "audio/mpeg" => "mp3",
+        "audio/wav" => "wav",
+        _ => "bin",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HASH: &str = "ab00000000000000000000000000000000000000000000000000000000000000";
+
+    #[test]
+    fn test_mime_to_extension() {
+        assert_eq!(mime_to_extension("image/jpeg"), "jpg");
+        assert_eq!(mime_to_extension("image/png"), "png");
+        assert_eq!(mime_to_extension("video/mp4"), "mp4");
+        assert_eq!(mime_to_extension("application/octet-stream"), "bin");
+    }
+
+    #[test]
+    fn test_filesystem_put_get_delete() {
+        let tmp = tempfile::tempdir().unwrap();
+        let backend = FilesystemMediaBackend::new(tmp.path().to_path_buf()).unwrap();
+
+        assert!(!backend.exists(HASH));
+        assert!(backend.put(HASH, b"data", "image/png").unwrap());
+        assert!(!backend.put(HASH, b"data", "image/png").unwrap());
+
+        assert!(backend.exists(HASH));
+        assert_eq!(backend.get(HASH).unwrap(), b"data");
+
+        let path = backend.local_path(HASH).unwrap();
+        assert_eq!(path, tmp.path().join("ab").join(format!("{}.png", HASH)));
+
+        assert!(backend.delete(HASH).unwrap());
+        assert!(!backend.exists(HASH));
+        assert!(!backend.delete(HASH).unwrap());
+        assert!(backend.get(HASH).is_err());
+    }
+
+    #[test]
+    fn test_config_builds_filesystem_backend_at_custom_root() {
+        let tmp = tempfile::tempdir().unwrap();
+        let root = tmp.path().join("external").join("media");
+        let config = MediaBackendConfig::Filesystem { root: root.clone() };
+
+        let backend = config.build().unwrap();
+        backend.put(HASH, b"data", "image/jpeg").unwrap();
+
+        assert!(root.join("ab").join(format!("{}.jpg", HASH)).exists());
+    }
+
+    #[test]
+    fn test_memory_backend_put_get_delete() {
+        let backend = MediaBackendConfig::Memory.build().unwrap();
+
+        assert!(!backend.exists(HASH));
+        assert!(backend.put(HASH, b"data", "image/png").unwrap());
+        assert!(!backend.put(HASH, b"data", "image/png").unwrap());
+        assert_eq!(backend.get(HASH).unwrap(), b"data");
+        assert!(backend.local_path(HASH).is_none());
+
+        assert!(backend.delete(HASH).unwrap());
+        assert!(!backend.exists(HASH));
+        assert!(backend.get(HASH).is_err());
+    }
+
+    #[test]
+    fn test_default_config_uses_media_subdir() {
+        let config = MediaBackendConfig::default_for(Path::new("/data"));
+        assert_eq!(
+            config,
+            MediaBackendConfig::Filesystem {
+                root: PathBuf::from("/data/media")
+            }
+        );
+    }
+}
@@ -1,44 +1,52 @@
 //! Media storage service with SHA256 content-addressing
 //!
-//! Stores media files on disk using their SHA256 hash as the filename,
-//! organized in a two-level directory structure to avoid having too many
-//! files in a single directory.
+//! Media is addressed by the SHA256 hash of its content. The bytes
+//! themselves live in a pluggable `MediaBackend`; by default files are
+//! stored on disk in a two-level directory structure to avoid having too
+//! many files in a single directory.
 //!
-//! File layout: `{app_data}/media/{first-2-chars-of-hash}/{hash}.{ext}`
+//! Default file layout: `{app_data}/media/{first-2-chars-of-hash}/{hash}.{ext}`
 
 use sha2::{Digest, Sha256};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use crate::db::Database;
+use crate::db::{Database, MessagesRepository};
 use crate::error::{AppError, Result};
+use crate::services::media_backend::{MediaBackend, MediaBackendConfig};
 
 /// Default chunk size for P2P media transfer (256 KB)
 const DEFAULT_CHUNK_SIZE: u32 = 256 * 1024;
 
 /// Service for content-addressed media file storage
 pub struct MediaStorageService {
-    media_dir: PathBuf,
+    backend: Arc<dyn MediaBackend>,
     db: Arc<Database>,
 }
 
 impl MediaStorageService {
-    /// Create a new media storage service.
+    /// Create a new media storage service using the default filesystem backend.
     ///
     /// The `media/` directory is created under `app_data_dir` if it does not
     /// already exist.
     pub fn new(app_data_dir: &Path, db: Arc<Database>) -> Result<Self> {
-        let media_dir = app_data_dir.join("media");
-        std::fs::create_dir_all(&media_dir)?;
+        Self::from_config(&MediaBackendConfig::default_for(app_data_dir), db)
+    }
+
+    /// Create a media storage service using the configured backend
+    pub fn from_config(config: &MediaBackendConfig, db: Arc<Database>) -> Result<Self> {
+        Ok(Self::with_backend(config.build()?, db))
+    }
 
-        Ok(Self { media_dir, db })
+    /// Create a media storage service on top of an existing backend
+    pub fn with_backend(backend: Arc<dyn MediaBackend>, db: Arc<Database>) -> Self {
+        Self { backend, db }
     }
 
     /// Store media file data, returning the hex-encoded SHA256 hash.
     ///
-    /// If a file with the same hash already exists on disk it is not
-    /// overwritten -- the existing path is reused (content-addressing
-    /// guarantees identical content).
+    /// If content with the same hash is already stored it is not
+    /// overwritten (content-addressing guarantees identical content).
     pub fn store_media(&self, file_data: &[u8], mime_type: &str) -> Result<String> {
         // Compute SHA256 hash
         let mut hasher = Sha256::new();
@@ -46,20 +54,8 @@ impl MediaStorageService {
         let hash_bytes = hasher.finalize();
         let hash = hex::encode(hash_bytes);
 
-        // Determine file extension from MIME type
-        let ext = mime_to_extension(mime_type);
-
-        // Build storage path: media/{first2}/{hash}.{ext}
-        let subdir = &hash[..2];
-        let dir_path = self.media_dir.join(subdir);
-        std::fs::create_dir_all(&dir_path)?;
-
-        let file_name = format!("{}.{}", hash, ext);
-        let file_path = dir_path.join(&file_name);
-
-        // Only write if the file doesn't already exist (idempotent)
-        if !file_path.exists() {
-            std::fs::write(&file_path, file_data)?;
+        // Only write if the content isn't already stored (idempotent)
+        if self.backend.put(&hash, file_data, mime_type)? {
             tracing::info!(
                 hash = %hash,
                 size = file_data.len(),
@@ -75,9 +71,8 @@ impl MediaStorageService {
 
     /// Read the full media file for a given hash.
     pub fn get_media(&self, hash: &str) -> Result<Vec<u8>> {
-        let file_path = self.resolve_path(hash)?;
-        let data = std::fs::read(&file_path)?;
-        Ok(data)
+        validate_hash(hash)?;
+        self.backend.get(hash)
     }
 
     /// Read a chunk of a media file for P2P transfer.
@@ -113,116 +108,80 @@ impl MediaStorageService {
         Ok((chunk, total_chunks))
     }
 
-    /// Check whether a media file exists on disk.
+    /// Check whether a media file is stored.
     pub fn has_media(&self, hash: &str) -> bool {
-        self.resolve_path(hash).is_ok()
+        validate_hash(hash).is_ok() && self.backend.exists(hash)
     }
 
-    /// Delete a media file from disk if no other `post_media` rows reference
-    /// the same hash.
+    /// Delete a media file from storage if no other `post_media` or
+    /// `message_media` rows reference the same hash.
     pub fn delete_media_if_orphaned(&self, hash: &str) -> Result<()> {
-        // Count how many post_media rows still reference this hash
+        validate_hash(hash)?;
+
+        // Count how many post_media and message_media rows still reference this hash
         let count: i64 = self
             .db
             .with_connection(|conn| {
                 conn.query_row(
-                    "SELECT COUNT(*) FROM post_media WHERE media_hash = ?",
+                    "SELECT (SELECT COUNT(*) FROM post_media WHERE media_hash = ?1)
+                          + (SELECT COUNT(*) FROM message_media WHERE media_hash = ?1)",
                     [hash],
                     |row| row.get(0),
                 )
             })
             .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
-        if count == 0 {
-            // No references remain -- safe to delete the file
-            if let Ok(file_path) = self.resolve_path(hash) {
-                std::fs::remove_file(&file_path)?;
-                tracing::info!(hash = %hash, "Deleted orphaned media file");
-
-                // Try to remove the parent sub-directory if it is now empty
-                if let Some(parent) = file_path.parent() {
-                    let _ = std::fs::remove_dir(parent); // ignore error (dir may not be empty)
-                }
-            }
+        // No references remain -- safe to delete the file
+        if count == 0 && self.backend.delete(hash)? {
+            tracing::info!(hash = %hash, "Deleted orphaned media file");
         }
 
         Ok(())
     }
 
+    /// Delete the audio of voice notes whose messages are gone, unless
+    /// something else still uses it. Returns how many voice notes were dropped.
+    pub fn prune_message_media(&self) -> Result<usize> {
+        let hashes = MessagesRepository::take_orphaned_message_media(&self.db)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        for hash in &hashes {
+            self.delete_media_if_orphaned(hash)?;
+        }
+        Ok(hashes.len())
+    }
+
     /// Get the absolute filesystem path for a media file.
     ///
-    /// This is used by the `get_media_url` command to return a path the
-    /// frontend can load via Tauri's asset protocol.
+    /// Only available when the backend stores files locally.
     pub fn get_media_path(&self, hash: &str) -> Result<PathBuf> {
-        self.resolve_path(hash)
-    }
-
-    // ── private helpers ──────────────────────────────────────────────
-
-    /// Resolve the on-disk path for a hash, trying known extensions.
-    fn resolve_path(&self, hash: &str) -> Result<PathBuf> {
-        // Validate hash looks reasonable (hex, 64 chars for SHA256)
-        if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
-            return Err(AppError::InvalidData(format!(
-                "Invalid media hash: {}",
-                hash
-            )));
-        }
-
-        let subdir = &hash[..2];
-        let dir_path = self.media_dir.join(subdir);
-
-        // Try common extensions
-        for ext in KNOWN_EXTENSIONS {
-            let candidate = dir_path.join(format!("{}.{}", hash, ext));
-            if candidate.exists() {
-                return Ok(candidate);
-            }
-        }
-
-        Err(AppError::NotFound(format!(
-            "Media file not found for hash: {}",
-            hash
-        )))
+        validate_hash(hash)?;
+        self.backend
+            .local_path(hash)
+            .ok_or_else(|| AppError::NotFound(format!("Media file not found for hash: {}", hash)))
     }
 }
 
-/// Known file extensions to try when resolving a hash to a path.
-const KNOWN_EXTENSIONS: &[&str] = &[
-    "jpg", "jpeg", "png", "gif", "webp", "svg", "bmp", "ico", // images
-    "mp4", "webm", "mov", "avi", "mkv", // video
-    "bin", // fallback
-];
-
-/// Map a MIME type to a file extension.
-fn mime_to_extension(mime_type: &str) -> &'static str {
-    match mime_type {
-        "image/jpeg" => "jpg",
-        "image/png" => "png",
-        "image/gif" => "gif",
-        "image/webp" => "webp",
-        "image/svg+xml" => "svg",
-        "image/bmp" => "bmp",
-        "image/x-icon" | "image/vnd.microsoft.icon" => "ico",
-        "video/mp4" => "mp4",
-        "video/webm" => "webm",
-        "video/quicktime" => "mov",
-        "video/x-msvideo" => "avi",
-        "video/x-matroska" => "mkv",
-        _ => "bin",
+/// Validate that a hash looks reasonable (hex, 64 chars for SHA256)
+fn validate_hash(hash: &str) -> Result<()> {
+    if hash.len() != 64 || !hash.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Err(AppError::InvalidData(format!(
+            "Invalid media hash: {}",
+            hash
+        )));
     }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::services::media_backend::MemoryMediaBackend;
 
-    #[test]
-    fn test_mime_to_extension() {
-        assert_eq!(mime_to_extension("image/jpeg"), "jpg");
-        assert_eq!(mime_to_extension("image/png"), "png");
-        assert_eq!(mime_to_extension("video/mp4"), "mp4");
-        assert_eq!(mime_to_extension("application/octet-stream"), "bin");
+    fn create_in_memory_service() -> (Arc<MemoryMediaBackend>, MediaStorageService) {
+        let backend = Arc::new(MemoryMediaBackend::default());
+        let db = Arc::new(Database::in_memory().unwrap());
+        let service = MediaStorageService::with_backend(backend.clone(), db);
+        (backend, service)
     }
 
     #[test]
@@ -289,4 +248,62 @@ mod tests {
         assert!(!service.has_media("not-a-valid-hash"));
         assert!(service.get_media("tooshort").is_err());
     }
+
+    #[test]
+    fn test_in_memory_backend_store_and_chunk() {
+        let (backend, service) = create_in_memory_service();
+
+        let hash = service.store_media(b"0123456789", "image/png").unwrap();
+        assert!(backend.exists(&hash));
+        assert!(service.has_media(&hash));
+        assert_eq!(service.get_media(&hash).unwrap(), b"0123456789");
+
+        let (chunk, total) = service.get_media_chunk(&hash, 1, 4).unwrap();
+        assert_eq!(total, 3);
+        assert_eq!(chunk, b"4567");
+
+        // No local path for a non-filesystem backend
+        assert!(service.get_media_path(&hash).is_err());
+    }
+
+    #[test]
+    fn test_in_memory_backend_delete_orphaned() {
+        let (backend, service) = create_in_memory_service();
+
+        let hash = service.store_media(b"orphan", "image/png").unwrap();
+        service.delete_media_if_orphaned(&hash).unwrap();
+
+        assert!(!backend.exists(&hash));
+        assert!(!service.has_media(&hash));
+    }
+
+    #[test]
+    fn test_prune_message_media_keeps_shared_audio() {
+        let (backend, service) = create_in_memory_service();
+        let hash = service.store_media(b"OggS voice", "audio/ogg").unwrap();
+
+        // Two voice notes forwarding the same clip; neither message exists
+        // any more, so both rows are orphaned
+        for message_id in ["msg-1", "msg-2"] {
+            MessagesRepository::insert_message_media(
+                &service.db,
+                &crate::db::MessageMedia {
+                    message_id: message_id.to_string(),
+                    media_hash: hash.clone(),
+                    mime_type: "audio/ogg".to_string(),
+                    size_bytes: 10,
+                    duration_ms: 1_000,
+                },
+            )
+            .unwrap();
+        }
+
+        // Still referenced, so a direct delete leaves it alone
+        service.delete_media_if_orphaned(&hash).unwrap();
+        assert!(backend.exists(&hash));
+
+        assert_eq!(service.prune_message_media().unwrap(), 2);
+        assert!(!backend.exists(&hash));
+        assert_eq!(service.prune_message_media().unwrap(), 0);
+    }
 }
@@ -1,27 +1,86 @@
 //! Messaging service for sending and receiving direct messages
 
 use ed25519_dalek::VerifyingKey;
-use std::sync::Arc;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 use uuid::Uuid;
-use x25519_dalek::PublicKey as X25519Public;
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret as X25519Secret};
 
+use crate::db::repositories::settings_repo::{
+    SETTING_MESSAGE_CIPHER, SETTING_MESSAGE_MAX_SEND_ATTEMPTS, SETTING_MESSAGE_NONCE_STRATEGY,
+    SETTING_MESSAGE_RETENTION_DAYS,
+};
+use crate::db::repositories::SettingsRepository;
 use crate::db::{
-    Capability, Conversation, Database, MessageData, MessageStatus, MessagesRepository,
-    RecordMessageEventParams,
+    Capability, Conversation, ConversationRetention, Database, HeldMessageChange, Message,
+    MessageCursor, MessageData, MessageMedia, MessageStatus, MessagesRepository, PendingOutbound,
+    PendingOutboundKind, PendingOutboundRepository, RatchetRepository, RecordMessageEventParams,
 };
 use crate::error::{AppError, Result};
-use crate::p2p::protocols::messaging::derive_conversation_id;
+use crate::models::LocalIdentity;
+use crate::p2p::protocols::messaging::{derive_conversation_id, MAX_READ_RECEIPT_MESSAGES};
+use crate::services::clock::{system_clock, Clock};
 use crate::services::{
-    verify, ContactsService, CryptoService, IdentityService, PermissionsService, Signable,
-    SignableDirectMessage, SignableMessageAck,
+    decode_canonical, markdown, verify, ContactsService, CryptoService, FeatureFlag,
+    FeatureFlagsService, IdentityService, MessageCipher, PermissionsService, RatchetHeader,
+    RatchetSession, Signable, SignableDirectMessage, SignableMessageAck, SignableMessageDelete,
+    SignableMessageEdit, SignableReadReceipt, SignaturePolicy, VoiceNote, CONTENT_TYPE_VOICE,
+    CURRENT_PROTOCOL_VERSION, CURRENT_SIG_VERSION, NONCE_SALT_LEN, PROTOCOL_VERSION_RATCHET,
 };
 
+/// How the AES-GCM nonce for outgoing messages is derived
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum NonceStrategy {
+    /// Mix a random per-message salt, stored and sent with the message, into
+    /// the counter. Nonces stay unique even if the send counter resets.
+    #[default]
+    SaltedCounter,
+    /// Use the conversation send counter alone. Only safe while the counter
+    /// never regresses; kept for peers that predate salted nonces.
+    Counter,
+}
+
+impl NonceStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NonceStrategy::SaltedCounter => "salted_counter",
+            NonceStrategy::Counter => "counter",
+        }
+    }
+}
+
+impl std::str::FromStr for NonceStrategy {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "salted_counter" => Ok(NonceStrategy::SaltedCounter),
+            "counter" => Ok(NonceStrategy::Counter),
+            _ => Err(format!("Unknown nonce strategy: {}", s)),
+        }
+    }
+}
+
+/// Outcome of one message retention sweep
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetentionSweepSummary {
+    pub messages_deleted: i64,
+    pub conversations_pruned: usize,
+}
+
 /// Service for managing direct messages
 pub struct MessagingService {
     db: Arc<Database>,
     identity_service: Arc<IdentityService>,
     contacts_service: Arc<ContactsService>,
     permissions_service: Arc<PermissionsService>,
+    /// Serializes load-modify-save of ratchet sessions
+    ratchet_lock: Mutex<()>,
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
 }
 
 /// A decrypted message for the UI
@@ -40,6 +99,69 @@ pub struct DecryptedMessage {
     pub status: String,
     pub is_outgoing: bool,
     pub edited_at: Option<i64>,
+    /// When the sender deleted the message; `content` is then empty
+    pub deleted_at: Option<i64>,
+    pub reply_preview: Option<ReplyPreview>,
+    /// The audio of a voice note message
+    pub voice: Option<VoiceNote>,
+}
+
+/// A page of a conversation, oldest message first
+#[derive(Debug, Clone)]
+pub struct MessagePage {
+    pub messages: Vec<DecryptedMessage>,
+    /// Where the next (older) page starts; `None` once the conversation's
+    /// first message is in this page
+    pub next_cursor: Option<MessageCursor>,
+}
+
+/// Messages returned per page when the caller doesn't say
+pub const DEFAULT_MESSAGE_PAGE_SIZE: i64 = 50;
+
+/// Most messages returned in one page; larger requests are clamped
+pub const MAX_MESSAGE_PAGE_SIZE: i64 = 200;
+
+/// Search results returned when the caller doesn't say
+pub const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Most search results returned; larger requests are clamped
+pub const MAX_SEARCH_LIMIT: i64 = 200;
+
+/// Messages decrypted per batch while searching
+const SEARCH_BATCH_SIZE: i64 = 200;
+
+/// Times an undelivered message is sent before giving up, unless configured
+pub const DEFAULT_MAX_SEND_ATTEMPTS: u32 = 10;
+
+/// Wait after the first failed delivery; it doubles with each further failure
+const OUTBOUND_RETRY_BASE_SECS: i64 = 30;
+
+/// Longest wait between delivery attempts
+const OUTBOUND_RETRY_MAX_SECS: i64 = 60 * 60;
+
+/// Edits and deletes held per peer for messages that haven't arrived yet
+const MAX_HELD_CHANGES_PER_PEER: i64 = 256;
+
+/// Maximum number of characters kept in a quoted message snippet
+pub const REPLY_SNIPPET_MAX_CHARS: usize = 120;
+
+/// Placeholder snippet shown when the quoted message no longer exists
+pub const DELETED_MESSAGE_SNIPPET: &str = "[deleted message]";
+
+/// Placeholder preview shown when the last message can't be decrypted
+pub const ENCRYPTED_MESSAGE_PREVIEW: &str = "[encrypted]";
+
+/// Preview shown for a voice note without a caption
+pub const VOICE_NOTE_PREVIEW: &str = "[voice note]";
+
+/// Denormalized preview of the message a reply quotes
+#[derive(Debug, Clone)]
+pub struct ReplyPreview {
+    pub message_id: String,
+    /// Sender of the quoted message (None if the message was deleted)
+    pub sender_peer_id: Option<String>,
+    pub snippet: String,
+    pub is_deleted: bool,
 }
 
 /// A message ready to be sent over the network
@@ -56,6 +178,51 @@ pub struct OutgoingMessage {
     pub lamport_clock: u64,
     pub timestamp: i64,
     pub signature: Vec<u8>,
+    pub nonce_salt: Option<Vec<u8>>,
+    pub protocol_version: u32,
+    pub ratchet: Option<RatchetHeader>,
+    /// Cipher the content is encrypted with, also used for the stored copy
+    pub cipher: MessageCipher,
+    pub voice: Option<VoiceNote>,
+}
+
+/// A signed edit of one of our messages, ready to be sent to the recipient
+#[derive(Debug, Clone)]
+pub struct OutgoingMessageEdit {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub sender_peer_id: String,
+    pub recipient_peer_id: String,
+    /// The new content, encrypted as a new message to the recipient would be
+    pub content_encrypted: Vec<u8>,
+    /// Empty when the content is ratcheted
+    pub nonce_salt: Vec<u8>,
+    pub lamport_clock: u64,
+    pub edited_at: i64,
+    pub ratchet: Option<RatchetHeader>,
+    pub signature: Vec<u8>,
+}
+
+/// A signed tombstone for one of our messages, ready to be sent to the recipient
+#[derive(Debug, Clone)]
+pub struct OutgoingMessageDelete {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub sender_peer_id: String,
+    pub recipient_peer_id: String,
+    pub lamport_clock: u64,
+    pub deleted_at: i64,
+    pub signature: Vec<u8>,
+}
+
+/// A sealed message with what gets stored locally for it
+struct SealedMessage {
+    outgoing: OutgoingMessage,
+    payload_cbor: Vec<u8>,
+    /// Content encrypted under the conversation key. Differs from the sent
+    /// content for ratcheted messages, whose message key is discarded.
+    stored_content: Vec<u8>,
+    stored_nonce_salt: Option<Vec<u8>>,
 }
 
 /// Parameters for processing an incoming message from the network
@@ -71,6 +238,16 @@ pub struct IncomingMessageParams<'a> {
     pub lamport_clock: u64,
     pub timestamp: i64,
     pub signature: &'a [u8],
+    pub nonce_salt: Option<&'a [u8]>,
+    /// Signature scheme version the sender signed with
+    pub sig_version: u32,
+    /// Messaging protocol version the sender announced
+    pub protocol_version: u32,
+    pub ratchet: Option<&'a RatchetHeader>,
+    /// Cipher identifier the sender announced; unknown ones are rejected
+    pub cipher: &'a str,
+    /// Voice note the message carries, required for `voice` content
+    pub voice: Option<&'a VoiceNote>,
 }
 
 impl MessagingService {
@@ -86,7 +263,305 @@ impl MessagingService {
             identity_service,
             contacts_service,
             permissions_service,
+            ratchet_lock: Mutex::new(()),
+            clock: system_clock(),
+        }
+    }
+
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
+    /// The clock this service reads the time from
+    pub fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /// Get the configured nonce strategy for outgoing messages
+    pub fn get_nonce_strategy(&self) -> Result<NonceStrategy> {
+        Ok(
+            SettingsRepository::get(&self.db, SETTING_MESSAGE_NONCE_STRATEGY)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .and_then(|value| value.parse().ok())
+                .unwrap_or_default(),
+        )
+    }
+
+    /// Persist the nonce strategy used for outgoing messages
+    pub fn set_nonce_strategy(&self, strategy: NonceStrategy) -> Result<()> {
+        SettingsRepository::set(&self.db, SETTING_MESSAGE_NONCE_STRATEGY, strategy.as_str())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the cipher preferred for outgoing messages
+    pub fn get_message_cipher(&self) -> Result<MessageCipher> {
+        Ok(SettingsRepository::get(&self.db, SETTING_MESSAGE_CIPHER)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .and_then(|value| value.parse().ok())
+            .unwrap_or_default())
+    }
+
+    /// Persist the cipher preferred for outgoing messages
+    pub fn set_message_cipher(&self, cipher: MessageCipher) -> Result<()> {
+        SettingsRepository::set(&self.db, SETTING_MESSAGE_CIPHER, cipher.as_str())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Pick the cipher for a statically keyed message to a peer.
+    ///
+    /// The preferred cipher is only used once the peer has announced support
+    /// for it; everyone else, including peers that never announced any,
+    /// gets AES-256-GCM.
+    fn cipher_for_peer(&self, peer_id: &str) -> Result<MessageCipher> {
+        let preferred = self.get_message_cipher()?;
+        if preferred == MessageCipher::default() {
+            return Ok(preferred);
+        }
+        let supported = self
+            .contacts_service
+            .get_supported_ciphers(peer_id)?
+            .unwrap_or_default();
+        Ok(if supported.iter().any(|c| c == preferred.as_str()) {
+            preferred
+        } else {
+            MessageCipher::default()
+        })
+    }
+
+    /// X25519 key a conversation with `peer_id` is keyed to: the contact's,
+    /// or our own for notes to self
+    fn conversation_x25519_public(
+        &self,
+        identity: &LocalIdentity,
+        peer_id: &str,
+    ) -> Result<Vec<u8>> {
+        if peer_id == identity.peer_id {
+            return Ok(identity.x25519_public.clone());
+        }
+        self.contacts_service
+            .get_x25519_public(peer_id)?
+            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))
+    }
+
+    /// Whether `peer_id` is our own, making the conversation notes to self
+    pub fn is_note_to_self(&self, peer_id: &str) -> Result<bool> {
+        Ok(self
+            .identity_service
+            .get_identity()?
+            .is_some_and(|identity| identity.peer_id == peer_id))
+    }
+
+    /// Get the global message retention window in days (`None` keeps messages forever)
+    pub fn get_message_retention(&self) -> Result<Option<u32>> {
+        Ok(
+            SettingsRepository::get(&self.db, SETTING_MESSAGE_RETENTION_DAYS)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .and_then(|value| value.parse().ok()),
+        )
+    }
+
+    /// Set the global message retention window in days (`None` disables pruning)
+    pub fn set_message_retention(&self, days: Option<u32>) -> Result<()> {
+        match days {
+            Some(0) => Err(AppError::Validation(
+                "Retention must be at least one day".to_string(),
+            )),
+            Some(days) => {
+                SettingsRepository::set(&self.db, SETTING_MESSAGE_RETENTION_DAYS, &days.to_string())
+                    .map_err(|e| AppError::DatabaseString(e.to_string()))
+            }
+            None => SettingsRepository::delete(&self.db, SETTING_MESSAGE_RETENTION_DAYS)
+                .map(|_| ())
+                .map_err(|e| AppError::DatabaseString(e.to_string())),
+        }
+    }
+
+    /// Get how many times an undelivered message is sent before giving up
+    pub fn get_max_send_attempts(&self) -> Result<u32> {
+        Ok(
+            SettingsRepository::get(&self.db, SETTING_MESSAGE_MAX_SEND_ATTEMPTS)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(DEFAULT_MAX_SEND_ATTEMPTS),
+        )
+    }
+
+    /// Set how many times an undelivered message is sent before giving up
+    pub fn set_max_send_attempts(&self, attempts: u32) -> Result<()> {
+        if attempts == 0 {
+            return Err(AppError::Validation(
+                "Messages must be sent at least once".to_string(),
+            ));
+        }
+        SettingsRepository::set(
+            &self.db,
+            SETTING_MESSAGE_MAX_SEND_ATTEMPTS,
+            &attempts.to_string(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the retention override for the conversation with a peer
+    pub fn get_conversation_retention(
+        &self,
+        peer_id: &str,
+    ) -> Result<Option<ConversationRetention>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
+        let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
+
+        MessagesRepository::get_conversation_retention(&self.db, &conversation_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Override retention for the conversation with a peer.
+    ///
+    /// `never_delete` opts the conversation out of pruning entirely. With
+    /// neither a window nor the opt-out, the override is removed and the
+    /// global window applies again.
+    pub fn set_conversation_retention(
+        &self,
+        peer_id: &str,
+        retention_days: Option<u32>,
+        never_delete: bool,
+    ) -> Result<()> {
+        if retention_days == Some(0) {
+            return Err(AppError::Validation(
+                "Retention must be at least one day".to_string(),
+            ));
+        }
+
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
+        let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
+
+        if retention_days.is_none() && !never_delete {
+            MessagesRepository::clear_conversation_retention(&self.db, &conversation_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            return Ok(());
+        }
+
+        MessagesRepository::set_conversation_retention(
+            &self.db,
+            &ConversationRetention {
+                conversation_id,
+                retention_days,
+                never_delete,
+            },
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Delete messages older than their conversation's retention window.
+    ///
+    /// Conversations without an override use the global window; those opted
+    /// out with `never_delete` are skipped.
+    pub fn sweep_expired_messages(&self, now: i64) -> Result<RetentionSweepSummary> {
+        const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+
+        let default_days = self.get_message_retention()?;
+        let conversation_ids = MessagesRepository::get_conversation_ids(&self.db)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        let mut summary = RetentionSweepSummary::default();
+        for conversation_id in conversation_ids {
+            let retention =
+                MessagesRepository::get_conversation_retention(&self.db, &conversation_id)
+                    .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            let days = match retention {
+                Some(r) if r.never_delete => continue,
+                Some(r) => r.retention_days.or(default_days),
+                None => default_days,
+            };
+            let Some(days) = days else {
+                continue;
+            };
+
+            let cutoff = now - i64::from(days) * SECONDS_PER_DAY;
+            let deleted =
+                MessagesRepository::delete_messages_before(&self.db, &conversation_id, cutoff)
+                    .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            if deleted > 0 {
+                summary.messages_deleted += deleted;
+                summary.conversations_pruned += 1;
+            }
+        }
+
+        if summary.messages_deleted > 0 {
+            tracing::info!(
+                "Retention sweep deleted {} messages from {} conversations",
+                summary.messages_deleted,
+                summary.conversations_pruned
+            );
         }
+        Ok(summary)
+    }
+
+    /// Encrypt content for storage or sending, returning the ciphertext and
+    /// the salt it was encrypted with (None for counter-only nonces)
+    fn encrypt_content(
+        conv_key: &[u8; 32],
+        plaintext: &[u8],
+        nonce_counter: u64,
+        strategy: NonceStrategy,
+        cipher: MessageCipher,
+    ) -> Result<(Vec<u8>, Option<Vec<u8>>)> {
+        match strategy {
+            NonceStrategy::SaltedCounter => {
+                let salt = CryptoService::generate_nonce_salt();
+                let nonce = CryptoService::nonce_from_salted_counter(&salt, nonce_counter);
+                let ciphertext =
+                    CryptoService::encrypt_with_cipher(cipher, conv_key, plaintext, &nonce)?;
+                Ok((ciphertext, Some(salt.to_vec())))
+            }
+            NonceStrategy::Counter => Ok((
+                CryptoService::encrypt_with_cipher(
+                    cipher,
+                    conv_key,
+                    plaintext,
+                    &CryptoService::nonce_from_counter(nonce_counter),
+                )?,
+                None,
+            )),
+        }
+    }
+
+    /// Decrypt content using the counter and, if present, the stored salt
+    fn decrypt_bytes(
+        conv_key: &[u8; 32],
+        ciphertext: &[u8],
+        nonce_counter: u64,
+        nonce_salt: Option<&[u8]>,
+        cipher: MessageCipher,
+    ) -> Result<Vec<u8>> {
+        let nonce = match nonce_salt {
+            Some(salt) => {
+                let salt: &[u8; NONCE_SALT_LEN] = salt
+                    .try_into()
+                    .map_err(|_| AppError::Crypto("Invalid nonce salt length".to_string()))?;
+                CryptoService::nonce_from_salted_counter(salt, nonce_counter)
+            }
+            None => CryptoService::nonce_from_counter(nonce_counter),
+        };
+        CryptoService::decrypt_with_cipher(cipher, conv_key, ciphertext, &nonce)
+    }
+
+    /// Decrypt a stored message's content with the cipher recorded for it
+    fn decrypt_message_bytes(conv_key: &[u8; 32], msg: &Message) -> Result<Vec<u8>> {
+        let cipher = msg.cipher.parse().map_err(AppError::Crypto)?;
+        Self::decrypt_bytes(
+            conv_key,
+            &msg.content_encrypted,
+            msg.nonce_counter,
+            msg.nonce_salt.as_deref(),
+            cipher,
+        )
     }
 
     /// Send a new message to a peer
@@ -96,6 +571,41 @@ impl MessagingService {
         content: &str,
         content_type: &str,
         reply_to: Option<&str>,
+    ) -> Result<OutgoingMessage> {
+        if content_type == CONTENT_TYPE_VOICE {
+            return Err(AppError::Validation(
+                "Voice notes must be sent with their audio".to_string(),
+            ));
+        }
+        self.send_with_voice(recipient_peer_id, content, content_type, reply_to, None)
+    }
+
+    /// Send a voice note whose audio is already in media storage.
+    /// `caption` is sent as the (encrypted) message content.
+    pub fn send_voice_note(
+        &self,
+        recipient_peer_id: &str,
+        voice: &VoiceNote,
+        caption: &str,
+        reply_to: Option<&str>,
+    ) -> Result<OutgoingMessage> {
+        voice.validate()?;
+        self.send_with_voice(
+            recipient_peer_id,
+            caption,
+            CONTENT_TYPE_VOICE,
+            reply_to,
+            Some(voice),
+        )
+    }
+
+    fn send_with_voice(
+        &self,
+        recipient_peer_id: &str,
+        content: &str,
+        content_type: &str,
+        reply_to: Option<&str>,
+        voice: Option<&VoiceNote>,
     ) -> Result<OutgoingMessage> {
         // Get our identity
         let identity = self
@@ -103,10 +613,29 @@ impl MessagingService {
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
+        // Derive conversation ID
+        let conversation_id = derive_conversation_id(&identity.peer_id, recipient_peer_id);
+
+        // Validate that the quoted message belongs to this conversation
+        if let Some(reply_to_id) = reply_to {
+            let quoted = MessagesRepository::get_by_message_id(&self.db, reply_to_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .ok_or_else(|| AppError::NotFound("Quoted message not found".to_string()))?;
+            if quoted.conversation_id != conversation_id {
+                return Err(AppError::Validation(
+                    "Quoted message is not part of this conversation".to_string(),
+                ));
+            }
+        }
+
+        // Notes to self are sealed to our own key and never leave this device
+        let note_to_self = recipient_peer_id == identity.peer_id;
+
         // Check we have chat permission with this peer
-        if !self
-            .permissions_service
-            .peer_has_capability(recipient_peer_id, Capability::Chat)?
+        if !note_to_self
+            && !self
+                .permissions_service
+                .peer_has_capability(recipient_peer_id, Capability::Chat)?
         {
             return Err(AppError::PermissionDenied(
                 "No chat permission with this peer".to_string(),
@@ -114,115 +643,247 @@ impl MessagingService {
         }
 
         // Get recipient's X25519 public key for encryption
-        let x25519_public = self
-            .contacts_service
-            .get_x25519_public(recipient_peer_id)?
-            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
-
-        // Get our X25519 keys
-        let our_keys = self.identity_service.get_unlocked_keys()?;
-
-        // Derive conversation ID and encryption key
-        let conversation_id = derive_conversation_id(&identity.peer_id, recipient_peer_id);
-        let their_public = X25519Public::from(
-            <[u8; 32]>::try_from(x25519_public.as_slice())
-                .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))?,
-        );
-        let shared_secret = CryptoService::x25519_dh(&our_keys.x25519_secret, &their_public);
-        let conv_key = CryptoService::derive_conversation_key(
-            &shared_secret,
-            &conversation_id,
+        let x25519_public = self.conversation_x25519_public(&identity, recipient_peer_id)?;
+
+        let SealedMessage {
+            outgoing,
+            payload_cbor,
+            stored_content,
+            stored_nonce_salt,
+        } = self.seal_message(
             &identity.peer_id,
             recipient_peer_id,
-        );
-
-        // Get next nonce counter
-        let nonce_counter = self
-            .db
-            .next_send_counter(&conversation_id)
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
-
-        // Encrypt content
-        let content_encrypted = CryptoService::encrypt_message_with_counter(
-            &conv_key,
-            content.as_bytes(),
-            nonce_counter,
+            &x25519_public,
+            content,
+            content_type,
+            reply_to,
+            voice,
+            !note_to_self,
         )?;
 
-        // Create message
-        let message_id = Uuid::new_v4().to_string();
-        let lamport_clock =
-            self.db
-                .next_lamport_clock(&identity.peer_id)
-                .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
-        let timestamp = chrono::Utc::now().timestamp();
-
-        // Create signable and sign
-        tracing::info!(
-            "MESSAGE SEND - sender_peer_id: {} (len={}), recipient_peer_id: {} (len={})",
-            identity.peer_id,
-            identity.peer_id.len(),
-            recipient_peer_id,
-            recipient_peer_id.len()
-        );
-        let signable = SignableDirectMessage {
-            message_id: message_id.clone(),
-            conversation_id: conversation_id.clone(),
-            sender_peer_id: identity.peer_id.clone(),
-            recipient_peer_id: recipient_peer_id.to_string(),
-            content_encrypted: content_encrypted.clone(),
-            content_type: content_type.to_string(),
-            reply_to: reply_to.map(String::from),
-            nonce_counter,
-            lamport_clock,
-            timestamp,
-        };
-
-        let signature = self.identity_service.sign(&signable)?;
-
         // Store locally
         let msg_data = MessageData {
-            message_id: message_id.clone(),
-            conversation_id: conversation_id.clone(),
-            sender_peer_id: identity.peer_id.clone(),
+            message_id: outgoing.message_id.clone(),
+            conversation_id: outgoing.conversation_id.clone(),
+            sender_peer_id: outgoing.sender_peer_id.clone(),
             recipient_peer_id: recipient_peer_id.to_string(),
-            content_encrypted: content_encrypted.clone(),
+            content_encrypted: stored_content,
             content_type: content_type.to_string(),
             reply_to_message_id: reply_to.map(String::from),
-            nonce_counter,
-            lamport_clock: lamport_clock as i64,
-            sent_at: timestamp,
+            nonce_counter: outgoing.nonce_counter,
+            nonce_salt: stored_nonce_salt,
+            cipher: outgoing.cipher.as_str().to_string(),
+            lamport_clock: outgoing.lamport_clock as i64,
+            sent_at: outgoing.timestamp,
             received_at: None,
-            status: MessageStatus::Pending,
+            // A note to self is already where it's going, and already seen
+            status: if note_to_self {
+                MessageStatus::Read
+            } else {
+                MessageStatus::Pending
+            },
         };
 
         MessagesRepository::insert_message(&self.db, &msg_data)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if let Some(voice) = voice {
+            self.store_voice_note(&outgoing.message_id, voice)?;
+        }
 
         // Record event
-        let event_id = format!("sent:{}", message_id);
-        let payload_cbor = signable.signable_bytes()?;
+        let event_id = format!("sent:{}", outgoing.message_id);
         MessagesRepository::record_message_event(
             &self.db,
             &RecordMessageEventParams {
                 event_id: &event_id,
                 event_type: "sent",
-                message_id: &message_id,
-                conversation_id: &conversation_id,
-                sender_peer_id: &identity.peer_id,
+                message_id: &outgoing.message_id,
+                conversation_id: &outgoing.conversation_id,
+                sender_peer_id: &outgoing.sender_peer_id,
                 recipient_peer_id,
-                lamport_clock: lamport_clock as i64,
-                timestamp,
+                lamport_clock: outgoing.lamport_clock as i64,
+                timestamp: outgoing.timestamp,
                 payload_cbor: &payload_cbor,
-                signature: &signature,
+                signature: &outgoing.signature,
+                sig_version: CURRENT_SIG_VERSION,
             },
         )
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
-        Ok(OutgoingMessage {
+        Ok(outgoing)
+    }
+
+    /// Encrypt and sign a one-off message for a peer that is not a contact.
+    ///
+    /// `x25519_public` comes from a transient identity exchange rather than
+    /// the contacts table. The message is not stored and no chat permission is
+    /// needed, but blocked peers are refused.
+    pub fn send_transient_message(
+        &self,
+        recipient_peer_id: &str,
+        x25519_public: &[u8],
+        content: &str,
+        content_type: &str,
+    ) -> Result<OutgoingMessage> {
+        if self.contacts_service.is_blocked(recipient_peer_id)? {
+            return Err(AppError::PermissionDenied(
+                "Cannot message a blocked peer".to_string(),
+            ));
+        }
+
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        // Non-contacts have no ratchet session, so this is always statically keyed
+        let sealed = self.seal_message(
+            &identity.peer_id,
+            recipient_peer_id,
+            x25519_public,
+            content,
+            content_type,
+            None,
+            None,
+            false,
+        )?;
+        Ok(sealed.outgoing)
+    }
+
+    /// Encrypt and sign a message, returning it with the signed payload bytes.
+    ///
+    /// With `allow_ratchet`, content goes out under the conversation's ratchet
+    /// once the peer has announced support for it and the ratchet encryption
+    /// feature flag is on.
+    #[allow(clippy::too_many_arguments)]
+    fn seal_message(
+        &self,
+        sender_peer_id: &str,
+        recipient_peer_id: &str,
+        x25519_public: &[u8],
+        content: &str,
+        content_type: &str,
+        reply_to: Option<&str>,
+        voice: Option<&VoiceNote>,
+        allow_ratchet: bool,
+    ) -> Result<SealedMessage> {
+        let conversation_id = derive_conversation_id(sender_peer_id, recipient_peer_id);
+        let content = markdown::sanitize_content(content_type, content);
+
+        // Get our X25519 keys
+        let our_keys = self.identity_service.get_unlocked_keys()?;
+
+        // Derive encryption key
+        let their_public = X25519Public::from(
+            <[u8; 32]>::try_from(x25519_public)
+                .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))?,
+        );
+        let shared_secret = CryptoService::x25519_dh(&our_keys.x25519_secret, &their_public);
+        let conv_key = CryptoService::derive_conversation_key(
+            &shared_secret,
+            &conversation_id,
+            sender_peer_id,
+            recipient_peer_id,
+        );
+
+        // Get next nonce counter
+        let nonce_counter = self
+            .db
+            .next_send_counter(&conversation_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        let ratcheted = if allow_ratchet {
+            self.ratchet_encrypt(
+                &conv_key,
+                &conversation_id,
+                recipient_peer_id,
+                &our_keys.x25519_secret,
+                content.as_bytes(),
+            )?
+        } else {
+            None
+        };
+        // Ratcheted content is always AES-256-GCM, as is its stored copy
+        let (content_encrypted, nonce_salt, ratchet, stored_content, stored_nonce_salt, cipher) =
+            match ratcheted {
+                Some((header, ciphertext)) => {
+                    let (stored_content, stored_nonce_salt) = Self::encrypt_content(
+                        &conv_key,
+                        content.as_bytes(),
+                        nonce_counter,
+                        NonceStrategy::SaltedCounter,
+                        MessageCipher::Aes256Gcm,
+                    )?;
+                    (
+                        ciphertext,
+                        None,
+                        Some(header),
+                        stored_content,
+                        stored_nonce_salt,
+                        MessageCipher::Aes256Gcm,
+                    )
+                }
+                None => {
+                    let cipher = self.cipher_for_peer(recipient_peer_id)?;
+                    let (ciphertext, nonce_salt) = Self::encrypt_content(
+                        &conv_key,
+                        content.as_bytes(),
+                        nonce_counter,
+                        self.get_nonce_strategy()?,
+                        cipher,
+                    )?;
+                    (
+                        ciphertext.clone(),
+                        nonce_salt.clone(),
+                        None,
+                        ciphertext,
+                        nonce_salt,
+                        cipher,
+                    )
+                }
+            };
+
+        // Create message
+        let message_id = Uuid::new_v4().to_string();
+        let lamport_clock =
+            self.db
+                .next_lamport_clock(sender_peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
+        let timestamp = self.clock.timestamp();
+
+        // Create signable and sign
+        tracing::info!(
+            "MESSAGE SEND - sender_peer_id: {} (len={}), recipient_peer_id: {} (len={})",
+            sender_peer_id,
+            sender_peer_id.len(),
+            recipient_peer_id,
+            recipient_peer_id.len()
+        );
+        let signable = SignableDirectMessage {
+            message_id: message_id.clone(),
+            conversation_id: conversation_id.clone(),
+            sender_peer_id: sender_peer_id.to_string(),
+            recipient_peer_id: recipient_peer_id.to_string(),
+            content_encrypted: content_encrypted.clone(),
+            content_type: content_type.to_string(),
+            reply_to: reply_to.map(String::from),
+            nonce_counter,
+            lamport_clock,
+            timestamp,
+            nonce_salt: nonce_salt.clone(),
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            ratchet: ratchet.clone(),
+            cipher: cipher.as_str().to_string(),
+            voice: voice.cloned(),
+        };
+
+        let signature = self.identity_service.sign(&signable)?;
+        let payload_cbor = signable.signable_bytes()?;
+
+        let outgoing = OutgoingMessage {
             message_id,
             conversation_id,
-            sender_peer_id: identity.peer_id,
+            sender_peer_id: sender_peer_id.to_string(),
             recipient_peer_id: recipient_peer_id.to_string(),
             content_encrypted,
             content_type: content_type.to_string(),
@@ -231,9 +892,220 @@ impl MessagingService {
             lamport_clock,
             timestamp,
             signature,
+            nonce_salt,
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            ratchet,
+            cipher,
+            voice: voice.cloned(),
+        };
+        Ok(SealedMessage {
+            outgoing,
+            payload_cbor,
+            stored_content,
+            stored_nonce_salt,
         })
     }
 
+    /// Encrypt content under the conversation's ratchet, if the peer has
+    /// announced support for it and the ratchet encryption feature flag is
+    /// on. Returns None when the content should be keyed statically instead.
+    fn ratchet_encrypt(
+        &self,
+        conv_key: &[u8; 32],
+        conversation_id: &str,
+        recipient_peer_id: &str,
+        our_static: &X25519Secret,
+        plaintext: &[u8],
+    ) -> Result<Option<(RatchetHeader, Vec<u8>)>> {
+        if !FeatureFlagsService::is_enabled_in(&self.db, FeatureFlag::RatchetEncryption)? {
+            return Ok(None);
+        }
+
+        // Keep the ratchet locked until its new state is saved
+        let _ratchet_guard = self.ratchet_guard();
+        let Some(mut session) = self
+            .load_ratchet_session(conversation_id, our_static)?
+            .filter(RatchetSession::supports_ratchet)
+        else {
+            return Ok(None);
+        };
+        let (header, ciphertext) = session.encrypt(conv_key, plaintext)?;
+        self.save_ratchet_session(conversation_id, recipient_peer_id, &session, our_static)?;
+        Ok(Some((header, ciphertext)))
+    }
+
+    fn ratchet_guard(&self) -> MutexGuard<'_, ()> {
+        self.ratchet_lock
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Load a conversation's ratchet session, if one has been started
+    fn load_ratchet_session(
+        &self,
+        conversation_id: &str,
+        our_static: &X25519Secret,
+    ) -> Result<Option<RatchetSession>> {
+        RatchetRepository::get_state(&self.db, conversation_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .map(|sealed| RatchetSession::open(&sealed, &RatchetSession::storage_key(our_static)))
+            .transpose()
+    }
+
+    fn save_ratchet_session(
+        &self,
+        conversation_id: &str,
+        peer_id: &str,
+        session: &RatchetSession,
+        our_static: &X25519Secret,
+    ) -> Result<()> {
+        let sealed = session.seal(&RatchetSession::storage_key(our_static))?;
+        RatchetRepository::save_state(
+            &self.db,
+            conversation_id,
+            peer_id,
+            &sealed,
+            self.clock.timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Record the protocol version an incoming message announced and, for a
+    /// ratcheted message, decrypt it while its message key still exists.
+    ///
+    /// Returns the content to store for a ratcheted message, re-encrypted
+    /// under the conversation key with a fresh salt.
+    fn open_ratchet(
+        &self,
+        params: &IncomingMessageParams<'_>,
+    ) -> Result<Option<(Vec<u8>, Option<Vec<u8>>)>> {
+        if params.protocol_version < PROTOCOL_VERSION_RATCHET {
+            if params.ratchet.is_some() {
+                return Err(AppError::Validation(
+                    "Ratchet header requires protocol version 2".to_string(),
+                ));
+            }
+            // Nothing to track for peers we never ratcheted with
+            if RatchetRepository::get_state(&self.db, params.conversation_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?
+                .is_none()
+            {
+                return Ok(None);
+            }
+        }
+
+        let our_keys = self.identity_service.get_unlocked_keys()?;
+        let their_static = self.contact_x25519_static(params.sender_peer_id)?;
+
+        let _ratchet_guard = self.ratchet_guard();
+        let mut session = self
+            .load_ratchet_session(params.conversation_id, &our_keys.x25519_secret)?
+            .unwrap_or_else(|| RatchetSession::new(their_static, params.protocol_version));
+        // The version is signed, so the latest announcement wins even when
+        // the peer goes back to an older client
+        session.peer_protocol_version = params.protocol_version;
+
+        let stored = match params.ratchet {
+            Some(header) => {
+                let shared_secret = CryptoService::x25519_dh(
+                    &our_keys.x25519_secret,
+                    &X25519Public::from(their_static),
+                );
+                let conv_key = CryptoService::derive_conversation_key(
+                    &shared_secret,
+                    params.conversation_id,
+                    params.recipient_peer_id,
+                    params.sender_peer_id,
+                );
+                let plaintext = session.decrypt(
+                    &conv_key,
+                    &our_keys.x25519_secret,
+                    header,
+                    params.content_encrypted,
+                )?;
+                Some(Self::encrypt_content(
+                    &conv_key,
+                    &plaintext,
+                    params.nonce_counter,
+                    NonceStrategy::SaltedCounter,
+                    MessageCipher::Aes256Gcm,
+                )?)
+            }
+            None => None,
+        };
+
+        self.save_ratchet_session(
+            params.conversation_id,
+            params.sender_peer_id,
+            &session,
+            &our_keys.x25519_secret,
+        )?;
+        Ok(stored)
+    }
+
+    /// Decrypt a ratcheted edit of `original` and re-encrypt it for storage
+    /// under the conversation key, with the message's cipher and nonce
+    /// counter and a fresh salt
+    fn open_ratcheted_edit(
+        &self,
+        edit: &SignableMessageEdit,
+        header: &RatchetHeader,
+        original: &Message,
+    ) -> Result<(Vec<u8>, Vec<u8>)> {
+        let our_keys = self.identity_service.get_unlocked_keys()?;
+        let their_static = self.contact_x25519_static(&edit.sender_peer_id)?;
+        let shared_secret =
+            CryptoService::x25519_dh(&our_keys.x25519_secret, &X25519Public::from(their_static));
+        let conv_key = CryptoService::derive_conversation_key(
+            &shared_secret,
+            &original.conversation_id,
+            &original.recipient_peer_id,
+            &edit.sender_peer_id,
+        );
+
+        let plaintext = {
+            let _ratchet_guard = self.ratchet_guard();
+            // Only a peer on protocol 2 or later sends ratcheted content
+            let mut session = self
+                .load_ratchet_session(&original.conversation_id, &our_keys.x25519_secret)?
+                .unwrap_or_else(|| RatchetSession::new(their_static, PROTOCOL_VERSION_RATCHET));
+            let plaintext = session.decrypt(
+                &conv_key,
+                &our_keys.x25519_secret,
+                header,
+                &edit.content_encrypted,
+            )?;
+            self.save_ratchet_session(
+                &original.conversation_id,
+                &edit.sender_peer_id,
+                &session,
+                &our_keys.x25519_secret,
+            )?;
+            plaintext
+        };
+
+        let (content, nonce_salt) = Self::encrypt_content(
+            &conv_key,
+            &plaintext,
+            original.nonce_counter,
+            NonceStrategy::SaltedCounter,
+            original.cipher.parse().map_err(AppError::Crypto)?,
+        )?;
+        let nonce_salt = nonce_salt
+            .ok_or_else(|| AppError::Crypto("Salted nonce produced no salt".to_string()))?;
+        Ok((content, nonce_salt))
+    }
+
+    /// A contact's X25519 public key
+    fn contact_x25519_static(&self, peer_id: &str) -> Result<[u8; 32]> {
+        let x25519_public = self
+            .contacts_service
+            .get_x25519_public(peer_id)?
+            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
+        <[u8; 32]>::try_from(x25519_public.as_slice())
+            .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))
+    }
+
     /// Process an incoming message from the network
     pub fn process_incoming_message(&self, params: &IncomingMessageParams<'_>) -> Result<()> {
         let message_id = params.message_id;
@@ -247,6 +1119,7 @@ impl MessagingService {
         let lamport_clock = params.lamport_clock;
         let timestamp = params.timestamp;
         let signature = params.signature;
+        let nonce_salt = params.nonce_salt;
         // Verify we are the recipient
         let identity = self
             .identity_service
@@ -270,6 +1143,53 @@ impl MessagingService {
             return Err(AppError::Validation("Message not for us".to_string()));
         }
 
+        // Both sides derive the same id for a pair, so anything else is spoofed
+        if conversation_id != derive_conversation_id(sender_peer_id, recipient_peer_id) {
+            return Err(AppError::Validation(
+                "Conversation id does not match sender and recipient".to_string(),
+            ));
+        }
+
+        if nonce_salt.is_some_and(|salt| salt.len() != NONCE_SALT_LEN) {
+            return Err(AppError::Crypto("Invalid nonce salt length".to_string()));
+        }
+
+        // An unknown cipher can't be decrypted later, so refuse it up front
+        let cipher: MessageCipher = params.cipher.parse().map_err(AppError::Crypto)?;
+        if params.ratchet.is_some() && cipher != MessageCipher::Aes256Gcm {
+            return Err(AppError::Validation(
+                "Ratcheted messages must use AES-256-GCM".to_string(),
+            ));
+        }
+
+        // Voice notes must name their audio, and only voice notes may
+        match (content_type == CONTENT_TYPE_VOICE, params.voice) {
+            (true, Some(voice)) => voice.validate()?,
+            (false, None) => {}
+            (true, None) => {
+                return Err(AppError::Validation(
+                    "Voice note message has no audio".to_string(),
+                ))
+            }
+            (false, Some(_)) => {
+                return Err(AppError::Validation(format!(
+                    "Only voice notes carry audio, not {} messages",
+                    content_type
+                )))
+            }
+        }
+
+        // A resend of a message we already stored, because our response never
+        // reached the sender, is accepted again rather than taken for a replay
+        if MessagesRepository::get_by_message_id(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .is_some_and(|stored| {
+                stored.sender_peer_id == sender_peer_id && stored.conversation_id == conversation_id
+            })
+        {
+            return Ok(());
+        }
+
         // Check for replay (BEFORE decryption)
         if !self
             .db
@@ -292,6 +1212,16 @@ impl MessagingService {
                 AppError::NotFound("Sender not in contacts".to_string())
             })?;
 
+        // Being a contact isn't enough, we must have granted them chat
+        if !self
+            .permissions_service
+            .peer_has_capability(sender_peer_id, Capability::Chat)?
+        {
+            return Err(AppError::PermissionDenied(
+                "Sender doesn't have chat permission".to_string(),
+            ));
+        }
+
         // Verify signature
         let signable = SignableDirectMessage {
             message_id: message_id.to_string(),
@@ -304,6 +1234,11 @@ impl MessagingService {
             nonce_counter,
             lamport_clock,
             timestamp,
+            nonce_salt: nonce_salt.map(<[u8]>::to_vec),
+            protocol_version: params.protocol_version,
+            ratchet: params.ratchet.cloned(),
+            cipher: params.cipher.to_string(),
+            voice: params.voice.cloned(),
         };
 
         let verifying_key = VerifyingKey::from_bytes(
@@ -314,7 +1249,12 @@ impl MessagingService {
         )
         .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
 
-        if !verify(&verifying_key, &signable, signature)? {
+        if !SignaturePolicy::from_settings(&self.db)?.verify(
+            &verifying_key,
+            &signable,
+            signature,
+            params.sig_version,
+        )? {
             return Err(AppError::Crypto("Invalid message signature".to_string()));
         }
 
@@ -325,22 +1265,71 @@ impl MessagingService {
             return Ok(()); // Already processed
         }
 
+        // Ratcheted content can only be decrypted now, so it is kept
+        // re-encrypted under the conversation key instead
+        let (stored_content, stored_nonce_salt, stored_cipher) = match self.open_ratchet(params)? {
+            Some((content, salt)) => (content, salt, MessageCipher::Aes256Gcm),
+            None => (
+                content_encrypted.to_vec(),
+                nonce_salt.map(<[u8]>::to_vec),
+                cipher,
+            ),
+        };
+
+        // A sender's clock never repeats, so a reused clock is a replay even
+        // when it arrives under a new message id. Lower clocks than the
+        // highest seen are fine: they fill gaps left by reordered delivery.
+        let highest_clock = self
+            .db
+            .get_highest_received_message_clock(conversation_id, sender_peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if let Some(previous_message_id) = self
+            .db
+            .check_and_record_message_clock(
+                conversation_id,
+                sender_peer_id,
+                lamport_clock,
+                message_id,
+            )
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        {
+            tracing::warn!(
+                "Message {} from {} reuses lamport clock {} of message {}",
+                message_id,
+                sender_peer_id,
+                lamport_clock,
+                previous_message_id
+            );
+            return Err(AppError::Crypto("Replay attack detected".to_string()));
+        }
+        if let Some(highest) = highest_clock.filter(|&highest| lamport_clock < highest) {
+            tracing::debug!(
+                "Message {} from {} arrived out of order (clock {} < {})",
+                message_id,
+                sender_peer_id,
+                lamport_clock,
+                highest
+            );
+        }
+
         // Update lamport clock
         self.db
             .update_lamport_clock(sender_peer_id, lamport_clock as i64)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
         // Store message
-        let received_at = chrono::Utc::now().timestamp();
+        let received_at = self.clock.timestamp();
         let msg_data = MessageData {
             message_id: message_id.to_string(),
             conversation_id: conversation_id.to_string(),
             sender_peer_id: sender_peer_id.to_string(),
             recipient_peer_id: recipient_peer_id.to_string(),
-            content_encrypted: content_encrypted.to_vec(),
+            content_encrypted: stored_content,
             content_type: content_type.to_string(),
             reply_to_message_id: reply_to.map(String::from),
             nonce_counter,
+            nonce_salt: stored_nonce_salt,
+            cipher: stored_cipher.as_str().to_string(),
             lamport_clock: lamport_clock as i64,
             sent_at: timestamp,
             received_at: Some(received_at),
@@ -349,6 +1338,9 @@ impl MessagingService {
 
         MessagesRepository::insert_message(&self.db, &msg_data)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if let Some(voice) = params.voice {
+            self.store_voice_note(message_id, voice)?;
+        }
 
         // Record event
         let event_id = format!("received:{}", message_id);
@@ -366,10 +1358,80 @@ impl MessagingService {
                 timestamp,
                 payload_cbor: &payload_cbor,
                 signature,
+                sig_version: params.sig_version,
             },
         )
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
+        self.apply_held_changes(message_id)?;
+
+        Ok(())
+    }
+
+    /// Apply the edits and delete that arrived before a message, now that it
+    /// has. Each is checked again as if it had just arrived.
+    fn apply_held_changes(&self, message_id: &str) -> Result<()> {
+        let held = MessagesRepository::take_held_changes(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        for change in held {
+            let applied = match change.change_type.as_str() {
+                "edit" => decode_canonical::<SignableMessageEdit>(&change.payload_cbor)
+                    .and_then(|edit| self.process_incoming_edit(&edit, &change.signature)),
+                "delete" => decode_canonical::<SignableMessageDelete>(&change.payload_cbor)
+                    .and_then(|delete| self.process_incoming_delete(&delete, &change.signature)),
+                other => Err(AppError::Validation(format!(
+                    "Unknown held change type {}",
+                    other
+                ))),
+            };
+            if let Err(e) = applied {
+                tracing::warn!(
+                    "Dropping held {} for message {}: {}",
+                    change.change_type,
+                    message_id,
+                    e
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// Keep a verified edit or delete whose message hasn't arrived yet
+    fn hold_change(
+        &self,
+        change_type: &str,
+        message_id: &str,
+        sender_peer_id: &str,
+        lamport_clock: u64,
+        signable: &impl Signable,
+        signature: &[u8],
+    ) -> Result<()> {
+        let held = MessagesRepository::count_held_changes(&self.db, sender_peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if held >= MAX_HELD_CHANGES_PER_PEER {
+            return Err(AppError::Validation(
+                "Too many changes held for messages that haven't arrived".to_string(),
+            ));
+        }
+
+        MessagesRepository::hold_change(
+            &self.db,
+            &HeldMessageChange {
+                message_id: message_id.to_string(),
+                change_type: change_type.to_string(),
+                sender_peer_id: sender_peer_id.to_string(),
+                lamport_clock: lamport_clock as i64,
+                payload_cbor: signable.signable_bytes()?,
+                signature: signature.to_vec(),
+            },
+            self.clock.timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        tracing::debug!(
+            "Holding {} for message {} until it arrives",
+            change_type,
+            message_id
+        );
         Ok(())
     }
 
@@ -384,7 +1446,7 @@ impl MessagingService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))?
             .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
 
-        let timestamp = chrono::Utc::now().timestamp();
+        let timestamp = self.clock.timestamp();
 
         let signable = SignableMessageAck {
             message_id: message_id.to_string(),
@@ -410,7 +1472,7 @@ impl MessagingService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))?
             .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
 
-        let timestamp = chrono::Utc::now().timestamp();
+        let timestamp = self.clock.timestamp();
 
         let signable = SignableMessageAck {
             message_id: message_id.to_string(),
@@ -429,34 +1491,121 @@ impl MessagingService {
         Ok((signable, signature))
     }
 
-    /// Process an incoming acknowledgment
-    pub fn process_incoming_ack(
+    /// Create a read receipt telling `peer_id` we've seen `message_ids`.
+    ///
+    /// A receipt covers at most `MAX_READ_RECEIPT_MESSAGES`; split larger
+    /// batches across several.
+    pub fn create_read_receipt(
         &self,
-        message_id: &str,
-        conversation_id: &str,
-        ack_sender_peer_id: &str,
-        status: &str,
-        timestamp: i64,
-        signature: &[u8],
-    ) -> Result<()> {
-        // Get the ack sender's public key
-        let sender_public_key = self
-            .contacts_service
-            .get_public_key(ack_sender_peer_id)?
-            .ok_or_else(|| AppError::NotFound("Ack sender not in contacts".to_string()))?;
+        peer_id: &str,
+        message_ids: &[String],
+    ) -> Result<(SignableReadReceipt, Vec<u8>)> {
+        if message_ids.len() > MAX_READ_RECEIPT_MESSAGES {
+            return Err(AppError::Validation(format!(
+                "A read receipt covers at most {} messages",
+                MAX_READ_RECEIPT_MESSAGES
+            )));
+        }
 
-        // Verify signature
-        let signable = SignableMessageAck {
-            message_id: message_id.to_string(),
-            conversation_id: conversation_id.to_string(),
-            ack_sender_peer_id: ack_sender_peer_id.to_string(),
-            status: status.to_string(),
-            timestamp,
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let signable = SignableReadReceipt {
+            conversation_id: derive_conversation_id(&identity.peer_id, peer_id),
+            reader_peer_id: identity.peer_id,
+            message_ids: message_ids.to_vec(),
+            read_at: self.clock.timestamp(),
         };
+        let signature = self.identity_service.sign(&signable)?;
 
-        let verifying_key = VerifyingKey::from_bytes(
-            sender_public_key
-                .as_slice()
+        Ok((signable, signature))
+    }
+
+    /// Process a peer's read receipt, marking the messages we sent them as
+    /// read. Returns the IDs of the messages that changed.
+    pub fn process_incoming_read_receipt(
+        &self,
+        receipt: &SignableReadReceipt,
+        signature: &[u8],
+    ) -> Result<Vec<String>> {
+        if receipt.message_ids.len() > MAX_READ_RECEIPT_MESSAGES {
+            return Err(AppError::Validation(format!(
+                "Read receipt covers {} messages, more than {}",
+                receipt.message_ids.len(),
+                MAX_READ_RECEIPT_MESSAGES
+            )));
+        }
+
+        let reader_public_key = self
+            .contacts_service
+            .get_public_key(&receipt.reader_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Reader not in contacts".to_string()))?;
+        let verifying_key = VerifyingKey::from_bytes(
+            reader_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        if !verify(&verifying_key, receipt, signature)? {
+            return Err(AppError::Crypto(
+                "Invalid read receipt signature".to_string(),
+            ));
+        }
+
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+        if receipt.conversation_id
+            != derive_conversation_id(&identity.peer_id, &receipt.reader_peer_id)
+        {
+            return Err(AppError::Validation(
+                "Read receipt is for a different conversation".to_string(),
+            ));
+        }
+
+        MessagesRepository::mark_messages_read(
+            &self.db,
+            &receipt.conversation_id,
+            &receipt.reader_peer_id,
+            &receipt.message_ids,
+            receipt.read_at,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Process an incoming acknowledgment
+    pub fn process_incoming_ack(
+        &self,
+        message_id: &str,
+        conversation_id: &str,
+        ack_sender_peer_id: &str,
+        status: &str,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<()> {
+        // Get the ack sender's public key
+        let sender_public_key = self
+            .contacts_service
+            .get_public_key(ack_sender_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Ack sender not in contacts".to_string()))?;
+
+        // Verify signature
+        let signable = SignableMessageAck {
+            message_id: message_id.to_string(),
+            conversation_id: conversation_id.to_string(),
+            ack_sender_peer_id: ack_sender_peer_id.to_string(),
+            status: status.to_string(),
+            timestamp,
+        };
+
+        let verifying_key = VerifyingKey::from_bytes(
+            sender_public_key
+                .as_slice()
                 .try_into()
                 .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
         )
@@ -487,12 +1636,46 @@ impl MessagingService {
         Ok(())
     }
 
+    /// Get a page of a conversation, paging back from `before` (the newest
+    /// messages when `None`). `limit` defaults to `DEFAULT_MESSAGE_PAGE_SIZE`
+    /// and is clamped to `MAX_MESSAGE_PAGE_SIZE`.
+    pub fn get_message_page(
+        &self,
+        peer_id: &str,
+        limit: Option<i64>,
+        before: Option<&MessageCursor>,
+    ) -> Result<MessagePage> {
+        let limit = limit
+            .unwrap_or(DEFAULT_MESSAGE_PAGE_SIZE)
+            .clamp(1, MAX_MESSAGE_PAGE_SIZE);
+
+        // One extra message tells us whether an older page exists
+        let mut messages = self.get_conversation_messages(peer_id, limit + 1, before)?;
+        let has_more = messages.len() as i64 > limit;
+        if has_more {
+            messages.remove(0);
+        }
+        let next_cursor = if has_more {
+            messages.first().map(|oldest| MessageCursor {
+                sent_at: oldest.sent_at,
+                message_id: Some(oldest.message_id.clone()),
+            })
+        } else {
+            None
+        };
+
+        Ok(MessagePage {
+            messages,
+            next_cursor,
+        })
+    }
+
     /// Get messages for a conversation, decrypted
     pub fn get_conversation_messages(
         &self,
         peer_id: &str,
         limit: i64,
-        before_timestamp: Option<i64>,
+        before: Option<&MessageCursor>,
     ) -> Result<Vec<DecryptedMessage>> {
         let identity = self
             .identity_service
@@ -506,15 +1689,12 @@ impl MessagingService {
             &self.db,
             &conversation_id,
             limit,
-            before_timestamp,
+            before,
         )
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
         // Get peer's X25519 key for decryption
-        let x25519_public = self
-            .contacts_service
-            .get_x25519_public(peer_id)?
-            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
+        let x25519_public = self.conversation_x25519_public(&identity, peer_id)?;
 
         let our_keys = self.identity_service.get_unlocked_keys()?;
 
@@ -534,55 +1714,333 @@ impl MessagingService {
         // Decrypt messages
         let mut decrypted = Vec::new();
         for msg in messages {
-            let content = match CryptoService::decrypt_message_with_counter(
-                &conv_key,
-                &msg.content_encrypted,
-                msg.nonce_counter,
-            ) {
-                Ok(bytes) => String::from_utf8_lossy(&bytes).to_string(),
-                Err(_) => "[Decryption failed]".to_string(),
+            // A deleted message keeps its place in the conversation, nothing else
+            let content = if msg.deleted_at.is_some() {
+                String::new()
+            } else {
+                Self::decrypt_content(&conv_key, &msg)
             };
-
-            decrypted.push(DecryptedMessage {
-                message_id: msg.message_id,
-                conversation_id: msg.conversation_id,
-                sender_peer_id: msg.sender_peer_id.clone(),
-                recipient_peer_id: msg.recipient_peer_id,
+            decrypted.push(self.to_decrypted_message(
+                &conv_key,
+                &identity.peer_id,
+                msg,
                 content,
-                content_type: msg.content_type,
-                reply_to_message_id: msg.reply_to_message_id,
-                sent_at: msg.sent_at,
-                delivered_at: msg.delivered_at,
-                read_at: msg.read_at,
-                status: msg.status,
-                is_outgoing: msg.sender_peer_id == identity.peer_id,
-                edited_at: msg.edited_at,
-            });
+            )?);
         }
 
         Ok(decrypted)
     }
 
-    /// Get all conversations
-    pub fn get_conversations(&self) -> Result<Vec<Conversation>> {
+    /// Search message content, newest first, across every conversation or
+    /// just `conversation_id`. `limit` defaults to `DEFAULT_SEARCH_LIMIT` and
+    /// is clamped to `MAX_SEARCH_LIMIT`.
+    ///
+    /// Content is stored encrypted, so messages are decrypted batch by batch
+    /// and matched as a case-insensitive substring. Messages that can't be
+    /// decrypted, including those with former contacts, are skipped.
+    /// `on_matches` gets each batch's new matches as the scan goes.
+    pub fn search_messages(
+        &self,
+        query: &str,
+        conversation_id: Option<&str>,
+        limit: Option<i64>,
+        mut on_matches: impl FnMut(&[DecryptedMessage]),
+    ) -> Result<Vec<DecryptedMessage>> {
+        let needle = query.trim().to_lowercase();
+        if needle.is_empty() {
+            return Err(AppError::Validation("Search query is empty".to_string()));
+        }
+        let limit = limit
+            .unwrap_or(DEFAULT_SEARCH_LIMIT)
+            .clamp(1, MAX_SEARCH_LIMIT) as usize;
+
         let identity = self
             .identity_service
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+        let our_keys = self.identity_service.get_unlocked_keys()?;
+
+        // Conversation keys by conversation, None where the peer's key is gone
+        let mut conv_keys: HashMap<String, Option<[u8; 32]>> = HashMap::new();
+        let mut matches = Vec::new();
+        let mut cursor: Option<MessageCursor> = None;
+        loop {
+            let batch = MessagesRepository::search(
+                &self.db,
+                conversation_id,
+                SEARCH_BATCH_SIZE,
+                cursor.as_ref(),
+            )
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            let exhausted = (batch.len() as i64) < SEARCH_BATCH_SIZE;
+            cursor = batch.last().map(|last| MessageCursor {
+                sent_at: last.sent_at,
+                message_id: Some(last.message_id.clone()),
+            });
+
+            let batch_start = matches.len();
+            for msg in batch {
+                let conv_key = *conv_keys
+                    .entry(msg.conversation_id.clone())
+                    .or_insert_with(|| {
+                        let peer_id = if msg.sender_peer_id == identity.peer_id {
+                            &msg.recipient_peer_id
+                        } else {
+                            &msg.sender_peer_id
+                        };
+                        let x25519_public =
+                            self.conversation_x25519_public(&identity, peer_id).ok()?;
+                        let their_public = <[u8; 32]>::try_from(x25519_public.as_slice()).ok()?;
+                        let shared_secret = CryptoService::x25519_dh(
+                            &our_keys.x25519_secret,
+                            &X25519Public::from(their_public),
+                        );
+                        Some(CryptoService::derive_conversation_key(
+                            &shared_secret,
+                            &msg.conversation_id,
+                            &identity.peer_id,
+                            peer_id,
+                        ))
+                    });
+                let Some(conv_key) = conv_key else {
+                    continue;
+                };
+                let Ok(bytes) = Self::decrypt_message_bytes(&conv_key, &msg) else {
+                    continue;
+                };
+                let content =
+                    markdown::sanitize_content(&msg.content_type, &String::from_utf8_lossy(&bytes))
+                        .into_owned();
+                if !content.to_lowercase().contains(&needle) {
+                    continue;
+                }
+
+                matches.push(self.to_decrypted_message(
+                    &conv_key,
+                    &identity.peer_id,
+                    msg,
+                    content,
+                )?);
+                if matches.len() == limit {
+                    break;
+                }
+            }
+
+            if matches.len() > batch_start {
+                on_matches(&matches[batch_start..]);
+            }
+            if exhausted || matches.len() == limit {
+                break;
+            }
+        }
+
+        Ok(matches)
+    }
 
-        MessagesRepository::get_conversations(&self.db, &identity.peer_id)
+    /// Pair a stored message with its decrypted content, adding the reply
+    /// preview and voice note it shows with. Deleted messages get neither.
+    fn to_decrypted_message(
+        &self,
+        conv_key: &[u8; 32],
+        our_peer_id: &str,
+        msg: Message,
+        content: String,
+    ) -> Result<DecryptedMessage> {
+        let deleted = msg.deleted_at.is_some();
+        let reply_preview = match msg.reply_to_message_id.as_deref() {
+            Some(reply_to_id) if !deleted => {
+                Some(self.build_reply_preview(conv_key, &msg.conversation_id, reply_to_id)?)
+            }
+            _ => None,
+        };
+        let voice = if msg.content_type == CONTENT_TYPE_VOICE && !deleted {
+            self.get_voice_note(&msg.message_id)?
+        } else {
+            None
+        };
+
+        Ok(DecryptedMessage {
+            is_outgoing: msg.sender_peer_id == our_peer_id,
+            message_id: msg.message_id,
+            conversation_id: msg.conversation_id,
+            sender_peer_id: msg.sender_peer_id,
+            recipient_peer_id: msg.recipient_peer_id,
+            content,
+            content_type: msg.content_type,
+            reply_to_message_id: msg.reply_to_message_id,
+            sent_at: msg.sent_at,
+            delivered_at: msg.delivered_at,
+            read_at: msg.read_at,
+            status: msg.status,
+            edited_at: msg.edited_at,
+            deleted_at: msg.deleted_at,
+            reply_preview,
+            voice,
+        })
+    }
+
+    /// Record the audio a voice note message references
+    fn store_voice_note(&self, message_id: &str, voice: &VoiceNote) -> Result<()> {
+        MessagesRepository::insert_message_media(
+            &self.db,
+            &MessageMedia {
+                message_id: message_id.to_string(),
+                media_hash: voice.media_hash.clone(),
+                mime_type: voice.mime_type.clone(),
+                size_bytes: voice.size_bytes as i64,
+                duration_ms: voice.duration_ms as i64,
+            },
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get the voice note a stored message carries, if any
+    pub fn get_voice_note(&self, message_id: &str) -> Result<Option<VoiceNote>> {
+        MessagesRepository::get_message_media(&self.db, message_id)
+            .map(|media| media.map(voice_note_from_media))
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Get a stored voice note whose audio is `media_hash`, so audio fetched
+    /// from a peer can be checked against what the message announced
+    pub fn voice_note_for_media(&self, media_hash: &str) -> Result<Option<VoiceNote>> {
+        MessagesRepository::get_message_media_by_hash(&self.db, media_hash)
+            .map(|media| media.map(voice_note_from_media))
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Whether `media_hash` is the audio of a voice note exchanged with
+    /// `peer_id`, who may then fetch it without a file transfer grant
+    pub fn voice_note_shared_with(&self, media_hash: &str, peer_id: &str) -> Result<bool> {
+        MessagesRepository::message_media_shared_with(&self.db, media_hash, peer_id)
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
-    /// Mark a conversation as read
-    pub fn mark_conversation_read(&self, peer_id: &str) -> Result<i64> {
+    /// Decrypt a stored message's content, falling back to a placeholder.
+    ///
+    /// Markdown is sanitized again here: received messages are stored
+    /// encrypted exactly as sent, so this is where a peer's content is first
+    /// seen in the clear.
+    fn decrypt_content(conv_key: &[u8; 32], msg: &Message) -> String {
+        match Self::decrypt_message_bytes(conv_key, msg) {
+            Ok(bytes) => {
+                let content = String::from_utf8_lossy(&bytes);
+                markdown::sanitize_content(&msg.content_type, &content).into_owned()
+            }
+            Err(_) => "[Decryption failed]".to_string(),
+        }
+    }
+
+    /// Build the quoted-message preview for a reply.
+    ///
+    /// A quoted message that is missing, deleted by its sender, or belongs to
+    /// a different conversation is rendered as `[deleted message]` instead of
+    /// failing.
+    fn build_reply_preview(
+        &self,
+        conv_key: &[u8; 32],
+        conversation_id: &str,
+        reply_to_id: &str,
+    ) -> Result<ReplyPreview> {
+        let quoted = MessagesRepository::get_by_message_id(&self.db, reply_to_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .filter(|m| m.conversation_id == conversation_id && m.deleted_at.is_none());
+
+        Ok(match quoted {
+            Some(quoted) => ReplyPreview {
+                message_id: reply_to_id.to_string(),
+                snippet: truncate_snippet(&Self::decrypt_content(conv_key, &quoted)),
+                sender_peer_id: Some(quoted.sender_peer_id),
+                is_deleted: false,
+            },
+            None => ReplyPreview {
+                message_id: reply_to_id.to_string(),
+                sender_peer_id: None,
+                snippet: DELETED_MESSAGE_SNIPPET.to_string(),
+                is_deleted: true,
+            },
+        })
+    }
+
+    /// Get all conversations, pinned first and then by most recent message,
+    /// each with a preview of its last message
+    pub fn get_conversations(&self) -> Result<Vec<Conversation>> {
         let identity = self
             .identity_service
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
+        let mut conversations = MessagesRepository::get_conversations(&self.db, &identity.peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        // A locked identity still lists conversations, just without previews
+        let our_keys = self.identity_service.get_unlocked_keys().ok();
+        for conversation in &mut conversations {
+            let peer_x25519_public = if conversation.is_note_to_self {
+                Some(identity.x25519_public.as_slice())
+            } else {
+                conversation.peer_x25519_public.as_deref()
+            };
+            let preview = our_keys.as_ref().and_then(|keys| {
+                let their_public = <[u8; 32]>::try_from(peer_x25519_public?).ok()?;
+                let shared_secret = CryptoService::x25519_dh(
+                    &keys.x25519_secret,
+                    &X25519Public::from(their_public),
+                );
+                let conv_key = CryptoService::derive_conversation_key(
+                    &shared_secret,
+                    &conversation.conversation_id,
+                    &identity.peer_id,
+                    &conversation.peer_id,
+                );
+                let message = &conversation.last_message;
+                if message.deleted_at.is_some() {
+                    return Some(DELETED_MESSAGE_SNIPPET.to_string());
+                }
+                let bytes = Self::decrypt_message_bytes(&conv_key, message).ok()?;
+                let content = String::from_utf8_lossy(&bytes);
+                if message.content_type == CONTENT_TYPE_VOICE && content.trim().is_empty() {
+                    return Some(VOICE_NOTE_PREVIEW.to_string());
+                }
+                Some(truncate_snippet(&markdown::sanitize_content(
+                    &message.content_type,
+                    &content,
+                )))
+            });
+            conversation.last_message_preview =
+                Some(preview.unwrap_or_else(|| ENCRYPTED_MESSAGE_PREVIEW.to_string()));
+        }
+
+        Ok(conversations)
+    }
+
+    /// Pin the conversation with a peer to the top of the list, or unpin it
+    pub fn set_conversation_pinned(&self, peer_id: &str, pinned: bool) -> Result<()> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
         let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
-        let timestamp = chrono::Utc::now().timestamp();
+
+        MessagesRepository::set_conversation_pinned(
+            &self.db,
+            &conversation_id,
+            pinned,
+            self.clock.timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Mark a conversation as read, returning the IDs of the messages that
+    /// weren't already, for `create_read_receipt`
+    pub fn mark_conversation_read(&self, peer_id: &str) -> Result<Vec<String>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
+        let timestamp = self.clock.timestamp();
 
         MessagesRepository::mark_conversation_read(
             &self.db,
@@ -612,113 +2070,124 @@ impl MessagingService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
-    /// Clear all messages in a conversation (keeps the conversation visible if new messages arrive)
-    pub fn clear_conversation_history(&self, peer_id: &str) -> Result<i64> {
-        let identity = self
-            .identity_service
-            .get_identity()?
-            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
-
-        let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
-
-        MessagesRepository::clear_conversation_messages(&self.db, &conversation_id)
+    /// Record whether an outgoing message reached the peer
+    pub fn record_send_outcome(&self, message_id: &str, sent: bool) -> Result<bool> {
+        MessagesRepository::mark_send_outcome(&self.db, message_id, sent)
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
-    /// Edit a sent message's content (re-encrypts and updates the DB)
-    pub fn edit_message(&self, message_id: &str, new_content: &str) -> Result<()> {
-        let identity = self
-            .identity_service
-            .get_identity()?
-            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
-
-        // Get the original message
-        let original = MessagesRepository::get_by_message_id(&self.db, message_id)
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
-
-        // Verify we are the sender
-        if original.sender_peer_id != identity.peer_id {
-            return Err(AppError::PermissionDenied(
-                "Can only edit your own messages".to_string(),
-            ));
-        }
-
-        // Determine the peer (the other party)
-        let peer_id = &original.recipient_peer_id;
-
-        // Get peer's X25519 key for encryption
-        let x25519_public = self
-            .contacts_service
-            .get_x25519_public(peer_id)?
-            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
-
-        let our_keys = self.identity_service.get_unlocked_keys()?;
+    /// Queue an encoded message until its recipient confirms it
+    pub fn queue_outbound(&self, message_id: &str, peer_id: &str, payload: &[u8]) -> Result<()> {
+        PendingOutboundRepository::enqueue(
+            &self.db,
+            message_id,
+            peer_id,
+            payload,
+            self.clock.timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
 
-        // Derive conversation key
-        let their_public = X25519Public::from(
-            <[u8; 32]>::try_from(x25519_public.as_slice())
-                .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))?,
-        );
-        let shared_secret = CryptoService::x25519_dh(&our_keys.x25519_secret, &their_public);
-        let conv_key = CryptoService::derive_conversation_key(
-            &shared_secret,
-            &original.conversation_id,
-            &identity.peer_id,
+    /// Queue an encoded edit of a message until the recipient confirms it,
+    /// replacing any older edit of it still waiting. Returns its queue key.
+    pub fn queue_outbound_edit(
+        &self,
+        message_id: &str,
+        peer_id: &str,
+        payload: &[u8],
+    ) -> Result<String> {
+        self.queue_outbound_change(
+            format!("edit:{}", message_id),
+            PendingOutboundKind::Edit,
+            Some(message_id),
             peer_id,
-        );
+            payload,
+        )
+    }
 
-        // Re-encrypt with the same nonce counter (content replacement)
-        let new_content_encrypted = CryptoService::encrypt_message_with_counter(
-            &conv_key,
-            new_content.as_bytes(),
-            original.nonce_counter,
-        )?;
+    /// Queue an encoded delete of a message until the recipient confirms it.
+    /// Returns its queue key.
+    pub fn queue_outbound_delete(
+        &self,
+        message_id: &str,
+        peer_id: &str,
+        payload: &[u8],
+    ) -> Result<String> {
+        self.queue_outbound_change(
+            format!("delete:{}", message_id),
+            PendingOutboundKind::Delete,
+            Some(message_id),
+            peer_id,
+            payload,
+        )
+    }
 
-        let edited_at = chrono::Utc::now().timestamp();
+    /// Queue an encoded read receipt until the peer confirms it. Returns its
+    /// queue key.
+    pub fn queue_outbound_read_receipt(&self, peer_id: &str, payload: &[u8]) -> Result<String> {
+        self.queue_outbound_change(
+            format!("receipt:{}", Uuid::new_v4()),
+            PendingOutboundKind::ReadReceipt,
+            None,
+            peer_id,
+            payload,
+        )
+    }
 
-        // Update in database
-        MessagesRepository::update_message_content(
+    fn queue_outbound_change(
+        &self,
+        key: String,
+        kind: PendingOutboundKind,
+        target_message_id: Option<&str>,
+        peer_id: &str,
+        payload: &[u8],
+    ) -> Result<String> {
+        PendingOutboundRepository::enqueue_change(
             &self.db,
-            message_id,
-            &new_content_encrypted,
-            edited_at,
+            &key,
+            kind,
+            target_message_id,
+            peer_id,
+            payload,
+            self.clock.timestamp(),
         )
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(key)
+    }
 
-        Ok(())
+    /// Replace the payload of a queued message, keeping its attempts.
+    /// Returns false if it has left the queue.
+    pub fn replace_queued_payload(&self, message_id: &str, payload: &[u8]) -> Result<bool> {
+        PendingOutboundRepository::replace_payload(&self.db, message_id, payload)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
-    /// Apply an incoming edit from a peer (re-encrypts and stores)
-    pub fn apply_incoming_edit(&self, message_id: &str, new_content: &str) -> Result<()> {
+    /// Re-seal one of our messages still waiting in the outbound queue with
+    /// its current content, so an edit made before it was delivered doesn't
+    /// follow a stale original. Returns None if the message isn't queued.
+    ///
+    /// The message keeps its ID, clock and nonce counter; only its content
+    /// and signature change.
+    pub fn reseal_queued_message(&self, message_id: &str) -> Result<Option<OutgoingMessage>> {
+        let queued = PendingOutboundRepository::get(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .is_some_and(|pending| pending.kind == PendingOutboundKind::Message);
+        if !queued {
+            return Ok(None);
+        }
+
         let identity = self
             .identity_service
             .get_identity()?
-            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
-
-        // Get the original message
-        let original = MessagesRepository::get_by_message_id(&self.db, message_id)
-            .map_err(|e| AppError::DatabaseString(e.to_string()))?
-            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
-
-        // Verify the message was sent by the peer (not by us)
-        if original.sender_peer_id == identity.peer_id {
-            return Err(AppError::Validation(
-                "Cannot apply incoming edit to our own message".to_string(),
-            ));
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+        let msg = self.own_message(&identity, message_id, "resend")?;
+        if msg.deleted_at.is_some() {
+            return Ok(None);
         }
 
-        let peer_id = &original.sender_peer_id;
-
-        // Get peer's X25519 key for encryption
-        let x25519_public = self
-            .contacts_service
-            .get_x25519_public(peer_id)?
-            .ok_or_else(|| AppError::NotFound("Contact not found".to_string()))?;
-
+        let peer_id = &msg.recipient_peer_id;
+        let x25519_public = self.conversation_x25519_public(&identity, peer_id)?;
         let our_keys = self.identity_service.get_unlocked_keys()?;
-
-        // Derive conversation key
         let their_public = X25519Public::from(
             <[u8; 32]>::try_from(x25519_public.as_slice())
                 .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))?,
@@ -726,29 +2195,546 @@ impl MessagingService {
         let shared_secret = CryptoService::x25519_dh(&our_keys.x25519_secret, &their_public);
         let conv_key = CryptoService::derive_conversation_key(
             &shared_secret,
-            &original.conversation_id,
+            &msg.conversation_id,
             &identity.peer_id,
             peer_id,
         );
+        let content = Self::decrypt_message_bytes(&conv_key, &msg)?;
 
-        // Re-encrypt with the same nonce counter
-        let new_content_encrypted = CryptoService::encrypt_message_with_counter(
+        // Sealed the way a new message would be; our stored copy already is
+        // under the conversation key
+        let ratcheted = self.ratchet_encrypt(
             &conv_key,
-            new_content.as_bytes(),
-            original.nonce_counter,
+            &msg.conversation_id,
+            peer_id,
+            &our_keys.x25519_secret,
+            &content,
         )?;
+        let (content_encrypted, nonce_salt, ratchet, cipher) = match ratcheted {
+            Some((header, ciphertext)) => {
+                (ciphertext, None, Some(header), MessageCipher::Aes256Gcm)
+            }
+            None => (
+                msg.content_encrypted.clone(),
+                msg.nonce_salt.clone(),
+                None,
+                msg.cipher.parse().map_err(AppError::Crypto)?,
+            ),
+        };
+        let voice = self.get_voice_note(message_id)?;
+
+        let signable = SignableDirectMessage {
+            message_id: msg.message_id.clone(),
+            conversation_id: msg.conversation_id.clone(),
+            sender_peer_id: msg.sender_peer_id.clone(),
+            recipient_peer_id: msg.recipient_peer_id.clone(),
+            content_encrypted: content_encrypted.clone(),
+            content_type: msg.content_type.clone(),
+            reply_to: msg.reply_to_message_id.clone(),
+            nonce_counter: msg.nonce_counter,
+            lamport_clock: msg.lamport_clock as u64,
+            timestamp: msg.sent_at,
+            nonce_salt: nonce_salt.clone(),
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            ratchet: ratchet.clone(),
+            cipher: cipher.as_str().to_string(),
+            voice: voice.clone(),
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(Some(OutgoingMessage {
+            message_id: signable.message_id,
+            conversation_id: signable.conversation_id,
+            sender_peer_id: signable.sender_peer_id,
+            recipient_peer_id: signable.recipient_peer_id,
+            content_encrypted,
+            content_type: signable.content_type,
+            reply_to: signable.reply_to,
+            nonce_counter: signable.nonce_counter,
+            lamport_clock: signable.lamport_clock,
+            timestamp: signable.timestamp,
+            signature,
+            nonce_salt,
+            protocol_version: CURRENT_PROTOCOL_VERSION,
+            ratchet,
+            cipher,
+            voice,
+        }))
+    }
+
+    /// Every message queued for a peer, due or not, to send once it's reachable
+    pub fn pending_outbound_for(&self, peer_id: &str) -> Result<Vec<PendingOutbound>> {
+        PendingOutboundRepository::get_for_peer(&self.db, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Queued messages, for any peer, whose next attempt is due
+    pub fn due_outbound(&self) -> Result<Vec<PendingOutbound>> {
+        PendingOutboundRepository::get_due(&self.db, self.clock.timestamp())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Take a delivered payload off the queue, marking a message sent
+    pub fn outbound_delivered(&self, message_id: &str) -> Result<()> {
+        let pending = PendingOutboundRepository::get(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        PendingOutboundRepository::remove(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        let kind = pending.map_or(PendingOutboundKind::Message, |pending| pending.kind);
+        if kind == PendingOutboundKind::Message {
+            self.record_send_outcome(message_id, true)?;
+        }
+        Ok(())
+    }
+
+    /// Record a failed delivery attempt and back off before the next one.
+    ///
+    /// Returns the attempts made once the last allowed one fails: the payload
+    /// is then taken off the queue, and a message is marked failed.
+    pub fn outbound_failed(&self, message_id: &str, error: &str) -> Result<Option<u32>> {
+        let Some(pending) = PendingOutboundRepository::get(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        let attempts = pending.attempts + 1;
+        if attempts >= self.get_max_send_attempts()? {
+            PendingOutboundRepository::remove(&self.db, message_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            if pending.kind == PendingOutboundKind::Message {
+                self.record_send_outcome(message_id, false)?;
+            }
+            return Ok(Some(attempts));
+        }
+
+        let backoff = OUTBOUND_RETRY_BASE_SECS
+            .saturating_mul(1 << (attempts - 1).min(16))
+            .min(OUTBOUND_RETRY_MAX_SECS);
+        PendingOutboundRepository::record_failure(
+            &self.db,
+            message_id,
+            error,
+            self.clock.timestamp() + backoff,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(None)
+    }
+
+    /// Count messages still waiting for delivery, to one peer or to anyone
+    pub fn get_pending_message_count(&self, peer_id: Option<&str>) -> Result<i64> {
+        PendingOutboundRepository::count(&self.db, peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Clear all messages in a conversation (keeps the conversation visible if new messages arrive)
+    pub fn clear_conversation_history(&self, peer_id: &str) -> Result<i64> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
+
+        let conversation_id = derive_conversation_id(&identity.peer_id, peer_id);
+
+        MessagesRepository::clear_conversation_messages(&self.db, &conversation_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Edit one of our messages: re-encrypts the new content, stores it and
+    /// returns the signed edit for the recipient.
+    ///
+    /// Edits carry a fresh lamport clock, so whichever edit has the highest
+    /// clock wins on both sides regardless of delivery order.
+    pub fn edit_message(&self, message_id: &str, new_content: &str) -> Result<OutgoingMessageEdit> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
+
+        let original = self.own_message(&identity, message_id, "edit")?;
+        if original.deleted_at.is_some() {
+            return Err(AppError::Validation(
+                "Cannot edit a deleted message".to_string(),
+            ));
+        }
+
+        // Determine the peer (the other party)
+        let peer_id = &original.recipient_peer_id;
+
+        // Get peer's X25519 key for encryption
+        let x25519_public = self.conversation_x25519_public(&identity, peer_id)?;
+
+        let our_keys = self.identity_service.get_unlocked_keys()?;
+
+        // Derive conversation key
+        let their_public = X25519Public::from(
+            <[u8; 32]>::try_from(x25519_public.as_slice())
+                .map_err(|_| AppError::Crypto("Invalid X25519 key".to_string()))?,
+        );
+        let shared_secret = CryptoService::x25519_dh(&our_keys.x25519_secret, &their_public);
+        let conv_key = CryptoService::derive_conversation_key(
+            &shared_secret,
+            &original.conversation_id,
+            &identity.peer_id,
+            peer_id,
+        );
 
-        let edited_at = chrono::Utc::now().timestamp();
+        let new_content = markdown::sanitize_content(&original.content_type, new_content);
+
+        // Our copy is re-encrypted under a fresh salt so the replaced content
+        // never reuses a nonce, keeping the cipher recorded for the message
+        let (stored_content, stored_nonce_salt) = Self::encrypt_content(
+            &conv_key,
+            new_content.as_bytes(),
+            original.nonce_counter,
+            NonceStrategy::SaltedCounter,
+            original.cipher.parse().map_err(AppError::Crypto)?,
+        )?;
+        let stored_nonce_salt = stored_nonce_salt
+            .ok_or_else(|| AppError::Crypto("Salted nonce produced no salt".to_string()))?;
+
+        // The recipient gets it the way a new message would go: under the
+        // ratchet when the conversation has one, otherwise as we stored it,
+        // since the conversation key is shared
+        let note_to_self = *peer_id == identity.peer_id;
+        let ratcheted = if note_to_self {
+            None
+        } else {
+            self.ratchet_encrypt(
+                &conv_key,
+                &original.conversation_id,
+                peer_id,
+                &our_keys.x25519_secret,
+                new_content.as_bytes(),
+            )?
+        };
+        let (content_encrypted, nonce_salt, ratchet) = match ratcheted {
+            Some((header, ciphertext)) => (ciphertext, Vec::new(), Some(header)),
+            None => (stored_content.clone(), stored_nonce_salt.clone(), None),
+        };
+
+        let lamport_clock =
+            self.db
+                .next_lamport_clock(&identity.peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
+        let edited_at = self.clock.timestamp();
+
+        let signable = SignableMessageEdit {
+            message_id: message_id.to_string(),
+            conversation_id: original.conversation_id.clone(),
+            sender_peer_id: identity.peer_id.clone(),
+            content_encrypted,
+            nonce_salt,
+            lamport_clock,
+            edited_at,
+            ratchet,
+        };
+        let signature = self.identity_service.sign(&signable)?;
 
-        // Update in database
-        MessagesRepository::update_message_content(
+        MessagesRepository::apply_edit(
             &self.db,
             message_id,
-            &new_content_encrypted,
+            &stored_content,
+            Some(&stored_nonce_salt),
+            lamport_clock as i64,
+            edited_at,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        self.record_edit_event(&signable, &original.recipient_peer_id, &signature)?;
+
+        Ok(OutgoingMessageEdit {
+            message_id: signable.message_id,
+            conversation_id: signable.conversation_id,
+            sender_peer_id: signable.sender_peer_id,
+            recipient_peer_id: original.recipient_peer_id,
+            content_encrypted: signable.content_encrypted,
+            nonce_salt: signable.nonce_salt,
+            lamport_clock,
             edited_at,
+            ratchet: signable.ratchet,
+            signature,
+        })
+    }
+
+    /// Delete one of our messages: wipes its content, leaving a tombstone,
+    /// and returns the signed tombstone for the recipient.
+    ///
+    /// Deleting an already deleted message signs its tombstone again, so a
+    /// delete that never reached the recipient can be retried.
+    pub fn delete_message(&self, message_id: &str) -> Result<OutgoingMessageDelete> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::NotFound("No identity".to_string()))?;
+
+        let original = self.own_message(&identity, message_id, "delete")?;
+
+        let lamport_clock =
+            self.db
+                .next_lamport_clock(&identity.peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
+        let deleted_at = original
+            .deleted_at
+            .unwrap_or_else(|| self.clock.timestamp());
+
+        let signable = SignableMessageDelete {
+            message_id: message_id.to_string(),
+            conversation_id: original.conversation_id.clone(),
+            sender_peer_id: identity.peer_id.clone(),
+            lamport_clock,
+            deleted_at,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        if MessagesRepository::soft_delete_message(&self.db, message_id, deleted_at)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        {
+            self.record_delete_event(&signable, &original.recipient_peer_id, &signature)?;
+        }
+
+        Ok(OutgoingMessageDelete {
+            message_id: signable.message_id,
+            conversation_id: signable.conversation_id,
+            sender_peer_id: signable.sender_peer_id,
+            recipient_peer_id: original.recipient_peer_id,
+            lamport_clock,
+            deleted_at,
+            signature,
+        })
+    }
+
+    /// Apply a peer's signed edit of a message they sent us.
+    ///
+    /// Returns false when the edit changed nothing: a newer edit was already
+    /// applied, the message has since been deleted, or it hasn't arrived yet
+    /// and the edit is held until it does.
+    pub fn process_incoming_edit(
+        &self,
+        edit: &SignableMessageEdit,
+        signature: &[u8],
+    ) -> Result<bool> {
+        // Ratcheted content has no salt of its own; it gets one when stored
+        let expected_salt_len = if edit.ratchet.is_some() {
+            0
+        } else {
+            NONCE_SALT_LEN
+        };
+        if edit.nonce_salt.len() != expected_salt_len {
+            return Err(AppError::Crypto("Invalid nonce salt length".to_string()));
+        }
+        let Some(original) = self.verify_sender_change(
+            &edit.message_id,
+            &edit.conversation_id,
+            &edit.sender_peer_id,
+            edit,
+            signature,
+        )?
+        else {
+            self.hold_change(
+                "edit",
+                &edit.message_id,
+                &edit.sender_peer_id,
+                edit.lamport_clock,
+                edit,
+                signature,
+            )?;
+            return Ok(false);
+        };
+        if original.deleted_at.is_some() {
+            return Ok(false);
+        }
+
+        let (content, nonce_salt) = match &edit.ratchet {
+            Some(header) => self.open_ratcheted_edit(edit, header, &original)?,
+            None => (edit.content_encrypted.clone(), edit.nonce_salt.clone()),
+        };
+        let applied = MessagesRepository::apply_edit(
+            &self.db,
+            &edit.message_id,
+            &content,
+            Some(&nonce_salt),
+            edit.lamport_clock as i64,
+            edit.edited_at,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if applied {
+            self.db
+                .update_lamport_clock(&edit.sender_peer_id, edit.lamport_clock as i64)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            self.record_edit_event(edit, &original.recipient_peer_id, signature)?;
+        }
+
+        Ok(applied)
+    }
+
+    /// Apply a peer's signed tombstone for a message they sent us.
+    ///
+    /// Returns false if the message was already deleted, so repeated
+    /// tombstones are harmless, or if it hasn't arrived yet, in which case
+    /// the tombstone is held and applied when it does.
+    pub fn process_incoming_delete(
+        &self,
+        delete: &SignableMessageDelete,
+        signature: &[u8],
+    ) -> Result<bool> {
+        let Some(original) = self.verify_sender_change(
+            &delete.message_id,
+            &delete.conversation_id,
+            &delete.sender_peer_id,
+            delete,
+            signature,
+        )?
+        else {
+            self.hold_change(
+                "delete",
+                &delete.message_id,
+                &delete.sender_peer_id,
+                delete.lamport_clock,
+                delete,
+                signature,
+            )?;
+            return Ok(false);
+        };
+
+        let deleted = MessagesRepository::soft_delete_message(
+            &self.db,
+            &delete.message_id,
+            delete.deleted_at,
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if deleted {
+            self.db
+                .update_lamport_clock(&delete.sender_peer_id, delete.lamport_clock as i64)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+            self.record_delete_event(delete, &original.recipient_peer_id, signature)?;
+        }
+
+        Ok(deleted)
+    }
+
+    /// Get a stored message we sent, refusing anyone else's
+    fn own_message(
+        &self,
+        identity: &LocalIdentity,
+        message_id: &str,
+        action: &str,
+    ) -> Result<Message> {
+        let message = MessagesRepository::get_by_message_id(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .ok_or_else(|| AppError::NotFound("Message not found".to_string()))?;
+
+        if message.sender_peer_id != identity.peer_id {
+            return Err(AppError::PermissionDenied(format!(
+                "Can only {} your own messages",
+                action
+            )));
+        }
+
+        Ok(message)
+    }
+
+    /// Check a peer's signed change to a stored message and return the
+    /// message, or None if it hasn't arrived yet. Only the message's original
+    /// sender may change it.
+    fn verify_sender_change(
+        &self,
+        message_id: &str,
+        conversation_id: &str,
+        editor_peer_id: &str,
+        signable: &impl Signable,
+        signature: &[u8],
+    ) -> Result<Option<Message>> {
+        let editor_public_key = self
+            .contacts_service
+            .get_public_key(editor_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Sender not in contacts".to_string()))?;
+        let verifying_key = VerifyingKey::from_bytes(
+            editor_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        if !verify(&verifying_key, signable, signature)? {
+            return Err(AppError::Crypto(
+                "Invalid message change signature".to_string(),
+            ));
+        }
+
+        let Some(original) = MessagesRepository::get_by_message_id(&self.db, message_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        else {
+            return Ok(None);
+        };
+
+        if original.sender_peer_id != editor_peer_id {
+            return Err(AppError::PermissionDenied(
+                "Only the sender can change a message".to_string(),
+            ));
+        }
+        if original.conversation_id != conversation_id {
+            return Err(AppError::Validation(
+                "Message belongs to a different conversation".to_string(),
+            ));
+        }
+
+        Ok(Some(original))
+    }
+
+    /// Record an applied edit in the message event log
+    fn record_edit_event(
+        &self,
+        edit: &SignableMessageEdit,
+        recipient_peer_id: &str,
+        signature: &[u8],
+    ) -> Result<()> {
+        let event_id = format!("edited:{}:{}", edit.message_id, edit.lamport_clock);
+        MessagesRepository::record_message_event(
+            &self.db,
+            &RecordMessageEventParams {
+                event_id: &event_id,
+                event_type: "edited",
+                message_id: &edit.message_id,
+                conversation_id: &edit.conversation_id,
+                sender_peer_id: &edit.sender_peer_id,
+                recipient_peer_id,
+                lamport_clock: edit.lamport_clock as i64,
+                timestamp: edit.edited_at,
+                payload_cbor: &edit.signable_bytes()?,
+                signature,
+                sig_version: CURRENT_SIG_VERSION,
+            },
         )
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(())
+    }
 
+    /// Record an applied deletion in the message event log
+    fn record_delete_event(
+        &self,
+        delete: &SignableMessageDelete,
+        recipient_peer_id: &str,
+        signature: &[u8],
+    ) -> Result<()> {
+        let event_id = format!("deleted:{}", delete.message_id);
+        MessagesRepository::record_message_event(
+            &self.db,
+            &RecordMessageEventParams {
+                event_id: &event_id,
+                event_type: "deleted",
+                message_id: &delete.message_id,
+                conversation_id: &delete.conversation_id,
+                sender_peer_id: &delete.sender_peer_id,
+                recipient_peer_id,
+                lamport_clock: delete.lamport_clock as i64,
+                timestamp: delete.deleted_at,
+                payload_cbor: &delete.signable_bytes()?,
+                signature,
+                sig_version: CURRENT_SIG_VERSION,
+            },
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
         Ok(())
     }
 
@@ -772,12 +2758,33 @@ impl MessagingService {
     }
 }
 
+fn voice_note_from_media(media: MessageMedia) -> VoiceNote {
+    VoiceNote {
+        media_hash: media.media_hash,
+        mime_type: media.mime_type,
+        size_bytes: media.size_bytes as u64,
+        duration_ms: media.duration_ms as u64,
+    }
+}
+
+/// Truncate message text to a single-line snippet of at most `REPLY_SNIPPET_MAX_CHARS`
+fn truncate_snippet(content: &str) -> String {
+    let flattened = content.split_whitespace().collect::<Vec<_>>().join(" ");
+    if flattened.chars().count() <= REPLY_SNIPPET_MAX_CHARS {
+        return flattened;
+    }
+    let truncated: String = flattened.chars().take(REPLY_SNIPPET_MAX_CHARS).collect();
+    format!("{}…", truncated.trim_end())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::db::{Capability, ContactData, ContactsRepository};
     use crate::models::CreateIdentityRequest;
-    use crate::services::{ContactsService, CryptoService, PermissionsService};
+    use crate::services::{
+        ContactsService, CryptoService, PermissionsService, PROTOCOL_VERSION_STATIC,
+    };
     use std::sync::Arc;
 
     /// Set up two identities (ours and a peer) and return the service plus metadata.
@@ -803,6 +2810,7 @@ mod tests {
                 passphrase: "test-pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
         let our_peer_id = info.peer_id;
@@ -892,6 +2900,7 @@ mod tests {
                 passphrase: "pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
 
@@ -903,6 +2912,38 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_send_transient_message_to_non_contact() {
+        let (service, _identity, our_peer_id, _peer_peer_id) = create_test_env();
+        let (_secret, stranger_x25519) = CryptoService::generate_x25519_keypair();
+
+        let msg = service
+            .send_transient_message(
+                "12D3KooWStranger",
+                stranger_x25519.as_bytes(),
+                "Hello!",
+                "text",
+            )
+            .unwrap();
+
+        assert_eq!(msg.sender_peer_id, our_peer_id);
+        assert_eq!(msg.recipient_peer_id, "12D3KooWStranger");
+        assert!(!msg.signature.is_empty());
+        // Nothing is persisted for one-off messages
+        assert!(service.get_conversations().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_send_transient_message_refuses_blocked_peer() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+        let (_secret, x25519) = CryptoService::generate_x25519_keypair();
+        ContactsRepository::block_contact(service.db(), &peer_peer_id).unwrap();
+
+        let result =
+            service.send_transient_message(&peer_peer_id, x25519.as_bytes(), "Hello!", "text");
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
     #[test]
     fn test_send_multiple_messages_increment_counters() {
         let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
@@ -918,11 +2959,138 @@ mod tests {
         assert!(msg2.lamport_clock > msg1.lamport_clock);
     }
 
+    /// Nonce actually used for an outgoing message
+    fn outgoing_nonce(msg: &OutgoingMessage) -> [u8; 12] {
+        match &msg.nonce_salt {
+            Some(salt) => CryptoService::nonce_from_salted_counter(
+                salt.as_slice().try_into().unwrap(),
+                msg.nonce_counter,
+            ),
+            None => CryptoService::nonce_from_counter(msg.nonce_counter),
+        }
+    }
+
     #[test]
-    fn test_get_conversations() {
+    fn test_nonces_never_repeat_across_counter_reset() {
         let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
 
-        // Send a message to create a conversation
+        let mut nonces = std::collections::HashSet::new();
+        for round in 0..3 {
+            for i in 0..5 {
+                let msg = service
+                    .send_message(&peer_peer_id, &format!("{}-{}", round, i), "text", None)
+                    .unwrap();
+                assert_eq!(msg.nonce_counter, i + 1);
+                assert!(nonces.insert(outgoing_nonce(&msg)), "nonce reused");
+            }
+
+            // Simulate a fresh install restoring the same key: counters start over
+            service
+                .db
+                .with_connection(|conn| conn.execute("DELETE FROM conversation_counters", []))
+                .unwrap();
+        }
+
+        // Every message still decrypts with its stored salt
+        let messages = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap();
+        assert_eq!(messages.len(), 15);
+        assert!(messages.iter().all(|m| m.content != "[Decryption failed]"));
+    }
+
+    #[test]
+    fn test_message_pages_cover_conversation_once() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        // Usually sent within one second, so the message ID orders them
+        for i in 0..5 {
+            service
+                .send_message(&peer_peer_id, &format!("msg {}", i), "text", None)
+                .unwrap();
+        }
+
+        let mut seen = Vec::new();
+        let mut cursor = None;
+        loop {
+            let page = service
+                .get_message_page(&peer_peer_id, Some(2), cursor.as_ref())
+                .unwrap();
+            assert!(page.messages.len() <= 2);
+            seen.splice(0..0, page.messages.into_iter().map(|m| m.message_id));
+            match page.next_cursor {
+                Some(next) => cursor = Some(next),
+                None => break,
+            }
+        }
+
+        let all: Vec<String> = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap()
+            .into_iter()
+            .map(|m| m.message_id)
+            .collect();
+        assert_eq!(seen, all);
+
+        // Oversized requests are clamped rather than refused
+        let page = service
+            .get_message_page(&peer_peer_id, Some(MAX_MESSAGE_PAGE_SIZE * 10), None)
+            .unwrap();
+        assert_eq!(page.messages.len(), 5);
+        assert!(page.next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_counter_nonce_strategy_omits_salt() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+        assert_eq!(
+            service.get_nonce_strategy().unwrap(),
+            NonceStrategy::SaltedCounter
+        );
+
+        service.set_nonce_strategy(NonceStrategy::Counter).unwrap();
+        assert_eq!(
+            service.get_nonce_strategy().unwrap(),
+            NonceStrategy::Counter
+        );
+
+        let msg = service
+            .send_message(&peer_peer_id, "Legacy", "text", None)
+            .unwrap();
+        assert!(msg.nonce_salt.is_none());
+
+        let messages = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap();
+        assert_eq!(messages[0].content, "Legacy");
+    }
+
+    #[test]
+    fn test_edit_message_uses_fresh_salt() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        let msg = service
+            .send_message(&peer_peer_id, "Before", "text", None)
+            .unwrap();
+        service.edit_message(&msg.message_id, "After").unwrap();
+
+        let stored = MessagesRepository::get_by_message_id(&service.db, &msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.nonce_counter, msg.nonce_counter);
+        assert_ne!(stored.nonce_salt, msg.nonce_salt);
+
+        let messages = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap();
+        assert_eq!(messages[0].content, "After");
+    }
+
+    #[test]
+    fn test_get_conversations() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        // Send a message to create a conversation
         service
             .send_message(&peer_peer_id, "Hello!", "text", None)
             .unwrap();
@@ -932,6 +3100,145 @@ mod tests {
         assert_eq!(conversations[0].peer_id, peer_peer_id);
     }
 
+    #[test]
+    fn test_get_conversations_previews_last_message() {
+        let (service, identity_service, _our_peer_id, peer_peer_id) = create_test_env();
+
+        service
+            .send_message(&peer_peer_id, "First", "text", None)
+            .unwrap();
+        let long = "word ".repeat(100);
+        service
+            .send_message(&peer_peer_id, &long, "text", None)
+            .unwrap();
+
+        let conversations = service.get_conversations().unwrap();
+        let preview = conversations[0].last_message_preview.as_deref().unwrap();
+        assert!(preview.starts_with("word word"));
+        assert!(preview.ends_with('…'));
+        assert!(preview.chars().count() <= REPLY_SNIPPET_MAX_CHARS + 1);
+
+        // Without keys the conversation is still listed
+        identity_service.lock();
+        let conversations = service.get_conversations().unwrap();
+        assert_eq!(
+            conversations[0].last_message_preview.as_deref(),
+            Some(ENCRYPTED_MESSAGE_PREVIEW)
+        );
+    }
+
+    #[test]
+    fn test_note_to_self_is_stored_and_readable() {
+        let (service, _identity, our_peer_id, _peer_peer_id) = create_test_env();
+
+        // No contact or chat grant is needed to message ourselves
+        let note = service
+            .send_message(&our_peer_id, "Buy milk", "text", None)
+            .unwrap();
+        assert_eq!(note.recipient_peer_id, our_peer_id);
+        assert!(note.ratchet.is_none());
+        let voice = VoiceNote {
+            media_hash: "ef".repeat(32),
+            mime_type: "audio/ogg".to_string(),
+            size_bytes: 1024,
+            duration_ms: 2_000,
+        };
+        service
+            .send_voice_note(&our_peer_id, &voice, "", Some(&note.message_id))
+            .unwrap();
+        assert!(service.is_note_to_self(&our_peer_id).unwrap());
+
+        let messages = service
+            .get_conversation_messages(&our_peer_id, 50, None)
+            .unwrap();
+        assert_eq!(messages.len(), 2);
+        assert!(messages.iter().all(|m| m.is_outgoing));
+        assert!(messages.iter().all(|m| m.status == "read"));
+        let text = messages.iter().find(|m| m.content_type == "text").unwrap();
+        assert_eq!(text.content, "Buy milk");
+        let voice_message = messages
+            .iter()
+            .find(|m| m.content_type == CONTENT_TYPE_VOICE)
+            .unwrap();
+        assert_eq!(voice_message.voice.as_ref(), Some(&voice));
+        assert_eq!(
+            voice_message.reply_preview.as_ref().unwrap().snippet,
+            "Buy milk"
+        );
+
+        // Listed as notes to self, never unread
+        let conversations = service.get_conversations().unwrap();
+        assert_eq!(conversations.len(), 1);
+        assert!(conversations[0].is_note_to_self);
+        assert_eq!(conversations[0].peer_id, our_peer_id);
+        assert_eq!(conversations[0].unread_count, 0);
+        assert_eq!(
+            conversations[0].last_message_preview.as_deref(),
+            Some(VOICE_NOTE_PREVIEW)
+        );
+
+        service
+            .edit_message(&note.message_id, "Buy oat milk")
+            .unwrap();
+        let messages = service
+            .get_conversation_messages(&our_peer_id, 50, None)
+            .unwrap();
+        assert!(messages.iter().any(|m| m.content == "Buy oat milk"));
+    }
+
+    #[test]
+    fn test_pinned_conversation_listed_first() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        service
+            .send_message(&peer_peer_id, "Older", "text", None)
+            .unwrap();
+        // A newer conversation with someone who isn't a contact
+        let identity = service.identity_service.get_identity().unwrap().unwrap();
+        let other_conv = derive_conversation_id(&identity.peer_id, "12D3KooWOther");
+        MessagesRepository::insert_message(
+            &service.db,
+            &MessageData {
+                message_id: "msg-other".to_string(),
+                conversation_id: other_conv.clone(),
+                sender_peer_id: "12D3KooWOther".to_string(),
+                recipient_peer_id: identity.peer_id.clone(),
+                content_encrypted: vec![1, 2, 3],
+                content_type: "text".to_string(),
+                reply_to_message_id: None,
+                nonce_counter: 1,
+                nonce_salt: None,
+                cipher: "aes-256-gcm".to_string(),
+                lamport_clock: 1,
+                sent_at: chrono::Utc::now().timestamp() + 60,
+                received_at: None,
+                status: MessageStatus::Delivered,
+            },
+        )
+        .unwrap();
+
+        let conversations = service.get_conversations().unwrap();
+        assert_eq!(conversations[0].conversation_id, other_conv);
+        assert_eq!(
+            conversations[0].last_message_preview.as_deref(),
+            Some(ENCRYPTED_MESSAGE_PREVIEW)
+        );
+
+        service
+            .set_conversation_pinned(&peer_peer_id, true)
+            .unwrap();
+        let conversations = service.get_conversations().unwrap();
+        assert_eq!(conversations[0].peer_id, peer_peer_id);
+        assert!(conversations[0].pinned);
+        assert!(!conversations[1].pinned);
+
+        service
+            .set_conversation_pinned(&peer_peer_id, false)
+            .unwrap();
+        let conversations = service.get_conversations().unwrap();
+        assert_eq!(conversations[0].conversation_id, other_conv);
+    }
+
     #[test]
     fn test_get_conversations_empty() {
         let (service, _identity, _our_peer_id, _peer_peer_id) = create_test_env();
@@ -1025,10 +3332,1306 @@ mod tests {
         assert_eq!(id1, id2);
     }
 
+    #[test]
+    fn test_conversation_id_matches_from_both_sides() {
+        let (service, _identity, our_peer_id, peer_peer_id) = create_test_env();
+
+        let msg = service
+            .send_message(&peer_peer_id, "Hello!", "text", None)
+            .unwrap();
+
+        assert_eq!(
+            msg.conversation_id,
+            derive_conversation_id(&peer_peer_id, &our_peer_id)
+        );
+    }
+
+    #[test]
+    fn test_incoming_message_without_chat_grant_rejected() {
+        let (service, _identity, our_peer_id, peer_peer_id) = create_test_env();
+
+        let grants = service
+            .permissions_service
+            .get_granted_permissions()
+            .unwrap();
+        for grant in grants {
+            service
+                .permissions_service
+                .revoke_permission(&grant.grant_id)
+                .unwrap();
+        }
+
+        let result = service.process_incoming_message(&IncomingMessageParams {
+            message_id: "msg-1",
+            conversation_id: &derive_conversation_id(&peer_peer_id, &our_peer_id),
+            sender_peer_id: &peer_peer_id,
+            recipient_peer_id: &our_peer_id,
+            content_encrypted: b"ciphertext",
+            content_type: "text",
+            reply_to: None,
+            nonce_counter: 1,
+            lamport_clock: 1,
+            timestamp: chrono::Utc::now().timestamp(),
+            signature: &[0u8; 64],
+            nonce_salt: None,
+            sig_version: CURRENT_SIG_VERSION,
+            protocol_version: PROTOCOL_VERSION_STATIC,
+            ratchet: None,
+            cipher: "aes-256-gcm",
+            voice: None,
+        });
+
+        assert!(matches!(result, Err(AppError::PermissionDenied(_))));
+    }
+
+    #[test]
+    fn test_incoming_message_with_spoofed_conversation_id_rejected() {
+        let (service, _identity, our_peer_id, peer_peer_id) = create_test_env();
+
+        let spoofed = derive_conversation_id(&peer_peer_id, "12D3KooWSomeoneElse");
+        let result = service.process_incoming_message(&IncomingMessageParams {
+            message_id: "msg-1",
+            conversation_id: &spoofed,
+            sender_peer_id: &peer_peer_id,
+            recipient_peer_id: &our_peer_id,
+            content_encrypted: b"ciphertext",
+            content_type: "text",
+            reply_to: None,
+            nonce_counter: 1,
+            lamport_clock: 1,
+            timestamp: chrono::Utc::now().timestamp(),
+            signature: &[0u8; 64],
+            nonce_salt: None,
+            sig_version: CURRENT_SIG_VERSION,
+            protocol_version: PROTOCOL_VERSION_STATIC,
+            ratchet: None,
+            cipher: "aes-256-gcm",
+            voice: None,
+        });
+
+        match result {
+            Err(AppError::Validation(msg)) => assert!(msg.contains("Conversation id")),
+            other => panic!("Expected validation error, got {:?}", other.err()),
+        }
+
+        // Rejected before the replay check, so the nonce was not consumed
+        let expected = derive_conversation_id(&peer_peer_id, &our_peer_id);
+        assert!(service
+            .db
+            .check_and_record_nonce(&expected, &peer_peer_id, 1)
+            .unwrap());
+    }
+
     #[test]
     fn test_conversation_id_different_peers() {
         let id1 = derive_conversation_id("peer-a", "peer-b");
         let id2 = derive_conversation_id("peer-a", "peer-c");
         assert_ne!(id1, id2);
     }
+
+    #[test]
+    fn test_reply_to_includes_snippet_preview() {
+        let (service, _identity, our_peer_id, peer_peer_id) = create_test_env();
+
+        let original = service
+            .send_message(&peer_peer_id, "The original message", "text", None)
+            .unwrap();
+        service
+            .send_message(
+                &peer_peer_id,
+                "A reply",
+                "text",
+                Some(original.message_id.as_str()),
+            )
+            .unwrap();
+
+        let messages = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap();
+        let reply = messages
+            .iter()
+            .find(|m| m.content == "A reply")
+            .expect("reply should be returned");
+        let preview = reply.reply_preview.as_ref().expect("preview expected");
+        assert_eq!(preview.message_id, original.message_id);
+        assert_eq!(
+            preview.sender_peer_id.as_deref(),
+            Some(our_peer_id.as_str())
+        );
+        assert_eq!(preview.snippet, "The original message");
+        assert!(!preview.is_deleted);
+    }
+
+    #[test]
+    fn test_reply_to_unknown_message_fails() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        let result = service.send_message(&peer_peer_id, "A reply", "text", Some("missing-id"));
+        assert!(matches!(result, Err(AppError::NotFound(_))));
+    }
+
+    #[test]
+    fn test_reply_to_deleted_message_shows_placeholder() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+
+        let original = service
+            .send_message(&peer_peer_id, "Soon to be gone", "text", None)
+            .unwrap();
+        let reply = service
+            .send_message(
+                &peer_peer_id,
+                "A reply",
+                "text",
+                Some(original.message_id.as_str()),
+            )
+            .unwrap();
+
+        service
+            .db()
+            .with_connection(|conn| {
+                conn.execute(
+                    "DELETE FROM messages WHERE message_id = ?",
+                    [&original.message_id],
+                )
+            })
+            .unwrap();
+
+        let messages = service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].message_id, reply.message_id);
+        let preview = messages[0].reply_preview.as_ref().unwrap();
+        assert!(preview.is_deleted);
+        assert!(preview.sender_peer_id.is_none());
+        assert_eq!(preview.snippet, DELETED_MESSAGE_SNIPPET);
+    }
+
+    #[test]
+    fn test_truncate_snippet() {
+        assert_eq!(truncate_snippet("short\ntext"), "short text");
+
+        let long = "a".repeat(REPLY_SNIPPET_MAX_CHARS + 10);
+        let snippet = truncate_snippet(&long);
+        assert_eq!(snippet.chars().count(), REPLY_SNIPPET_MAX_CHARS + 1);
+        assert!(snippet.ends_with('…'));
+    }
+
+    #[test]
+    fn test_retention_sweep_respects_overrides() {
+        let (service, _identity, _our_peer_id, peer_peer_id) = create_test_env();
+        const DAY: i64 = 24 * 60 * 60;
+
+        let msg = service
+            .send_message(&peer_peer_id, "Old news", "text", None)
+            .unwrap();
+        let later = msg.sent_at + 30 * DAY;
+
+        // No retention configured: nothing is pruned
+        assert_eq!(service.get_message_retention().unwrap(), None);
+        assert_eq!(
+            service.sweep_expired_messages(later).unwrap(),
+            RetentionSweepSummary::default()
+        );
+
+        assert!(matches!(
+            service.set_message_retention(Some(0)),
+            Err(AppError::Validation(_))
+        ));
+        service.set_message_retention(Some(7)).unwrap();
+        assert_eq!(service.get_message_retention().unwrap(), Some(7));
+
+        // Still inside the window
+        assert_eq!(
+            service
+                .sweep_expired_messages(msg.sent_at + DAY)
+                .unwrap()
+                .messages_deleted,
+            0
+        );
+
+        // Opted out of pruning
+        service
+            .set_conversation_retention(&peer_peer_id, None, true)
+            .unwrap();
+        assert_eq!(
+            service
+                .sweep_expired_messages(later)
+                .unwrap()
+                .messages_deleted,
+            0
+        );
+
+        // A longer per-conversation window wins over the global one
+        service
+            .set_conversation_retention(&peer_peer_id, Some(60), false)
+            .unwrap();
+        assert_eq!(
+            service
+                .get_conversation_retention(&peer_peer_id)
+                .unwrap()
+                .unwrap()
+                .retention_days,
+            Some(60)
+        );
+        assert_eq!(
+            service
+                .sweep_expired_messages(later)
+                .unwrap()
+                .messages_deleted,
+            0
+        );
+
+        // Clearing the override falls back to the global window
+        service
+            .set_conversation_retention(&peer_peer_id, None, false)
+            .unwrap();
+        assert!(service
+            .get_conversation_retention(&peer_peer_id)
+            .unwrap()
+            .is_none());
+        assert_eq!(
+            service.sweep_expired_messages(later).unwrap(),
+            RetentionSweepSummary {
+                messages_deleted: 1,
+                conversations_pruned: 1,
+            }
+        );
+        assert!(service
+            .get_conversation_messages(&peer_peer_id, 50, None)
+            .unwrap()
+            .is_empty());
+    }
+
+    /// Add a contact whose messages the test can sign, with chat granted
+    fn add_signing_peer(service: &MessagingService) -> (ed25519_dalek::SigningKey, String) {
+        let (signing_key, verifying_key) = CryptoService::generate_ed25519_keypair();
+        let (_, x25519_public) = CryptoService::generate_x25519_keypair();
+        let peer_id = "12D3KooWSigningPeer".to_string();
+
+        ContactsRepository::add_contact(
+            &service.db,
+            &ContactData {
+                peer_id: peer_id.clone(),
+                public_key: verifying_key.to_bytes().to_vec(),
+                x25519_public: x25519_public.to_bytes().to_vec(),
+                display_name: "Signing Peer".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+        service
+            .permissions_service
+            .create_permission_grant(&peer_id, Capability::Chat, None)
+            .unwrap();
+
+        (signing_key, peer_id)
+    }
+
+    /// Deliver a correctly signed message from `sender` to us
+    fn receive_signed(
+        service: &MessagingService,
+        signing_key: &ed25519_dalek::SigningKey,
+        sender: &str,
+        recipient: &str,
+        message_id: &str,
+        nonce_counter: u64,
+        lamport_clock: u64,
+    ) -> Result<()> {
+        let conversation_id = derive_conversation_id(sender, recipient);
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignableDirectMessage {
+            message_id: message_id.to_string(),
+            conversation_id: conversation_id.clone(),
+            sender_peer_id: sender.to_string(),
+            recipient_peer_id: recipient.to_string(),
+            content_encrypted: b"ciphertext".to_vec(),
+            content_type: "text".to_string(),
+            reply_to: None,
+            nonce_counter,
+            lamport_clock,
+            timestamp,
+            nonce_salt: None,
+            protocol_version: PROTOCOL_VERSION_STATIC,
+            ratchet: None,
+            cipher: "aes-256-gcm".to_string(),
+            voice: None,
+        };
+        let signature = crate::services::sign(signing_key, &signable).unwrap();
+
+        service.process_incoming_message(&IncomingMessageParams {
+            message_id,
+            conversation_id: &conversation_id,
+            sender_peer_id: sender,
+            recipient_peer_id: recipient,
+            content_encrypted: b"ciphertext",
+            content_type: "text",
+            reply_to: None,
+            nonce_counter,
+            lamport_clock,
+            timestamp,
+            signature: &signature,
+            nonce_salt: None,
+            sig_version: CURRENT_SIG_VERSION,
+            protocol_version: PROTOCOL_VERSION_STATIC,
+            ratchet: None,
+            cipher: "aes-256-gcm",
+            voice: None,
+        })
+    }
+
+    #[test]
+    fn test_incoming_message_reusing_lamport_clock_rejected() {
+        let (service, _identity, our_peer_id, _) = create_test_env();
+        let (key, sender) = add_signing_peer(&service);
+
+        receive_signed(&service, &key, &sender, &our_peer_id, "msg-1", 1, 5).unwrap();
+
+        // Same clock under a fresh message id and nonce
+        let result = receive_signed(&service, &key, &sender, &our_peer_id, "msg-2", 2, 5);
+        assert!(matches!(result, Err(AppError::Crypto(_))));
+        assert!(!MessagesRepository::message_exists(&service.db, "msg-2").unwrap());
+
+        // The same clock in another conversation is unrelated
+        let conversation_id = derive_conversation_id(&sender, &our_peer_id);
+        assert_eq!(
+            service
+                .db
+                .get_highest_received_message_clock(&conversation_id, &sender)
+                .unwrap(),
+            Some(5)
+        );
+        assert_eq!(
+            service
+                .db
+                .check_and_record_message_clock("other-conversation", &sender, 5, "msg-3")
+                .unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_incoming_messages_out_of_order_fill_gaps() {
+        let (service, _identity, our_peer_id, _) = create_test_env();
+        let (key, sender) = add_signing_peer(&service);
+
+        // Clock 7 arrives before 3 and 5, which were sent earlier
+        receive_signed(&service, &key, &sender, &our_peer_id, "msg-7", 7, 7).unwrap();
+        receive_signed(&service, &key, &sender, &our_peer_id, "msg-3", 3, 3).unwrap();
+        receive_signed(&service, &key, &sender, &our_peer_id, "msg-5", 5, 5).unwrap();
+
+        for message_id in ["msg-3", "msg-5", "msg-7"] {
+            assert!(MessagesRepository::message_exists(&service.db, message_id).unwrap());
+        }
+
+        // A filled gap is no longer open
+        let result = receive_signed(&service, &key, &sender, &our_peer_id, "msg-3b", 8, 3);
+        assert!(matches!(result, Err(AppError::Crypto(_))));
+    }
+
+    /// A messaging service with its own identity, plus that identity
+    fn create_user(name: &str) -> (MessagingService, crate::models::IdentityInfo) {
+        let db = Arc::new(Database::in_memory().unwrap());
+        let identity_service = Arc::new(IdentityService::new(db.clone()));
+        let contacts_service = Arc::new(ContactsService::new(db.clone(), identity_service.clone()));
+        let permissions_service = Arc::new(PermissionsService::new(
+            db.clone(),
+            identity_service.clone(),
+        ));
+        let info = identity_service
+            .create_identity(CreateIdentityRequest {
+                display_name: name.to_string(),
+                passphrase: "test-pass".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+
+        let service =
+            MessagingService::new(db, identity_service, contacts_service, permissions_service);
+        (service, info)
+    }
+
+    /// Add `other` as a contact of `service` with chat granted
+    fn befriend(service: &MessagingService, other: &crate::models::IdentityInfo) {
+        use base64::Engine;
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        ContactsRepository::add_contact(
+            &service.db,
+            &ContactData {
+                peer_id: other.peer_id.clone(),
+                public_key: engine.decode(&other.public_key).unwrap(),
+                x25519_public: engine.decode(&other.x25519_public).unwrap(),
+                display_name: other.display_name.clone(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+        service
+            .permissions_service
+            .create_permission_grant(&other.peer_id, Capability::Chat, None)
+            .unwrap();
+    }
+
+    fn deliver(to: &MessagingService, msg: &OutgoingMessage) -> Result<()> {
+        to.process_incoming_message(&IncomingMessageParams {
+            message_id: &msg.message_id,
+            conversation_id: &msg.conversation_id,
+            sender_peer_id: &msg.sender_peer_id,
+            recipient_peer_id: &msg.recipient_peer_id,
+            content_encrypted: &msg.content_encrypted,
+            content_type: &msg.content_type,
+            reply_to: msg.reply_to.as_deref(),
+            nonce_counter: msg.nonce_counter,
+            lamport_clock: msg.lamport_clock,
+            timestamp: msg.timestamp,
+            signature: &msg.signature,
+            nonce_salt: msg.nonce_salt.as_deref(),
+            sig_version: CURRENT_SIG_VERSION,
+            protocol_version: msg.protocol_version,
+            ratchet: msg.ratchet.as_ref(),
+            cipher: msg.cipher.as_str(),
+            voice: msg.voice.as_ref(),
+        })
+    }
+
+    fn history(service: &MessagingService, peer_id: &str) -> Vec<String> {
+        let mut contents: Vec<String> = service
+            .get_conversation_messages(peer_id, 10, None)
+            .unwrap()
+            .into_iter()
+            .map(|msg| msg.content)
+            .collect();
+        contents.sort();
+        contents
+    }
+
+    fn enable_ratchet(service: &MessagingService) {
+        FeatureFlagsService::new(service.db.clone())
+            .set_enabled(FeatureFlag::RatchetEncryption, true)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_conversation_upgrades_to_ratchet() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+        enable_ratchet(&alice);
+        enable_ratchet(&bob);
+
+        // Alice hasn't heard Bob announce protocol 2 yet
+        let first = alice
+            .send_message(&bob_info.peer_id, "hello bob", "text", None)
+            .unwrap();
+        assert!(first.ratchet.is_none());
+        assert_eq!(first.protocol_version, CURRENT_PROTOCOL_VERSION);
+        deliver(&bob, &first).unwrap();
+
+        let reply = bob
+            .send_message(&alice_info.peer_id, "hello alice", "text", None)
+            .unwrap();
+        assert!(reply.ratchet.is_some());
+        deliver(&alice, &reply).unwrap();
+
+        let second = alice
+            .send_message(&bob_info.peer_id, "ratcheted", "text", None)
+            .unwrap();
+        assert!(second.ratchet.is_some());
+        deliver(&bob, &second).unwrap();
+
+        // A replay under a fresh message id is still rejected
+        let mut replayed = second.clone();
+        replayed.message_id = "replayed".to_string();
+        assert!(deliver(&bob, &replayed).is_err());
+
+        // Both sides read every message from their stored copies
+        let expected = vec![
+            "hello alice".to_string(),
+            "hello bob".to_string(),
+            "ratcheted".to_string(),
+        ];
+        assert_eq!(history(&alice, &bob_info.peer_id), expected);
+        assert_eq!(history(&bob, &alice_info.peer_id), expected);
+    }
+
+    #[test]
+    fn test_ratchet_flag_off_still_reads_ratcheted_messages() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+        enable_ratchet(&bob);
+
+        let first = alice
+            .send_message(&bob_info.peer_id, "hello bob", "text", None)
+            .unwrap();
+        deliver(&bob, &first).unwrap();
+        let reply = bob
+            .send_message(&alice_info.peer_id, "hello alice", "text", None)
+            .unwrap();
+        assert!(reply.ratchet.is_some());
+        deliver(&alice, &reply).unwrap();
+
+        // Without the flag Alice keeps sending statically keyed messages
+        let second = alice
+            .send_message(&bob_info.peer_id, "still static", "text", None)
+            .unwrap();
+        assert!(second.ratchet.is_none());
+        deliver(&bob, &second).unwrap();
+
+        let expected = vec![
+            "hello alice".to_string(),
+            "hello bob".to_string(),
+            "still static".to_string(),
+        ];
+        assert_eq!(history(&alice, &bob_info.peer_id), expected);
+        assert_eq!(history(&bob, &alice_info.peer_id), expected);
+    }
+
+    #[test]
+    fn test_voice_note_delivered_with_metadata() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let voice = VoiceNote {
+            media_hash: "cd".repeat(32),
+            mime_type: "audio/ogg".to_string(),
+            size_bytes: 4096,
+            duration_ms: 2_500,
+        };
+        // Plain sends can't claim to be voice notes
+        assert!(alice
+            .send_message(&bob_info.peer_id, "", CONTENT_TYPE_VOICE, None)
+            .is_err());
+
+        let sent = alice
+            .send_voice_note(&bob_info.peer_id, &voice, "", None)
+            .unwrap();
+        assert_eq!(sent.voice.as_ref(), Some(&voice));
+
+        // Stripping the audio from a voice note, or adding audio to a text
+        // message, is refused before anything is stored
+        let mut stripped = sent.clone();
+        stripped.voice = None;
+        assert!(matches!(
+            deliver(&bob, &stripped),
+            Err(AppError::Validation(_))
+        ));
+        let mut smuggled = sent.clone();
+        smuggled.content_type = "text".to_string();
+        assert!(matches!(
+            deliver(&bob, &smuggled),
+            Err(AppError::Validation(_))
+        ));
+
+        deliver(&bob, &sent).unwrap();
+        let received = bob
+            .get_conversation_messages(&alice_info.peer_id, 10, None)
+            .unwrap();
+        assert_eq!(received.len(), 1);
+        assert_eq!(received[0].content_type, CONTENT_TYPE_VOICE);
+        assert_eq!(received[0].voice.as_ref(), Some(&voice));
+        assert_eq!(
+            bob.voice_note_for_media(&voice.media_hash).unwrap(),
+            Some(voice.clone())
+        );
+        assert!(bob
+            .voice_note_shared_with(&voice.media_hash, &alice_info.peer_id)
+            .unwrap());
+        assert!(!bob
+            .voice_note_shared_with(&voice.media_hash, "12D3KooWStranger")
+            .unwrap());
+
+        let conversations = bob.get_conversations().unwrap();
+        assert_eq!(
+            conversations[0].last_message_preview.as_deref(),
+            Some(VOICE_NOTE_PREVIEW)
+        );
+    }
+
+    #[test]
+    fn test_legacy_peer_gets_static_messages() {
+        let (service, _identity, our_peer_id, _) = create_test_env();
+        let (key, sender) = add_signing_peer(&service);
+
+        receive_signed(&service, &key, &sender, &our_peer_id, "msg-1", 1, 1).unwrap();
+
+        let reply = service.send_message(&sender, "hi", "text", None).unwrap();
+        assert!(reply.ratchet.is_none());
+        assert!(
+            RatchetRepository::get_state(&service.db, &reply.conversation_id)
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_preferred_cipher_needs_peer_support() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+        alice
+            .set_message_cipher(MessageCipher::ChaCha20Poly1305)
+            .unwrap();
+
+        // Bob hasn't announced ChaCha20-Poly1305 yet
+        let first = alice
+            .send_message(&bob_info.peer_id, "plain aes", "text", None)
+            .unwrap();
+        assert_eq!(first.cipher, MessageCipher::Aes256Gcm);
+        deliver(&bob, &first).unwrap();
+
+        alice
+            .contacts_service
+            .set_supported_ciphers(&bob_info.peer_id, &MessageCipher::supported_identifiers())
+            .unwrap();
+        let second = alice
+            .send_message(&bob_info.peer_id, "chacha", "text", None)
+            .unwrap();
+        assert_eq!(second.cipher, MessageCipher::ChaCha20Poly1305);
+        deliver(&bob, &second).unwrap();
+
+        // Each message is read back with the cipher stored for it
+        let expected = vec!["chacha".to_string(), "plain aes".to_string()];
+        assert_eq!(history(&alice, &bob_info.peer_id), expected);
+        assert_eq!(history(&bob, &alice_info.peer_id), expected);
+    }
+
+    #[test]
+    fn test_incoming_message_with_unknown_cipher_rejected() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "hello", "text", None)
+            .unwrap();
+        let result = bob.process_incoming_message(&IncomingMessageParams {
+            message_id: &msg.message_id,
+            conversation_id: &msg.conversation_id,
+            sender_peer_id: &msg.sender_peer_id,
+            recipient_peer_id: &msg.recipient_peer_id,
+            content_encrypted: &msg.content_encrypted,
+            content_type: &msg.content_type,
+            reply_to: None,
+            nonce_counter: msg.nonce_counter,
+            lamport_clock: msg.lamport_clock,
+            timestamp: msg.timestamp,
+            signature: &msg.signature,
+            nonce_salt: msg.nonce_salt.as_deref(),
+            sig_version: CURRENT_SIG_VERSION,
+            protocol_version: msg.protocol_version,
+            ratchet: None,
+            cipher: "post-quantum-someday",
+            voice: None,
+        });
+
+        assert!(matches!(result, Err(AppError::Crypto(_))));
+        assert!(!MessagesRepository::message_exists(&bob.db, &msg.message_id).unwrap());
+
+        // Nothing was recorded, so the genuine message still goes through
+        deliver(&bob, &msg).unwrap();
+    }
+
+    fn deliver_edit(to: &MessagingService, edit: &OutgoingMessageEdit) -> Result<bool> {
+        to.process_incoming_edit(
+            &SignableMessageEdit {
+                message_id: edit.message_id.clone(),
+                conversation_id: edit.conversation_id.clone(),
+                sender_peer_id: edit.sender_peer_id.clone(),
+                content_encrypted: edit.content_encrypted.clone(),
+                nonce_salt: edit.nonce_salt.clone(),
+                lamport_clock: edit.lamport_clock,
+                edited_at: edit.edited_at,
+                ratchet: edit.ratchet.clone(),
+            },
+            &edit.signature,
+        )
+    }
+
+    fn deliver_delete(to: &MessagingService, delete: &OutgoingMessageDelete) -> Result<bool> {
+        to.process_incoming_delete(
+            &SignableMessageDelete {
+                message_id: delete.message_id.clone(),
+                conversation_id: delete.conversation_id.clone(),
+                sender_peer_id: delete.sender_peer_id.clone(),
+                lamport_clock: delete.lamport_clock,
+                deleted_at: delete.deleted_at,
+            },
+            &delete.signature,
+        )
+    }
+
+    #[test]
+    fn test_out_of_order_edits_settle_on_latest() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "helo", "text", None)
+            .unwrap();
+        deliver(&bob, &msg).unwrap();
+
+        let first = alice.edit_message(&msg.message_id, "hello").unwrap();
+        let second = alice.edit_message(&msg.message_id, "hello!").unwrap();
+        assert!(second.lamport_clock > first.lamport_clock);
+
+        // The later edit overtakes the earlier one on the way
+        assert!(deliver_edit(&bob, &second).unwrap());
+        assert!(!deliver_edit(&bob, &first).unwrap());
+
+        let expected = vec!["hello!".to_string()];
+        assert_eq!(history(&alice, &bob_info.peer_id), expected);
+        assert_eq!(history(&bob, &alice_info.peer_id), expected);
+    }
+
+    #[test]
+    fn test_edit_of_ratcheted_conversation_uses_ratchet() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+        enable_ratchet(&alice);
+        enable_ratchet(&bob);
+
+        let first = alice
+            .send_message(&bob_info.peer_id, "hello bob", "text", None)
+            .unwrap();
+        deliver(&bob, &first).unwrap();
+        let reply = bob
+            .send_message(&alice_info.peer_id, "hello alice", "text", None)
+            .unwrap();
+        deliver(&alice, &reply).unwrap();
+
+        let edit = alice.edit_message(&first.message_id, "hi bob").unwrap();
+        assert!(edit.ratchet.is_some());
+        assert!(edit.nonce_salt.is_empty());
+        assert!(deliver_edit(&bob, &edit).unwrap());
+
+        let expected = vec!["hello alice".to_string(), "hi bob".to_string()];
+        assert_eq!(history(&alice, &bob_info.peer_id), expected);
+        assert_eq!(history(&bob, &alice_info.peer_id), expected);
+    }
+
+    #[test]
+    fn test_changes_arriving_before_their_message_are_held() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let kept = alice
+            .send_message(&bob_info.peer_id, "helo", "text", None)
+            .unwrap();
+        let removed = alice
+            .send_message(&bob_info.peer_id, "wrong chat", "text", None)
+            .unwrap();
+        let first_edit = alice.edit_message(&kept.message_id, "hello").unwrap();
+        let second_edit = alice.edit_message(&kept.message_id, "hello!").unwrap();
+        let delete = alice.delete_message(&removed.message_id).unwrap();
+
+        // The changes overtake the messages they change
+        assert!(!deliver_edit(&bob, &second_edit).unwrap());
+        assert!(!deliver_edit(&bob, &first_edit).unwrap());
+        assert!(!deliver_delete(&bob, &delete).unwrap());
+        assert!(history(&bob, &alice_info.peer_id).is_empty());
+
+        deliver(&bob, &kept).unwrap();
+        deliver(&bob, &removed).unwrap();
+
+        let messages = bob
+            .get_conversation_messages(&alice_info.peer_id, 10, None)
+            .unwrap();
+        let kept_now = messages
+            .iter()
+            .find(|msg| msg.message_id == kept.message_id)
+            .unwrap();
+        assert_eq!(kept_now.content, "hello!");
+        let removed_now = messages
+            .iter()
+            .find(|msg| msg.message_id == removed.message_id)
+            .unwrap();
+        assert_eq!(removed_now.deleted_at, Some(delete.deleted_at));
+        assert!(removed_now.content.is_empty());
+    }
+
+    #[test]
+    fn test_delete_message_is_idempotent() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "wrong chat", "text", None)
+            .unwrap();
+        deliver(&bob, &msg).unwrap();
+
+        let delete = alice.delete_message(&msg.message_id).unwrap();
+        assert!(deliver_delete(&bob, &delete).unwrap());
+        assert!(!deliver_delete(&bob, &delete).unwrap());
+
+        // Retrying keeps the original tombstone
+        let retry = alice.delete_message(&msg.message_id).unwrap();
+        assert_eq!(retry.deleted_at, delete.deleted_at);
+        assert!(!deliver_delete(&bob, &retry).unwrap());
+
+        let messages = bob
+            .get_conversation_messages(&alice_info.peer_id, 10, None)
+            .unwrap();
+        assert_eq!(messages.len(), 1);
+        assert_eq!(messages[0].deleted_at, Some(delete.deleted_at));
+        assert!(messages[0].content.is_empty());
+
+        // A deleted message can no longer be edited, by either side
+        assert!(matches!(
+            alice.edit_message(&msg.message_id, "oops"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(matches!(
+            bob.delete_message(&msg.message_id),
+            Err(AppError::PermissionDenied(_))
+        ));
+    }
+
+    #[test]
+    fn test_delete_message_leaves_no_ciphertext() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "my password is hunter2", "text", None)
+            .unwrap();
+        deliver(&bob, &msg).unwrap();
+        let edit = alice.edit_message(&msg.message_id, "never mind").unwrap();
+        assert!(deliver_edit(&bob, &edit).unwrap());
+
+        let delete = alice.delete_message(&msg.message_id).unwrap();
+        assert!(deliver_delete(&bob, &delete).unwrap());
+
+        for service in [&alice, &bob] {
+            service
+                .db()
+                .with_connection(|conn| {
+                    let event_types: Vec<String> = conn
+                        .prepare("SELECT event_type FROM message_events WHERE message_id = ?")?
+                        .query_map([&msg.message_id], |row| row.get(0))?
+                        .collect::<rusqlite::Result<_>>()?;
+                    assert_eq!(event_types, vec!["deleted"]);
+
+                    for ciphertext in [&msg.content_encrypted, &edit.content_encrypted] {
+                        let copies: i64 = conn.query_row(
+                            "SELECT (SELECT COUNT(*) FROM messages
+                                     WHERE instr(content_encrypted, ?1) > 0)
+                                  + (SELECT COUNT(*) FROM message_events
+                                     WHERE instr(payload_cbor, ?1) > 0)",
+                            [ciphertext],
+                            |row| row.get(0),
+                        )?;
+                        assert_eq!(copies, 0);
+                    }
+                    Ok(())
+                })
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_edit_from_someone_other_than_sender_rejected() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        let (carol, carol_info) = create_user("Carol");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+        befriend(&bob, &carol_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "original", "text", None)
+            .unwrap();
+        deliver(&bob, &msg).unwrap();
+
+        // Carol signs a valid edit of a message she didn't send
+        let forged = SignableMessageEdit {
+            message_id: msg.message_id.clone(),
+            conversation_id: msg.conversation_id.clone(),
+            sender_peer_id: carol_info.peer_id.clone(),
+            content_encrypted: vec![0; 32],
+            nonce_salt: vec![0; NONCE_SALT_LEN],
+            lamport_clock: 1_000,
+            edited_at: 1,
+            ratchet: None,
+        };
+        let signature = carol.identity_service.sign(&forged).unwrap();
+        assert!(matches!(
+            bob.process_incoming_edit(&forged, &signature),
+            Err(AppError::PermissionDenied(_))
+        ));
+
+        // Nor can she pass it off as Alice's
+        let impersonated = SignableMessageEdit {
+            sender_peer_id: alice_info.peer_id.clone(),
+            ..forged
+        };
+        let signature = carol.identity_service.sign(&impersonated).unwrap();
+        assert!(matches!(
+            bob.process_incoming_edit(&impersonated, &signature),
+            Err(AppError::Crypto(_))
+        ));
+
+        assert_eq!(
+            history(&bob, &alice_info.peer_id),
+            vec!["original".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_search_messages_skips_undecryptable() {
+        use crate::services::clock::MockClock;
+
+        // Messages a second apart, so newest first is well defined
+        let clock = Arc::new(MockClock::at_timestamp(1_700_000_000));
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        let alice = alice.with_clock(clock.clone());
+        let bob = bob.with_clock(clock.clone());
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let first = alice
+            .send_message(&bob_info.peer_id, "Lunch on Friday?", "text", None)
+            .unwrap();
+        deliver(&bob, &first).unwrap();
+        clock.advance(chrono::Duration::seconds(1));
+        let reply = bob
+            .send_message(&alice_info.peer_id, "friday works", "text", None)
+            .unwrap();
+        deliver(&alice, &reply).unwrap();
+        clock.advance(chrono::Duration::seconds(1));
+        let other = alice
+            .send_message(&bob_info.peer_id, "unrelated", "text", None)
+            .unwrap();
+        deliver(&bob, &other).unwrap();
+
+        // Case-insensitive substring match, newest first
+        let mut streamed = 0;
+        let found = alice
+            .search_messages("FRIDAY", None, None, |batch| streamed += batch.len())
+            .unwrap();
+        let ids: Vec<&str> = found.iter().map(|m| m.message_id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![reply.message_id.as_str(), first.message_id.as_str()]
+        );
+        assert_eq!(streamed, 2);
+        assert!(!found[0].is_outgoing);
+
+        let limited = alice
+            .search_messages("friday", Some(&first.conversation_id), Some(1), |_| {})
+            .unwrap();
+        assert_eq!(limited.len(), 1);
+
+        // A corrupted message is skipped, not fatal
+        alice
+            .db()
+            .with_connection(|conn| {
+                conn.execute(
+                    "UPDATE messages SET content_encrypted = X'00' WHERE message_id = ?",
+                    [&reply.message_id],
+                )
+            })
+            .unwrap();
+        let found = alice.search_messages("friday", None, None, |_| {}).unwrap();
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].message_id, first.message_id);
+
+        assert!(matches!(
+            alice.search_messages("  ", None, None, |_| {}),
+            Err(AppError::Validation(_))
+        ));
+    }
+
+    #[test]
+    fn test_read_receipt_marks_sent_messages_seen() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let mut sent = Vec::new();
+        for content in ["one", "two"] {
+            let msg = alice
+                .send_message(&bob_info.peer_id, content, "text", None)
+                .unwrap();
+            deliver(&bob, &msg).unwrap();
+            sent.push(msg.message_id);
+        }
+
+        let mut read = bob.mark_conversation_read(&alice_info.peer_id).unwrap();
+        read.sort();
+        sent.sort();
+        assert_eq!(read, sent);
+        let (receipt, signature) = bob.create_read_receipt(&alice_info.peer_id, &read).unwrap();
+
+        // A tampered receipt is refused
+        let mut tampered = receipt.clone();
+        tampered.read_at += 1;
+        assert!(matches!(
+            alice.process_incoming_read_receipt(&tampered, &signature),
+            Err(AppError::Crypto(_))
+        ));
+
+        let mut seen = alice
+            .process_incoming_read_receipt(&receipt, &signature)
+            .unwrap();
+        seen.sort();
+        assert_eq!(seen, sent);
+        for message_id in &sent {
+            let stored = MessagesRepository::get_by_message_id(&alice.db, message_id)
+                .unwrap()
+                .unwrap();
+            assert_eq!(stored.status, "read");
+            assert_eq!(stored.read_at, Some(receipt.read_at));
+        }
+
+        // Receipts are idempotent, and reading again has nothing new to report
+        assert!(alice
+            .process_incoming_read_receipt(&receipt, &signature)
+            .unwrap()
+            .is_empty());
+        assert!(bob
+            .mark_conversation_read(&alice_info.peer_id)
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn test_resent_message_accepted_once() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "did you get this?", "text", None)
+            .unwrap();
+        deliver(&bob, &msg).unwrap();
+
+        // Our response was lost, so Alice sends the same message again
+        deliver(&bob, &msg).unwrap();
+        assert_eq!(
+            history(&bob, &alice_info.peer_id),
+            vec!["did you get this?"]
+        );
+    }
+
+    #[test]
+    fn test_outbound_queue_backs_off_then_gives_up() {
+        use crate::services::clock::MockClock;
+
+        let clock = Arc::new(MockClock::at_timestamp(1_700_000_000));
+        let (alice, _) = create_user("Alice");
+        let (_, bob_info) = create_user("Bob");
+        let alice = alice.with_clock(clock.clone());
+        befriend(&alice, &bob_info);
+        alice.set_max_send_attempts(3).unwrap();
+        assert!(alice.set_max_send_attempts(0).is_err());
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "are you there?", "text", None)
+            .unwrap();
+        alice
+            .queue_outbound(&msg.message_id, &bob_info.peer_id, b"payload")
+            .unwrap();
+        assert_eq!(
+            alice
+                .get_pending_message_count(Some(&bob_info.peer_id))
+                .unwrap(),
+            1
+        );
+        assert_eq!(alice.due_outbound().unwrap().len(), 1);
+
+        // Each failure waits twice as long before the next attempt
+        assert_eq!(
+            alice.outbound_failed(&msg.message_id, "offline").unwrap(),
+            None
+        );
+        clock.advance(chrono::Duration::seconds(OUTBOUND_RETRY_BASE_SECS - 1));
+        assert!(alice.due_outbound().unwrap().is_empty());
+        clock.advance(chrono::Duration::seconds(1));
+        assert_eq!(alice.due_outbound().unwrap().len(), 1);
+
+        assert_eq!(
+            alice.outbound_failed(&msg.message_id, "offline").unwrap(),
+            None
+        );
+        clock.advance(chrono::Duration::seconds(OUTBOUND_RETRY_BASE_SECS));
+        assert!(alice.due_outbound().unwrap().is_empty());
+
+        // Reconnecting sends it regardless of the backoff
+        let queued = alice.pending_outbound_for(&bob_info.peer_id).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].attempts, 2);
+        assert_eq!(queued[0].last_error.as_deref(), Some("offline"));
+
+        // The last allowed attempt fails: the message leaves the queue, failed
+        assert_eq!(
+            alice.outbound_failed(&msg.message_id, "offline").unwrap(),
+            Some(3)
+        );
+        assert_eq!(alice.get_pending_message_count(None).unwrap(), 0);
+        let stored = MessagesRepository::get_by_message_id(&alice.db, &msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, "failed");
+    }
+
+    #[test]
+    fn test_outbound_delivered_leaves_queue() {
+        let (alice, _) = create_user("Alice");
+        let (_, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "hello", "text", None)
+            .unwrap();
+        alice
+            .queue_outbound(&msg.message_id, &bob_info.peer_id, b"payload")
+            .unwrap();
+        alice.outbound_delivered(&msg.message_id).unwrap();
+
+        assert_eq!(alice.get_pending_message_count(None).unwrap(), 0);
+        let stored = MessagesRepository::get_by_message_id(&alice.db, &msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, "sent");
+    }
+
+    #[test]
+    fn test_offline_edit_is_queued_and_resends_new_content() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "helo", "text", None)
+            .unwrap();
+        alice
+            .queue_outbound(&msg.message_id, &bob_info.peer_id, b"original")
+            .unwrap();
+
+        // Bob is offline: the edit queues behind the message, which is
+        // re-sealed with the new content
+        let edit = alice.edit_message(&msg.message_id, "hello").unwrap();
+        let resealed = alice
+            .reseal_queued_message(&msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(resealed.message_id, msg.message_id);
+        assert!(alice
+            .replace_queued_payload(&msg.message_id, b"resealed")
+            .unwrap());
+        let edit_key = alice
+            .queue_outbound_edit(&msg.message_id, &bob_info.peer_id, b"edit")
+            .unwrap();
+
+        let queued = alice.pending_outbound_for(&bob_info.peer_id).unwrap();
+        assert_eq!(queued.len(), 2);
+        let queued_msg = queued
+            .iter()
+            .find(|pending| pending.message_id == msg.message_id)
+            .unwrap();
+        assert_eq!(queued_msg.payload, b"resealed".to_vec());
+        let queued_edit = queued
+            .iter()
+            .find(|pending| pending.message_id == edit_key)
+            .unwrap();
+        assert_eq!(queued_edit.kind, PendingOutboundKind::Edit);
+        assert_eq!(alice.get_pending_message_count(None).unwrap(), 1);
+
+        // Bob comes online and gets both, in either order
+        assert!(!deliver_edit(&bob, &edit).unwrap());
+        deliver(&bob, &resealed).unwrap();
+        assert_eq!(
+            history(&bob, &alice_info.peer_id),
+            vec!["hello".to_string()]
+        );
+
+        // Confirming the edit doesn't mark the message sent
+        alice.outbound_delivered(&edit_key).unwrap();
+        let stored = MessagesRepository::get_by_message_id(&alice.db, &msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.status, "pending");
+        assert_eq!(
+            alice.pending_outbound_for(&bob_info.peer_id).unwrap().len(),
+            1
+        );
+    }
+
+    #[test]
+    fn test_offline_delete_is_queued_in_place_of_message() {
+        let (alice, alice_info) = create_user("Alice");
+        let (bob, bob_info) = create_user("Bob");
+        befriend(&alice, &bob_info);
+        befriend(&bob, &alice_info);
+
+        let msg = alice
+            .send_message(&bob_info.peer_id, "wrong chat", "text", None)
+            .unwrap();
+        alice
+            .queue_outbound(&msg.message_id, &bob_info.peer_id, b"original")
+            .unwrap();
+        alice.edit_message(&msg.message_id, "wrong chat!").unwrap();
+        alice
+            .queue_outbound_edit(&msg.message_id, &bob_info.peer_id, b"edit")
+            .unwrap();
+
+        // The undelivered message and its edit leave the queue; the delete
+        // waits for Bob in case the message reached him after all
+        let delete = alice.delete_message(&msg.message_id).unwrap();
+        let delete_key = alice
+            .queue_outbound_delete(&msg.message_id, &bob_info.peer_id, b"delete")
+            .unwrap();
+        assert!(alice
+            .reseal_queued_message(&msg.message_id)
+            .unwrap()
+            .is_none());
+
+        let queued = alice.pending_outbound_for(&bob_info.peer_id).unwrap();
+        assert_eq!(queued.len(), 1);
+        assert_eq!(queued[0].message_id, delete_key);
+        assert_eq!(queued[0].kind, PendingOutboundKind::Delete);
+        assert_eq!(alice.get_pending_message_count(None).unwrap(), 0);
+
+        // Giving up on the delete leaves the message as it was
+        alice.set_max_send_attempts(1).unwrap();
+        assert_eq!(
+            alice.outbound_failed(&delete_key, "offline").unwrap(),
+            Some(1)
+        );
+        assert!(alice
+            .pending_outbound_for(&bob_info.peer_id)
+            .unwrap()
+            .is_empty());
+        let stored = MessagesRepository::get_by_message_id(&alice.db, &msg.message_id)
+            .unwrap()
+            .unwrap();
+        assert_eq!(stored.deleted_at, Some(delete.deleted_at));
+        assert_eq!(stored.status, "pending");
+    }
 }
@@ -1,46 +1,93 @@
 pub mod accounts_service;
 pub mod board_service;
 pub mod calling_service;
+pub mod clock;
+pub mod community_invite;
 pub mod contacts_service;
 pub mod content_sync_service;
 pub mod crypto_service;
+pub mod feature_flags_service;
+pub mod feed_import;
 pub mod feed_service;
+pub mod identity_qr;
 pub mod identity_service;
+pub mod markdown;
+pub mod media_backend;
 pub mod media_service;
 pub mod messaging_service;
 pub mod permissions_service;
 pub mod posts_service;
+pub mod ratchet;
 pub mod signing;
+pub mod voice_note;
 
 pub use accounts_service::AccountsService;
 pub use board_service::BoardService;
 pub use calling_service::{
-    Call, CallState, CallingService, OutgoingAnswer, OutgoingHangup, OutgoingIce, OutgoingOffer,
+    Call, CallState, CallingService, OutgoingAnswer, OutgoingCallData, OutgoingHangup, OutgoingIce,
+    OutgoingOffer,
+};
+pub use clock::{Clock, MockClock, SystemClock};
+pub use community_invite::CommunityInviteToken;
+pub use contacts_service::{
+    ContactField, ContactSuggestion, ContactUpsert, ContactsService, SharedContact, StaleContact,
+    StaleReason,
 };
-pub use contacts_service::ContactsService;
 pub use content_sync_service::{
-    ContentSyncService, OutgoingManifestRequest, OutgoingManifestResponse,
+    ContentSyncService, OutgoingManifestRequest, OutgoingManifestResponse, PostViewSettings,
+};
+pub use crypto_service::{
+    is_legacy_message_cipher, legacy_message_cipher, CryptoService, KdfParams, MessageCipher,
+    NONCE_SALT_LEN,
 };
-pub use crypto_service::CryptoService;
+pub use feature_flags_service::{FeatureFlag, FeatureFlagState, FeatureFlagsService};
 pub use feed_service::{FeedItem, FeedService};
+pub use identity_qr::IdentityQrPayload;
 pub use identity_service::IdentityService;
+pub use media_backend::{
+    FilesystemMediaBackend, MediaBackend, MediaBackendConfig, MemoryMediaBackend,
+};
 pub use media_service::MediaStorageService;
-pub use messaging_service::{DecryptedMessage, MessagingService, OutgoingMessage};
+pub use messaging_service::{
+    DecryptedMessage, MessagePage, MessagingService, NonceStrategy, OutgoingMessage,
+    OutgoingMessageDelete, OutgoingMessageEdit, ReplyPreview, RetentionSweepSummary,
+};
 pub use permissions_service::{
-    PermissionGrantMessage, PermissionRequestMessage, PermissionRevokeMessage, PermissionsService,
+    GrantDirection, PermissionAckMessage, PermissionGrantMessage, PermissionHistoryEvent,
+    PermissionRequestMessage, PermissionRevokeMessage, PermissionsService,
+};
+pub use posts_service::{
+    ensure_attachment_count, max_post_attachments, OutgoingPost, OutgoingPostDelete,
+    OutgoingPostPin, OutgoingPostUpdate, PostsService, DEFAULT_MAX_POST_ATTACHMENTS,
+    MAX_POST_ATTACHMENTS_LIMIT,
+};
+pub use ratchet::{
+    is_legacy_protocol_version, legacy_protocol_version, RatchetHeader, RatchetSession,
+    CURRENT_PROTOCOL_VERSION, PROTOCOL_VERSION_RATCHET, PROTOCOL_VERSION_STATIC,
 };
-pub use posts_service::{OutgoingPost, OutgoingPostDelete, OutgoingPostUpdate, PostsService};
 pub use signing::{
+    decode_canonical,
+    legacy_sig_version,
     sign,
     verify,
+    ManifestDetailLevel,
     PermissionProof,
+    PostHeader,
     PostSummary,
     Signable,
     // Board messages
+    SignableBoardCreate,
     SignableBoardListRequest,
     SignableBoardPost,
     SignableBoardPostDelete,
+    SignableBoardPostReaction,
     SignableBoardPostsRequest,
+    // In-call data channel
+    SignableCallData,
+    // Community invites
+    SignableCommunityInvite,
+    // Contact suggestions
+    SignableContactListRequest,
     // Content sync
     SignableContentManifestRequest,
     SignableContentManifestResponse,
@@ -48,19 +95,33 @@ pub use signing::{
     SignableDirectMessage,
     // Wall post relay sync
     SignableGetWallPosts,
+    // Presence
+    SignableHeartbeat,
     // Identity messages
     SignableIdentityRequest,
     SignableIdentityResponse,
+    SignableInviteRequest,
+    // Media fetch
+    SignableMediaFetchRequest,
     SignableMessageAck,
+    SignableMessageDelete,
+    SignableMessageEdit,
+    SignablePeerDeregistration,
     SignablePeerRegistration,
+    SignablePermissionAck,
     SignablePermissionGrant,
     // Permission messages
     SignablePermissionRequest,
     SignablePermissionRevoke,
     // Post messages
     SignablePost,
+    SignablePostAnnouncement,
     SignablePostDelete,
+    SignablePostPin,
     SignablePostUpdate,
+    SignablePostView,
+    SignableReadReceipt,
+    SignableRelayTimestamp,
     SignableSignalingAnswer,
     SignableSignalingHangup,
     SignableSignalingIce,
@@ -68,6 +129,11 @@ pub use signing::{
     SignableSignalingOffer,
     SignableWallPostDelete,
     SignableWallPostSubmit,
-    // Media fetch
-    SignableMediaFetchRequest,
+    SignaturePolicy,
+    CURRENT_SIG_VERSION,
+    SIG_VERSION_CBOR,
+};
+pub use voice_note::{
+    normalize_voice_mime_type, VoiceNote, CONTENT_TYPE_VOICE, MAX_VOICE_NOTE_BYTES,
+    MAX_VOICE_NOTE_DURATION_MS,
 };
@@ -2,15 +2,18 @@
 
 use ed25519_dalek::VerifyingKey;
 use std::sync::Arc;
+use tracing::warn;
 use uuid::Uuid;
 
+use crate::db::repositories::settings_repo::SETTING_DEFAULT_GRANT_POLICY;
+use crate::db::repositories::SettingsRepository;
 use crate::db::{
     Capability, Database, GrantData, Permission, PermissionsRepository, RecordPermissionEventParams,
 };
 use crate::error::{AppError, Result};
 use crate::services::{
-    verify, IdentityService, Signable, SignablePermissionGrant, SignablePermissionRequest,
-    SignablePermissionRevoke,
+    decode_canonical, verify, IdentityService, Signable, SignablePermissionAck,
+    SignablePermissionGrant, SignablePermissionRequest, SignablePermissionRevoke,
 };
 
 /// Service for managing permissions
@@ -46,6 +49,71 @@ pub struct PermissionGrantMessage {
     pub payload_cbor: Vec<u8>,
 }
 
+impl PermissionGrantMessage {
+    /// Rebuild a grant from its signed payload, as carried over the network.
+    /// The signature is checked when the grant is processed.
+    pub fn from_signed_payload(payload_cbor: Vec<u8>, signature: Vec<u8>) -> Result<Self> {
+        let signable = decode_canonical::<SignablePermissionGrant>(&payload_cbor)?;
+        Ok(Self {
+            grant_id: signable.grant_id,
+            issuer_peer_id: signable.issuer_peer_id,
+            subject_peer_id: signable.subject_peer_id,
+            capability: signable.capability,
+            scope: signable.scope,
+            lamport_clock: signable.lamport_clock,
+            issued_at: signable.issued_at,
+            expires_at: signable.expires_at,
+            signature,
+            payload_cbor,
+        })
+    }
+}
+
+/// A subject's signed acknowledgement of grants issued to them
+#[derive(Debug, Clone)]
+pub struct PermissionAckMessage {
+    pub issuer_peer_id: String,
+    pub subject_peer_id: String,
+    pub grant_ids: Vec<String>,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
+/// Which way a grant or revocation went between us and a peer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GrantDirection {
+    /// We issued it to the peer
+    Granted,
+    /// The peer issued it to us
+    Received,
+}
+
+impl GrantDirection {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GrantDirection::Granted => "granted",
+            GrantDirection::Received => "received",
+        }
+    }
+}
+
+/// A grant or revocation from the permission event log
+#[derive(Debug, Clone)]
+pub struct PermissionHistoryEvent {
+    pub event_id: String,
+    /// "grant" or "revoke"
+    pub event_type: String,
+    pub grant_id: String,
+    pub capability: String,
+    pub issuer_peer_id: String,
+    pub subject_peer_id: String,
+    pub direction: GrantDirection,
+    /// When the issuer signed the grant or revocation
+    pub timestamp: i64,
+    pub expires_at: Option<i64>,
+    pub signature: Vec<u8>,
+}
+
 /// A permission revoke message
 #[derive(Debug, Clone)]
 pub struct PermissionRevokeMessage {
@@ -197,6 +265,51 @@ impl PermissionsService {
         })
     }
 
+    /// Get the capabilities new contacts are granted. Until the user sets a
+    /// policy this is [`Capability::CONTACT_DEFAULTS`].
+    pub fn get_default_grant_policy(&self) -> Result<Vec<Capability>> {
+        let stored = SettingsRepository::get(&self.db, SETTING_DEFAULT_GRANT_POLICY)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        let Some(stored) = stored else {
+            return Ok(Capability::CONTACT_DEFAULTS.to_vec());
+        };
+
+        // Unknown names (e.g. from a newer version) are skipped
+        let names: Vec<&str> = stored.split(',').map(str::trim).collect();
+        Ok(Capability::ALL
+            .into_iter()
+            .filter(|capability| names.contains(&capability.as_str()))
+            .collect())
+    }
+
+    /// Set the capabilities new contacts are granted. An empty policy grants
+    /// nothing, leaving every capability to be granted by hand.
+    pub fn set_default_grant_policy(&self, capabilities: &[Capability]) -> Result<()> {
+        let value = Capability::ALL
+            .into_iter()
+            .filter(|capability| capabilities.contains(capability))
+            .map(|capability| capability.as_str())
+            .collect::<Vec<_>>()
+            .join(",");
+        SettingsRepository::set(&self.db, SETTING_DEFAULT_GRANT_POLICY, &value)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
+    /// Grant a new contact the capabilities in the default grant policy,
+    /// skipping any it already holds so a contact that re-identifies doesn't
+    /// collect duplicate grants
+    pub fn grant_contact_defaults(&self, subject_peer_id: &str) -> Result<Vec<Capability>> {
+        let mut granted = Vec::new();
+        for capability in self.get_default_grant_policy()? {
+            if self.peer_has_capability(subject_peer_id, capability)? {
+                continue;
+            }
+            self.create_permission_grant(subject_peer_id, capability, None)?;
+            granted.push(capability);
+        }
+        Ok(granted)
+    }
+
     /// Revoke a previously granted permission
     pub fn revoke_permission(&self, grant_id: &str) -> Result<PermissionRevokeMessage> {
         let identity = self
@@ -299,6 +412,15 @@ impl PermissionsService {
             return Err(AppError::Crypto("Invalid grant signature".to_string()));
         }
 
+        // The payload is stored and handed on as proof, so it must be exactly
+        // the bytes the signature covers
+        decode_canonical::<SignablePermissionGrant>(&grant.payload_cbor)?;
+        if grant.payload_cbor != signable.signable_bytes()? {
+            return Err(AppError::InvalidData(
+                "Grant payload does not match the grant".to_string(),
+            ));
+        }
+
         // Check for deduplication
         let event_id = format!("grant:{}", grant.grant_id);
         if PermissionsRepository::event_exists(&self.db, &event_id)
@@ -426,6 +548,130 @@ impl PermissionsService {
         Ok(())
     }
 
+    // ============================================================
+    // Reconciliation
+    // ============================================================
+
+    /// Grants we issued to `peer_id` that they haven't acknowledged yet.
+    ///
+    /// These are resent whenever the peer reconnects, until an ack from
+    /// [`Self::process_permission_ack`] marks them as received.
+    pub fn reconcile_permissions(&self, peer_id: &str) -> Result<Vec<PermissionGrantMessage>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let pending =
+            PermissionsRepository::get_unacknowledged_grants(&self.db, &identity.peer_id, peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        let mut grants = Vec::with_capacity(pending.len());
+        for permission in pending {
+            match PermissionGrantMessage::from_signed_payload(
+                permission.payload_cbor,
+                permission.signature,
+            ) {
+                Ok(grant) => grants.push(grant),
+                Err(e) => warn!("Stored grant {} is unreadable: {}", permission.grant_id, e),
+            }
+        }
+        Ok(grants)
+    }
+
+    /// Store grants resent by `issuer_peer_id` and sign an ack for the ones
+    /// we now hold. Grants for someone else, or from anyone but the issuer,
+    /// are left out of the ack so the issuer keeps them pending.
+    pub fn accept_reconciled_grants(
+        &self,
+        issuer_peer_id: &str,
+        grants: &[PermissionGrantMessage],
+        issuer_public_key: &[u8],
+    ) -> Result<PermissionAckMessage> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let mut grant_ids = Vec::new();
+        for grant in grants {
+            if grant.issuer_peer_id != issuer_peer_id || grant.subject_peer_id != identity.peer_id {
+                warn!(
+                    "Ignoring resent grant {} from {}: not issued by them to us",
+                    grant.grant_id, issuer_peer_id
+                );
+                continue;
+            }
+            match self.process_incoming_grant(grant, issuer_public_key) {
+                Ok(()) => grant_ids.push(grant.grant_id.clone()),
+                Err(e) => warn!("Rejected resent grant {}: {}", grant.grant_id, e),
+            }
+        }
+
+        let timestamp = chrono::Utc::now().timestamp();
+        let signable = SignablePermissionAck {
+            issuer_peer_id: issuer_peer_id.to_string(),
+            subject_peer_id: identity.peer_id.clone(),
+            grant_ids: grant_ids.clone(),
+            timestamp,
+        };
+        let signature = self.identity_service.sign(&signable)?;
+
+        Ok(PermissionAckMessage {
+            issuer_peer_id: issuer_peer_id.to_string(),
+            subject_peer_id: identity.peer_id,
+            grant_ids,
+            timestamp,
+            signature,
+        })
+    }
+
+    /// Verify a subject's ack and stop resending the grants it covers.
+    /// Returns how many grants were newly acknowledged.
+    pub fn process_permission_ack(
+        &self,
+        ack: &PermissionAckMessage,
+        subject_public_key: &[u8],
+    ) -> Result<usize> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        if ack.issuer_peer_id != identity.peer_id {
+            return Err(AppError::Validation(
+                "Ack is for another issuer's grants".to_string(),
+            ));
+        }
+
+        let signable = SignablePermissionAck {
+            issuer_peer_id: ack.issuer_peer_id.clone(),
+            subject_peer_id: ack.subject_peer_id.clone(),
+            grant_ids: ack.grant_ids.clone(),
+            timestamp: ack.timestamp,
+        };
+
+        let verifying_key = VerifyingKey::from_bytes(
+            subject_public_key
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        if !verify(&verifying_key, &signable, &ack.signature)? {
+            return Err(AppError::Crypto("Invalid ack signature".to_string()));
+        }
+
+        PermissionsRepository::mark_grants_acknowledged(
+            &self.db,
+            &identity.peer_id,
+            &ack.subject_peer_id,
+            &ack.grant_ids,
+            chrono::Utc::now().timestamp(),
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     // ============================================================
     // Query Methods
     // ============================================================
@@ -488,6 +734,52 @@ impl PermissionsService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Every grant and revocation between us and `peer_id`, in both
+    /// directions, oldest first
+    pub fn get_permission_history(&self, peer_id: &str) -> Result<Vec<PermissionHistoryEvent>> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        let events =
+            PermissionsRepository::get_grant_events_between(&self.db, &identity.peer_id, peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        let mut history: Vec<PermissionHistoryEvent> = events
+            .into_iter()
+            .map(|event| {
+                let issuer_peer_id = event.issuer_peer_id.unwrap_or(event.author_peer_id);
+                // Revocations carry their time only in the signed payload
+                let timestamp = match event.issued_at {
+                    Some(issued_at) => issued_at,
+                    None => decode_canonical::<SignablePermissionRevoke>(&event.payload_cbor)
+                        .map(|revoke| revoke.revoked_at)
+                        .unwrap_or(event.received_at),
+                };
+                PermissionHistoryEvent {
+                    direction: if issuer_peer_id == identity.peer_id {
+                        GrantDirection::Granted
+                    } else {
+                        GrantDirection::Received
+                    },
+                    event_id: event.event_id,
+                    event_type: event.event_type,
+                    grant_id: event.entity_id,
+                    capability: event.capability,
+                    issuer_peer_id,
+                    subject_peer_id: event.subject_peer_id,
+                    timestamp,
+                    expires_at: event.expires_at,
+                    signature: event.signature,
+                }
+            })
+            .collect();
+        // Stable, so events signed in the same second keep their recorded order
+        history.sort_by_key(|event| event.timestamp);
+        Ok(history)
+    }
+
     /// Get all peers we can chat with (we granted them chat)
     pub fn get_chat_peers(&self) -> Result<Vec<String>> {
         let identity = self
@@ -543,6 +835,7 @@ mod tests {
                 passphrase: "password123".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
         identity_service.unlock("password123").unwrap();
@@ -571,6 +864,7 @@ mod tests {
                 passphrase: "password123".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
         identity_service.unlock("password123").unwrap();
@@ -594,4 +888,341 @@ mod tests {
             .peer_has_capability("12D3KooWSubject", Capability::Chat)
             .unwrap());
     }
+
+    #[test]
+    fn test_grant_contact_defaults_skips_existing_grants() {
+        let (db, identity_service, permissions_service) = create_test_service();
+
+        identity_service
+            .create_identity(CreateIdentityRequest {
+                display_name: "Test User".to_string(),
+                passphrase: "password123".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+        identity_service.unlock("password123").unwrap();
+
+        permissions_service
+            .create_permission_grant("12D3KooWSubject", Capability::Chat, None)
+            .unwrap();
+
+        let granted = permissions_service
+            .grant_contact_defaults("12D3KooWSubject")
+            .unwrap();
+        assert_eq!(granted, vec![Capability::FileTransfer]);
+
+        // Calling again (e.g. on re-identification) grants nothing new
+        assert!(permissions_service
+            .grant_contact_defaults("12D3KooWSubject")
+            .unwrap()
+            .is_empty());
+        let grants = PermissionsRepository::get_permissions_by_issuer(
+            &db,
+            &identity_service.get_identity().unwrap().unwrap().peer_id,
+        )
+        .unwrap();
+        assert_eq!(grants.len(), 2);
+        assert!(!permissions_service
+            .peer_has_capability("12D3KooWSubject", Capability::CallInitiate)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_default_grant_policy() {
+        let (db, identity_service, permissions_service) = create_test_service();
+
+        identity_service
+            .create_identity(CreateIdentityRequest {
+                display_name: "Test User".to_string(),
+                passphrase: "password123".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+        identity_service.unlock("password123").unwrap();
+
+        assert_eq!(
+            permissions_service.get_default_grant_policy().unwrap(),
+            Capability::CONTACT_DEFAULTS.to_vec()
+        );
+
+        permissions_service
+            .set_default_grant_policy(&[Capability::WallRead, Capability::Chat])
+            .unwrap();
+        assert_eq!(
+            permissions_service.get_default_grant_policy().unwrap(),
+            vec![Capability::Chat, Capability::WallRead]
+        );
+        assert_eq!(
+            permissions_service
+                .grant_contact_defaults("12D3KooWSocial")
+                .unwrap(),
+            vec![Capability::Chat, Capability::WallRead]
+        );
+
+        // An empty policy grants nothing
+        permissions_service.set_default_grant_policy(&[]).unwrap();
+        assert!(permissions_service
+            .get_default_grant_policy()
+            .unwrap()
+            .is_empty());
+        assert!(permissions_service
+            .grant_contact_defaults("12D3KooWPrivate")
+            .unwrap()
+            .is_empty());
+        assert!(!permissions_service
+            .peer_has_capability("12D3KooWPrivate", Capability::Chat)
+            .unwrap());
+
+        // Names this version doesn't know are ignored
+        SettingsRepository::set(&db, SETTING_DEFAULT_GRANT_POLICY, "chat,teleport").unwrap();
+        assert_eq!(
+            permissions_service.get_default_grant_policy().unwrap(),
+            vec![Capability::Chat]
+        );
+    }
+
+    /// A grant signed by a freshly generated issuer, with its payload
+    fn signed_incoming_grant() -> (PermissionGrantMessage, Vec<u8>) {
+        let issuer_key = ed25519_dalek::SigningKey::generate(&mut rand::rngs::OsRng);
+        let signable = SignablePermissionGrant {
+            grant_id: "grant-incoming".to_string(),
+            issuer_peer_id: "12D3KooWIssuer".to_string(),
+            subject_peer_id: "12D3KooWSubject".to_string(),
+            capability: "wall_read".to_string(),
+            scope: None,
+            lamport_clock: 1,
+            issued_at: 1000,
+            expires_at: None,
+        };
+        let grant = PermissionGrantMessage {
+            grant_id: signable.grant_id.clone(),
+            issuer_peer_id: signable.issuer_peer_id.clone(),
+            subject_peer_id: signable.subject_peer_id.clone(),
+            capability: signable.capability.clone(),
+            scope: None,
+            lamport_clock: signable.lamport_clock,
+            issued_at: signable.issued_at,
+            expires_at: None,
+            signature: crate::services::sign(&issuer_key, &signable).unwrap(),
+            payload_cbor: signable.signable_bytes().unwrap(),
+        };
+        (grant, issuer_key.verifying_key().to_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_incoming_grant_requires_canonical_payload() {
+        let (_, _, permissions_service) = create_test_service();
+        let (grant, issuer_public_key) = signed_incoming_grant();
+
+        // The grant_id string header rewritten in long form (0x78 length byte)
+        let mut non_canonical = grant.clone();
+        let key_end = 1 + 1 + "grant_id".len();
+        let mut payload = grant.payload_cbor[..key_end].to_vec();
+        payload.push(0x78);
+        payload.push(grant.grant_id.len() as u8);
+        payload.extend_from_slice(&grant.payload_cbor[key_end + 1..]);
+        non_canonical.payload_cbor = payload;
+        assert!(matches!(
+            permissions_service.process_incoming_grant(&non_canonical, &issuer_public_key),
+            Err(AppError::InvalidData(_))
+        ));
+
+        // Canonical, but for a different grant than the one signed
+        let mut mismatched = grant.clone();
+        mismatched.payload_cbor = SignablePermissionGrant {
+            grant_id: "grant-other".to_string(),
+            issuer_peer_id: grant.issuer_peer_id.clone(),
+            subject_peer_id: grant.subject_peer_id.clone(),
+            capability: grant.capability.clone(),
+            scope: None,
+            lamport_clock: 1,
+            issued_at: 1000,
+            expires_at: None,
+        }
+        .signable_bytes()
+        .unwrap();
+        assert!(permissions_service
+            .process_incoming_grant(&mismatched, &issuer_public_key)
+            .is_err());
+
+        permissions_service
+            .process_incoming_grant(&grant, &issuer_public_key)
+            .unwrap();
+    }
+
+    /// A permissions service with its own database and an unlocked identity.
+    /// Returns the service, its peer ID and public key.
+    fn create_peer(name: &str) -> (PermissionsService, String, Vec<u8>) {
+        let (_, identity_service, permissions_service) = create_test_service();
+        identity_service
+            .create_identity(CreateIdentityRequest {
+                display_name: name.to_string(),
+                passphrase: "password123".to_string(),
+                bio: None,
+                passphrase_hint: None,
+                kdf_profile: None,
+            })
+            .unwrap();
+        identity_service.unlock("password123").unwrap();
+        let identity = identity_service.get_identity().unwrap().unwrap();
+        (permissions_service, identity.peer_id, identity.public_key)
+    }
+
+    #[test]
+    fn test_grants_are_resent_until_acknowledged() {
+        let (issuer, issuer_id, issuer_key) = create_peer("Issuer");
+        let (subject, subject_id, subject_key) = create_peer("Subject");
+
+        issuer
+            .create_permission_grant(&subject_id, Capability::WallRead, None)
+            .unwrap();
+        let pending = issuer.reconcile_permissions(&subject_id).unwrap();
+        assert_eq!(pending.len(), 1);
+
+        let ack = subject
+            .accept_reconciled_grants(&issuer_id, &pending, &issuer_key)
+            .unwrap();
+        assert_eq!(ack.grant_ids, vec![pending[0].grant_id.clone()]);
+        assert!(subject
+            .we_have_capability(&issuer_id, Capability::WallRead)
+            .unwrap());
+
+        // Still pending until the ack comes back
+        assert_eq!(issuer.reconcile_permissions(&subject_id).unwrap().len(), 1);
+        assert_eq!(
+            issuer.process_permission_ack(&ack, &subject_key).unwrap(),
+            1
+        );
+        assert!(issuer
+            .reconcile_permissions(&subject_id)
+            .unwrap()
+            .is_empty());
+
+        // A repeated ack changes nothing, and a resent grant is acked again
+        assert_eq!(
+            issuer.process_permission_ack(&ack, &subject_key).unwrap(),
+            0
+        );
+        let again = subject
+            .accept_reconciled_grants(&issuer_id, &pending, &issuer_key)
+            .unwrap();
+        assert_eq!(again.grant_ids.len(), 1);
+    }
+
+    #[test]
+    fn test_permission_history_covers_both_directions() {
+        let (alice, alice_id, alice_key) = create_peer("Alice");
+        let (bob, bob_id, _bob_key) = create_peer("Bob");
+
+        let wall = alice
+            .create_permission_grant(&bob_id, Capability::WallRead, None)
+            .unwrap();
+        bob.create_permission_grant(&alice_id, Capability::Chat, None)
+            .unwrap();
+        let revoke = alice.revoke_permission(&wall.grant_id).unwrap();
+
+        let history = alice.get_permission_history(&bob_id).unwrap();
+        let summary: Vec<(&str, &str, GrantDirection)> = history
+            .iter()
+            .map(|e| (e.event_type.as_str(), e.capability.as_str(), e.direction))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("grant", "wall_read", GrantDirection::Granted),
+                ("revoke", "wall_read", GrantDirection::Granted),
+            ]
+        );
+        assert_eq!(history[1].timestamp, revoke.revoked_at);
+        assert_eq!(history[1].signature, revoke.signature);
+
+        // Bob sees Alice's grant and revocation as received, beside his own grant
+        bob.process_incoming_grant(&wall, &alice_key).unwrap();
+        bob.process_incoming_revoke(&revoke, &alice_key).unwrap();
+        let history = bob.get_permission_history(&alice_id).unwrap();
+        assert_eq!(history.len(), 3);
+        assert_eq!(
+            history
+                .iter()
+                .filter(|e| e.direction == GrantDirection::Received)
+                .count(),
+            2
+        );
+        let own = history
+            .iter()
+            .find(|e| e.direction == GrantDirection::Granted)
+            .unwrap();
+        assert_eq!(own.capability, "chat");
+        assert_eq!(own.issuer_peer_id, bob_id);
+    }
+
+    #[test]
+    fn test_permission_ack_must_be_signed_by_subject() {
+        let (issuer, issuer_id, issuer_key) = create_peer("Issuer");
+        let (subject, subject_id, _) = create_peer("Subject");
+        let (_, _, stranger_key) = create_peer("Stranger");
+
+        issuer
+            .create_permission_grant(&subject_id, Capability::WallRead, None)
+            .unwrap();
+        let pending = issuer.reconcile_permissions(&subject_id).unwrap();
+        let ack = subject
+            .accept_reconciled_grants(&issuer_id, &pending, &issuer_key)
+            .unwrap();
+
+        assert!(matches!(
+            issuer.process_permission_ack(&ack, &stranger_key),
+            Err(AppError::Crypto(_))
+        ));
+        assert_eq!(issuer.reconcile_permissions(&subject_id).unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_resent_grants_for_someone_else_are_not_acknowledged() {
+        let (issuer, _, issuer_key) = create_peer("Issuer");
+        let (subject, subject_id, _) = create_peer("Subject");
+
+        issuer
+            .create_permission_grant("12D3KooWSomeoneElse", Capability::WallRead, None)
+            .unwrap();
+        let pending = issuer.reconcile_permissions("12D3KooWSomeoneElse").unwrap();
+        let ack = subject
+            .accept_reconciled_grants(&pending[0].issuer_peer_id, &pending, &issuer_key)
+            .unwrap();
+
+        assert!(ack.grant_ids.is_empty());
+        assert_eq!(ack.subject_peer_id, subject_id);
+        assert!(subject.get_received_permissions().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_local_only_grants_are_not_reconciled() {
+        let (issuer, issuer_id, _) = create_peer("Issuer");
+
+        PermissionsRepository::upsert_grant(
+            &issuer.db,
+            &GrantData {
+                grant_id: "migration-020-chat-12D3KooWSubject".to_string(),
+                issuer_peer_id: issuer_id,
+                subject_peer_id: "12D3KooWSubject".to_string(),
+                capability: "chat".to_string(),
+                scope_json: None,
+                lamport_clock: 0,
+                issued_at: 1000,
+                expires_at: None,
+                payload_cbor: Vec::new(),
+                signature: Vec::new(),
+            },
+        )
+        .unwrap();
+
+        assert!(issuer
+            .reconcile_permissions("12D3KooWSubject")
+            .unwrap()
+            .is_empty());
+    }
 }
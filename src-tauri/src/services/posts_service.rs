@@ -1,25 +1,60 @@
 //! Posts service for managing wall/blog posts
 
 use ed25519_dalek::VerifyingKey;
+use sha2::{Digest, Sha256};
 use std::sync::Arc;
 use uuid::Uuid;
 
+use crate::db::repositories::settings_repo::SETTING_MAX_POST_ATTACHMENTS;
+use crate::db::repositories::SettingsRepository;
 use crate::db::{
-    Capability, Database, Post, PostData, PostMedia, PostMediaData, PostVisibility,
+    Capability, Database, Post, PostData, PostEvent, PostMedia, PostMediaData, PostVisibility,
     PostsRepository, RecordPostEventParams,
 };
 use crate::error::{AppError, Result};
+use crate::services::clock::{system_clock, Clock};
+use crate::services::feed_import::FeedEntry;
 use crate::services::{
-    verify, ContactsService, IdentityService, PermissionsService, Signable, SignablePost,
-    SignablePostDelete, SignablePostUpdate,
+    markdown, verify, ContactsService, IdentityService, PermissionsService, Signable, SignablePost,
+    SignablePostDelete, SignablePostPin, SignablePostUpdate, SignaturePolicy, CURRENT_SIG_VERSION,
 };
 
+/// Media attachments allowed on a post until the user changes it
+pub const DEFAULT_MAX_POST_ATTACHMENTS: usize = 10;
+
+/// Highest attachment limit that can be configured. Every attachment of a
+/// received post may be fetched from the author, so this bounds the fan-out.
+pub const MAX_POST_ATTACHMENTS_LIMIT: usize = 100;
+
+/// The configured maximum number of media attachments per post
+pub fn max_post_attachments(db: &Database) -> Result<usize> {
+    let value = SettingsRepository::get(db, SETTING_MAX_POST_ATTACHMENTS)
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+    Ok(value
+        .and_then(|value| value.parse().ok())
+        .unwrap_or(DEFAULT_MAX_POST_ATTACHMENTS))
+}
+
+/// Reject a post carrying more attachments than the configured maximum
+pub fn ensure_attachment_count(db: &Database, count: usize) -> Result<()> {
+    let max = max_post_attachments(db)?;
+    if count > max {
+        return Err(AppError::Validation(format!(
+            "Post has {} media attachments, more than the maximum of {}",
+            count, max
+        )));
+    }
+    Ok(())
+}
+
 /// Service for managing wall/blog posts
 pub struct PostsService {
     db: Arc<Database>,
     identity_service: Arc<IdentityService>,
     contacts_service: Arc<ContactsService>,
     permissions_service: Arc<PermissionsService>,
+    /// Source of the current time
+    clock: Arc<dyn Clock>,
 }
 
 /// A post ready to be synced over the network
@@ -57,6 +92,17 @@ pub struct OutgoingPostDelete {
     pub signature: Vec<u8>,
 }
 
+/// A pin or unpin ready to be synced
+#[derive(Debug, Clone)]
+pub struct OutgoingPostPin {
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub pinned: bool,
+    pub lamport_clock: u64,
+    pub timestamp: i64,
+    pub signature: Vec<u8>,
+}
+
 /// Parameters for adding media to a post
 pub struct AddMediaParams<'a> {
     pub post_id: &'a str,
@@ -82,6 +128,8 @@ pub struct IncomingPostParams<'a> {
     pub lamport_clock: u64,
     pub created_at: i64,
     pub signature: &'a [u8],
+    /// Signature scheme version the author signed with
+    pub sig_version: u32,
 }
 
 impl PostsService {
@@ -97,27 +145,78 @@ impl PostsService {
             identity_service,
             contacts_service,
             permissions_service,
+            clock: system_clock(),
         }
     }
 
+    /// Read the time from `clock` instead of the system clock
+    pub fn with_clock(mut self, clock: Arc<dyn Clock>) -> Self {
+        self.clock = clock;
+        self
+    }
+
     /// Create a new post
     pub fn create_post(
         &self,
         content_type: &str,
         content_text: Option<&str>,
         visibility: PostVisibility,
+    ) -> Result<OutgoingPost> {
+        self.create_post_at(
+            content_type,
+            content_text,
+            visibility,
+            self.clock.timestamp(),
+        )
+    }
+
+    /// Import a feed entry as a post dated with the entry's publication time.
+    /// Returns `None` for an entry that was imported before.
+    pub fn import_feed_entry(
+        &self,
+        entry: &FeedEntry,
+        visibility: PostVisibility,
+    ) -> Result<Option<OutgoingPost>> {
+        let existing = PostsRepository::get_imported_entry(&self.db, &entry.key)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if existing.is_some() {
+            return Ok(None);
+        }
+
+        // Entries dated in the future are imported as of now
+        let now = self.clock.timestamp();
+        let created_at = entry
+            .published_at
+            .map_or(now, |published| published.min(now));
+        let post = self.create_post_at("text", Some(&entry.post_text()), visibility, created_at)?;
+
+        PostsRepository::record_imported_entry(&self.db, &entry.key, &post.post_id, now)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        Ok(Some(post))
+    }
+
+    /// Create a post dated `created_at`
+    fn create_post_at(
+        &self,
+        content_type: &str,
+        content_text: Option<&str>,
+        visibility: PostVisibility,
+        created_at: i64,
     ) -> Result<OutgoingPost> {
         let identity = self
             .identity_service
             .get_identity()?
             .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
 
+        // Markdown is sanitized before signing so peers receive it as stored
+        let content_text = content_text.map(|text| markdown::sanitize_content(content_type, text));
+        let content_text = content_text.as_deref();
+
         let post_id = Uuid::new_v4().to_string();
         let lamport_clock =
             self.db
                 .next_lamport_clock(&identity.peer_id)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
-        let created_at = chrono::Utc::now().timestamp();
 
         // Create signable
         let signable = SignablePost {
@@ -143,6 +242,7 @@ impl PostsService {
             lamport_clock: lamport_clock as i64,
             created_at,
             signature: signature.clone(),
+            sig_version: CURRENT_SIG_VERSION,
         };
 
         PostsRepository::insert_post(&self.db, &post_data)
@@ -201,11 +301,15 @@ impl PostsService {
             ));
         }
 
+        let content_text =
+            content_text.map(|text| markdown::sanitize_content(&post.content_type, text));
+        let content_text = content_text.as_deref();
+
         let lamport_clock =
             self.db
                 .next_lamport_clock(&identity.peer_id)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
-        let updated_at = chrono::Utc::now().timestamp();
+        let updated_at = self.clock.timestamp();
 
         // Create signable
         let signable = SignablePostUpdate {
@@ -278,7 +382,7 @@ impl PostsService {
             self.db
                 .next_lamport_clock(&identity.peer_id)
                 .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
-        let deleted_at = chrono::Utc::now().timestamp();
+        let deleted_at = self.clock.timestamp();
 
         // Create signable
         let signable = SignablePostDelete {
@@ -321,6 +425,120 @@ impl PostsService {
         })
     }
 
+    /// Pin one of our posts to the top of our wall, replacing any previous pin
+    pub fn pin_post(&self, post_id: &str) -> Result<OutgoingPostPin> {
+        self.set_pinned(post_id, true)
+    }
+
+    /// Unpin our currently pinned post
+    pub fn unpin_post(&self, post_id: &str) -> Result<OutgoingPostPin> {
+        self.set_pinned(post_id, false)
+    }
+
+    fn set_pinned(&self, post_id: &str, pinned: bool) -> Result<OutgoingPostPin> {
+        let identity = self
+            .identity_service
+            .get_identity()?
+            .ok_or_else(|| AppError::IdentityNotFound("No identity".to_string()))?;
+
+        // Verify we own the post
+        let post = PostsRepository::get_by_post_id(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .filter(|p| p.deleted_at.is_none())
+            .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+
+        if post.author_peer_id != identity.peer_id {
+            return Err(AppError::PermissionDenied(
+                "Cannot pin another user's post".to_string(),
+            ));
+        }
+        if !pinned && !post.pinned {
+            return Err(AppError::Validation("Post is not pinned".to_string()));
+        }
+
+        let lamport_clock =
+            self.db
+                .next_lamport_clock(&identity.peer_id)
+                .map_err(|e| AppError::DatabaseString(e.to_string()))? as u64;
+        let timestamp = self.clock.timestamp();
+
+        let signable = SignablePostPin {
+            post_id: post_id.to_string(),
+            author_peer_id: identity.peer_id.clone(),
+            pinned,
+            lamport_clock,
+            timestamp,
+        };
+
+        let signature = self.identity_service.sign(&signable)?;
+
+        self.apply_pin(&signable, &signature)?;
+
+        Ok(OutgoingPostPin {
+            post_id: post_id.to_string(),
+            author_peer_id: identity.peer_id,
+            pinned,
+            lamport_clock,
+            timestamp,
+            signature,
+        })
+    }
+
+    /// Apply a (verified) pin change locally and record it as a post event
+    fn apply_pin(&self, signable: &SignablePostPin, signature: &[u8]) -> Result<()> {
+        let post_id = signable.post_id.as_str();
+        let author_peer_id = signable.author_peer_id.as_str();
+
+        if signable.pinned {
+            PostsRepository::pin_post(&self.db, author_peer_id, post_id)
+        } else {
+            PostsRepository::unpin_post(&self.db, author_peer_id, post_id)
+        }
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        let event_type = if signable.pinned {
+            "pinned"
+        } else {
+            "unpinned"
+        };
+        let event_id = format!("{}:{}:{}", event_type, post_id, signable.lamport_clock);
+        let payload_cbor = signable.signable_bytes()?;
+        PostsRepository::record_post_event(
+            &self.db,
+            &RecordPostEventParams {
+                event_id: &event_id,
+                event_type,
+                post_id,
+                author_peer_id,
+                lamport_clock: signable.lamport_clock as i64,
+                timestamp: signable.timestamp,
+                payload_cbor: &payload_cbor,
+                signature,
+            },
+        )
+        .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        Ok(())
+    }
+
+    /// Get the most media attachments a post may carry
+    pub fn get_max_post_attachments(&self) -> Result<usize> {
+        max_post_attachments(&self.db)
+    }
+
+    /// Set the most media attachments a post may carry. Applies to our own
+    /// posts and to posts received from peers.
+    pub fn set_max_post_attachments(&self, max: usize) -> Result<()> {
+        if !(1..=MAX_POST_ATTACHMENTS_LIMIT).contains(&max) {
+            return Err(AppError::Validation(format!(
+                "Maximum attachments must be between 1 and {}",
+                MAX_POST_ATTACHMENTS_LIMIT
+            )));
+        }
+        SettingsRepository::set(&self.db, SETTING_MAX_POST_ATTACHMENTS, &max.to_string())
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Add media to a post
     pub fn add_media_to_post(&self, params: &AddMediaParams<'_>) -> Result<()> {
         let identity = self
@@ -339,6 +557,11 @@ impl PostsService {
             ));
         }
 
+        let attached = PostsRepository::get_post_media(&self.db, params.post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .len();
+        ensure_attachment_count(&self.db, attached + 1)?;
+
         let media_data = PostMediaData {
             post_id: params.post_id.to_string(),
             media_hash: params.media_hash.to_string(),
@@ -362,6 +585,15 @@ impl PostsService {
             .map_err(|e| AppError::DatabaseString(e.to_string()))
     }
 
+    /// Get the recorded events for a post, oldest lamport clock first.
+    ///
+    /// Only applied events are recorded; a remote update that lost to a newer
+    /// version never appears here.
+    pub fn get_post_events(&self, post_id: &str) -> Result<Vec<PostEvent>> {
+        PostsRepository::get_post_events(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))
+    }
+
     /// Get a post by ID
     pub fn get_post(&self, post_id: &str) -> Result<Option<Post>> {
         PostsRepository::get_by_post_id(&self.db, post_id)
@@ -420,6 +652,9 @@ impl PostsService {
         let lamport_clock = params.lamport_clock;
         let created_at = params.created_at;
         let signature = params.signature;
+        // Checked first: an oversized list costs a signature check over all of it
+        ensure_attachment_count(&self.db, media_hashes.len())?;
+
         // Get author's public key for verification
         let author_public_key = self
             .contacts_service
@@ -446,9 +681,15 @@ impl PostsService {
         )
         .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
 
-        if !verify(&verifying_key, &signable, signature)? {
+        if !SignaturePolicy::from_settings(&self.db)?.verify(
+            &verifying_key,
+            &signable,
+            signature,
+            params.sig_version,
+        )? {
             return Err(AppError::Crypto("Invalid post signature".to_string()));
         }
+        markdown::ensure_sanitized(content_type, content_text)?;
 
         // Check if we already have this post
         if let Some(existing) = PostsRepository::get_by_post_id(&self.db, post_id)
@@ -487,6 +728,7 @@ impl PostsService {
             lamport_clock: lamport_clock as i64,
             created_at,
             signature: signature.to_vec(),
+            sig_version: params.sig_version,
         };
 
         // Use upsert behavior
@@ -572,10 +814,12 @@ impl PostsService {
         let existing = PostsRepository::get_by_post_id(&self.db, post_id)
             .map_err(|e| AppError::DatabaseString(e.to_string()))?
             .ok_or_else(|| AppError::NotFound("Post not found".to_string()))?;
+        markdown::ensure_sanitized(&existing.content_type, content_text)?;
 
-        if lamport_clock <= existing.lamport_clock as u64 {
-            return Ok(()); // Already have newer or same version
+        if !update_wins(&existing, lamport_clock, content_text) {
+            return Ok(()); // Already have a newer version, or the winning one at this clock
         }
+        let concurrent_edit = lamport_clock == existing.lamport_clock as u64;
 
         // Update lamport clock
         self.db
@@ -595,24 +839,92 @@ impl PostsService {
         // Record event
         let event_id = format!("updated:{}:{}", post_id, lamport_clock);
         let payload_cbor = signable.signable_bytes()?;
-        PostsRepository::record_post_event(
-            &self.db,
-            &RecordPostEventParams {
-                event_id: &event_id,
-                event_type: "updated",
-                post_id,
-                author_peer_id,
-                lamport_clock: lamport_clock as i64,
-                timestamp: updated_at,
-                payload_cbor: &payload_cbor,
-                signature,
-            },
-        )
+        let event = RecordPostEventParams {
+            event_id: &event_id,
+            event_type: "updated",
+            post_id,
+            author_peer_id,
+            lamport_clock: lamport_clock as i64,
+            timestamp: updated_at,
+            payload_cbor: &payload_cbor,
+            signature,
+        };
+        // The losing edit's event at this clock is superseded
+        if concurrent_edit {
+            PostsRepository::replace_post_event(&self.db, &event)
+        } else {
+            PostsRepository::record_post_event(&self.db, &event)
+        }
         .map_err(|e| AppError::DatabaseString(e.to_string()))?;
 
         Ok(())
     }
 
+    /// Process an incoming pin or unpin.
+    ///
+    /// The author's device is canonical: only their signed events count, and
+    /// an event older than the last one we applied for them is ignored.
+    pub fn process_incoming_post_pin(
+        &self,
+        post_id: &str,
+        author_peer_id: &str,
+        pinned: bool,
+        lamport_clock: u64,
+        timestamp: i64,
+        signature: &[u8],
+    ) -> Result<()> {
+        // Get author's public key
+        let author_public_key = self
+            .contacts_service
+            .get_public_key(author_peer_id)?
+            .ok_or_else(|| AppError::NotFound("Author not in contacts".to_string()))?;
+
+        // Verify signature
+        let signable = SignablePostPin {
+            post_id: post_id.to_string(),
+            author_peer_id: author_peer_id.to_string(),
+            pinned,
+            lamport_clock,
+            timestamp,
+        };
+
+        let verifying_key = VerifyingKey::from_bytes(
+            author_public_key
+                .as_slice()
+                .try_into()
+                .map_err(|_| AppError::Crypto("Invalid public key length".to_string()))?,
+        )
+        .map_err(|e| AppError::Crypto(format!("Invalid public key: {}", e)))?;
+
+        if !verify(&verifying_key, &signable, signature)? {
+            return Err(AppError::Crypto("Invalid post pin signature".to_string()));
+        }
+
+        // Only the author can pin their own posts
+        if let Some(post) = PostsRepository::get_by_post_id(&self.db, post_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+        {
+            if post.author_peer_id != author_peer_id {
+                return Err(AppError::PermissionDenied(
+                    "Cannot pin another user's post".to_string(),
+                ));
+            }
+        }
+
+        let latest = PostsRepository::get_latest_pin_clock(&self.db, author_peer_id)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+        if latest.is_some_and(|clock| lamport_clock as i64 <= clock) {
+            return Ok(()); // Already have a newer pin state
+        }
+
+        // Update lamport clock
+        self.db
+            .update_lamport_clock(author_peer_id, lamport_clock as i64)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?;
+
+        self.apply_pin(&signable, signature)
+    }
+
     /// Get the database reference (for testing)
     #[cfg(test)]
     pub fn db(&self) -> &Database {
@@ -697,6 +1009,20 @@ impl PostsService {
     }
 }
 
+/// Whether an incoming update replaces the stored version of a post.
+///
+/// The higher lamport clock wins. Two edits made at the same clock (two
+/// devices of one identity, or a misbehaving peer) are broken by comparing
+/// content hashes, so every peer keeps the same edit whichever arrived first.
+fn update_wins(existing: &Post, lamport_clock: u64, content_text: Option<&str>) -> bool {
+    let existing_clock = existing.lamport_clock as u64;
+    if lamport_clock != existing_clock {
+        return lamport_clock > existing_clock;
+    }
+    let content_hash = |text: Option<&str>| Sha256::digest(text.unwrap_or_default().as_bytes());
+    content_hash(content_text) > content_hash(existing.content_text.as_deref())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -734,6 +1060,7 @@ mod tests {
                 passphrase: "test-pass".to_string(),
                 bio: None,
                 passphrase_hint: None,
+                kdf_profile: None,
             })
             .unwrap();
 
@@ -765,6 +1092,53 @@ mod tests {
         assert!(!post.signature.is_empty());
     }
 
+    #[test]
+    fn test_import_feed_entry_skips_duplicates() {
+        let (_db, _identity, _contacts, _perms, service, peer_id) = create_test_env();
+
+        let entry = FeedEntry {
+            key: "https://blog.example.com/hello".to_string(),
+            title: Some("Hello".to_string()),
+            link: Some("https://blog.example.com/hello".to_string()),
+            body: "From my old blog".to_string(),
+            published_at: Some(1672653600),
+            enclosures: Vec::new(),
+        };
+
+        let post = service
+            .import_feed_entry(&entry, PostVisibility::Public)
+            .unwrap()
+            .unwrap();
+        assert_eq!(post.author_peer_id, peer_id);
+        assert_eq!(post.created_at, 1672653600);
+        assert_eq!(
+            post.content_text.as_deref(),
+            Some("Hello\n\nFrom my old blog")
+        );
+
+        // The stored post keeps the imported date
+        let stored = service.get_post(&post.post_id).unwrap().unwrap();
+        assert_eq!(stored.created_at, 1672653600);
+
+        assert!(service
+            .import_feed_entry(&entry, PostVisibility::Public)
+            .unwrap()
+            .is_none());
+        assert_eq!(service.get_my_posts(50, None).unwrap().len(), 1);
+
+        // A publication date in the future is clamped to now
+        let future = FeedEntry {
+            key: "future".to_string(),
+            published_at: Some(i64::MAX),
+            ..entry
+        };
+        let post = service
+            .import_feed_entry(&future, PostVisibility::Public)
+            .unwrap()
+            .unwrap();
+        assert!(post.created_at <= chrono::Utc::now().timestamp());
+    }
+
     #[test]
     fn test_create_post_contacts_visibility() {
         let (_db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
@@ -985,6 +1359,108 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_add_media_stops_at_max_attachments() {
+        let (_db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+        assert_eq!(
+            service.get_max_post_attachments().unwrap(),
+            DEFAULT_MAX_POST_ATTACHMENTS
+        );
+        service.set_max_post_attachments(2).unwrap();
+        assert!(service.set_max_post_attachments(0).is_err());
+        assert!(service
+            .set_max_post_attachments(MAX_POST_ATTACHMENTS_LIMIT + 1)
+            .is_err());
+
+        let created = service
+            .create_post("text", Some("Gallery"), PostVisibility::Public)
+            .unwrap();
+        let add = |media_hash: &str, sort_order: i32| {
+            service.add_media_to_post(&AddMediaParams {
+                post_id: &created.post_id,
+                media_hash,
+                media_type: "image",
+                mime_type: "image/png",
+                file_name: "photo.png",
+                file_size: 100,
+                width: None,
+                height: None,
+                duration_seconds: None,
+                sort_order,
+            })
+        };
+
+        add("hash-1", 0).unwrap();
+        add("hash-2", 1).unwrap();
+        assert!(matches!(add("hash-3", 2), Err(AppError::Validation(_))));
+        assert_eq!(service.get_post_media(&created.post_id).unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_incoming_post_with_too_many_attachments_is_rejected() {
+        use crate::db::{ContactData, ContactsRepository};
+        use crate::services::{sign, CryptoService};
+
+        let (db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+        service.set_max_post_attachments(3).unwrap();
+
+        let (author_key, author_verifying) = CryptoService::generate_ed25519_keypair();
+        let (_, author_x25519) = CryptoService::generate_x25519_keypair();
+        let author = "12D3KooWAuthor";
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: author.to_string(),
+                public_key: author_verifying.to_bytes().to_vec(),
+                x25519_public: author_x25519.to_bytes().to_vec(),
+                display_name: "Author".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        let receive = |post_id: &str, attachments: usize| {
+            let media_hashes: Vec<String> =
+                (0..attachments).map(|i| format!("{:064x}", i)).collect();
+            let signable = SignablePost {
+                post_id: post_id.to_string(),
+                author_peer_id: author.to_string(),
+                content_type: "text".to_string(),
+                content_text: Some("look".to_string()),
+                media_hashes: media_hashes.clone(),
+                visibility: "public".to_string(),
+                lamport_clock: 1,
+                created_at: 1000,
+            };
+            let signature = sign(&author_key, &signable).unwrap();
+            service.process_incoming_post(&IncomingPostParams {
+                post_id,
+                author_peer_id: author,
+                content_type: "text",
+                content_text: Some("look"),
+                media_hashes: &media_hashes,
+                visibility: "public",
+                lamport_clock: 1,
+                created_at: 1000,
+                signature: &signature,
+                sig_version: CURRENT_SIG_VERSION,
+            })
+        };
+
+        // Validly signed by a contact, but naming thousands of media files
+        assert!(matches!(
+            receive("flood-post", 5000),
+            Err(AppError::Validation(_))
+        ));
+        assert!(service.get_post("flood-post").unwrap().is_none());
+
+        // One past the limit is refused, the limit itself is fine
+        assert!(receive("over-post", 4).is_err());
+        receive("full-post", 3).unwrap();
+        assert!(service.get_post("full-post").unwrap().is_some());
+    }
+
     #[test]
     fn test_post_event_recorded() {
         let (_db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
@@ -1009,4 +1485,306 @@ mod tests {
         let result = service.create_post("text", Some("Should fail"), PostVisibility::Public);
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_pin_post_replaces_previous_pin() {
+        let (_db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+
+        let first = service
+            .create_post("text", Some("First"), PostVisibility::Public)
+            .unwrap();
+        let second = service
+            .create_post("text", Some("Second"), PostVisibility::Public)
+            .unwrap();
+        let newest = service
+            .create_post("text", Some("Newest"), PostVisibility::Public)
+            .unwrap();
+
+        let pin = service.pin_post(&first.post_id).unwrap();
+        assert!(pin.pinned);
+        assert!(pin.lamport_clock > newest.lamport_clock);
+        service.pin_post(&second.post_id).unwrap();
+
+        let posts = service.get_my_posts(10, None).unwrap();
+        assert_eq!(posts[0].post_id, second.post_id);
+        assert!(posts[0].pinned);
+        assert_eq!(posts.iter().filter(|p| p.pinned).count(), 1);
+        assert_eq!(posts[1].post_id, newest.post_id);
+
+        // Only the pinned post can be unpinned
+        assert!(matches!(
+            service.unpin_post(&first.post_id),
+            Err(AppError::Validation(_))
+        ));
+        let unpin = service.unpin_post(&second.post_id).unwrap();
+        assert!(!unpin.pinned);
+        assert!(service
+            .get_my_posts(10, None)
+            .unwrap()
+            .iter()
+            .all(|p| !p.pinned));
+    }
+
+    #[test]
+    fn test_cannot_pin_another_users_post() {
+        let (db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+
+        PostsRepository::insert_remote_post(
+            &db,
+            &PostData {
+                post_id: "remote-post".to_string(),
+                author_peer_id: "12D3KooWSomeoneElse".to_string(),
+                content_type: "text".to_string(),
+                content_text: None,
+                visibility: PostVisibility::Public,
+                lamport_clock: 1,
+                created_at: 1000,
+                signature: vec![1],
+                sig_version: CURRENT_SIG_VERSION,
+            },
+        )
+        .unwrap();
+
+        assert!(matches!(
+            service.pin_post("remote-post"),
+            Err(AppError::PermissionDenied(_))
+        ));
+        assert!(matches!(
+            service.pin_post("missing-post"),
+            Err(AppError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_process_incoming_post_pin() {
+        use crate::db::{ContactData, ContactsRepository};
+        use crate::services::{sign, CryptoService};
+
+        let (db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+
+        let (author_key, author_verifying) = CryptoService::generate_ed25519_keypair();
+        let (_, author_x25519) = CryptoService::generate_x25519_keypair();
+        let author = "12D3KooWAuthor".to_string();
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: author.clone(),
+                public_key: author_verifying.to_bytes().to_vec(),
+                x25519_public: author_x25519.to_bytes().to_vec(),
+                display_name: "Author".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+        PostsRepository::insert_remote_post(
+            &db,
+            &PostData {
+                post_id: "remote-post".to_string(),
+                author_peer_id: author.clone(),
+                content_type: "text".to_string(),
+                content_text: None,
+                visibility: PostVisibility::Public,
+                lamport_clock: 1,
+                created_at: 1000,
+                signature: vec![1],
+                sig_version: CURRENT_SIG_VERSION,
+            },
+        )
+        .unwrap();
+
+        let pin = |pinned: bool, lamport_clock: u64| SignablePostPin {
+            post_id: "remote-post".to_string(),
+            author_peer_id: author.clone(),
+            pinned,
+            lamport_clock,
+            timestamp: 2000,
+        };
+        let is_pinned = || service.get_post("remote-post").unwrap().unwrap().pinned;
+
+        // A forged signature is rejected
+        assert!(service
+            .process_incoming_post_pin("remote-post", &author, true, 5, 2000, &[0u8; 64])
+            .is_err());
+        assert!(!is_pinned());
+
+        let signature = sign(&author_key, &pin(true, 5)).unwrap();
+        service
+            .process_incoming_post_pin("remote-post", &author, true, 5, 2000, &signature)
+            .unwrap();
+        assert!(is_pinned());
+
+        // An older unpin arriving late does not override the newer pin
+        let signature = sign(&author_key, &pin(false, 3)).unwrap();
+        service
+            .process_incoming_post_pin("remote-post", &author, false, 3, 2000, &signature)
+            .unwrap();
+        assert!(is_pinned());
+
+        let signature = sign(&author_key, &pin(false, 6)).unwrap();
+        service
+            .process_incoming_post_pin("remote-post", &author, false, 6, 2000, &signature)
+            .unwrap();
+        assert!(!is_pinned());
+    }
+
+    #[test]
+    fn test_concurrent_post_updates_converge() {
+        use crate::db::{ContactData, ContactsRepository};
+        use crate::services::{sign, CryptoService};
+
+        let (author_key, author_verifying) = CryptoService::generate_ed25519_keypair();
+        let author = "12D3KooWAuthor".to_string();
+        let update = |text: &str| {
+            let signable = SignablePostUpdate {
+                post_id: "remote-post".to_string(),
+                author_peer_id: author.clone(),
+                content_text: Some(text.to_string()),
+                lamport_clock: 2,
+                updated_at: 2000,
+            };
+            (text.to_string(), sign(&author_key, &signable).unwrap())
+        };
+        let edits = [update("Edited on laptop"), update("Edited on phone")];
+
+        // Apply the same two clock-2 edits in both orders on separate peers
+        let mut results = Vec::new();
+        for order in [[0, 1], [1, 0]] {
+            let (db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+            ContactsRepository::add_contact(
+                &db,
+                &ContactData {
+                    peer_id: author.clone(),
+                    public_key: author_verifying.to_bytes().to_vec(),
+                    x25519_public: vec![0u8; 32],
+                    display_name: "Author".to_string(),
+                    avatar_hash: None,
+                    bio: None,
+                },
+            )
+            .unwrap();
+            PostsRepository::insert_remote_post(
+                &db,
+                &PostData {
+                    post_id: "remote-post".to_string(),
+                    author_peer_id: author.clone(),
+                    content_type: "text".to_string(),
+                    content_text: Some("Original".to_string()),
+                    visibility: PostVisibility::Public,
+                    lamport_clock: 1,
+                    created_at: 1000,
+                    signature: vec![1],
+                    sig_version: CURRENT_SIG_VERSION,
+                },
+            )
+            .unwrap();
+
+            for index in order {
+                let (text, signature) = &edits[index];
+                service
+                    .process_incoming_post_update(
+                        "remote-post",
+                        &author,
+                        Some(text),
+                        2,
+                        2000,
+                        signature,
+                    )
+                    .unwrap();
+            }
+            let post = service.get_post("remote-post").unwrap().unwrap();
+            assert_eq!(post.lamport_clock, 2);
+            results.push(post.content_text);
+        }
+
+        assert_eq!(results[0], results[1]);
+        assert_ne!(results[0], Some("Original".to_string()));
+    }
+
+    #[test]
+    fn test_markdown_posts_are_sanitized_before_signing() {
+        let (_db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+
+        let post = service
+            .create_post(
+                markdown::CONTENT_TYPE_MARKDOWN,
+                Some("**hi** <script>alert(1)</script>[x](javascript:alert(1))"),
+                PostVisibility::Public,
+            )
+            .unwrap();
+        assert_eq!(post.content_text.as_deref(), Some("**hi** [x](#)"));
+        let stored = service.get_post(&post.post_id).unwrap().unwrap();
+        assert_eq!(stored.content_text.as_deref(), Some("**hi** [x](#)"));
+
+        let update = service
+            .update_post(&post.post_id, Some("<b>edited</b>"))
+            .unwrap();
+        assert_eq!(update.content_text.as_deref(), Some("edited"));
+
+        // Plain text posts are stored as written
+        let text = service
+            .create_post("text", Some("<b>literal</b>"), PostVisibility::Public)
+            .unwrap();
+        assert_eq!(text.content_text.as_deref(), Some("<b>literal</b>"));
+    }
+
+    #[test]
+    fn test_incoming_markdown_post_with_html_is_rejected() {
+        use crate::db::{ContactData, ContactsRepository};
+        use crate::services::{sign, CryptoService};
+
+        let (db, _identity, _contacts, _perms, service, _peer_id) = create_test_env();
+
+        let (author_key, author_verifying) = CryptoService::generate_ed25519_keypair();
+        let (_, author_x25519) = CryptoService::generate_x25519_keypair();
+        let author = "12D3KooWAuthor";
+        ContactsRepository::add_contact(
+            &db,
+            &ContactData {
+                peer_id: author.to_string(),
+                public_key: author_verifying.to_bytes().to_vec(),
+                x25519_public: author_x25519.to_bytes().to_vec(),
+                display_name: "Author".to_string(),
+                avatar_hash: None,
+                bio: None,
+            },
+        )
+        .unwrap();
+
+        let receive = |post_id: &str, content_text: &str| {
+            let signable = SignablePost {
+                post_id: post_id.to_string(),
+                author_peer_id: author.to_string(),
+                content_type: markdown::CONTENT_TYPE_MARKDOWN.to_string(),
+                content_text: Some(content_text.to_string()),
+                media_hashes: Vec::new(),
+                visibility: "public".to_string(),
+                lamport_clock: 1,
+                created_at: 1000,
+            };
+            let signature = sign(&author_key, &signable).unwrap();
+            service.process_incoming_post(&IncomingPostParams {
+                post_id,
+                author_peer_id: author,
+                content_type: markdown::CONTENT_TYPE_MARKDOWN,
+                content_text: Some(content_text),
+                media_hashes: &[],
+                visibility: "public",
+                lamport_clock: 1,
+                created_at: 1000,
+                signature: &signature,
+                sig_version: CURRENT_SIG_VERSION,
+            })
+        };
+
+        // Validly signed, but smuggling HTML past the sender-side sanitizer
+        assert!(matches!(
+            receive("unsafe-post", "hi <img src=x onerror=alert(1)>"),
+            Err(AppError::Validation(_))
+        ));
+        assert!(service.get_post("unsafe-post").unwrap().is_none());
+
+        receive("safe-post", "hi **there**").unwrap();
+        assert!(service.get_post("safe-post").unwrap().is_some());
+    }
 }
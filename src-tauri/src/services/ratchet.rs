@@ -0,0 +1,497 @@
+//! Per-conversation key ratchet for direct messages
+//!
+//! Messages between peers that both speak messaging protocol 2 are encrypted
+//! with one-time message keys instead of the static conversation key. Each
+//! side keeps a ratchet key pair and advertises its public half in every
+//! message header:
+//!
+//! - A sending chain is derived from DH(our ratchet key, their latest ratchet
+//!   key), salted with the conversation key so it stays bound to both
+//!   identities. Every message steps the chain and its key is discarded.
+//! - Receiving a message carrying a newer ratchet key from the peer replaces
+//!   our own ratchet key, so each round trip brings fresh DH secrets. Once
+//!   both secrets behind a chain are gone, its messages can't be recovered,
+//!   even with the long-term X25519 keys.
+//!
+//! Chains aren't chained through a shared root key, so messages sent by both
+//! sides at the same time still decrypt. A peer's first chain is derived
+//! against our static X25519 key, which is why messages sent before we ever
+//! reply are only protected by the symmetric half of the ratchet.
+//!
+//! Peers on protocol 1 never see any of this: we only ratchet once a signed
+//! message from the peer has announced protocol 2, and until then keep using
+//! the static conversation key.
+
+use hkdf::Hkdf;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use x25519_dalek::{PublicKey as X25519Public, StaticSecret as X25519Secret};
+
+use crate::error::{AppError, Result};
+use crate::services::CryptoService;
+
+/// Messages encrypted with the static conversation key
+pub const PROTOCOL_VERSION_STATIC: u32 = 1;
+/// Messages may carry a ratchet header
+pub const PROTOCOL_VERSION_RATCHET: u32 = 2;
+/// Protocol version this build announces
+pub const CURRENT_PROTOCOL_VERSION: u32 = PROTOCOL_VERSION_RATCHET;
+
+/// Most message keys one chain may skip ahead by
+const MAX_SKIP: u32 = 256;
+/// Most skipped message keys kept for late arrivals
+const MAX_SKIPPED_KEYS: usize = 512;
+/// Most replaced ratchet keys of ours kept for messages still in flight
+const MAX_RETIRED_KEYS: usize = 4;
+/// Most receiving chains kept per conversation
+const MAX_RECEIVE_CHAINS: usize = 4;
+
+/// Protocol version assumed for peers that don't announce one
+pub fn legacy_protocol_version() -> u32 {
+    PROTOCOL_VERSION_STATIC
+}
+
+/// Whether a version can be left out of signed bytes
+pub fn is_legacy_protocol_version(version: &u32) -> bool {
+    *version == PROTOCOL_VERSION_STATIC
+}
+
+/// Ratchet state sent with each ratcheted message
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RatchetHeader {
+    /// Sender's current ratchet public key
+    pub sender_key: Vec<u8>,
+    /// Bumped every time the sender replaces its ratchet key
+    pub generation: u32,
+    /// Recipient key the sending chain was derived against
+    pub recipient_key: Vec<u8>,
+    /// Position of the message in its chain
+    pub index: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SendChain {
+    recipient_key: [u8; 32],
+    chain_key: [u8; 32],
+    index: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct ReceiveChain {
+    sender_key: [u8; 32],
+    recipient_key: [u8; 32],
+    chain_key: [u8; 32],
+    next_index: u32,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct SkippedKey {
+    sender_key: [u8; 32],
+    recipient_key: [u8; 32],
+    index: u32,
+    message_key: [u8; 32],
+}
+
+/// Ratchet state for one conversation
+#[derive(Clone, Serialize, Deserialize)]
+pub struct RatchetSession {
+    /// Latest protocol version the peer announced
+    pub peer_protocol_version: u32,
+    our_secret: [u8; 32],
+    our_generation: u32,
+    retired_secrets: Vec<[u8; 32]>,
+    their_key: [u8; 32],
+    their_generation: u32,
+    send_chain: Option<SendChain>,
+    receive_chains: Vec<ReceiveChain>,
+    skipped_keys: Vec<SkippedKey>,
+}
+
+impl RatchetSession {
+    /// Start a session with a peer, targeting their static X25519 key
+    /// until they send us a ratchet key of their own
+    pub fn new(their_static_key: [u8; 32], peer_protocol_version: u32) -> Self {
+        Self {
+            peer_protocol_version,
+            our_secret: fresh_secret(),
+            our_generation: 1,
+            retired_secrets: Vec::new(),
+            their_key: their_static_key,
+            their_generation: 0,
+            send_chain: None,
+            receive_chains: Vec::new(),
+            skipped_keys: Vec::new(),
+        }
+    }
+
+    /// Whether messages to the peer should be ratcheted
+    pub fn supports_ratchet(&self) -> bool {
+        self.peer_protocol_version >= PROTOCOL_VERSION_RATCHET
+    }
+
+    fn our_public(&self) -> [u8; 32] {
+        X25519Public::from(&X25519Secret::from(self.our_secret)).to_bytes()
+    }
+
+    /// Encrypt a message with the next key of our sending chain
+    pub fn encrypt(
+        &mut self,
+        conv_key: &[u8; 32],
+        plaintext: &[u8],
+    ) -> Result<(RatchetHeader, Vec<u8>)> {
+        let our_public = self.our_public();
+        let mut chain = match self.send_chain.take() {
+            Some(chain) if chain.recipient_key == self.their_key => chain,
+            _ => {
+                let shared = CryptoService::x25519_dh(
+                    &X25519Secret::from(self.our_secret),
+                    &X25519Public::from(self.their_key),
+                );
+                SendChain {
+                    recipient_key: self.their_key,
+                    chain_key: derive_chain_key(conv_key, &shared, &our_public, &self.their_key),
+                    index: 0,
+                }
+            }
+        };
+
+        let (next_chain_key, message_key) = step_chain(&chain.chain_key);
+        let header = RatchetHeader {
+            sender_key: our_public.to_vec(),
+            generation: self.our_generation,
+            recipient_key: chain.recipient_key.to_vec(),
+            index: chain.index,
+        };
+        let ciphertext = CryptoService::encrypt_message_with_counter(
+            &message_key,
+            plaintext,
+            chain.index as u64,
+        )?;
+
+        chain.chain_key = next_chain_key;
+        chain.index += 1;
+        self.send_chain = Some(chain);
+        Ok((header, ciphertext))
+    }
+
+    /// Decrypt a ratcheted message. The session only changes if decryption
+    /// succeeds, and the message key can't be used again afterwards.
+    pub fn decrypt(
+        &mut self,
+        conv_key: &[u8; 32],
+        our_static: &X25519Secret,
+        header: &RatchetHeader,
+        ciphertext: &[u8],
+    ) -> Result<Vec<u8>> {
+        let sender_key = key_bytes(&header.sender_key)?;
+        let recipient_key = key_bytes(&header.recipient_key)?;
+
+        let mut next = self.clone();
+        let (message_key, new_chain) = next.take_message_key(
+            conv_key,
+            our_static,
+            sender_key,
+            recipient_key,
+            header.index,
+        )?;
+        let plaintext = CryptoService::decrypt_message_with_counter(
+            &message_key,
+            ciphertext,
+            header.index as u64,
+        )?;
+
+        // A peer that lost its session starts over against our static key
+        let restarted = new_chain && recipient_key == X25519Public::from(our_static).to_bytes();
+        if header.generation > next.their_generation || restarted {
+            next.their_key = sender_key;
+            next.their_generation = header.generation;
+            next.rotate();
+        }
+
+        *self = next;
+        Ok(plaintext)
+    }
+
+    /// Find the key for a received message, advancing its chain past it.
+    /// Also reports whether the chain was new.
+    fn take_message_key(
+        &mut self,
+        conv_key: &[u8; 32],
+        our_static: &X25519Secret,
+        sender_key: [u8; 32],
+        recipient_key: [u8; 32],
+        index: u32,
+    ) -> Result<([u8; 32], bool)> {
+        if let Some(pos) = self.skipped_keys.iter().position(|skipped| {
+            skipped.sender_key == sender_key
+                && skipped.recipient_key == recipient_key
+                && skipped.index == index
+        }) {
+            return Ok((self.skipped_keys.remove(pos).message_key, false));
+        }
+
+        let existing = self.receive_chains.iter().position(|chain| {
+            chain.sender_key == sender_key && chain.recipient_key == recipient_key
+        });
+        let pos = match existing {
+            Some(pos) => pos,
+            None => {
+                let secret = self.secret_for(our_static, &recipient_key).ok_or_else(|| {
+                    AppError::Crypto("Message targets an unknown ratchet key".to_string())
+                })?;
+                let shared = CryptoService::x25519_dh(&secret, &X25519Public::from(sender_key));
+                if self.receive_chains.len() >= MAX_RECEIVE_CHAINS {
+                    self.receive_chains.remove(0);
+                }
+                self.receive_chains.push(ReceiveChain {
+                    sender_key,
+                    recipient_key,
+                    chain_key: derive_chain_key(conv_key, &shared, &sender_key, &recipient_key),
+                    next_index: 0,
+                });
+                self.receive_chains.len() - 1
+            }
+        };
+
+        let chain = &mut self.receive_chains[pos];
+        if index < chain.next_index {
+            return Err(AppError::Crypto(
+                "Ratchet message key was already used".to_string(),
+            ));
+        }
+        if index - chain.next_index > MAX_SKIP {
+            return Err(AppError::Crypto(
+                "Too many skipped ratchet messages".to_string(),
+            ));
+        }
+
+        while chain.next_index < index {
+            let (next_chain_key, message_key) = step_chain(&chain.chain_key);
+            self.skipped_keys.push(SkippedKey {
+                sender_key,
+                recipient_key,
+                index: chain.next_index,
+                message_key,
+            });
+            chain.chain_key = next_chain_key;
+            chain.next_index += 1;
+        }
+        let (next_chain_key, message_key) = step_chain(&chain.chain_key);
+        chain.chain_key = next_chain_key;
+        chain.next_index += 1;
+
+        if self.skipped_keys.len() > MAX_SKIPPED_KEYS {
+            let excess = self.skipped_keys.len() - MAX_SKIPPED_KEYS;
+            self.skipped_keys.drain(..excess);
+        }
+        Ok((message_key, existing.is_none()))
+    }
+
+    /// Our secret for one of our public keys, current, retired or static
+    fn secret_for(&self, our_static: &X25519Secret, public: &[u8; 32]) -> Option<X25519Secret> {
+        std::iter::once(&self.our_secret)
+            .chain(&self.retired_secrets)
+            .map(|secret| X25519Secret::from(*secret))
+            .chain(std::iter::once(our_static.clone()))
+            .find(|secret| X25519Public::from(secret).as_bytes() == public)
+    }
+
+    /// Replace our ratchet key, keeping the old one for messages in flight
+    fn rotate(&mut self) {
+        self.retired_secrets.insert(0, self.our_secret);
+        self.retired_secrets.truncate(MAX_RETIRED_KEYS);
+        self.our_secret = fresh_secret();
+        self.our_generation += 1;
+        self.send_chain = None;
+    }
+
+    /// Key the stored session is encrypted under
+    pub fn storage_key(our_static: &X25519Secret) -> [u8; 32] {
+        hkdf_expand(
+            b"harbor:v2:ratchet-state",
+            our_static.as_bytes(),
+            b"storage",
+        )
+    }
+
+    /// Serialize and encrypt the session for storage
+    pub fn seal(&self, storage_key: &[u8; 32]) -> Result<Vec<u8>> {
+        let state = serde_json::to_vec(self).map_err(|e| {
+            AppError::Serialization(format!("Failed to serialize ratchet session: {}", e))
+        })?;
+        CryptoService::encrypt_message(storage_key, &state)
+    }
+
+    /// Decrypt and deserialize a stored session
+    pub fn open(sealed: &[u8], storage_key: &[u8; 32]) -> Result<Self> {
+        let state = CryptoService::decrypt_message(storage_key, sealed)?;
+        serde_json::from_slice(&state).map_err(|e| {
+            AppError::Serialization(format!("Failed to deserialize ratchet session: {}", e))
+        })
+    }
+}
+
+fn fresh_secret() -> [u8; 32] {
+    let (secret, _) = CryptoService::generate_x25519_keypair();
+    secret.to_bytes()
+}
+
+fn key_bytes(key: &[u8]) -> Result<[u8; 32]> {
+    key.try_into()
+        .map_err(|_| AppError::Crypto("Invalid ratchet key length".to_string()))
+}
+
+fn hkdf_expand(salt: &[u8], ikm: &[u8], info: &[u8]) -> [u8; 32] {
+    let hk = Hkdf::<Sha256>::new(Some(salt), ikm);
+    let mut key = [0u8; 32];
+    hk.expand(info, &mut key).expect("HKDF expand failed");
+    key
+}
+
+/// First key of the chain from `sender_key` to `recipient_key`
+fn derive_chain_key(
+    conv_key: &[u8; 32],
+    shared_secret: &[u8; 32],
+    sender_key: &[u8; 32],
+    recipient_key: &[u8; 32],
+) -> [u8; 32] {
+    let mut info = b"harbor:v2:ratchet-chain".to_vec();
+    info.extend_from_slice(sender_key);
+    info.extend_from_slice(recipient_key);
+    hkdf_expand(conv_key, shared_secret, &info)
+}
+
+/// Advance a chain, returning the next chain key and this step's message key
+fn step_chain(chain_key: &[u8; 32]) -> ([u8; 32], [u8; 32]) {
+    (
+        hkdf_expand(b"harbor:v2:ratchet-step", chain_key, b"chain"),
+        hkdf_expand(b"harbor:v2:ratchet-step", chain_key, b"message"),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Party {
+        static_secret: X25519Secret,
+        session: RatchetSession,
+    }
+
+    fn pair() -> (Party, Party, [u8; 32]) {
+        let (alice_static, alice_public) = CryptoService::generate_x25519_keypair();
+        let (bob_static, bob_public) = CryptoService::generate_x25519_keypair();
+        let alice = Party {
+            static_secret: alice_static,
+            session: RatchetSession::new(bob_public.to_bytes(), PROTOCOL_VERSION_RATCHET),
+        };
+        let bob = Party {
+            static_secret: bob_static,
+            session: RatchetSession::new(alice_public.to_bytes(), PROTOCOL_VERSION_RATCHET),
+        };
+        (alice, bob, [42u8; 32])
+    }
+
+    fn send(from: &mut Party, conv_key: &[u8; 32], text: &str) -> (RatchetHeader, Vec<u8>) {
+        from.session.encrypt(conv_key, text.as_bytes()).unwrap()
+    }
+
+    fn receive(
+        to: &mut Party,
+        conv_key: &[u8; 32],
+        sealed: &(RatchetHeader, Vec<u8>),
+    ) -> Result<String> {
+        let plaintext = to
+            .session
+            .decrypt(conv_key, &to.static_secret, &sealed.0, &sealed.1)?;
+        Ok(String::from_utf8(plaintext).unwrap())
+    }
+
+    #[test]
+    fn test_round_trips_replace_ratchet_keys() {
+        let (mut alice, mut bob, conv_key) = pair();
+
+        let first = send(&mut alice, &conv_key, "hi bob");
+        assert_eq!(receive(&mut bob, &conv_key, &first).unwrap(), "hi bob");
+
+        let reply = send(&mut bob, &conv_key, "hi alice");
+        assert_eq!(reply.0.recipient_key, first.0.sender_key);
+        assert_eq!(receive(&mut alice, &conv_key, &reply).unwrap(), "hi alice");
+
+        // Alice moved to a new key after hearing Bob's
+        let second = send(&mut alice, &conv_key, "again");
+        assert_ne!(second.0.sender_key, first.0.sender_key);
+        assert_eq!(second.0.recipient_key, reply.0.sender_key);
+        assert_eq!(second.0.generation, first.0.generation + 1);
+        assert_eq!(receive(&mut bob, &conv_key, &second).unwrap(), "again");
+    }
+
+    #[test]
+    fn test_message_key_is_single_use() {
+        let (mut alice, mut bob, conv_key) = pair();
+        let sealed = send(&mut alice, &conv_key, "once");
+        receive(&mut bob, &conv_key, &sealed).unwrap();
+
+        assert!(matches!(
+            receive(&mut bob, &conv_key, &sealed),
+            Err(AppError::Crypto(_))
+        ));
+    }
+
+    #[test]
+    fn test_out_of_order_messages_decrypt() {
+        let (mut alice, mut bob, conv_key) = pair();
+        let first = send(&mut alice, &conv_key, "one");
+        let second = send(&mut alice, &conv_key, "two");
+        let third = send(&mut alice, &conv_key, "three");
+
+        assert_eq!(receive(&mut bob, &conv_key, &third).unwrap(), "three");
+        assert_eq!(receive(&mut bob, &conv_key, &first).unwrap(), "one");
+        assert_eq!(receive(&mut bob, &conv_key, &second).unwrap(), "two");
+    }
+
+    #[test]
+    fn test_simultaneous_first_messages_decrypt() {
+        let (mut alice, mut bob, conv_key) = pair();
+        let from_alice = send(&mut alice, &conv_key, "from alice");
+        let from_bob = send(&mut bob, &conv_key, "from bob");
+
+        assert_eq!(
+            receive(&mut bob, &conv_key, &from_alice).unwrap(),
+            "from alice"
+        );
+        assert_eq!(
+            receive(&mut alice, &conv_key, &from_bob).unwrap(),
+            "from bob"
+        );
+
+        let next = send(&mut alice, &conv_key, "next");
+        assert_eq!(receive(&mut bob, &conv_key, &next).unwrap(), "next");
+    }
+
+    #[test]
+    fn test_failed_decryption_leaves_session_unchanged() {
+        let (mut alice, mut bob, conv_key) = pair();
+        let mut sealed = send(&mut alice, &conv_key, "hello");
+        let genuine = sealed.1.clone();
+        sealed.1[0] ^= 0xff;
+
+        assert!(receive(&mut bob, &conv_key, &sealed).is_err());
+        sealed.1 = genuine;
+        assert_eq!(receive(&mut bob, &conv_key, &sealed).unwrap(), "hello");
+    }
+
+    #[test]
+    fn test_sealed_session_round_trip() {
+        let (mut alice, mut bob, conv_key) = pair();
+        let storage_key = RatchetSession::storage_key(&alice.static_secret);
+        let sealed_state = alice.session.seal(&storage_key).unwrap();
+
+        alice.session = RatchetSession::open(&sealed_state, &storage_key).unwrap();
+        let sealed = send(&mut alice, &conv_key, "restored");
+        assert_eq!(receive(&mut bob, &conv_key, &sealed).unwrap(), "restored");
+
+        let other_key = RatchetSession::storage_key(&bob.static_secret);
+        assert!(RatchetSession::open(&sealed_state, &other_key).is_err());
+    }
+}
@@ -8,12 +8,19 @@
 //! 1. **Signature is NEVER part of signed bytes**: The signature field is excluded from
 //!    the data being signed. We sign the payload, then attach the signature.
 //!
-//! 2. **CBOR encoding is deterministic**: We use RFC 8949 canonical CBOR encoding
-//!    (sorted keys, minimal encoding) via ciborium.
+//! 2. **CBOR encoding is deterministic**: ciborium writes every value in its
+//!    shortest form with definite lengths, and struct fields as map keys in
+//!    declaration order. That exact encoding is the canonical one.
 //!
 //! 3. **All signable structs have a `Signable` variant**: For each protocol message,
 //!    we define a version without the signature field for signing.
 //!
+//! 4. **Received CBOR must be canonical**: Payloads that arrive as raw bytes are
+//!    decoded with `decode_canonical`, which rejects anything that doesn't
+//!    re-encode to the same bytes (long-form integers, indefinite lengths,
+//!    reordered keys, trailing data). Otherwise one payload could travel as
+//!    several byte strings, only one of which matches the signature.
+//!
 //! ## Signing Process
 //!
 //! 1. Create the signable payload (struct without signature)
@@ -27,9 +34,26 @@
 //! 2. Create signable payload from message fields (excluding signature)
 //! 3. CBOR-encode with canonical encoding
 //! 4. Verify signature against raw bytes
-
+//!
+//! ## Signature Versions
+//!
+//! Stored posts and messages record the scheme version they were signed
+//! with (`sig_version`), and so do the wire messages carrying them. When the
+//! signing rules change, new items get a new version while old ones keep
+//! verifying under the rules they were signed with. `SignaturePolicy`
+//! dispatches on the version and can be tightened to refuse old schemes.
+
+use crate::db::repositories::settings_repo::SETTING_MIN_SIGNATURE_VERSION;
+use crate::db::repositories::SettingsRepository;
+use crate::db::Database;
 use crate::error::{AppError, Result};
+use crate::services::crypto_service::{is_legacy_message_cipher, legacy_message_cipher};
+use crate::services::ratchet::{
+    is_legacy_protocol_version, legacy_protocol_version, RatchetHeader,
+};
+use crate::services::voice_note::VoiceNote;
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use serde::de::DeserializeOwned;
 use serde::{Deserialize, Serialize};
 
 /// Trait for types that can be canonically signed
@@ -64,6 +88,83 @@ pub fn verify(
     Ok(verifying_key.verify(&bytes, &signature).is_ok())
 }
 
+/// Decode a signable payload received as raw CBOR, rejecting any encoding
+/// other than the canonical one produced by `signable_bytes`
+pub fn decode_canonical<T: Signable + DeserializeOwned>(bytes: &[u8]) -> Result<T> {
+    let payload: T = ciborium::from_reader(bytes)
+        .map_err(|e| AppError::InvalidData(format!("CBOR decoding failed: {}", e)))?;
+
+    if payload.signable_bytes()? != bytes {
+        return Err(AppError::InvalidData(
+            "Payload is not canonically encoded".to_string(),
+        ));
+    }
+
+    Ok(payload)
+}
+
+/// Scheme version 1: Ed25519 over the canonical CBOR of the signable struct,
+/// with no domain separation. Everything signed before versions were
+/// recorded uses it.
+pub const SIG_VERSION_CBOR: u32 = 1;
+
+/// Scheme version new signatures are made with
+pub const CURRENT_SIG_VERSION: u32 = SIG_VERSION_CBOR;
+
+/// Which signature scheme versions verification accepts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SignaturePolicy {
+    /// Signatures made with an older scheme are rejected
+    pub min_version: u32,
+}
+
+impl Default for SignaturePolicy {
+    fn default() -> Self {
+        Self {
+            min_version: SIG_VERSION_CBOR,
+        }
+    }
+}
+
+impl SignaturePolicy {
+    /// Load the policy from settings, accepting every known scheme by default
+    pub fn from_settings(db: &Database) -> Result<Self> {
+        let min_version = SettingsRepository::get(db, SETTING_MIN_SIGNATURE_VERSION)
+            .map_err(|e| AppError::DatabaseString(e.to_string()))?
+            .and_then(|value| value.parse::<u32>().ok())
+            .unwrap_or(SIG_VERSION_CBOR);
+        Ok(Self { min_version })
+    }
+
+    /// Verify a signature using the rules of the scheme it was made with
+    pub fn verify(
+        &self,
+        verifying_key: &VerifyingKey,
+        signable: &impl Signable,
+        signature_bytes: &[u8],
+        sig_version: u32,
+    ) -> Result<bool> {
+        if sig_version < self.min_version {
+            return Err(AppError::Crypto(format!(
+                "Signature version {} is older than the minimum accepted ({})",
+                sig_version, self.min_version
+            )));
+        }
+        match sig_version {
+            SIG_VERSION_CBOR => verify(verifying_key, signable, signature_bytes),
+            other => Err(AppError::Crypto(format!(
+                "Unsupported signature version {}",
+                other
+            ))),
+        }
+    }
+}
+
+/// Serde default for `sig_version` fields, for peers that predate them
+pub fn legacy_sig_version() -> u32 {
+    SIG_VERSION_CBOR
+}
+
 // ============================================================
 // IDENTITY MESSAGES
 // ============================================================
@@ -92,6 +193,16 @@ pub struct SignableIdentityResponse {
 
 impl Signable for SignableIdentityResponse {}
 
+/// Signable request for a contact's shared contact list
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableContactListRequest {
+    pub requester_peer_id: String,
+    pub responder_peer_id: String,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableContactListRequest {}
+
 // ============================================================
 // PERMISSION MESSAGES
 // ============================================================
@@ -135,6 +246,17 @@ pub struct SignablePermissionRevoke {
 
 impl Signable for SignablePermissionRevoke {}
 
+/// Subject's acknowledgement that it has stored an issuer's grants
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignablePermissionAck {
+    pub issuer_peer_id: String,
+    pub subject_peer_id: String,
+    pub grant_ids: Vec<String>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignablePermissionAck {}
+
 // ============================================================
 // MESSAGE TYPES
 // ============================================================
@@ -147,6 +269,14 @@ impl Signable for SignablePermissionRevoke {}
 /// - Attacker cannot modify the counter without invalidating signature
 /// - Each message has a cryptographically bound nonce
 /// - Replay of exact message is detected via `check_and_record_nonce()`
+///
+/// `nonce_salt` is likewise signed when present. It is omitted for legacy
+/// counter-only messages so their signed bytes are unchanged.
+///
+/// `protocol_version` and `ratchet` follow the same rule: they are left out
+/// for protocol 1 messages, and signed otherwise so a peer's announced
+/// version can't be downgraded in transit. So is `cipher`, which is only
+/// signed when it names something other than AES-256-GCM.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignableDirectMessage {
     pub message_id: String,
@@ -159,6 +289,22 @@ pub struct SignableDirectMessage {
     pub nonce_counter: u64, // For replay protection - bound to signature
     pub lamport_clock: u64,
     pub timestamp: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub nonce_salt: Option<Vec<u8>>,
+    #[serde(
+        default = "legacy_protocol_version",
+        skip_serializing_if = "is_legacy_protocol_version"
+    )]
+    pub protocol_version: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratchet: Option<RatchetHeader>,
+    #[serde(
+        default = "legacy_message_cipher",
+        skip_serializing_if = "is_legacy_message_cipher"
+    )]
+    pub cipher: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub voice: Option<VoiceNote>,
 }
 
 impl Signable for SignableDirectMessage {}
@@ -175,6 +321,49 @@ pub struct SignableMessageAck {
 
 impl Signable for SignableMessageAck {}
 
+/// Signable version of ReadReceipt (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableReadReceipt {
+    pub conversation_id: String,
+    pub reader_peer_id: String,
+    pub message_ids: Vec<String>,
+    pub read_at: i64,
+}
+
+impl Signable for SignableReadReceipt {}
+
+/// Signable version of MessageEdit (excludes signature)
+///
+/// `ratchet` is only present, and signed, for edits encrypted under the
+/// conversation's ratchet, so statically keyed edits sign the same bytes as
+/// before.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableMessageEdit {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub sender_peer_id: String,
+    pub content_encrypted: Vec<u8>,
+    pub nonce_salt: Vec<u8>,
+    pub lamport_clock: u64,
+    pub edited_at: i64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ratchet: Option<RatchetHeader>,
+}
+
+impl Signable for SignableMessageEdit {}
+
+/// Signable version of MessageDelete (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableMessageDelete {
+    pub message_id: String,
+    pub conversation_id: String,
+    pub sender_peer_id: String,
+    pub lamport_clock: u64,
+    pub deleted_at: i64,
+}
+
+impl Signable for SignableMessageDelete {}
+
 // ============================================================
 // POST MESSAGES
 // ============================================================
@@ -217,6 +406,18 @@ pub struct SignablePostDelete {
 
 impl Signable for SignablePostDelete {}
 
+/// Signable pin/unpin of a post on its author's wall (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignablePostPin {
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub pinned: bool,
+    pub lamport_clock: u64,
+    pub timestamp: i64,
+}
+
+impl Signable for SignablePostPin {}
+
 /// Signable version of PostLike (excludes signature)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignablePostLike {
@@ -256,6 +457,18 @@ pub struct SignableBoardPostDelete {
 
 impl Signable for SignableBoardPostDelete {}
 
+/// Signable version of a board post reaction (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableBoardPostReaction {
+    pub post_id: String,
+    pub reactor_peer_id: String,
+    pub reaction_type: String,
+    pub remove: bool,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableBoardPostReaction {}
+
 /// Signable version of a peer registration (excludes signature)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignablePeerRegistration {
@@ -266,6 +479,15 @@ pub struct SignablePeerRegistration {
 
 impl Signable for SignablePeerRegistration {}
 
+/// Signable version of a peer deregistration (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignablePeerDeregistration {
+    pub peer_id: String,
+    pub timestamp: i64,
+}
+
+impl Signable for SignablePeerDeregistration {}
+
 /// Signable version of a board list request (excludes signature)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignableBoardListRequest {
@@ -285,6 +507,42 @@ pub struct SignableBoardPostsRequest {
 
 impl Signable for SignableBoardPostsRequest {}
 
+/// Signable version of a board creation request (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableBoardCreate {
+    pub requester_peer_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableBoardCreate {}
+
+/// Signable version of a request asking a relay to issue a community invite
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableInviteRequest {
+    pub requester_peer_id: String,
+    pub expires_at: Option<i64>,
+    pub max_uses: Option<u32>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableInviteRequest {}
+
+/// The part of a community invite signed by the issuing relay.
+/// Must match `SignableCommunityInvite` in the relay server.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SignableCommunityInvite {
+    pub relay_address: String,
+    pub community_name: String,
+    pub relay_peer_id: String,
+    pub issued_at: i64,
+    pub expires_at: Option<i64>,
+    pub max_uses: Option<u32>,
+}
+
+impl Signable for SignableCommunityInvite {}
+
 // ============================================================
 // WALL POST MESSAGES (relay-synced personal posts)
 // ============================================================
@@ -328,6 +586,20 @@ pub struct SignableWallPostDelete {
 
 impl Signable for SignableWallPostDelete {}
 
+/// A community relay's record of when it received a board or wall post,
+/// signed with the relay's identity key. Covering the author's signature
+/// ties the time to the post exactly as the author signed it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableRelayTimestamp {
+    pub relay_peer_id: String,
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub author_signature: Vec<u8>,
+    pub relay_observed_at: i64,
+}
+
+impl Signable for SignableRelayTimestamp {}
+
 // ============================================================
 // MEDIA FETCH (P2P image transfer)
 // ============================================================
@@ -342,6 +614,19 @@ pub struct SignableMediaFetchRequest {
 
 impl Signable for SignableMediaFetchRequest {}
 
+// ============================================================
+// PRESENCE
+// ============================================================
+
+/// Signable version of a presence heartbeat (excludes signature)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableHeartbeat {
+    pub peer_id: String,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableHeartbeat {}
+
 // ============================================================
 // SIGNALING (Voice Calls)
 // ============================================================
@@ -400,6 +685,20 @@ pub struct SignableSignalingHangup {
 
 impl Signable for SignableSignalingHangup {}
 
+/// Signable version of an in-call data frame (excludes signature)
+///
+/// Data frames only travel while a call with a negotiated data channel is
+/// connected; the call ID ties each frame to that call.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignableCallData {
+    pub call_id: String,
+    pub sender_peer_id: String,
+    pub payload: Vec<u8>,
+    pub timestamp: i64,
+}
+
+impl Signable for SignableCallData {}
+
 // ============================================================
 // CONTENT SYNC
 // ============================================================
@@ -417,10 +716,30 @@ pub struct SignableContentManifestRequest {
     pub cursor: std::collections::HashMap<String, u64>,
     pub limit: u32,
     pub timestamp: i64,
+    /// Omitted when `Full` so signatures match peers that predate this field
+    #[serde(default, skip_serializing_if = "ManifestDetailLevel::is_full")]
+    pub detail_level: ManifestDetailLevel,
 }
 
 impl Signable for SignableContentManifestRequest {}
 
+/// How much per-post detail a manifest response should carry
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ManifestDetailLevel {
+    /// Full [`PostSummary`] entries (content type, media hashes, timestamps)
+    #[default]
+    Full,
+    /// Only post IDs and lamport clocks, for a cheap "do I have everything?" check
+    HeadersOnly,
+}
+
+impl ManifestDetailLevel {
+    pub fn is_full(&self) -> bool {
+        *self == ManifestDetailLevel::Full
+    }
+}
+
 /// Signable version of ContentManifestResponse (excludes signature)
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SignableContentManifestResponse {
@@ -432,10 +751,40 @@ pub struct SignableContentManifestResponse {
     /// Updated cursor for next request (author_peer_id -> lamport_clock)
     pub next_cursor: std::collections::HashMap<String, u64>,
     pub timestamp: i64,
+    /// Post headers for headers-only responses (empty, and omitted, otherwise)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub headers: Vec<PostHeader>,
 }
 
 impl Signable for SignableContentManifestResponse {}
 
+/// Signable view report, sent to an author after fetching one of their posts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignablePostView {
+    pub post_id: String,
+    pub author_peer_id: String,
+    pub viewer_peer_id: String,
+    pub viewed_at: i64,
+}
+
+impl Signable for SignablePostView {}
+
+/// Signable announcement of a new post, pushed to online contacts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignablePostAnnouncement {
+    pub post: PostSummary,
+    pub timestamp: i64,
+}
+
+impl Signable for SignablePostAnnouncement {}
+
+/// Minimal post entry for headers-only manifest responses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PostHeader {
+    pub post_id: String,
+    pub lamport_clock: u64,
+}
+
 /// Summary of a post for manifest responses
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PostSummary {
@@ -571,6 +920,11 @@ mod tests {
             nonce_counter: 42,
             lamport_clock: 5,
             timestamp: 1234567890,
+            nonce_salt: Some(vec![7u8; 12]),
+            protocol_version: legacy_protocol_version(),
+            ratchet: None,
+            cipher: legacy_message_cipher(),
+            voice: None,
         };
 
         let signature = sign(&signing_key, &msg).unwrap();
@@ -582,6 +936,95 @@ mod tests {
             ..msg.clone()
         };
         assert!(!verify(&verifying_key, &tampered, &signature).unwrap());
+
+        // Stripping or swapping the salt also invalidates the signature
+        let unsalted = SignableDirectMessage {
+            nonce_salt: None,
+            ..msg.clone()
+        };
+        assert!(!verify(&verifying_key, &unsalted, &signature).unwrap());
+        let resalted = SignableDirectMessage {
+            nonce_salt: Some(vec![8u8; 12]),
+            ..msg.clone()
+        };
+        assert!(!verify(&verifying_key, &resalted, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_direct_message_protocol_version_is_signed() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+
+        let msg = SignableDirectMessage {
+            message_id: "msg-1".to_string(),
+            conversation_id: "conv-1".to_string(),
+            sender_peer_id: "12D3KooWSender".to_string(),
+            recipient_peer_id: "12D3KooWRecipient".to_string(),
+            content_encrypted: vec![1, 2, 3, 4],
+            content_type: "text".to_string(),
+            reply_to: None,
+            nonce_counter: 42,
+            lamport_clock: 5,
+            timestamp: 1234567890,
+            nonce_salt: None,
+            protocol_version: 2,
+            ratchet: Some(RatchetHeader {
+                sender_key: vec![1u8; 32],
+                generation: 1,
+                recipient_key: vec![2u8; 32],
+                index: 0,
+            }),
+            cipher: legacy_message_cipher(),
+            voice: None,
+        };
+        let signature = sign(&signing_key, &msg).unwrap();
+        assert!(verify(&verifying_key, &msg, &signature).unwrap());
+
+        let downgraded = SignableDirectMessage {
+            protocol_version: legacy_protocol_version(),
+            ..msg.clone()
+        };
+        assert!(!verify(&verifying_key, &downgraded, &signature).unwrap());
+        let unratcheted = SignableDirectMessage {
+            ratchet: None,
+            ..msg.clone()
+        };
+        assert!(!verify(&verifying_key, &unratcheted, &signature).unwrap());
+    }
+
+    #[test]
+    fn test_direct_message_cipher_is_signed() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+
+        let legacy = SignableDirectMessage {
+            message_id: "msg-1".to_string(),
+            conversation_id: "conv-1".to_string(),
+            sender_peer_id: "12D3KooWSender".to_string(),
+            recipient_peer_id: "12D3KooWRecipient".to_string(),
+            content_encrypted: vec![1, 2, 3, 4],
+            content_type: "text".to_string(),
+            reply_to: None,
+            nonce_counter: 42,
+            lamport_clock: 5,
+            timestamp: 1234567890,
+            nonce_salt: None,
+            protocol_version: legacy_protocol_version(),
+            ratchet: None,
+            cipher: legacy_message_cipher(),
+            voice: None,
+        };
+        let chacha = SignableDirectMessage {
+            cipher: "chacha20-poly1305".to_string(),
+            ..legacy.clone()
+        };
+
+        // The default cipher leaves the signed bytes of older messages alone
+        assert!(legacy.signable_bytes().unwrap().len() < chacha.signable_bytes().unwrap().len());
+
+        let signature = sign(&signing_key, &chacha).unwrap();
+        assert!(verify(&verifying_key, &chacha, &signature).unwrap());
+        assert!(!verify(&verifying_key, &legacy, &signature).unwrap());
     }
 
     #[test]
@@ -637,6 +1080,30 @@ mod tests {
         assert!(verify(&verifying_key, &delete, &signature).unwrap());
     }
 
+    #[test]
+    fn test_sign_and_verify_post_pin() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+
+        let pin = SignablePostPin {
+            post_id: "post-1".to_string(),
+            author_peer_id: "12D3KooWAuthor".to_string(),
+            pinned: true,
+            lamport_clock: 4,
+            timestamp: 1234567920,
+        };
+
+        let signature = sign(&signing_key, &pin).unwrap();
+        assert!(verify(&verifying_key, &pin, &signature).unwrap());
+
+        // Flipping the pin state must invalidate the signature
+        let unpin = SignablePostPin {
+            pinned: false,
+            ..pin
+        };
+        assert!(!verify(&verifying_key, &unpin, &signature).unwrap());
+    }
+
     #[test]
     fn test_sign_and_verify_message_ack() {
         let signing_key = SigningKey::generate(&mut OsRng);
@@ -724,12 +1191,53 @@ mod tests {
             cursor,
             limit: 50,
             timestamp: 1234567890,
+            detail_level: ManifestDetailLevel::Full,
         };
 
         let signature = sign(&signing_key, &request).unwrap();
         assert!(verify(&verifying_key, &request, &signature).unwrap());
     }
 
+    #[test]
+    fn test_full_manifest_request_bytes_unchanged_by_detail_level() {
+        /// Shape of the request before `detail_level` existed
+        #[derive(Serialize)]
+        struct LegacyManifestRequest {
+            requester_peer_id: String,
+            cursor: std::collections::HashMap<String, u64>,
+            limit: u32,
+            timestamp: i64,
+        }
+        impl Signable for LegacyManifestRequest {}
+
+        let legacy = LegacyManifestRequest {
+            requester_peer_id: "12D3KooWRequester".to_string(),
+            cursor: std::collections::HashMap::new(),
+            limit: 50,
+            timestamp: 1234567890,
+        };
+        let full = SignableContentManifestRequest {
+            requester_peer_id: legacy.requester_peer_id.clone(),
+            cursor: std::collections::HashMap::new(),
+            limit: 50,
+            timestamp: 1234567890,
+            detail_level: ManifestDetailLevel::Full,
+        };
+        let headers_only = SignableContentManifestRequest {
+            detail_level: ManifestDetailLevel::HeadersOnly,
+            ..full.clone()
+        };
+
+        assert_eq!(
+            full.signable_bytes().unwrap(),
+            legacy.signable_bytes().unwrap()
+        );
+        assert_ne!(
+            headers_only.signable_bytes().unwrap(),
+            legacy.signable_bytes().unwrap()
+        );
+    }
+
     #[test]
     fn test_different_data_produces_different_bytes() {
         let req1 = SignableIdentityRequest {
@@ -777,4 +1285,122 @@ mod tests {
         let signature = sign(&signing_key, &like).unwrap();
         assert!(verify(&verifying_key, &like, &signature).unwrap());
     }
+
+    #[test]
+    fn test_sign_and_verify_heartbeat() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+
+        let heartbeat = SignableHeartbeat {
+            peer_id: "12D3KooWTest".to_string(),
+            timestamp: 1234567890,
+        };
+
+        let signature = sign(&signing_key, &heartbeat).unwrap();
+        assert!(verify(&verifying_key, &heartbeat, &signature).unwrap());
+    }
+
+    /// Canonical encoding of `{requester_peer_id: "a", timestamp: 1}`
+    fn canonical_identity_request() -> Vec<u8> {
+        let mut bytes = vec![0xa2, 0x71];
+        bytes.extend_from_slice(b"requester_peer_id");
+        bytes.extend_from_slice(&[0x61, b'a', 0x69]);
+        bytes.extend_from_slice(b"timestamp");
+        bytes.push(0x01);
+        bytes
+    }
+
+    #[test]
+    fn test_decode_canonical_accepts_signable_bytes() {
+        let request = SignableIdentityRequest {
+            requester_peer_id: "a".to_string(),
+            timestamp: 1,
+        };
+        let bytes = request.signable_bytes().unwrap();
+        assert_eq!(bytes, canonical_identity_request());
+
+        let decoded: SignableIdentityRequest = decode_canonical(&bytes).unwrap();
+        assert_eq!(decoded.requester_peer_id, "a");
+        assert_eq!(decoded.timestamp, 1);
+    }
+
+    #[test]
+    fn test_decode_canonical_rejects_non_canonical_cbor() {
+        let canonical = canonical_identity_request();
+        let (peer_entry, timestamp_entry) = canonical[1..].split_at(20);
+
+        // Timestamp as a 4-byte integer instead of a single byte
+        let mut long_integer = canonical.clone();
+        long_integer.pop();
+        long_integer.extend_from_slice(&[0x1a, 0x00, 0x00, 0x00, 0x01]);
+
+        // Keys in a different order
+        let mut reordered = vec![0xa2];
+        reordered.extend_from_slice(timestamp_entry);
+        reordered.extend_from_slice(peer_entry);
+
+        // Indefinite-length map
+        let mut indefinite = vec![0xbf];
+        indefinite.extend_from_slice(&canonical[1..]);
+        indefinite.push(0xff);
+
+        // Extra bytes after the payload
+        let mut trailing = canonical.clone();
+        trailing.push(0x00);
+
+        for bytes in [long_integer, reordered, indefinite, trailing] {
+            // Each variant still carries the same payload...
+            let lenient: SignableIdentityRequest = ciborium::from_reader(&bytes[..]).unwrap();
+            assert_eq!(lenient.timestamp, 1);
+            // ...but is rejected as non-canonical
+            assert!(matches!(
+                decode_canonical::<SignableIdentityRequest>(&bytes),
+                Err(AppError::InvalidData(_))
+            ));
+        }
+    }
+
+    #[test]
+    fn test_signature_policy_dispatches_on_version() {
+        let signing_key = SigningKey::generate(&mut OsRng);
+        let verifying_key = signing_key.verifying_key();
+        let request = SignableIdentityRequest {
+            requester_peer_id: "12D3KooWTest".to_string(),
+            timestamp: 1234567890,
+        };
+        let signature = sign(&signing_key, &request).unwrap();
+
+        let policy = SignaturePolicy::default();
+        assert!(policy
+            .verify(&verifying_key, &request, &signature, SIG_VERSION_CBOR)
+            .unwrap());
+        // A scheme this build doesn't know can't be checked either way
+        assert!(policy
+            .verify(
+                &verifying_key,
+                &request,
+                &signature,
+                CURRENT_SIG_VERSION + 1
+            )
+            .is_err());
+
+        let strict = SignaturePolicy {
+            min_version: SIG_VERSION_CBOR + 1,
+        };
+        assert!(strict
+            .verify(&verifying_key, &request, &signature, SIG_VERSION_CBOR)
+            .is_err());
+    }
+
+    #[test]
+    fn test_signature_policy_from_settings() {
+        let db = Database::in_memory().unwrap();
+        assert_eq!(
+            SignaturePolicy::from_settings(&db).unwrap(),
+            SignaturePolicy::default()
+        );
+
+        SettingsRepository::set(&db, SETTING_MIN_SIGNATURE_VERSION, "2").unwrap();
+        assert_eq!(SignaturePolicy::from_settings(&db).unwrap().min_version, 2);
+    }
 }
@@ -0,0 +1,208 @@
+//! Voice notes: short audio clips sent as direct messages.
+//!
+//! The audio is stored content-addressed in `MediaStorageService` and the
+//! message carries a signed `VoiceNote` naming it, which the recipient uses
+//! to fetch the clip from the sender over media sync. Audio is stored as
+//! recorded; only the container is checked, nothing is transcoded.
+
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::error::{AppError, Result};
+
+/// Content type of messages carrying a voice note
+pub const CONTENT_TYPE_VOICE: &str = "voice";
+
+/// Largest voice note accepted, sent or received (5 MiB)
+pub const MAX_VOICE_NOTE_BYTES: u64 = 5 * 1024 * 1024;
+
+/// Longest voice note accepted (5 minutes)
+pub const MAX_VOICE_NOTE_DURATION_MS: u64 = 5 * 60 * 1000;
+
+/// Audio containers a voice note may use
+pub const VOICE_NOTE_MIME_TYPES: [&str; 5] = [
+    "audio/ogg",
+    "audio/webm",
+    "audio/mp4",
+    "audio/mpeg",
+    "audio/wav",
+];
+
+/// A voice note attached to a message (signed with it)
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VoiceNote {
+    /// SHA256 of the audio in media storage
+    pub media_hash: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub duration_ms: u64,
+}
+
+impl VoiceNote {
+    /// Describe recorded audio as a voice note, checking it against the limits
+    /// before anything is stored. `media_hash` is the hash media storage will
+    /// file the audio under.
+    pub fn for_audio(data: &[u8], mime_type: &str, duration_ms: u64) -> Result<Self> {
+        let voice = Self {
+            media_hash: hex::encode(Sha256::digest(data)),
+            mime_type: normalize_voice_mime_type(mime_type),
+            size_bytes: data.len() as u64,
+            duration_ms,
+        };
+        voice.validate()?;
+        voice.validate_audio(data)?;
+        Ok(voice)
+    }
+
+    /// Check the announced metadata against the voice note limits
+    pub fn validate(&self) -> Result<()> {
+        if self.media_hash.len() != 64 || !self.media_hash.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(AppError::Validation(format!(
+                "Invalid voice note media hash: {}",
+                self.media_hash
+            )));
+        }
+        if !VOICE_NOTE_MIME_TYPES.contains(&self.mime_type.as_str()) {
+            return Err(AppError::Validation(format!(
+                "Unsupported voice note format: {}",
+                self.mime_type
+            )));
+        }
+        if self.size_bytes == 0 || self.size_bytes > MAX_VOICE_NOTE_BYTES {
+            return Err(AppError::Validation(format!(
+                "Voice note must be between 1 and {} bytes",
+                MAX_VOICE_NOTE_BYTES
+            )));
+        }
+        if self.duration_ms == 0 || self.duration_ms > MAX_VOICE_NOTE_DURATION_MS {
+            return Err(AppError::Validation(format!(
+                "Voice note must be between 1 ms and {} ms long",
+                MAX_VOICE_NOTE_DURATION_MS
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that `data` is the audio this note describes
+    pub fn validate_audio(&self, data: &[u8]) -> Result<()> {
+        if data.len() as u64 != self.size_bytes {
+            return Err(AppError::Validation(format!(
+                "Voice note is {} bytes but {} were announced",
+                data.len(),
+                self.size_bytes
+            )));
+        }
+        if !matches_container(&self.mime_type, data) {
+            return Err(AppError::Validation(format!(
+                "Voice note data is not {} audio",
+                self.mime_type
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Reduce a recorder MIME type such as `audio/webm;codecs=opus` to the
+/// container type voice notes are stored under
+pub fn normalize_voice_mime_type(mime_type: &str) -> String {
+    let essence = mime_type.split(';').next().unwrap_or_default().trim();
+    match essence.to_ascii_lowercase().as_str() {
+        "audio/x-m4a" | "audio/m4a" => "audio/mp4".to_string(),
+        "audio/mp3" => "audio/mpeg".to_string(),
+        "audio/x-wav" | "audio/wave" => "audio/wav".to_string(),
+        other => other.to_string(),
+    }
+}
+
+/// Whether `data` starts with the signature of the container `mime_type` names
+fn matches_container(mime_type: &str, data: &[u8]) -> bool {
+    match mime_type {
+        "audio/ogg" => data.starts_with(b"OggS"),
+        "audio/webm" => data.starts_with(&[0x1A, 0x45, 0xDF, 0xA3]),
+        "audio/mp4" => data.len() >= 8 && &data[4..8] == b"ftyp",
+        // An ID3 tag or a bare MPEG frame sync
+        "audio/mpeg" => {
+            data.starts_with(b"ID3")
+                || (data.len() >= 2 && data[0] == 0xFF && data[1] & 0xE0 == 0xE0)
+        }
+        "audio/wav" => data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WAVE",
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ogg_note(data: &[u8]) -> VoiceNote {
+        VoiceNote {
+            media_hash: "ab".repeat(32),
+            mime_type: "audio/ogg".to_string(),
+            size_bytes: data.len() as u64,
+            duration_ms: 3_000,
+        }
+    }
+
+    #[test]
+    fn test_voice_note_limits() {
+        let data = b"OggS\0\x02rest of the page";
+        assert!(ogg_note(data).validate().is_ok());
+
+        let too_long = VoiceNote {
+            duration_ms: MAX_VOICE_NOTE_DURATION_MS + 1,
+            ..ogg_note(data)
+        };
+        assert!(too_long.validate().is_err());
+
+        let too_big = VoiceNote {
+            size_bytes: MAX_VOICE_NOTE_BYTES + 1,
+            ..ogg_note(data)
+        };
+        assert!(too_big.validate().is_err());
+
+        let video = VoiceNote {
+            mime_type: "video/mp4".to_string(),
+            ..ogg_note(data)
+        };
+        assert!(video.validate().is_err());
+
+        let bad_hash = VoiceNote {
+            media_hash: "not-a-hash".to_string(),
+            ..ogg_note(data)
+        };
+        assert!(bad_hash.validate().is_err());
+    }
+
+    #[test]
+    fn test_voice_note_audio_must_match_container_and_size() {
+        let data = b"OggS\0\x02rest of the page";
+        let note = ogg_note(data);
+        assert!(note.validate_audio(data).is_ok());
+
+        // A PNG claiming to be Ogg audio
+        let png = b"\x89PNG\r\n\x1a\n0000000000000000";
+        assert!(ogg_note(png).validate_audio(png).is_err());
+
+        // Truncated or padded data doesn't match the announced size
+        assert!(note.validate_audio(&data[..10]).is_err());
+
+        let recorded = VoiceNote::for_audio(data, "audio/ogg;codecs=opus", 3_000).unwrap();
+        assert_eq!(recorded.mime_type, "audio/ogg");
+        assert_eq!(recorded.size_bytes, data.len() as u64);
+        assert!(VoiceNote::for_audio(png, "audio/ogg", 3_000).is_err());
+    }
+
+    #[test]
+    fn test_normalize_voice_mime_type() {
+        assert_eq!(
+            normalize_voice_mime_type("audio/webm;codecs=opus"),
+            "audio/webm"
+        );
+        assert_eq!(
+            normalize_voice_mime_type("Audio/OGG; codecs=opus"),
+            "audio/ogg"
+        );
+        assert_eq!(normalize_voice_mime_type("audio/x-m4a"), "audio/mp4");
+        assert_eq!(normalize_voice_mime_type("audio/x-wav"), "audio/wav");
+    }
+}
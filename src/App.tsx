@@ -1,10 +1,17 @@
 import { useEffect, useState } from 'react';
 import { HashRouter, Routes, Route, Navigate } from 'react-router-dom';
-import { Toaster } from 'react-hot-toast';
+import toast, { Toaster } from 'react-hot-toast';
 import { useIdentityStore, useNetworkStore, useSettingsStore, useAccountsStore } from './stores';
 import { useTauriEvents } from './hooks';
+import { databaseService, networkService, postsService } from './services';
+import type { DatabaseStatus } from './services/database';
 import { MainLayout } from './components/layout';
-import { AccountSelection, CreateIdentity, UnlockIdentity } from './components/onboarding';
+import {
+  AccountSelection,
+  CreateIdentity,
+  DatabaseRecovery,
+  UnlockIdentity,
+} from './components/onboarding';
 import { ErrorBoundary } from './components/common/ErrorBoundary';
 import { HarborIcon } from './components/icons';
 import { BoardsPage, ChatPage, WallPage, FeedPage, NetworkPage, SettingsPage } from './pages';
@@ -92,15 +99,60 @@ function AppContent() {
   // UI state for account flow
   const [showCreateAccount, setShowCreateAccount] = useState(false);
   const [selectedAccount, setSelectedAccount] = useState<AccountInfo | null>(null);
+  const [dbStatus, setDbStatus] = useState<DatabaseStatus | null>(null);
 
   // Set up Tauri event listeners for real-time updates from backend
   useTauriEvents();
 
+  // A database that failed to open leaves the app in recovery mode
+  useEffect(() => {
+    databaseService
+      .getDatabaseStatus()
+      .then((status) => {
+        setDbStatus(status);
+        if (status.rebuilt) {
+          toast.error(
+            'Your data was damaged and has been repaired. Some recent items may be missing.',
+          );
+        }
+      })
+      .catch((error) => console.error('[Harbor] Failed to get database status:', error));
+  }, []);
+
   // Load accounts on mount
   useEffect(() => {
     loadAccounts();
   }, [loadAccounts]);
 
+  // Background sync pauses on metered connections; only the webview can tell
+  useEffect(() => {
+    const connection = (
+      navigator as Navigator & {
+        connection?: EventTarget & { saveData?: boolean; type?: string };
+      }
+    ).connection;
+    if (!connection) return;
+
+    const report = () => {
+      const metered = connection.saveData === true || connection.type === 'cellular';
+      postsService.setMeteredConnection(metered).catch(() => {});
+    };
+    report();
+    connection.addEventListener('change', report);
+    return () => connection.removeEventListener('change', report);
+  }, []);
+
+  // Reconnect to relays and bootstrap nodes as soon as the OS reports we're back online
+  useEffect(() => {
+    const reconnect = () => {
+      if (useNetworkStore.getState().isRunning) {
+        networkService.reconcileConnections().catch(() => {});
+      }
+    };
+    window.addEventListener('online', reconnect);
+    return () => window.removeEventListener('online', reconnect);
+  }, []);
+
   // Initialize identity after accounts are loaded
   useEffect(() => {
     if (!accountsLoading) {
@@ -147,6 +199,10 @@ function AppContent() {
     }
   }, [state.status, checkStatus, autoStartNetwork, startNetwork]);
 
+  if (dbStatus?.error) {
+    return <DatabaseRecovery status={dbStatus} />;
+  }
+
   // Loading state
   if (accountsLoading || state.status === 'loading') {
     return <LoadingScreen />;
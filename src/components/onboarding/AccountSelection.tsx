@@ -167,6 +167,7 @@ export function AccountSelection({ onSelectAccount, onCreateAccount }: AccountSe
                       className="w-12 h-12 rounded-full flex items-center justify-center text-white font-semibold flex-shrink-0"
                       style={{
                         background:
+                          account.accentColor ??
                           'linear-gradient(135deg, hsl(var(--harbor-primary)), hsl(var(--harbor-accent)))',
                       }}
                     >
@@ -0,0 +1,113 @@
+import { useState } from 'react';
+import { relaunch } from '@tauri-apps/plugin-process';
+import { Button } from '../common';
+import { HarborIcon, ShieldIcon } from '../icons';
+import { databaseService, type DatabaseStatus } from '../../services/database';
+
+interface DatabaseRecoveryProps {
+  status: DatabaseStatus;
+}
+
+/** Shown instead of the app when the database couldn't be opened at startup */
+export function DatabaseRecovery({ status }: DatabaseRecoveryProps) {
+  const [restoring, setRestoring] = useState(false);
+  const [restoreError, setRestoreError] = useState<string | null>(null);
+
+  const handleRestore = async () => {
+    setRestoring(true);
+    setRestoreError(null);
+    try {
+      await databaseService.restoreDatabaseBackup();
+      await relaunch();
+    } catch (error) {
+      setRestoreError(error instanceof Error ? error.message : String(error));
+      setRestoring(false);
+    }
+  };
+
+  return (
+    <div
+      className="min-h-screen flex items-center justify-center p-6"
+      style={{
+        background:
+          'linear-gradient(135deg, hsl(220 91% 8%) 0%, hsl(262 60% 12%) 50%, hsl(220 91% 8%) 100%)',
+      }}
+    >
+      <div className="w-full max-w-md">
+        <div
+          className="rounded-2xl p-8 relative overflow-hidden"
+          style={{
+            background: 'hsl(var(--harbor-bg-elevated))',
+            border: '1px solid hsl(var(--harbor-border-subtle))',
+            boxShadow: '0 25px 50px -12px rgba(0, 0, 0, 0.5)',
+          }}
+        >
+          <div className="text-center mb-6">
+            <div className="flex items-center justify-center gap-3 mb-6">
+              <div
+                className="w-12 h-12 rounded-xl flex items-center justify-center"
+                style={{
+                  background:
+                    'linear-gradient(135deg, hsl(var(--harbor-primary)), hsl(var(--harbor-accent)))',
+                }}
+              >
+                <HarborIcon className="w-7 h-7 text-white" />
+              </div>
+              <span
+                className="text-xl font-bold"
+                style={{ color: 'hsl(var(--harbor-text-primary))' }}
+              >
+                Harbor
+              </span>
+            </div>
+
+            <ShieldIcon
+              className="w-10 h-10 mx-auto mb-4"
+              style={{ color: 'hsl(var(--harbor-error))' }}
+            />
+            <h1
+              className="text-2xl font-bold mb-2"
+              style={{ color: 'hsl(var(--harbor-text-primary))' }}
+            >
+              Your data couldn't be opened
+            </h1>
+            <p className="text-sm" style={{ color: 'hsl(var(--harbor-text-secondary))' }}>
+              Harbor's database is damaged or unreadable, so nothing has been loaded. Your data has
+              not been changed.
+            </p>
+          </div>
+
+          <div
+            className="mb-6 p-4 rounded-xl text-xs font-mono break-all"
+            style={{
+              background: 'hsl(var(--harbor-surface-1))',
+              border: '1px solid hsl(var(--harbor-border-subtle))',
+              color: 'hsl(var(--harbor-text-tertiary))',
+            }}
+          >
+            <p>{status.error}</p>
+            <p className="mt-2">{status.path}</p>
+            {status.corruptCopy && <p className="mt-2">Copy kept at {status.corruptCopy}</p>}
+          </div>
+
+          {restoreError && (
+            <p className="mb-4 text-sm" style={{ color: 'hsl(var(--harbor-error))' }}>
+              {restoreError}
+            </p>
+          )}
+
+          {status.backupTakenAt !== null ? (
+            <Button className="w-full" onClick={handleRestore} loading={restoring}>
+              Restore backup from {new Date(status.backupTakenAt * 1000).toLocaleString()}
+            </Button>
+          ) : (
+            <p className="text-sm" style={{ color: 'hsl(var(--harbor-text-secondary))' }}>
+              No backup is available. Restore your identity from an exported backup after moving
+              the damaged file out of the way.
+            </p>
+          )}
+        </div>
+      </div>
+    </div>
+  );
+}
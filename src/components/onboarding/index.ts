@@ -1,3 +1,4 @@
 export { AccountSelection } from './AccountSelection';
 export { CreateIdentity } from './CreateIdentity';
+export { DatabaseRecovery } from './DatabaseRecovery';
 export { UnlockIdentity } from './UnlockIdentity';
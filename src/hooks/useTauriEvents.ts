@@ -1,8 +1,14 @@
 import { useEffect, useRef } from 'react';
 import { listen, UnlistenFn } from '@tauri-apps/api/event';
 import toast from 'react-hot-toast';
-import type { NetworkEvent } from '../types';
-import { useNetworkStore, useContactsStore, useMessagingStore, useFeedStore } from '../stores';
+import type { NetworkEvent, ResyncProgress, RetentionSweepSummary } from '../types';
+import {
+  useNetworkStore,
+  useContactsStore,
+  useMessagingStore,
+  useFeedStore,
+  useBoardsStore,
+} from '../stores';
 import { mediaService } from '../services/media';
 
 /**
@@ -23,6 +29,33 @@ export function useTauriEvents() {
       });
       unlistenersRef.current.push(unlistenNetwork);
 
+      // Expired messages were pruned by the background retention sweep
+      const unlistenRetention = await listen<RetentionSweepSummary>('harbor:retention', (event) => {
+        console.log(
+          `[Retention] Pruned ${event.payload.messagesDeleted} messages from ${event.payload.conversationsPruned} conversations`,
+        );
+        useMessagingStore.getState().loadConversations();
+      });
+      unlistenersRef.current.push(unlistenRetention);
+
+      // Catching up after a backup restore
+      const unlistenResync = await listen<ResyncProgress>('harbor:resync', (event) => {
+        const progress = event.payload;
+        if (progress.error) {
+          console.warn(`[Resync] ${progress.peerId}: ${progress.error}`);
+        }
+        if (progress.stage === 'done') {
+          toast.success(`Caught up: ${progress.postsFetched} posts fetched`, { id: 'resync' });
+          useFeedStore.getState().loadFeed();
+          useBoardsStore.getState().loadCommunities();
+          return;
+        }
+        toast.loading(`Catching up: ${progress.completed}/${progress.total} ${progress.stage}`, {
+          id: 'resync',
+        });
+      });
+      unlistenersRef.current.push(unlistenResync);
+
       // Future: Listen to message events
       // const unlistenMessage = await listen<MessageEvent>(
       //   "harbor:message",
@@ -33,8 +66,27 @@ export function useTauriEvents() {
 
     function handleNetworkEvent(event: NetworkEvent) {
       switch (event.type) {
+        case 'dial_started':
+          console.log(
+            `[Network] Dialing ${event.peer_id} at ${event.address ?? 'known addresses'} (attempt ${event.attempt_id})`,
+          );
+          useNetworkStore.getState().dialStarted({
+            attemptId: event.attempt_id,
+            peerId: event.peer_id,
+            address: event.address,
+          });
+          break;
+
+        case 'dial_failed':
+          console.warn(
+            `[Network] Dial attempt ${event.attempt_id} to ${event.peer_id} failed: ${event.error}`,
+          );
+          useNetworkStore.getState().dialFailed(event.attempt_id);
+          break;
+
         case 'peer_connected':
           console.log(`[Network] Peer connected: ${event.peer_id}`);
+          useNetworkStore.getState().clearDialAttempts(event.peer_id);
           // Refresh the full peer list to get updated info
           refreshPeers();
           refreshStats();
@@ -45,10 +97,16 @@ export function useTauriEvents() {
 
         case 'peer_disconnected':
           console.log(`[Network] Peer disconnected: ${event.peer_id}`);
+          useNetworkStore.getState().removeNearbyPeer(event.peer_id);
           refreshPeers();
           refreshStats();
           break;
 
+        case 'peer_evicted':
+          // A peer_disconnected event follows, which refreshes the peer list
+          console.log(`[Network] Evicted idle peer: ${event.peer_id}`);
+          break;
+
         case 'peer_discovered':
           console.log(`[Network] Peer discovered: ${event.peer_id}`);
           refreshPeers();
@@ -78,6 +136,32 @@ export function useTauriEvents() {
           refreshContacts();
           break;
 
+        case 'message_send_result':
+          if (event.delivered) {
+            console.log(`[Network] Message ${event.correlation_id} delivered to ${event.peer_id}`);
+          } else {
+            console.warn(
+              `[Network] Message ${event.correlation_id} to ${event.peer_id} failed: ${event.error}`,
+            );
+          }
+          break;
+
+        case 'message_delivery_failed':
+          console.warn(
+            `[Network] Gave up on message ${event.message_id} to ${event.peer_id} after ${event.attempts} attempts: ${event.error}`,
+          );
+          useMessagingStore.getState().updateMessageStatus(event.message_id, 'failed');
+          toast.error('A message could not be delivered');
+          break;
+
+        case 'messages_seen': {
+          const { updateMessageStatus } = useMessagingStore.getState();
+          for (const messageId of event.message_ids) {
+            updateMessageStatus(messageId, 'read', undefined, event.read_at);
+          }
+          break;
+        }
+
         case 'listening_on':
           console.log(`[Network] Listening on: ${event.address}`);
           break;
@@ -92,10 +176,67 @@ export function useTauriEvents() {
 
         case 'contact_added':
           console.log(`[Network] Contact added: ${event.display_name} (${event.peer_id})`);
+          useNetworkStore.getState().removeNearbyPeer(event.peer_id);
           refreshContacts();
           toast.success(`Added ${event.display_name} to contacts!`);
           break;
 
+        case 'identity_conflict_detected':
+          console.error(`[Network] Another device at ${event.address} is using this identity`);
+          toast.error(
+            'Another device is using this identity. Messages may go to it instead of you. ' +
+              'Stop Harbor on the other device or give it a new identity.',
+            { duration: Infinity },
+          );
+          break;
+
+        case 'contact_updated':
+          console.log(
+            `[Network] Contact updated: ${event.display_name} (${event.peer_id}), changed: ${event.changed_fields.join(', ')}`,
+          );
+          // Refresh anything that displays contact names
+          refreshContacts();
+          useMessagingStore.getState().loadConversations();
+          useFeedStore.getState().loadFeed();
+          break;
+
+        case 'contact_renamed':
+          if (event.verified) {
+            console.log(
+              `[Network] Contact renamed: ${event.previous_name} -> ${event.display_name} (${event.peer_id})`,
+            );
+          } else {
+            console.warn(
+              `[Network] Unverified contact renamed: ${event.previous_name} -> ${event.display_name} (${event.peer_id})`,
+            );
+            toast(`${event.previous_name} is now calling themselves "${event.display_name}"`, {
+              icon: '⚠️',
+            });
+          }
+          break;
+
+        case 'nearby_peer_identified':
+          console.log(`[Network] Nearby peer: ${event.display_name} (${event.peer_id})`);
+          useNetworkStore.getState().nearbyPeerIdentified({
+            peerId: event.peer_id,
+            displayName: event.display_name,
+            avatarHash: event.avatar_hash,
+            bio: event.bio,
+          });
+          break;
+
+        case 'relay_health_changed':
+          if (event.health === 'degraded') {
+            console.warn(`[Network] Relay ${event.relay_peer_id} stopped answering pings`);
+            toast('Relay not responding - you may become unreachable', { icon: '⚠️' });
+          } else {
+            console.log(
+              `[Network] Relay ${event.relay_peer_id} responding again (${event.rtt_ms ?? '?'} ms)`,
+            );
+          }
+          useNetworkStore.getState().refreshStats();
+          break;
+
         case 'nat_status_changed':
           console.log(`[Network] NAT status changed: ${event.status}`);
           // Update NAT status in store
@@ -125,6 +266,8 @@ export function useTauriEvents() {
         case 'hole_punch_succeeded':
           console.log(`[Network] Hole punch succeeded with: ${event.peer_id}`);
           toast.success('Direct connection established!');
+          // The peer's connection type changed from relayed to hole punched
+          refreshPeers();
           break;
 
         case 'content_manifest_received':
@@ -143,6 +286,24 @@ export function useTauriEvents() {
           console.warn(`[Network] Content sync error from ${event.peer_id}: ${event.error}`);
           break;
 
+        case 'likes_updated':
+          // Coalesced by the backend; views re-query get_posts_likes_batch once
+          console.log(`[Network] Likes updated for ${event.post_ids.length} posts`);
+          break;
+
+        case 'activity_received':
+          // Already stored; the activity feed re-queries get_activity
+          console.log(
+            `[Network] Activity: ${event.kind} on ${event.target_id} by ${event.actor_peer_id}`,
+          );
+          break;
+
+        case 'request_failed':
+          console.warn(
+            `[Network] ${event.protocol} request to ${event.peer_id} failed: ${event.reason}`,
+          );
+          break;
+
         case 'wall_post_synced':
           console.log(`[Network] Wall post synced to relay: ${event.post_id}`);
           break;
@@ -161,9 +322,60 @@ export function useTauriEvents() {
           useFeedStore.getState().loadFeed();
           break;
 
+        case 'call_data_received':
+          console.log(
+            `[Network] Call data from ${event.peer_id} on call ${event.call_id} (${event.payload.length} bytes)`,
+          );
+          break;
+
         case 'wall_post_deleted_on_relay':
           console.log(`[Network] Wall post deleted on relay: ${event.post_id}`);
           break;
+
+        case 'new_board_posts': {
+          console.log(`[Network] ${event.count} new posts on subscribed board ${event.board_id}`);
+          const boardsState = useBoardsStore.getState();
+          const board = boardsState.boards.find(
+            (b) => b.boardId === event.board_id && b.relayPeerId === event.relay_peer_id,
+          );
+          if (
+            boardsState.activeBoard?.boardId === event.board_id &&
+            boardsState.activeBoard?.relayPeerId === event.relay_peer_id
+          ) {
+            boardsState.loadBoardPosts();
+          }
+          const plural = event.count === 1 ? 'post' : 'posts';
+          toast(`${event.count} new ${plural} in ${board ? board.name : 'a subscribed board'}`, {
+            icon: '📌',
+          });
+          break;
+        }
+
+        case 'board_post_reactions_updated':
+          useBoardsStore.getState().applyReactions(
+            event.relay_peer_id,
+            event.post_id,
+            event.reaction_count,
+            event.my_reaction,
+          );
+          break;
+
+        case 'board_created': {
+          console.log(`[Network] Board ${event.name} created on ${event.relay_peer_id}`);
+          const boardsState = useBoardsStore.getState();
+          if (boardsState.activeCommunity?.relayPeerId === event.relay_peer_id) {
+            boardsState.loadBoards();
+          }
+          toast.success(`Board "${event.name}" created`);
+          break;
+        }
+
+        case 'community_invite_created':
+          navigator.clipboard
+            .writeText(event.token)
+            .then(() => toast.success('Invite copied to clipboard'))
+            .catch(() => toast.error('Invite created but could not be copied to the clipboard'));
+          break;
       }
     }
 
@@ -2,6 +2,7 @@ import { useState, useEffect, useRef } from 'react';
 import toast from 'react-hot-toast';
 import { useBoardsStore, useIdentityStore } from '../stores';
 import type { CommunityInfo, BoardInfo, BoardPost } from '../types/boards';
+import { describeRelayTimestamp } from '../utils/formatting';
 
 function formatTimeAgo(unixSeconds: number): string {
   const now = Date.now();
@@ -31,11 +32,14 @@ function PostCard({
   post,
   isOwnPost,
   onDelete,
+  onToggleReaction,
 }: {
   post: BoardPost;
   isOwnPost: boolean;
   onDelete: (postId: string) => void;
+  onToggleReaction: (postId: string) => void;
 }) {
+  const relayTimestamp = describeRelayTimestamp(post.createdAt, post.relayObservedAt);
   return (
     <div
       className="p-4 rounded-xl"
@@ -60,8 +64,18 @@ function PostCard({
             <p className="text-sm font-medium" style={{ color: 'hsl(var(--harbor-text-primary))' }}>
               {post.authorDisplayName || shortPeerId(post.authorPeerId)}
             </p>
-            <p className="text-xs" style={{ color: 'hsl(var(--harbor-text-tertiary))' }}>
+            <p
+              className="text-xs"
+              style={{ color: 'hsl(var(--harbor-text-tertiary))' }}
+              title={relayTimestamp?.label}
+            >
               {formatTimeAgo(post.createdAt)}
+              {relayTimestamp?.discrepant && (
+                <span style={{ color: 'hsl(var(--harbor-warning))' }}>
+                  {' '}
+                  · time disputed by relay
+                </span>
+              )}
             </p>
           </div>
         </div>
@@ -95,6 +109,32 @@ function PostCard({
       >
         {post.contentText}
       </p>
+      {/* Reactions */}
+      <button
+        onClick={() => onToggleReaction(post.postId)}
+        className="mt-3 flex items-center gap-1.5 transition-colors duration-200"
+        title={post.myReaction ? 'Remove like' : 'Like'}
+        style={{
+          color: post.myReaction
+            ? 'hsl(var(--harbor-error))'
+            : 'hsl(var(--harbor-text-tertiary))',
+        }}
+      >
+        <svg
+          className="w-4 h-4"
+          fill={post.myReaction ? 'currentColor' : 'none'}
+          viewBox="0 0 24 24"
+          stroke="currentColor"
+        >
+          <path
+            strokeLinecap="round"
+            strokeLinejoin="round"
+            strokeWidth={1.5}
+            d="M4.318 6.318a4.5 4.5 0 000 6.364L12 20.364l7.682-7.682a4.5 4.5 0 00-6.364-6.364L12 7.636l-1.318-1.318a4.5 4.5 0 00-6.364 0z"
+          />
+        </svg>
+        <span className="text-xs">{post.reactionCount}</span>
+      </button>
     </div>
   );
 }
@@ -307,7 +347,7 @@ function JoinCommunityForm({ onJoin }: { onJoin: (address: string) => Promise<vo
         type="text"
         value={address}
         onChange={(e) => setAddress(e.target.value)}
-        placeholder="/ip4/.../p2p/12D3Koo..."
+        placeholder="/ip4/.../p2p/12D3Koo... or harbor://invite/..."
         className="flex-1 px-3 py-2 rounded-lg text-sm outline-none"
         style={{
           background: 'hsl(var(--harbor-surface-1))',
@@ -347,11 +387,13 @@ export function BoardsPage() {
     error,
     loadCommunities,
     joinCommunity,
+    createInvite,
     leaveCommunity,
     selectCommunity,
     selectBoard,
     submitPost,
     deletePost,
+    toggleReaction,
     refreshBoard,
   } = useBoardsStore();
 
@@ -368,11 +410,28 @@ export function BoardsPage() {
     }
   };
 
+  const handleToggleReaction = async (postId: string) => {
+    try {
+      await toggleReaction(postId);
+    } catch {
+      toast.error('Failed to react to post');
+    }
+  };
+
   const handleSubmitPost = async (text: string) => {
     await submitPost(text);
     toast.success('Post submitted');
   };
 
+  const handleCreateInvite = async () => {
+    try {
+      // Member invites last a week; the relay may shorten that
+      await createInvite(7 * 24 * 60 * 60);
+    } catch (error) {
+      toast.error(`Failed to create invite: ${error}`);
+    }
+  };
+
   const handleLeaveCommunity = async (relayPeerId: string) => {
     try {
       await leaveCommunity(relayPeerId);
@@ -428,8 +487,8 @@ export function BoardsPage() {
               Join a Community
             </h2>
             <p className="text-sm mb-6" style={{ color: 'hsl(var(--harbor-text-secondary))' }}>
-              Communities are hosted on relay servers. Enter a relay address to join and start
-              browsing boards.
+              Communities are hosted on relay servers. Paste an invite or a relay address to join
+              and start browsing boards.
             </p>
             <div className="max-w-lg mx-auto">
               <JoinCommunityForm onJoin={joinCommunity} />
@@ -455,21 +514,36 @@ export function BoardsPage() {
                 : 'Select a community'}
             </p>
           </div>
-          {activeCommunity && activeBoard && (
-            <button
-              onClick={refreshBoard}
-              disabled={isLoading}
-              className="px-3 py-1.5 rounded-lg text-sm font-medium transition-all"
-              style={{
-                background: 'hsl(var(--harbor-surface-1))',
-                color: 'hsl(var(--harbor-text-primary))',
-                border: '1px solid hsl(var(--harbor-border-subtle))',
-                opacity: isLoading ? 0.5 : 1,
-              }}
-            >
-              {isLoading ? 'Syncing...' : 'Refresh'}
-            </button>
-          )}
+          <div className="flex items-center gap-2">
+            {activeCommunity && (
+              <button
+                onClick={handleCreateInvite}
+                className="px-3 py-1.5 rounded-lg text-sm font-medium transition-all"
+                style={{
+                  background: 'hsl(var(--harbor-surface-1))',
+                  color: 'hsl(var(--harbor-text-primary))',
+                  border: '1px solid hsl(var(--harbor-border-subtle))',
+                }}
+              >
+                Invite
+              </button>
+            )}
+            {activeCommunity && activeBoard && (
+              <button
+                onClick={refreshBoard}
+                disabled={isLoading}
+                className="px-3 py-1.5 rounded-lg text-sm font-medium transition-all"
+                style={{
+                  background: 'hsl(var(--harbor-surface-1))',
+                  color: 'hsl(var(--harbor-text-primary))',
+                  border: '1px solid hsl(var(--harbor-border-subtle))',
+                  opacity: isLoading ? 0.5 : 1,
+                }}
+              >
+                {isLoading ? 'Syncing...' : 'Refresh'}
+              </button>
+            )}
+          </div>
         </div>
 
         {/* Join community form */}
@@ -549,6 +623,7 @@ export function BoardsPage() {
                       post={post}
                       isOwnPost={identity?.peerId === post.authorPeerId}
                       onDelete={handleDeletePost}
+                      onToggleReaction={handleToggleReaction}
                     />
                   ))}
                 </div>
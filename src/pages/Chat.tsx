@@ -16,9 +16,11 @@ import {
   PencilIcon,
   CheckIcon,
 } from '../components/icons';
-import { useContactsStore, useMessagingStore } from '../stores';
+import { useContactsStore, useIdentityStore, useMessagingStore } from '../stores';
+import { messagingService } from '../services/messaging';
 import { getInitials, getContactColor, formatRelativeTime } from '../utils/formatting';
 import { EmojiPicker } from '../components/common/EmojiPicker';
+import type { Conversation } from '../types';
 
 const log = createLogger('Chat');
 
@@ -222,16 +224,20 @@ function ConversationMenu({
   isOpen,
   onClose,
   onArchive,
+  onTogglePin,
   onClearHistory,
   onDelete,
   isArchived,
+  isPinned,
 }: {
   isOpen: boolean;
   onClose: () => void;
   onArchive: () => void;
+  onTogglePin: () => void;
   onClearHistory: () => void;
   onDelete: () => void;
   isArchived: boolean;
+  isPinned: boolean;
 }) {
   const menuRef = useRef<HTMLDivElement>(null);
 
@@ -276,6 +282,24 @@ function ConversationMenu({
         </svg>
         {isArchived ? 'Unarchive' : 'Archive'}
       </button>
+      <button
+        onClick={() => {
+          onTogglePin();
+          onClose();
+        }}
+        className="w-full px-4 py-3 text-left text-sm flex items-center gap-3 transition-colors hover:bg-white/5"
+        style={{ color: 'hsl(var(--harbor-text-primary))' }}
+      >
+        <svg className="w-4 h-4" fill="none" viewBox="0 0 24 24" stroke="currentColor">
+          <path
+            strokeLinecap="round"
+            strokeLinejoin="round"
+            strokeWidth={1.5}
+            d="M5 5a2 2 0 012-2h10a2 2 0 012 2v16l-7-3.5L5 21V5z"
+          />
+        </svg>
+        {isPinned ? 'Unpin' : 'Pin'}
+      </button>
       <button
         onClick={() => {
           onClearHistory();
@@ -618,14 +642,40 @@ interface UnifiedConversation {
   lastMessage: string;
   timestamp: Date;
   unread: number;
+  pinned: boolean;
   isReal: boolean; // true = real contact, false = mock
 }
 
+/** The "Notes to self" entry: messages sent to our own peer ID, kept on this device */
+function noteToSelfConversation(
+  ownPeerId: string,
+  conversations: Conversation[],
+): UnifiedConversation {
+  const notes = conversations.find((c) => c.isNoteToSelf);
+  return {
+    id: `real-${ownPeerId}`,
+    peerId: ownPeerId,
+    name: 'Notes to self',
+    online: true,
+    avatarGradient: getContactColor(ownPeerId),
+    lastMessage: notes?.lastMessagePreview ?? 'Jot something down',
+    timestamp: notes ? new Date(notes.lastMessageAt * 1000) : new Date(0),
+    unread: 0,
+    pinned: notes?.pinned ?? false,
+    isReal: true,
+  };
+}
+
 export function ChatPage() {
   const navigate = useNavigate();
 
   // Real contacts and messaging
   const { contacts, loadContacts } = useContactsStore();
+  const identityState = useIdentityStore((s) => s.state);
+  const ownPeerId =
+    identityState.status === 'unlocked' || identityState.status === 'locked'
+      ? identityState.identity.peerId
+      : '';
   const {
     conversations: realConversations,
     messages: realMessages,
@@ -729,26 +779,39 @@ export function ChatPage() {
     return () => document.removeEventListener('keydown', handleGlobalKeyDown);
   }, [selectedConversation, showMessageSearch]);
 
-  // Build conversation list from real contacts
+  // Build conversation list from real contacts plus notes to self,
+  // pinned first and then most recent
   const unifiedConversations = useMemo<UnifiedConversation[]>(
     () =>
-      contacts.map((contact): UnifiedConversation => {
-        const realConv = realConversations.find((c) => c.peerId === contact.peerId);
-        return {
-          id: `real-${contact.peerId}`,
-          peerId: contact.peerId,
-          name: contact.displayName,
-          online: true, // Assume online for now - would need presence tracking
-          avatarGradient: getContactColor(contact.peerId),
-          lastMessage: realConv ? 'Tap to view messages' : 'Start a conversation',
-          timestamp: realConv
-            ? new Date(realConv.lastMessageAt * 1000)
-            : new Date(contact.addedAt * 1000),
-          unread: realConv?.unreadCount || 0,
-          isReal: true,
-        };
-      }),
-    [contacts, realConversations],
+      [
+        ...(ownPeerId ? [noteToSelfConversation(ownPeerId, realConversations)] : []),
+        ...contacts.map((contact): UnifiedConversation => {
+          const realConv = realConversations.find((c) => c.peerId === contact.peerId);
+          const preview = realConv?.lastMessagePreview ?? '';
+          return {
+            id: `real-${contact.peerId}`,
+            peerId: contact.peerId,
+            name: contact.displayName,
+            online: true, // Assume online for now - would need presence tracking
+            avatarGradient: getContactColor(contact.peerId),
+            lastMessage: realConv
+              ? realConv.lastMessageIsOutgoing
+                ? `You: ${preview}`
+                : preview
+              : 'Start a conversation',
+            timestamp: realConv
+              ? new Date(realConv.lastMessageAt * 1000)
+              : new Date(contact.addedAt * 1000),
+            unread: realConv?.unreadCount || 0,
+            pinned: realConv?.pinned ?? false,
+            isReal: true,
+          };
+        }),
+      ].sort(
+        (a, b) =>
+          Number(b.pinned) - Number(a.pinned) || b.timestamp.getTime() - a.timestamp.getTime(),
+      ),
+    [contacts, realConversations, ownPeerId],
   );
 
   // Separate active and archived conversations
@@ -1026,6 +1089,15 @@ export function ChatPage() {
     }
   };
 
+  const handleTogglePin = async (conversation: UnifiedConversation) => {
+    try {
+      await messagingService.setConversationPinned(conversation.peerId, !conversation.pinned);
+      await loadConversations();
+    } catch {
+      toast.error('Failed to update pinned conversations');
+    }
+  };
+
   const handleClearHistory = (conversation: UnifiedConversation) => {
     setConfirmDialog({
       isOpen: true,
@@ -1303,7 +1375,9 @@ export function ChatPage() {
                       isOpen={openMenuId === conversation.id}
                       onClose={() => setOpenMenuId(null)}
                       isArchived={isArchived(conversation.peerId)}
+                      isPinned={conversation.pinned}
                       onArchive={() => handleArchive(conversation)}
+                      onTogglePin={() => handleTogglePin(conversation)}
                       onClearHistory={() => handleClearHistory(conversation)}
                       onDelete={() => handleDeleteConversation(conversation)}
                     />
@@ -1447,7 +1521,9 @@ export function ChatPage() {
               isOpen={headerMenuOpen}
               onClose={() => setHeaderMenuOpen(false)}
               isArchived={isArchived(selectedConv!.peerId)}
+              isPinned={selectedConv!.pinned}
               onArchive={() => handleArchive(selectedConv!)}
+              onTogglePin={() => handleTogglePin(selectedConv!)}
               onClearHistory={() => handleClearHistory(selectedConv!)}
               onDelete={() => handleDeleteConversation(selectedConv!)}
             />
@@ -10,7 +10,12 @@ import type { SharedFrom, Comment } from '../stores';
 import { useIdentityStore } from '../stores';
 
 const log = createLogger('Feed');
-import { getInitials, getContactColor, formatDate } from '../utils/formatting';
+import {
+  getInitials,
+  getContactColor,
+  formatDate,
+  describeRelayTimestamp,
+} from '../utils/formatting';
 
 /** Relay sync polling interval in milliseconds (30 seconds) */
 const RELAY_SYNC_INTERVAL_MS = 30_000;
@@ -473,6 +478,7 @@ interface UnifiedPost {
   };
   isReal: boolean;
   media?: { type: 'image' | 'video'; url: string; name?: string }[];
+  relayTimestamp?: { label: string; discrepant: boolean } | null;
 }
 
 type FeedTab = 'all' | 'saved';
@@ -576,6 +582,7 @@ const allPosts: UnifiedPost[] = useMemo(() => {
         },
         isReal: true,
         media: postMediaMap[item.postId],
+        relayTimestamp: describeRelayTimestamp(item.createdAt, item.relayObservedAt),
       };
     })
     .sort((a, b) => b.timestamp.getTime() - a.timestamp.getTime());
@@ -870,8 +877,15 @@ return (
                       <p
                         className="text-xs"
                         style={{ color: 'hsl(var(--harbor-text-tertiary))' }}
+                        title={post.relayTimestamp?.label}
                       >
                         {formatDate(post.timestamp)}
+                        {post.relayTimestamp?.discrepant && (
+                          <span style={{ color: 'hsl(var(--harbor-warning))' }}>
+                            {' '}
+                            · time disputed by relay
+                          </span>
+                        )}
                       </p>
                     </div>
                   </div>
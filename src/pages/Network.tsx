@@ -20,6 +20,7 @@ import {
   RELAY_CLOUDFORMATION_TEMPLATE,
   COMMUNITY_RELAY_CLOUDFORMATION_TEMPLATE,
 } from '../constants/cloudformation-template';
+import type { ConnectionType, PeerInfo } from '../types';
 
 // Adjectives and animals for generating human-friendly peer names
 const ADJECTIVES = [
@@ -122,6 +123,22 @@ function getPeerColor(peerId: string): string {
   return colors[Math.abs(hash) % colors.length];
 }
 
+const CONNECTION_TYPE_LABELS: Record<ConnectionType, string> = {
+  direct: '🔒 Direct',
+  holepunched: '🔒 Direct (hole punched)',
+  relayed: '↻ Relayed',
+};
+
+/** Short description of the software a peer reported over Identify */
+function describePeerSoftware(peer: PeerInfo): string | null {
+  if (peer.appVersion) {
+    return peer.platform
+      ? `Harbor ${peer.appVersion} · ${peer.platform}`
+      : `Harbor ${peer.appVersion}`;
+  }
+  return peer.agentVersion;
+}
+
 // Inline toggle component
 function Toggle({ enabled, onChange }: { enabled: boolean; onChange: (value: boolean) => void }) {
   return (
@@ -1178,6 +1195,9 @@ export function NetworkPage() {
                           peerId={peer.peerId}
                           displayName={displayName}
                           isConnected
+                          connectionType={peer.connectionType}
+                          relayPeerId={peer.relayPeerId}
+                          software={describePeerSoftware(peer)}
                           actionLabel={knownContact ? 'Message' : 'Add Contact'}
                           actionStyle="success"
                           onAction={async () => {
@@ -1323,6 +1343,9 @@ function PeerRow({
   peerId,
   displayName,
   isConnected,
+  connectionType,
+  relayPeerId,
+  software,
   actionLabel,
   actionStyle,
   onAction,
@@ -1330,6 +1353,10 @@ function PeerRow({
   peerId: string;
   displayName?: string;
   isConnected?: boolean;
+  connectionType?: ConnectionType;
+  relayPeerId?: string | null;
+  /** Software the peer reported, e.g. `Harbor 1.3.0 · macos` */
+  software?: string | null;
   actionLabel: string;
   actionStyle: 'primary' | 'success';
   onAction: () => Promise<void>;
@@ -1364,6 +1391,7 @@ function PeerRow({
           title={peerId}
         >
           {peerId.slice(0, 12)}...{peerId.slice(-6)}
+          {software && <span className="font-sans"> · {software}</span>}
         </p>
       </div>
 
@@ -1373,8 +1401,12 @@ function PeerRow({
             className="w-2 h-2 rounded-full animate-pulse"
             style={{ background: 'hsl(var(--harbor-success))' }}
           />
-          <span className="text-xs" style={{ color: 'hsl(var(--harbor-text-tertiary))' }}>
-            Connected
+          <span
+            className="text-xs"
+            style={{ color: 'hsl(var(--harbor-text-tertiary))' }}
+            title={relayPeerId ? `Relayed via ${relayPeerId}` : undefined}
+          >
+            {connectionType ? CONNECTION_TYPE_LABELS[connectionType] : 'Connected'}
           </span>
         </div>
       )}
@@ -126,6 +126,8 @@ export function WallPage() {
     createPost,
     updatePost,
     deletePost,
+    pinPost,
+    unpinPost,
     likePost,
     editingPostId,
     setEditingPost,
@@ -284,6 +286,17 @@ export function WallPage() {
     }
   };
 
+  const handleTogglePin = async (postId: string, pinned: boolean) => {
+    try {
+      await (pinned ? unpinPost(postId) : pinPost(postId));
+      setShowPostMenu(null);
+      toast.success(pinned ? 'Post unpinned' : 'Post pinned to top');
+    } catch (err) {
+      console.error('Failed to update pin:', err);
+      toast.error(pinned ? 'Failed to unpin post' : 'Failed to pin post');
+    }
+  };
+
   const handleStartEdit = (postId: string, content: string) => {
     setEditContent(content);
     setEditingPost(postId);
@@ -751,6 +764,7 @@ export function WallPage() {
                       </div>
                       <p className="text-xs" style={{ color: 'hsl(var(--harbor-text-tertiary))' }}>
                         {formatDate(post.timestamp)}
+                        {post.pinned && ' · Pinned'}
                       </p>
                     </div>
                   </div>
@@ -783,6 +797,13 @@ export function WallPage() {
                         >
                           Edit post
                         </button>
+                        <button
+                          onClick={() => handleTogglePin(post.postId, post.pinned ?? false)}
+                          className="w-full px-4 py-2.5 text-left text-sm transition-colors hover:bg-white/5"
+                          style={{ color: 'hsl(var(--harbor-text-primary))' }}
+                        >
+                          {post.pinned ? 'Unpin post' : 'Pin to top'}
+                        </button>
                         <button
                           onClick={() => handleDeletePost(post.postId)}
                           className="w-full px-4 py-2.5 text-left text-sm transition-colors hover:bg-white/5"
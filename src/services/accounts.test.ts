@@ -95,13 +95,20 @@ describe('accountsService', () => {
     it('should invoke update_account_metadata with all params', async () => {
       vi.mocked(invoke).mockResolvedValue({});
 
-      await accountsService.updateAccountMetadata('acct-1', 'New Name', 'New bio', 'hash123');
+      await accountsService.updateAccountMetadata(
+        'acct-1',
+        'New Name',
+        'New bio',
+        'hash123',
+        '#f97316',
+      );
 
       expect(invoke).toHaveBeenCalledWith('update_account_metadata', {
         accountId: 'acct-1',
         displayName: 'New Name',
         bio: 'New bio',
         avatarHash: 'hash123',
+        accentColor: '#f97316',
       });
     });
 
@@ -115,6 +122,7 @@ describe('accountsService', () => {
         displayName: undefined,
         bio: undefined,
         avatarHash: undefined,
+        accentColor: undefined,
       });
     });
   });
@@ -39,12 +39,14 @@ export const accountsService = {
     displayName?: string,
     bio?: string | null,
     avatarHash?: string | null,
+    accentColor?: string | null,
   ): Promise<AccountInfo> {
     return invoke<AccountInfo>('update_account_metadata', {
       accountId,
       displayName,
       bio,
       avatarHash,
+      accentColor,
     });
   },
 };
@@ -0,0 +1,50 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { activityService } from './activity';
+import { invoke } from '@tauri-apps/api/core';
+
+describe('activityService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('getActivity', () => {
+    it('should invoke get_activity with paging args', async () => {
+      const mockItems = [
+        {
+          id: 3,
+          kind: 'view',
+          actorPeerId: 'peer-a',
+          actorDisplayName: 'Alice',
+          targetId: 'post-1',
+          createdAt: 1700000200,
+          readAt: null,
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockItems);
+
+      const result = await activityService.getActivity(20, 1700000300);
+
+      expect(invoke).toHaveBeenCalledWith('get_activity', { limit: 20, before: 1700000300 });
+      expect(result).toEqual(mockItems);
+    });
+  });
+
+  describe('markActivityRead', () => {
+    it('should invoke mark_activity_read with ids', async () => {
+      vi.mocked(invoke).mockResolvedValue(2);
+
+      const result = await activityService.markActivityRead([1, 2]);
+
+      expect(invoke).toHaveBeenCalledWith('mark_activity_read', { ids: [1, 2] });
+      expect(result).toBe(2);
+    });
+
+    it('should mark everything read when no ids are given', async () => {
+      vi.mocked(invoke).mockResolvedValue(5);
+
+      await activityService.markActivityRead();
+
+      expect(invoke).toHaveBeenCalledWith('mark_activity_read', { ids: undefined });
+    });
+  });
+});
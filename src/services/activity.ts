@@ -0,0 +1,32 @@
+import { invoke } from '@tauri-apps/api/core';
+import type { ActivityKind } from '../types';
+
+/** An entry in the activity feed */
+export interface ActivityItem {
+  id: number;
+  kind: ActivityKind;
+  actorPeerId: string;
+  actorDisplayName: string | null;
+  /** Post or comment the activity is about */
+  targetId: string;
+  createdAt: number;
+  readAt: number | null;
+}
+
+/** Activity service - wraps Tauri commands for the activity feed */
+export const activityService = {
+  /** Get activity on our content, newest first; pass the oldest createdAt to page back */
+  async getActivity(limit?: number, before?: number): Promise<ActivityItem[]> {
+    return invoke<ActivityItem[]>('get_activity', { limit, before });
+  },
+
+  /** Mark entries as read, or all of them when ids is omitted */
+  async markActivityRead(ids?: number[]): Promise<number> {
+    return invoke<number>('mark_activity_read', { ids });
+  },
+
+  /** Get the number of unread activity entries */
+  async getUnreadActivityCount(): Promise<number> {
+    return invoke<number>('get_unread_activity_count');
+  },
+};
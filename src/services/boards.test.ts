@@ -79,6 +79,55 @@ describe('boardsService', () => {
     });
   });
 
+  describe('joinCommunityFromInvite', () => {
+    it('should invoke join_community_from_invite', async () => {
+      const invite = {
+        relayPeerId: 'relay-1',
+        relayAddress: '/ip4/203.0.113.7/tcp/4001/p2p/relay-1',
+        communityName: 'Harbor Community',
+        issuedAt: 1700000000,
+        expiresAt: null,
+        maxUses: 10,
+      };
+      vi.mocked(invoke).mockResolvedValue(invite);
+
+      const result = await boardsService.joinCommunityFromInvite('harbor://invite/abc');
+
+      expect(invoke).toHaveBeenCalledWith('join_community_from_invite', {
+        token: 'harbor://invite/abc',
+      });
+      expect(result).toEqual(invite);
+    });
+  });
+
+  describe('createCommunityInvite', () => {
+    it('should invoke create_community_invite', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await boardsService.createCommunityInvite('relay-1', 86400, 5);
+
+      expect(invoke).toHaveBeenCalledWith('create_community_invite', {
+        relayPeerId: 'relay-1',
+        expiresInSecs: 86400,
+        maxUses: 5,
+      });
+    });
+  });
+
+  describe('createBoard', () => {
+    it('should invoke create_board', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await boardsService.createBoard('relay-1', 'Announcements', 'News and updates');
+
+      expect(invoke).toHaveBeenCalledWith('create_board', {
+        relayPeerId: 'relay-1',
+        name: 'Announcements',
+        description: 'News and updates',
+      });
+    });
+  });
+
   describe('deleteBoardPost', () => {
     it('should invoke delete_board_post', async () => {
       vi.mocked(invoke).mockResolvedValue(undefined);
@@ -92,6 +141,34 @@ describe('boardsService', () => {
     });
   });
 
+  describe('reactToBoardPost', () => {
+    it('should invoke react_to_board_post', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await boardsService.reactToBoardPost('relay-1', 'bp-1', false, 'like');
+
+      expect(invoke).toHaveBeenCalledWith('react_to_board_post', {
+        relayPeerId: 'relay-1',
+        postId: 'bp-1',
+        reactionType: 'like',
+        remove: false,
+      });
+    });
+
+    it('should withdraw a reaction', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await boardsService.reactToBoardPost('relay-1', 'bp-1', true);
+
+      expect(invoke).toHaveBeenCalledWith('react_to_board_post', {
+        relayPeerId: 'relay-1',
+        postId: 'bp-1',
+        reactionType: undefined,
+        remove: true,
+      });
+    });
+  });
+
   describe('syncBoard', () => {
     it('should invoke sync_board', async () => {
       vi.mocked(invoke).mockResolvedValue(undefined);
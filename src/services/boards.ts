@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { CommunityInfo, BoardInfo, BoardPost } from '../types/boards';
+import type { CommunityInfo, CommunityInvite, BoardInfo, BoardPost } from '../types/boards';
 
 /** Boards service - wraps Tauri commands for community board functionality */
 export const boardsService = {
@@ -13,6 +13,23 @@ export const boardsService = {
     return invoke<void>('join_community', { relayAddress });
   },
 
+  /** Join a community from a signed invite (`harbor://invite/...`) */
+  async joinCommunityFromInvite(token: string): Promise<CommunityInvite> {
+    return invoke<CommunityInvite>('join_community_from_invite', { token });
+  },
+
+  /**
+   * Ask a community relay for an invite to share. The invite arrives as a
+   * community_invite_created event.
+   */
+  async createCommunityInvite(
+    relayPeerId: string,
+    expiresInSecs?: number,
+    maxUses?: number,
+  ): Promise<void> {
+    return invoke<void>('create_community_invite', { relayPeerId, expiresInSecs, maxUses });
+  },
+
   /** Leave a community */
   async leaveCommunity(relayPeerId: string): Promise<void> {
     return invoke<void>('leave_community', { relayPeerId });
@@ -47,13 +64,41 @@ export const boardsService = {
     });
   },
 
+  /** Ask a relay to create a new board (result arrives as a board_created event) */
+  async createBoard(relayPeerId: string, name: string, description?: string): Promise<void> {
+    return invoke<void>('create_board', { relayPeerId, name, description });
+  },
+
   /** Delete a board post */
   async deleteBoardPost(relayPeerId: string, postId: string): Promise<void> {
     return invoke<void>('delete_board_post', { relayPeerId, postId });
   },
 
+  /**
+   * React to a board post, or withdraw our reaction when `remove` is set.
+   * The updated count arrives as a board_post_reactions_updated event.
+   */
+  async reactToBoardPost(
+    relayPeerId: string,
+    postId: string,
+    remove: boolean,
+    reactionType?: string,
+  ): Promise<void> {
+    return invoke<void>('react_to_board_post', { relayPeerId, postId, reactionType, remove });
+  },
+
   /** Sync a board (fetch latest from relay) */
   async syncBoard(relayPeerId: string, boardId: string): Promise<void> {
     return invoke<void>('sync_board', { relayPeerId, boardId });
   },
+
+  /** Subscribe to a board to be notified of new posts */
+  async subscribeBoard(relayPeerId: string, boardId: string): Promise<void> {
+    return invoke<void>('subscribe_board', { relayPeerId, boardId });
+  },
+
+  /** Unsubscribe from a board */
+  async unsubscribeBoard(relayPeerId: string, boardId: string): Promise<void> {
+    return invoke<void>('unsubscribe_board', { relayPeerId, boardId });
+  },
 };
@@ -33,6 +33,11 @@ export const callingService = {
     return invoke<HangupResult>('hangup_call', { callId, reason });
   },
 
+  /** Send text or a small file over a connected call's data channel */
+  async sendCallData(callId: string, data: number[]): Promise<void> {
+    return invoke<void>('send_call_data', { callId, data });
+  },
+
   /** Process an incoming offer (validate it) */
   async processOffer(
     callId: string,
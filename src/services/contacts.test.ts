@@ -93,6 +93,17 @@ describe('contactsService', () => {
     });
   });
 
+  describe('forgetContact', () => {
+    it('should invoke forget_contact', async () => {
+      vi.mocked(invoke).mockResolvedValue(12);
+
+      const result = await contactsService.forgetContact('peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('forget_contact', { peerId: 'peer-alice' });
+      expect(result).toBe(12);
+    });
+  });
+
   describe('isContact', () => {
     it('should invoke is_contact', async () => {
       vi.mocked(invoke).mockResolvedValue(true);
@@ -115,6 +126,30 @@ describe('contactsService', () => {
     });
   });
 
+  describe('getStaleContacts', () => {
+    it('should invoke get_stale_contacts with the inactivity window', async () => {
+      const stale = [
+        {
+          peerId: 'peer-alice',
+          displayName: 'Alice',
+          avatarHash: null,
+          isBlocked: false,
+          lastSeenAt: null,
+          lastMessageAt: null,
+          lastPostAt: null,
+          addedAt: 1000,
+          reasons: ['never_connected', 'never_messaged', 'no_posts_synced'],
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(stale);
+
+      const result = await contactsService.getStaleContacts(90);
+
+      expect(invoke).toHaveBeenCalledWith('get_stale_contacts', { inactiveDays: 90 });
+      expect(result).toEqual(stale);
+    });
+  });
+
   describe('requestPeerIdentity', () => {
     it('should invoke request_peer_identity', async () => {
       vi.mocked(invoke).mockResolvedValue(undefined);
@@ -124,4 +159,68 @@ describe('contactsService', () => {
       expect(invoke).toHaveBeenCalledWith('request_peer_identity', { peerId: 'peer-alice' });
     });
   });
+
+  describe('getNameHistory', () => {
+    it('should invoke get_contact_name_history with peerId', async () => {
+      const history = [
+        { displayName: 'Alice', observedAt: 1000 },
+        { displayName: 'Bob', observedAt: 2000 },
+      ];
+      vi.mocked(invoke).mockResolvedValue(history);
+
+      const result = await contactsService.getNameHistory('peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('get_contact_name_history', { peerId: 'peer-alice' });
+      expect(result).toEqual(history);
+    });
+  });
+
+  describe('contact groups', () => {
+    it('should invoke create_contact_group', async () => {
+      const group = { groupId: 'g1', name: 'Family', memberCount: 0, createdAt: 1000 };
+      vi.mocked(invoke).mockResolvedValue(group);
+
+      const result = await contactsService.createGroup('Family');
+
+      expect(invoke).toHaveBeenCalledWith('create_contact_group', { name: 'Family' });
+      expect(result).toEqual(group);
+    });
+
+    it('should invoke add_contact_to_group', async () => {
+      vi.mocked(invoke).mockResolvedValue(true);
+
+      await contactsService.addToGroup('g1', 'peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('add_contact_to_group', {
+        groupId: 'g1',
+        peerId: 'peer-alice',
+      });
+    });
+
+    it('should invoke get_contact_group_members', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await contactsService.getGroupMembers('g1');
+
+      expect(invoke).toHaveBeenCalledWith('get_contact_group_members', { groupId: 'g1' });
+    });
+  });
+
+  describe('contact suggestions', () => {
+    it('should invoke get_contact_suggestions', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await contactsService.getContactSuggestions();
+
+      expect(invoke).toHaveBeenCalledWith('get_contact_suggestions');
+    });
+
+    it('should invoke set_share_contact_list with enabled', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await contactsService.setShareContactList(true);
+
+      expect(invoke).toHaveBeenCalledWith('set_share_contact_list', { enabled: true });
+    });
+  });
 });
@@ -1,5 +1,12 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Contact, ContactData } from '../types';
+import type {
+  Contact,
+  ContactData,
+  ContactGroup,
+  ContactNameChange,
+  ContactSuggestion,
+  StaleContact,
+} from '../types';
 
 /** Contacts service - wraps Tauri commands */
 export const contactsService = {
@@ -48,6 +55,11 @@ export const contactsService = {
     return invoke<boolean>('remove_contact', { peerId });
   },
 
+  /** Forget a contact and everything stored about them. Returns the number of rows deleted. */
+  async forgetContact(peerId: string): Promise<number> {
+    return invoke<number>('forget_contact', { peerId });
+  },
+
   /** Check if a peer is a contact */
   async isContact(peerId: string): Promise<boolean> {
     return invoke<boolean>('is_contact', { peerId });
@@ -58,8 +70,63 @@ export const contactsService = {
     return invoke<boolean>('is_contact_blocked', { peerId });
   },
 
+  /** List contacts with no activity in the last `inactiveDays` days (read-only) */
+  async getStaleContacts(inactiveDays: number): Promise<StaleContact[]> {
+    return invoke<StaleContact[]>('get_stale_contacts', { inactiveDays });
+  },
+
+  /** Get every display name observed for a peer, oldest first */
+  async getNameHistory(peerId: string): Promise<ContactNameChange[]> {
+    return invoke<ContactNameChange[]>('get_contact_name_history', { peerId });
+  },
+
+  /** Create a contact group */
+  async createGroup(name: string): Promise<ContactGroup> {
+    return invoke<ContactGroup>('create_contact_group', { name });
+  },
+
+  /** Delete a contact group (its contacts are kept) */
+  async deleteGroup(groupId: string): Promise<boolean> {
+    return invoke<boolean>('delete_contact_group', { groupId });
+  },
+
+  /** Get all contact groups */
+  async getGroups(): Promise<ContactGroup[]> {
+    return invoke<ContactGroup[]>('get_contact_groups');
+  },
+
+  /** Add a contact to a group */
+  async addToGroup(groupId: string, peerId: string): Promise<boolean> {
+    return invoke<boolean>('add_contact_to_group', { groupId, peerId });
+  },
+
+  /** Remove a contact from a group */
+  async removeFromGroup(groupId: string, peerId: string): Promise<boolean> {
+    return invoke<boolean>('remove_contact_from_group', { groupId, peerId });
+  },
+
+  /** Get the contacts in a group */
+  async getGroupMembers(groupId: string): Promise<Contact[]> {
+    return invoke<Contact[]>('get_contact_group_members', { groupId });
+  },
+
   /** Request identity exchange with a peer (adds them as a contact) */
   async requestPeerIdentity(peerId: string): Promise<void> {
     return invoke<void>('request_peer_identity', { peerId });
   },
+
+  /** Suggest peers known to connected contacts that granted us share_contacts */
+  async getContactSuggestions(): Promise<ContactSuggestion[]> {
+    return invoke<ContactSuggestion[]>('get_contact_suggestions');
+  },
+
+  /** Whether contacts granted share_contacts may fetch our contact list */
+  async getShareContactList(): Promise<boolean> {
+    return invoke<boolean>('get_share_contact_list');
+  },
+
+  /** Turn sharing our contact list on or off */
+  async setShareContactList(enabled: boolean): Promise<void> {
+    return invoke<void>('set_share_contact_list', { enabled });
+  },
 };
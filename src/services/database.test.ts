@@ -0,0 +1,37 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { databaseService } from './database';
+import { invoke } from '@tauri-apps/api/core';
+
+describe('databaseService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('getDatabaseStatus', () => {
+    it('should invoke get_database_status', async () => {
+      const mockStatus = {
+        path: '/data/harbor.db',
+        error: 'database disk image is malformed',
+        rebuilt: false,
+        corruptCopy: '/data/harbor.db.corrupt-1700000000',
+        backupTakenAt: 1699990000,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockStatus);
+
+      const result = await databaseService.getDatabaseStatus();
+
+      expect(invoke).toHaveBeenCalledWith('get_database_status');
+      expect(result).toEqual(mockStatus);
+    });
+  });
+
+  describe('restoreDatabaseBackup', () => {
+    it('should invoke restore_database_backup', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await databaseService.restoreDatabaseBackup();
+
+      expect(invoke).toHaveBeenCalledWith('restore_database_backup');
+    });
+  });
+});
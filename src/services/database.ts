@@ -0,0 +1,27 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** How the database opened at startup */
+export interface DatabaseStatus {
+  path: string;
+  /** Set when the database couldn't be opened; only recovery is possible */
+  error: string | null;
+  /** Whether a corrupted database was rebuilt from what could still be read */
+  rebuilt: boolean;
+  /** Copy of the corrupted file kept aside */
+  corruptCopy: string | null;
+  /** When the last known-good backup was taken (unix seconds) */
+  backupTakenAt: number | null;
+}
+
+/** Database service - wraps Tauri commands for startup status and recovery */
+export const databaseService = {
+  /** Get how opening the database went at startup */
+  async getDatabaseStatus(): Promise<DatabaseStatus> {
+    return invoke<DatabaseStatus>('get_database_status');
+  },
+
+  /** Replace a database that failed to open with its last backup; relaunch afterwards */
+  async restoreDatabaseBackup(): Promise<void> {
+    return invoke('restore_database_backup');
+  },
+};
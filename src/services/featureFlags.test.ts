@@ -0,0 +1,47 @@
+import { describe, it, expect, vi, beforeEach } from 'vitest';
+import { featureFlagsService } from './featureFlags';
+import { invoke } from '@tauri-apps/api/core';
+
+describe('featureFlagsService', () => {
+  beforeEach(() => {
+    vi.clearAllMocks();
+  });
+
+  describe('getFeatureFlag', () => {
+    it('should invoke get_feature_flag with the flag name', async () => {
+      vi.mocked(invoke).mockResolvedValue(true);
+
+      const result = await featureFlagsService.getFeatureFlag('auto_sync_timer');
+
+      expect(invoke).toHaveBeenCalledWith('get_feature_flag', { name: 'auto_sync_timer' });
+      expect(result).toBe(true);
+    });
+  });
+
+  describe('setFeatureFlag', () => {
+    it('should invoke set_feature_flag with the name and state', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await featureFlagsService.setFeatureFlag('auto_sync_timer', false);
+
+      expect(invoke).toHaveBeenCalledWith('set_feature_flag', {
+        name: 'auto_sync_timer',
+        enabled: false,
+      });
+    });
+  });
+
+  describe('listFeatureFlags', () => {
+    it('should invoke list_feature_flags', async () => {
+      const mockFlags = [
+        { name: 'auto_sync_timer', description: 'Sync content in the background', enabled: false },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockFlags);
+
+      const result = await featureFlagsService.listFeatureFlags();
+
+      expect(invoke).toHaveBeenCalledWith('list_feature_flags');
+      expect(result).toEqual(mockFlags);
+    });
+  });
+});
@@ -0,0 +1,26 @@
+import { invoke } from '@tauri-apps/api/core';
+
+/** A feature flag and its state for the current identity */
+export interface FeatureFlagInfo {
+  name: string;
+  description: string;
+  enabled: boolean;
+}
+
+/** Feature flags service - wraps Tauri commands for per-identity feature flags */
+export const featureFlagsService = {
+  /** Whether a feature flag is on; unknown names are rejected */
+  async getFeatureFlag(name: string): Promise<boolean> {
+    return invoke<boolean>('get_feature_flag', { name });
+  },
+
+  /** Turn a feature flag on or off */
+  async setFeatureFlag(name: string, enabled: boolean): Promise<void> {
+    return invoke('set_feature_flag', { name, enabled });
+  },
+
+  /** List every known feature flag with its description and state */
+  async listFeatureFlags(): Promise<FeatureFlagInfo[]> {
+    return invoke<FeatureFlagInfo[]>('list_feature_flags');
+  },
+};
@@ -29,6 +29,18 @@ describe('feedService', () => {
         beforeTimestamp: undefined,
       });
     });
+
+    it('should invoke get_feed with a groupId filter', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await feedService.getFeed(20, undefined, 'g1');
+
+      expect(invoke).toHaveBeenCalledWith('get_feed', {
+        limit: 20,
+        beforeTimestamp: undefined,
+        groupId: 'g1',
+      });
+    });
   });
 
   describe('getWall', () => {
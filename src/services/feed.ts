@@ -4,8 +4,8 @@ import type { FeedItem } from '../types';
 /** Feed service - wraps Tauri commands for feed functionality */
 export const feedService = {
   /** Get the user's feed (posts from contacts) */
-  async getFeed(limit?: number, beforeTimestamp?: number): Promise<FeedItem[]> {
-    return invoke<FeedItem[]>('get_feed', { limit, beforeTimestamp });
+  async getFeed(limit?: number, beforeTimestamp?: number, groupId?: string): Promise<FeedItem[]> {
+    return invoke<FeedItem[]>('get_feed', { limit, beforeTimestamp, groupId });
   },
 
   /** Get a specific user's wall */
@@ -21,6 +21,15 @@ export const feedService = {
     });
   },
 
+  /** Get a single contact's posts in timeline order */
+  async getContactFeed(
+    peerId: string,
+    limit?: number,
+    beforeTimestamp?: number,
+  ): Promise<FeedItem[]> {
+    return invoke<FeedItem[]>('get_contact_feed', { peerId, limit, beforeTimestamp });
+  },
+
   /** Sync all contact walls from the relay server into the local feed */
   async syncFromRelay(): Promise<void> {
     return invoke<void>('sync_feed_from_relay');
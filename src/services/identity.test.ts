@@ -39,6 +39,7 @@ describe('identityService', () => {
         avatarHash: null,
         bio: 'Hello',
         passphraseHint: null,
+        accentColor: null,
         createdAt: 1700000000,
         updatedAt: 1700000000,
       };
@@ -70,6 +71,7 @@ describe('identityService', () => {
         avatarHash: null,
         bio: null,
         passphraseHint: null,
+        accentColor: null,
         createdAt: 1700000000,
         updatedAt: 1700000000,
       };
@@ -142,6 +144,83 @@ describe('identityService', () => {
     });
   });
 
+  describe('updateAccentColor', () => {
+    it('should invoke update_accent_color', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await identityService.updateAccentColor('#3b82f6');
+
+      expect(invoke).toHaveBeenCalledWith('update_accent_color', { accentColor: '#3b82f6' });
+    });
+  });
+
+  describe('changePassphrase', () => {
+    it('should invoke change_passphrase with both passphrases and profile', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await identityService.changePassphrase('old', 'new', 'strong');
+
+      expect(invoke).toHaveBeenCalledWith('change_passphrase', {
+        currentPassphrase: 'old',
+        newPassphrase: 'new',
+        kdfProfile: 'strong',
+      });
+    });
+  });
+
+  describe('createEphemeralIdentity', () => {
+    it('should invoke create_ephemeral_identity with display name', async () => {
+      vi.mocked(invoke).mockResolvedValue({ peerId: '12D3KooWGuest', displayName: 'Guest' });
+
+      const result = await identityService.createEphemeralIdentity('Guest');
+
+      expect(invoke).toHaveBeenCalledWith('create_ephemeral_identity', { displayName: 'Guest' });
+      expect(result.peerId).toBe('12D3KooWGuest');
+    });
+  });
+
+  describe('isEphemeral', () => {
+    it('should invoke is_identity_ephemeral', async () => {
+      vi.mocked(invoke).mockResolvedValue(true);
+
+      const result = await identityService.isEphemeral();
+
+      expect(invoke).toHaveBeenCalledWith('is_identity_ephemeral');
+      expect(result).toBe(true);
+    });
+  });
+
+  describe('exportBackup', () => {
+    it('should invoke export_identity_backup', async () => {
+      const mockBackup = {
+        version: 1,
+        peerId: '12D3KooWTest',
+        publicKey: 'base64key',
+        x25519Public: 'base64x25519',
+        privateKeyEncrypted: 'base64encrypted',
+        displayName: 'Test User',
+        bio: null,
+        passphraseHint: null,
+        createdAt: 1700000000,
+        exportedAt: 1700000100,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockBackup);
+
+      const result = await identityService.exportBackup();
+
+      expect(invoke).toHaveBeenCalledWith('export_identity_backup');
+      expect(result).toEqual(mockBackup);
+    });
+
+    it('should propagate the error for ephemeral identities', async () => {
+      vi.mocked(invoke).mockRejectedValue('Cannot back up an ephemeral identity');
+
+      await expect(identityService.exportBackup()).rejects.toBe(
+        'Cannot back up an ephemeral identity',
+      );
+    });
+  });
+
   describe('getPeerId', () => {
     it('should invoke get_peer_id', async () => {
       vi.mocked(invoke).mockResolvedValue('12D3KooWTest');
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { IdentityInfo, CreateIdentityRequest } from '../types';
+import type { IdentityInfo, CreateIdentityRequest, IdentityBackup, KdfProfile } from '../types';
 
 /** Identity service - wraps Tauri commands */
 export const identityService = {
@@ -23,11 +23,35 @@ export const identityService = {
     return invoke<IdentityInfo>('create_identity', { request });
   },
 
+  /** Create a throwaway guest identity (only when the app runs in ephemeral mode) */
+  async createEphemeralIdentity(displayName: string): Promise<IdentityInfo> {
+    return invoke<IdentityInfo>('create_ephemeral_identity', { displayName });
+  },
+
+  /** Check if the identity is an ephemeral guest identity */
+  async isEphemeral(): Promise<boolean> {
+    return invoke<boolean>('is_identity_ephemeral');
+  },
+
+  /** Export an identity backup (rejected for ephemeral identities) */
+  async exportBackup(): Promise<IdentityBackup> {
+    return invoke<IdentityBackup>('export_identity_backup');
+  },
+
   /** Unlock the identity with passphrase */
   async unlock(passphrase: string): Promise<IdentityInfo> {
     return invoke<IdentityInfo>('unlock_identity', { passphrase });
   },
 
+  /** Change the passphrase, optionally switching key derivation profile */
+  async changePassphrase(
+    currentPassphrase: string,
+    newPassphrase: string,
+    kdfProfile?: KdfProfile,
+  ): Promise<void> {
+    return invoke('change_passphrase', { currentPassphrase, newPassphrase, kdfProfile });
+  },
+
   /** Lock the identity */
   async lock(): Promise<void> {
     return invoke('lock_identity');
@@ -48,6 +72,11 @@ export const identityService = {
     return invoke('update_passphrase_hint', { hint });
   },
 
+  /** Update accent color (a hex color like #3b82f6, or null to clear) */
+  async updateAccentColor(accentColor: string | null): Promise<void> {
+    return invoke('update_accent_color', { accentColor });
+  },
+
   /** Get the local peer ID */
   async getPeerId(): Promise<string> {
     return invoke<string>('get_peer_id');
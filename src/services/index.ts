@@ -8,5 +8,8 @@ export { postsService } from './posts';
 export { feedService } from './feed';
 export { mediaService } from './media';
 export { commentsService } from './comments';
+export { activityService } from './activity';
+export { databaseService } from './database';
+export { featureFlagsService } from './featureFlags';
 export { callingService } from './calling';
 export * as loggingService from './logging';
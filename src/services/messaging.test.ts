@@ -37,17 +37,116 @@ describe('messagingService', () => {
     });
   });
 
+  describe('sendVoiceNote', () => {
+    it('should invoke send_voice_note with the audio bytes', async () => {
+      const mockResult = { messageId: 'msg-1', conversationId: 'conv-1', sentAt: 1700000000 };
+      vi.mocked(invoke).mockResolvedValue(mockResult);
+
+      const audio = new Uint8Array([0x4f, 0x67, 0x67, 0x53]);
+      const result = await messagingService.sendVoiceNote(
+        'peer-alice',
+        audio,
+        'audio/webm;codecs=opus',
+        2999.6,
+      );
+
+      expect(invoke).toHaveBeenCalledWith('send_voice_note', {
+        peerId: 'peer-alice',
+        data: [0x4f, 0x67, 0x67, 0x53],
+        mimeType: 'audio/webm;codecs=opus',
+        durationMs: 3000,
+        caption: undefined,
+        replyTo: undefined,
+      });
+      expect(result).toEqual(mockResult);
+    });
+  });
+
+  describe('sendDirectMessage', () => {
+    it('should invoke send_direct_message with the address', async () => {
+      const mockResult = {
+        messageId: 'msg-1',
+        peerId: '12D3KooWBob',
+        displayName: 'Bob',
+        sentAt: 1700000000,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockResult);
+
+      const result = await messagingService.sendDirectMessage(
+        '/ip4/1.2.3.4/tcp/4001/p2p/12D3KooWBob',
+        'Hi!',
+        undefined,
+        true,
+      );
+
+      expect(invoke).toHaveBeenCalledWith('send_direct_message', {
+        multiaddr: '/ip4/1.2.3.4/tcp/4001/p2p/12D3KooWBob',
+        content: 'Hi!',
+        contentType: undefined,
+        disconnectAfter: true,
+      });
+      expect(result).toEqual(mockResult);
+    });
+  });
+
   describe('getMessages', () => {
     it('should invoke get_messages with peerId and pagination', async () => {
-      vi.mocked(invoke).mockResolvedValue([]);
+      vi.mocked(invoke).mockResolvedValue({ messages: [], nextCursor: null });
 
-      await messagingService.getMessages('peer-alice', 100, 1700000000);
+      await messagingService.getMessages('peer-alice', 100, {
+        beforeTimestamp: 1700000000,
+        beforeMessageId: 'msg-1',
+      });
 
       expect(invoke).toHaveBeenCalledWith('get_messages', {
         peerId: 'peer-alice',
         limit: 100,
         beforeTimestamp: 1700000000,
+        beforeMessageId: 'msg-1',
+      });
+    });
+
+    it('should load the newest page without a cursor', async () => {
+      const page = { messages: [], nextCursor: null };
+      vi.mocked(invoke).mockResolvedValue(page);
+
+      const result = await messagingService.getMessages('peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('get_messages', {
+        peerId: 'peer-alice',
+        limit: undefined,
+        beforeTimestamp: undefined,
+        beforeMessageId: undefined,
+      });
+      expect(result).toEqual(page);
+    });
+  });
+
+  describe('searchMessages', () => {
+    it('should invoke search_messages with the query and scope', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      const result = await messagingService.searchMessages('friday', 'conv-1', 20);
+
+      expect(invoke).toHaveBeenCalledWith('search_messages', {
+        query: 'friday',
+        conversationId: 'conv-1',
+        limit: 20,
+      });
+      expect(result).toEqual([]);
+    });
+  });
+
+  describe('getPendingMessageCount', () => {
+    it('should invoke get_pending_message_count for a peer', async () => {
+      vi.mocked(invoke).mockResolvedValue(3);
+
+      const count = await messagingService.getPendingMessageCount('12D3KooWPeer');
+
+      expect(invoke).toHaveBeenCalledWith('get_pending_message_count', {
+        peerId: '12D3KooWPeer',
       });
+      expect(count).toBe(3);
     });
   });
 
@@ -61,6 +160,19 @@ describe('messagingService', () => {
     });
   });
 
+  describe('setConversationPinned', () => {
+    it('should invoke set_conversation_pinned', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await messagingService.setConversationPinned('12D3KooWPeer', true);
+
+      expect(invoke).toHaveBeenCalledWith('set_conversation_pinned', {
+        peerId: '12D3KooWPeer',
+        pinned: true,
+      });
+    });
+  });
+
   describe('markConversationRead', () => {
     it('should invoke mark_conversation_read', async () => {
       vi.mocked(invoke).mockResolvedValue(3);
@@ -93,4 +205,74 @@ describe('messagingService', () => {
       expect(result).toBe(12);
     });
   });
+
+  describe('nonce strategy', () => {
+    it('should invoke get_message_nonce_strategy', async () => {
+      vi.mocked(invoke).mockResolvedValue('salted_counter');
+
+      const result = await messagingService.getNonceStrategy();
+
+      expect(invoke).toHaveBeenCalledWith('get_message_nonce_strategy');
+      expect(result).toBe('salted_counter');
+    });
+
+    it('should invoke set_message_nonce_strategy', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await messagingService.setNonceStrategy('counter');
+
+      expect(invoke).toHaveBeenCalledWith('set_message_nonce_strategy', { strategy: 'counter' });
+    });
+  });
+
+  describe('message cipher', () => {
+    it('should invoke get_message_cipher', async () => {
+      vi.mocked(invoke).mockResolvedValue('aes-256-gcm');
+
+      const result = await messagingService.getMessageCipher();
+
+      expect(invoke).toHaveBeenCalledWith('get_message_cipher');
+      expect(result).toBe('aes-256-gcm');
+    });
+
+    it('should invoke set_message_cipher', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await messagingService.setMessageCipher('chacha20-poly1305');
+
+      expect(invoke).toHaveBeenCalledWith('set_message_cipher', { cipher: 'chacha20-poly1305' });
+    });
+  });
+
+  describe('retention', () => {
+    it('should invoke set_message_retention with days', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await messagingService.setMessageRetention(30);
+
+      expect(invoke).toHaveBeenCalledWith('set_message_retention', { days: 30 });
+    });
+
+    it('should invoke set_conversation_retention with override', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await messagingService.setConversationRetention('12D3KooWPeer', null, true);
+
+      expect(invoke).toHaveBeenCalledWith('set_conversation_retention', {
+        peerId: '12D3KooWPeer',
+        retentionDays: null,
+        neverDelete: true,
+      });
+    });
+
+    it('should return the sweep summary', async () => {
+      const summary = { messagesDeleted: 3, conversationsPruned: 1 };
+      vi.mocked(invoke).mockResolvedValue(summary);
+
+      const result = await messagingService.runRetentionSweep();
+
+      expect(invoke).toHaveBeenCalledWith('run_message_retention_sweep');
+      expect(result).toEqual(summary);
+    });
+  });
 });
@@ -1,5 +1,16 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Message, Conversation, SendMessageResult } from '../types';
+import type {
+  Message,
+  MessageCursor,
+  MessagePage,
+  Conversation,
+  ConversationRetention,
+  DirectSendResult,
+  MessageCipher,
+  NonceStrategy,
+  RetentionSweepSummary,
+  SendMessageResult,
+} from '../types';
 
 /** Messaging service - wraps Tauri commands */
 export const messagingService = {
@@ -18,15 +29,62 @@ export const messagingService = {
     });
   },
 
-  /** Get messages for a conversation */
-  async getMessages(peerId: string, limit?: number, beforeTimestamp?: number): Promise<Message[]> {
-    return invoke<Message[]>('get_messages', {
+  /** Send recorded audio to a peer as a voice note, with an optional caption */
+  async sendVoiceNote(
+    peerId: string,
+    data: Uint8Array,
+    mimeType: string,
+    durationMs: number,
+    caption?: string,
+    replyTo?: string,
+  ): Promise<SendMessageResult> {
+    return invoke<SendMessageResult>('send_voice_note', {
+      peerId,
+      data: Array.from(data),
+      mimeType,
+      durationMs: Math.round(durationMs),
+      caption,
+      replyTo,
+    });
+  },
+
+  /** Send a one-off message to a peer by address without adding them as a contact */
+  async sendDirectMessage(
+    multiaddr: string,
+    content: string,
+    contentType?: string,
+    disconnectAfter?: boolean,
+  ): Promise<DirectSendResult> {
+    return invoke<DirectSendResult>('send_direct_message', {
+      multiaddr,
+      content,
+      contentType,
+      disconnectAfter,
+    });
+  },
+
+  /**
+   * Get a page of a conversation, newest page first. Pass the returned
+   * `nextCursor` to load older messages; `limit` is capped at 200.
+   */
+  async getMessages(peerId: string, limit?: number, cursor?: MessageCursor): Promise<MessagePage> {
+    return invoke<MessagePage>('get_messages', {
       peerId,
       limit,
-      beforeTimestamp,
+      beforeTimestamp: cursor?.beforeTimestamp,
+      beforeMessageId: cursor?.beforeMessageId,
     });
   },
 
+  /**
+   * Search message content, newest first, in every conversation or just
+   * `conversationId`. Matches are case-insensitive substrings; `limit` is
+   * capped at 200. Matches also arrive in batches as `harbor:message-search`.
+   */
+  async searchMessages(query: string, conversationId?: string, limit?: number): Promise<Message[]> {
+    return invoke<Message[]>('search_messages', { query, conversationId, limit });
+  },
+
   /** Get all conversations */
   async getConversations(): Promise<Conversation[]> {
     return invoke<Conversation[]>('get_conversations');
@@ -46,4 +104,73 @@ export const messagingService = {
   async getTotalUnreadCount(): Promise<number> {
     return invoke<number>('get_total_unread_count');
   },
+
+  /** Get how outgoing message nonces are derived */
+  async getNonceStrategy(): Promise<NonceStrategy> {
+    return invoke<NonceStrategy>('get_message_nonce_strategy');
+  },
+
+  /** Set how outgoing message nonces are derived */
+  async setNonceStrategy(strategy: NonceStrategy): Promise<void> {
+    return invoke<void>('set_message_nonce_strategy', { strategy });
+  },
+
+  /** Get the cipher preferred for outgoing messages */
+  async getMessageCipher(): Promise<MessageCipher> {
+    return invoke<MessageCipher>('get_message_cipher');
+  },
+
+  /** Set the cipher preferred for outgoing messages */
+  async setMessageCipher(cipher: MessageCipher): Promise<void> {
+    return invoke<void>('set_message_cipher', { cipher });
+  },
+
+  /** Get the global message retention window in days (null keeps messages forever) */
+  async getMessageRetention(): Promise<number | null> {
+    return invoke<number | null>('get_message_retention');
+  },
+
+  /** Set the global message retention window in days (null disables pruning) */
+  async setMessageRetention(days: number | null): Promise<void> {
+    return invoke<void>('set_message_retention', { days });
+  },
+
+  /** Count messages still waiting for delivery, to one peer or (without a peer) to anyone */
+  async getPendingMessageCount(peerId?: string): Promise<number> {
+    return invoke<number>('get_pending_message_count', { peerId });
+  },
+
+  /** Get how many times an undelivered message is sent before giving up */
+  async getMaxSendAttempts(): Promise<number> {
+    return invoke<number>('get_max_send_attempts');
+  },
+
+  /** Set how many times an undelivered message is sent before giving up */
+  async setMaxSendAttempts(attempts: number): Promise<void> {
+    return invoke<void>('set_max_send_attempts', { attempts });
+  },
+
+  /** Get the retention override for a conversation */
+  async getConversationRetention(peerId: string): Promise<ConversationRetention | null> {
+    return invoke<ConversationRetention | null>('get_conversation_retention', { peerId });
+  },
+
+  /** Override retention for a conversation (null days and neverDelete false clears it) */
+  async setConversationRetention(
+    peerId: string,
+    retentionDays: number | null,
+    neverDelete: boolean,
+  ): Promise<void> {
+    return invoke<void>('set_conversation_retention', { peerId, retentionDays, neverDelete });
+  },
+
+  /** Pin a conversation to the top of the list, or unpin it */
+  async setConversationPinned(peerId: string, pinned: boolean): Promise<void> {
+    return invoke<void>('set_conversation_pinned', { peerId, pinned });
+  },
+
+  /** Prune expired messages now */
+  async runRetentionSweep(): Promise<RetentionSweepSummary> {
+    return invoke<RetentionSweepSummary>('run_message_retention_sweep');
+  },
 };
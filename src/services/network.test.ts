@@ -103,6 +103,183 @@ describe('networkService', () => {
     });
   });
 
+  describe('getNetworkDiagnostics', () => {
+    it('should invoke get_network_diagnostics', async () => {
+      const mockDiagnostics = {
+        networkRunning: false,
+        natStatus: 'private',
+        natStatusSource: 'manual',
+        detectedNatStatus: 'unknown',
+        natOverride: 'private',
+        autonatEnabled: true,
+        addressFilter: 'routable',
+        connectedPeers: 0,
+        relayAddresses: [],
+        relayLimits: [],
+        relayLiveness: [],
+        externalAddresses: [],
+        uptimeSeconds: 0,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockDiagnostics);
+
+      const result = await networkService.getNetworkDiagnostics();
+
+      expect(invoke).toHaveBeenCalledWith('get_network_diagnostics');
+      expect(result).toEqual(mockDiagnostics);
+    });
+  });
+
+  describe('getConnectionFailures', () => {
+    it('should invoke get_connection_failures', async () => {
+      const mockFailures = [
+        {
+          peerId: '12D3KooWTest',
+          address: '/ip4/192.168.1.5/tcp/9000',
+          category: 'handshake',
+          detail: 'Handshake failed: noise: decrypt error',
+          occurredAt: 1700000000,
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockFailures);
+
+      const result = await networkService.getConnectionFailures('12D3KooWTest', 20);
+
+      expect(invoke).toHaveBeenCalledWith('get_connection_failures', {
+        peerId: '12D3KooWTest',
+        limit: 20,
+      });
+      expect(result).toEqual(mockFailures);
+    });
+
+    it('should pass null filters by default', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await networkService.getConnectionFailures();
+
+      expect(invoke).toHaveBeenCalledWith('get_connection_failures', {
+        peerId: null,
+        limit: null,
+      });
+    });
+  });
+
+  describe('runHealthCheck', () => {
+    it('should invoke run_health_check', async () => {
+      const mockReport = {
+        status: 'fail',
+        checks: [
+          { name: 'database', status: 'ok', message: 'Schema version 32' },
+          { name: 'network', status: 'fail', message: 'Network is not running' },
+        ],
+        generatedAt: 1700000000,
+      };
+      vi.mocked(invoke).mockResolvedValue(mockReport);
+
+      const result = await networkService.runHealthCheck();
+
+      expect(invoke).toHaveBeenCalledWith('run_health_check');
+      expect(result).toEqual(mockReport);
+    });
+  });
+
+  describe('setNatOverride', () => {
+    it('should invoke set_nat_override with status', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setNatOverride('private');
+
+      expect(invoke).toHaveBeenCalledWith('set_nat_override', { status: 'private' });
+    });
+
+    it('should pass null to clear the override', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setNatOverride(null);
+
+      expect(invoke).toHaveBeenCalledWith('set_nat_override', { status: null });
+    });
+  });
+
+  describe('setAddressFilter', () => {
+    it('should invoke set_address_filter', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setAddressFilter('public_only');
+
+      expect(invoke).toHaveBeenCalledWith('set_address_filter', { filter: 'public_only' });
+    });
+  });
+
+  describe('getDhtRoutingTable', () => {
+    it('should invoke get_dht_routing_table', async () => {
+      const table = {
+        localPeerId: '12D3KooWLocal',
+        totalPeers: 1,
+        buckets: [
+          { index: 255, peers: [{ peerId: '12D3KooWPeer', connected: true, addresses: [] }] },
+        ],
+      };
+      vi.mocked(invoke).mockResolvedValue(table);
+
+      const result = await networkService.getDhtRoutingTable();
+
+      expect(invoke).toHaveBeenCalledWith('get_dht_routing_table');
+      expect(result).toEqual(table);
+    });
+  });
+
+  describe('setSyncStrategy', () => {
+    it('should invoke set_sync_strategy with the tagged strategy', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setSyncStrategy({ kind: 'round_robin', max_peers: 4 });
+
+      expect(invoke).toHaveBeenCalledWith('set_sync_strategy', {
+        strategy: { kind: 'round_robin', max_peers: 4 },
+      });
+    });
+  });
+
+  describe('setReconcileInterval', () => {
+    it('should invoke set_reconcile_interval with seconds', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setReconcileInterval(120);
+
+      expect(invoke).toHaveBeenCalledWith('set_reconcile_interval', { secs: 120 });
+    });
+  });
+
+  describe('setHeartbeatInterval', () => {
+    it('should invoke set_heartbeat_interval with seconds', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setHeartbeatInterval(30);
+
+      expect(invoke).toHaveBeenCalledWith('set_heartbeat_interval', { secs: 30 });
+    });
+  });
+
+  describe('reconcileConnections', () => {
+    it('should invoke reconcile_connections', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.reconcileConnections();
+
+      expect(invoke).toHaveBeenCalledWith('reconcile_connections');
+    });
+  });
+
+  describe('setAutonatEnabled', () => {
+    it('should invoke set_autonat_enabled', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await networkService.setAutonatEnabled(false);
+
+      expect(invoke).toHaveBeenCalledWith('set_autonat_enabled', { enabled: false });
+    });
+  });
+
   describe('getShareableContactString', () => {
     it('should invoke get_shareable_contact_string', async () => {
       vi.mocked(invoke).mockResolvedValue('harbor://contact/...');
@@ -127,6 +304,30 @@ describe('networkService', () => {
     });
   });
 
+  describe('getIdentityQrPayload', () => {
+    it('should invoke get_identity_qr_payload and return bytes', async () => {
+      vi.mocked(invoke).mockResolvedValue([1, 2, 3]);
+
+      const result = await networkService.getIdentityQrPayload();
+
+      expect(invoke).toHaveBeenCalledWith('get_identity_qr_payload');
+      expect(result).toEqual(new Uint8Array([1, 2, 3]));
+    });
+  });
+
+  describe('addContactFromQrPayload', () => {
+    it('should invoke add_contact_from_qr_payload with a byte array', async () => {
+      vi.mocked(invoke).mockResolvedValue('peer-alice');
+
+      const result = await networkService.addContactFromQrPayload(new Uint8Array([1, 2, 3]));
+
+      expect(invoke).toHaveBeenCalledWith('add_contact_from_qr_payload', {
+        payload: [1, 2, 3],
+      });
+      expect(result).toBe('peer-alice');
+    });
+  });
+
   describe('syncFeed', () => {
     it('should invoke sync_feed with limit', async () => {
       vi.mocked(invoke).mockResolvedValue(undefined);
@@ -136,4 +337,35 @@ describe('networkService', () => {
       expect(invoke).toHaveBeenCalledWith('sync_feed', { limit: 50 });
     });
   });
+
+  describe('syncWithPeer', () => {
+    it('should invoke sync_with_peer and return the summary', async () => {
+      const summary = { peerId: 'peer-alice', postsFetched: 2, hasMore: false, errors: [] };
+      vi.mocked(invoke).mockResolvedValue(summary);
+
+      const result = await networkService.syncWithPeer('peer-alice', 20);
+
+      expect(invoke).toHaveBeenCalledWith('sync_with_peer', { peerId: 'peer-alice', limit: 20 });
+      expect(result).toEqual(summary);
+    });
+  });
+
+  describe('resyncAll', () => {
+    it('should invoke resync_all and return the summary', async () => {
+      const summary = {
+        cursorsCleared: 4,
+        boardCursorsCleared: 2,
+        peersSynced: 1,
+        postsFetched: 12,
+        communitiesRejoined: 1,
+        errors: [],
+      };
+      vi.mocked(invoke).mockResolvedValue(summary);
+
+      const result = await networkService.resyncAll();
+
+      expect(invoke).toHaveBeenCalledWith('resync_all');
+      expect(result).toEqual(summary);
+    });
+  });
 });
@@ -1,5 +1,19 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { PeerInfo, NetworkStats } from '../types';
+import type {
+  AddressFilter,
+  BoundPorts,
+  ConnectionFailureInfo,
+  DhtRoutingTable,
+  HealthReport,
+  ListenPortSettings,
+  PeerInfo,
+  NetworkStats,
+  NatStatus,
+  NetworkDiagnostics,
+  PeerSyncSummary,
+  ResyncSummary,
+  SyncStrategy,
+} from '../types';
 
 /** Start the P2P network (requires unlocked identity) */
 export async function startNetwork(): Promise<void> {
@@ -31,7 +45,7 @@ export async function bootstrapNetwork(): Promise<void> {
   return invoke('bootstrap_network');
 }
 
-/** Get listening addresses (for sharing with remote peers) */
+/** Get listening addresses, including LAN addresses (loopback and link-local are dropped) */
 export async function getListeningAddresses(): Promise<string[]> {
   return invoke('get_listening_addresses');
 }
@@ -56,11 +70,127 @@ export async function connectToPublicRelays(): Promise<void> {
   return invoke<void>('connect_to_public_relays');
 }
 
+/** Re-dial dropped relays and bootstrap nodes now, e.g. when the OS reports it's back online */
+export async function reconcileConnections(): Promise<void> {
+  return invoke<void>('reconcile_connections');
+}
+
 /** Get current NAT status */
 export async function getNatStatus(): Promise<string> {
   return invoke<string>('get_nat_status');
 }
 
+/** Get NAT/connectivity diagnostics (works while the network is stopped) */
+export async function getNetworkDiagnostics(): Promise<NetworkDiagnostics> {
+  return invoke<NetworkDiagnostics>('get_network_diagnostics');
+}
+
+/** Get recent connection failures, newest first, optionally only for one peer */
+export async function getConnectionFailures(
+  peerId?: string,
+  limit?: number,
+): Promise<ConnectionFailureInfo[]> {
+  return invoke<ConnectionFailureInfo[]>('get_connection_failures', {
+    peerId: peerId ?? null,
+    limit: limit ?? null,
+  });
+}
+
+/** Delete the recorded connection failures, returning how many were deleted */
+export async function clearConnectionFailures(): Promise<number> {
+  return invoke<number>('clear_connection_failures');
+}
+
+/** Check every backend subsystem; failures are entries in the report, never errors */
+export async function runHealthCheck(): Promise<HealthReport> {
+  return invoke<HealthReport>('run_health_check');
+}
+
+/** Manually override the NAT status, or pass null to return to auto-detection */
+export async function setNatOverride(status: NatStatus | null): Promise<void> {
+  return invoke<void>('set_nat_override', { status });
+}
+
+/** Enable or disable AutoNAT probing (takes effect on next network start) */
+export async function setAutonatEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>('set_autonat_enabled', { enabled });
+}
+
+/** Whether newly connected non-contacts are automatically asked for their identity */
+export async function getAutoRequestIdentity(): Promise<boolean> {
+  return invoke<boolean>('get_auto_request_identity');
+}
+
+/** Enable or disable automatic identity requests (takes effect on next network start) */
+export async function setAutoRequestIdentity(enabled: boolean): Promise<void> {
+  return invoke<void>('set_auto_request_identity', { enabled });
+}
+
+/** Get the configured TCP/QUIC listen ports */
+export async function getListenPorts(): Promise<ListenPortSettings> {
+  return invoke<ListenPortSettings>('get_listen_ports');
+}
+
+/** Set the TCP/QUIC listen ports (takes effect on next network start) */
+export async function setListenPorts(settings: ListenPortSettings): Promise<void> {
+  return invoke<void>('set_listen_ports', { settings });
+}
+
+/** Get the ports the running network actually bound to */
+export async function getBoundPorts(): Promise<BoundPorts> {
+  return invoke<BoundPorts>('get_bound_ports');
+}
+
+/** Get the peers in the DHT routing table, for debugging peer discovery */
+export async function getDhtRoutingTable(): Promise<DhtRoutingTable> {
+  return invoke<DhtRoutingTable>('get_dht_routing_table');
+}
+
+/** Set which of our addresses are announced to remote peers (takes effect on next network start) */
+export async function setAddressFilter(filter: AddressFilter): Promise<void> {
+  return invoke<void>('set_address_filter', { filter });
+}
+
+/** Get how feed sync picks which connected peers to ask */
+export async function getSyncStrategy(): Promise<SyncStrategy> {
+  return invoke<SyncStrategy>('get_sync_strategy');
+}
+
+/** Set how feed sync picks which connected peers to ask (takes effect on next network start) */
+export async function setSyncStrategy(strategy: SyncStrategy): Promise<void> {
+  return invoke<void>('set_sync_strategy', { strategy });
+}
+
+/** Whether signed presence heartbeats are exchanged with connected contacts */
+export async function getHeartbeatEnabled(): Promise<boolean> {
+  return invoke<boolean>('get_heartbeat_enabled');
+}
+
+/** Enable or disable presence heartbeats (takes effect on next network start) */
+export async function setHeartbeatEnabled(enabled: boolean): Promise<void> {
+  return invoke<void>('set_heartbeat_enabled', { enabled });
+}
+
+/** Get the seconds between presence heartbeats */
+export async function getHeartbeatInterval(): Promise<number> {
+  return invoke<number>('get_heartbeat_interval');
+}
+
+/** Set the seconds between presence heartbeats (takes effect on next network start) */
+export async function setHeartbeatInterval(secs: number): Promise<void> {
+  return invoke<void>('set_heartbeat_interval', { secs });
+}
+
+/** Get the seconds between reconnection passes over relays and bootstrap nodes (0 = off) */
+export async function getReconcileInterval(): Promise<number> {
+  return invoke<number>('get_reconcile_interval');
+}
+
+/** Set the seconds between reconnection passes (takes effect on next network start) */
+export async function setReconcileInterval(secs: number): Promise<void> {
+  return invoke<void>('set_reconcile_interval', { secs });
+}
+
 /** Get shareable addresses (relay addresses that work globally) */
 export async function getShareableAddresses(): Promise<string[]> {
   return invoke<string[]>('get_shareable_addresses');
@@ -76,6 +206,31 @@ export async function addContactFromString(contactString: string): Promise<strin
   return invoke<string>('add_contact_from_string', { contactString });
 }
 
+/** Get our identity as a compact binary payload for rendering as a QR code */
+export async function getIdentityQrPayload(): Promise<Uint8Array> {
+  const bytes = await invoke<number[]>('get_identity_qr_payload');
+  return Uint8Array.from(bytes);
+}
+
+/** Add a contact from a scanned identity QR payload. Returns the contact's peer ID. */
+export async function addContactFromQrPayload(payload: Uint8Array): Promise<string> {
+  return invoke<string>('add_contact_from_qr_payload', { payload: Array.from(payload) });
+}
+
 export async function syncFeed(limit?: number): Promise<void> {
   return invoke<void>('sync_feed', { limit });
 }
+
+/** Sync one peer's content and wait for the result */
+export async function syncWithPeer(peerId: string, limit?: number): Promise<PeerSyncSummary> {
+  return invoke<PeerSyncSummary>('sync_with_peer', { peerId, limit });
+}
+
+/**
+ * Catch up after restoring a backup: forget inbound sync cursors, re-sync all
+ * online contacts from the start and re-join communities. Local posts are kept.
+ * Progress is emitted as `harbor:resync`.
+ */
+export async function resyncAll(): Promise<ResyncSummary> {
+  return invoke<ResyncSummary>('resync_all');
+}
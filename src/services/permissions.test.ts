@@ -94,6 +94,31 @@ describe('permissionsService', () => {
     });
   });
 
+  describe('getPermissionHistory', () => {
+    it('should invoke get_permission_history with the peer ID', async () => {
+      const history = [
+        {
+          eventId: 'e1',
+          eventType: 'grant',
+          grantId: 'grant-1',
+          capability: 'wall_read',
+          issuerPeerId: 'peer-me',
+          subjectPeerId: 'peer-alice',
+          direction: 'granted',
+          timestamp: 1700000000,
+          expiresAt: null,
+          signature: 'abcd',
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(history);
+
+      const result = await permissionsService.getPermissionHistory('peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('get_permission_history', { peerId: 'peer-alice' });
+      expect(result).toEqual(history);
+    });
+  });
+
   describe('getChatPeers', () => {
     it('should invoke get_chat_peers', async () => {
       vi.mocked(invoke).mockResolvedValue(['peer-alice', 'peer-bob']);
@@ -116,4 +141,49 @@ describe('permissionsService', () => {
       });
     });
   });
+
+  describe('grantGroupPermission', () => {
+    it('should invoke grant_group_permission', async () => {
+      vi.mocked(invoke).mockResolvedValue([]);
+
+      await permissionsService.grantGroupPermission('g1', 'wall_read');
+
+      expect(invoke).toHaveBeenCalledWith('grant_group_permission', {
+        groupId: 'g1',
+        capability: 'wall_read',
+        expiresInSeconds: undefined,
+      });
+    });
+  });
+
+  describe('default grant policy', () => {
+    it('should invoke get_default_grant_policy', async () => {
+      vi.mocked(invoke).mockResolvedValue(['chat', 'file_transfer']);
+
+      const result = await permissionsService.getDefaultGrantPolicy();
+
+      expect(invoke).toHaveBeenCalledWith('get_default_grant_policy');
+      expect(result).toEqual(['chat', 'file_transfer']);
+    });
+
+    it('should invoke set_default_grant_policy', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await permissionsService.setDefaultGrantPolicy(['chat', 'wall_read']);
+
+      expect(invoke).toHaveBeenCalledWith('set_default_grant_policy', {
+        capabilities: ['chat', 'wall_read'],
+      });
+    });
+  });
+
+  describe('reconcilePermissions', () => {
+    it('should invoke reconcile_permissions', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await permissionsService.reconcilePermissions('12D3KooWPeer');
+
+      expect(invoke).toHaveBeenCalledWith('reconcile_permissions', { peerId: '12D3KooWPeer' });
+    });
+  });
 });
@@ -1,5 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Capability, PermissionInfo, GrantResult } from '../types';
+import type { Capability, PermissionInfo, PermissionHistoryEntry, GrantResult } from '../types';
 
 /** Permissions service - wraps Tauri commands */
 export const permissionsService = {
@@ -41,13 +41,46 @@ export const permissionsService = {
     return invoke<PermissionInfo[]>('get_received_permissions');
   },
 
+  /** Get every grant and revocation between us and a peer, in both directions, oldest first */
+  async getPermissionHistory(peerId: string): Promise<PermissionHistoryEntry[]> {
+    return invoke<PermissionHistoryEntry[]>('get_permission_history', { peerId });
+  },
+
   /** Get all peers we can chat with */
   async getChatPeers(): Promise<string[]> {
     return invoke<string[]>('get_chat_peers');
   },
 
-  /** Grant all standard permissions (chat, wall_read, call) to a peer */
+  /** Grant every capability (chat, wall_read, call, board_post, file_transfer, share_contacts) */
   async grantAllPermissions(subjectPeerId: string): Promise<GrantResult[]> {
     return invoke<GrantResult[]>('grant_all_permissions', { subjectPeerId });
   },
+
+  /** Grant a permission to every member of a contact group */
+  async grantGroupPermission(
+    groupId: string,
+    capability: Capability,
+    expiresInSeconds?: number | null,
+  ): Promise<GrantResult[]> {
+    return invoke<GrantResult[]>('grant_group_permission', {
+      groupId,
+      capability,
+      expiresInSeconds,
+    });
+  },
+
+  /** Get the capabilities granted to new contacts */
+  async getDefaultGrantPolicy(): Promise<Capability[]> {
+    return invoke<Capability[]>('get_default_grant_policy');
+  },
+
+  /** Set the capabilities granted to new contacts (an empty list grants nothing) */
+  async setDefaultGrantPolicy(capabilities: Capability[]): Promise<void> {
+    return invoke<void>('set_default_grant_policy', { capabilities });
+  },
+
+  /** Resend grants a connected peer hasn't acknowledged (also done on every reconnect) */
+  async reconcilePermissions(peerId: string): Promise<void> {
+    return invoke<void>('reconcile_permissions', { peerId });
+  },
 };
@@ -46,6 +46,26 @@ describe('postsService', () => {
     });
   });
 
+  describe('pinPost', () => {
+    it('should invoke pin_post', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await postsService.pinPost('post-1');
+
+      expect(invoke).toHaveBeenCalledWith('pin_post', { postId: 'post-1' });
+    });
+  });
+
+  describe('unpinPost', () => {
+    it('should invoke unpin_post', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await postsService.unpinPost('post-1');
+
+      expect(invoke).toHaveBeenCalledWith('unpin_post', { postId: 'post-1' });
+    });
+  });
+
   describe('getPost', () => {
     it('should invoke get_post and return result', async () => {
       const mockPost = {
@@ -59,6 +79,7 @@ describe('postsService', () => {
         updatedAt: 1700000000,
         deletedAt: null,
         isLocal: true,
+        pinned: false,
       };
       vi.mocked(invoke).mockResolvedValue(mockPost);
 
@@ -77,6 +98,30 @@ describe('postsService', () => {
     });
   });
 
+  describe('getPostEvents', () => {
+    it('should invoke get_post_events and return the history', async () => {
+      const mockEvents = [
+        {
+          eventId: 'created:post-1',
+          eventType: 'created',
+          postId: 'post-1',
+          authorPeerId: 'peer-me',
+          lamportClock: 1,
+          timestamp: 1700000000,
+          payloadHex: 'a0',
+          signatureHex: '01020304',
+          receivedAt: 1700000000,
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(mockEvents);
+
+      const result = await postsService.getPostEvents('post-1');
+
+      expect(invoke).toHaveBeenCalledWith('get_post_events', { postId: 'post-1' });
+      expect(result).toEqual(mockEvents);
+    });
+  });
+
   describe('getMyPosts', () => {
     it('should invoke get_my_posts with pagination', async () => {
       vi.mocked(invoke).mockResolvedValue([]);
@@ -145,4 +190,99 @@ describe('postsService', () => {
       expect(invoke).toHaveBeenCalledWith('get_post_media', { postId: 'post-1' });
     });
   });
+
+  describe('max post attachments', () => {
+    it('should invoke the attachment limit commands', async () => {
+      vi.mocked(invoke).mockResolvedValueOnce(10).mockResolvedValueOnce(undefined);
+
+      expect(await postsService.getMaxPostAttachments()).toBe(10);
+      await postsService.setMaxPostAttachments(4);
+
+      expect(invoke).toHaveBeenCalledWith('get_max_post_attachments');
+      expect(invoke).toHaveBeenCalledWith('set_max_post_attachments', { max: 4 });
+    });
+  });
+
+  describe('getPostViews', () => {
+    it('should invoke get_post_views', async () => {
+      const summary = { postId: 'post-1', viewCount: 1, viewers: [] };
+      vi.mocked(invoke).mockResolvedValue(summary);
+
+      const result = await postsService.getPostViews('post-1');
+
+      expect(invoke).toHaveBeenCalledWith('get_post_views', { postId: 'post-1' });
+      expect(result).toEqual(summary);
+    });
+  });
+
+  describe('getPostDeliveryStatus', () => {
+    it('should invoke get_post_delivery_status', async () => {
+      const deliveries = [
+        {
+          recipientPeerId: 'peer-1',
+          displayName: 'Alice',
+          status: 'delivered',
+          attemptedAt: 1700000000,
+          deliveredAt: 1700000001,
+          error: null,
+        },
+      ];
+      vi.mocked(invoke).mockResolvedValue(deliveries);
+
+      const result = await postsService.getPostDeliveryStatus('post-1');
+
+      expect(invoke).toHaveBeenCalledWith('get_post_delivery_status', { postId: 'post-1' });
+      expect(result).toEqual(deliveries);
+    });
+  });
+
+  describe('setPostViewSettings', () => {
+    it('should invoke set_post_view_settings', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await postsService.setPostViewSettings({ reportViews: false, recordViewTimes: false });
+
+      expect(invoke).toHaveBeenCalledWith('set_post_view_settings', {
+        settings: { reportViews: false, recordViewTimes: false },
+      });
+    });
+  });
+
+  describe('setAutoSyncInterval', () => {
+    it('should invoke set_auto_sync_interval', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await postsService.setAutoSyncInterval(600);
+
+      expect(invoke).toHaveBeenCalledWith('set_auto_sync_interval', { secs: 600 });
+    });
+  });
+
+  describe('setMinSignatureVersion', () => {
+    it('should invoke set_min_signature_version', async () => {
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await postsService.setMinSignatureVersion(1);
+
+      expect(invoke).toHaveBeenCalledWith('set_min_signature_version', { version: 1 });
+    });
+  });
+
+  describe('importPostsFromFeed', () => {
+    it('should invoke import_posts_from_feed', async () => {
+      const summary = { imported: 2, duplicates: 1, skipped: 0, mediaFailed: 0, postIds: [] };
+      vi.mocked(invoke).mockResolvedValue(summary);
+
+      const result = await postsService.importPostsFromFeed(
+        'https://blog.example.com/feed.xml',
+        'public',
+      );
+
+      expect(invoke).toHaveBeenCalledWith('import_posts_from_feed', {
+        urlOrFile: 'https://blog.example.com/feed.xml',
+        visibility: 'public',
+      });
+      expect(result).toEqual(summary);
+    });
+  });
 });
@@ -1,18 +1,33 @@
 import { invoke } from '@tauri-apps/api/core';
-import type { Post, PostMedia, PostVisibility, CreatePostResult } from '../types';
+import type {
+  Post,
+  PostEvent,
+  PostMedia,
+  PostVisibility,
+  CreatePostResult,
+  PostViewSummary,
+  PostViewSettings,
+  PostDelivery,
+  FeedImportSummary,
+} from '../types';
 
 /** Posts service - wraps Tauri commands for wall/blog functionality */
 export const postsService = {
-  /** Create a new post */
+  /**
+   * Create a new post. Shareable posts are pushed to online contacts unless
+   * `pushToContacts` is false.
+   */
   async createPost(
     contentType: string,
     contentText?: string,
     visibility?: PostVisibility,
+    pushToContacts?: boolean,
   ): Promise<CreatePostResult> {
     return invoke<CreatePostResult>('create_post', {
       contentType,
       contentText,
       visibility,
+      pushToContacts,
     });
   },
 
@@ -26,11 +41,29 @@ export const postsService = {
     return invoke<void>('delete_post', { postId });
   },
 
+  /** Pin one of our posts to the top of our wall (replaces any previous pin) */
+  async pinPost(postId: string): Promise<void> {
+    return invoke<void>('pin_post', { postId });
+  },
+
+  /** Unpin our pinned post */
+  async unpinPost(postId: string): Promise<void> {
+    return invoke<void>('unpin_post', { postId });
+  },
+
   /** Get a single post by ID */
   async getPost(postId: string): Promise<Post | null> {
     return invoke<Post | null>('get_post', { postId });
   },
 
+  /**
+   * Get a post's recorded event history, oldest lamport clock first.
+   * Remote updates that lost to a newer version are not recorded.
+   */
+  async getPostEvents(postId: string): Promise<PostEvent[]> {
+    return invoke<PostEvent[]>('get_post_events', { postId });
+  },
+
   /** Get the local user's posts (their wall) */
   async getMyPosts(limit?: number, beforeTimestamp?: number): Promise<Post[]> {
     return invoke<Post[]>('get_my_posts', { limit, beforeTimestamp });
@@ -82,4 +115,67 @@ export const postsService = {
   async getPostMedia(postId: string): Promise<PostMedia[]> {
     return invoke<PostMedia[]>('get_post_media', { postId });
   },
+
+  /** Get the most media attachments a post may carry */
+  async getMaxPostAttachments(): Promise<number> {
+    return invoke<number>('get_max_post_attachments');
+  },
+
+  /** Set the most media attachments a post may carry, ours or a peer's (1-100) */
+  async setMaxPostAttachments(max: number): Promise<void> {
+    return invoke<void>('set_max_post_attachments', { max });
+  },
+
+  /** Get the view count and viewers of one of our posts */
+  async getPostViews(postId: string): Promise<PostViewSummary> {
+    return invoke<PostViewSummary>('get_post_views', { postId });
+  },
+
+  /** Get which online contacts a post was pushed to and whether it arrived */
+  async getPostDeliveryStatus(postId: string): Promise<PostDelivery[]> {
+    return invoke<PostDelivery[]>('get_post_delivery_status', { postId });
+  },
+
+  /** Get the post view privacy settings */
+  async getPostViewSettings(): Promise<PostViewSettings> {
+    return invoke<PostViewSettings>('get_post_view_settings');
+  },
+
+  /** Update the post view privacy settings */
+  async setPostViewSettings(settings: PostViewSettings): Promise<void> {
+    return invoke<void>('set_post_view_settings', { settings });
+  },
+
+  /** Get the background sync interval in seconds (0 = off) */
+  async getAutoSyncInterval(): Promise<number> {
+    return invoke<number>('get_auto_sync_interval');
+  },
+
+  /** Set the background sync interval in seconds; 0 turns background sync off */
+  async setAutoSyncInterval(secs: number): Promise<void> {
+    return invoke<void>('set_auto_sync_interval', { secs });
+  },
+
+  /** Report whether the current connection is metered (pauses background sync) */
+  async setMeteredConnection(metered: boolean): Promise<void> {
+    return invoke<void>('set_metered_connection', { metered });
+  },
+
+  /** Get the oldest signature scheme version accepted on incoming posts and messages */
+  async getMinSignatureVersion(): Promise<number> {
+    return invoke<number>('get_min_signature_version');
+  },
+
+  /** Refuse incoming posts and messages signed with an older scheme than `version` */
+  async setMinSignatureVersion(version: number): Promise<void> {
+    return invoke<void>('set_min_signature_version', { version });
+  },
+
+  /** Import posts from an RSS/Atom feed URL or file; already-imported entries are skipped */
+  async importPostsFromFeed(
+    urlOrFile: string,
+    visibility?: PostVisibility,
+  ): Promise<FeedImportSummary> {
+    return invoke<FeedImportSummary>('import_posts_from_feed', { urlOrFile, visibility });
+  },
 };
@@ -20,6 +20,7 @@ const mockAccounts: AccountInfo[] = [
     displayName: 'User One',
     bio: 'First account',
     avatarHash: null,
+    accentColor: '#3b82f6',
     createdAt: 1700000000,
     lastAccessedAt: 1700000100,
     dataPath: '/data/acct-1',
@@ -30,6 +31,7 @@ const mockAccounts: AccountInfo[] = [
     displayName: 'User Two',
     bio: null,
     avatarHash: null,
+    accentColor: null,
     createdAt: 1700000200,
     lastAccessedAt: 1700000200,
     dataPath: '/data/acct-2',
@@ -11,7 +11,9 @@ vi.mock('../services/boards', () => ({
     getBoardPosts: vi.fn(),
     submitBoardPost: vi.fn(),
     deleteBoardPost: vi.fn(),
+    reactToBoardPost: vi.fn(),
     syncBoard: vi.fn(),
+    createBoard: vi.fn(),
   },
 }));
 
@@ -29,6 +31,7 @@ const mockBoard = {
   name: 'General',
   description: 'General discussion',
   isDefault: true,
+  isSubscribed: false,
 };
 
 const mockBoardPost = {
@@ -41,6 +44,8 @@ const mockBoardPost = {
   contentText: 'First board post',
   lamportClock: 1,
   createdAt: 1700000100,
+  reactionCount: 0,
+  myReaction: null,
 };
 
 describe('useBoardsStore', () => {
@@ -99,6 +104,19 @@ describe('useBoardsStore', () => {
 
       expect(useBoardsStore.getState().error).toContain('Join failed');
     });
+
+    it('should explain when the relay does not host boards', async () => {
+      vi.mocked(boardsService.joinCommunity).mockRejectedValue({
+        code: 'NOT_COMMUNITY_RELAY',
+        message: "This relay doesn't host community boards",
+      });
+
+      await expect(
+        useBoardsStore.getState().joinCommunity('/ip4/1.2.3.4/tcp/9000'),
+      ).rejects.toBeDefined();
+
+      expect(useBoardsStore.getState().error).toContain("doesn't host boards");
+    });
   });
 
   describe('leaveCommunity', () => {
@@ -179,6 +197,37 @@ describe('useBoardsStore', () => {
     });
   });
 
+  describe('loadBoards', () => {
+    it('should reload boards for the active community', async () => {
+      const newBoard = { ...mockBoard, boardId: 'board-new', name: 'New', isDefault: false };
+      useBoardsStore.setState({ activeCommunity: mockCommunity, activeBoard: mockBoard });
+      vi.mocked(boardsService.getBoards).mockResolvedValue([mockBoard, newBoard]);
+
+      await useBoardsStore.getState().loadBoards();
+
+      expect(boardsService.getBoards).toHaveBeenCalledWith('relay-1');
+      expect(useBoardsStore.getState().boards).toEqual([mockBoard, newBoard]);
+      expect(useBoardsStore.getState().activeBoard).toEqual(mockBoard);
+    });
+  });
+
+  describe('createBoard', () => {
+    it('should request board creation on the active community', async () => {
+      useBoardsStore.setState({ activeCommunity: mockCommunity });
+      vi.mocked(boardsService.createBoard).mockResolvedValue(undefined);
+
+      await useBoardsStore.getState().createBoard('Announcements', 'News');
+
+      expect(boardsService.createBoard).toHaveBeenCalledWith('relay-1', 'Announcements', 'News');
+    });
+
+    it('should not create if no active community', async () => {
+      await useBoardsStore.getState().createBoard('Announcements');
+
+      expect(boardsService.createBoard).not.toHaveBeenCalled();
+    });
+  });
+
   describe('loadBoardPosts', () => {
     it('should not load if no active community or board', async () => {
       await useBoardsStore.getState().loadBoardPosts();
@@ -251,6 +300,47 @@ describe('useBoardsStore', () => {
     });
   });
 
+  describe('toggleReaction', () => {
+    it('should react to a post we have not reacted to', async () => {
+      useBoardsStore.setState({
+        activeCommunity: mockCommunity,
+        boardPosts: [mockBoardPost],
+      });
+      vi.mocked(boardsService.reactToBoardPost).mockResolvedValue(undefined);
+
+      await useBoardsStore.getState().toggleReaction('bp-1');
+
+      expect(boardsService.reactToBoardPost).toHaveBeenCalledWith('relay-1', 'bp-1', false);
+    });
+
+    it('should withdraw an existing reaction', async () => {
+      useBoardsStore.setState({
+        activeCommunity: mockCommunity,
+        boardPosts: [{ ...mockBoardPost, reactionCount: 1, myReaction: 'like' }],
+      });
+      vi.mocked(boardsService.reactToBoardPost).mockResolvedValue(undefined);
+
+      await useBoardsStore.getState().toggleReaction('bp-1');
+
+      expect(boardsService.reactToBoardPost).toHaveBeenCalledWith('relay-1', 'bp-1', true);
+    });
+  });
+
+  describe('applyReactions', () => {
+    it('should update the count and our reaction on the matching post', () => {
+      useBoardsStore.setState({
+        boardPosts: [mockBoardPost, { ...mockBoardPost, postId: 'bp-2' }],
+      });
+
+      useBoardsStore.getState().applyReactions('relay-1', 'bp-1', 3, 'like');
+
+      const [first, second] = useBoardsStore.getState().boardPosts;
+      expect(first.reactionCount).toBe(3);
+      expect(first.myReaction).toBe('like');
+      expect(second.reactionCount).toBe(0);
+    });
+  });
+
   describe('refreshBoard', () => {
     it('should sync and reload board posts', async () => {
       useBoardsStore.setState({
@@ -1,6 +1,15 @@
 import { create } from 'zustand';
 import { boardsService } from '../services/boards';
-import type { CommunityInfo, BoardInfo, BoardPost } from '../types/boards';
+import { INVITE_PREFIX, type CommunityInfo, type BoardInfo, type BoardPost } from '../types/boards';
+import { isErrorResponse } from '../utils/errors';
+
+/** Turn a relay without board support into a message the user can act on */
+function describeBoardError(error: unknown): string {
+  if (isErrorResponse(error) && error.code === 'NOT_COMMUNITY_RELAY') {
+    return "This relay doesn't host boards. Try a community relay instead.";
+  }
+  return String(error);
+}
 
 interface BoardsState {
   // State
@@ -15,14 +24,25 @@ interface BoardsState {
 
   // Actions
   loadCommunities: () => Promise<void>;
+  /** Join by relay address or by a `harbor://invite/...` token */
   joinCommunity: (relayAddress: string) => Promise<void>;
+  createInvite: (expiresInSecs?: number, maxUses?: number) => Promise<void>;
   leaveCommunity: (relayPeerId: string) => Promise<void>;
   selectCommunity: (community: CommunityInfo) => Promise<void>;
   selectBoard: (board: BoardInfo) => Promise<void>;
+  loadBoards: () => Promise<void>;
+  createBoard: (name: string, description?: string) => Promise<void>;
   loadBoardPosts: (limit?: number) => Promise<void>;
   loadMorePosts: (limit?: number) => Promise<void>;
   submitPost: (contentText: string) => Promise<void>;
   deletePost: (postId: string) => Promise<void>;
+  toggleReaction: (postId: string) => Promise<void>;
+  applyReactions: (
+    relayPeerId: string,
+    postId: string,
+    reactionCount: number,
+    myReaction: string | null,
+  ) => void;
   refreshBoard: () => Promise<void>;
 }
 
@@ -51,13 +71,35 @@ export const useBoardsStore = create<BoardsState>((set, get) => ({
   joinCommunity: async (relayAddress: string) => {
     set({ isLoading: true, error: null });
     try {
-      await boardsService.joinCommunity(relayAddress);
+      if (relayAddress.startsWith(INVITE_PREFIX)) {
+        await boardsService.joinCommunityFromInvite(relayAddress);
+      } else {
+        await boardsService.joinCommunity(relayAddress);
+      }
       // Reload communities list
       const communities = await boardsService.getCommunities();
       set({ communities, isLoading: false });
     } catch (error) {
       console.error('Failed to join community:', error);
-      set({ error: String(error), isLoading: false });
+      set({ error: describeBoardError(error), isLoading: false });
+      throw error;
+    }
+  },
+
+  createInvite: async (expiresInSecs?: number, maxUses?: number) => {
+    const { activeCommunity } = get();
+    if (!activeCommunity) return;
+
+    try {
+      // The relay replies asynchronously with a community_invite_created event
+      await boardsService.createCommunityInvite(
+        activeCommunity.relayPeerId,
+        expiresInSecs,
+        maxUses,
+      );
+    } catch (error) {
+      console.error('Failed to request invite:', error);
+      set({ error: String(error) });
       throw error;
     }
   },
@@ -110,6 +152,33 @@ export const useBoardsStore = create<BoardsState>((set, get) => ({
     get().loadBoardPosts();
   },
 
+  loadBoards: async () => {
+    const { activeCommunity } = get();
+    if (!activeCommunity) return;
+
+    try {
+      const boards = await boardsService.getBoards(activeCommunity.relayPeerId);
+      set({ boards });
+    } catch (error) {
+      console.error('Failed to load boards:', error);
+      set({ error: String(error) });
+    }
+  },
+
+  createBoard: async (name: string, description?: string) => {
+    const { activeCommunity } = get();
+    if (!activeCommunity) return;
+
+    try {
+      // The relay replies asynchronously; boards reload on the board_created event
+      await boardsService.createBoard(activeCommunity.relayPeerId, name, description);
+    } catch (error) {
+      console.error('Failed to create board:', error);
+      set({ error: String(error) });
+      throw error;
+    }
+  },
+
   loadBoardPosts: async (limit: number = 50) => {
     const { activeCommunity, activeBoard } = get();
     if (!activeCommunity || !activeBoard) return;
@@ -171,7 +240,7 @@ export const useBoardsStore = create<BoardsState>((set, get) => ({
       get().loadBoardPosts();
     } catch (error) {
       console.error('Failed to submit post:', error);
-      set({ error: String(error) });
+      set({ error: describeBoardError(error) });
       throw error;
     }
   },
@@ -193,6 +262,40 @@ export const useBoardsStore = create<BoardsState>((set, get) => ({
     }
   },
 
+  toggleReaction: async (postId: string) => {
+    const { activeCommunity, boardPosts } = get();
+    const post = boardPosts.find((p) => p.postId === postId);
+    if (!activeCommunity || !post) return;
+
+    try {
+      // The relay's count arrives as an event and is applied by applyReactions
+      await boardsService.reactToBoardPost(
+        activeCommunity.relayPeerId,
+        postId,
+        post.myReaction !== null,
+      );
+    } catch (error) {
+      console.error('Failed to react to post:', error);
+      set({ error: describeBoardError(error) });
+      throw error;
+    }
+  },
+
+  applyReactions: (
+    relayPeerId: string,
+    postId: string,
+    reactionCount: number,
+    myReaction: string | null,
+  ) => {
+    set((state) => ({
+      boardPosts: state.boardPosts.map((p) =>
+        p.postId === postId && p.relayPeerId === relayPeerId
+          ? { ...p, reactionCount, myReaction }
+          : p,
+      ),
+    }));
+  },
+
   refreshBoard: async () => {
     const { activeCommunity, activeBoard } = get();
     if (!activeCommunity || !activeBoard) return;
@@ -36,6 +36,7 @@ const mockFeedItems = [
     createdAt: 1700000100,
     updatedAt: 1700000100,
     isLocal: false,
+    pinned: false,
   },
   {
     postId: 'feed-2',
@@ -48,6 +49,7 @@ const mockFeedItems = [
     createdAt: 1700000000,
     updatedAt: 1700000000,
     isLocal: false,
+    pinned: false,
   },
 ];
 
@@ -24,6 +24,7 @@ const mockIdentity = {
   avatarHash: null,
   bio: 'Test bio',
   passphraseHint: null,
+  accentColor: null,
   createdAt: 1704067200000,
   updatedAt: 1704067200000,
 };
@@ -22,9 +22,13 @@ describe('useMessagingStore', () => {
         {
           conversationId: 'conv-1',
           peerId: 'peer-alice',
-          lastMessage: 'Hello',
           lastMessageAt: 1700000100,
+          lastMessagePreview: 'Hello',
+          lastMessageIsOutgoing: false,
+          lastMessageStatus: 'delivered',
           unreadCount: 2,
+          pinned: false,
+          isNoteToSelf: false,
         },
       ];
       vi.mocked(invoke).mockResolvedValue(mockConversations);
@@ -65,7 +69,7 @@ describe('useMessagingStore', () => {
           editedAt: null,
         },
       ];
-      vi.mocked(invoke).mockResolvedValue(mockMessages);
+      vi.mocked(invoke).mockResolvedValue({ messages: mockMessages, nextCursor: null });
 
       await useMessagingStore.getState().loadMessages('peer-alice');
 
@@ -118,7 +122,7 @@ describe('useMessagingStore', () => {
 
   describe('setActiveConversation', () => {
     it('should set active conversation and load messages', async () => {
-      vi.mocked(invoke).mockResolvedValue([]);
+      vi.mocked(invoke).mockResolvedValue({ messages: [], nextCursor: null });
 
       useMessagingStore.getState().setActiveConversation('peer-alice');
 
@@ -321,6 +325,44 @@ describe('useMessagingStore', () => {
     });
   });
 
+  describe('deleteMessage', () => {
+    it('should clear the content and set deletedAt', async () => {
+      useMessagingStore.setState({
+        messages: {
+          'peer-alice': [
+            {
+              messageId: 'msg-1',
+              conversationId: 'conv-1',
+              senderPeerId: 'peer-me',
+              recipientPeerId: 'peer-alice',
+              content: 'Wrong chat',
+              contentType: 'text',
+              replyToMessageId: null,
+              sentAt: 1700000100,
+              deliveredAt: null,
+              readAt: null,
+              status: 'sent' as const,
+              isOutgoing: true,
+              editedAt: null,
+            },
+          ],
+        },
+      });
+
+      vi.mocked(invoke).mockResolvedValue(undefined);
+
+      await useMessagingStore.getState().deleteMessage('msg-1', 'peer-alice');
+
+      expect(invoke).toHaveBeenCalledWith('delete_message', {
+        messageId: 'msg-1',
+        peerId: 'peer-alice',
+      });
+      const msg = useMessagingStore.getState().messages['peer-alice'][0];
+      expect(msg.content).toBe('');
+      expect(msg.deletedAt).toBeGreaterThan(0);
+    });
+  });
+
   describe('clearConversationHistory', () => {
     it('should clear messages and refresh conversations', async () => {
       useMessagingStore.setState({
@@ -2,7 +2,7 @@ import { create } from 'zustand';
 import { persist } from 'zustand/middleware';
 import { invoke } from '@tauri-apps/api/core';
 import { createLogger } from '../utils/logger';
-import type { Message, Conversation, SendMessageResult } from '../types';
+import type { Message, MessagePage, Conversation, SendMessageResult } from '../types';
 
 const log = createLogger('MessagingStore');
 
@@ -38,6 +38,7 @@ interface MessagingState {
   clearConversationHistory: (peerId: string) => Promise<void>;
   deleteConversation: (peerId: string) => Promise<void>;
   editMessage: (messageId: string, newContent: string, peerId: string) => Promise<void>;
+  deleteMessage: (messageId: string, peerId: string) => Promise<void>;
   archiveConversation: (peerId: string) => void;
   unarchiveConversation: (peerId: string) => void;
   isArchived: (peerId: string) => boolean;
@@ -71,7 +72,7 @@ export const useMessagingStore = create<MessagingState>()(
       loadMessages: async (peerId: string) => {
         set({ isLoading: true, error: null });
         try {
-          const messages = await invoke<Message[]>('get_messages', {
+          const { messages } = await invoke<MessagePage>('get_messages', {
             peerId,
             limit: 100,
           });
@@ -291,6 +292,38 @@ export const useMessagingStore = create<MessagingState>()(
         }
       },
 
+      // Delete a sent message, leaving a tombstone in its place
+      deleteMessage: async (messageId: string, peerId: string) => {
+        try {
+          await invoke('delete_message', { messageId, peerId });
+
+          set((state) => {
+            const peerMessages = state.messages[peerId];
+            if (!peerMessages) return state;
+
+            return {
+              messages: {
+                ...state.messages,
+                [peerId]: peerMessages.map((msg) =>
+                  msg.messageId === messageId
+                    ? {
+                        ...msg,
+                        content: '',
+                        voice: null,
+                        replyPreview: null,
+                        deletedAt: msg.deletedAt ?? Math.floor(Date.now() / 1000),
+                      }
+                    : msg,
+                ),
+              },
+            };
+          });
+        } catch (error) {
+          log.error('Failed to delete message', error);
+          throw error;
+        }
+      },
+
       // Archive a conversation (client-side only)
       archiveConversation: (peerId: string) => {
         set((state) => ({
@@ -19,8 +19,15 @@ const mockStats = {
   totalBytesOut: 2000,
   uptimeSeconds: 3600,
   natStatus: 'public' as const,
+  detectedNatStatus: 'public' as const,
+  natOverride: null,
+  autonatEnabled: true,
   relayAddresses: [],
+  relayLimits: [],
+  relayLiveness: [],
+  droppedEvents: 0,
   externalAddresses: [],
+  addressFilter: 'routable' as const,
 };
 
 const mockPeers = [
@@ -28,9 +35,13 @@ const mockPeers = [
     peerId: '12D3KooWPeer1',
     addresses: ['/ip4/192.168.1.1/tcp/9000'],
     protocolVersion: 'harbor/1.0.0',
-    agentVersion: 'harbor/0.1.0',
+    agentVersion: 'harbor/0.1.0 (linux)',
+    appVersion: '0.1.0',
+    platform: 'linux',
     isConnected: true,
     lastSeen: Date.now(),
+    connectionType: 'direct' as const,
+    relayPeerId: null,
   },
 ];
 
@@ -46,10 +57,19 @@ describe('useNetworkStore', () => {
         totalBytesOut: 0,
         uptimeSeconds: 0,
         natStatus: 'unknown',
+        detectedNatStatus: 'unknown',
+        natOverride: null,
+        autonatEnabled: true,
         relayAddresses: [],
+        relayLimits: [],
+        relayLiveness: [],
+        droppedEvents: 0,
         externalAddresses: [],
+        addressFilter: 'routable',
       },
       listeningAddresses: [],
+      dialAttempts: [],
+      nearbyPeers: [],
       error: null,
       isLoading: false,
     });
@@ -162,4 +182,44 @@ describe('useNetworkStore', () => {
       expect(state.stats.relayAddresses).toContain('/p2p-circuit/relay2');
     });
   });
+
+  describe('dial attempts', () => {
+    it('should track concurrent attempts to the same peer separately', () => {
+      const store = useNetworkStore.getState();
+      store.dialStarted({ attemptId: 1, peerId: 'peer-a', address: '/ip4/10.0.0.1/tcp/9000' });
+      store.dialStarted({ attemptId: 2, peerId: 'peer-a', address: '/ip4/1.2.3.4/tcp/9000' });
+      store.dialStarted({ attemptId: 3, peerId: 'peer-b', address: null });
+
+      useNetworkStore.getState().dialFailed(1);
+      expect(useNetworkStore.getState().dialAttempts.map((a) => a.attemptId)).toEqual([2, 3]);
+
+      useNetworkStore.getState().clearDialAttempts('peer-a');
+      expect(useNetworkStore.getState().dialAttempts).toEqual([
+        { attemptId: 3, peerId: 'peer-b', address: null },
+      ]);
+    });
+  });
+
+  describe('nearby peers', () => {
+    it('should replace a re-identified peer and drop removed ones', () => {
+      const peer = (peerId: string, displayName: string) => ({
+        peerId,
+        displayName,
+        avatarHash: null,
+        bio: null,
+      });
+      const store = useNetworkStore.getState();
+      store.nearbyPeerIdentified(peer('peer-a', 'Alice'));
+      store.nearbyPeerIdentified(peer('peer-b', 'Bob'));
+      store.nearbyPeerIdentified(peer('peer-a', 'Alicia'));
+
+      expect(useNetworkStore.getState().nearbyPeers.map((p) => p.displayName)).toEqual([
+        'Bob',
+        'Alicia',
+      ]);
+
+      useNetworkStore.getState().removeNearbyPeer('peer-b');
+      expect(useNetworkStore.getState().nearbyPeers.map((p) => p.peerId)).toEqual(['peer-a']);
+    });
+  });
 });
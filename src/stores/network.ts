@@ -1,5 +1,12 @@
 import { create } from 'zustand';
-import type { PeerInfo, NetworkStats, ConnectionStatus, NatStatus } from '../types';
+import type {
+  PeerInfo,
+  NetworkStats,
+  ConnectionStatus,
+  NatStatus,
+  DialAttempt,
+  NearbyPeer,
+} from '../types';
 import * as networkService from '../services/network';
 
 export type RelayStatus = 'disconnected' | 'connecting' | 'connected';
@@ -13,6 +20,10 @@ interface NetworkState {
   listeningAddresses: string[];
   shareableAddresses: string[];
   relayStatus: RelayStatus;
+  /** Outgoing dials in progress, for "connecting..." indicators */
+  dialAttempts: DialAttempt[];
+  /** Identified non-contacts on the network, for the "people nearby" view */
+  nearbyPeers: NearbyPeer[];
   error: string | null;
   isLoading: boolean;
 
@@ -32,6 +43,13 @@ interface NetworkState {
   // NAT status update (called by event handler)
   setNatStatus: (status: NatStatus) => void;
   addRelayAddress: (address: string) => void;
+  // Dial attempt tracking (called by event handler)
+  dialStarted: (attempt: DialAttempt) => void;
+  dialFailed: (attemptId: number) => void;
+  clearDialAttempts: (peerId: string) => void;
+  // Nearby peer tracking (called by event handler)
+  nearbyPeerIdentified: (peer: NearbyPeer) => void;
+  removeNearbyPeer: (peerId: string) => void;
 }
 
 const initialStats: NetworkStats = {
@@ -40,8 +58,15 @@ const initialStats: NetworkStats = {
   totalBytesOut: 0,
   uptimeSeconds: 0,
   natStatus: 'unknown',
+  detectedNatStatus: 'unknown',
+  natOverride: null,
+  autonatEnabled: true,
   relayAddresses: [],
+  relayLimits: [],
+  relayLiveness: [],
+  droppedEvents: 0,
   externalAddresses: [],
+  addressFilter: 'routable',
 };
 
 export const useNetworkStore = create<NetworkState>((set, get) => ({
@@ -53,6 +78,8 @@ export const useNetworkStore = create<NetworkState>((set, get) => ({
   listeningAddresses: [],
   shareableAddresses: [],
   relayStatus: 'disconnected',
+  dialAttempts: [],
+  nearbyPeers: [],
   error: null,
   isLoading: false,
 
@@ -89,6 +116,8 @@ export const useNetworkStore = create<NetworkState>((set, get) => ({
         listeningAddresses: [],
         shareableAddresses: [],
         relayStatus: 'disconnected',
+        dialAttempts: [],
+        nearbyPeers: [],
         isLoading: false,
       });
     } catch (error) {
@@ -243,4 +272,37 @@ export const useNetworkStore = create<NetworkState>((set, get) => ({
       };
     });
   },
+
+  // Track a dial attempt (called by event handler)
+  dialStarted: (attempt: DialAttempt) => {
+    set((state) => ({ dialAttempts: [...state.dialAttempts, attempt] }));
+  },
+
+  // Drop a failed dial attempt (called by event handler)
+  dialFailed: (attemptId: number) => {
+    set((state) => ({
+      dialAttempts: state.dialAttempts.filter((a) => a.attemptId !== attemptId),
+    }));
+  },
+
+  // Drop all dial attempts for a peer once it is connected
+  clearDialAttempts: (peerId: string) => {
+    set((state) => ({
+      dialAttempts: state.dialAttempts.filter((a) => a.peerId !== peerId),
+    }));
+  },
+
+  // Add or refresh a nearby peer (called by event handler)
+  nearbyPeerIdentified: (peer: NearbyPeer) => {
+    set((state) => ({
+      nearbyPeers: [...state.nearbyPeers.filter((p) => p.peerId !== peer.peerId), peer],
+    }));
+  },
+
+  // Drop a nearby peer once it disconnects or becomes a contact
+  removeNearbyPeer: (peerId: string) => {
+    set((state) => ({
+      nearbyPeers: state.nearbyPeers.filter((p) => p.peerId !== peerId),
+    }));
+  },
 }));
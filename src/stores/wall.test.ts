@@ -9,6 +9,8 @@ vi.mock('../services/posts', () => ({
     createPost: vi.fn(),
     updatePost: vi.fn(),
     deletePost: vi.fn(),
+    pinPost: vi.fn(),
+    unpinPost: vi.fn(),
     addPostMedia: vi.fn(),
   },
 }));
@@ -24,6 +26,7 @@ const mockBackendPost = {
   updatedAt: 1700000000,
   deletedAt: null,
   isLocal: true,
+  pinned: false,
 };
 
 describe('useWallStore', () => {
@@ -257,6 +260,49 @@ describe('useWallStore', () => {
     });
   });
 
+  describe('pinPost', () => {
+    const makePost = (postId: string, seconds: number, pinned = false) => ({
+      postId,
+      content: 'test',
+      contentType: 'post' as const,
+      timestamp: new Date(seconds * 1000),
+      likes: 0,
+      comments: 0,
+      liked: false,
+      authorPeerId: 'peer-abc',
+      visibility: 'contacts',
+      lamportClock: 0,
+      pinned,
+    });
+
+    it('should move the pinned post to the top and unpin the previous one', async () => {
+      useWallStore.setState({
+        posts: [makePost('post-3', 3), makePost('post-2', 2, true), makePost('post-1', 1)],
+      });
+      vi.mocked(postsService.pinPost).mockResolvedValue(undefined);
+
+      await useWallStore.getState().pinPost('post-1');
+
+      expect(postsService.pinPost).toHaveBeenCalledWith('post-1');
+      const posts = useWallStore.getState().posts;
+      expect(posts.map((p) => p.postId)).toEqual(['post-1', 'post-3', 'post-2']);
+      expect(posts.map((p) => p.pinned)).toEqual([true, false, false]);
+    });
+
+    it('should restore chronological order on unpin', async () => {
+      useWallStore.setState({
+        posts: [makePost('post-1', 1, true), makePost('post-2', 2)],
+      });
+      vi.mocked(postsService.unpinPost).mockResolvedValue(undefined);
+
+      await useWallStore.getState().unpinPost('post-1');
+
+      const posts = useWallStore.getState().posts;
+      expect(posts.map((p) => p.postId)).toEqual(['post-2', 'post-1']);
+      expect(posts.every((p) => !p.pinned)).toBe(true);
+    });
+  });
+
   describe('likePost', () => {
     const makePost = (postId: string, liked = false, likes = 0) => ({
       postId,
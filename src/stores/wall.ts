@@ -36,6 +36,7 @@ export interface WallPost {
   authorPeerId: string;
   visibility: string;
   lamportClock: number;
+  pinned?: boolean;
 }
 
 interface WallState {
@@ -54,6 +55,8 @@ interface WallState {
   shareToWall: (comment: string, sharedFrom: SharedFrom) => Promise<void>;
   updatePost: (postId: string, content: string) => Promise<void>;
   deletePost: (postId: string) => Promise<void>;
+  pinPost: (postId: string) => Promise<void>;
+  unpinPost: (postId: string) => Promise<void>;
   likePost: (postId: string) => void; // Local-only for now (likes not in backend schema)
   setEditingPost: (postId: string | null) => void;
 }
@@ -122,9 +125,19 @@ async function toWallPost(post: Post, media?: PostMedia[]): Promise<WallPost> {
     authorPeerId: post.authorPeerId,
     visibility: post.visibility,
     lamportClock: post.lamportClock,
+    pinned: post.pinned,
   };
 }
 
+/** Order posts the way the backend does: pinned post first, then newest first */
+function pinnedFirst(posts: WallPost[]): WallPost[] {
+  return [...posts].sort(
+    (a, b) =>
+      Number(b.pinned ?? false) - Number(a.pinned ?? false) ||
+      b.timestamp.getTime() - a.timestamp.getTime(),
+  );
+}
+
 /** Read a File object into a Uint8Array */
 async function readFileAsBytes(file: File): Promise<Uint8Array> {
   const buffer = await file.arrayBuffer();
@@ -314,6 +327,35 @@ export const useWallStore = create<WallState>((set) => ({
     }
   },
 
+  pinPost: async (postId: string) => {
+    try {
+      await postsService.pinPost(postId);
+
+      // Only one post can be pinned at a time
+      set((state) => ({
+        posts: pinnedFirst(state.posts.map((p) => ({ ...p, pinned: p.postId === postId }))),
+      }));
+    } catch (err) {
+      log.error('Failed to pin post', err);
+      throw err;
+    }
+  },
+
+  unpinPost: async (postId: string) => {
+    try {
+      await postsService.unpinPost(postId);
+
+      set((state) => ({
+        posts: pinnedFirst(
+          state.posts.map((p) => (p.postId === postId ? { ...p, pinned: false } : p)),
+        ),
+      }));
+    } catch (err) {
+      log.error('Failed to unpin post', err);
+      throw err;
+    }
+  },
+
   setEditingPost: (postId: string | null) => {
     set({ editingPostId: postId });
   },
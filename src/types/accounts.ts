@@ -8,6 +8,8 @@ export interface AccountInfo {
   avatarHash: string | null;
   /** Short bio */
   bio: string | null;
+  /** Hex accent color for telling accounts apart */
+  accentColor: string | null;
   /** Peer ID for this account */
   peerId: string;
   /** When the account was created (timestamp) */
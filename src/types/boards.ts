@@ -7,6 +7,20 @@ export interface CommunityInfo {
   lastSyncAt: number | null;
 }
 
+/** A verified community invite */
+export interface CommunityInvite {
+  relayPeerId: string;
+  relayAddress: string;
+  communityName: string;
+  issuedAt: number;
+  expiresAt: number | null;
+  /** Suggested number of uses; relays don't count them */
+  maxUses: number | null;
+}
+
+/** Text form of a community invite starts with this */
+export const INVITE_PREFIX = 'harbor://invite/';
+
 /** Board info from the backend */
 export interface BoardInfo {
   boardId: string;
@@ -14,6 +28,7 @@ export interface BoardInfo {
   name: string;
   description: string | null;
   isDefault: boolean;
+  isSubscribed: boolean;
 }
 
 /** Board post info from the backend */
@@ -27,4 +42,10 @@ export interface BoardPost {
   contentText: string | null;
   lamportClock: number;
   createdAt: number;
+  /** When the relay received the post, verified against its signature */
+  relayObservedAt?: number | null;
+  /** Number of peers who reacted, as counted by the relay */
+  reactionCount: number;
+  /** Our own reaction, if we reacted */
+  myReaction: string | null;
 }
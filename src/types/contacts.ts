@@ -14,6 +14,56 @@ export interface Contact {
   updatedAt: number;
 }
 
+/** Why a contact is considered stale */
+export type StaleReason =
+  | 'never_connected'
+  | 'no_recent_connection'
+  | 'never_messaged'
+  | 'no_recent_messages'
+  | 'no_posts_synced'
+  | 'no_recent_posts';
+
+/** A contact suggested for cleanup */
+export interface StaleContact {
+  peerId: string;
+  displayName: string;
+  avatarHash: string | null;
+  isBlocked: boolean;
+  lastSeenAt: number | null;
+  lastMessageAt: number | null;
+  lastPostAt: number | null;
+  addedAt: number;
+  reasons: StaleReason[];
+}
+
+/** A contact as shared by another peer */
+export interface SharedContact {
+  peerId: string;
+  displayName: string;
+}
+
+/** A peer our contacts know but we don't; never added automatically */
+export interface ContactSuggestion {
+  peerId: string;
+  displayName: string;
+  /** The contacts whose shared lists include this peer */
+  knownVia: SharedContact[];
+}
+
+/** A display name observed for a contact */
+export interface ContactNameChange {
+  displayName: string;
+  observedAt: number;
+}
+
+/** A named group of contacts */
+export interface ContactGroup {
+  groupId: string;
+  name: string;
+  memberCount: number;
+  createdAt: number;
+}
+
 /** Data needed to add a new contact */
 export interface ContactData {
   peerId: string;
@@ -10,4 +10,7 @@ export interface FeedItem {
   createdAt: number;
   updatedAt: number;
   isLocal: boolean;
+  pinned: boolean;
+  /** When a community relay received the post, verified against its signature */
+  relayObservedAt?: number | null;
 }
@@ -7,18 +7,38 @@ export interface IdentityInfo {
   avatarHash: string | null;
   bio: string | null;
   passphraseHint: string | null;
+  /** Hex accent color for telling accounts apart (local only) */
+  accentColor: string | null;
   createdAt: number;
   updatedAt: number;
 }
 
+/** Portable identity backup (private keys stay passphrase-encrypted) */
+export interface IdentityBackup {
+  version: number;
+  peerId: string;
+  publicKey: string; // base64 encoded
+  x25519Public: string; // base64 encoded
+  privateKeyEncrypted: string; // base64 encoded
+  displayName: string;
+  bio: string | null;
+  passphraseHint: string | null;
+  createdAt: number;
+  exportedAt: number;
+}
+
 /** Request to create a new identity */
 export interface CreateIdentityRequest {
   displayName: string;
   passphrase: string;
   bio?: string;
   passphraseHint?: string;
+  kdfProfile?: KdfProfile;
 }
 
+/** Strength of the passphrase key derivation */
+export type KdfProfile = 'light' | 'standard' | 'strong';
+
 /** Application state for identity */
 export type IdentityState =
   | { status: 'loading' }
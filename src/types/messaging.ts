@@ -13,17 +13,84 @@ export interface Message {
   status: MessageStatus;
   isOutgoing: boolean;
   editedAt: number | null;
+  /** When the sender deleted the message; `content` is then empty */
+  deletedAt?: number | null;
+  replyPreview?: ReplyPreview | null;
+  /** Audio of a `voice` message; load it with `mediaService.getMediaUrl` */
+  voice?: VoiceNote | null;
+}
+
+/** Matches found so far by `searchMessages`, emitted as `harbor:message-search` */
+export interface MessageSearchProgress {
+  query: string;
+  messages: Message[];
+}
+
+/** A page of a conversation, oldest message first */
+export interface MessagePage {
+  messages: Message[];
+  /** Where the next older page starts; null at the start of the conversation */
+  nextCursor: MessageCursor | null;
+}
+
+/** Position to page back from, as returned in `MessagePage.nextCursor` */
+export interface MessageCursor {
+  beforeTimestamp: number;
+  beforeMessageId: string | null;
+}
+
+/** Voice note audio attached to a message */
+export interface VoiceNote {
+  mediaHash: string;
+  mimeType: string;
+  sizeBytes: number;
+  durationMs: number;
+}
+
+/** Denormalized preview of the message a reply quotes */
+export interface ReplyPreview {
+  messageId: string;
+  senderPeerId: string | null;
+  snippet: string;
+  isDeleted: boolean;
 }
 
 /** Message delivery status */
 export type MessageStatus = 'pending' | 'sent' | 'delivered' | 'read' | 'failed';
 
+/** How outgoing message nonces are derived; `counter` is for older peers only */
+export type NonceStrategy = 'salted_counter' | 'counter';
+
+/** Cipher for outgoing messages; used only with contacts that announced support */
+export type MessageCipher = 'aes-256-gcm' | 'chacha20-poly1305';
+
+/** Per-conversation retention override */
+export interface ConversationRetention {
+  /** Conversation-specific window in days; null uses the global window */
+  retentionDays: number | null;
+  /** Never prune this conversation */
+  neverDelete: boolean;
+}
+
+/** Result of a retention sweep (also emitted as `harbor:retention`) */
+export interface RetentionSweepSummary {
+  messagesDeleted: number;
+  conversationsPruned: number;
+}
+
 /** A conversation summary */
 export interface Conversation {
   conversationId: string;
   peerId: string;
   lastMessageAt: number;
+  /** Decrypted, truncated last message, or `[encrypted]` when it can't be read */
+  lastMessagePreview: string | null;
+  lastMessageIsOutgoing: boolean;
+  lastMessageStatus: MessageStatus;
   unreadCount: number;
+  pinned: boolean;
+  /** Listed as "Notes to self": messages sent to our own peer ID */
+  isNoteToSelf: boolean;
 }
 
 /** Result of sending a message */
@@ -31,4 +98,15 @@ export interface SendMessageResult {
   messageId: string;
   conversationId: string;
   sentAt: number;
+  /** Matches the `message_send_result` network event for this send */
+  correlationId: string;
+}
+
+/** Result of a one-off message sent to a peer by address */
+export interface DirectSendResult {
+  messageId: string;
+  peerId: string;
+  /** Display name the peer announced during the identity exchange */
+  displayName: string;
+  sentAt: number;
 }
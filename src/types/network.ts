@@ -4,14 +4,114 @@ export type ConnectionStatus = 'disconnected' | 'connecting' | 'connected';
 /** NAT status detected by AutoNAT */
 export type NatStatus = 'unknown' | 'public' | 'private' | 'behind_nat';
 
+/**
+ * Which of our addresses are announced to remote peers.
+ * - all: everything, including loopback
+ * - routable: drop loopback and link-local (default)
+ * - public_only: also drop private ranges
+ */
+export type AddressFilter = 'all' | 'routable' | 'public_only';
+
+/** What someone did to our content, as reported in the activity feed */
+export type ActivityKind = 'like' | 'reaction' | 'comment' | 'view';
+
+/** How feed sync picks which connected peers to ask */
+export type SyncStrategy =
+  | { kind: 'all' }
+  | { kind: 'contacts_only' }
+  | { kind: 'top_reputation'; max_peers: number }
+  | { kind: 'round_robin'; max_peers: number };
+
 /** Information about a peer */
 export interface PeerInfo {
   peerId: string;
   addresses: string[];
   protocolVersion: string | null;
   agentVersion: string | null;
+  /** Harbor version read from `agentVersion`; null for other software */
+  appVersion: string | null;
+  /** Platform read from `agentVersion` */
+  platform: string | null;
   isConnected: boolean;
   lastSeen: number | null;
+  /** Best of the open connections: hole punched or direct before relayed */
+  connectionType: ConnectionType;
+  /** The relay carrying the connection when it is relayed */
+  relayPeerId: string | null;
+}
+
+/** How we reach a connected peer */
+export type ConnectionType = 'direct' | 'relayed' | 'holepunched';
+
+/** Result of syncing content with a single peer */
+export interface PeerSyncSummary {
+  peerId: string;
+  postsFetched: number;
+  /** The peer has more posts beyond this batch */
+  hasMore: boolean;
+  errors: string[];
+}
+
+/** Which part of a resync a progress event belongs to */
+export type ResyncStage = 'contacts' | 'communities' | 'done';
+
+/** Progress of `resyncAll`, emitted as `harbor:resync` */
+export interface ResyncProgress {
+  stage: ResyncStage;
+  /** Contacts or communities handled so far in this stage */
+  completed: number;
+  total: number;
+  /** Contact or relay just handled */
+  peerId: string | null;
+  /** Posts fetched from the contact just handled */
+  postsFetched: number;
+  error: string | null;
+}
+
+/** What `resyncAll` did */
+export interface ResyncSummary {
+  cursorsCleared: number;
+  boardCursorsCleared: number;
+  /** Online contacts synced without errors */
+  peersSynced: number;
+  postsFetched: number;
+  communitiesRejoined: number;
+  errors: string[];
+}
+
+/** Listen ports as configured in settings; 0 lets the OS pick */
+export interface ListenPortSettings {
+  tcpPort: number;
+  quicPort: number;
+  /** Use a random port instead of failing when a fixed port is taken */
+  portFallback: boolean;
+}
+
+/** Ports the running network actually bound to; null until the listener reports */
+export interface BoundPorts {
+  tcp: number | null;
+  quic: number | null;
+}
+
+/** A peer known to the Kademlia routing table */
+export interface DhtRoutingPeer {
+  peerId: string;
+  connected: boolean;
+  /** Only filled in by debug builds */
+  addresses: string[];
+}
+
+/** A non-empty k-bucket; peers are between 2^index and 2^(index+1) away */
+export interface DhtBucket {
+  index: number;
+  peers: DhtRoutingPeer[];
+}
+
+/** Snapshot of the Kademlia routing table */
+export interface DhtRoutingTable {
+  localPeerId: string;
+  totalPeers: number;
+  buckets: DhtBucket[];
 }
 
 /** Network statistics */
@@ -20,12 +120,126 @@ export interface NetworkStats {
   totalBytesIn: number;
   totalBytesOut: number;
   uptimeSeconds: number;
-  /** Current NAT status */
+  /** Current NAT status (the manual override if one is set) */
   natStatus: NatStatus;
+  /** NAT status as detected by AutoNAT and relay reservations */
+  detectedNatStatus: NatStatus;
+  /** Manual NAT status override, if set */
+  natOverride: NatStatus | null;
+  /** Whether AutoNAT probing is enabled */
+  autonatEnabled: boolean;
   /** Relay addresses we can be reached at (via relay) */
   relayAddresses: string[];
   /** External addresses discovered via AutoNAT */
   externalAddresses: string[];
+  /** Which of our addresses are announced to remote peers */
+  addressFilter: AddressFilter;
+  /** Circuit limits advertised by the relays we hold reservations on */
+  relayLimits: RelayCircuitLimits[];
+  /** Ping liveness of the relays we hold reservations on */
+  relayLiveness: RelayLiveness[];
+  /** Progress and diagnostic events dropped because the app fell behind */
+  droppedEvents: number;
+}
+
+/** Circuit limits a relay advertised; null means no limit */
+export interface RelayCircuitLimits {
+  relayPeerId: string;
+  maxDurationSecs: number | null;
+  maxBytes: number | null;
+}
+
+/** Whether a held relay is answering pings */
+export type RelayHealth = 'alive' | 'degraded';
+
+/** Ping liveness of a relay we hold a reservation on */
+export interface RelayLiveness {
+  relayPeerId: string;
+  health: RelayHealth;
+  /** Round-trip time of the last answered ping */
+  rttMs: number | null;
+  /** When the relay last answered a ping */
+  lastPongAt: number | null;
+  /** Pings that failed since the last answered one */
+  consecutiveFailures: number;
+}
+
+/** Where the reported NAT status comes from */
+export type NatStatusSource = 'manual' | 'detected';
+
+/** Network diagnostics for troubleshooting connectivity */
+export interface NetworkDiagnostics {
+  networkRunning: boolean;
+  /** Effective NAT status used for connection decisions */
+  natStatus: NatStatus;
+  /** Whether natStatus is a manual override or detected */
+  natStatusSource: NatStatusSource;
+  /** What AutoNAT/relay detection reports, regardless of any override */
+  detectedNatStatus: NatStatus;
+  natOverride: NatStatus | null;
+  autonatEnabled: boolean;
+  addressFilter: AddressFilter;
+  connectedPeers: number;
+  relayAddresses: string[];
+  /** Circuit limits advertised by each relay in use */
+  relayLimits: RelayCircuitLimits[];
+  /** Ping liveness of each relay we hold a reservation on */
+  relayLiveness: RelayLiveness[];
+  externalAddresses: string[];
+  uptimeSeconds: number;
+}
+
+/** Why a connection failed, as far as the error lets the backend tell */
+export type ConnectionFailureCategory =
+  | 'handshake'
+  | 'timeout'
+  | 'transport_unsupported'
+  | 'multiaddr_unreachable'
+  | 'denied'
+  | 'other';
+
+/** A recorded connection failure, for diagnosing transport problems */
+export interface ConnectionFailureInfo {
+  /** null for incoming connections that failed before authenticating */
+  peerId: string | null;
+  address: string | null;
+  category: ConnectionFailureCategory;
+  detail: string;
+  occurredAt: number;
+}
+
+/** Outcome of a single backend health check */
+export type HealthStatus = 'ok' | 'warn' | 'fail';
+
+/** One subsystem's health */
+export interface HealthCheck {
+  /** Stable identifier, e.g. "database" or "relays" */
+  name: string;
+  status: HealthStatus;
+  message: string;
+}
+
+/** Backend health report: every check, plus the worst status among them */
+export interface HealthReport {
+  status: HealthStatus;
+  checks: HealthCheck[];
+  generatedAt: number;
+}
+
+/** An outgoing dial attempt that hasn't connected or failed yet */
+export interface DialAttempt {
+  attemptId: number;
+  peerId: string;
+  /** Null when the address comes from peer discovery */
+  address: string | null;
+}
+
+/** A connected non-contact that answered an automatic identity request */
+export interface NearbyPeer {
+  peerId: string;
+  displayName: string;
+  avatarHash: string | null;
+  bio: string | null;
 }
 
 /** Network events emitted by the backend.
@@ -37,20 +251,111 @@ export interface NetworkStats {
 export type NetworkEvent =
   | { type: 'peer_discovered'; peer_id: string }
   | { type: 'peer_expired'; peer_id: string }
+  | {
+      type: 'dial_started';
+      attempt_id: number;
+      peer_id: string;
+      address: string | null;
+    }
+  | {
+      type: 'dial_failed';
+      attempt_id: number;
+      peer_id: string;
+      address: string | null;
+      error: string;
+    }
+  | {
+      type: 'connection_failed';
+      peer_id: string | null;
+      address: string | null;
+      category: ConnectionFailureCategory;
+      detail: string;
+    }
   | { type: 'peer_connected'; peer_id: string }
   | { type: 'peer_disconnected'; peer_id: string }
+  | { type: 'peer_evicted'; peer_id: string }
   | { type: 'external_address_discovered'; address: string }
   | { type: 'listening_on'; address: string }
   | { type: 'message_received'; peer_id: string; protocol: string; payload: number[] }
+  | {
+      type: 'message_send_result';
+      correlation_id: string;
+      peer_id: string;
+      delivered: boolean;
+      error: string | null;
+    }
+  | {
+      type: 'message_delivery_failed';
+      message_id: string;
+      peer_id: string;
+      attempts: number;
+      error: string;
+    }
   | { type: 'status_changed'; status: ConnectionStatus }
   | { type: 'contact_added'; peer_id: string; display_name: string }
+  | { type: 'identity_conflict_detected'; address: string }
+  | {
+      type: 'contact_updated';
+      peer_id: string;
+      display_name: string;
+      bio: string | null;
+      avatar_hash: string | null;
+      changed_fields: string[];
+    }
+  | {
+      type: 'contact_renamed';
+      peer_id: string;
+      previous_name: string;
+      display_name: string;
+      verified: boolean;
+    }
+  | {
+      type: 'nearby_peer_identified';
+      peer_id: string;
+      display_name: string;
+      avatar_hash: string | null;
+      bio: string | null;
+    }
+  | {
+      type: 'relay_health_changed';
+      relay_peer_id: string;
+      health: RelayHealth;
+      rtt_ms: number | null;
+    }
   | { type: 'nat_status_changed'; status: NatStatus }
   | { type: 'relay_connected'; relay_address: string }
   | { type: 'hole_punch_succeeded'; peer_id: string }
   | { type: 'content_manifest_received'; peer_id: string; post_count: number; has_more: boolean }
   | { type: 'content_fetched'; peer_id: string; post_id: string }
   | { type: 'content_sync_error'; peer_id: string; error: string }
+  | { type: 'likes_updated'; post_ids: string[] }
+  | { type: 'activity_received'; kind: ActivityKind; actor_peer_id: string; target_id: string }
+  | { type: 'request_failed'; peer_id: string; protocol: string; reason: string }
+  | {
+      type: 'messages_seen';
+      peer_id: string;
+      conversation_id: string;
+      message_ids: string[];
+      read_at: number;
+    }
   | { type: 'wall_post_synced'; relay_peer_id: string; post_id: string }
   | { type: 'wall_posts_received'; relay_peer_id: string; author_peer_id: string; post_count: number }
   | { type: 'wall_post_deleted_on_relay'; relay_peer_id: string; post_id: string }
-  | { type: 'media_fetched'; peer_id: string; media_hash: string };
+  | { type: 'media_fetched'; peer_id: string; media_hash: string }
+  | {
+      type: 'call_data_received';
+      call_id: string;
+      peer_id: string;
+      payload: number[];
+      timestamp: number;
+    }
+  | { type: 'new_board_posts'; relay_peer_id: string; board_id: string; count: number }
+  | {
+      type: 'board_post_reactions_updated';
+      relay_peer_id: string;
+      post_id: string;
+      reaction_count: number;
+      my_reaction: string | null;
+    }
+  | { type: 'board_created'; relay_peer_id: string; board_id: string; name: string }
+  | { type: 'community_invite_created'; relay_peer_id: string; token: string };
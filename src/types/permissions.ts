@@ -1,5 +1,11 @@
 /** Permission capability types */
-export type Capability = 'chat' | 'wall_read' | 'call';
+export type Capability =
+  | 'chat'
+  | 'wall_read'
+  | 'call'
+  | 'board_post'
+  | 'file_transfer'
+  | 'share_contacts';
 
 /** Permission info */
 export interface PermissionInfo {
@@ -12,6 +18,23 @@ export interface PermissionInfo {
   isValid: boolean;
 }
 
+/** A grant or revocation between us and a peer */
+export interface PermissionHistoryEntry {
+  eventId: string;
+  eventType: 'grant' | 'revoke';
+  grantId: string;
+  capability: string;
+  issuerPeerId: string;
+  subjectPeerId: string;
+  /** `granted` if we issued it to the peer, `received` if they issued it to us */
+  direction: 'granted' | 'received';
+  /** When the issuer signed it (unix seconds) */
+  timestamp: number;
+  expiresAt: number | null;
+  /** Hex-encoded issuer signature */
+  signature: string;
+}
+
 /** Result of granting a permission */
 export interface GrantResult {
   grantId: string;
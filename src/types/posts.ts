@@ -10,10 +10,14 @@ export interface Post {
   updatedAt: number;
   deletedAt: number | null;
   isLocal: boolean;
+  /** Pinned to the top of the author's wall (at most one per author) */
+  pinned: boolean;
+  /** When a community relay received the post, verified against its signature */
+  relayObservedAt?: number | null;
 }
 
-/** Post visibility setting */
-export type PostVisibility = 'contacts' | 'public';
+/** Post visibility setting ('private' posts are only ever visible to their author) */
+export type PostVisibility = 'contacts' | 'public' | 'private';
 
 /** Post media attachment */
 export interface PostMedia {
@@ -30,8 +34,70 @@ export interface PostMedia {
   sortOrder: number;
 }
 
+/** A recorded post event, for diagnosing sync divergence */
+export interface PostEvent {
+  eventId: string;
+  eventType: 'created' | 'updated' | 'deleted' | 'received';
+  postId: string;
+  authorPeerId: string;
+  lamportClock: number;
+  timestamp: number;
+  /** Hex of the signed CBOR payload */
+  payloadHex: string | null;
+  signatureHex: string;
+  receivedAt: number;
+}
+
 /** Result of creating a post */
 export interface CreatePostResult {
   postId: string;
   createdAt: number;
 }
+
+/** A contact that reported viewing one of our posts */
+export interface PostViewer {
+  peerId: string;
+  displayName: string | null;
+  /** Only set when precise view timing was enabled */
+  viewedAt: number | null;
+}
+
+/** View count and viewers of one of our posts */
+export interface PostViewSummary {
+  postId: string;
+  viewCount: number;
+  viewers: PostViewer[];
+}
+
+/** Whether a pushed post reached a contact */
+export type DeliveryStatus = 'pending' | 'delivered' | 'failed';
+
+/** Push delivery state of one of our posts for one contact */
+export interface PostDelivery {
+  recipientPeerId: string;
+  displayName: string | null;
+  status: DeliveryStatus;
+  attemptedAt: number;
+  deliveredAt: number | null;
+  error: string | null;
+}
+
+/** Result of importing posts from an RSS/Atom feed */
+export interface FeedImportSummary {
+  imported: number;
+  /** Entries already imported by an earlier run */
+  duplicates: number;
+  /** Malformed entries that were left out */
+  skipped: number;
+  /** Enclosures that couldn't be fetched or aren't images or videos */
+  mediaFailed: number;
+  postIds: string[];
+}
+
+/** Privacy settings for post view reports */
+export interface PostViewSettings {
+  /** Tell authors when we fetch their posts */
+  reportViews: boolean;
+  /** Keep the time of each view reported on our own posts */
+  recordViewTimes: boolean;
+}
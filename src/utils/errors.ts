@@ -22,6 +22,8 @@ export type ErrorCode =
   | 'NETWORK_CONNECTION_FAILED'
   | 'NETWORK_PEER_UNREACHABLE'
   | 'NETWORK_TIMEOUT'
+  | 'NETWORK_PORT_IN_USE'
+  | 'NOT_COMMUNITY_RELAY'
   | 'INTERNAL_ERROR';
 
 export interface ErrorResponse {
@@ -0,0 +1,23 @@
+import { describe, it, expect } from 'vitest';
+import { describeRelayTimestamp, RELAY_TIME_TOLERANCE_SECS } from './formatting';
+
+describe('formatting', () => {
+  describe('describeRelayTimestamp', () => {
+    it('should return null without a relay timestamp', () => {
+      expect(describeRelayTimestamp(1000, null)).toBeNull();
+      expect(describeRelayTimestamp(1000, undefined)).toBeNull();
+    });
+
+    it('should accept claimed times within the tolerance', () => {
+      const result = describeRelayTimestamp(1000, 1000 + RELAY_TIME_TOLERANCE_SECS);
+      expect(result?.discrepant).toBe(false);
+      expect(result?.label).toMatch(/^Relay received/);
+    });
+
+    it('should flag backdated and forward-dated posts', () => {
+      const observed = 1_700_000_000;
+      expect(describeRelayTimestamp(observed - 86400, observed)?.discrepant).toBe(true);
+      expect(describeRelayTimestamp(observed + 86400, observed)?.discrepant).toBe(true);
+    });
+  });
+});
@@ -84,6 +84,29 @@ export function formatTimeAgo(unixSeconds: number): string {
   return date.toLocaleDateString();
 }
 
+/** Seconds a post's claimed time may differ from its relay-observed time before it is flagged */
+export const RELAY_TIME_TOLERANCE_SECS = 10 * 60;
+
+/**
+ * Describe a post's relay-anchored receive time next to its self-reported one.
+ * Returns null when no relay vouched for the post. Both times are unix seconds.
+ */
+export function describeRelayTimestamp(
+  createdAt: number,
+  relayObservedAt: number | null | undefined,
+): { label: string; discrepant: boolean } | null {
+  if (relayObservedAt == null) return null;
+  const discrepant = Math.abs(createdAt - relayObservedAt) > RELAY_TIME_TOLERANCE_SECS;
+  const observed = new Date(relayObservedAt * 1000).toLocaleString();
+  const claimed = new Date(createdAt * 1000).toLocaleString();
+  return {
+    label: discrepant
+      ? `Author claims ${claimed}, but the relay received it ${observed}`
+      : `Relay received ${observed}`,
+    discrepant,
+  };
+}
+
 /** Truncate a peer ID for display: first 12 chars + last 6. */
 export function shortPeerId(peerId: string): string {
   if (peerId.length <= 20) return peerId;